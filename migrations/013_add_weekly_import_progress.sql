@@ -0,0 +1,18 @@
+-- Migration 013: Add week-level import progress
+-- Date: 2026-10-15
+-- Purpose: Track in-season datasets per week so incremental updates only re-pull finished weeks
+
+-- Week 0 represents a whole-season import; weeks 1+ are in-season granularity
+ALTER TABLE import_progress ADD COLUMN IF NOT EXISTS week INT NOT NULL DEFAULT 0;
+
+ALTER TABLE import_progress DROP CONSTRAINT IF EXISTS import_progress_season_data_type_key;
+ALTER TABLE import_progress DROP CONSTRAINT IF EXISTS import_progress_season_data_type_week_key;
+ALTER TABLE import_progress ADD CONSTRAINT import_progress_season_data_type_week_key
+    UNIQUE (season, data_type, week);
+
+-- The pipeline compares game updates against the last weekly import
+ALTER TABLE games ADD COLUMN IF NOT EXISTS updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS idx_import_progress_season_week ON import_progress(season, data_type, week);
+
+COMMENT ON COLUMN import_progress.week IS 'Week number for in-season imports (0 = entire season)';
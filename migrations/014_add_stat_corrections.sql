@@ -0,0 +1,19 @@
+-- Migration 014: Add stat corrections log
+-- Date: 2026-10-15
+-- Purpose: Record field-level changes found by the Thursday stat correction sweep
+
+CREATE TABLE IF NOT EXISTS stat_corrections (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    player_id UUID NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    season INT NOT NULL,
+    week INT NOT NULL,
+    field VARCHAR(50) NOT NULL,
+    old_value INT,
+    new_value INT,
+    detected_at TIMESTAMP DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_stat_corrections_season_week ON stat_corrections(season, week);
+CREATE INDEX IF NOT EXISTS idx_stat_corrections_player ON stat_corrections(player_id);
+
+COMMENT ON TABLE stat_corrections IS 'Official stat corrections detected when re-importing recent weeks';
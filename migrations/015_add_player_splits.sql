@@ -0,0 +1,28 @@
+-- Migration 015: Add player splits
+-- Date: 2026-10-15
+-- Purpose: Derived per-season stat splits (regular season vs fantasy playoff weeks)
+
+CREATE TABLE IF NOT EXISTS player_splits (
+    player_id UUID NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    season INT NOT NULL,
+    split VARCHAR(50) NOT NULL,          -- 'regular_season', 'fantasy_playoffs'
+    first_week INT NOT NULL,
+    last_week INT NOT NULL,
+    games INT NOT NULL DEFAULT 0,
+    passing_yards INT DEFAULT 0,
+    rushing_yards INT DEFAULT 0,
+    receiving_yards INT DEFAULT 0,
+    passing_tds INT DEFAULT 0,
+    rushing_tds INT DEFAULT 0,
+    receiving_tds INT DEFAULT 0,
+    receptions INT DEFAULT 0,
+    targets INT DEFAULT 0,
+    interceptions INT DEFAULT 0,
+    updated_at TIMESTAMP DEFAULT NOW(),
+    PRIMARY KEY (player_id, season, split)
+);
+
+CREATE INDEX IF NOT EXISTS idx_player_splits_season_split ON player_splits(season, split);
+
+COMMENT ON TABLE player_splits IS 'Derived per-player stat splits, refreshed after each stat import';
+COMMENT ON COLUMN player_splits.split IS 'Split name: regular_season, fantasy_playoffs (REG weeks 15-17)';
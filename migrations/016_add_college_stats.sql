@@ -0,0 +1,33 @@
+-- Migration 016: Add college stats
+-- Date: 2026-10-15
+-- Purpose: College production for NFL players, imported from cfbfastR releases for pre-draft profiles
+
+CREATE TABLE IF NOT EXISTS college_stats (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    cfb_player_id VARCHAR(50) NOT NULL,
+    player_id UUID REFERENCES players(id) ON DELETE SET NULL,
+    season INT NOT NULL,
+    player_name TEXT NOT NULL,
+    team TEXT,
+    conference TEXT,
+    position TEXT,
+    games INT,
+    passing_completions INT,
+    passing_attempts INT,
+    passing_yards INT,
+    passing_tds INT,
+    interceptions INT,
+    rushing_attempts INT,
+    rushing_yards INT,
+    rushing_tds INT,
+    receptions INT,
+    receiving_yards INT,
+    receiving_tds INT,
+    created_at TIMESTAMP DEFAULT NOW(),
+    updated_at TIMESTAMP DEFAULT NOW(),
+    UNIQUE (cfb_player_id, season)
+);
+
+CREATE INDEX IF NOT EXISTS idx_college_stats_player ON college_stats(player_id);
+
+COMMENT ON TABLE college_stats IS 'College season production for players who reached the NFL';
@@ -0,0 +1,47 @@
+-- Migration 017: Add game betting results
+-- Date: 2026-10-15
+-- Purpose: Grade each completed game against the closing spread and total
+
+CREATE TABLE IF NOT EXISTS game_betting_results (
+    nfl_game_id TEXT PRIMARY KEY,
+    season INT NOT NULL,
+    week INT NOT NULL,
+    home_team TEXT NOT NULL,
+    away_team TEXT NOT NULL,
+    home_coach TEXT,
+    away_coach TEXT,
+    home_score INT NOT NULL,
+    away_score INT NOT NULL,
+    spread_line NUMERIC(5,1),            -- nflverse convention: expected home margin
+    total_line NUMERIC(5,1),
+    spread_result VARCHAR(10),           -- 'home', 'away', 'push'
+    total_result VARCHAR(10),            -- 'over', 'under', 'push'
+    graded_at TIMESTAMP DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_game_betting_results_season ON game_betting_results(season, week);
+
+-- Per-team ATS and over/under records
+CREATE OR REPLACE VIEW team_ats_records AS
+WITH sides AS (
+    SELECT season, home_team AS team, home_coach AS coach,
+           CASE spread_result WHEN 'home' THEN 'cover' WHEN 'away' THEN 'loss' ELSE spread_result END AS ats,
+           total_result
+    FROM game_betting_results
+    UNION ALL
+    SELECT season, away_team AS team, away_coach AS coach,
+           CASE spread_result WHEN 'away' THEN 'cover' WHEN 'home' THEN 'loss' ELSE spread_result END AS ats,
+           total_result
+    FROM game_betting_results
+)
+SELECT season, team, coach,
+       COUNT(*) FILTER (WHERE ats = 'cover') AS ats_wins,
+       COUNT(*) FILTER (WHERE ats = 'loss') AS ats_losses,
+       COUNT(*) FILTER (WHERE ats = 'push') AS ats_pushes,
+       COUNT(*) FILTER (WHERE total_result = 'over') AS overs,
+       COUNT(*) FILTER (WHERE total_result = 'under') AS unders,
+       COUNT(*) FILTER (WHERE total_result = 'push') AS total_pushes
+FROM sides
+GROUP BY season, team, coach;
+
+COMMENT ON TABLE game_betting_results IS 'Completed games graded against the closing spread and total';
@@ -0,0 +1,49 @@
+-- Migration 018: Add coach and referee reports
+-- Date: 2026-10-15
+-- Purpose: Seasonal coach records (overall, ATS, post-bye, favorite/underdog) and referee crew tendencies
+
+ALTER TABLE game_betting_results ADD COLUMN IF NOT EXISTS referee TEXT;
+ALTER TABLE game_betting_results ADD COLUMN IF NOT EXISTS home_rest INT;
+ALTER TABLE game_betting_results ADD COLUMN IF NOT EXISTS away_rest INT;
+
+CREATE TABLE IF NOT EXISTS coach_records (
+    season INT NOT NULL,
+    coach TEXT NOT NULL,
+    team TEXT NOT NULL,
+    games INT NOT NULL DEFAULT 0,
+    wins INT NOT NULL DEFAULT 0,
+    losses INT NOT NULL DEFAULT 0,
+    ties INT NOT NULL DEFAULT 0,
+    ats_wins INT NOT NULL DEFAULT 0,
+    ats_losses INT NOT NULL DEFAULT 0,
+    ats_pushes INT NOT NULL DEFAULT 0,
+    post_bye_wins INT NOT NULL DEFAULT 0,
+    post_bye_losses INT NOT NULL DEFAULT 0,
+    favorite_wins INT NOT NULL DEFAULT 0,
+    favorite_losses INT NOT NULL DEFAULT 0,
+    favorite_ats_wins INT NOT NULL DEFAULT 0,
+    favorite_ats_losses INT NOT NULL DEFAULT 0,
+    underdog_wins INT NOT NULL DEFAULT 0,
+    underdog_losses INT NOT NULL DEFAULT 0,
+    underdog_ats_wins INT NOT NULL DEFAULT 0,
+    underdog_ats_losses INT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP DEFAULT NOW(),
+    PRIMARY KEY (season, coach, team)
+);
+
+CREATE TABLE IF NOT EXISTS referee_tendencies (
+    season INT NOT NULL,
+    referee TEXT NOT NULL,
+    games INT NOT NULL DEFAULT 0,
+    home_wins INT NOT NULL DEFAULT 0,
+    home_win_rate NUMERIC(5,3),
+    avg_total_points NUMERIC(5,2),
+    over_rate NUMERIC(5,3),
+    penalties_per_game NUMERIC(5,2),     -- NULL when team box scores aren't loaded
+    penalty_yards_per_game NUMERIC(6,2),
+    updated_at TIMESTAMP DEFAULT NOW(),
+    PRIMARY KEY (season, referee)
+);
+
+COMMENT ON TABLE coach_records IS 'Seasonal head coach records derived from game_betting_results';
+COMMENT ON TABLE referee_tendencies IS 'Seasonal referee crew tendencies derived from game_betting_results and game_team_stats';
@@ -0,0 +1,18 @@
+-- Migration 019: Add season type to games and game stats
+-- Date: 2026-10-15
+-- Purpose: Import preseason and postseason data alongside the regular season
+
+ALTER TABLE games ADD COLUMN IF NOT EXISTS season_type VARCHAR(4) NOT NULL DEFAULT 'REG';
+ALTER TABLE game_stats ADD COLUMN IF NOT EXISTS season_type VARCHAR(4) NOT NULL DEFAULT 'REG';
+
+-- Preseason weeks 1-3 overlap regular season week numbers, so season type is part of the key
+ALTER TABLE game_stats DROP CONSTRAINT IF EXISTS game_stats_player_season_week_unique;
+ALTER TABLE game_stats DROP CONSTRAINT IF EXISTS game_stats_player_season_week_type_unique;
+ALTER TABLE game_stats ADD CONSTRAINT game_stats_player_season_week_type_unique
+    UNIQUE (player_id, season, week, season_type);
+
+CREATE INDEX IF NOT EXISTS idx_games_season_type ON games(season, season_type);
+CREATE INDEX IF NOT EXISTS idx_game_stats_season_type ON game_stats(season, season_type);
+
+COMMENT ON COLUMN games.season_type IS 'REG, POST or PRE (nflverse game_type WC/DIV/CON/SB map to POST)';
+COMMENT ON COLUMN game_stats.season_type IS 'REG, POST or PRE';
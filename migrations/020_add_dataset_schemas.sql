@@ -0,0 +1,24 @@
+-- Migration 020: Add dataset schema tracking
+-- Date: 2026-10-15
+-- Purpose: Record the CSV header of every imported dataset file so column renames across seasons are visible
+
+CREATE TABLE IF NOT EXISTS dataset_schemas (
+    dataset VARCHAR(50) NOT NULL,
+    season INT NOT NULL,
+    columns TEXT[] NOT NULL,
+    missing_fields TEXT[] NOT NULL DEFAULT '{}',
+    added_columns TEXT[] NOT NULL DEFAULT '{}',
+    removed_columns TEXT[] NOT NULL DEFAULT '{}',
+    recorded_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (dataset, season)
+);
+
+-- Seasons whose columns differ from the prior recorded season
+CREATE OR REPLACE VIEW dataset_schema_drift AS
+SELECT dataset, season, missing_fields, added_columns, removed_columns, recorded_at
+FROM dataset_schemas
+WHERE cardinality(added_columns) > 0 OR cardinality(removed_columns) > 0
+ORDER BY dataset, season;
+
+COMMENT ON TABLE dataset_schemas IS 'CSV header per dataset and season, with drift against the parser and the previous season';
+COMMENT ON COLUMN dataset_schemas.missing_fields IS 'Parser fields with no column of the same name (may be covered by a serde alias)';
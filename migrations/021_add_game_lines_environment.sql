@@ -0,0 +1,22 @@
+-- Migration 021: Add betting lines and environment to games
+-- Date: 2026-10-15
+-- Purpose: Persist nflverse closing lines, roof/surface and officiating already parsed from the schedule
+
+ALTER TABLE games ADD COLUMN IF NOT EXISTS spread_line NUMERIC(4,1);
+ALTER TABLE games ADD COLUMN IF NOT EXISTS total_line NUMERIC(4,1);
+ALTER TABLE games ADD COLUMN IF NOT EXISTS home_moneyline INT;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS away_moneyline INT;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS home_spread_odds INT;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS away_spread_odds INT;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS over_odds INT;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS under_odds INT;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS roof TEXT;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS surface TEXT;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS referee TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_games_referee ON games(referee) WHERE referee IS NOT NULL;
+
+COMMENT ON COLUMN games.spread_line IS 'Closing spread as expected home margin (nflverse convention; positive = home favored)';
+COMMENT ON COLUMN games.total_line IS 'Closing over/under total';
+COMMENT ON COLUMN games.roof IS 'outdoors, dome, closed or open';
+COMMENT ON COLUMN games.referee IS 'Referee (crew chief) name';
@@ -0,0 +1,31 @@
+-- Migration 022: Add weekly injury reports
+-- Date: 2026-10-15
+-- Purpose: Store nflverse weekly injury reports (game status and practice participation) per player and week
+
+-- player_injuries holds the current ESPN status; this table keeps every weekly report
+CREATE TABLE IF NOT EXISTS injuries (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    player_id UUID NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    team_id UUID REFERENCES teams(id),
+    season INT NOT NULL,
+    season_type VARCHAR(4) NOT NULL DEFAULT 'REG',
+    week INT NOT NULL,
+    report_status TEXT,
+    report_primary_injury TEXT,
+    report_secondary_injury TEXT,
+    practice_status TEXT,
+    practice_primary_injury TEXT,
+    practice_secondary_injury TEXT,
+    date_modified TIMESTAMP,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (player_id, season, season_type, week)
+);
+
+CREATE INDEX IF NOT EXISTS idx_injuries_season_week ON injuries(season, week);
+CREATE INDEX IF NOT EXISTS idx_injuries_team ON injuries(team_id, season, week);
+CREATE INDEX IF NOT EXISTS idx_injuries_status ON injuries(report_status) WHERE report_status IS NOT NULL;
+
+COMMENT ON TABLE injuries IS 'Weekly NFL injury reports from nflverse';
+COMMENT ON COLUMN injuries.report_status IS 'Game designation: Out, Doubtful, Questionable (NULL when not listed)';
+COMMENT ON COLUMN injuries.practice_status IS 'Practice participation, e.g. Did Not Participate In Practice, Limited Participation in Practice';
@@ -0,0 +1,28 @@
+-- Migration 023: Add weekly depth charts
+-- Date: 2026-10-15
+-- Purpose: Store nflverse weekly depth charts so lookups like "who was RB2 in week 6" are a single query
+
+CREATE TABLE IF NOT EXISTS depth_charts (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    season INT NOT NULL,
+    season_type VARCHAR(4) NOT NULL DEFAULT 'REG',
+    week INT NOT NULL,
+    team_id UUID NOT NULL REFERENCES teams(id),
+    position VARCHAR(10) NOT NULL,
+    depth_rank INT NOT NULL,
+    formation VARCHAR(20),
+    player_id UUID REFERENCES players(id) ON DELETE SET NULL,
+    gsis_id TEXT,
+    player_name TEXT,
+    jersey_number INT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (season, season_type, week, team_id, position, depth_rank)
+);
+
+CREATE INDEX IF NOT EXISTS idx_depth_charts_player ON depth_charts(player_id, season, week);
+CREATE INDEX IF NOT EXISTS idx_depth_charts_team_week ON depth_charts(team_id, season, week);
+
+COMMENT ON TABLE depth_charts IS 'Weekly team depth charts from nflverse';
+COMMENT ON COLUMN depth_charts.position IS 'Depth chart slot (QB, RB, LWR, LDE, KR, ...)';
+COMMENT ON COLUMN depth_charts.depth_rank IS '1 = starter, 2 = backup, ...';
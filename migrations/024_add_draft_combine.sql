@@ -0,0 +1,70 @@
+-- Migration 024: Add draft picks and combine results
+-- Date: 2026-10-15
+-- Purpose: Full NFL draft history and scouting combine measurements from nflverse, linked to players
+
+CREATE TABLE IF NOT EXISTS draft_picks (
+    season INT NOT NULL,
+    round INT NOT NULL,
+    pick INT NOT NULL,
+    team_id UUID REFERENCES teams(id),
+    team_abbr VARCHAR(5) NOT NULL,
+    player_id UUID REFERENCES players(id) ON DELETE SET NULL,
+    gsis_id TEXT,
+    pfr_player_id TEXT,
+    cfb_player_id TEXT,
+    player_name TEXT NOT NULL,
+    position VARCHAR(10),
+    category VARCHAR(10),
+    side VARCHAR(2),
+    college TEXT,
+    age INT,
+    hof BOOLEAN,
+    allpro INT,
+    probowls INT,
+    seasons_started INT,
+    w_av INT,
+    car_av INT,
+    games INT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (season, pick)
+);
+
+CREATE INDEX IF NOT EXISTS idx_draft_picks_player ON draft_picks(player_id);
+CREATE INDEX IF NOT EXISTS idx_draft_picks_pfr ON draft_picks(pfr_player_id);
+CREATE INDEX IF NOT EXISTS idx_draft_picks_team ON draft_picks(team_id, season);
+
+CREATE TABLE IF NOT EXISTS combine_results (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    season INT NOT NULL,
+    player_id UUID REFERENCES players(id) ON DELETE SET NULL,
+    pfr_id TEXT,
+    cfb_id TEXT,
+    player_name TEXT NOT NULL,
+    position VARCHAR(10) NOT NULL,
+    school TEXT,
+    draft_team TEXT,
+    draft_round INT,
+    draft_pick INT,
+    height_inches INT,
+    weight_pounds INT,
+    forty NUMERIC(4,2),
+    bench INT,
+    vertical NUMERIC(4,1),
+    broad_jump INT,
+    cone NUMERIC(4,2),
+    shuttle NUMERIC(4,2),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Many older combine rows have no PFR ID, so participants are keyed by name, position and school
+CREATE UNIQUE INDEX IF NOT EXISTS idx_combine_results_participant
+    ON combine_results(season, player_name, position, (COALESCE(school, '')));
+CREATE INDEX IF NOT EXISTS idx_combine_results_player ON combine_results(player_id);
+CREATE INDEX IF NOT EXISTS idx_combine_results_pfr ON combine_results(pfr_id);
+
+COMMENT ON TABLE draft_picks IS 'NFL draft history from nflverse (every pick since 1980)';
+COMMENT ON COLUMN draft_picks.w_av IS 'Pro Football Reference weighted career Approximate Value';
+COMMENT ON TABLE combine_results IS 'NFL scouting combine measurements from nflverse';
+COMMENT ON COLUMN combine_results.player_id IS 'Linked through draft_picks.pfr_player_id';
@@ -0,0 +1,15 @@
+-- Migration 025: Add roster pedigree fields to players
+-- Date: 2026-10-15
+-- Purpose: Persist entry year and drafting team from nflverse rosters alongside birth date, experience and draft slot
+
+ALTER TABLE players ADD COLUMN IF NOT EXISTS entry_year INT;
+ALTER TABLE players ADD COLUMN IF NOT EXISTS draft_club VARCHAR(5);
+
+CREATE INDEX IF NOT EXISTS idx_players_entry_year ON players(entry_year);
+CREATE INDEX IF NOT EXISTS idx_players_birth_date ON players(birth_date) WHERE birth_date IS NOT NULL;
+
+COMMENT ON COLUMN players.entry_year IS 'First league year (drafted or undrafted), from nflverse rosters';
+COMMENT ON COLUMN players.draft_club IS 'Team abbreviation that drafted the player; NULL for undrafted players';
+COMMENT ON COLUMN players.draft_year IS 'Draft year (entry year of drafted players)';
+COMMENT ON COLUMN players.draft_pick IS 'Overall draft pick number';
+COMMENT ON COLUMN players.years_pro IS 'Accrued seasons as of the latest imported roster (nflverse years_exp)';
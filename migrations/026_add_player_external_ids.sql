@@ -0,0 +1,16 @@
+-- Migration 026: Add player external ID crosswalk
+-- Date: 2026-10-15
+-- Purpose: Map players to ESPN, Sportradar, Yahoo, Rotowire, PFF, PFR, FantasyData and Sleeper IDs from nflverse rosters
+
+CREATE TABLE IF NOT EXISTS player_external_ids (
+    player_id UUID NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    source VARCHAR(20) NOT NULL,
+    external_id TEXT NOT NULL,
+    updated_at TIMESTAMP DEFAULT NOW(),
+    PRIMARY KEY (player_id, source)
+);
+
+CREATE INDEX IF NOT EXISTS idx_player_external_ids_lookup ON player_external_ids(source, external_id);
+
+COMMENT ON TABLE player_external_ids IS 'Player IDs in other providers, one row per player and source (populated by roster imports)';
+COMMENT ON COLUMN player_external_ids.source IS 'espn, sportradar, yahoo, rotowire, pff, pfr, fantasy_data or sleeper';
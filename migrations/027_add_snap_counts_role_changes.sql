@@ -0,0 +1,55 @@
+-- Migration 027: Add snap counts and detected role changes
+-- Date: 2026-10-15
+-- Purpose: Store nflverse per-game snap counts and the depth chart / snap share changes derived from them
+
+CREATE TABLE IF NOT EXISTS snap_counts (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    nfl_game_id VARCHAR(20) NOT NULL,
+    season INT NOT NULL,
+    season_type VARCHAR(4) NOT NULL DEFAULT 'REG',
+    week INT NOT NULL,
+    team_id UUID REFERENCES teams(id),
+    player_id UUID REFERENCES players(id) ON DELETE SET NULL,
+    pfr_player_id TEXT NOT NULL,
+    player_name TEXT NOT NULL,
+    position VARCHAR(10),
+    offense_snaps INT,
+    offense_pct NUMERIC(4,3),
+    defense_snaps INT,
+    defense_pct NUMERIC(4,3),
+    st_snaps INT,
+    st_pct NUMERIC(4,3),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (nfl_game_id, pfr_player_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_snap_counts_player ON snap_counts(player_id, season, week);
+CREATE INDEX IF NOT EXISTS idx_snap_counts_team_week ON snap_counts(team_id, season, week);
+
+COMMENT ON TABLE snap_counts IS 'Per-game offensive, defensive and special teams snaps from nflverse (2012+)';
+COMMENT ON COLUMN snap_counts.offense_pct IS 'Share of team offensive snaps, 0-1';
+
+CREATE TABLE IF NOT EXISTS role_changes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    season INT NOT NULL,
+    season_type VARCHAR(4) NOT NULL DEFAULT 'REG',
+    week INT NOT NULL,
+    team_id UUID NOT NULL REFERENCES teams(id),
+    position VARCHAR(10) NOT NULL,
+    kind VARCHAR(30) NOT NULL,
+    player_id UUID REFERENCES players(id) ON DELETE SET NULL,
+    player_name TEXT NOT NULL,
+    previous_player_id UUID REFERENCES players(id) ON DELETE SET NULL,
+    previous_player_name TEXT NOT NULL,
+    magnitude NUMERIC(6,3) NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (season, season_type, week, team_id, position, kind)
+);
+
+CREATE INDEX IF NOT EXISTS idx_role_changes_player ON role_changes(player_id, season);
+CREATE INDEX IF NOT EXISTS idx_role_changes_week ON role_changes(season, week);
+
+COMMENT ON TABLE role_changes IS 'Position battles: a new depth chart starter or snap share leader, rebuilt per season by the pipeline';
+COMMENT ON COLUMN role_changes.kind IS 'depth_chart_starter or snap_share_leader';
+COMMENT ON COLUMN role_changes.magnitude IS 'depth_chart_starter: ranks climbed; snap_share_leader: swing in the snap share gap (0-1)';
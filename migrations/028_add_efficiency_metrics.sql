@@ -0,0 +1,29 @@
+-- Migration 028: Add play-by-play efficiency metrics
+-- Date: 2026-10-15
+-- Purpose: Store EPA and success rate per team and player, over all plays and with garbage time removed
+
+CREATE TABLE IF NOT EXISTS efficiency_metrics (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    season INT NOT NULL,
+    season_type VARCHAR(4) NOT NULL DEFAULT 'REG',
+    role VARCHAR(20) NOT NULL,
+    team_id UUID REFERENCES teams(id),
+    player_id UUID REFERENCES players(id) ON DELETE CASCADE,
+    garbage_time_filtered BOOLEAN NOT NULL,
+    wp_threshold NUMERIC(3,2),
+    plays INT NOT NULL,
+    total_epa NUMERIC(10,3) NOT NULL,
+    epa_per_play NUMERIC(7,4) NOT NULL,
+    success_rate NUMERIC(5,4) NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    CHECK ((team_id IS NULL) <> (player_id IS NULL))
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_efficiency_metrics_key
+    ON efficiency_metrics(season, season_type, role, COALESCE(team_id, player_id), garbage_time_filtered);
+CREATE INDEX IF NOT EXISTS idx_efficiency_metrics_player ON efficiency_metrics(player_id, season) WHERE player_id IS NOT NULL;
+
+COMMENT ON TABLE efficiency_metrics IS 'Season EPA/play and success rate from nflverse play-by-play, rebuilt per season by the pipeline';
+COMMENT ON COLUMN efficiency_metrics.role IS 'team_offense, passer, rusher or receiver';
+COMMENT ON COLUMN efficiency_metrics.garbage_time_filtered IS 'True when plays with win probability outside [wp_threshold, 1 - wp_threshold] are excluded';
+COMMENT ON COLUMN efficiency_metrics.wp_threshold IS 'Garbage-time win probability threshold used (NULL for unfiltered rows)';
@@ -0,0 +1,23 @@
+-- Migration 029: Add weekly team ratings
+-- Date: 2026-10-15
+-- Purpose: Store opponent-adjusted team strength (Simple Rating System) after every week
+
+CREATE TABLE IF NOT EXISTS team_ratings (
+    season INT NOT NULL,
+    season_type VARCHAR(4) NOT NULL DEFAULT 'REG',
+    week INT NOT NULL,
+    team_id UUID NOT NULL REFERENCES teams(id),
+    games INT NOT NULL,
+    mov NUMERIC(6,2) NOT NULL,
+    sos NUMERIC(6,2) NOT NULL,
+    srs NUMERIC(6,2) NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (season, season_type, week, team_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_team_ratings_team ON team_ratings(team_id, season);
+
+COMMENT ON TABLE team_ratings IS 'Team strength through each week of a season, rebuilt per season by the pipeline';
+COMMENT ON COLUMN team_ratings.mov IS 'Average margin of victory through the week';
+COMMENT ON COLUMN team_ratings.sos IS 'Strength of schedule: average SRS of opponents faced';
+COMMENT ON COLUMN team_ratings.srs IS 'Simple Rating System (mov + sos), points better than an average team';
@@ -0,0 +1,20 @@
+-- Migration 030: Add Pythagorean expectation and one-score records to standings
+-- Date: 2026-10-15
+-- Purpose: Flag teams whose record outran (or trailed) their point differential
+
+ALTER TABLE team_standings ADD COLUMN IF NOT EXISTS pythagorean_win_pct DECIMAL(5,3);
+ALTER TABLE team_standings ADD COLUMN IF NOT EXISTS expected_wins DECIMAL(4,2);
+ALTER TABLE team_standings ADD COLUMN IF NOT EXISTS win_luck DECIMAL(4,2);
+ALTER TABLE team_standings ADD COLUMN IF NOT EXISTS one_score_wins INT;
+ALTER TABLE team_standings ADD COLUMN IF NOT EXISTS one_score_losses INT;
+ALTER TABLE team_standings ADD COLUMN IF NOT EXISTS one_score_ties INT;
+
+-- Season totals use week NULL, which the (team_id, season, week) constraint can't match on conflict
+CREATE UNIQUE INDEX IF NOT EXISTS idx_team_standings_season_total
+    ON team_standings(team_id, season) WHERE week IS NULL;
+
+COMMENT ON COLUMN team_standings.pythagorean_win_pct IS 'Win percentage expected from points for and against (exponent 2.37)';
+COMMENT ON COLUMN team_standings.expected_wins IS 'Pythagorean win percentage times games played';
+COMMENT ON COLUMN team_standings.win_luck IS 'Actual wins (ties as half) minus expected wins; large values are regression candidates';
+COMMENT ON COLUMN team_standings.one_score_wins IS 'Wins by 8 points or fewer';
+COMMENT ON COLUMN team_standings.one_score_losses IS 'Losses by 8 points or fewer';
@@ -0,0 +1,52 @@
+-- Migration 031: Add player contracts and value metrics
+-- Date: 2026-10-15
+-- Purpose: OverTheCap contract history joined with draft slots to measure production per cap dollar
+
+CREATE TABLE IF NOT EXISTS player_contracts (
+    id BIGSERIAL PRIMARY KEY,
+    player_id UUID REFERENCES players(id) ON DELETE SET NULL,
+    gsis_id TEXT,
+    otc_id TEXT,
+    player_name TEXT NOT NULL,
+    position VARCHAR(10),
+    team TEXT NOT NULL,
+    is_active BOOLEAN,
+    year_signed INT NOT NULL,
+    years INT,
+    value NUMERIC(8,3),
+    apy NUMERIC(8,3),
+    guaranteed NUMERIC(8,3),
+    apy_cap_pct NUMERIC(6,4),
+    draft_year INT,
+    draft_round INT,
+    draft_overall INT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_player_contracts_player ON player_contracts(player_id, year_signed);
+
+CREATE TABLE IF NOT EXISTS value_metrics (
+    season INT NOT NULL,
+    player_id UUID NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    position VARCHAR(10),
+    contract_year_signed INT NOT NULL,
+    apy NUMERIC(8,3) NOT NULL,
+    apy_cap_pct NUMERIC(6,4),
+    rookie_contract BOOLEAN NOT NULL,
+    draft_round INT,
+    draft_pick INT,
+    games INT NOT NULL,
+    fantasy_points_ppr NUMERIC(7,2) NOT NULL,
+    points_per_million NUMERIC(9,2),
+    cost_per_point NUMERIC(12,2),
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (season, player_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_value_metrics_rank ON value_metrics(season, position, points_per_million DESC);
+
+COMMENT ON TABLE player_contracts IS 'OverTheCap contract history via nflverse, replaced on each --mode contracts run; money in millions';
+COMMENT ON TABLE value_metrics IS 'Regular-season production per cap dollar under the contract in effect, rebuilt per season by the pipeline';
+COMMENT ON COLUMN value_metrics.rookie_contract IS 'Contract signed in the player''s draft year';
+COMMENT ON COLUMN value_metrics.points_per_million IS 'PPR fantasy points per $1M of average annual value';
+COMMENT ON COLUMN value_metrics.cost_per_point IS 'Average annual value in dollars per PPR fantasy point';
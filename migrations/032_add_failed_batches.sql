@@ -0,0 +1,10 @@
+-- Migration 032: Track failed batches in import progress
+-- Date: 2026-10-15
+-- Purpose: Batches are written in one transaction each; record the ones rolled back so partial imports are visible
+
+ALTER TABLE import_progress ADD COLUMN IF NOT EXISTS failed_batches INT NOT NULL DEFAULT 0;
+ALTER TABLE import_progress ADD COLUMN IF NOT EXISTS failed_records INT NOT NULL DEFAULT 0;
+
+COMMENT ON COLUMN import_progress.failed_batches IS 'Batches rolled back during the latest run; a completed run with failures is marked partial';
+COMMENT ON COLUMN import_progress.failed_records IS 'Rows in the rolled-back batches (none of them were written)';
+COMMENT ON COLUMN import_progress.error_message IS 'Error from the most recent failed batch';
@@ -0,0 +1,7 @@
+-- Migration 033: Source file fingerprints in import progress
+-- Date: 2026-10-15
+-- Purpose: Skip re-importing a dataset/season whose downloaded file is byte-for-byte unchanged since its last completed import
+
+ALTER TABLE import_progress ADD COLUMN IF NOT EXISTS source_fingerprint TEXT;
+
+COMMENT ON COLUMN import_progress.source_fingerprint IS 'SHA-256 of the source file as downloaded, recorded when the season import completed';
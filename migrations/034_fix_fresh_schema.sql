@@ -0,0 +1,34 @@
+-- Migration 034: Fixes for a database built from these migrations alone
+-- Date: 2026-10-15
+-- Purpose: Migration 007 indexes columns the Go importer created (player_team_history.start_date,
+-- game_team_stats.season, player_injuries) and uses gin_trgm_ops before pg_trgm exists, so on a fresh
+-- database those statements fail and are skipped. Create the equivalent indexes on the columns these
+-- migrations define. The same goes for game_stats: nflverse stat lines are keyed by player, season,
+-- week and season type, not by game, and their upserts have always set updated_at.
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX IF NOT EXISTS idx_teams_name_trgm ON teams USING gin(name gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_game_team_stats_team_game ON game_team_stats(team_id, game_id);
+
+-- A Go importer database keeps its start_date/end_date index
+DO $$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_schema = current_schema() AND table_name = 'player_team_history' AND column_name = 'season_start'
+    ) THEN
+        CREATE INDEX IF NOT EXISTS idx_player_team_history_player_seasons
+            ON player_team_history(player_id, season_start, season_end);
+    END IF;
+END $$;
+
+ALTER TABLE game_stats ADD COLUMN IF NOT EXISTS updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP;
+ALTER TABLE game_stats ALTER COLUMN game_id DROP NOT NULL;
+
+COMMENT ON COLUMN game_stats.updated_at IS 'Last time the stat line was written';
+COMMENT ON COLUMN game_stats.game_id IS 'Set by the Go importer; weekly nflverse lines match games on season, week, season type and team_id';
+
+ANALYZE teams;
+ANALYZE player_team_history;
+ANALYZE game_team_stats;
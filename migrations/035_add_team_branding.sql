@@ -0,0 +1,10 @@
+-- Migration 035: Team branding columns
+-- Date: 2026-10-15
+-- Purpose: Store the nickname, primary color and wordmark loaded by seed-teams next to the existing alternate_color and logo_url
+
+ALTER TABLE teams ADD COLUMN IF NOT EXISTS nickname TEXT;
+ALTER TABLE teams ADD COLUMN IF NOT EXISTS color VARCHAR(10);
+ALTER TABLE teams ADD COLUMN IF NOT EXISTS wordmark_url TEXT;
+
+COMMENT ON COLUMN teams.color IS 'Primary team color (hex), from nflverse teams_colors_logos';
+COMMENT ON COLUMN teams.wordmark_url IS 'Team wordmark image URL, from nflverse teams_colors_logos';
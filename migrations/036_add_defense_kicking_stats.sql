@@ -0,0 +1,21 @@
+-- Migration 036: Defensive and kicking stat columns
+-- Date: 2026-10-15
+-- Purpose: Store IDP and kicker production from nflverse player stats next to the existing tackles, field goal and extra point columns
+
+ALTER TABLE game_stats ADD COLUMN IF NOT EXISTS special_teams_tds INTEGER DEFAULT 0;
+ALTER TABLE game_stats ADD COLUMN IF NOT EXISTS solo_tackles INTEGER DEFAULT 0;
+ALTER TABLE game_stats ADD COLUMN IF NOT EXISTS tackle_assists INTEGER DEFAULT 0;
+ALTER TABLE game_stats ADD COLUMN IF NOT EXISTS def_sacks NUMERIC(4, 1) DEFAULT 0;
+ALTER TABLE game_stats ADD COLUMN IF NOT EXISTS def_interceptions INTEGER DEFAULT 0;
+ALTER TABLE game_stats ADD COLUMN IF NOT EXISTS def_fumbles_forced INTEGER DEFAULT 0;
+ALTER TABLE game_stats ADD COLUMN IF NOT EXISTS def_fumble_recoveries INTEGER DEFAULT 0;
+ALTER TABLE game_stats ADD COLUMN IF NOT EXISTS def_tds INTEGER DEFAULT 0;
+ALTER TABLE game_stats ADD COLUMN IF NOT EXISTS def_safeties INTEGER DEFAULT 0;
+ALTER TABLE game_stats ADD COLUMN IF NOT EXISTS fg_long INTEGER;
+ALTER TABLE game_stats ADD COLUMN IF NOT EXISTS fg_made_0_39 INTEGER DEFAULT 0;
+ALTER TABLE game_stats ADD COLUMN IF NOT EXISTS fg_made_40_49 INTEGER DEFAULT 0;
+ALTER TABLE game_stats ADD COLUMN IF NOT EXISTS fg_made_50_plus INTEGER DEFAULT 0;
+
+COMMENT ON COLUMN game_stats.tackles IS 'Solo tackles plus tackle assists';
+COMMENT ON COLUMN game_stats.def_sacks IS 'Sacks made by a defender (half sacks when shared); game_stats.sacks is unused';
+COMMENT ON COLUMN game_stats.def_fumble_recoveries IS 'Opponent fumbles recovered';
@@ -0,0 +1,22 @@
+-- Migration 037: Fantasy points per player and week
+-- Date: 2026-10-15
+-- Purpose: Weekly fantasy points under each scoring format (standard, half PPR, PPR and custom --scoring-config formats), rebuilt from game_stats after each import
+
+CREATE TABLE IF NOT EXISTS fantasy_points (
+    player_id UUID NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    season INTEGER NOT NULL,
+    week INTEGER NOT NULL,
+    season_type VARCHAR(4) NOT NULL DEFAULT 'REG',
+    scoring TEXT NOT NULL,
+    points NUMERIC(6, 2) NOT NULL,
+    fantasy_playoff_week BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMP DEFAULT NOW(),
+    PRIMARY KEY (player_id, season, week, season_type, scoring)
+);
+
+CREATE INDEX IF NOT EXISTS idx_fantasy_points_leaders ON fantasy_points (season, scoring, week, points DESC);
+CREATE INDEX IF NOT EXISTS idx_fantasy_points_playoff_weeks ON fantasy_points (season, scoring, points DESC)
+    WHERE fantasy_playoff_week;
+
+COMMENT ON TABLE fantasy_points IS 'Fantasy points per player, week and scoring format, derived from game_stats';
+COMMENT ON COLUMN fantasy_points.fantasy_playoff_week IS 'Regular-season week in FANTASY_PLAYOFF_WEEKS (transformer.rs), when most leagues hold their playoffs';
@@ -0,0 +1,23 @@
+-- Migration 038: Roster moves between weekly snapshots
+-- Date: 2026-10-15
+-- Purpose: Players added to, removed from or changed on (team, position, status, jersey number) a roster from one week to the next, diffed from the nflverse weekly rosters
+
+CREATE TABLE IF NOT EXISTS roster_moves (
+    id SERIAL PRIMARY KEY,
+    season INTEGER NOT NULL,
+    week INTEGER NOT NULL,
+    previous_week INTEGER NOT NULL,
+    gsis_id VARCHAR(20) NOT NULL,
+    player_id UUID REFERENCES players(id) ON DELETE SET NULL,
+    player_name VARCHAR(255) NOT NULL,
+    move VARCHAR(10) NOT NULL CHECK (move IN ('added', 'removed', 'changed')),
+    field VARCHAR(20),
+    old_value TEXT,
+    new_value TEXT,
+    created_at TIMESTAMP DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_roster_moves_season_week ON roster_moves (season, week);
+CREATE INDEX IF NOT EXISTS idx_roster_moves_player ON roster_moves (player_id);
+
+COMMENT ON TABLE roster_moves IS 'Week-over-week roster changes, rebuilt per season from weekly roster snapshots';
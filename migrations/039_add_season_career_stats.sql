@@ -0,0 +1,110 @@
+-- Migration 039: Season and career stat aggregates
+-- Date: 2026-10-15
+-- Purpose: Per-player season and career totals, games played and per-game averages, rebuilt from game_stats after each import (or all at once with --mode aggregate) so readers don't GROUP BY game_stats per request
+
+CREATE TABLE IF NOT EXISTS season_stats (
+    player_id UUID NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    season INTEGER NOT NULL,
+    season_type VARCHAR(4) NOT NULL DEFAULT 'REG',
+    games INTEGER NOT NULL,
+    completions INTEGER NOT NULL DEFAULT 0,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    passing_yards INTEGER NOT NULL DEFAULT 0,
+    passing_tds INTEGER NOT NULL DEFAULT 0,
+    interceptions INTEGER NOT NULL DEFAULT 0,
+    rushing_yards INTEGER NOT NULL DEFAULT 0,
+    rushing_tds INTEGER NOT NULL DEFAULT 0,
+    targets INTEGER NOT NULL DEFAULT 0,
+    receptions INTEGER NOT NULL DEFAULT 0,
+    receiving_yards INTEGER NOT NULL DEFAULT 0,
+    receiving_tds INTEGER NOT NULL DEFAULT 0,
+    fumbles INTEGER NOT NULL DEFAULT 0,
+    fumbles_lost INTEGER NOT NULL DEFAULT 0,
+    two_point_conversions INTEGER NOT NULL DEFAULT 0,
+    special_teams_tds INTEGER NOT NULL DEFAULT 0,
+    tackles INTEGER NOT NULL DEFAULT 0,
+    solo_tackles INTEGER NOT NULL DEFAULT 0,
+    tackle_assists INTEGER NOT NULL DEFAULT 0,
+    tackles_for_loss INTEGER NOT NULL DEFAULT 0,
+    qb_hits INTEGER NOT NULL DEFAULT 0,
+    passes_defended INTEGER NOT NULL DEFAULT 0,
+    def_interceptions INTEGER NOT NULL DEFAULT 0,
+    def_fumbles_forced INTEGER NOT NULL DEFAULT 0,
+    def_fumble_recoveries INTEGER NOT NULL DEFAULT 0,
+    def_tds INTEGER NOT NULL DEFAULT 0,
+    def_safeties INTEGER NOT NULL DEFAULT 0,
+    field_goals_made INTEGER NOT NULL DEFAULT 0,
+    field_goals_attempted INTEGER NOT NULL DEFAULT 0,
+    fg_made_0_39 INTEGER NOT NULL DEFAULT 0,
+    fg_made_40_49 INTEGER NOT NULL DEFAULT 0,
+    fg_made_50_plus INTEGER NOT NULL DEFAULT 0,
+    extra_points_made INTEGER NOT NULL DEFAULT 0,
+    extra_points_attempted INTEGER NOT NULL DEFAULT 0,
+    def_sacks NUMERIC(6, 1) NOT NULL DEFAULT 0,
+    fg_long INTEGER,
+    passing_yards_per_game NUMERIC(7, 2) NOT NULL DEFAULT 0,
+    rushing_yards_per_game NUMERIC(7, 2) NOT NULL DEFAULT 0,
+    receiving_yards_per_game NUMERIC(7, 2) NOT NULL DEFAULT 0,
+    receptions_per_game NUMERIC(7, 2) NOT NULL DEFAULT 0,
+    targets_per_game NUMERIC(7, 2) NOT NULL DEFAULT 0,
+    tackles_per_game NUMERIC(7, 2) NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP DEFAULT NOW(),
+    PRIMARY KEY (player_id, season, season_type)
+);
+
+CREATE INDEX IF NOT EXISTS idx_season_stats_season ON season_stats (season, season_type);
+
+CREATE TABLE IF NOT EXISTS career_stats (
+    player_id UUID NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    season_type VARCHAR(4) NOT NULL DEFAULT 'REG',
+    first_season INTEGER NOT NULL,
+    last_season INTEGER NOT NULL,
+    seasons INTEGER NOT NULL,
+    games INTEGER NOT NULL,
+    completions INTEGER NOT NULL DEFAULT 0,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    passing_yards INTEGER NOT NULL DEFAULT 0,
+    passing_tds INTEGER NOT NULL DEFAULT 0,
+    interceptions INTEGER NOT NULL DEFAULT 0,
+    rushing_yards INTEGER NOT NULL DEFAULT 0,
+    rushing_tds INTEGER NOT NULL DEFAULT 0,
+    targets INTEGER NOT NULL DEFAULT 0,
+    receptions INTEGER NOT NULL DEFAULT 0,
+    receiving_yards INTEGER NOT NULL DEFAULT 0,
+    receiving_tds INTEGER NOT NULL DEFAULT 0,
+    fumbles INTEGER NOT NULL DEFAULT 0,
+    fumbles_lost INTEGER NOT NULL DEFAULT 0,
+    two_point_conversions INTEGER NOT NULL DEFAULT 0,
+    special_teams_tds INTEGER NOT NULL DEFAULT 0,
+    tackles INTEGER NOT NULL DEFAULT 0,
+    solo_tackles INTEGER NOT NULL DEFAULT 0,
+    tackle_assists INTEGER NOT NULL DEFAULT 0,
+    tackles_for_loss INTEGER NOT NULL DEFAULT 0,
+    qb_hits INTEGER NOT NULL DEFAULT 0,
+    passes_defended INTEGER NOT NULL DEFAULT 0,
+    def_interceptions INTEGER NOT NULL DEFAULT 0,
+    def_fumbles_forced INTEGER NOT NULL DEFAULT 0,
+    def_fumble_recoveries INTEGER NOT NULL DEFAULT 0,
+    def_tds INTEGER NOT NULL DEFAULT 0,
+    def_safeties INTEGER NOT NULL DEFAULT 0,
+    field_goals_made INTEGER NOT NULL DEFAULT 0,
+    field_goals_attempted INTEGER NOT NULL DEFAULT 0,
+    fg_made_0_39 INTEGER NOT NULL DEFAULT 0,
+    fg_made_40_49 INTEGER NOT NULL DEFAULT 0,
+    fg_made_50_plus INTEGER NOT NULL DEFAULT 0,
+    extra_points_made INTEGER NOT NULL DEFAULT 0,
+    extra_points_attempted INTEGER NOT NULL DEFAULT 0,
+    def_sacks NUMERIC(6, 1) NOT NULL DEFAULT 0,
+    fg_long INTEGER,
+    passing_yards_per_game NUMERIC(7, 2) NOT NULL DEFAULT 0,
+    rushing_yards_per_game NUMERIC(7, 2) NOT NULL DEFAULT 0,
+    receiving_yards_per_game NUMERIC(7, 2) NOT NULL DEFAULT 0,
+    receptions_per_game NUMERIC(7, 2) NOT NULL DEFAULT 0,
+    targets_per_game NUMERIC(7, 2) NOT NULL DEFAULT 0,
+    tackles_per_game NUMERIC(7, 2) NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP DEFAULT NOW(),
+    PRIMARY KEY (player_id, season_type)
+);
+
+COMMENT ON TABLE season_stats IS 'Player totals and per-game averages per season and season type, derived from game_stats';
+COMMENT ON TABLE career_stats IS 'Player career totals and per-game averages per season type, derived from game_stats';
@@ -0,0 +1,30 @@
+-- Migration 040: Usage trend alerts
+-- Date: 2026-10-15
+-- Purpose: Store carries and target share per game, and flag weeks where a player's snap share, touches or target share moved sharply from their recent baseline
+
+ALTER TABLE game_stats ADD COLUMN IF NOT EXISTS carries INTEGER DEFAULT 0;
+ALTER TABLE game_stats ADD COLUMN IF NOT EXISTS target_share NUMERIC(5, 4);
+
+ALTER TABLE season_stats ADD COLUMN IF NOT EXISTS carries INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE career_stats ADD COLUMN IF NOT EXISTS carries INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS usage_alerts (
+    id SERIAL PRIMARY KEY,
+    season INTEGER NOT NULL,
+    season_type VARCHAR(4) NOT NULL DEFAULT 'REG',
+    week INTEGER NOT NULL,
+    player_id UUID NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    player_name VARCHAR(255) NOT NULL,
+    position VARCHAR(10),
+    metric VARCHAR(20) NOT NULL CHECK (metric IN ('snap_share', 'touches', 'target_share')),
+    direction VARCHAR(5) NOT NULL CHECK (direction IN ('spike', 'drop')),
+    baseline NUMERIC(6, 3) NOT NULL,
+    value NUMERIC(6, 3) NOT NULL,
+    delta NUMERIC(6, 3) NOT NULL,
+    created_at TIMESTAMP DEFAULT NOW(),
+    UNIQUE (season, season_type, week, player_id, metric)
+);
+
+CREATE INDEX IF NOT EXISTS idx_usage_alerts_week ON usage_alerts (season, week);
+
+COMMENT ON TABLE usage_alerts IS 'Snap share, touch and target share spikes and drops against each player''s previous three games';
@@ -0,0 +1,8 @@
+-- Migration 041: Division and conference ties in weekly standings
+-- Date: 2026-10-15
+-- Purpose: The pipeline now rebuilds team_standings after every completed week; record tied division and conference games alongside the existing win/loss splits
+
+ALTER TABLE team_standings ADD COLUMN IF NOT EXISTS division_ties INTEGER DEFAULT 0;
+ALTER TABLE team_standings ADD COLUMN IF NOT EXISTS conference_ties INTEGER DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_team_standings_season_week ON team_standings (season, week);
@@ -0,0 +1,12 @@
+-- Migration 042: Elo ratings
+-- Date: 2026-10-15
+-- Purpose: Keep schedule rest days and neutral-site locations on games, and store each team's Elo rating after every week next to its SRS
+
+ALTER TABLE games ADD COLUMN IF NOT EXISTS home_rest INTEGER;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS away_rest INTEGER;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS location TEXT;
+
+ALTER TABLE team_ratings ADD COLUMN IF NOT EXISTS elo NUMERIC(6, 1);
+
+COMMENT ON COLUMN games.location IS 'Home or Neutral, from the nflverse schedule';
+COMMENT ON COLUMN team_ratings.elo IS 'Elo rating after the week (FiveThirtyEight method; 1505 is average)';
@@ -0,0 +1,20 @@
+-- Migration 043: Weather splits
+-- Date: 2026-10-15
+-- Purpose: Record each stat line's team so games can be matched to their weather, and store wind/cold passing and kicking deltas against fair-weather games in player_splits
+
+ALTER TABLE game_stats ADD COLUMN IF NOT EXISTS team_id UUID REFERENCES teams(id);
+
+ALTER TABLE player_splits ADD COLUMN IF NOT EXISTS completions INT DEFAULT 0;
+ALTER TABLE player_splits ADD COLUMN IF NOT EXISTS attempts INT DEFAULT 0;
+ALTER TABLE player_splits ADD COLUMN IF NOT EXISTS field_goals_made INT DEFAULT 0;
+ALTER TABLE player_splits ADD COLUMN IF NOT EXISTS field_goals_attempted INT DEFAULT 0;
+ALTER TABLE player_splits ADD COLUMN IF NOT EXISTS baseline_games INT;
+ALTER TABLE player_splits ADD COLUMN IF NOT EXISTS passing_yards_per_game_delta NUMERIC(6, 1);
+ALTER TABLE player_splits ADD COLUMN IF NOT EXISTS yards_per_attempt_delta NUMERIC(5, 2);
+ALTER TABLE player_splits ADD COLUMN IF NOT EXISTS completion_pct_delta NUMERIC(5, 3);
+ALTER TABLE player_splits ADD COLUMN IF NOT EXISTS fg_pct_delta NUMERIC(5, 3);
+
+COMMENT ON COLUMN game_stats.team_id IS 'Team the player played for in the game (nflverse recent_team)';
+COMMENT ON COLUMN player_splits.split IS 'Split name: regular_season, fantasy_playoffs (REG weeks 15-17), wind, cold';
+COMMENT ON COLUMN player_splits.baseline_games IS 'Weather splits: fair-weather games (indoors, or outdoors without wind or cold) the deltas compare against';
+COMMENT ON COLUMN player_splits.passing_yards_per_game_delta IS 'Weather splits: passing yards per game in the condition minus the fair-weather average';
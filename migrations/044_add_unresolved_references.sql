@@ -0,0 +1,21 @@
+-- Migration 044: Unresolved references
+-- Date: 2026-10-15
+-- Purpose: Collect the team and player lookups imports failed on (rows dropped or left unlinked) for the --mode unresolved report
+
+CREATE TABLE IF NOT EXISTS unresolved_references (
+    kind TEXT NOT NULL,                  -- 'team' or 'player'
+    reference TEXT NOT NULL,             -- team abbreviation or GSIS ID
+    dataset TEXT NOT NULL,               -- source dataset, e.g. 'player_stats'
+    context TEXT,                        -- e.g. the player's name
+    last_season INT NOT NULL,
+    occurrences BIGINT NOT NULL DEFAULT 0,
+    first_seen TIMESTAMP NOT NULL DEFAULT NOW(),
+    last_seen TIMESTAMP NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMP,
+    PRIMARY KEY (kind, reference, dataset)
+);
+
+CREATE INDEX IF NOT EXISTS idx_unresolved_references_open ON unresolved_references(last_seen) WHERE resolved_at IS NULL;
+
+COMMENT ON TABLE unresolved_references IS 'Failed team/player lookups during imports, with occurrence counts; see --mode unresolved';
+COMMENT ON COLUMN unresolved_references.resolved_at IS 'Set when the report finds the team or player now exists; cleared if the lookup fails again';
@@ -0,0 +1,31 @@
+-- Migration 045: Data sources and attribution
+-- Date: 2026-10-15
+-- Purpose: License and attribution text for each upstream source, and which datasets every run read from them
+
+CREATE TABLE IF NOT EXISTS sources (
+    key TEXT PRIMARY KEY,                -- 'nflverse', 'espn', ...
+    name TEXT NOT NULL,
+    homepage TEXT NOT NULL,
+    license TEXT NOT NULL,
+    attribution TEXT NOT NULL,           -- credit line to reproduce alongside the data
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS source_runs (
+    id BIGSERIAL PRIMARY KEY,
+    run_started_at TIMESTAMP NOT NULL,
+    mode TEXT NOT NULL,
+    source_key TEXT NOT NULL REFERENCES sources(key),
+    dataset TEXT NOT NULL,
+    season INT NOT NULL DEFAULT 0,       -- 0 for files covering every season
+    url TEXT NOT NULL,
+    license TEXT NOT NULL,               -- as of the run
+    attribution TEXT NOT NULL,
+    recorded_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    UNIQUE (run_started_at, dataset, season)
+);
+
+CREATE INDEX IF NOT EXISTS idx_source_runs_source ON source_runs(source_key, run_started_at DESC);
+
+COMMENT ON TABLE sources IS 'Upstream data sources with license and attribution; rows are kept current by the pipeline';
+COMMENT ON TABLE source_runs IS 'Datasets each pipeline run read, with the license and attribution in force at the time';
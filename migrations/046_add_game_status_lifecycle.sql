@@ -0,0 +1,12 @@
+-- Migration 046: Game status lifecycle
+-- Date: 2026-10-15
+-- Purpose: Schedule imports used to mark every game 'final'; reopen unplayed future games as 'scheduled'
+
+UPDATE games
+SET status = 'scheduled', updated_at = NOW()
+WHERE status = 'final'
+  AND home_score IS NULL
+  AND away_score IS NULL
+  AND game_date >= CURRENT_DATE - 1;
+
+COMMENT ON COLUMN games.status IS 'scheduled, in_progress (live mode) or final; only moves forward (see game_status.rs)';
@@ -0,0 +1,37 @@
+-- Migration 047: Kickoff timestamps with time zone
+-- Date: 2026-10-15
+-- Purpose: games.game_date held the schedule's gameday at midnight (or, for games
+-- from the ESPN ingester, the UTC kickoff without a zone); store the kickoff instant
+
+-- Views in the pack read game_date and block the type change; keep the installed
+-- definitions and recreate them once the column is converted
+CREATE TEMP TABLE kickoff_views ON COMMIT DROP AS
+SELECT c.relname AS name, rtrim(pg_get_viewdef(c.oid), ';') AS definition
+FROM pg_class c
+JOIN pg_namespace n ON n.oid = c.relnamespace
+WHERE c.relkind = 'v' AND n.nspname = current_schema() AND c.relname IN ('player_game_logs', 'team_schedules');
+
+DROP VIEW IF EXISTS player_game_logs;
+DROP VIEW IF EXISTS team_schedules;
+
+ALTER TABLE games ALTER COLUMN game_date TYPE TIMESTAMPTZ USING
+    CASE
+        -- The ESPN ingester keys games by ESPN event ID and wrote the UTC kickoff,
+        -- midnight included
+        WHEN nfl_game_id ~ '^\d+$' THEN game_date AT TIME ZONE 'UTC'
+        -- Schedule rows: gameday plus the Eastern kickoff time
+        WHEN game_time_et ~ '^\d{1,2}:\d{2}(:\d{2})?$'
+            THEN (game_date::date + game_time_et::time) AT TIME ZONE 'America/New_York'
+        ELSE game_date::date::timestamp AT TIME ZONE 'America/New_York'
+    END;
+
+DO $$
+DECLARE
+    view RECORD;
+BEGIN
+    FOR view IN SELECT name, definition FROM kickoff_views LOOP
+        EXECUTE format('CREATE VIEW %I AS %s', view.name, view.definition);
+    END LOOP;
+END $$;
+
+COMMENT ON COLUMN games.game_date IS 'Kickoff instant; midnight Eastern on the gameday when no kickoff time is known';
@@ -0,0 +1,12 @@
+-- Migration 048: ESPN game IDs
+-- Date: 2026-10-15
+-- Purpose: Link games to ESPN events so ESPN's schedule and box scores merge into
+-- nflverse games instead of duplicating them
+
+ALTER TABLE games ADD COLUMN IF NOT EXISTS espn_game_id TEXT;
+
+-- Games written by the Go importer use the ESPN event ID as nfl_game_id
+UPDATE games SET espn_game_id = nfl_game_id
+WHERE espn_game_id IS NULL AND nfl_game_id ~ '^[0-9]+$';
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_games_espn_game_id ON games(espn_game_id);
@@ -0,0 +1,7 @@
+-- Migration 049: Add match confidence to the player ID crosswalk
+-- Date: 2026-10-15
+-- Purpose: Mark ESPN IDs inferred by the athlete backfill (name, birth date, team and position matching) apart from IDs the source data provides
+
+ALTER TABLE player_external_ids ADD COLUMN IF NOT EXISTS match_confidence NUMERIC(4,3);
+
+COMMENT ON COLUMN player_external_ids.match_confidence IS 'NULL when the source data provided the ID; 0-1 score when the ESPN ID backfill (--mode espn-ids) inferred it';
@@ -0,0 +1,35 @@
+-- Migration 050: Per-field provenance for games and players
+-- Date: 2026-10-15
+-- Purpose: Record which source (nflverse, local, espn, live) wrote each merged column so
+-- writers can apply per-field source precedence (--precedence)
+
+ALTER TABLE games ADD COLUMN IF NOT EXISTS provenance JSONB NOT NULL DEFAULT '{}';
+ALTER TABLE players ADD COLUMN IF NOT EXISTS provenance JSONB NOT NULL DEFAULT '{}';
+
+-- Existing values: games the Go importer wrote carry ESPN event IDs, everything else came from nflverse
+UPDATE games g SET provenance = (
+    SELECT COALESCE(jsonb_object_agg(c.col, CASE WHEN g.nfl_game_id ~ '^[0-9]+$' THEN 'espn' ELSE 'nflverse' END), '{}')
+    FROM (VALUES
+        ('season_type', g.season_type IS NOT NULL), ('week', g.week IS NOT NULL), ('game_date', g.game_date IS NOT NULL),
+        ('home_score', g.home_score IS NOT NULL), ('away_score', g.away_score IS NOT NULL),
+        ('venue_name', g.venue_name IS NOT NULL), ('venue_city', g.venue_city IS NOT NULL),
+        ('venue_state', g.venue_state IS NOT NULL), ('attendance', g.attendance IS NOT NULL)
+    ) AS c (col, present)
+    WHERE c.present
+)
+WHERE g.provenance = '{}';
+
+-- Players without a GSIS ID were created from ESPN rosters
+UPDATE players p SET provenance = (
+    SELECT COALESCE(jsonb_object_agg(c.col, CASE WHEN p.nfl_id IS NOT NULL THEN 'nflverse' ELSE 'espn' END), '{}')
+    FROM (VALUES
+        ('team_id', p.team_id IS NOT NULL), ('position', p.position IS NOT NULL),
+        ('jersey_number', p.jersey_number IS NOT NULL), ('height_inches', p.height_inches IS NOT NULL),
+        ('weight_pounds', p.weight_pounds IS NOT NULL), ('college', p.college IS NOT NULL)
+    ) AS c (col, present)
+    WHERE c.present
+)
+WHERE p.provenance = '{}';
+
+COMMENT ON COLUMN games.provenance IS 'Source of each merged column: {"home_score": "live", "venue_name": "espn", ...}';
+COMMENT ON COLUMN players.provenance IS 'Source of each merged roster column: {"team_id": "nflverse", ...}';
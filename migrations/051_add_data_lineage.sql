@@ -0,0 +1,35 @@
+-- Migration 051: Data lineage
+-- Date: 2026-10-15
+-- Purpose: Trace imported rows back to the source file (URL, release tag, download time, file hash)
+-- and row hash that produced them (--lineage)
+
+CREATE TABLE IF NOT EXISTS source_files (
+    id BIGSERIAL PRIMARY KEY,
+    run_started_at TIMESTAMP NOT NULL,
+    dataset TEXT NOT NULL,
+    season INT NOT NULL,
+    url TEXT NOT NULL,                   -- download URL, or the path under --source-dir
+    release_tag TEXT,                    -- GitHub release the asset belongs to
+    downloaded_at TIMESTAMPTZ NOT NULL,
+    sha256 TEXT,                         -- whole file, when the stage fingerprinted it
+    recorded_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_source_files_dataset ON source_files(dataset, season, run_started_at DESC);
+
+CREATE TABLE IF NOT EXISTS data_lineage (
+    dataset TEXT NOT NULL,
+    row_key TEXT NOT NULL,               -- the dataset's key columns joined with '/', or the row number
+    source_file_id BIGINT NOT NULL REFERENCES source_files(id),
+    row_hash TEXT NOT NULL,              -- SHA-256 of the row's fields as read
+    previous_row_hash TEXT,              -- hash before the row last changed
+    first_seen_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (dataset, row_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_data_lineage_file ON data_lineage(source_file_id);
+CREATE INDEX IF NOT EXISTS idx_data_lineage_key ON data_lineage(row_key text_pattern_ops);
+
+COMMENT ON TABLE source_files IS 'Source files read by runs with --lineage';
+COMMENT ON TABLE data_lineage IS 'The source file and row hash behind each imported row, per dataset (--mode lineage --key ...)';
@@ -0,0 +1,25 @@
+-- Migration 052: Game news
+-- Date: 2026-10-15
+-- Purpose: Store ESPN game recaps and related headlines linked to games (--with-news, --mode game-news)
+-- as textual context alongside the structured stats
+
+CREATE TABLE IF NOT EXISTS game_news (
+    id BIGSERIAL PRIMARY KEY,
+    game_id UUID NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    source TEXT NOT NULL DEFAULT 'espn',
+    article_id TEXT NOT NULL,            -- the source's article ID
+    kind TEXT NOT NULL,                  -- 'recap' or 'headline'
+    headline TEXT NOT NULL,
+    summary TEXT,
+    body TEXT,                           -- recap text without markup
+    url TEXT,
+    published_at TIMESTAMPTZ,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    UNIQUE (game_id, source, article_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_game_news_game ON game_news(game_id, kind);
+CREATE INDEX IF NOT EXISTS idx_game_news_published ON game_news(published_at DESC);
+
+COMMENT ON TABLE game_news IS 'Recaps and headlines about each game, from ESPN game summaries';
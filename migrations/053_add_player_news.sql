@@ -0,0 +1,27 @@
+-- Migration 053: Player news
+-- Date: 2026-10-15
+-- Purpose: Store injury updates, transactions and other news items from the configured feed (--news-feed),
+-- linked to players through ESPN athlete tags or name matching
+
+CREATE TABLE IF NOT EXISTS player_news (
+    id BIGSERIAL PRIMARY KEY,
+    player_id UUID NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    source TEXT NOT NULL,                -- 'espn' or 'rss'
+    feed_url TEXT NOT NULL,
+    item_id TEXT NOT NULL,               -- ESPN article ID, or the RSS item's guid or link
+    category TEXT NOT NULL,              -- 'injury', 'transaction' or 'news'
+    headline TEXT NOT NULL,
+    summary TEXT,
+    url TEXT,
+    published_at TIMESTAMPTZ,
+    match_method TEXT NOT NULL,          -- 'espn_id' (tagged athlete in the crosswalk) or 'name'
+    match_confidence NUMERIC(4,3) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    UNIQUE (player_id, source, item_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_player_news_player ON player_news(player_id, published_at DESC);
+CREATE INDEX IF NOT EXISTS idx_player_news_category ON player_news(category, published_at DESC);
+
+COMMENT ON TABLE player_news IS 'News items about players, one row per item and linked player (--mode player-news)';
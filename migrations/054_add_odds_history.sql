@@ -0,0 +1,25 @@
+-- Migration 054: Odds history
+-- Date: 2026-10-15
+-- Purpose: Capture betting line movement per game and bookmaker from an odds provider (--mode odds, daemon mode
+-- with ODDS_API_KEY), next to the closing lines the schedule file provides in games
+
+CREATE TABLE IF NOT EXISTS odds_history (
+    id BIGSERIAL PRIMARY KEY,
+    game_id UUID NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    event_id TEXT NOT NULL,              -- the provider's event ID
+    bookmaker TEXT NOT NULL,             -- e.g. 'draftkings'
+    captured_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    bookmaker_updated_at TIMESTAMPTZ,    -- when the bookmaker last moved a line on the game
+    spread_line NUMERIC(4,1),            -- expected home margin, as games.spread_line (positive = home favored)
+    home_spread_price INT,               -- American odds
+    away_spread_price INT,
+    total_line NUMERIC(4,1),
+    over_price INT,
+    under_price INT,
+    home_moneyline INT,
+    away_moneyline INT
+);
+
+CREATE INDEX IF NOT EXISTS idx_odds_history_game ON odds_history(game_id, bookmaker, captured_at DESC);
+
+COMMENT ON TABLE odds_history IS 'Betting lines per game and bookmaker, one row each time a line moved';
@@ -0,0 +1,16 @@
+-- Migration 055: Row hashes
+-- Date: 2026-10-15
+-- Purpose: Store a content hash of the source row behind each player, game and stat line so re-imports skip
+-- unchanged rows instead of rewriting them and bumping updated_at
+
+ALTER TABLE players ADD COLUMN IF NOT EXISTS row_hash TEXT;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS row_hash TEXT;
+ALTER TABLE game_stats ADD COLUMN IF NOT EXISTS row_hash TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_players_row_hash ON players(row_hash);
+CREATE INDEX IF NOT EXISTS idx_games_row_hash ON games(row_hash);
+CREATE INDEX IF NOT EXISTS idx_game_stats_row_hash ON game_stats(row_hash);
+
+COMMENT ON COLUMN players.row_hash IS 'SHA-256 of the roster row and merge settings last written; unchanged rows are skipped';
+COMMENT ON COLUMN games.row_hash IS 'SHA-256 of the schedule row and merge settings last written; unchanged rows are skipped';
+COMMENT ON COLUMN game_stats.row_hash IS 'SHA-256 of the stat row last written; unchanged rows are skipped';
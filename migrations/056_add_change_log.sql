@@ -0,0 +1,48 @@
+-- Migration 056: Change log
+-- Date: 2026-10-15
+-- Purpose: Record old and new values of every field an update changes on players, games and game_stats
+-- (stat corrections, roster moves, rescheduled games), read with --mode changes
+
+CREATE TABLE IF NOT EXISTS change_log (
+    id BIGSERIAL PRIMARY KEY,
+    table_name TEXT NOT NULL,
+    row_id UUID NOT NULL,
+    field TEXT NOT NULL,
+    old_value JSONB,
+    new_value JSONB,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_change_log_changed_at ON change_log(changed_at);
+CREATE INDEX IF NOT EXISTS idx_change_log_row ON change_log(table_name, row_id, changed_at);
+
+-- One change_log row per changed column; trigger arguments name columns to ignore
+CREATE OR REPLACE FUNCTION record_changes() RETURNS trigger AS $$
+BEGIN
+    INSERT INTO change_log (table_name, row_id, field, old_value, new_value)
+    SELECT TG_TABLE_NAME, NEW.id, n.key, NULLIF(o.value, 'null'::jsonb), NULLIF(n.value, 'null'::jsonb)
+    FROM jsonb_each(to_jsonb(NEW)) n
+    JOIN jsonb_each(to_jsonb(OLD)) o ON o.key = n.key
+    WHERE n.value IS DISTINCT FROM o.value
+      AND NOT n.key = ANY(TG_ARGV);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Bookkeeping columns, and the live game clock, which moves every poll
+DROP TRIGGER IF EXISTS players_change_log ON players;
+CREATE TRIGGER players_change_log
+    AFTER UPDATE ON players
+    FOR EACH ROW EXECUTE FUNCTION record_changes('updated_at', 'row_hash', 'provenance');
+
+DROP TRIGGER IF EXISTS games_change_log ON games;
+CREATE TRIGGER games_change_log
+    AFTER UPDATE ON games
+    FOR EACH ROW EXECUTE FUNCTION record_changes('updated_at', 'row_hash', 'provenance', 'status_detail', 'current_period', 'game_clock');
+
+DROP TRIGGER IF EXISTS game_stats_change_log ON game_stats;
+CREATE TRIGGER game_stats_change_log
+    AFTER UPDATE ON game_stats
+    FOR EACH ROW EXECUTE FUNCTION record_changes('updated_at', 'row_hash');
+
+COMMENT ON TABLE change_log IS 'Field-level changes to players, games and game_stats, one row per changed column';
@@ -0,0 +1,79 @@
+-- Migration 057: Fantasy leagues
+-- Date: 2026-10-15
+-- Purpose: Store fantasy platform leagues (Sleeper) with their rosters and weekly matchups, linked to players
+-- through the platform's player ID in player_external_ids
+
+CREATE TABLE IF NOT EXISTS fantasy_leagues (
+    platform TEXT NOT NULL,              -- 'sleeper'
+    league_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    season INT NOT NULL,
+    status TEXT,                         -- e.g. 'in_season', 'complete'
+    total_rosters INT,
+    scoring_settings JSONB,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (platform, league_id)
+);
+
+CREATE TABLE IF NOT EXISTS fantasy_rosters (
+    platform TEXT NOT NULL,
+    league_id TEXT NOT NULL,
+    roster_id INT NOT NULL,
+    owner_id TEXT,
+    owner_name TEXT,
+    team_name TEXT,
+    wins INT,
+    losses INT,
+    ties INT,
+    points_for NUMERIC(8,2),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (platform, league_id, roster_id),
+    FOREIGN KEY (platform, league_id) REFERENCES fantasy_leagues(platform, league_id) ON DELETE CASCADE
+);
+
+-- Current roster, replaced on every import
+CREATE TABLE IF NOT EXISTS fantasy_roster_players (
+    platform TEXT NOT NULL,
+    league_id TEXT NOT NULL,
+    roster_id INT NOT NULL,
+    platform_player_id TEXT NOT NULL,
+    player_id UUID REFERENCES players(id) ON DELETE SET NULL,
+    team_id UUID REFERENCES teams(id) ON DELETE SET NULL,   -- team defenses
+    slot TEXT NOT NULL,                                     -- 'starter', 'bench', 'reserve' or 'taxi'
+    PRIMARY KEY (platform, league_id, roster_id, platform_player_id),
+    FOREIGN KEY (platform, league_id, roster_id) REFERENCES fantasy_rosters(platform, league_id, roster_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_fantasy_roster_players_player ON fantasy_roster_players(player_id);
+
+CREATE TABLE IF NOT EXISTS fantasy_matchups (
+    platform TEXT NOT NULL,
+    league_id TEXT NOT NULL,
+    season INT NOT NULL,
+    week INT NOT NULL,
+    roster_id INT NOT NULL,
+    matchup_id INT,                      -- rosters sharing it play each other; NULL on a bye
+    points NUMERIC(8,2),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (platform, league_id, week, roster_id),
+    FOREIGN KEY (platform, league_id) REFERENCES fantasy_leagues(platform, league_id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS fantasy_matchup_players (
+    platform TEXT NOT NULL,
+    league_id TEXT NOT NULL,
+    week INT NOT NULL,
+    roster_id INT NOT NULL,
+    platform_player_id TEXT NOT NULL,
+    player_id UUID REFERENCES players(id) ON DELETE SET NULL,
+    team_id UUID REFERENCES teams(id) ON DELETE SET NULL,
+    starter BOOLEAN NOT NULL,
+    points NUMERIC(8,2),
+    PRIMARY KEY (platform, league_id, week, roster_id, platform_player_id),
+    FOREIGN KEY (platform, league_id, week, roster_id) REFERENCES fantasy_matchups(platform, league_id, week, roster_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_fantasy_matchup_players_player ON fantasy_matchup_players(player_id);
+
+COMMENT ON TABLE fantasy_leagues IS 'Fantasy platform leagues imported with --mode sleeper';
+COMMENT ON TABLE fantasy_roster_players IS 'Current fantasy rosters, linked to players by the platform''s player ID';
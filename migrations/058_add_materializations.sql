@@ -0,0 +1,14 @@
+-- Migration 058: Materialization state
+-- Date: 2026-10-15
+-- Purpose: Record the freshness key of each derived table's season at its last refresh, so updates only
+-- recompute derived tables whose inputs changed
+
+CREATE TABLE IF NOT EXISTS materializations (
+    name VARCHAR(50) NOT NULL,             -- derived table, e.g. 'player_splits', 'team_standings'
+    season INTEGER NOT NULL,
+    freshness_key VARCHAR(64) NOT NULL,    -- SHA-256 of the inputs' row counts and newest xmin, plus settings
+    refreshed_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (name, season)
+);
+
+COMMENT ON TABLE materializations IS 'Inputs state behind the last refresh of each derived table per season';
@@ -0,0 +1,18 @@
+-- Migration 059: API keys for serve mode
+-- Date: 2026-10-15
+-- Purpose: Serve mode answers only requests carrying a key listed here; keys are stored as SHA-256 hashes and each has its own rate limit
+
+CREATE TABLE IF NOT EXISTS api_keys (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE CHECK (key_hash ~ '^[0-9a-f]{64}$'),
+    rate_limit_per_minute INTEGER NOT NULL DEFAULT 60 CHECK (rate_limit_per_minute > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    revoked_at TIMESTAMPTZ
+);
+
+COMMENT ON TABLE api_keys IS 'Keys serve mode accepts (api_keys.rs); the keys themselves are never stored';
+COMMENT ON COLUMN api_keys.name IS 'Who or what the key was issued to';
+COMMENT ON COLUMN api_keys.key_hash IS 'Lowercase hex SHA-256 of the key';
+COMMENT ON COLUMN api_keys.rate_limit_per_minute IS 'Requests the key may make per minute, in bursts of up to this many';
+COMMENT ON COLUMN api_keys.revoked_at IS 'Set by --mode api-keys --action revoke; serve mode notices within a minute';
@@ -0,0 +1,21 @@
+-- View: current_rosters
+-- Requires: migration 002 (players.years_pro)
+-- Purpose: Players currently on a team (active, reserve lists and practice squad; released and retired players excluded)
+
+CREATE VIEW current_rosters AS
+SELECT t.abbreviation AS team,
+       t.name AS team_name,
+       p.id AS player_id,
+       p.nfl_id AS gsis_id,
+       p.name AS player_name,
+       p.position,
+       p.jersey_number,
+       p.status,
+       p.height_inches,
+       p.weight_pounds,
+       date_part('year', age(p.birth_date))::int AS age,
+       p.college,
+       p.years_pro
+FROM players p
+JOIN teams t ON t.id = p.team_id
+WHERE COALESCE(p.status, 'active') NOT IN ('CUT', 'RET', 'UFA', 'inactive', 'retired');
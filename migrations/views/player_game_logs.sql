@@ -0,0 +1,48 @@
+-- View: player_game_logs
+-- Requires: migration 043 (game_stats.team_id)
+-- Purpose: One row per player per game with team, opponent, result and the common box score columns
+
+CREATE VIEW player_game_logs AS
+SELECT g.player_id,
+       p.name AS player_name,
+       p.position,
+       g.season,
+       g.season_type,
+       g.week,
+       gm.game_date,
+       t.abbreviation AS team,
+       opp.abbreviation AS opponent,
+       CASE WHEN gm.id IS NULL THEN NULL WHEN gm.home_team_id = g.team_id THEN 'home' ELSE 'away' END AS home_away,
+       CASE
+           WHEN gm.home_score IS NULL OR gm.away_score IS NULL THEN NULL
+           WHEN gm.home_score = gm.away_score THEN 'T'
+           WHEN (gm.home_score > gm.away_score) = (gm.home_team_id = g.team_id) THEN 'W'
+           ELSE 'L'
+       END AS result,
+       g.completions,
+       g.attempts,
+       g.passing_yards,
+       g.passing_tds,
+       g.interceptions,
+       g.carries,
+       g.rushing_yards,
+       g.rushing_tds,
+       g.targets,
+       g.receptions,
+       g.receiving_yards,
+       g.receiving_tds,
+       g.fumbles_lost,
+       g.tackles,
+       g.def_sacks,
+       g.def_interceptions,
+       g.field_goals_made,
+       g.field_goals_attempted,
+       fp.points AS ppr_points
+FROM game_stats g
+JOIN players p ON p.id = g.player_id
+LEFT JOIN teams t ON t.id = g.team_id
+LEFT JOIN games gm ON gm.season = g.season AND gm.week = g.week AND gm.season_type = g.season_type
+     AND g.team_id IN (gm.home_team_id, gm.away_team_id)
+LEFT JOIN teams opp ON opp.id = CASE WHEN gm.home_team_id = g.team_id THEN gm.away_team_id ELSE gm.home_team_id END
+LEFT JOIN fantasy_points fp ON fp.player_id = g.player_id AND fp.season = g.season AND fp.week = g.week
+     AND fp.season_type = g.season_type AND fp.scoring = 'ppr';
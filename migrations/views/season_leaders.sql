@@ -0,0 +1,31 @@
+-- View: season_leaders
+-- Requires: migration 039 (season_stats)
+-- Purpose: Regular-season totals with each player's rank in the main categories (1 = league leader)
+
+CREATE VIEW season_leaders AS
+SELECT s.season,
+       s.player_id,
+       p.name AS player_name,
+       p.position,
+       s.games,
+       s.passing_yards,
+       s.passing_tds,
+       s.rushing_yards,
+       s.rushing_tds,
+       s.receptions,
+       s.receiving_yards,
+       s.receiving_tds,
+       s.passing_tds + s.rushing_tds + s.receiving_tds AS total_tds,
+       s.tackles,
+       s.def_sacks,
+       s.def_interceptions,
+       RANK() OVER (PARTITION BY s.season ORDER BY s.passing_yards DESC) AS passing_yards_rank,
+       RANK() OVER (PARTITION BY s.season ORDER BY s.rushing_yards DESC) AS rushing_yards_rank,
+       RANK() OVER (PARTITION BY s.season ORDER BY s.receiving_yards DESC) AS receiving_yards_rank,
+       RANK() OVER (PARTITION BY s.season ORDER BY s.receptions DESC) AS receptions_rank,
+       RANK() OVER (PARTITION BY s.season ORDER BY s.passing_tds + s.rushing_tds + s.receiving_tds DESC) AS total_tds_rank,
+       RANK() OVER (PARTITION BY s.season ORDER BY s.def_sacks DESC) AS sacks_rank,
+       RANK() OVER (PARTITION BY s.season ORDER BY s.tackles DESC) AS tackles_rank
+FROM season_stats s
+JOIN players p ON p.id = s.player_id
+WHERE s.season_type = 'REG';
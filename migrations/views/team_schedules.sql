@@ -0,0 +1,33 @@
+-- View: team_schedules
+-- Requires: migration 021 (closing lines and roof on games)
+-- Purpose: Each game twice, once from each team's side, with opponent, score, result and the line from that team's perspective
+
+CREATE VIEW team_schedules AS
+SELECT gm.id AS game_id,
+       gm.season,
+       gm.season_type,
+       gm.week,
+       gm.game_date,
+       t.abbreviation AS team,
+       opp.abbreviation AS opponent,
+       side.home_away,
+       side.team_score,
+       side.opponent_score,
+       CASE
+           WHEN side.team_score IS NULL OR side.opponent_score IS NULL THEN NULL
+           WHEN side.team_score > side.opponent_score THEN 'W'
+           WHEN side.team_score < side.opponent_score THEN 'L'
+           ELSE 'T'
+       END AS result,
+       gm.status,
+       side.spread_line,
+       gm.total_line,
+       gm.roof,
+       gm.venue_name
+FROM games gm
+CROSS JOIN LATERAL (
+    VALUES ('home', gm.home_team_id, gm.away_team_id, gm.home_score, gm.away_score, gm.spread_line),
+           ('away', gm.away_team_id, gm.home_team_id, gm.away_score, gm.home_score, -gm.spread_line)
+) AS side (home_away, team_id, opponent_id, team_score, opponent_score, spread_line)
+JOIN teams t ON t.id = side.team_id
+JOIN teams opp ON opp.id = side.opponent_id;
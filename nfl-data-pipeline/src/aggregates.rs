@@ -0,0 +1,129 @@
+use anyhow::{Context, Result};
+use postgres::types::ToSql;
+use postgres::{Client, Transaction};
+
+use crate::transformer::{DEFENSE_KICKING_COLUMNS, OFFENSE_COLUMNS};
+
+/// Stats averaged per game played, stored as `<stat>_per_game`
+pub const PER_GAME_COLUMNS: [&str; 6] = [
+    "passing_yards",
+    "rushing_yards",
+    "receiving_yards",
+    "receptions",
+    "targets",
+    "tackles",
+];
+
+/// game_stats columns summed into `season_stats` and `career_stats`. `fg_long`
+/// is a maximum rather than a sum.
+fn sum_columns() -> impl Iterator<Item = &'static str> {
+    OFFENSE_COLUMNS
+        .into_iter()
+        .chain(DEFENSE_KICKING_COLUMNS)
+        .filter(|column| *column != "fg_long")
+        .chain(["def_sacks"])
+}
+
+/// Stat columns of both tables, in insert order
+fn stat_columns() -> Vec<String> {
+    let mut columns: Vec<String> = sum_columns().map(str::to_string).collect();
+    columns.push("fg_long".to_string());
+    columns.extend(PER_GAME_COLUMNS.iter().map(|column| format!("{}_per_game", column)));
+    columns
+}
+
+/// Aggregates matching `stat_columns` over the grouped game_stats rows
+fn stat_select() -> Vec<String> {
+    let mut select: Vec<String> = sum_columns().map(|column| format!("COALESCE(SUM({}), 0)", column)).collect();
+    select.push("MAX(fg_long)".to_string());
+    select.extend(
+        PER_GAME_COLUMNS
+            .iter()
+            .map(|column| format!("ROUND(COALESCE(SUM({}), 0)::numeric / COUNT(*), 2)", column)),
+    );
+    select
+}
+
+/// Rebuild a season's `season_stats`, then `career_stats` for every player who
+/// appeared in it. Returns the number of (season, career) rows written.
+pub fn refresh_aggregates(client: &mut Client, season: i32) -> Result<(u64, u64)> {
+    let mut tx = client.transaction()?;
+
+    tx.execute("DELETE FROM season_stats WHERE season = $1", &[&season])?;
+    let seasons = insert_season_stats(&mut tx, "WHERE season = $1", &[&season])?;
+
+    let players = "player_id IN (SELECT player_id FROM game_stats WHERE season = $1)";
+    tx.execute(&format!("DELETE FROM career_stats WHERE {}", players), &[&season])?;
+    let careers = insert_career_stats(&mut tx, &format!("WHERE {}", players), &[&season])?;
+
+    tx.commit()?;
+    Ok((seasons, careers))
+}
+
+/// `--mode aggregate`: rebuild both tables for every season in game_stats
+pub fn rebuild_aggregates(client: &mut Client) -> Result<(u64, u64)> {
+    let mut tx = client.transaction()?;
+
+    tx.execute("DELETE FROM season_stats", &[])?;
+    tx.execute("DELETE FROM career_stats", &[])?;
+    let seasons = insert_season_stats(&mut tx, "", &[])?;
+    let careers = insert_career_stats(&mut tx, "", &[])?;
+
+    tx.commit()?;
+    Ok((seasons, careers))
+}
+
+fn insert_season_stats(tx: &mut Transaction, filter: &str, params: &[&(dyn ToSql + Sync)]) -> Result<u64> {
+    tx.execute(
+        &format!(
+            "INSERT INTO season_stats (player_id, season, season_type, games, {}, updated_at)
+             SELECT player_id, season, season_type, COUNT(*), {}, NOW()
+             FROM game_stats
+             {}
+             GROUP BY player_id, season, season_type",
+            stat_columns().join(", "),
+            stat_select().join(", "),
+            filter
+        ),
+        params,
+    )
+    .context("Failed to refresh season_stats")
+}
+
+fn insert_career_stats(tx: &mut Transaction, filter: &str, params: &[&(dyn ToSql + Sync)]) -> Result<u64> {
+    tx.execute(
+        &format!(
+            "INSERT INTO career_stats (player_id, season_type, first_season, last_season, seasons, games, {}, updated_at)
+             SELECT player_id, season_type, MIN(season), MAX(season), COUNT(DISTINCT season), COUNT(*), {}, NOW()
+             FROM game_stats
+             {}
+             GROUP BY player_id, season_type",
+            stat_columns().join(", "),
+            stat_select().join(", "),
+            filter
+        ),
+        params,
+    )
+    .context("Failed to refresh career_stats")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_columns_and_aggregates_line_up() {
+        let columns = stat_columns();
+        let select = stat_select();
+        assert_eq!(columns.len(), select.len());
+
+        let position = |name: &str| columns.iter().position(|c| c == name).unwrap();
+        assert_eq!(select[position("def_sacks")], "COALESCE(SUM(def_sacks), 0)");
+        assert_eq!(select[position("fg_long")], "MAX(fg_long)");
+        assert_eq!(
+            select[position("receptions_per_game")],
+            "ROUND(COALESCE(SUM(receptions), 0)::numeric / COUNT(*), 2)"
+        );
+        assert_eq!(columns.iter().filter(|c| *c == "fg_long").count(), 1);
+    }
+}
@@ -0,0 +1,840 @@
+use anyhow::{Context, Result};
+use async_graphql::dynamic::Schema;
+use async_graphql_axum::GraphQL;
+use axum::extract::ws::{rejection::WebSocketUpgradeRejection, WebSocketUpgrade};
+use axum::extract::{OriginalUri, RawQuery, State};
+use axum::http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode};
+use axum::middleware;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use log::{info, warn};
+use postgres::types::ToSql;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tower::limit::ConcurrencyLimitLayer;
+use tower_http::cors::{Any, CorsLayer};
+use tower_http::limit::RequestBodyLimitLayer;
+use tower_http::timeout::TimeoutLayer;
+
+use crate::api_keys::{self, ApiKeys};
+use crate::database::Pool;
+use crate::export::INTERNAL_COLUMNS;
+use crate::graphql;
+use crate::websocket::{self, Hub};
+
+/// Where `--mode serve` listens unless `--addr` says otherwise
+pub const DEFAULT_ADDR: &str = "127.0.0.1:8080";
+/// Page size when `limit` isn't given, and the most one request can ask for
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 500;
+/// Search matches returned per kind
+const SEARCH_LIMIT: i64 = 20;
+/// A request not answered in this long gets a 408
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Largest request body accepted
+const MAX_BODY: usize = 1024 * 1024;
+/// Requests handled at once; more wait for a slot
+const MAX_CONCURRENT: usize = 256;
+
+/// Decoded query string pairs, in order
+#[derive(Debug, Default, Clone)]
+pub struct Params(Vec<(String, String)>);
+
+impl Params {
+    pub fn parse(query: &str) -> Self {
+        Params(form_urlencoded::parse(query.as_bytes()).into_owned().collect())
+    }
+
+    /// The last value of a query parameter, blank ones ignored
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.0.iter().rev().find(|(key, value)| key == name && !value.is_empty()).map(|(_, value)| value.as_str())
+    }
+
+    fn int_param(&self, name: &str) -> Result<Option<i64>, ApiError> {
+        self.param(name)
+            .map(|value| value.parse().map_err(|_| ApiError::bad_request(format!("{} must be a whole number, got {:?}", name, value))))
+            .transpose()
+    }
+}
+
+impl FromIterator<(String, String)> for Params {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(pairs: I) -> Self {
+        Params(pairs.into_iter().collect())
+    }
+}
+
+/// A request the API refuses, answered as `{"error": message}`
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        ApiError { status, message: message.into() }
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        ApiError::new(StatusCode::BAD_REQUEST, message)
+    }
+
+    fn not_found() -> Self {
+        ApiError::new(StatusCode::NOT_FOUND, "Not found")
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        json((self.status, serde_json::json!({ "error": self.message }).to_string()))
+    }
+}
+
+/// A JSON body already serialized (Postgres builds most of them)
+pub fn json(body: impl IntoResponse) -> Response {
+    ([(header::CONTENT_TYPE, "application/json")], body).into_response()
+}
+
+/// `limit` and `offset`, bounded
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Page {
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl Page {
+    pub fn from_params(params: &Params) -> Result<Self, ApiError> {
+        let limit = params.int_param("limit")?.unwrap_or(DEFAULT_LIMIT);
+        let offset = params.int_param("offset")?.unwrap_or(0);
+        if !(1..=MAX_LIMIT).contains(&limit) {
+            return Err(ApiError::bad_request(format!("limit must be between 1 and {}", MAX_LIMIT)));
+        }
+        if offset < 0 {
+            return Err(ApiError::bad_request("offset can't be negative"));
+        }
+        Ok(Page { limit, offset })
+    }
+}
+
+/// A `SELECT` of one JSON object per row: the table's columns minus merge
+/// bookkeeping, plus team and player references resolved to their public IDs
+pub(crate) struct Select {
+    from: String,
+    /// Resolved fields merged into each row, as `jsonb_build_object` arguments
+    resolved: &'static str,
+    /// The columns rows are ordered by, unique together, with their SQL types
+    /// for reading them back from a cursor
+    keys: &'static [(&'static str, &'static str)],
+    pub order: String,
+    conditions: Vec<String>,
+    params: Vec<Box<dyn ToSql + Send + Sync>>,
+    /// Only these fields of each row, when set
+    fields: Option<Vec<String>>,
+    /// Whether the page starts after a cursor rather than at an offset
+    cursor: bool,
+}
+
+impl Select {
+    fn new(from: &str, resolved: &'static str, keys: &'static [(&'static str, &'static str)]) -> Self {
+        let order = keys.iter().map(|(key, _)| *key).collect::<Vec<_>>().join(", ");
+        Select {
+            from: from.to_string(),
+            resolved,
+            keys,
+            order,
+            conditions: Vec::new(),
+            params: Vec::new(),
+            fields: None,
+            cursor: false,
+        }
+    }
+
+    /// Add a `JOIN` clause after the builder's own
+    pub fn join(&mut self, join: &str) {
+        self.from.push(' ');
+        self.from.push_str(join);
+    }
+
+    /// Bind `value`, returning its placeholder
+    fn bind(&mut self, value: impl ToSql + Send + Sync + 'static) -> String {
+        self.params.push(Box::new(value));
+        format!("${}", self.params.len())
+    }
+
+    /// Add `condition`, with `$` standing for `value`'s placeholder
+    pub fn filter(&mut self, condition: &str, value: impl ToSql + Send + Sync + 'static) {
+        let placeholder = self.bind(value);
+        self.conditions.push(condition.replace('$', &placeholder));
+    }
+
+    /// Rows after the one `cursor` was taken from, in key order
+    fn after(&mut self, cursor: Vec<String>) {
+        let values: Vec<String> =
+            cursor.into_iter().zip(self.keys).map(|(value, (_, ty))| format!("{}::text::{}", self.bind(value), ty)).collect();
+        let keys: Vec<&str> = self.keys.iter().map(|(key, _)| *key).collect();
+        self.conditions.push(format!("({}) > ({})", keys.join(", "), values.join(", ")));
+        self.cursor = true;
+    }
+
+    fn row(&mut self) -> String {
+        let mut row = INTERNAL_COLUMNS.iter().fold("to_jsonb(x)".to_string(), |row, column| format!("{} - '{}'", row, column));
+        if !self.resolved.is_empty() {
+            row = format!("({}) || jsonb_build_object({})", row, self.resolved);
+        }
+        if let Some(fields) = self.fields.take() {
+            let fields = self.bind(fields);
+            row = format!("(SELECT COALESCE(jsonb_object_agg(f.key, f.value), '{{}}') FROM jsonb_each({}) f WHERE f.key = ANY({}))", row, fields);
+        }
+        row
+    }
+
+    /// The last row's keys as text, for the next page's cursor
+    fn cursor_sql(&self) -> String {
+        let keys: Vec<String> = self.keys.iter().map(|(key, _)| format!("({})::text", key)).collect();
+        format!("jsonb_build_array({})", keys.join(", "))
+    }
+
+    /// `FROM`, joins and conditions
+    fn source(&self) -> String {
+        match self.conditions.is_empty() {
+            true => self.from.clone(),
+            false => format!("{} WHERE {}", self.from, self.conditions.join(" AND ")),
+        }
+    }
+
+    /// A page of rows as a JSON array, with how many there were and the last
+    /// row's cursor
+    fn page_sql(&mut self, page: Page) -> String {
+        let row = self.row();
+        let limit = self.bind(page.limit);
+        let offset = self.bind(page.offset);
+        format!(
+            "SELECT COALESCE(json_agg(r.row), '[]')::text, COUNT(*), ((array_agg(r.cursor))[COUNT(*)::int])::text FROM \
+             (SELECT {} AS row, {} AS cursor FROM {} ORDER BY {} LIMIT {} OFFSET {}) r",
+            row,
+            self.cursor_sql(),
+            self.source(),
+            self.order,
+            limit,
+            offset
+        )
+    }
+
+    /// Up to `limit` rows for each of `parents`, matched on `parent`, as
+    /// `(parent, JSON array)` rows
+    fn grouped_sql(&mut self, parent: &str, parents: Vec<String>, limit: i64) -> String {
+        self.filter(&format!("{} = ANY($)", parent), parents);
+        let limit = self.bind(limit);
+        let row = self.row();
+        format!(
+            "SELECT r.parent, json_agg(r.row ORDER BY r.n)::text FROM \
+             (SELECT {} AS parent, {} AS row, row_number() OVER (PARTITION BY {} ORDER BY {}) AS n FROM {}) r \
+             WHERE r.n <= {} GROUP BY r.parent",
+            parent,
+            row,
+            parent,
+            self.order,
+            self.source(),
+            limit
+        )
+    }
+
+    /// The first row as a JSON object
+    fn one_sql(&mut self) -> String {
+        let row = self.row();
+        format!("SELECT ({})::text FROM {} ORDER BY {} LIMIT 1", row, self.source(), self.order)
+    }
+
+    /// The latest `updated_at` among the matching rows, how many there are, and
+    /// a digest of their IDs and row versions (`xmin`, new on every write, even
+    /// one that leaves `updated_at` alone). Run before the rows' query, which
+    /// binds more parameters.
+    fn version_sql(&self) -> String {
+        format!(
+            "SELECT max(x.updated_at)::text, COUNT(*), md5(string_agg(x.id::text || ':' || x.xmin::text, ',' ORDER BY x.id)) FROM {}",
+            self.source()
+        )
+    }
+
+    fn params(&self) -> Vec<&(dyn ToSql + Sync)> {
+        self.params.iter().map(|param| param.as_ref() as &(dyn ToSql + Sync)).collect()
+    }
+}
+
+pub(crate) fn players() -> Select {
+    Select::new("players x LEFT JOIN teams t ON t.id = x.team_id", "'team', t.abbreviation", &[("x.name", "text"), ("x.id", "uuid")])
+}
+
+pub(crate) fn teams() -> Select {
+    Select::new("teams x", "", &[("x.abbreviation", "text"), ("x.id", "uuid")])
+}
+
+pub(crate) fn games() -> Select {
+    Select::new(
+        "games x JOIN teams h ON h.id = x.home_team_id JOIN teams a ON a.id = x.away_team_id",
+        "'home_team', h.abbreviation, 'away_team', a.abbreviation",
+        &[("x.season", "int"), ("x.week", "int"), ("x.game_date", "timestamptz"), ("x.id", "uuid")],
+    )
+}
+
+pub(crate) fn stats() -> Select {
+    Select::new(
+        "game_stats x LEFT JOIN players p ON p.id = x.player_id LEFT JOIN teams t ON t.id = x.team_id",
+        "'player_nfl_id', p.nfl_id, 'player_name', p.name, 'position', p.position, 'team', t.abbreviation",
+        &[("x.season", "int"), ("x.week", "int"), ("COALESCE(p.name, '')", "text"), ("x.id", "uuid")],
+    )
+}
+
+/// Applies `season`, `week` and `team` (for tables with `team_id`) from the query string
+fn season_week_team(select: &mut Select, params: &Params, team: &str) -> Result<(), ApiError> {
+    if let Some(season) = params.int_param("season")? {
+        select.filter("x.season = $", season as i32);
+    }
+    if let Some(week) = params.int_param("week")? {
+        select.filter("x.week = $", week as i32);
+    }
+    if let Some(abbr) = params.param("team") {
+        select.filter(team, abbr.to_uppercase());
+    }
+    Ok(())
+}
+
+/// Applies `season` and `week` to a list without them as "has a row in
+/// `exists` that week", e.g. players who recorded stats
+fn active_in(select: &mut Select, params: &Params, exists: &str) -> Result<(), ApiError> {
+    let mut conditions = vec![exists.to_string()];
+    if let Some(season) = params.int_param("season")? {
+        conditions.push(format!("s.season = {}", select.bind(season as i32)));
+    }
+    if let Some(week) = params.int_param("week")? {
+        conditions.push(format!("s.week = {}", select.bind(week as i32)));
+    }
+    if conditions.len() > 1 {
+        select.conditions.push(format!("EXISTS (SELECT 1 FROM {})", conditions.join(" AND ")));
+    }
+    Ok(())
+}
+
+/// A filter the endpoint has no column for is a 400, not silently ignored
+fn unsupported(params: &Params, names: &[&str]) -> Result<(), ApiError> {
+    match names.iter().find(|name| params.param(name).is_some()) {
+        Some(name) => Err(ApiError::bad_request(format!("{} isn't a filter on this endpoint", name))),
+        None => Ok(()),
+    }
+}
+
+/// `fields`: the comma-separated row fields to return, unknown names ignored
+fn fields(select: &mut Select, params: &Params) {
+    if let Some(fields) = params.param("fields") {
+        select.fields = Some(fields.split(',').map(str::trim).filter(|field| !field.is_empty()).map(str::to_string).collect());
+    }
+}
+
+/// A list route: field selection, then the page at `offset` or after `cursor`
+fn list_route(mut select: Select, params: &Params) -> Result<Route, ApiError> {
+    fields(&mut select, params);
+    let page = Page::from_params(params)?;
+    if let Some(cursor) = params.param("cursor") {
+        if page.offset > 0 {
+            return Err(ApiError::bad_request("Use cursor or offset, not both"));
+        }
+        let cursor = decode_cursor(cursor).filter(|cursor| cursor.len() == select.keys.len());
+        select.after(cursor.ok_or_else(|| ApiError::bad_request("cursor isn't one this endpoint returned"))?);
+    }
+    Ok(Route::List(select, page))
+}
+
+fn one_route(mut select: Select, params: &Params) -> Route {
+    fields(&mut select, params);
+    Route::One(select)
+}
+
+/// A page's last row keys as an opaque `next_cursor`
+fn encode_cursor(keys: &str) -> String {
+    keys.bytes().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_cursor(cursor: &str) -> Option<Vec<String>> {
+    let bytes = (0..cursor.len())
+        .step_by(2)
+        .map(|i| cursor.get(i..i + 2).and_then(|hex| u8::from_str_radix(hex, 16).ok()))
+        .collect::<Option<Vec<u8>>>()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// What a request asks for: a page of rows, one row, or a search
+pub(crate) enum Route {
+    List(Select, Page),
+    One(Select),
+    Search(String),
+}
+
+pub(crate) fn route(path: &str, params: &Params) -> Result<Route, ApiError> {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').filter(|s| !s.is_empty()).collect();
+    let route = match segments.as_slice() {
+        ["players"] => {
+            let mut select = players();
+            if let Some(team) = params.param("team") {
+                select.filter("t.abbreviation = $", team.to_uppercase());
+            }
+            if let Some(position) = params.param("position") {
+                select.filter("x.position = $", position.to_uppercase());
+            }
+            if let Some(status) = params.param("status") {
+                select.filter("x.status = $", status.to_string());
+            }
+            active_in(&mut select, params, "game_stats s WHERE s.player_id = x.id")?;
+            list_route(select, params)?
+        }
+        ["players", id] => {
+            let mut select = players();
+            select.filter("(x.nfl_id = $ OR x.id::text = $)", id.to_string());
+            one_route(select, params)
+        }
+        ["players", id, "stats"] => {
+            let mut select = stats();
+            select.filter("(p.nfl_id = $ OR p.id::text = $)", id.to_string());
+            season_week_team(&mut select, params, "t.abbreviation = $")?;
+            if let Some(position) = params.param("position") {
+                select.filter("p.position = $", position.to_uppercase());
+            }
+            list_route(select, params)?
+        }
+        ["teams"] => {
+            unsupported(params, &["position"])?;
+            let mut select = teams();
+            if let Some(conference) = params.param("conference") {
+                select.filter("x.conference = $", conference.to_uppercase());
+            }
+            if let Some(division) = params.param("division") {
+                select.filter("x.division = $", division.to_string());
+            }
+            if let Some(team) = params.param("team") {
+                select.filter("x.abbreviation = $", team.to_uppercase());
+            }
+            active_in(&mut select, params, "games s WHERE x.id IN (s.home_team_id, s.away_team_id)")?;
+            list_route(select, params)?
+        }
+        ["teams", abbr] => {
+            let mut select = teams();
+            select.filter("x.abbreviation = $", abbr.to_uppercase());
+            one_route(select, params)
+        }
+        ["games"] => {
+            unsupported(params, &["position"])?;
+            let mut select = games();
+            season_week_team(&mut select, params, "(h.abbreviation = $ OR a.abbreviation = $)")?;
+            if let Some(status) = params.param("status") {
+                select.filter("x.status = $", status.to_string());
+            }
+            list_route(select, params)?
+        }
+        ["games", id] => {
+            let mut select = games();
+            select.filter("(x.nfl_game_id = $ OR x.id::text = $)", id.to_string());
+            one_route(select, params)
+        }
+        ["stats"] => {
+            let mut select = stats();
+            season_week_team(&mut select, params, "t.abbreviation = $")?;
+            if let Some(position) = params.param("position") {
+                select.filter("p.position = $", position.to_uppercase());
+            }
+            if let Some(player) = params.param("player") {
+                select.filter("p.nfl_id = $", player.to_string());
+            }
+            list_route(select, params)?
+        }
+        ["search"] => match params.param("q").map(str::trim) {
+            Some(q) if q.chars().count() >= 2 => Route::Search(q.to_string()),
+            _ => return Err(ApiError::bad_request("q must be at least 2 characters")),
+        },
+        _ => return Err(ApiError::not_found()),
+    };
+    Ok(route)
+}
+
+/// Page of rows as `{"data": [...], "limit", "offset", "next_offset", "next_cursor"}`
+fn list(client: &mut postgres::Client, mut select: Select, page: Page) -> Result<String> {
+    let sql = select.page_sql(page);
+    let row = client.query_one(&sql, &select.params())?;
+    let count: i64 = row.get(1);
+    let full = count == page.limit;
+    let next_offset = (full && !select.cursor).then_some(page.offset + page.limit);
+    let next_cursor = row.get::<_, Option<String>>(2).filter(|_| full).map(|keys| encode_cursor(&keys));
+    Ok(format!(
+        r#"{{"data":{},"limit":{},"offset":{},"next_offset":{},"next_cursor":{}}}"#,
+        row.get::<_, String>(0),
+        page.limit,
+        page.offset,
+        serde_json::json!(next_offset),
+        serde_json::json!(next_cursor)
+    ))
+}
+
+/// A page of rows as JSON objects
+pub(crate) fn rows(client: &mut postgres::Client, mut select: Select, page: Page) -> Result<Vec<serde_json::Value>> {
+    let sql = select.page_sql(page);
+    let json: String = client.query_one(&sql, &select.params())?.get(0);
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// The first row as a JSON object, if there is one
+pub(crate) fn one(client: &mut postgres::Client, mut select: Select) -> Result<Option<serde_json::Value>> {
+    let sql = select.one_sql();
+    let row = client.query_opt(&sql, &select.params())?;
+    row.map(|row| Ok(serde_json::from_str(row.get(0))?)).transpose()
+}
+
+/// Up to `limit` rows for each of `parents`, by the `parent` expression's
+/// text; parents without rows are left out
+pub(crate) fn grouped(
+    client: &mut postgres::Client,
+    mut select: Select,
+    parent: &str,
+    parents: Vec<String>,
+    limit: i64,
+) -> Result<HashMap<String, Vec<serde_json::Value>>> {
+    let sql = select.grouped_sql(parent, parents, limit);
+    client
+        .query(&sql, &select.params())?
+        .iter()
+        .map(|row| Ok((row.get(0), serde_json::from_str(row.get(1))?)))
+        .collect()
+}
+
+/// A strong ETag for `target` (path and query) over the rows `select` matches,
+/// from their latest `updated_at`, count and row versions
+fn etag(client: &mut postgres::Client, select: &Select, target: &str) -> Result<String> {
+    let row = client.query_one(&select.version_sql(), &select.params())?;
+    let (updated, count, versions): (Option<String>, i64, Option<String>) = (row.get(0), row.get(1), row.get(2));
+    let digest = Sha256::digest(format!("{}\n{}\n{}\n{}", target, updated.unwrap_or_default(), count, versions.unwrap_or_default()));
+    Ok(format!("\"{}\"", digest[..16].iter().map(|b| format!("{:02x}", b)).collect::<String>()))
+}
+
+/// Whether an `If-None-Match` header lists `etag` (or is `*`)
+fn not_modified(if_none_match: Option<&str>, etag: &str) -> bool {
+    if_none_match.is_some_and(|tags| tags.split(',').map(|tag| tag.trim().trim_start_matches("W/")).any(|tag| tag == etag || tag == "*"))
+}
+
+/// Players by name and teams by name, city or abbreviation, closest matches first
+fn search(client: &mut postgres::Client, q: &str) -> Result<String> {
+    let mut players = players();
+    players.filter("strpos(lower(x.name), lower($)) > 0", q.to_string());
+    players.order = "strpos(lower(x.name), lower($1)), x.name, x.id".to_string();
+    let mut teams = teams();
+    teams.filter(
+        "(strpos(lower(x.name), lower($)) > 0 OR strpos(lower(x.city), lower($)) > 0 OR lower(x.abbreviation) = lower($))",
+        q.to_string(),
+    );
+
+    let mut found = Vec::new();
+    for mut select in [players, teams] {
+        let sql = select.page_sql(Page { limit: SEARCH_LIMIT, offset: 0 });
+        found.push(client.query_one(&sql, &select.params())?.get::<_, String>(0));
+    }
+    Ok(format!(r#"{{"players":{},"teams":{}}}"#, found[0], found[1]))
+}
+
+/// What every handler shares
+#[derive(Clone)]
+pub(crate) struct AppState {
+    pub pool: Pool,
+    pub hub: Arc<Hub>,
+    pub schema: Schema,
+    pub keys: Arc<ApiKeys>,
+}
+
+/// Run `query` on a pooled connection, off the async workers since the
+/// Postgres client blocks
+pub(crate) async fn with_client<T: Send + 'static>(
+    pool: &Pool,
+    query: impl FnOnce(&mut postgres::Client) -> Result<T> + Send + 'static,
+) -> Result<T> {
+    let pool = pool.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut client = pool.get().context("No database connection available")?;
+        query(&mut client)
+    })
+    .await?
+}
+
+/// What a REST request gets back
+enum Answer {
+    Body(String, Option<String>),
+    NotModified(String),
+    Missing,
+}
+
+/// The REST endpoints: a page of rows, one row or a search, by path. Pages and
+/// rows carry an ETag, and a matching `If-None-Match` gets a 304 without
+/// reading the rows.
+async fn rest(State(state): State<AppState>, OriginalUri(uri): OriginalUri, RawQuery(query): RawQuery, headers: HeaderMap) -> Response {
+    let params = Params::parse(query.as_deref().unwrap_or_default());
+    let route = match route(uri.path(), &params) {
+        Ok(route) => route,
+        Err(e) => return e.into_response(),
+    };
+    let target = uri.to_string();
+    let if_none_match = headers.get(header::IF_NONE_MATCH).and_then(|value| value.to_str().ok()).map(str::to_string);
+    let answered = with_client(&state.pool, move |client| {
+        let etag = match &route {
+            Route::List(select, _) | Route::One(select) => Some(etag(client, select, &target)?),
+            Route::Search(_) => None,
+        };
+        if let Some(etag) = etag.clone().filter(|etag| not_modified(if_none_match.as_deref(), etag)) {
+            return Ok(Answer::NotModified(etag));
+        }
+        let body = match route {
+            Route::List(select, page) => Some(list(client, select, page)?),
+            Route::One(select) => one(client, select)?.map(|row| row.to_string()),
+            Route::Search(q) => Some(search(client, &q)?),
+        };
+        Ok(body.map_or(Answer::Missing, |body| Answer::Body(body, etag)))
+    })
+    .await;
+    match answered {
+        Ok(Answer::Body(body, etag)) => {
+            let mut response = json(body);
+            if let Some(etag) = etag.and_then(|etag| HeaderValue::from_str(&etag).ok()) {
+                response.headers_mut().insert(header::ETAG, etag);
+            }
+            response
+        }
+        Ok(Answer::NotModified(etag)) => (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response(),
+        Ok(Answer::Missing) => ApiError::not_found().into_response(),
+        Err(e) => {
+            warn!("⚠️  GET {} failed: {:#}", uri.path(), e);
+            ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Query failed").into_response()
+        }
+    }
+}
+
+async fn health() -> Response {
+    json(r#"{"status":"ok"}"#)
+}
+
+async fn live(State(state): State<AppState>, upgrade: Result<WebSocketUpgrade, WebSocketUpgradeRejection>) -> Response {
+    match upgrade {
+        Ok(upgrade) => websocket::upgrade(upgrade, state.hub),
+        Err(_) => ApiError::new(StatusCode::UPGRADE_REQUIRED, "/live is a WebSocket; connect with a WebSocket client").into_response(),
+    }
+}
+
+async fn not_found() -> ApiError {
+    ApiError::not_found()
+}
+
+/// Every serve mode route, with request timeouts, a body size limit and a cap
+/// on requests in flight. hyper bounds request headers. All but `/health`
+/// need an API key.
+pub(crate) fn router(state: AppState) -> Router {
+    let cors = CorsLayer::new()
+        .allow_origin(Any)
+        .allow_headers([header::AUTHORIZATION, header::IF_NONE_MATCH, HeaderName::from_static("x-api-key")])
+        .expose_headers([header::ETAG, header::RETRY_AFTER]);
+    Router::new()
+        .route("/players", get(rest))
+        .route("/players/{id}", get(rest))
+        .route("/players/{id}/stats", get(rest))
+        .route("/teams", get(rest))
+        .route("/teams/{abbr}", get(rest))
+        .route("/games", get(rest))
+        .route("/games/{id}", get(rest))
+        .route("/stats", get(rest))
+        .route("/search", get(rest))
+        .route_service("/graphql", GraphQL::new(state.schema.clone()))
+        .route("/live", get(live))
+        .route_layer(middleware::from_fn_with_state(Arc::clone(&state.keys), api_keys::require_key))
+        .route("/health", get(health))
+        .fallback(not_found)
+        .layer(cors)
+        .layer(TimeoutLayer::with_status_code(StatusCode::REQUEST_TIMEOUT, REQUEST_TIMEOUT))
+        .layer(RequestBodyLimitLayer::new(MAX_BODY))
+        .layer(ConcurrencyLimitLayer::new(MAX_CONCURRENT))
+        .with_state(state)
+}
+
+/// Serve the read API on `addr` until SIGTERM or SIGINT, each query on a
+/// connection from `pool`. Live score updates announced on the database at
+/// `database_url` are pushed to `/live` clients.
+pub fn serve(pool: Pool, database_url: &str, addr: &str) -> Result<()> {
+    let schema = graphql::schema(&pool)?;
+    let keys = Arc::new(ApiKeys::new(pool.clone()));
+    if keys.active()? == 0 {
+        warn!("⚠️  No API keys in api_keys; every request but /health will get a 401 (see --mode api-keys)");
+    }
+    let hub = Arc::new(Hub::default());
+    let relayed = Arc::clone(&hub);
+    let database_url = database_url.to_string();
+    thread::spawn(move || websocket::relay(database_url, relayed));
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(async {
+        let listener = tokio::net::TcpListener::bind(addr).await.with_context(|| format!("Failed to bind --addr {}", addr))?;
+        info!("🌐 API listening on http://{}", addr);
+        axum::serve(listener, router(AppState { pool, hub, schema, keys })).with_graceful_shutdown(shutdown_signal()).await?;
+        info!("🛑 Shutdown signal received, API stopping");
+        Ok(())
+    })
+}
+
+async fn shutdown_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+    let Ok(mut terminate) = signal(SignalKind::terminate()) else {
+        return std::future::pending().await;
+    };
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate.recv() => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use postgres_native_tls::MakeTlsConnector;
+    use r2d2_postgres::PostgresConnectionManager;
+    use tower::ServiceExt;
+
+    use crate::api_keys::ApiKey;
+
+    fn params(query: &str) -> Params {
+        Params::parse(query)
+    }
+
+    #[test]
+    fn test_params() {
+        let params = params("team=kc&week=&player=00-0033873&team=buf");
+        assert_eq!(params.param("team"), Some("buf"));
+        assert_eq!(params.param("week"), None);
+        assert_eq!(params.param("player"), Some("00-0033873"));
+    }
+
+    #[test]
+    fn test_routes() {
+        let Ok(Route::List(mut select, page)) = route("/games", &params("season=2023&team=kc&limit=10")) else {
+            panic!("expected a list");
+        };
+        assert_eq!(page, Page { limit: 10, offset: 0 });
+        assert_eq!(
+            select.version_sql(),
+            "SELECT max(x.updated_at)::text, COUNT(*), md5(string_agg(x.id::text || ':' || x.xmin::text, ',' ORDER BY x.id)) \
+             FROM games x JOIN teams h ON h.id = x.home_team_id JOIN teams a ON a.id = x.away_team_id \
+             WHERE x.season = $1 AND (h.abbreviation = $2 OR a.abbreviation = $2)"
+        );
+        assert_eq!(
+            select.page_sql(page),
+            "SELECT COALESCE(json_agg(r.row), '[]')::text, COUNT(*), ((array_agg(r.cursor))[COUNT(*)::int])::text FROM \
+             (SELECT (to_jsonb(x) - 'row_hash' - 'provenance') || jsonb_build_object('home_team', h.abbreviation, 'away_team', a.abbreviation) AS row, \
+             jsonb_build_array((x.season)::text, (x.week)::text, (x.game_date)::text, (x.id)::text) AS cursor \
+             FROM games x JOIN teams h ON h.id = x.home_team_id JOIN teams a ON a.id = x.away_team_id \
+             WHERE x.season = $1 AND (h.abbreviation = $2 OR a.abbreviation = $2) \
+             ORDER BY x.season, x.week, x.game_date, x.id LIMIT $3 OFFSET $4) r"
+        );
+        assert_eq!(select.params.len(), 4);
+
+        let cursor = encode_cursor(r#"["Patrick Mahomes", "9c5d6bc3-4683-4e4a-8378-d72e0f369bd2"]"#);
+        let Ok(Route::List(mut select, _)) = route("/players", &params(&format!("position=qb&season=2024&fields=name,%20team&cursor={}", cursor)))
+        else {
+            panic!("expected a list");
+        };
+        assert!(select.cursor);
+        assert_eq!(
+            select.source(),
+            "players x LEFT JOIN teams t ON t.id = x.team_id WHERE x.position = $1 \
+             AND EXISTS (SELECT 1 FROM game_stats s WHERE s.player_id = x.id AND s.season = $2) \
+             AND (x.name, x.id) > ($3::text::text, $4::text::uuid)"
+        );
+        assert_eq!(
+            select.row(),
+            "(SELECT COALESCE(jsonb_object_agg(f.key, f.value), '{}') \
+             FROM jsonb_each((to_jsonb(x) - 'row_hash' - 'provenance') || jsonb_build_object('team', t.abbreviation)) f WHERE f.key = ANY($5))"
+        );
+
+        let mut select = stats();
+        select.join("JOIN games g ON g.season = x.season");
+        select.filter("p.position = $", "WR".to_string());
+        assert_eq!(
+            select.grouped_sql("g.id::text", vec!["a".to_string(), "b".to_string()], 3),
+            "SELECT r.parent, json_agg(r.row ORDER BY r.n)::text FROM \
+             (SELECT g.id::text AS parent, (to_jsonb(x) - 'row_hash' - 'provenance') || jsonb_build_object('player_nfl_id', p.nfl_id, 'player_name', p.name, 'position', p.position, 'team', t.abbreviation) AS row, \
+             row_number() OVER (PARTITION BY g.id::text ORDER BY x.season, x.week, COALESCE(p.name, ''), x.id) AS n \
+             FROM game_stats x LEFT JOIN players p ON p.id = x.player_id LEFT JOIN teams t ON t.id = x.team_id JOIN games g ON g.season = x.season \
+             WHERE p.position = $1 AND g.id::text = ANY($2)) r \
+             WHERE r.n <= $3 GROUP BY r.parent"
+        );
+
+        assert!(matches!(route("/players/00-0033873/stats", &params("")), Ok(Route::List(..))));
+        assert!(matches!(route("/teams/kc", &params("")), Ok(Route::One(_))));
+        assert!(matches!(route("/search", &params("q=%20mahomes")), Ok(Route::Search(q)) if q == "mahomes"));
+        let both = format!("offset=50&cursor={}", encode_cursor(r#"["KC", "x"]"#));
+        for (path, query, status) in [
+            ("/search", "q=m", 400),
+            ("/stats", "limit=0", 400),
+            ("/games", "week=one", 400),
+            ("/games", "position=QB", 400),
+            ("/teams", "cursor=5b5d", 400),
+            ("/teams", "cursor=not-hex", 400),
+            ("/teams", &both, 400),
+            ("/plays", "", 404),
+        ] {
+            let Err(e) = route(path, &params(query)) else {
+                panic!("{}?{} should be rejected", path, query);
+            };
+            assert_eq!(e.status.as_u16(), status, "{}?{}", path, query);
+        }
+    }
+
+    /// Requests the router answers without touching the database
+    #[tokio::test]
+    async fn test_router_rejects_without_querying() {
+        let manager = PostgresConnectionManager::new(
+            "postgres://localhost:1/none".parse().unwrap(),
+            MakeTlsConnector::new(native_tls::TlsConnector::new().unwrap()),
+        );
+        let pool: Pool = r2d2::Pool::builder().build_unchecked(manager);
+        let schema = graphql::build(&graphql::Columns::default(), pool.clone()).unwrap();
+        let keys = Arc::new(ApiKeys::new(pool.clone()));
+        keys.remember(api_keys::hash_key("test-key"), Some(ApiKey { id: 1, per_minute: 60 }));
+        keys.remember(api_keys::hash_key("slow-key"), Some(ApiKey { id: 2, per_minute: 1 }));
+        keys.remember(api_keys::hash_key("bad-key"), None);
+        let app = router(AppState { pool, hub: Arc::default(), schema, keys });
+        let send_as = |key: &str, method: &str, uri: &str| {
+            let request = Request::builder().method(method).uri(uri).header("x-api-key", key).body(Body::empty()).unwrap();
+            app.clone().oneshot(request)
+        };
+        let send = |method: &str, uri: &str| send_as("test-key", method, uri);
+
+        assert_eq!(send_as("", "GET", "/health").await.unwrap().status(), StatusCode::OK);
+        assert_eq!(send_as("", "GET", "/teams").await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(send_as("bad-key", "GET", "/teams").await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(send_as("", "GET", "/live").await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(send_as("", "POST", "/graphql").await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(send_as("slow-key", "GET", "/stats?week=one").await.unwrap().status(), StatusCode::BAD_REQUEST);
+        let limited = send_as("slow-key", "GET", "/stats?week=one").await.unwrap();
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(limited.headers()[header::RETRY_AFTER], "60");
+        assert_eq!(send("GET", "/plays").await.unwrap().status(), StatusCode::NOT_FOUND);
+        assert_eq!(send("GET", "/stats?week=one").await.unwrap().status(), StatusCode::BAD_REQUEST);
+        assert_eq!(send("POST", "/players").await.unwrap().status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(send("GET", "/live").await.unwrap().status(), StatusCode::UPGRADE_REQUIRED);
+        assert_eq!(send("GET", "/graphql?query=%7B__typename%7D").await.unwrap().status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_if_none_match() {
+        let etag = "\"3919e89452671b6d\"";
+        assert!(not_modified(Some("\"3919e89452671b6d\""), etag));
+        assert!(not_modified(Some("\"old\", W/\"3919e89452671b6d\""), etag));
+        assert!(not_modified(Some("*"), etag));
+        assert!(!not_modified(Some("\"old\""), etag));
+        assert!(!not_modified(None, etag));
+    }
+}
@@ -0,0 +1,294 @@
+//! API keys for serve mode. Every route but `/health` needs a key from the
+//! `api_keys` table, sent as `Authorization: Bearer <key>`, `X-Api-Key: <key>`
+//! or, for browser WebSocket clients that can't set headers, `?api_key=<key>`.
+//! Keys are issued by `--mode api-keys` and looked up by their SHA-256 hash,
+//! and each gets a token bucket refilled at its `rate_limit_per_minute`.
+
+use anyhow::{Context, Result};
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use log::warn;
+use postgres::Client;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::api::{self, ApiError, Params};
+use crate::database::Pool;
+
+/// How long a looked-up key is trusted before it is read again, so a revoked
+/// key stops working within this long
+const KEY_TTL: Duration = Duration::from_secs(60);
+/// How long an unknown or revoked key is refused without reading the table
+const UNKNOWN_TTL: Duration = Duration::from_secs(10);
+/// How often expired lookups and idle buckets are dropped
+const PRUNE_INTERVAL: Duration = Duration::from_secs(10);
+/// A bucket untouched this long has refilled, so dropping it changes nothing
+const BUCKET_IDLE: Duration = Duration::from_secs(60);
+/// `rate_limit_per_minute` for keys created without `--rate-limit`
+pub const DEFAULT_RATE_LIMIT: i32 = 60;
+
+/// A key from `api_keys`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ApiKey {
+    pub id: i32,
+    pub per_minute: i32,
+}
+
+/// Requests a key may still make, refilled continuously up to its per-minute limit
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    at: Instant,
+}
+
+impl Bucket {
+    fn full(key: ApiKey, now: Instant) -> Self {
+        Bucket { tokens: key.per_minute as f64, at: now }
+    }
+
+    /// Spend a token, or say how long until one is available
+    fn take(&mut self, key: ApiKey, now: Instant) -> Result<(), Duration> {
+        let per_second = key.per_minute as f64 / 60.0;
+        self.tokens = (self.tokens + now.duration_since(self.at).as_secs_f64() * per_second).min(key.per_minute as f64);
+        self.at = now;
+        if self.tokens < 1.0 {
+            return Err(Duration::from_secs_f64((1.0 - self.tokens) / per_second));
+        }
+        self.tokens -= 1.0;
+        Ok(())
+    }
+}
+
+/// The keys serve mode has looked up, unknown ones included, and what each
+/// valid key has left to spend
+pub(crate) struct ApiKeys {
+    pool: Pool,
+    known: Mutex<HashMap<String, (Option<ApiKey>, Instant)>>,
+    buckets: Mutex<HashMap<i32, Bucket>>,
+    pruned: Mutex<Instant>,
+}
+
+impl ApiKeys {
+    pub fn new(pool: Pool) -> Self {
+        ApiKeys { pool, known: Mutex::default(), buckets: Mutex::default(), pruned: Mutex::new(Instant::now()) }
+    }
+
+    /// Keys that haven't been revoked
+    pub fn active(&self) -> Result<i64> {
+        let mut client = self.pool.get().context("No database connection available")?;
+        Ok(client.query_one("SELECT COUNT(*) FROM api_keys WHERE revoked_at IS NULL", &[])?.get(0))
+    }
+
+    /// Answer lookups of `hash` from memory (`None` for an unknown key) until
+    /// the entry expires. A key that turned out to be revoked loses its bucket.
+    pub fn remember(&self, hash: String, key: Option<ApiKey>) {
+        let previous = self.known.lock().unwrap().insert(hash, (key, Instant::now()));
+        if let (Some((Some(previous), _)), None) = (previous, key) {
+            self.buckets.lock().unwrap().remove(&previous.id);
+        }
+    }
+
+    /// The unrevoked key with `hash`, from memory while it's fresh
+    async fn find(&self, hash: String) -> Result<Option<ApiKey>> {
+        let now = Instant::now();
+        self.prune(now);
+        if let Some((key, at)) = self.known.lock().unwrap().get(&hash) {
+            let ttl = if key.is_some() { KEY_TTL } else { UNKNOWN_TTL };
+            if now.duration_since(*at) < ttl {
+                return Ok(*key);
+            }
+        }
+        let lookup = hash.clone();
+        let key = api::with_client(&self.pool, move |client| {
+            let row = client.query_opt(
+                "SELECT id, rate_limit_per_minute FROM api_keys WHERE key_hash = $1 AND revoked_at IS NULL",
+                &[&lookup],
+            )?;
+            Ok(row.map(|row| ApiKey { id: row.get(0), per_minute: row.get(1) }))
+        })
+        .await?;
+        self.remember(hash, key);
+        Ok(key)
+    }
+
+    /// Drop expired lookups and buckets idle long enough to be full, at most
+    /// once per `PRUNE_INTERVAL`
+    fn prune(&self, now: Instant) {
+        {
+            let mut pruned = self.pruned.lock().unwrap();
+            if now.duration_since(*pruned) < PRUNE_INTERVAL {
+                return;
+            }
+            *pruned = now;
+        }
+        self.known.lock().unwrap().retain(|_, (key, at)| now.duration_since(*at) < if key.is_some() { KEY_TTL } else { UNKNOWN_TTL });
+        self.buckets.lock().unwrap().retain(|_, bucket| now.duration_since(bucket.at) < BUCKET_IDLE);
+    }
+
+    fn take(&self, key: ApiKey) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        buckets.entry(key.id).or_insert_with(|| Bucket::full(key, now)).take(key, now)
+    }
+}
+
+/// Lowercase hex SHA-256 of a key, as stored in `api_keys.key_hash`
+pub(crate) fn hash_key(key: &str) -> String {
+    Sha256::digest(key.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// A row of `api_keys`, for `--mode api-keys --action list`
+#[derive(Debug)]
+pub struct KeyInfo {
+    pub id: i32,
+    pub name: String,
+    pub per_minute: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub revoked_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Issue a key named `name`, storing only its hash. The key itself is returned
+/// once and can't be recovered.
+pub fn create(client: &mut Client, name: &str, per_minute: i32) -> Result<(i32, String)> {
+    let key = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    let id = client
+        .query_one(
+            "INSERT INTO api_keys (name, key_hash, rate_limit_per_minute) VALUES ($1, $2, $3) RETURNING id",
+            &[&name, &hash_key(&key), &per_minute],
+        )
+        .context("Failed to store API key (apply migrations/059_add_api_keys.sql)")?
+        .get(0);
+    Ok((id, key))
+}
+
+/// Every key, oldest first
+pub fn list(client: &mut Client) -> Result<Vec<KeyInfo>> {
+    let rows = client.query("SELECT id, name, rate_limit_per_minute, created_at, revoked_at FROM api_keys ORDER BY id", &[])?;
+    Ok(rows
+        .iter()
+        .map(|row| KeyInfo {
+            id: row.get(0),
+            name: row.get(1),
+            per_minute: row.get(2),
+            created_at: row.get(3),
+            revoked_at: row.get(4),
+        })
+        .collect())
+}
+
+/// Stop accepting key `id`; false when there is no such unrevoked key
+pub fn revoke(client: &mut Client, id: i32) -> Result<bool> {
+    Ok(client.execute("UPDATE api_keys SET revoked_at = NOW() WHERE id = $1 AND revoked_at IS NULL", &[&id])? > 0)
+}
+
+/// The key a request carries, from its headers or query string
+fn presented(request: &Request) -> Option<String> {
+    let headers = request.headers();
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim);
+    let header = headers.get("x-api-key").and_then(|value| value.to_str().ok()).map(str::trim);
+    let query = || Params::parse(request.uri().query().unwrap_or_default()).param("api_key").map(str::to_string);
+    bearer.or(header).filter(|key| !key.is_empty()).map(str::to_string).or_else(query)
+}
+
+fn unauthorized(message: &str) -> Response {
+    let mut response = ApiError::new(StatusCode::UNAUTHORIZED, message).into_response();
+    response.headers_mut().insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    response
+}
+
+/// Middleware refusing requests without a valid key (401) or over its rate limit (429)
+pub(crate) async fn require_key(State(keys): State<Arc<ApiKeys>>, request: Request, next: Next) -> Response {
+    let Some(presented) = presented(&request) else {
+        return unauthorized("An API key is required: send Authorization: Bearer <key>");
+    };
+    let key = match keys.find(hash_key(&presented)).await {
+        Ok(Some(key)) => key,
+        Ok(None) => return unauthorized("Unknown or revoked API key"),
+        Err(e) => {
+            warn!("⚠️  API key lookup failed: {:#}", e);
+            return ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Can't check API keys right now").into_response();
+        }
+    };
+    if let Err(wait) = keys.take(key) {
+        let mut response = ApiError::new(StatusCode::TOO_MANY_REQUESTS, format!("Rate limit of {} requests per minute reached", key.per_minute))
+            .into_response();
+        response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(wait.as_secs_f64().ceil().max(1.0) as u64));
+        return response;
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use postgres_native_tls::MakeTlsConnector;
+    use r2d2_postgres::PostgresConnectionManager;
+
+    #[test]
+    fn test_bucket_refills_at_the_per_minute_rate() {
+        let key = ApiKey { id: 1, per_minute: 2 };
+        let start = Instant::now();
+        let mut bucket = Bucket::full(key, start);
+        assert!(bucket.take(key, start).is_ok());
+        assert!(bucket.take(key, start).is_ok());
+        assert_eq!(bucket.take(key, start), Err(Duration::from_secs(30)));
+        assert!(bucket.take(key, start + Duration::from_secs(30)).is_ok());
+        // idle time doesn't bank more than a minute's worth
+        let later = start + Duration::from_secs(3600);
+        assert!(bucket.take(key, later).is_ok() && bucket.take(key, later).is_ok());
+        assert!(bucket.take(key, later).is_err());
+    }
+
+    #[test]
+    fn test_presented_key() {
+        let request = |name: &str, value: &str, uri: &str| {
+            let builder = Request::builder().uri(uri);
+            let builder = match name.is_empty() {
+                true => builder,
+                false => builder.header(name, value),
+            };
+            presented(&builder.body(Body::empty()).unwrap())
+        };
+        assert_eq!(request("authorization", "Bearer abc ", "/teams"), Some("abc".to_string()));
+        assert_eq!(request("x-api-key", "abc", "/teams"), Some("abc".to_string()));
+        assert_eq!(request("", "", "/live?api_key=abc"), Some("abc".to_string()));
+        assert_eq!(request("authorization", "Basic abc", "/teams"), None);
+        assert_eq!(request("x-api-key", "", "/teams?api_key="), None);
+        assert_eq!(hash_key("abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    }
+
+    /// Unknown keys are answered from memory, and buckets of revoked or idle
+    /// keys are dropped
+    #[tokio::test]
+    async fn test_lookups_and_buckets_are_bounded() {
+        let manager = PostgresConnectionManager::new(
+            "postgres://localhost:1/none".parse().unwrap(),
+            MakeTlsConnector::new(native_tls::TlsConnector::new().unwrap()),
+        );
+        let keys = ApiKeys::new(r2d2::Pool::builder().build_unchecked(manager));
+        keys.remember(hash_key("unknown"), None);
+        assert_eq!(keys.find(hash_key("unknown")).await.unwrap(), None);
+
+        let key = ApiKey { id: 7, per_minute: 60 };
+        keys.remember(hash_key("revoked"), Some(key));
+        assert!(keys.take(key).is_ok());
+        keys.remember(hash_key("revoked"), None);
+        assert!(keys.buckets.lock().unwrap().is_empty());
+
+        let idle = ApiKey { id: 8, per_minute: 60 };
+        assert!(keys.take(idle).is_ok());
+        keys.prune(Instant::now() + BUCKET_IDLE);
+        assert!(keys.buckets.lock().unwrap().is_empty());
+        assert!(keys.known.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,149 @@
+use anyhow::{anyhow, Context, Result};
+use arrow_array::RecordBatch;
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::FieldRef;
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_arrow::schema::{SchemaLike, TracingOptions};
+use std::fs::File;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::datasets::{self, Dataset, SourceFormat};
+use crate::downloader::Downloader;
+use crate::efficiency::{EfficiencyAggregator, GarbageTimeFilter};
+use crate::parser::PbpPlay;
+pub use crate::transformer::Normalize;
+
+/// Convert rows to a record batch, one column per serialized field.
+///
+/// The schema is traced from the rows themselves: strings are Utf8, and a column
+/// that is empty in every row has the Null type.
+pub fn to_record_batch<T: Serialize>(rows: &[T]) -> Result<RecordBatch> {
+    if rows.is_empty() {
+        return Err(anyhow!("Cannot build a record batch from zero rows"));
+    }
+    let options = TracingOptions::default()
+        .allow_null_fields(true)
+        .strings_as_large_utf8(false);
+    let fields = Vec::<FieldRef>::from_samples(rows, options)?;
+    Ok(serde_arrow::to_record_batch(&fields, &rows)?)
+}
+
+/// Write batches to an Arrow IPC file (readable by Polars, DataFusion and pyarrow)
+pub fn write_ipc(path: &Path, batches: &[RecordBatch]) -> Result<()> {
+    let first = batches.first().ok_or_else(|| anyhow!("No record batches to write"))?;
+    let file = File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut writer = FileWriter::try_new(file, &first.schema())?;
+    for batch in batches {
+        writer.write(batch)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// Parsed, normalized datasets as Arrow record batches, read straight from the
+/// sources without a database connection
+pub struct ArrowExport {
+    downloader: Downloader,
+    format: SourceFormat,
+    garbage_time: GarbageTimeFilter,
+}
+
+impl ArrowExport {
+    /// Uses the config's download settings (`--format`, `--cache-dir`,
+    /// `--source-dir`, memory budget) and garbage-time threshold
+    pub fn new(config: &Config) -> Result<Self> {
+        Ok(ArrowExport {
+            downloader: Downloader::from_config(config)?,
+            format: config.source_format,
+            garbage_time: GarbageTimeFilter { wp: config.garbage_time_wp },
+        })
+    }
+
+    /// Parse and normalize a dataset's rows for a season. Rows that fail to
+    /// parse are skipped with a warning, as in the import stages.
+    pub fn rows<T: DeserializeOwned + Normalize>(&self, dataset: &Dataset<T>, year: i32) -> Result<Vec<T>> {
+        let mut rows = Vec::new();
+        self.for_each(dataset, year, |row| rows.push(row))?;
+        Ok(rows)
+    }
+
+    /// One dataset-season as a single batch
+    pub fn record_batch<T: DeserializeOwned + Normalize + Serialize>(
+        &self,
+        dataset: &Dataset<T>,
+        year: i32,
+    ) -> Result<RecordBatch> {
+        to_record_batch(&self.rows(dataset, year)?)
+    }
+
+    /// Team and player EPA/success rate aggregates for a season, with and without
+    /// garbage time, computed from play-by-play as the efficiency stage does
+    pub fn efficiency(&self, year: i32) -> Result<RecordBatch> {
+        let mut aggregator = EfficiencyAggregator::new(self.garbage_time);
+        self.for_each(&datasets::PLAY_BY_PLAY, year, |play: PbpPlay| aggregator.add(&play))?;
+        to_record_batch(&aggregator.rows())
+    }
+
+    fn for_each<T: DeserializeOwned + Normalize>(
+        &self,
+        dataset: &Dataset<T>,
+        year: i32,
+        mut visit: impl FnMut(T),
+    ) -> Result<()> {
+        let (header, records) = self.downloader.records(dataset, year, self.format)?;
+        let mut failed = 0;
+        for record in records {
+            match record.and_then(|r| Ok(dataset.dialect.deserialize::<T>(&r, &header)?)) {
+                Ok(mut row) => {
+                    row.normalize();
+                    visit(row);
+                }
+                Err(_) => failed += 1,
+            }
+        }
+        if failed > 0 {
+            warn!("{} {} rows failed to parse and were skipped", failed, dataset.name);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ratings::TeamRating;
+    use arrow_array::{Array, Float64Array, StringArray};
+    use arrow_ipc::reader::FileReader;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_rows_become_typed_columns() {
+        let rows = vec![
+            TeamRating { team_id: Uuid::nil(), games: 3, mov: 4.5, sos: -1.0, srs: 3.5 },
+            TeamRating { team_id: Uuid::max(), games: 3, mov: -4.5, sos: 1.0, srs: -3.5 },
+        ];
+        let batch = to_record_batch(&rows).unwrap();
+
+        assert_eq!(batch.num_rows(), 2);
+        let srs = batch.column_by_name("srs").unwrap().as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!(srs.value(1), -3.5);
+        let team = batch.column_by_name("team_id").unwrap().as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(team.value(0), "00000000-0000-0000-0000-000000000000");
+    }
+
+    #[test]
+    fn test_ipc_round_trip() {
+        let rows = vec![TeamRating { team_id: Uuid::nil(), games: 1, mov: 7.0, sos: 0.0, srs: 7.0 }];
+        let path = std::env::temp_dir().join(format!("nfl-pipeline-arrow-test-{}.arrow", std::process::id()));
+        write_ipc(&path, &[to_record_batch(&rows).unwrap()]).unwrap();
+
+        let reader = FileReader::try_new(File::open(&path).unwrap(), None).unwrap();
+        let batches: Vec<RecordBatch> = reader.map(|b| b.unwrap()).collect();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(batches[0].num_rows(), 1);
+        assert_eq!(batches[0].schema().field_with_name("srs").unwrap().data_type(), &arrow_schema::DataType::Float64);
+    }
+}
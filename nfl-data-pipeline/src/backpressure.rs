@@ -0,0 +1,124 @@
+use anyhow::{Context, Result};
+use postgres::error::SqlState;
+use postgres::Client;
+use std::time::Duration;
+
+/// How often a stage samples the server's load
+pub const SAMPLE_EVERY: Duration = Duration::from_secs(5);
+/// Pause before retrying a batch that timed out or found no free connection
+pub const BACKOFF: Duration = Duration::from_secs(2);
+/// Connections left for everyone else (the API, dataclips, psql)
+const RESERVED_CONNECTIONS: i64 = 3;
+/// Sessions waiting on locks beyond which writers are contending with each other
+const MAX_LOCK_WAITS: i64 = 2;
+
+/// The server's load, as seen from one of the pipeline's sessions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Load {
+    /// Connections still available: the tighter of the server's
+    /// `max_connections` (less superuser slots) and the role's connection limit,
+    /// which is how Heroku's smaller plans cap connections
+    pub free_connections: i64,
+    /// Sessions waiting on a lock
+    pub lock_waits: i64,
+}
+
+/// What writers should do about a sample
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Give up this many writers' connections
+    Shed(usize),
+    /// Halve writers and batch size
+    Throttle(String),
+    /// Room to spare: allow one more writer
+    Relax,
+    /// No spare connection, no contention
+    Hold,
+}
+
+impl Load {
+    /// Writers a stage can start with: one per spare connection
+    pub fn writers_allowed(&self) -> usize {
+        (self.free_connections - RESERVED_CONNECTIONS).max(0) as usize
+    }
+
+    pub fn action(&self) -> Action {
+        let spare = self.free_connections - RESERVED_CONNECTIONS;
+        if spare < 0 {
+            Action::Shed(spare.unsigned_abs() as usize)
+        } else if self.lock_waits > MAX_LOCK_WAITS {
+            Action::Throttle(format!("{} sessions waiting on locks", self.lock_waits))
+        } else if spare > 0 {
+            Action::Relax
+        } else {
+            Action::Hold
+        }
+    }
+}
+
+pub fn sample(client: &mut Client) -> Result<Load> {
+    let row = client
+        .query_one(
+            "SELECT
+                 current_setting('max_connections')::bigint
+                     - current_setting('superuser_reserved_connections')::bigint
+                     - (SELECT COUNT(*) FROM pg_stat_activity WHERE backend_type = 'client backend'),
+                 (SELECT CASE WHEN rolconnlimit < 0 THEN NULL
+                              ELSE rolconnlimit - (SELECT COUNT(*) FROM pg_stat_activity WHERE usename = current_user)
+                         END
+                  FROM pg_roles WHERE rolname = current_user),
+                 (SELECT COUNT(*) FROM pg_stat_activity WHERE wait_event_type = 'Lock')",
+            &[],
+        )
+        .context("Failed to sample database load")?;
+    let server: i64 = row.get(0);
+    let role: Option<i64> = row.get(1);
+    Ok(Load { free_connections: role.map_or(server, |role| role.min(server)), lock_waits: row.get(2) })
+}
+
+/// Why a failed write means the server is overloaded, when it does: a statement
+/// timeout, or no connection slots left
+pub fn overload(e: &anyhow::Error) -> Option<&'static str> {
+    for cause in e.chain() {
+        if let Some(code) = cause.downcast_ref::<postgres::Error>().and_then(|e| e.code()) {
+            if *code == SqlState::QUERY_CANCELED {
+                return Some("statement timeout");
+            }
+            if *code == SqlState::TOO_MANY_CONNECTIONS {
+                return Some("too many connections");
+            }
+        }
+        // The pool reports a failed checkout as text
+        let message = cause.to_string();
+        if message.contains("too many connections") || message.contains("remaining connection slots are reserved") {
+            return Some("too many connections");
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_load_actions() {
+        assert_eq!(Load { free_connections: 1, lock_waits: 0 }.action(), Action::Shed(2));
+        assert_eq!(Load { free_connections: 3, lock_waits: 0 }.action(), Action::Hold);
+        assert_eq!(Load { free_connections: 4, lock_waits: 0 }.action(), Action::Relax);
+        assert_eq!(Load { free_connections: 9, lock_waits: 0 }.writers_allowed(), 6);
+        assert_eq!(Load { free_connections: 1, lock_waits: 0 }.writers_allowed(), 0);
+        assert_eq!(
+            Load { free_connections: 10, lock_waits: 5 }.action(),
+            Action::Throttle("5 sessions waiting on locks".to_string())
+        );
+    }
+
+    #[test]
+    fn test_overload_from_pool_errors() {
+        let e = anyhow!("FATAL: too many connections for role \"u8f3k\"").context("Failed to check out a database connection");
+        assert_eq!(overload(&e), Some("too many connections"));
+        assert_eq!(overload(&anyhow!("duplicate key value violates unique constraint")), None);
+    }
+}
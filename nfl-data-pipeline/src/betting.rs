@@ -0,0 +1,93 @@
+use crate::parser::Game;
+
+/// Outcome of a game against the spread
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpreadResult {
+    HomeCover,
+    AwayCover,
+    Push,
+}
+
+impl SpreadResult {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpreadResult::HomeCover => "home",
+            SpreadResult::AwayCover => "away",
+            SpreadResult::Push => "push",
+        }
+    }
+}
+
+/// Outcome of a game against the total
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TotalResult {
+    Over,
+    Under,
+    Push,
+}
+
+impl TotalResult {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TotalResult::Over => "over",
+            TotalResult::Under => "under",
+            TotalResult::Push => "push",
+        }
+    }
+}
+
+/// Grade a final score against the spread.
+///
+/// nflverse `spread_line` is the expected home margin (positive = home favored),
+/// so the home side covers when it wins by more than the line.
+pub fn grade_spread(home_score: i32, away_score: i32, spread_line: f64) -> SpreadResult {
+    let margin = (home_score - away_score) as f64;
+    if margin > spread_line {
+        SpreadResult::HomeCover
+    } else if margin < spread_line {
+        SpreadResult::AwayCover
+    } else {
+        SpreadResult::Push
+    }
+}
+
+/// Grade a final score against the total
+pub fn grade_total(home_score: i32, away_score: i32, total_line: f64) -> TotalResult {
+    let total = (home_score + away_score) as f64;
+    if total > total_line {
+        TotalResult::Over
+    } else if total < total_line {
+        TotalResult::Under
+    } else {
+        TotalResult::Push
+    }
+}
+
+/// Whether a schedule row is final and has a spread or total to grade against
+pub fn is_gradable(game: &Game) -> bool {
+    game.home_score.is_some() && game.away_score.is_some() && (game.spread_line.is_some() || game.total_line.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grade_spread() {
+        // Home favored by 3, wins by 7
+        assert_eq!(grade_spread(27, 20, 3.0), SpreadResult::HomeCover);
+        // Home favored by 3, wins by 3
+        assert_eq!(grade_spread(23, 20, 3.0), SpreadResult::Push);
+        // Home favored by 6.5, wins by 3
+        assert_eq!(grade_spread(23, 20, 6.5), SpreadResult::AwayCover);
+        // Home underdog by 2.5, loses by 1
+        assert_eq!(grade_spread(20, 21, -2.5), SpreadResult::HomeCover);
+    }
+
+    #[test]
+    fn test_grade_total() {
+        assert_eq!(grade_total(27, 20, 44.5), TotalResult::Over);
+        assert_eq!(grade_total(24, 20, 44.0), TotalResult::Push);
+        assert_eq!(grade_total(10, 13, 41.5), TotalResult::Under);
+    }
+}
@@ -0,0 +1,700 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use postgres::types::ToSql;
+use postgres::Client;
+use std::collections::HashMap;
+use std::io::Write;
+
+use crate::game_status::{self, GameStatus};
+use crate::parser::{Game, PlayerStat, RosterPlayer};
+use crate::precedence::{self, Precedence, Source};
+use crate::row_hashes;
+use crate::transformer;
+
+/// Bulk load players: COPY into a temp table, then merge with a single upsert
+pub fn copy_players(client: &mut Client, players: &[RosterPlayer], precedence: &Precedence, source: Source) -> Result<u64> {
+    let settings = row_hashes::merge_settings(precedence, source);
+    let mut rows = Vec::with_capacity(players.len());
+    for player in players {
+        rows.push(vec![
+            player.gsis_id.clone(),
+            player.full_name.clone(),
+            player.position.clone(),
+            transformer::normalize_team_abbr(&player.team),
+            copy_field(&player.jersey_number),
+            copy_field(&player.height.as_ref().and_then(|h| transformer::height_to_inches(h))),
+            copy_field(&player.weight),
+            player.college.clone().unwrap_or_default(),
+            player.status.clone().unwrap_or_else(|| "active".to_string()),
+            copy_field(&player.birth_date.as_deref().and_then(transformer::parse_date)),
+            copy_field(&player.years_exp),
+            copy_field(&player.entry_year),
+            copy_field(&player.rookie_year),
+            copy_field(&player.draft_club.as_deref().map(transformer::normalize_team_abbr)),
+            copy_field(&player.draft_number),
+            player.headshot_url.clone().unwrap_or_default(),
+            serde_json::to_string(&transformer::external_ids(player).into_iter().collect::<HashMap<_, _>>())?,
+            row_hashes::content_hash(player, &settings),
+        ]);
+    }
+
+    copy_and_merge_with(
+        client,
+        "CREATE TEMP TABLE tmp_players (
+             nfl_id TEXT,
+             name TEXT,
+             position TEXT,
+             team_abbr TEXT,
+             jersey_number INT,
+             height_inches INT,
+             weight_pounds INT,
+             college TEXT,
+             status TEXT,
+             birth_date DATE,
+             years_exp INT,
+             entry_year INT,
+             rookie_year INT,
+             draft_club TEXT,
+             draft_number INT,
+             headshot_url TEXT,
+             external_ids JSONB,
+             row_hash TEXT
+         ) ON COMMIT DROP",
+        "COPY tmp_players FROM STDIN WITH (FORMAT csv)",
+        &format!(
+            "INSERT INTO players (id, nfl_id, name, position, team_id, jersey_number, height_inches, weight_pounds, college, status,
+             birth_date, years_pro, entry_year, rookie_year, draft_club, draft_year, draft_pick, headshot_url, provenance, row_hash, created_at, updated_at)
+         SELECT DISTINCT ON (s.nfl_id)
+                uuid_generate_v4(), s.nfl_id, s.name, s.position, t.id, s.jersey_number,
+                s.height_inches, s.weight_pounds, s.college, s.status,
+                s.birth_date, s.years_exp, s.entry_year, s.rookie_year, NULLIF(s.draft_club, ''),
+                CASE WHEN s.draft_number IS NOT NULL THEN s.entry_year END, s.draft_number, NULLIF(s.headshot_url, ''),
+                {provenance}, s.row_hash, NOW(), NOW()
+         FROM tmp_players s
+         LEFT JOIN teams t ON t.abbreviation = s.team_abbr
+         ON CONFLICT (nfl_id) DO UPDATE SET
+             name = EXCLUDED.name,
+             {merge},
+             status = EXCLUDED.status,
+             birth_date = COALESCE(EXCLUDED.birth_date, players.birth_date),
+             years_pro = COALESCE(EXCLUDED.years_pro, players.years_pro),
+             entry_year = COALESCE(EXCLUDED.entry_year, players.entry_year),
+             rookie_year = COALESCE(EXCLUDED.rookie_year, players.rookie_year),
+             draft_club = COALESCE(EXCLUDED.draft_club, players.draft_club),
+             draft_year = COALESCE(EXCLUDED.draft_year, players.draft_year),
+             draft_pick = COALESCE(EXCLUDED.draft_pick, players.draft_pick),
+             headshot_url = COALESCE(EXCLUDED.headshot_url, players.headshot_url),
+             row_hash = EXCLUDED.row_hash,
+             updated_at = NOW()",
+            provenance = precedence::insert_sql(source, &[("position", "s.position"), ("team_id", "t.id"), ("jersey_number", "s.jersey_number"), ("height_inches", "s.height_inches"), ("weight_pounds", "s.weight_pounds"), ("college", "s.college")]),
+            merge = precedence::roster_upsert_sql(precedence, source)
+        ),
+        &[
+            "INSERT INTO player_external_ids (player_id, source, external_id, updated_at)
+             SELECT DISTINCT ON (p.id, e.key) p.id, e.key, e.value, NOW()
+             FROM tmp_players s
+             JOIN players p ON p.nfl_id = s.nfl_id
+             CROSS JOIN LATERAL jsonb_each_text(s.external_ids) e
+             ON CONFLICT (player_id, source) DO UPDATE SET
+                 external_id = EXCLUDED.external_id,
+                 match_confidence = NULL,
+                 updated_at = NOW()
+             WHERE player_external_ids.external_id IS DISTINCT FROM EXCLUDED.external_id
+                OR player_external_ids.match_confidence IS NOT NULL",
+        ],
+        &rows,
+    )
+}
+
+/// Bulk load player stats; rows for players not in the players table are skipped
+pub fn copy_stats(client: &mut Client, stats: &[PlayerStat]) -> Result<u64> {
+    let mut rows = Vec::with_capacity(stats.len());
+    for stat in stats {
+        let mut row = vec![
+            stat.player_id.clone(),
+            stat.season.to_string(),
+            stat.week.to_string(),
+            stat.season_type.clone(),
+            copy_field(&stat.passing_yards.map(|v| v as i32)),
+            copy_field(&stat.rushing_yards.map(|v| v as i32)),
+            copy_field(&stat.receiving_yards.map(|v| v as i32)),
+            copy_field(&stat.passing_tds),
+            copy_field(&stat.rushing_tds),
+            copy_field(&stat.receiving_tds),
+            copy_field(&stat.receptions.map(|v| v as i32)),
+            copy_field(&stat.targets.map(|v| v as i32)),
+            copy_field(&stat.attempts.map(|v| v as i32)),
+            copy_field(&stat.completions.map(|v| v as i32)),
+            copy_field(&stat.interceptions),
+        ];
+        row.extend(transformer::defense_kicking_values(stat).iter().map(copy_field));
+        row.push(copy_field(&stat.def_sacks));
+        row.push(copy_field(&stat.target_share));
+        row.push(copy_field(&stat.recent_team.as_deref().map(transformer::normalize_team_abbr)));
+        row.push(row_hashes::content_hash(stat, ""));
+        rows.push(row);
+    }
+
+    copy_and_merge(
+        client,
+        &format!("CREATE TEMP TABLE tmp_game_stats (
+             player_nfl_id TEXT,
+             season INT,
+             week INT,
+             season_type TEXT,
+             passing_yards INT,
+             rushing_yards INT,
+             receiving_yards INT,
+             passing_tds INT,
+             rushing_tds INT,
+             receiving_tds INT,
+             receptions INT,
+             targets INT,
+             attempts INT,
+             completions INT,
+             interceptions INT,
+             {},
+             def_sacks NUMERIC,
+             target_share NUMERIC,
+             team_abbr TEXT,
+             row_hash TEXT
+         ) ON COMMIT DROP", defense_kicking_sql("{} INT")),
+        "COPY tmp_game_stats FROM STDIN WITH (FORMAT csv)",
+        &format!("INSERT INTO game_stats (id, player_id, season, week, season_type, passing_yards, rushing_yards, receiving_yards, passing_tds, rushing_tds, receiving_tds, receptions, targets, attempts, completions, interceptions, {}, def_sacks, target_share, team_id, row_hash, created_at, updated_at)
+         SELECT DISTINCT ON (p.id, s.season, s.week, s.season_type)
+                uuid_generate_v4(), p.id, s.season, s.week, s.season_type, s.passing_yards, s.rushing_yards,
+                s.receiving_yards, s.passing_tds, s.rushing_tds, s.receiving_tds, s.receptions,
+                s.targets, s.attempts, s.completions, s.interceptions, {}, s.def_sacks, s.target_share, t.id, s.row_hash, NOW(), NOW()
+         FROM tmp_game_stats s
+         JOIN players p ON p.nfl_id = s.player_nfl_id
+         LEFT JOIN teams t ON t.abbreviation = s.team_abbr
+         ON CONFLICT (player_id, season, week, season_type) DO UPDATE SET
+             passing_yards = EXCLUDED.passing_yards,
+             rushing_yards = EXCLUDED.rushing_yards,
+             receiving_yards = EXCLUDED.receiving_yards,
+             passing_tds = EXCLUDED.passing_tds,
+             rushing_tds = EXCLUDED.rushing_tds,
+             receiving_tds = EXCLUDED.receiving_tds,
+             receptions = EXCLUDED.receptions,
+             targets = EXCLUDED.targets,
+             attempts = EXCLUDED.attempts,
+             completions = EXCLUDED.completions,
+             interceptions = EXCLUDED.interceptions,
+             {},
+             def_sacks = EXCLUDED.def_sacks,
+             target_share = EXCLUDED.target_share,
+             team_id = COALESCE(EXCLUDED.team_id, game_stats.team_id),
+             row_hash = EXCLUDED.row_hash,
+             updated_at = NOW()",
+            defense_kicking_sql("{}"),
+            defense_kicking_sql("s.{}"),
+            defense_kicking_sql("{} = EXCLUDED.{}"),
+        ),
+        &rows,
+    )
+}
+
+/// `template` filled in with each of `transformer::DEFENSE_KICKING_COLUMNS`, comma separated
+pub fn defense_kicking_sql(template: &str) -> String {
+    transformer::DEFENSE_KICKING_COLUMNS
+        .iter()
+        .map(|column| template.replace("{}", column))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Bulk load games; rows whose teams are not in the teams table are skipped
+pub fn copy_games(client: &mut Client, games: &[Game], precedence: &Precedence, source: Source) -> Result<u64> {
+    let settings = row_hashes::merge_settings(precedence, source);
+    let mut rows = Vec::with_capacity(games.len());
+    for game in games {
+        rows.push(vec![
+            game.game_id.clone(),
+            game.season.to_string(),
+            game.week.to_string(),
+            transformer::season_type_for_game_type(&game.game_type).to_string(),
+            copy_field(&transformer::kickoff(&game.gameday, game.gametime.as_deref()).map(|k| k.to_rfc3339())),
+            transformer::normalize_team_abbr(&game.home_team),
+            transformer::normalize_team_abbr(&game.away_team),
+            copy_field(&game.home_score),
+            copy_field(&game.away_score),
+            copy_field(&game.gametime),
+            copy_field(&game.spread_line),
+            copy_field(&game.total_line),
+            copy_field(&game.home_moneyline),
+            copy_field(&game.away_moneyline),
+            copy_field(&game.home_spread_odds),
+            copy_field(&game.away_spread_odds),
+            copy_field(&game.over_odds),
+            copy_field(&game.under_odds),
+            copy_field(&game.roof),
+            copy_field(&game.surface),
+            copy_field(&game.temp),
+            copy_field(&game.wind),
+            copy_field(&game.stadium),
+            copy_field(&game.referee),
+            copy_field(&game.home_rest),
+            copy_field(&game.away_rest),
+            copy_field(&game.location),
+            GameStatus::of(game).as_str().to_string(),
+            row_hashes::content_hash(game, &settings),
+        ]);
+    }
+
+    copy_and_merge(
+        client,
+        "CREATE TEMP TABLE tmp_games (
+             nfl_game_id TEXT,
+             season INT,
+             week INT,
+             season_type TEXT,
+             game_date TIMESTAMPTZ,
+             home_abbr TEXT,
+             away_abbr TEXT,
+             home_score INT,
+             away_score INT,
+             game_time_et TEXT,
+             spread_line NUMERIC,
+             total_line NUMERIC,
+             home_moneyline NUMERIC,
+             away_moneyline NUMERIC,
+             home_spread_odds NUMERIC,
+             away_spread_odds NUMERIC,
+             over_odds NUMERIC,
+             under_odds NUMERIC,
+             roof TEXT,
+             surface TEXT,
+             temp NUMERIC,
+             wind NUMERIC,
+             venue_name TEXT,
+             referee TEXT,
+             home_rest INT,
+             away_rest INT,
+             location TEXT,
+             status TEXT,
+             row_hash TEXT
+         ) ON COMMIT DROP",
+        "COPY tmp_games FROM STDIN WITH (FORMAT csv)",
+        &format!(
+            "INSERT INTO games (id, nfl_game_id, season, week, season_type, game_date, home_team_id, away_team_id, home_score, away_score, status,
+                 game_time_et, spread_line, total_line, home_moneyline, away_moneyline, home_spread_odds, away_spread_odds,
+                 over_odds, under_odds, roof, surface, weather_temp, weather_wind_speed, venue_name, referee, home_rest, away_rest,
+                 location, provenance, row_hash, created_at, updated_at)
+             SELECT DISTINCT ON (s.nfl_game_id)
+                    uuid_generate_v4(), s.nfl_game_id, s.season, s.week, s.season_type, s.game_date,
+                    ht.id, at.id, s.home_score, s.away_score, s.status,
+                    s.game_time_et, s.spread_line, s.total_line, s.home_moneyline, s.away_moneyline, s.home_spread_odds,
+                    s.away_spread_odds, s.over_odds, s.under_odds, s.roof, s.surface, round(s.temp), round(s.wind),
+                    s.venue_name, s.referee, s.home_rest, s.away_rest, s.location, {provenance}, s.row_hash, NOW(), NOW()
+             FROM tmp_games s
+             JOIN teams ht ON ht.abbreviation = s.home_abbr
+             JOIN teams at ON at.abbreviation = s.away_abbr
+             ON CONFLICT (nfl_game_id) DO UPDATE SET
+                 {merge},
+                 status = {status},
+                 game_time_et = COALESCE(EXCLUDED.game_time_et, games.game_time_et),
+                 spread_line = COALESCE(EXCLUDED.spread_line, games.spread_line),
+                 total_line = COALESCE(EXCLUDED.total_line, games.total_line),
+                 home_moneyline = COALESCE(EXCLUDED.home_moneyline, games.home_moneyline),
+                 away_moneyline = COALESCE(EXCLUDED.away_moneyline, games.away_moneyline),
+                 home_spread_odds = COALESCE(EXCLUDED.home_spread_odds, games.home_spread_odds),
+                 away_spread_odds = COALESCE(EXCLUDED.away_spread_odds, games.away_spread_odds),
+                 over_odds = COALESCE(EXCLUDED.over_odds, games.over_odds),
+                 under_odds = COALESCE(EXCLUDED.under_odds, games.under_odds),
+                 roof = COALESCE(EXCLUDED.roof, games.roof),
+                 surface = COALESCE(EXCLUDED.surface, games.surface),
+                 weather_temp = COALESCE(EXCLUDED.weather_temp, games.weather_temp),
+                 weather_wind_speed = COALESCE(EXCLUDED.weather_wind_speed, games.weather_wind_speed),
+                 referee = COALESCE(EXCLUDED.referee, games.referee),
+                 home_rest = COALESCE(EXCLUDED.home_rest, games.home_rest),
+                 away_rest = COALESCE(EXCLUDED.away_rest, games.away_rest),
+                 location = COALESCE(EXCLUDED.location, games.location),
+                 row_hash = EXCLUDED.row_hash,
+                 updated_at = NOW()",
+            provenance = precedence::insert_sql(source, &[("season_type", "s.season_type"), ("week", "s.week"), ("game_date", "s.game_date"), ("home_score", "s.home_score"), ("away_score", "s.away_score"), ("venue_name", "s.venue_name")]),
+            merge = precedence::schedule_upsert_sql(precedence, source),
+            status = game_status::merge_sql("games.status", "EXCLUDED.status")
+        ),
+        &rows,
+    )
+}
+
+/// Upsert a batch of players in one statement, passing each column as an array.
+/// When a batch repeats an nfl_id the last row wins, as it would row by row.
+pub fn unnest_players(client: &mut Client, players: &[RosterPlayer], precedence: &Precedence, source: Source) -> Result<u64> {
+    let nfl_ids: Vec<&str> = players.iter().map(|p| p.gsis_id.as_str()).collect();
+    let names: Vec<&str> = players.iter().map(|p| p.full_name.as_str()).collect();
+    let positions: Vec<&str> = players.iter().map(|p| p.position.as_str()).collect();
+    let teams: Vec<String> = players.iter().map(|p| transformer::normalize_team_abbr(&p.team)).collect();
+    let jerseys: Vec<Option<i32>> = players.iter().map(|p| p.jersey_number).collect();
+    let heights: Vec<Option<i32>> = players
+        .iter()
+        .map(|p| p.height.as_deref().and_then(transformer::height_to_inches))
+        .collect();
+    let weights: Vec<Option<i32>> = players.iter().map(|p| p.weight).collect();
+    let colleges: Vec<Option<&str>> = players.iter().map(|p| p.college.as_deref()).collect();
+    let statuses: Vec<&str> = players.iter().map(|p| p.status.as_deref().unwrap_or("active")).collect();
+    let birth_dates: Vec<Option<NaiveDate>> = players
+        .iter()
+        .map(|p| p.birth_date.as_deref().and_then(transformer::parse_date))
+        .collect();
+    let years_exp: Vec<Option<i32>> = players.iter().map(|p| p.years_exp).collect();
+    let entry_years: Vec<Option<i32>> = players.iter().map(|p| p.entry_year).collect();
+    let rookie_years: Vec<Option<i32>> = players.iter().map(|p| p.rookie_year).collect();
+    let draft_clubs: Vec<Option<String>> = players
+        .iter()
+        .map(|p| p.draft_club.as_deref().map(transformer::normalize_team_abbr))
+        .collect();
+    let draft_numbers: Vec<Option<i32>> = players.iter().map(|p| p.draft_number).collect();
+    let headshots: Vec<Option<&str>> = players.iter().map(|p| p.headshot_url.as_deref()).collect();
+    let settings = row_hashes::merge_settings(precedence, source);
+    let hashes: Vec<String> = players.iter().map(|p| row_hashes::content_hash(p, &settings)).collect();
+
+    let (mut id_players, mut id_sources, mut id_values) = (Vec::new(), Vec::new(), Vec::new());
+    for player in players {
+        for (source, external_id) in transformer::external_ids(player) {
+            id_players.push(player.gsis_id.as_str());
+            id_sources.push(source);
+            id_values.push(external_id);
+        }
+    }
+
+    let mut tx = client.transaction()?;
+    let merged = tx
+        .execute(
+            &format!(
+                "INSERT INTO players (id, nfl_id, name, position, team_id, jersey_number, height_inches, weight_pounds, college, status,
+                     birth_date, years_pro, entry_year, rookie_year, draft_club, draft_year, draft_pick, headshot_url, provenance, row_hash, created_at, updated_at)
+             SELECT DISTINCT ON (s.nfl_id)
+                    uuid_generate_v4(), s.nfl_id, s.name, s.position, t.id, s.jersey_number,
+                    s.height_inches, s.weight_pounds, s.college, s.status,
+                    s.birth_date, s.years_exp, s.entry_year, s.rookie_year, s.draft_club,
+                    CASE WHEN s.draft_number IS NOT NULL THEN s.entry_year END, s.draft_number, s.headshot_url,
+                    {provenance}, s.row_hash, NOW(), NOW()
+             FROM UNNEST($1::text[], $2::text[], $3::text[], $4::text[], $5::int[], $6::int[], $7::int[], $8::text[],
+                         $9::text[], $10::date[], $11::int[], $12::int[], $13::int[], $14::text[], $15::int[], $16::text[],
+                         $17::text[])
+                  WITH ORDINALITY AS s (nfl_id, name, position, team_abbr, jersey_number, height_inches, weight_pounds,
+                      college, status, birth_date, years_exp, entry_year, rookie_year, draft_club, draft_number,
+                      headshot_url, row_hash, ord)
+             LEFT JOIN teams t ON t.abbreviation = s.team_abbr
+             ORDER BY s.nfl_id, s.ord DESC
+             ON CONFLICT (nfl_id) DO UPDATE SET
+                 name = EXCLUDED.name,
+                 {merge},
+                 status = EXCLUDED.status,
+                 birth_date = COALESCE(EXCLUDED.birth_date, players.birth_date),
+                 years_pro = COALESCE(EXCLUDED.years_pro, players.years_pro),
+                 entry_year = COALESCE(EXCLUDED.entry_year, players.entry_year),
+                 rookie_year = COALESCE(EXCLUDED.rookie_year, players.rookie_year),
+                 draft_club = COALESCE(EXCLUDED.draft_club, players.draft_club),
+                 draft_year = COALESCE(EXCLUDED.draft_year, players.draft_year),
+                 draft_pick = COALESCE(EXCLUDED.draft_pick, players.draft_pick),
+                 headshot_url = COALESCE(EXCLUDED.headshot_url, players.headshot_url),
+                 row_hash = EXCLUDED.row_hash,
+                 updated_at = NOW()",
+                provenance = precedence::insert_sql(source, &[("position", "s.position"), ("team_id", "t.id"), ("jersey_number", "s.jersey_number"), ("height_inches", "s.height_inches"), ("weight_pounds", "s.weight_pounds"), ("college", "s.college")]),
+                merge = precedence::roster_upsert_sql(precedence, source)
+            ),
+            &[
+                &nfl_ids, &names, &positions, &teams, &jerseys, &heights, &weights, &colleges, &statuses,
+                &birth_dates, &years_exp, &entry_years, &rookie_years, &draft_clubs, &draft_numbers, &headshots, &hashes,
+            ],
+        )
+        .context("Batch player upsert failed")?;
+
+    tx.execute(
+        "INSERT INTO player_external_ids (player_id, source, external_id, updated_at)
+         SELECT DISTINCT ON (p.id, s.source) p.id, s.source, s.external_id, NOW()
+         FROM UNNEST($1::text[], $2::text[], $3::text[]) WITH ORDINALITY AS s (nfl_id, source, external_id, ord)
+         JOIN players p ON p.nfl_id = s.nfl_id
+         ORDER BY p.id, s.source, s.ord DESC
+         ON CONFLICT (player_id, source) DO UPDATE SET
+             external_id = EXCLUDED.external_id,
+             match_confidence = NULL,
+             updated_at = NOW()
+         WHERE player_external_ids.external_id IS DISTINCT FROM EXCLUDED.external_id
+            OR player_external_ids.match_confidence IS NOT NULL",
+        &[&id_players, &id_sources, &id_values],
+    )
+    .context("Batch external ID upsert failed")?;
+
+    tx.commit()?;
+    Ok(merged)
+}
+
+/// Upsert a batch of stat lines in one statement; rows for players not in the
+/// players table are skipped
+pub fn unnest_stats(client: &mut Client, stats: &[PlayerStat]) -> Result<u64> {
+    let as_i32 = |values: fn(&PlayerStat) -> Option<f64>| -> Vec<Option<i32>> {
+        stats.iter().map(|s| values(s).map(|v| v as i32)).collect()
+    };
+    let nfl_ids: Vec<&str> = stats.iter().map(|s| s.player_id.as_str()).collect();
+    let seasons: Vec<i32> = stats.iter().map(|s| s.season).collect();
+    let weeks: Vec<i32> = stats.iter().map(|s| s.week).collect();
+    let season_types: Vec<&str> = stats.iter().map(|s| s.season_type.as_str()).collect();
+    let passing_tds: Vec<Option<i32>> = stats.iter().map(|s| s.passing_tds).collect();
+    let rushing_tds: Vec<Option<i32>> = stats.iter().map(|s| s.rushing_tds).collect();
+    let receiving_tds: Vec<Option<i32>> = stats.iter().map(|s| s.receiving_tds).collect();
+    let interceptions: Vec<Option<i32>> = stats.iter().map(|s| s.interceptions).collect();
+    let extras: Vec<_> = stats.iter().map(transformer::defense_kicking_values).collect();
+    let extra_columns: Vec<Vec<Option<i32>>> = (0..transformer::DEFENSE_KICKING_COLUMNS.len())
+        .map(|i| extras.iter().map(|values| values[i]).collect())
+        .collect();
+    let def_sacks: Vec<Option<f64>> = stats.iter().map(|s| s.def_sacks).collect();
+    let target_shares: Vec<Option<f64>> = stats.iter().map(|s| s.target_share).collect();
+    let team_abbrs: Vec<Option<String>> =
+        stats.iter().map(|s| s.recent_team.as_deref().map(transformer::normalize_team_abbr)).collect();
+    let hashes: Vec<String> = stats.iter().map(|s| row_hashes::content_hash(s, "")).collect();
+    let extra_arrays = (16..16 + extra_columns.len())
+        .map(|n| format!("${}::int[]", n))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let def_sacks_param = 16 + extra_columns.len();
+
+    let (passing_yards, rushing_yards, receiving_yards) =
+        (as_i32(|s| s.passing_yards), as_i32(|s| s.rushing_yards), as_i32(|s| s.receiving_yards));
+    let (receptions, targets, attempts, completions) =
+        (as_i32(|s| s.receptions), as_i32(|s| s.targets), as_i32(|s| s.attempts), as_i32(|s| s.completions));
+    let mut params: Vec<&(dyn ToSql + Sync)> = vec![
+        &nfl_ids,
+        &seasons,
+        &weeks,
+        &season_types,
+        &passing_yards,
+        &rushing_yards,
+        &receiving_yards,
+        &passing_tds,
+        &rushing_tds,
+        &receiving_tds,
+        &receptions,
+        &targets,
+        &attempts,
+        &completions,
+        &interceptions,
+    ];
+    params.extend(extra_columns.iter().map(|column| column as &(dyn ToSql + Sync)));
+    params.push(&def_sacks);
+    params.push(&target_shares);
+    params.push(&team_abbrs);
+    params.push(&hashes);
+
+    client
+        .execute(
+            &format!("INSERT INTO game_stats (id, player_id, season, week, season_type, passing_yards, rushing_yards, receiving_yards, passing_tds, rushing_tds, receiving_tds, receptions, targets, attempts, completions, interceptions, {}, def_sacks, target_share, team_id, row_hash, created_at, updated_at)
+             SELECT DISTINCT ON (p.id, s.season, s.week, s.season_type)
+                    uuid_generate_v4(), p.id, s.season, s.week, s.season_type, s.passing_yards, s.rushing_yards,
+                    s.receiving_yards, s.passing_tds, s.rushing_tds, s.receiving_tds, s.receptions,
+                    s.targets, s.attempts, s.completions, s.interceptions, {}, s.def_sacks, s.target_share, t.id, s.row_hash, NOW(), NOW()
+             FROM UNNEST($1::text[], $2::int[], $3::int[], $4::text[], $5::int[], $6::int[], $7::int[], $8::int[],
+                         $9::int[], $10::int[], $11::int[], $12::int[], $13::int[], $14::int[], $15::int[],
+                         {}, ${}::float8[], ${}::float8[], ${}::text[], ${}::text[])
+                  WITH ORDINALITY AS s (player_nfl_id, season, week, season_type, passing_yards, rushing_yards,
+                      receiving_yards, passing_tds, rushing_tds, receiving_tds, receptions, targets, attempts,
+                      completions, interceptions, {}, def_sacks, target_share, team_abbr, row_hash, ord)
+             JOIN players p ON p.nfl_id = s.player_nfl_id
+             LEFT JOIN teams t ON t.abbreviation = s.team_abbr
+             ORDER BY p.id, s.season, s.week, s.season_type, s.ord DESC
+             ON CONFLICT (player_id, season, week, season_type) DO UPDATE SET
+                 passing_yards = EXCLUDED.passing_yards,
+                 rushing_yards = EXCLUDED.rushing_yards,
+                 receiving_yards = EXCLUDED.receiving_yards,
+                 passing_tds = EXCLUDED.passing_tds,
+                 rushing_tds = EXCLUDED.rushing_tds,
+                 receiving_tds = EXCLUDED.receiving_tds,
+                 receptions = EXCLUDED.receptions,
+                 targets = EXCLUDED.targets,
+                 attempts = EXCLUDED.attempts,
+                 completions = EXCLUDED.completions,
+                 interceptions = EXCLUDED.interceptions,
+                 {},
+                 def_sacks = EXCLUDED.def_sacks,
+                 target_share = EXCLUDED.target_share,
+                 team_id = COALESCE(EXCLUDED.team_id, game_stats.team_id),
+                 row_hash = EXCLUDED.row_hash,
+                 updated_at = NOW()",
+                defense_kicking_sql("{}"),
+                defense_kicking_sql("s.{}"),
+                extra_arrays,
+                def_sacks_param,
+                def_sacks_param + 1,
+                def_sacks_param + 2,
+                def_sacks_param + 3,
+                defense_kicking_sql("{}"),
+                defense_kicking_sql("{} = EXCLUDED.{}"),
+            ),
+            &params,
+        )
+        .context("Batch stat upsert failed")
+}
+
+/// Upsert a batch of games in one statement; games whose teams are not in the
+/// teams table are skipped
+pub fn unnest_games(client: &mut Client, games: &[Game], precedence: &Precedence, source: Source) -> Result<u64> {
+    let lines = |values: fn(&Game) -> Option<f64>| -> Vec<Option<f64>> { games.iter().map(values).collect() };
+    let game_ids: Vec<&str> = games.iter().map(|g| g.game_id.as_str()).collect();
+    let seasons: Vec<i32> = games.iter().map(|g| g.season).collect();
+    let weeks: Vec<i32> = games.iter().map(|g| g.week).collect();
+    let season_types: Vec<&str> = games
+        .iter()
+        .map(|g| transformer::season_type_for_game_type(&g.game_type))
+        .collect();
+    let kickoffs: Vec<Option<DateTime<Utc>>> = games
+        .iter()
+        .map(|g| transformer::kickoff(&g.gameday, g.gametime.as_deref()))
+        .collect();
+    let home_teams: Vec<String> = games.iter().map(|g| transformer::normalize_team_abbr(&g.home_team)).collect();
+    let away_teams: Vec<String> = games.iter().map(|g| transformer::normalize_team_abbr(&g.away_team)).collect();
+    let home_scores: Vec<Option<i32>> = games.iter().map(|g| g.home_score).collect();
+    let away_scores: Vec<Option<i32>> = games.iter().map(|g| g.away_score).collect();
+    let game_times: Vec<Option<&str>> = games.iter().map(|g| g.gametime.as_deref()).collect();
+    let roofs: Vec<Option<&str>> = games.iter().map(|g| g.roof.as_deref()).collect();
+    let surfaces: Vec<Option<&str>> = games.iter().map(|g| g.surface.as_deref()).collect();
+    let venues: Vec<Option<&str>> = games.iter().map(|g| g.stadium.as_deref()).collect();
+    let referees: Vec<Option<&str>> = games.iter().map(|g| g.referee.as_deref()).collect();
+    let home_rests: Vec<Option<i32>> = games.iter().map(|g| g.home_rest).collect();
+    let away_rests: Vec<Option<i32>> = games.iter().map(|g| g.away_rest).collect();
+    let locations: Vec<Option<&str>> = games.iter().map(|g| g.location.as_deref()).collect();
+    let statuses: Vec<&str> = games.iter().map(|g| GameStatus::of(g).as_str()).collect();
+    let settings = row_hashes::merge_settings(precedence, source);
+    let hashes: Vec<String> = games.iter().map(|g| row_hashes::content_hash(g, &settings)).collect();
+
+    client
+        .execute(
+            &format!(
+                "INSERT INTO games (id, nfl_game_id, season, week, season_type, game_date, home_team_id, away_team_id, home_score, away_score, status,
+                     game_time_et, spread_line, total_line, home_moneyline, away_moneyline, home_spread_odds, away_spread_odds,
+                     over_odds, under_odds, roof, surface, weather_temp, weather_wind_speed, venue_name, referee, home_rest, away_rest,
+                     location, provenance, row_hash, created_at, updated_at)
+                 SELECT DISTINCT ON (s.nfl_game_id)
+                        uuid_generate_v4(), s.nfl_game_id, s.season, s.week, s.season_type, s.game_date,
+                        ht.id, at.id, s.home_score, s.away_score, s.status,
+                        s.game_time_et, s.spread_line, s.total_line, s.home_moneyline, s.away_moneyline, s.home_spread_odds,
+                        s.away_spread_odds, s.over_odds, s.under_odds, s.roof, s.surface, round(s.temp), round(s.wind),
+                        s.venue_name, s.referee, s.home_rest, s.away_rest, s.location, {provenance}, s.row_hash, NOW(), NOW()
+                 FROM UNNEST($1::text[], $2::int[], $3::int[], $4::text[], $5::timestamptz[], $6::text[], $7::text[], $8::int[],
+                             $9::int[], $10::text[], $11::float8[], $12::float8[], $13::float8[], $14::float8[], $15::float8[],
+                             $16::float8[], $17::float8[], $18::float8[], $19::text[], $20::text[], $21::float8[],
+                             $22::float8[], $23::text[], $24::text[], $25::int[], $26::int[], $27::text[], $28::text[],
+                             $29::text[])
+                      WITH ORDINALITY AS s (nfl_game_id, season, week, season_type, game_date, home_abbr, away_abbr,
+                          home_score, away_score, game_time_et, spread_line, total_line, home_moneyline, away_moneyline,
+                          home_spread_odds, away_spread_odds, over_odds, under_odds, roof, surface, temp, wind,
+                          venue_name, referee, home_rest, away_rest, location, status, row_hash, ord)
+                 JOIN teams ht ON ht.abbreviation = s.home_abbr
+                 JOIN teams at ON at.abbreviation = s.away_abbr
+                 ORDER BY s.nfl_game_id, s.ord DESC
+                 ON CONFLICT (nfl_game_id) DO UPDATE SET
+                     {merge},
+                     status = {status},
+                     game_time_et = COALESCE(EXCLUDED.game_time_et, games.game_time_et),
+                     spread_line = COALESCE(EXCLUDED.spread_line, games.spread_line),
+                     total_line = COALESCE(EXCLUDED.total_line, games.total_line),
+                     home_moneyline = COALESCE(EXCLUDED.home_moneyline, games.home_moneyline),
+                     away_moneyline = COALESCE(EXCLUDED.away_moneyline, games.away_moneyline),
+                     home_spread_odds = COALESCE(EXCLUDED.home_spread_odds, games.home_spread_odds),
+                     away_spread_odds = COALESCE(EXCLUDED.away_spread_odds, games.away_spread_odds),
+                     over_odds = COALESCE(EXCLUDED.over_odds, games.over_odds),
+                     under_odds = COALESCE(EXCLUDED.under_odds, games.under_odds),
+                     roof = COALESCE(EXCLUDED.roof, games.roof),
+                     surface = COALESCE(EXCLUDED.surface, games.surface),
+                     weather_temp = COALESCE(EXCLUDED.weather_temp, games.weather_temp),
+                     weather_wind_speed = COALESCE(EXCLUDED.weather_wind_speed, games.weather_wind_speed),
+                     referee = COALESCE(EXCLUDED.referee, games.referee),
+                     home_rest = COALESCE(EXCLUDED.home_rest, games.home_rest),
+                     away_rest = COALESCE(EXCLUDED.away_rest, games.away_rest),
+                     location = COALESCE(EXCLUDED.location, games.location),
+                     row_hash = EXCLUDED.row_hash,
+                     updated_at = NOW()",
+                provenance = precedence::insert_sql(source, &[("season_type", "s.season_type"), ("week", "s.week"), ("game_date", "s.game_date"), ("home_score", "s.home_score"), ("away_score", "s.away_score"), ("venue_name", "s.venue_name")]),
+                merge = precedence::schedule_upsert_sql(precedence, source),
+                status = game_status::merge_sql("games.status", "EXCLUDED.status")
+            ),
+            &[
+                &game_ids,
+                &seasons,
+                &weeks,
+                &season_types,
+                &kickoffs,
+                &home_teams,
+                &away_teams,
+                &home_scores,
+                &away_scores,
+                &game_times,
+                &lines(|g| g.spread_line),
+                &lines(|g| g.total_line),
+                &lines(|g| g.home_moneyline),
+                &lines(|g| g.away_moneyline),
+                &lines(|g| g.home_spread_odds),
+                &lines(|g| g.away_spread_odds),
+                &lines(|g| g.over_odds),
+                &lines(|g| g.under_odds),
+                &roofs,
+                &surfaces,
+                &lines(|g| g.temp),
+                &lines(|g| g.wind),
+                &venues,
+                &referees,
+                &home_rests,
+                &away_rests,
+                &locations,
+                &statuses,
+                &hashes,
+            ],
+        )
+        .context("Batch game upsert failed")
+}
+
+/// Create a temp table, stream rows into it with COPY and merge them in one transaction.
+/// Returns the number of rows affected by the merge statement.
+fn copy_and_merge(
+    client: &mut Client,
+    create_sql: &str,
+    copy_sql: &str,
+    merge_sql: &str,
+    rows: &[Vec<String>],
+) -> Result<u64> {
+    copy_and_merge_with(client, create_sql, copy_sql, merge_sql, &[], rows)
+}
+
+/// `copy_and_merge`, then run `follow_up_sql` against the same temp table before it
+/// is dropped. Returns the row count of the main merge.
+fn copy_and_merge_with(
+    client: &mut Client,
+    create_sql: &str,
+    copy_sql: &str,
+    merge_sql: &str,
+    follow_up_sql: &[&str],
+    rows: &[Vec<String>],
+) -> Result<u64> {
+    let mut tx = client.transaction()?;
+    tx.batch_execute(create_sql)?;
+
+    {
+        let writer = tx.copy_in(copy_sql)?;
+        let mut csv_writer = csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(writer);
+        for row in rows {
+            csv_writer.write_record(row)?;
+        }
+        let mut writer = csv_writer
+            .into_inner()
+            .map_err(|e| anyhow::anyhow!("Failed to flush COPY data: {}", e))?;
+        writer.flush()?;
+        writer.finish().context("COPY failed")?;
+    }
+
+    let merged = tx.execute(merge_sql, &[]).context("Merge from temp table failed")?;
+    for sql in follow_up_sql {
+        tx.execute(*sql, &[]).context("Follow-up merge from temp table failed")?;
+    }
+    tx.commit()?;
+
+    Ok(merged)
+}
+
+/// Render an optional value as a CSV COPY field (empty = NULL)
+fn copy_field<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(|v| v.to_string()).unwrap_or_default()
+}
@@ -0,0 +1,102 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Validators the server sent with a cached file, replayed as conditional headers
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+/// Downloaded files on disk, keyed by URL (`--cache-dir`)
+pub struct DownloadCache {
+    dir: PathBuf,
+}
+
+impl DownloadCache {
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create cache directory {}", dir.display()))?;
+        Ok(DownloadCache { dir })
+    }
+
+    /// Cached entry for a URL, if both the file and its validators are present
+    pub fn lookup(&self, url: &str) -> Option<CacheEntry> {
+        let (body, meta) = self.paths(url);
+        if !body.exists() {
+            return None;
+        }
+        let entry: CacheEntry = serde_json::from_slice(&fs::read(meta).ok()?).ok()?;
+        (entry.url == url).then_some(entry)
+    }
+
+    /// Path of a cached file, if present
+    pub fn path(&self, url: &str) -> Option<PathBuf> {
+        self.lookup(url).map(|_| self.paths(url).0)
+    }
+
+    pub fn open(&self, url: &str) -> Result<File> {
+        let (body, _) = self.paths(url);
+        File::open(&body).with_context(|| format!("Failed to open cached file {}", body.display()))
+    }
+
+    /// Write a response body and its validators, replacing any previous copy.
+    /// The body goes to a temp file first so an interrupted download never
+    /// leaves a truncated file behind.
+    pub fn store(&self, entry: &CacheEntry, mut body: impl Read) -> Result<File> {
+        let (path, meta) = self.paths(&entry.url);
+        let partial = PathBuf::from(format!("{}.partial", path.display()));
+
+        let mut file = File::create(&partial)
+            .with_context(|| format!("Failed to create cache file {}", partial.display()))?;
+        io::copy(&mut body, &mut file).context("Failed to read response")?;
+        fs::rename(&partial, &path)?;
+        fs::write(&meta, serde_json::to_vec(entry)?)?;
+
+        self.open(&entry.url)
+    }
+
+    /// File and metadata paths for a URL. The key is the URL with every character
+    /// outside `[A-Za-z0-9.-]` replaced, so entries stay readable in the directory.
+    fn paths(&self, url: &str) -> (PathBuf, PathBuf) {
+        let key: String = url
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+            .collect();
+        (self.dir.join(&key), self.dir.join(format!("{}.meta.json", key)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_and_lookup_round_trip() {
+        let dir = std::env::temp_dir().join(format!("nfl-pipeline-cache-test-{}", std::process::id()));
+        let cache = DownloadCache::new(&dir).unwrap();
+        let url = "https://github.com/nflverse/nflverse-data/releases/download/injuries/injuries_2024.csv";
+        assert!(cache.lookup(url).is_none());
+
+        let entry = CacheEntry {
+            url: url.to_string(),
+            etag: Some("\"abc123\"".to_string()),
+            last_modified: None,
+        };
+        let mut file = cache.store(&entry, "season,week\n2024,1\n".as_bytes()).unwrap();
+        let mut contents = String::new();
+        file.read_to_string(&mut contents).unwrap();
+
+        assert_eq!(contents, "season,week\n2024,1\n");
+        assert_eq!(cache.lookup(url), Some(entry));
+        assert!(dir.join("github.com_nflverse_nflverse-data_releases_download_injuries_injuries_2024.csv").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
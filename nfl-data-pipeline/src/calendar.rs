@@ -0,0 +1,246 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::parser::Game;
+use crate::transformer;
+
+/// Schedule times in nflverse are Eastern
+const SCHEDULE_TZID: &str = "America/New_York";
+/// Calendar block reserved for a game
+const GAME_DURATION: &str = "PT3H30M";
+
+/// A game or bye on a team's calendar
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CalendarEvent {
+    Game {
+        game_id: String,
+        week: i32,
+        season_type: String,
+        /// Date as YYYY-MM-DD
+        date: String,
+        /// Kickoff as HH:MM Eastern, when scheduled
+        kickoff_et: Option<String>,
+        opponent: String,
+        home: bool,
+        stadium: Option<String>,
+    },
+    Bye {
+        week: i32,
+        /// First game date of the week, used to place the bye on a calendar
+        week_start: Option<String>,
+    },
+}
+
+/// A team's season availability calendar
+#[derive(Debug, Clone, Serialize)]
+pub struct TeamCalendar {
+    pub team: String,
+    pub season: i32,
+    pub events: Vec<CalendarEvent>,
+    /// Source credit lines (`--attribution`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attribution: Vec<String>,
+}
+
+/// Build per-team calendars from a season's schedule.
+/// Bye weeks are regular-season weeks in which a team has no game.
+pub fn build_team_calendars(games: &[Game]) -> Vec<TeamCalendar> {
+    let mut by_team: BTreeMap<String, Vec<&Game>> = BTreeMap::new();
+    let mut week_starts: BTreeMap<i32, String> = BTreeMap::new();
+    let mut reg_weeks = BTreeSet::new();
+
+    for game in games {
+        for team in [&game.home_team, &game.away_team] {
+            by_team
+                .entry(transformer::normalize_team_abbr(team))
+                .or_default()
+                .push(game);
+        }
+        if game.game_type == "REG" {
+            reg_weeks.insert(game.week);
+            let start = week_starts.entry(game.week).or_insert_with(|| game.gameday.clone());
+            if game.gameday < *start {
+                *start = game.gameday.clone();
+            }
+        }
+    }
+
+    by_team
+        .into_iter()
+        .map(|(team, team_games)| {
+            let season = team_games.first().map(|g| g.season).unwrap_or_default();
+            let played: BTreeSet<i32> = team_games
+                .iter()
+                .filter(|g| g.game_type == "REG")
+                .map(|g| g.week)
+                .collect();
+
+            let mut events: Vec<CalendarEvent> = team_games
+                .iter()
+                .map(|game| {
+                    let home = transformer::normalize_team_abbr(&game.home_team) == team;
+                    let opponent = if home { &game.away_team } else { &game.home_team };
+                    CalendarEvent::Game {
+                        game_id: game.game_id.clone(),
+                        week: game.week,
+                        season_type: transformer::season_type_for_game_type(&game.game_type).to_string(),
+                        date: game.gameday.clone(),
+                        kickoff_et: game.gametime.clone(),
+                        opponent: transformer::normalize_team_abbr(opponent),
+                        home,
+                        stadium: game.stadium.clone(),
+                    }
+                })
+                .collect();
+
+            for week in reg_weeks.difference(&played) {
+                events.push(CalendarEvent::Bye {
+                    week: *week,
+                    week_start: week_starts.get(week).cloned(),
+                });
+            }
+
+            events.sort_by_key(|e| match e {
+                CalendarEvent::Game { date, .. } => date.clone(),
+                CalendarEvent::Bye { week_start, .. } => week_start.clone().unwrap_or_default(),
+            });
+
+            TeamCalendar { team, season, events, attribution: Vec::new() }
+        })
+        .collect()
+}
+
+/// Render a team calendar as an iCalendar (RFC 5545) document
+pub fn to_ics(calendar: &TeamCalendar) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Grid Iron Mind//NFL Data Pipeline//EN".to_string(),
+        format!("X-WR-CALNAME:{} {} Schedule", calendar.team, calendar.season),
+    ];
+    if !calendar.attribution.is_empty() {
+        // Line breaks in text values are written as \n
+        let credits: Vec<String> = calendar.attribution.iter().map(|line| escape_text(line)).collect();
+        lines.push(format!("X-WR-CALDESC:{}", credits.join("\\n")));
+    }
+
+    for event in &calendar.events {
+        match event {
+            CalendarEvent::Game { game_id, date, kickoff_et, opponent, home, stadium, .. } => {
+                let summary = if *home {
+                    format!("{} vs {}", calendar.team, opponent)
+                } else {
+                    format!("{} @ {}", calendar.team, opponent)
+                };
+                lines.push("BEGIN:VEVENT".to_string());
+                lines.push(format!("UID:{}-{}@gridironmind", game_id, calendar.team));
+                match kickoff_et {
+                    Some(time) => {
+                        lines.push(format!(
+                            "DTSTART;TZID={}:{}T{}00",
+                            SCHEDULE_TZID,
+                            date.replace('-', ""),
+                            time.replace(':', "")
+                        ));
+                        lines.push(format!("DURATION:{}", GAME_DURATION));
+                    }
+                    None => lines.push(format!("DTSTART;VALUE=DATE:{}", date.replace('-', ""))),
+                }
+                lines.push(format!("SUMMARY:{}", summary));
+                if let Some(stadium) = stadium {
+                    lines.push(format!("LOCATION:{}", escape_text(stadium)));
+                }
+                lines.push("END:VEVENT".to_string());
+            }
+            CalendarEvent::Bye { week, week_start } => {
+                let Some(start) = week_start else { continue };
+                lines.push("BEGIN:VEVENT".to_string());
+                lines.push(format!("UID:{}-{}-bye-{}@gridironmind", calendar.season, calendar.team, week));
+                lines.push(format!("DTSTART;VALUE=DATE:{}", start.replace('-', "")));
+                lines.push(format!("SUMMARY:{} bye (week {})", calendar.team, week));
+                lines.push("TRANSP:TRANSPARENT".to_string());
+                lines.push("END:VEVENT".to_string());
+            }
+        }
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+    lines.join("\r\n") + "\r\n"
+}
+
+/// Escape commas, semicolons and backslashes in iCalendar text values
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace(';', "\\;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule() -> Vec<Game> {
+        let csv = "\
+game_id,season,game_type,week,gameday,gametime,away_team,home_team,stadium
+2024_01_BAL_KC,2024,REG,1,2024-09-05,20:20,BAL,KC,GEHA Field at Arrowhead Stadium
+2024_01_LA_DET,2024,REG,1,2024-09-08,20:20,LA,DET,Ford Field
+2024_02_KC_DET,2024,REG,2,2024-09-15,13:00,KC,DET,Ford Field
+2024_02_BAL_LA,2024,REG,2,2024-09-15,16:25,BAL,LA,SoFi Stadium
+";
+        csv::Reader::from_reader(csv.as_bytes())
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_byes_fill_missing_regular_season_weeks() {
+        let calendars = build_team_calendars(&schedule());
+        assert_eq!(calendars.len(), 4);
+
+        let kc = calendars.iter().find(|c| c.team == "KC").unwrap();
+        assert_eq!(kc.events.len(), 2);
+
+        let det = calendars.iter().find(|c| c.team == "DET").unwrap();
+        assert!(det.events.iter().all(|e| matches!(e, CalendarEvent::Game { .. })));
+
+        let la = calendars.iter().find(|c| c.team == "LA").unwrap();
+        assert!(matches!(
+            &la.events[1],
+            CalendarEvent::Game { opponent, home: true, .. } if opponent == "BAL"
+        ));
+    }
+
+    #[test]
+    fn test_bye_week_is_placed_on_week_start() {
+        let mut games = schedule();
+        games.retain(|g| g.game_id != "2024_02_KC_DET");
+        let calendars = build_team_calendars(&games);
+
+        let kc = calendars.iter().find(|c| c.team == "KC").unwrap();
+        assert_eq!(
+            kc.events[1],
+            CalendarEvent::Bye { week: 2, week_start: Some("2024-09-15".to_string()) }
+        );
+    }
+
+    #[test]
+    fn test_ics_output() {
+        let calendars = build_team_calendars(&schedule());
+        let kc = calendars.iter().find(|c| c.team == "KC").unwrap();
+        let ics = to_ics(kc);
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.contains("DTSTART;TZID=America/New_York:20240905T202000\r\n"));
+        assert!(ics.contains("SUMMARY:KC vs BAL\r\n"));
+        assert!(ics.contains("SUMMARY:KC @ DET\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(!ics.contains("X-WR-CALDESC"));
+
+        let mut credited = kc.clone();
+        credited.attribution = vec!["Data from nflverse, CC BY 4.0".to_string(), "Scores: ESPN".to_string()];
+        assert!(to_ics(&credited).contains("X-WR-CALDESC:Data from nflverse\\, CC BY 4.0\\nScores: ESPN\r\n"));
+    }
+}
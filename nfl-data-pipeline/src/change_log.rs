@@ -0,0 +1,102 @@
+use anyhow::{Context, Result};
+use log::info;
+use postgres::Client;
+
+/// Changes listed by `--mode changes`
+const REPORT_LIMIT: i64 = 500;
+
+/// Tables whose updates are recorded in `change_log`
+pub const TRACKED_TABLES: [&str; 3] = ["players", "games", "game_stats"];
+
+/// One field an update changed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub table: String,
+    /// Player name, game ID, or player and week for a stat line
+    pub row: String,
+    pub field: String,
+    /// JSON text of the value; `None` for NULL
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_at: String,
+}
+
+/// Changes recorded in the last `since_days` days, newest first, optionally
+/// for one table
+pub fn recent(client: &mut Client, since_days: i32, table: Option<&str>) -> Result<Vec<Change>> {
+    let rows = client
+        .query(
+            "SELECT c.table_name,
+                    COALESCE(p.name, sp.name || ' ' || gs.season || ' week ' || gs.week, g.nfl_game_id, c.row_id::text),
+                    c.field, c.old_value::text, c.new_value::text, to_char(c.changed_at, 'YYYY-MM-DD HH24:MI')
+             FROM change_log c
+             LEFT JOIN players p ON c.table_name = 'players' AND p.id = c.row_id
+             LEFT JOIN games g ON c.table_name = 'games' AND g.id = c.row_id
+             LEFT JOIN game_stats gs ON c.table_name = 'game_stats' AND gs.id = c.row_id
+             LEFT JOIN players sp ON sp.id = gs.player_id
+             WHERE c.changed_at >= NOW() - make_interval(days => $1)
+               AND ($2::text IS NULL OR c.table_name = $2)
+             ORDER BY c.changed_at DESC, c.id DESC
+             LIMIT $3",
+            &[&since_days, &table, &REPORT_LIMIT],
+        )
+        .context("Failed to load the change log")?;
+
+    Ok(rows
+        .iter()
+        .map(|row| Change {
+            table: row.get(0),
+            row: row.get(1),
+            field: row.get(2),
+            old_value: row.get(3),
+            new_value: row.get(4),
+            changed_at: row.get(5),
+        })
+        .collect())
+}
+
+/// Delete changes older than `retain_days`; returns the number removed
+pub fn prune(client: &mut Client, retain_days: i32) -> Result<u64> {
+    client
+        .execute("DELETE FROM change_log WHERE changed_at < NOW() - make_interval(days => $1)", &[&retain_days])
+        .context("Failed to prune the change log")
+}
+
+/// A logged value: strings without their JSON quotes, NULL as ∅
+fn display(value: Option<&str>) -> &str {
+    match value {
+        None => "∅",
+        Some(value) => value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value),
+    }
+}
+
+pub fn log_report(changes: &[Change], since_days: i32) {
+    info!("📝 Field changes in the last {} days: {}", since_days, changes.len());
+    for change in changes {
+        info!(
+            "  {} {} {}: {} {} → {}",
+            change.changed_at,
+            change.table,
+            change.row,
+            change.field,
+            display(change.old_value.as_deref()),
+            display(change.new_value.as_deref())
+        );
+    }
+    if changes.len() as i64 == REPORT_LIMIT {
+        info!("  … only the latest {} are listed; query change_log for the rest", REPORT_LIMIT);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_values() {
+        assert_eq!(display(None), "∅");
+        assert_eq!(display(Some("88")), "88");
+        assert_eq!(display(Some("\"KC\"")), "KC");
+        assert_eq!(display(Some("\"2024-09-06T00:20:00+00:00\"")), "2024-09-06T00:20:00+00:00");
+    }
+}
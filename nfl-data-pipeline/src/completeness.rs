@@ -0,0 +1,234 @@
+use anyhow::{Context, Result};
+use postgres::Client;
+use serde::Serialize;
+
+/// What a season is missing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GapKind {
+    /// Fewer (or more) regular season games than the era schedules
+    GameCount,
+    /// A regular season week without a single game
+    MissingWeek,
+    /// A final game without player stats for one of its teams
+    MissingTeamStats,
+    /// A team's player stats can't add up to its final score
+    ScoreMismatch,
+}
+
+/// One specific gap, e.g. week 7 of 2019, or BUF's stats for 2023_05_BUF_JAX
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Gap {
+    pub season: i32,
+    pub kind: GapKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub week: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub game_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub team: Option<String>,
+    pub detail: String,
+}
+
+/// A season's counts and the gaps found in it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SeasonAudit {
+    pub season: i32,
+    pub regular_games: i64,
+    pub expected_regular_games: i64,
+    pub final_games: i64,
+    pub gaps: Vec<Gap>,
+}
+
+/// Points a team's player stats leave unexplained beyond which they are
+/// incomplete: more than eight field goals' worth (the single-game record)
+const MAX_UNEXPLAINED_POINTS: i32 = 24;
+
+/// Regular season weeks: 17 until the 2021 expansion, 18 since
+pub fn regular_weeks(season: i32) -> i32 {
+    if season >= 2021 { 18 } else { 17 }
+}
+
+/// Regular season games on the schedule: 31 teams until Houston joined in 2002,
+/// 16 games each until 2021 and 17 since. 2022's BUF-CIN week 17 game was
+/// canceled and never replayed.
+pub fn expected_regular_games(season: i32) -> i64 {
+    match season {
+        ..=2001 => 248,
+        2002..=2020 => 256,
+        2022 => 271,
+        _ => 272,
+    }
+}
+
+/// Game count and week gaps of a season from its regular season game count and
+/// the weeks that have games. A season without games is one gap, not one per week.
+fn schedule_gaps(season: i32, games: i64, weeks: &[i32]) -> Vec<Gap> {
+    let mut gaps = Vec::new();
+    let expected = expected_regular_games(season);
+    if games != expected {
+        gaps.push(Gap {
+            season,
+            kind: GapKind::GameCount,
+            week: None,
+            game_id: None,
+            team: None,
+            detail: format!("{} regular season games, expected {}", games, expected),
+        });
+    }
+    if games == 0 {
+        return gaps;
+    }
+    for week in (1..=regular_weeks(season)).filter(|week| !weeks.contains(week)) {
+        gaps.push(Gap {
+            season,
+            kind: GapKind::MissingWeek,
+            week: Some(week),
+            game_id: None,
+            team: None,
+            detail: format!("no games in week {}", week),
+        });
+    }
+    gaps
+}
+
+/// Why a team's player stats don't fit its final score, if they obviously don't.
+///
+/// Touchdowns (rushing, receiving, return and defensive), two-point conversions
+/// and safeties are worth at least `6 × TDs + 2 × (2PT + safeties)`; more than
+/// the score means stats were counted twice. Assuming an extra point per
+/// touchdown, whatever is left is field goals, and more than 24 points of those
+/// means stat lines are missing.
+fn score_mismatch(score: i32, touchdowns: i32, two_point: i32, safeties: i32) -> Option<String> {
+    let floor = 6 * touchdowns + 2 * (two_point + safeties);
+    if floor > score {
+        return Some(format!("player stats have {} TDs ({}+ points) but the team scored {}", touchdowns, floor, score));
+    }
+    let unexplained = score - floor - touchdowns;
+    (unexplained > MAX_UNEXPLAINED_POINTS)
+        .then(|| format!("team scored {} but player stats have {} TDs ({} points unexplained)", score, touchdowns, unexplained))
+}
+
+/// Check a season's schedule against its era and every final game (regular
+/// season and playoffs) for player stats on both sides that add up to its
+/// score. Stats are matched on season, week, season type and team, since
+/// `game_stats.game_id` is only set when the game was imported first.
+pub fn audit(client: &mut Client, season: i32) -> Result<SeasonAudit> {
+    let row = client
+        .query_one(
+            "SELECT COUNT(*) FILTER (WHERE season_type = 'REG'),
+                    COUNT(*) FILTER (WHERE status = 'final'),
+                    COALESCE(ARRAY_AGG(DISTINCT week) FILTER (WHERE season_type = 'REG'), '{}')
+             FROM games WHERE season = $1",
+            &[&season],
+        )
+        .context("Failed to count games")?;
+    let (regular_games, final_games, weeks): (i64, i64, Vec<i32>) = (row.get(0), row.get(1), row.get(2));
+    let mut gaps = schedule_gaps(season, regular_games, &weeks);
+
+    let missing = client
+        .query(
+            "SELECT g.nfl_game_id, g.week, t.abbreviation
+             FROM games g
+             CROSS JOIN LATERAL (VALUES (g.home_team_id), (g.away_team_id)) AS side(team_id)
+             JOIN teams t ON t.id = side.team_id
+             WHERE g.season = $1 AND g.status = 'final'
+               AND NOT EXISTS (
+                   SELECT 1 FROM game_stats s
+                   WHERE s.season = g.season AND s.week = g.week
+                     AND s.season_type = g.season_type AND s.team_id = side.team_id
+               )
+             ORDER BY g.week, g.nfl_game_id, t.abbreviation",
+            &[&season],
+        )
+        .context("Failed to check games for player stats")?;
+    gaps.extend(missing.into_iter().map(|row| {
+        let (game_id, team): (String, String) = (row.get(0), row.get(2));
+        Gap {
+            season,
+            kind: GapKind::MissingTeamStats,
+            week: Some(row.get(1)),
+            detail: format!("{} has no player stats for {}", game_id, team),
+            game_id: Some(game_id),
+            team: Some(team),
+        }
+    }));
+
+    let totals = client
+        .query(
+            "SELECT g.nfl_game_id, g.week, t.abbreviation, side.score,
+                    SUM(COALESCE(s.rushing_tds, 0) + COALESCE(s.receiving_tds, 0)
+                        + COALESCE(s.special_teams_tds, 0) + COALESCE(s.def_tds, 0))::int,
+                    SUM(COALESCE(s.two_point_conversions, 0))::int,
+                    SUM(COALESCE(s.def_safeties, 0))::int
+             FROM games g
+             CROSS JOIN LATERAL (VALUES (g.home_team_id, g.home_score), (g.away_team_id, g.away_score)) AS side(team_id, score)
+             JOIN teams t ON t.id = side.team_id
+             JOIN game_stats s
+               ON s.season = g.season AND s.week = g.week
+              AND s.season_type = g.season_type AND s.team_id = side.team_id
+             WHERE g.season = $1 AND g.status = 'final' AND side.score IS NOT NULL
+             GROUP BY g.nfl_game_id, g.week, t.abbreviation, side.score
+             ORDER BY g.week, g.nfl_game_id, t.abbreviation",
+            &[&season],
+        )
+        .context("Failed to reconcile box scores")?;
+    for row in totals {
+        let (score, touchdowns, two_point, safeties): (i32, i32, i32, i32) = (row.get(3), row.get(4), row.get(5), row.get(6));
+        if let Some(mismatch) = score_mismatch(score, touchdowns, two_point, safeties) {
+            let (game_id, team): (String, String) = (row.get(0), row.get(2));
+            gaps.push(Gap {
+                season,
+                kind: GapKind::ScoreMismatch,
+                week: Some(row.get(1)),
+                detail: format!("{} {}: {}", game_id, team, mismatch),
+                game_id: Some(game_id),
+                team: Some(team),
+            });
+        }
+    }
+
+    Ok(SeasonAudit { season, regular_games, expected_regular_games: expected_regular_games(season), final_games, gaps })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expected_schedule_by_era() {
+        assert_eq!((expected_regular_games(2001), regular_weeks(2001)), (248, 17));
+        assert_eq!((expected_regular_games(2020), regular_weeks(2020)), (256, 17));
+        assert_eq!((expected_regular_games(2022), regular_weeks(2022)), (271, 18));
+        assert_eq!((expected_regular_games(2024), regular_weeks(2024)), (272, 18));
+    }
+
+    #[test]
+    fn test_schedule_gaps() {
+        let weeks: Vec<i32> = (1..=18).filter(|week| *week != 7).collect();
+        let gaps = schedule_gaps(2024, 256, &weeks);
+        assert_eq!(gaps.len(), 2);
+        assert_eq!(gaps[0].kind, GapKind::GameCount);
+        assert_eq!(gaps[0].detail, "256 regular season games, expected 272");
+        assert_eq!((gaps[1].kind, gaps[1].week), (GapKind::MissingWeek, Some(7)));
+
+        let full: Vec<i32> = (1..=17).collect();
+        assert!(schedule_gaps(2019, 256, &full).is_empty());
+        assert_eq!(schedule_gaps(2019, 0, &[]).len(), 1);
+    }
+
+    #[test]
+    fn test_score_mismatch() {
+        // 4 TDs, a 2PT conversion and 3 field goals
+        assert_eq!(score_mismatch(35, 4, 1, 0), None);
+        assert_eq!(score_mismatch(3, 0, 0, 0), None);
+        assert_eq!(
+            score_mismatch(17, 4, 0, 0).as_deref(),
+            Some("player stats have 4 TDs (24+ points) but the team scored 17")
+        );
+        assert_eq!(
+            score_mismatch(31, 0, 0, 0).as_deref(),
+            Some("team scored 31 but player stats have 0 TDs (31 points unexplained)")
+        );
+    }
+}
@@ -1,54 +1,888 @@
-use anyhow::{Result, Context};
+use anyhow::{Result, anyhow};
 use std::env;
 
+use crate::api;
+use crate::api_keys;
+use crate::archive;
+use crate::datasets::SourceFormat;
+use crate::espn;
+use crate::espn_ids;
+use crate::k8s;
+use crate::live;
+use crate::logging::LogFormat;
+use crate::news;
+use crate::odds;
+use crate::precedence::Precedence;
+use crate::sleeper;
+
+/// How parsed batches are written to PostgreSQL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadMethod {
+    /// One INSERT ... ON CONFLICT statement per row
+    Insert,
+    /// One INSERT ... SELECT FROM UNNEST statement per batch, columns bound as arrays
+    Unnest,
+    /// COPY into a temp table, then a single merge statement per batch
+    Copy,
+}
+
+impl LoadMethod {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "insert" => Some(LoadMethod::Insert),
+            "unnest" => Some(LoadMethod::Unnest),
+            "copy" => Some(LoadMethod::Copy),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoadMethod::Insert => "insert",
+            LoadMethod::Unnest => "unnest",
+            LoadMethod::Copy => "copy",
+        }
+    }
+}
+
+/// Where `--mode year` and `full` write rosters, schedules and player stats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sink {
+    /// The full pipeline against `DATABASE_URL`
+    Postgres,
+    /// Flat tables in a SQLite file at `--out`, needing no database server
+    Sqlite,
+    /// Season-partitioned Parquet files under `--out`
+    Parquet,
+}
+
+impl Sink {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "postgres" => Some(Sink::Postgres),
+            "sqlite" => Some(Sink::Sqlite),
+            "parquet" => Some(Sink::Parquet),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Sink::Postgres => "postgres",
+            Sink::Sqlite => "sqlite",
+            Sink::Parquet => "parquet",
+        }
+    }
+}
+
+/// Which source wins when nflverse and ESPN both provide a value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourcePriority {
+    /// ESPN only fills values nflverse left empty
+    Nflverse,
+    /// ESPN values replace nflverse ones
+    Espn,
+}
+
+impl SourcePriority {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "nflverse" => Some(SourcePriority::Nflverse),
+            "espn" => Some(SourcePriority::Espn),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SourcePriority::Nflverse => "nflverse",
+            SourcePriority::Espn => "espn",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Config {
     pub database_url: String,
+    /// Further databases the same run writes to after `database_url`
+    pub fanout_database_urls: Vec<String>,
     pub mode: String,
     pub year: i32,
     pub start_year: i32,
     pub end_year: i32,
     pub dry_run: bool,
+    /// Write roster, schedule and stat upserts to this file instead of running
+    /// them (implies `dry_run`)
+    pub emit_sql: Option<String>,
+    /// Run against a throwaway copy of the schema instead of production tables
+    pub simulate: bool,
+    pub force: bool,
+    /// Fail a stage when more than this fraction of its rows fail to parse or write
+    pub max_error_rate: Option<f64>,
+    pub include_college: bool,
+    /// Season types to import: REG, POST, PRE
+    pub season_types: Vec<String>,
+    /// Rows per batch; the starting point when auto-tuning
     pub batch_size: usize,
+    /// Adjust batch size and active writers from measured upsert latency
+    pub auto_tune: bool,
+    /// Throttle writers and batch size when the server runs short of connections,
+    /// rows wait on locks or statements time out (small Heroku plans)
+    pub backpressure: bool,
+    pub min_batch_size: usize,
+    pub max_batch_size: usize,
     pub max_retries: u32,
+    pub load_method: LoadMethod,
+    pub sink: Sink,
+    /// Concurrent upsert workers for rosters, schedules and stats (1 writes inline)
+    pub writers: usize,
+    /// Restrict output modes to one team abbreviation
+    pub team: Option<String>,
+    /// Table `--mode export` dumps
+    pub entity: Option<String>,
+    /// Restrict `--mode export` to one season and week
+    pub season: Option<i32>,
+    pub week: Option<i32>,
+    /// Output format for export-style modes (e.g. ics, json)
+    pub format: Option<String>,
+    /// Format requested from nflverse when importing (`--format parquet`)
+    pub source_format: SourceFormat,
+    /// Output directory for export-style modes
+    pub out_dir: String,
+    /// Append source attribution to exports (calendars, Google Sheets)
+    pub attribution: bool,
+    /// `--mode migrate` on a database without migration history: versions up to
+    /// this one are recorded as already applied
+    pub baseline: Option<i32>,
+    /// Action for `--mode history` (enable, prune or disable; default enable),
+    /// `--mode views` (install, drop or status; default install) and
+    /// `--mode api-keys` (create, list or revoke; default list)
+    pub action: Option<String>,
+    /// Days of row history kept when pruning
+    pub history_retain_days: i32,
+    /// Memory budget for downloaded files and in-flight batches, in MB (unlimited when unset)
+    pub memory_budget_mb: Option<usize>,
+    /// Directory for cached downloads, revalidated on each run
+    pub cache_dir: Option<String>,
+    /// Copy every downloaded file here before parsing: a directory or `s3://bucket/prefix`
+    pub archive: Option<String>,
+    /// JSONL file collecting rows that fail to parse or write; read back by `--mode replay`
+    pub dead_letter: Option<String>,
+    /// Read source files from this directory instead of downloading them
+    pub source_dir: Option<String>,
+    /// SQL for `--mode query-raw`
+    pub sql: Option<String>,
+    /// JSON file mapping derived tables to Google Sheets tabs
+    pub sheets_config: Option<String>,
+    /// JSON file with custom fantasy scoring formats
+    pub scoring_config: Option<String>,
+    /// Daemon update interval on game days, in minutes
+    pub game_day_interval_mins: u64,
+    /// Daemon update interval on other days, in minutes
+    pub idle_interval_mins: u64,
+    /// `--mode live` poll interval while games are on, in seconds
+    pub live_interval_secs: u64,
+    /// Scoreboard polled by `--mode live`
+    pub scoreboard_url: String,
+    /// Plays with win probability below this (or above 1 minus it) count as garbage time
+    pub garbage_time_wp: f64,
+    /// `--mode unresolved` and `--mode changes` look back this many days
+    pub since_days: i32,
+    /// Text, or one JSON object per line
+    pub log_format: LogFormat,
+    /// Endpoint for anonymous run reports; nothing is sent unless set
+    pub telemetry_url: Option<String>,
+    /// Webhook (e.g. Slack) for run summaries and stage failure alerts
+    pub notify_url: Option<String>,
+    /// Directory of Handlebars files replacing the built-in notification text
+    pub notify_templates: Option<String>,
+    /// Serve Prometheus metrics on this address (e.g. `0.0.0.0:9184`)
+    pub metrics_addr: Option<String>,
+    /// `--mode serve` listens here
+    pub api_addr: String,
+    /// Pushgateway that batch runs push their metrics to when they finish
+    pub pushgateway_url: Option<String>,
+    /// Merge ESPN's schedule, box scores and rosters into season imports
+    pub espn: bool,
+    /// Base URL of ESPN's site API
+    pub espn_api_url: String,
+    pub source_priority: SourcePriority,
+    /// Which source wins each field of `games` and `players`
+    pub precedence: Precedence,
+    /// ESPN's athlete list, read by the ESPN ID backfill
+    pub espn_athletes_url: String,
+    /// Lowest match score the ESPN ID backfill links
+    pub min_confidence: f64,
+    /// Pull game recaps, headlines and player news with ESPN imports
+    pub include_news: bool,
+    /// Player news feed: ESPN's news API or any RSS feed
+    pub news_feed_url: String,
+    /// The Odds API key; odds polling is off without it
+    pub odds_api_key: Option<String>,
+    /// Odds endpoint polled by `--mode odds` and the daemon
+    pub odds_api_url: String,
+    /// Bookmakers to capture lines from (every US bookmaker when empty)
+    pub odds_bookmakers: Vec<String>,
+    /// Sleeper league imported by `--mode sleeper` and incremental updates
+    pub sleeper_league: Option<String>,
+    /// Base URL of Sleeper's API
+    pub sleeper_api_url: String,
+    /// Record the source file and row hash behind every imported row
+    pub lineage: bool,
+    /// Dataset `--mode lineage` looks up and `--mode purge` deletes
+    pub dataset: Option<String>,
+    /// `--mode lineage` lookup: row key prefix
+    pub lineage_key: Option<String>,
+    /// `--mode changes` lookup: only changes to this table
+    pub change_table: Option<String>,
+    /// `--mode api-keys --action create`: who the key is for, and its requests per minute
+    pub key_name: Option<String>,
+    pub key_rate_limit: i32,
+    /// `--mode api-keys --action revoke`: the key to revoke
+    pub key_id: Option<i32>,
+    /// Container image for `--mode generate-k8s` manifests
+    pub k8s_image: String,
+    pub k8s_namespace: Option<String>,
+    /// Secret holding DATABASE_URL (and FANOUT_DATABASE_URLS when fanning out)
+    pub k8s_secret: String,
+    /// Modes `--mode generate-k8s` emits a Job or CronJob for
+    pub k8s_jobs: Vec<k8s::JobSpec>,
 }
 
 impl Config {
     pub fn from_env() -> Result<Self> {
         // Parse command line arguments
         let args: Vec<String> = env::args().collect();
+        Self::from_args(&args, |key| env::var(key).ok())
+    }
 
-        let mode = Self::get_arg(&args, "--mode").unwrap_or_else(|| "full".to_string());
-        let year = Self::get_arg(&args, "--year")
+    /// Configuration from command line arguments, with `var` looking up
+    /// environment variables
+    pub fn from_args(args: &[String], var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+
+        let mode = Self::get_arg(args, "--mode").unwrap_or_else(|| "full".to_string());
+        let year = Self::get_arg(args, "--year")
             .and_then(|s| s.parse().ok())
             .unwrap_or(2024);
-        let start_year = Self::get_arg(&args, "--start-year")
+        let start_year = Self::get_arg(args, "--start-year")
             .and_then(|s| s.parse().ok())
             .unwrap_or(2010);
-        let end_year = Self::get_arg(&args, "--end-year")
+        let end_year = Self::get_arg(args, "--end-year")
             .and_then(|s| s.parse().ok())
             .unwrap_or(2025);
-        let dry_run = args.contains(&"--dry-run".to_string());
+        let emit_sql = Self::get_arg(args, "--emit-sql");
+        let dry_run = args.contains(&"--dry-run".to_string()) || emit_sql.is_some();
+        let simulate = args.contains(&"--simulate".to_string());
+        let force = args.contains(&"--force".to_string());
+        let max_error_rate = match Self::get_arg(args, "--max-error-rate").or_else(|| var("PIPELINE_MAX_ERROR_RATE")) {
+            Some(value) => Some(Self::parse_error_rate(&value)?),
+            None => None,
+        };
+        let include_college = args.contains(&"--with-college".to_string());
+        let season_types = match Self::get_arg(args, "--season-types") {
+            Some(value) => Self::parse_season_types(&value)?,
+            None => vec!["REG".to_string()],
+        };
+        let load_method = match Self::get_arg(args, "--load-method") {
+            Some(value) => LoadMethod::parse(&value)
+                .ok_or_else(|| anyhow!("Invalid --load-method: {} (expected unnest, insert or copy)", value))?,
+            None => LoadMethod::Unnest,
+        };
+        let sink = match Self::get_arg(args, "--sink") {
+            Some(value) => Sink::parse(&value).ok_or_else(|| anyhow!("Invalid --sink: {} (expected postgres, sqlite or parquet)", value))?,
+            None => Sink::Postgres,
+        };
+        let auto_tune = args.contains(&"--auto-tune".to_string());
+        let backpressure = args.contains(&"--backpressure".to_string())
+            || var("PIPELINE_BACKPRESSURE").is_some_and(|v| v == "1" || v.eq_ignore_ascii_case("true"));
+        let batch_size = Self::parse_batch_size(args, "--batch-size", 500)?;
+        let min_batch_size = Self::parse_batch_size(args, "--min-batch-size", 100)?;
+        let max_batch_size = Self::parse_batch_size(args, "--max-batch-size", 5000)?;
+        if min_batch_size > max_batch_size {
+            return Err(anyhow!("--min-batch-size {} exceeds --max-batch-size {}", min_batch_size, max_batch_size));
+        }
+        let writers = match Self::get_arg(args, "--writers").or_else(|| var("PIPELINE_WRITERS")) {
+            Some(value) => value
+                .parse()
+                .ok()
+                .filter(|n: &usize| *n >= 1)
+                .ok_or_else(|| anyhow!("Invalid --writers: {} (expected a positive number)", value))?,
+            None => 1,
+        };
+
+        let team = Self::get_arg(args, "--team").map(|t| t.to_uppercase());
+        let entity = Self::get_arg(args, "--entity");
+        let season = match Self::get_arg(args, "--season") {
+            Some(value) => Some(value.parse().map_err(|_| anyhow!("Invalid --season: {}", value))?),
+            None => None,
+        };
+        let week = match Self::get_arg(args, "--week") {
+            Some(value) => Some(value.parse().map_err(|_| anyhow!("Invalid --week: {}", value))?),
+            None => None,
+        };
+        let format = Self::get_arg(args, "--format").map(|f| f.to_lowercase());
+        let source_format = match format.as_deref() {
+            Some("parquet") => SourceFormat::Parquet,
+            _ => SourceFormat::Csv,
+        };
+        let out_dir = Self::get_arg(args, "--out").unwrap_or_else(|| "./exports".to_string());
+        let attribution = args.contains(&"--attribution".to_string())
+            || var("PIPELINE_ATTRIBUTION").is_some_and(|v| v == "1" || v.eq_ignore_ascii_case("true"));
+
+        let baseline = match Self::get_arg(args, "--baseline") {
+            Some(value) => Some(value.parse().map_err(|_| anyhow!("Invalid --baseline: {} (expected a migration version)", value))?),
+            None => None,
+        };
+
+        let action = Self::get_arg(args, "--action");
+        let history_retain_days = Self::get_arg(args, "--retain-days")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(365);
+        let memory_budget_mb = Self::get_arg(args, "--memory-budget-mb")
+            .or_else(|| var("PIPELINE_MEMORY_BUDGET_MB"))
+            .and_then(|s| s.parse().ok());
+        let cache_dir = Self::get_arg(args, "--cache-dir")
+            .or_else(|| var("PIPELINE_CACHE_DIR"));
+        let archive = Self::get_arg(args, "--archive")
+            .or_else(|| var("PIPELINE_ARCHIVE"))
+            .filter(|destination| !destination.is_empty());
+        if let Some(destination) = &archive {
+            archive::Destination::parse(destination)?;
+        }
+        let dead_letter = Self::get_arg(args, "--dead-letter")
+            .or_else(|| var("PIPELINE_DEAD_LETTER"))
+            .filter(|path| !path.is_empty());
+        let source_dir = Self::get_arg(args, "--source-dir");
+        let sql = Self::get_arg(args, "--sql");
+        let sheets_config = Self::get_arg(args, "--sheets-config")
+            .or_else(|| var("PIPELINE_SHEETS_CONFIG"));
+        let scoring_config = Self::get_arg(args, "--scoring-config")
+            .or_else(|| var("PIPELINE_SCORING_CONFIG"));
+        let game_day_interval_mins = Self::get_arg(args, "--game-day-interval")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(15);
+        let idle_interval_mins = Self::get_arg(args, "--idle-interval")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(60);
+
+        let live_interval_secs = Self::get_arg(args, "--interval")
+            .or_else(|| var("LIVE_POLL_SECS"))
+            .and_then(|s| s.parse().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(30);
+        let scoreboard_url = var("ESPN_SCOREBOARD_URL").unwrap_or_else(|| live::SCOREBOARD_URL.to_string());
+
+        let garbage_time_wp = match Self::get_arg(args, "--garbage-time-wp") {
+            Some(value) => value
+                .parse()
+                .ok()
+                .filter(|wp: &f64| (0.0..0.5).contains(wp))
+                .ok_or_else(|| anyhow!("Invalid --garbage-time-wp: {} (expected a win probability from 0 to 0.5)", value))?,
+            None => 0.10,
+        };
+        let since_days = Self::get_arg(args, "--since-days")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(30);
+        let espn = args.contains(&"--espn".to_string())
+            || var("PIPELINE_ESPN").is_some_and(|v| v == "1" || v.eq_ignore_ascii_case("true"));
+        let espn_api_url = var("ESPN_API_URL").unwrap_or_else(|| espn::API_URL.to_string());
+        let source_priority = match Self::get_arg(args, "--source-priority").or_else(|| var("PIPELINE_SOURCE_PRIORITY")) {
+            Some(value) => SourcePriority::parse(&value)
+                .ok_or_else(|| anyhow!("Invalid --source-priority: {} (expected nflverse or espn)", value))?,
+            None => SourcePriority::Nflverse,
+        };
+        let log_format = match Self::get_arg(args, "--log-format").or_else(|| var("PIPELINE_LOG_FORMAT")) {
+            Some(value) => LogFormat::parse(&value)
+                .ok_or_else(|| anyhow!("Invalid --log-format: {} (expected text or json)", value))?,
+            None => LogFormat::Text,
+        };
+        let telemetry_url = Self::get_arg(args, "--telemetry-url")
+            .or_else(|| var("PIPELINE_TELEMETRY_URL"))
+            .filter(|url| !url.is_empty());
+        let notify_url = Self::get_arg(args, "--notify-url")
+            .or_else(|| var("PIPELINE_NOTIFY_URL"))
+            .filter(|url| !url.is_empty());
+        let notify_templates = Self::get_arg(args, "--notify-templates")
+            .or_else(|| var("PIPELINE_NOTIFY_TEMPLATES"))
+            .filter(|dir| !dir.is_empty());
+        let metrics_addr = Self::get_arg(args, "--metrics-addr")
+            .or_else(|| var("PIPELINE_METRICS_ADDR"))
+            .filter(|addr| !addr.is_empty());
+        let api_addr = Self::get_arg(args, "--addr")
+            .or_else(|| var("PIPELINE_API_ADDR"))
+            .filter(|addr| !addr.is_empty())
+            .unwrap_or_else(|| api::DEFAULT_ADDR.to_string());
+        let pushgateway_url = Self::get_arg(args, "--pushgateway")
+            .or_else(|| var("PIPELINE_PUSHGATEWAY_URL"))
+            .filter(|url| !url.is_empty());
+
+        let precedence = match Self::get_arg(args, "--precedence").or_else(|| var("PIPELINE_PRECEDENCE")) {
+            Some(value) => Precedence::parse(source_priority, &value)?,
+            None => Precedence::new(source_priority),
+        };
+        let espn_athletes_url = var("ESPN_ATHLETES_URL").unwrap_or_else(|| espn_ids::ATHLETES_URL.to_string());
+        let min_confidence = match Self::get_arg(args, "--min-confidence") {
+            Some(value) => value
+                .parse()
+                .ok()
+                .filter(|score: &f64| (0.0..=1.0).contains(score))
+                .ok_or_else(|| anyhow!("Invalid --min-confidence: {} (expected a score from 0 to 1)", value))?,
+            None => espn_ids::MIN_CONFIDENCE,
+        };
+        let include_news = args.contains(&"--with-news".to_string())
+            || var("PIPELINE_NEWS").is_some_and(|v| v == "1" || v.eq_ignore_ascii_case("true"));
+        let news_feed_url = Self::get_arg(args, "--news-feed")
+            .or_else(|| var("PIPELINE_NEWS_FEED"))
+            .filter(|url| !url.is_empty())
+            .unwrap_or_else(|| news::NEWS_URL.to_string());
+        let odds_api_key = var("ODDS_API_KEY").filter(|key| !key.is_empty());
+        let odds_api_url = var("ODDS_API_URL").unwrap_or_else(|| odds::ODDS_API_URL.to_string());
+        let odds_bookmakers = Self::get_arg(args, "--bookmakers")
+            .or_else(|| var("PIPELINE_ODDS_BOOKMAKERS"))
+            .map(|list| list.split(',').map(|b| b.trim().to_lowercase()).filter(|b| !b.is_empty()).collect())
+            .unwrap_or_default();
+        let sleeper_league = Self::get_arg(args, "--league")
+            .or_else(|| var("PIPELINE_SLEEPER_LEAGUE"))
+            .filter(|league| !league.is_empty());
+        let sleeper_api_url = var("SLEEPER_API_URL").unwrap_or_else(|| sleeper::API_URL.to_string());
+        let lineage = args.contains(&"--lineage".to_string())
+            || var("PIPELINE_LINEAGE").is_some_and(|v| v == "1" || v.eq_ignore_ascii_case("true"));
+        let dataset = Self::get_arg(args, "--dataset");
+        let lineage_key = Self::get_arg(args, "--key");
+        let change_table = Self::get_arg(args, "--table");
+        let key_name = Self::get_arg(args, "--name");
+        let key_rate_limit = match Self::get_arg(args, "--rate-limit") {
+            Some(value) => value
+                .parse()
+                .ok()
+                .filter(|n: &i32| *n >= 1)
+                .ok_or_else(|| anyhow!("Invalid --rate-limit: {} (expected requests per minute)", value))?,
+            None => api_keys::DEFAULT_RATE_LIMIT,
+        };
+        let key_id = match Self::get_arg(args, "--key-id") {
+            Some(value) => Some(value.parse().map_err(|_| anyhow!("Invalid --key-id: {}", value))?),
+            None => None,
+        };
+        let k8s_image = Self::get_arg(args, "--image")
+            .or_else(|| var("PIPELINE_K8S_IMAGE"))
+            .unwrap_or_else(|| format!("nfl-data-pipeline:{}", env!("CARGO_PKG_VERSION")));
+        let k8s_namespace = Self::get_arg(args, "--namespace");
+        let k8s_secret = Self::get_arg(args, "--secret").unwrap_or_else(|| "nfl-data-pipeline".to_string());
+        let k8s_jobs = match Self::get_arg(args, "--jobs").or_else(|| var("PIPELINE_K8S_JOBS")) {
+            Some(value) => k8s::parse_jobs(&value)?,
+            None => k8s::default_jobs(),
+        };
+
+        // query-raw reads local files only, generate-k8s only prints manifests,
+        // file sinks write no database
+        let database_url = match var("DATABASE_URL") {
+            Some(url) => url,
+            None if mode == "query-raw" || mode == "generate-k8s" || sink != Sink::Postgres => String::new(),
+            None => return Err(anyhow!("DATABASE_URL must be set in environment")),
+        };
 
-        let database_url = env::var("DATABASE_URL")
-            .context("DATABASE_URL must be set in environment")?;
+        let fanout_database_urls = var("FANOUT_DATABASE_URLS")
+            .map(|urls| Self::parse_url_list(&urls))
+            .unwrap_or_default();
 
         Ok(Config {
             database_url,
+            fanout_database_urls,
             mode,
             year,
             start_year,
             end_year,
             dry_run,
-            batch_size: 500,
+            emit_sql,
+            simulate,
+            force,
+            max_error_rate,
+            include_college,
+            season_types,
+            batch_size,
+            auto_tune,
+            backpressure,
+            min_batch_size,
+            max_batch_size,
             max_retries: 3,
+            load_method,
+            sink,
+            writers,
+            team,
+            entity,
+            season,
+            week,
+            format,
+            source_format,
+            out_dir,
+            attribution,
+            baseline,
+            action,
+            history_retain_days,
+            memory_budget_mb,
+            cache_dir,
+            archive,
+            dead_letter,
+            source_dir,
+            sql,
+            sheets_config,
+            scoring_config,
+            game_day_interval_mins,
+            idle_interval_mins,
+            live_interval_secs,
+            scoreboard_url,
+            garbage_time_wp,
+            since_days,
+            log_format,
+            telemetry_url,
+            notify_url,
+            notify_templates,
+            metrics_addr,
+            api_addr,
+            pushgateway_url,
+            espn,
+            espn_api_url,
+            source_priority,
+            precedence,
+            espn_athletes_url,
+            min_confidence,
+            include_news,
+            news_feed_url,
+            odds_api_key,
+            odds_api_url,
+            odds_bookmakers,
+            sleeper_league,
+            sleeper_api_url,
+            lineage,
+            dataset,
+            lineage_key,
+            change_table,
+            key_name,
+            key_rate_limit,
+            key_id,
+            k8s_image,
+            k8s_namespace,
+            k8s_secret,
+            k8s_jobs,
         })
     }
 
+    /// Flags reproducing this configuration in a scheduled run, other than
+    /// `--mode` and the one-off `--dry-run`, `--emit-sql`, `--simulate`, `--sql`, `--baseline`
+    /// and lineage, change log and API key arguments. Settings with an environment variable are in `job_env`.
+    pub fn job_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        let mut arg = |key: &str, value: String| args.extend([key.to_string(), value]);
+        if self.year != 2024 {
+            arg("--year", self.year.to_string());
+        }
+        if self.start_year != 2010 {
+            arg("--start-year", self.start_year.to_string());
+        }
+        if self.end_year != 2025 {
+            arg("--end-year", self.end_year.to_string());
+        }
+        if self.season_types != ["REG"] {
+            arg("--season-types", self.season_types.join(","));
+        }
+        if self.load_method != LoadMethod::Unnest {
+            arg("--load-method", self.load_method.as_str().to_string());
+        }
+        if self.sink != Sink::Postgres {
+            arg("--sink", self.sink.as_str().to_string());
+        }
+        if self.batch_size != 500 {
+            arg("--batch-size", self.batch_size.to_string());
+        }
+        if self.min_batch_size != 100 {
+            arg("--min-batch-size", self.min_batch_size.to_string());
+        }
+        if self.max_batch_size != 5000 {
+            arg("--max-batch-size", self.max_batch_size.to_string());
+        }
+        if let Some(team) = &self.team {
+            arg("--team", team.clone());
+        }
+        if let Some(entity) = &self.entity {
+            arg("--entity", entity.clone());
+        }
+        if let Some(season) = self.season {
+            arg("--season", season.to_string());
+        }
+        if let Some(week) = self.week {
+            arg("--week", week.to_string());
+        }
+        if let Some(format) = &self.format {
+            arg("--format", format.clone());
+        }
+        if self.out_dir != "./exports" {
+            arg("--out", self.out_dir.clone());
+        }
+        if let Some(action) = &self.action {
+            arg("--action", action.clone());
+        }
+        if self.history_retain_days != 365 {
+            arg("--retain-days", self.history_retain_days.to_string());
+        }
+        if let Some(source_dir) = &self.source_dir {
+            arg("--source-dir", source_dir.clone());
+        }
+        if self.game_day_interval_mins != 15 {
+            arg("--game-day-interval", self.game_day_interval_mins.to_string());
+        }
+        if self.idle_interval_mins != 60 {
+            arg("--idle-interval", self.idle_interval_mins.to_string());
+        }
+        if self.live_interval_secs != 30 {
+            arg("--interval", self.live_interval_secs.to_string());
+        }
+        if let Some(rate) = self.max_error_rate {
+            arg("--max-error-rate", rate.to_string());
+        }
+        if self.garbage_time_wp != 0.10 {
+            arg("--garbage-time-wp", self.garbage_time_wp.to_string());
+        }
+        if self.since_days != 30 {
+            arg("--since-days", self.since_days.to_string());
+        }
+        if self.precedence.has_rules() {
+            arg("--precedence", self.precedence.rules_string());
+        }
+        if self.min_confidence != espn_ids::MIN_CONFIDENCE {
+            arg("--min-confidence", self.min_confidence.to_string());
+        }
+
+        let flags = [
+            (self.force, "--force"),
+            (self.include_college, "--with-college"),
+            (self.auto_tune, "--auto-tune"),
+            (self.backpressure, "--backpressure"),
+        ];
+        args.extend(flags.into_iter().filter(|(set, _)| *set).map(|(_, flag)| flag.to_string()));
+        args
+    }
+
+    /// Environment variables reproducing this configuration, apart from the
+    /// database URLs and odds API key (secrets) and what `job_args` covers
+    pub fn job_env(&self) -> Vec<(&'static str, String)> {
+        let mut vars = Vec::new();
+        if self.writers != 1 {
+            vars.push(("PIPELINE_WRITERS", self.writers.to_string()));
+        }
+        if let Some(mb) = self.memory_budget_mb {
+            vars.push(("PIPELINE_MEMORY_BUDGET_MB", mb.to_string()));
+        }
+        if let Some(dir) = &self.cache_dir {
+            vars.push(("PIPELINE_CACHE_DIR", dir.clone()));
+        }
+        if let Some(destination) = &self.archive {
+            vars.push(("PIPELINE_ARCHIVE", destination.clone()));
+        }
+        if let Some(path) = &self.dead_letter {
+            vars.push(("PIPELINE_DEAD_LETTER", path.clone()));
+        }
+        if let Some(path) = &self.sheets_config {
+            vars.push(("PIPELINE_SHEETS_CONFIG", path.clone()));
+        }
+        if let Some(path) = &self.scoring_config {
+            vars.push(("PIPELINE_SCORING_CONFIG", path.clone()));
+        }
+        if self.attribution {
+            vars.push(("PIPELINE_ATTRIBUTION", "1".to_string()));
+        }
+        if self.espn {
+            vars.push(("PIPELINE_ESPN", "1".to_string()));
+        }
+        if self.include_news {
+            vars.push(("PIPELINE_NEWS", "1".to_string()));
+        }
+        if self.news_feed_url != news::NEWS_URL {
+            vars.push(("PIPELINE_NEWS_FEED", self.news_feed_url.clone()));
+        }
+        if !self.odds_bookmakers.is_empty() {
+            vars.push(("PIPELINE_ODDS_BOOKMAKERS", self.odds_bookmakers.join(",")));
+        }
+        if self.odds_api_url != odds::ODDS_API_URL {
+            vars.push(("ODDS_API_URL", self.odds_api_url.clone()));
+        }
+        if let Some(league) = &self.sleeper_league {
+            vars.push(("PIPELINE_SLEEPER_LEAGUE", league.clone()));
+        }
+        if self.sleeper_api_url != sleeper::API_URL {
+            vars.push(("SLEEPER_API_URL", self.sleeper_api_url.clone()));
+        }
+        if self.lineage {
+            vars.push(("PIPELINE_LINEAGE", "1".to_string()));
+        }
+        if self.log_format != LogFormat::Text {
+            vars.push(("PIPELINE_LOG_FORMAT", self.log_format.as_str().to_string()));
+        }
+        if self.source_priority != SourcePriority::Nflverse {
+            vars.push(("PIPELINE_SOURCE_PRIORITY", self.source_priority.as_str().to_string()));
+        }
+        if self.espn_api_url != espn::API_URL {
+            vars.push(("ESPN_API_URL", self.espn_api_url.clone()));
+        }
+        if self.espn_athletes_url != espn_ids::ATHLETES_URL {
+            vars.push(("ESPN_ATHLETES_URL", self.espn_athletes_url.clone()));
+        }
+        if self.scoreboard_url != live::SCOREBOARD_URL {
+            vars.push(("ESPN_SCOREBOARD_URL", self.scoreboard_url.clone()));
+        }
+        if let Some(url) = &self.telemetry_url {
+            vars.push(("PIPELINE_TELEMETRY_URL", url.clone()));
+        }
+        if let Some(url) = &self.notify_url {
+            vars.push(("PIPELINE_NOTIFY_URL", url.clone()));
+        }
+        if let Some(dir) = &self.notify_templates {
+            vars.push(("PIPELINE_NOTIFY_TEMPLATES", dir.clone()));
+        }
+        if let Some(addr) = &self.metrics_addr {
+            vars.push(("PIPELINE_METRICS_ADDR", addr.clone()));
+        }
+        if self.api_addr != api::DEFAULT_ADDR {
+            vars.push(("PIPELINE_API_ADDR", self.api_addr.clone()));
+        }
+        if let Some(url) = &self.pushgateway_url {
+            vars.push(("PIPELINE_PUSHGATEWAY_URL", url.clone()));
+        }
+        vars
+    }
+
+    /// Memory budget in bytes, if one was configured
+    pub fn memory_budget_bytes(&self) -> Option<usize> {
+        self.memory_budget_mb.map(|mb| mb * 1024 * 1024)
+    }
+
+    /// Upsert workers plus the connection held for serial work
+    pub fn pool_size(&self) -> u32 {
+        self.writers as u32 + 1
+    }
+
+    /// Whether batches go to parallel workers: set-based load methods only, since
+    /// row-by-row inserts share the pipeline's transaction
+    pub fn parallel_writes(&self) -> bool {
+        self.writers > 1 && !self.dry_run && self.load_method != LoadMethod::Insert
+    }
+
+    /// Every database the run writes to, primary first
+    pub fn target_urls(&self) -> Vec<&str> {
+        std::iter::once(self.database_url.as_str())
+            .chain(self.fanout_database_urls.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// This configuration pointed at another database
+    pub fn for_target(&self, database_url: &str) -> Config {
+        Config { database_url: database_url.to_string(), fanout_database_urls: Vec::new(), ..self.clone() }
+    }
+
+    /// Whether rows of a season type (REG, POST, PRE) should be imported
+    pub fn includes_season_type(&self, season_type: &str) -> bool {
+        self.season_types.iter().any(|t| t == season_type)
+    }
+
+    fn parse_batch_size(args: &[String], key: &str, default: usize) -> Result<usize> {
+        match Self::get_arg(args, key) {
+            Some(value) => value
+                .parse()
+                .ok()
+                .filter(|n: &usize| *n >= 1)
+                .ok_or_else(|| anyhow!("Invalid {}: {} (expected a positive number of rows)", key, value)),
+            None => Ok(default),
+        }
+    }
+
+    /// A percentage (`5%`) or a fraction (`0.05`)
+    fn parse_error_rate(value: &str) -> Result<f64> {
+        let (number, scale) = match value.trim().strip_suffix('%') {
+            Some(percent) => (percent, 100.0),
+            None => (value.trim(), 1.0),
+        };
+        number
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .map(|n| n / scale)
+            .filter(|rate| (0.0..=1.0).contains(rate))
+            .ok_or_else(|| anyhow!("Invalid --max-error-rate: {} (expected a percentage like 5% or a fraction like 0.05)", value))
+    }
+
+    fn parse_season_types(value: &str) -> Result<Vec<String>> {
+        let mut types = Vec::new();
+        for season_type in value.split(',').map(|t| t.trim().to_uppercase()) {
+            match season_type.as_str() {
+                "REG" | "POST" | "PRE" => types.push(season_type),
+                _ => return Err(anyhow!("Invalid season type: {} (expected REG, POST or PRE)", season_type)),
+            }
+        }
+        Ok(types)
+    }
+
+    /// Connection strings separated by commas or newlines (key=value strings
+    /// contain spaces, so those don't separate)
+    fn parse_url_list(urls: &str) -> Vec<String> {
+        urls.split([',', '\n'])
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
     fn get_arg(args: &[String], key: &str) -> Option<String> {
         args.iter()
             .position(|arg| arg == key)
             .and_then(|i| args.get(i + 1))
-            .map(|s| s.clone())
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str], vars: &[(&str, &str)]) -> Config {
+        let args: Vec<String> = std::iter::once("nfl-data-pipeline").chain(args.iter().copied()).map(String::from).collect();
+        Config::from_args(&args, |key| vars.iter().find(|(name, _)| *name == key).map(|(_, value)| value.to_string())).unwrap()
+    }
+
+    #[test]
+    fn test_job_args_and_env_reproduce_config() {
+        let config = parse(
+            &["--mode", "year", "--year", "2023", "--season-types", "REG,POST", "--load-method", "copy", "--sink", "sqlite",
+              "--entity", "game_stats", "--season", "2023", "--week", "5",
+              "--garbage-time-wp", "0.05", "--max-error-rate", "5%", "--min-confidence", "0.9", "--precedence", "venue=espn;scores@final=nflverse,live", "--force", "--auto-tune", "--backpressure", "--interval", "15"],
+            &[("DATABASE_URL", "postgres://db"), ("PIPELINE_WRITERS", "4"), ("PIPELINE_MEMORY_BUDGET_MB", "512"),
+              ("PIPELINE_ESPN", "true"), ("PIPELINE_SOURCE_PRIORITY", "espn"), ("PIPELINE_LINEAGE", "1"),
+              ("PIPELINE_LOG_FORMAT", "json"), ("PIPELINE_METRICS_ADDR", "0.0.0.0:9184"), ("PIPELINE_API_ADDR", "0.0.0.0:8080"),
+              ("PIPELINE_PUSHGATEWAY_URL", "http://pushgateway:9091"), ("PIPELINE_NOTIFY_URL", "https://hooks.slack.com/services/T0/B0/x"),
+              ("PIPELINE_NEWS", "true"), ("PIPELINE_NEWS_FEED", "https://example.com/nfl.rss"),
+              ("PIPELINE_ARCHIVE", "s3://nfl-raw/nflverse"), ("PIPELINE_DEAD_LETTER", "/var/lib/nfl/dead-letter.jsonl"),
+              ("PIPELINE_ODDS_BOOKMAKERS", "draftkings, FanDuel"),
+              ("ODDS_API_URL", "http://localhost:8765/odds"), ("PIPELINE_SLEEPER_LEAGUE", "1048326254392201216")],
+        );
+
+        let mut args = vec!["--mode".to_string(), config.mode.clone()];
+        args.extend(config.job_args());
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let mut vars = vec![("DATABASE_URL".to_string(), config.database_url.clone())];
+        vars.extend(config.job_env().into_iter().map(|(name, value)| (name.to_string(), value)));
+        let vars: Vec<(&str, &str)> = vars.iter().map(|(name, value)| (name.as_str(), value.as_str())).collect();
+
+        assert_eq!(format!("{:?}", parse(&args, &vars)), format!("{:?}", config));
+        assert!(parse(&[], &[("DATABASE_URL", "postgres://db")]).job_args().is_empty());
+    }
+
+    #[test]
+    fn test_max_error_rate() {
+        let rate = |value: &str| parse(&["--max-error-rate", value], &[("DATABASE_URL", "postgres://db")]).max_error_rate;
+        assert_eq!(rate("5%"), Some(0.05));
+        assert_eq!(rate("0.05"), Some(0.05));
+        assert_eq!(rate(" 12.5 % "), Some(0.125));
+        assert_eq!(parse(&[], &[("DATABASE_URL", "postgres://db")]).max_error_rate, None);
+
+        let args: Vec<String> = ["nfl-data-pipeline", "--max-error-rate", "150%"].iter().map(|s| s.to_string()).collect();
+        assert!(Config::from_args(&args, |key| (key == "DATABASE_URL").then(|| "postgres://db".to_string())).is_err());
     }
 }
@@ -0,0 +1,137 @@
+use postgres::Row;
+
+use crate::parser::PlayerStat;
+
+/// Stat columns stored in game_stats that official corrections can change
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatLine {
+    pub passing_yards: Option<i32>,
+    pub rushing_yards: Option<i32>,
+    pub receiving_yards: Option<i32>,
+    pub passing_tds: Option<i32>,
+    pub rushing_tds: Option<i32>,
+    pub receiving_tds: Option<i32>,
+    pub receptions: Option<i32>,
+    pub targets: Option<i32>,
+    pub attempts: Option<i32>,
+    pub completions: Option<i32>,
+    pub interceptions: Option<i32>,
+}
+
+/// A single field that changed between the stored and re-imported stat line
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatCorrection {
+    pub field: &'static str,
+    pub old_value: Option<i32>,
+    pub new_value: Option<i32>,
+}
+
+/// Season types the sweep re-checks; postseason weeks continue the regular
+/// season's numbering (19+), so one week window covers both
+pub const SWEPT_SEASON_TYPES: [&str; 2] = ["REG", "POST"];
+
+/// Column list matching `StatLine::from_row`
+pub const STAT_LINE_COLUMNS: &str = "passing_yards, rushing_yards, receiving_yards, passing_tds, rushing_tds, receiving_tds, receptions, targets, attempts, completions, interceptions";
+
+impl StatLine {
+    /// Build the line that would be written for a parsed stat row
+    pub fn from_stat(stat: &PlayerStat) -> Self {
+        StatLine {
+            passing_yards: stat.passing_yards.map(|v| v as i32),
+            rushing_yards: stat.rushing_yards.map(|v| v as i32),
+            receiving_yards: stat.receiving_yards.map(|v| v as i32),
+            passing_tds: stat.passing_tds,
+            rushing_tds: stat.rushing_tds,
+            receiving_tds: stat.receiving_tds,
+            receptions: stat.receptions.map(|v| v as i32),
+            targets: stat.targets.map(|v| v as i32),
+            attempts: stat.attempts.map(|v| v as i32),
+            completions: stat.completions.map(|v| v as i32),
+            interceptions: stat.interceptions,
+        }
+    }
+
+    /// Read a line selected with `STAT_LINE_COLUMNS`
+    pub fn from_row(row: &Row) -> Self {
+        StatLine {
+            passing_yards: row.get(0),
+            rushing_yards: row.get(1),
+            receiving_yards: row.get(2),
+            passing_tds: row.get(3),
+            rushing_tds: row.get(4),
+            receiving_tds: row.get(5),
+            receptions: row.get(6),
+            targets: row.get(7),
+            attempts: row.get(8),
+            completions: row.get(9),
+            interceptions: row.get(10),
+        }
+    }
+
+    fn fields(&self) -> [(&'static str, Option<i32>); 11] {
+        [
+            ("passing_yards", self.passing_yards),
+            ("rushing_yards", self.rushing_yards),
+            ("receiving_yards", self.receiving_yards),
+            ("passing_tds", self.passing_tds),
+            ("rushing_tds", self.rushing_tds),
+            ("receiving_tds", self.receiving_tds),
+            ("receptions", self.receptions),
+            ("targets", self.targets),
+            ("attempts", self.attempts),
+            ("completions", self.completions),
+            ("interceptions", self.interceptions),
+        ]
+    }
+}
+
+/// Compare a stored stat line against a re-imported one.
+/// Missing values are treated as zero so NULL → 0 isn't reported as a correction.
+pub fn diff_stat_lines(old: &StatLine, new: &StatLine) -> Vec<StatCorrection> {
+    old.fields()
+        .iter()
+        .zip(new.fields().iter())
+        .filter(|((_, before), (_, after))| before.unwrap_or(0) != after.unwrap_or(0))
+        .map(|((field, before), (_, after))| StatCorrection {
+            field,
+            old_value: *before,
+            new_value: *after,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_detects_changed_fields() {
+        let old = StatLine {
+            rushing_yards: Some(88),
+            rushing_tds: Some(1),
+            ..Default::default()
+        };
+        let new = StatLine {
+            rushing_yards: Some(91),
+            rushing_tds: Some(1),
+            ..Default::default()
+        };
+
+        let diffs = diff_stat_lines(&old, &new);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].field, "rushing_yards");
+        assert_eq!(diffs[0].old_value, Some(88));
+        assert_eq!(diffs[0].new_value, Some(91));
+    }
+
+    #[test]
+    fn test_diff_ignores_null_to_zero() {
+        let old = StatLine::default();
+        let new = StatLine {
+            targets: Some(0),
+            ..Default::default()
+        };
+
+        assert!(diff_stat_lines(&old, &new).is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+
+/// Month the new league year starts; January/February dates belong to the previous season
+const LEAGUE_YEAR_START_MONTH: u32 = 3;
+/// A week becomes current this many days before its first game
+const WEEK_LEAD_DAYS: i64 = 6;
+
+/// Game dates and completion for one week of a season, from the games table
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeekWindow {
+    pub season_type: String,
+    pub week: i32,
+    pub first_game: NaiveDate,
+    pub last_game: NaiveDate,
+    /// Every game has a final score
+    pub complete: bool,
+}
+
+/// The NFL season and week a date falls in
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CurrentWeek {
+    pub season: i32,
+    pub season_type: String,
+    pub week: i32,
+    /// False before the first week opens and after the last game is final
+    pub in_season: bool,
+}
+
+/// Season a calendar date belongs to (seasons run September through February)
+pub fn season_for_date(date: NaiveDate) -> i32 {
+    if date.month() < LEAGUE_YEAR_START_MONTH {
+        date.year() - 1
+    } else {
+        date.year()
+    }
+}
+
+/// Pick the current week from a season's week windows (sorted by first game).
+///
+/// The current week is the first one that still has unfinished games or games on or
+/// after `today`; it opens `WEEK_LEAD_DAYS` before its first game. Without a schedule
+/// the season is reported as out of season, week 0.
+pub fn resolve(season: i32, windows: &[WeekWindow], today: NaiveDate) -> CurrentWeek {
+    let offseason = |window: Option<&WeekWindow>| CurrentWeek {
+        season,
+        season_type: window.map(|w| w.season_type.clone()).unwrap_or_else(|| "REG".to_string()),
+        week: window.map(|w| w.week).unwrap_or(0),
+        in_season: false,
+    };
+
+    match windows.iter().find(|w| !w.complete || w.last_game >= today) {
+        Some(window) if (window.first_game - today).num_days() > WEEK_LEAD_DAYS => offseason(Some(window)),
+        Some(window) => CurrentWeek {
+            season,
+            season_type: window.season_type.clone(),
+            week: window.week,
+            in_season: true,
+        },
+        None => offseason(windows.last()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn window(season_type: &str, week: i32, first: NaiveDate, last: NaiveDate, complete: bool) -> WeekWindow {
+        WeekWindow {
+            season_type: season_type.to_string(),
+            week,
+            first_game: first,
+            last_game: last,
+            complete,
+        }
+    }
+
+    fn schedule() -> Vec<WeekWindow> {
+        vec![
+            window("REG", 1, date(2024, 9, 5), date(2024, 9, 9), true),
+            window("REG", 2, date(2024, 9, 12), date(2024, 9, 16), false),
+            window("POST", 19, date(2025, 1, 11), date(2025, 1, 13), false),
+        ]
+    }
+
+    #[test]
+    fn test_season_for_date() {
+        assert_eq!(season_for_date(date(2025, 2, 9)), 2024);
+        assert_eq!(season_for_date(date(2025, 9, 1)), 2025);
+    }
+
+    #[test]
+    fn test_next_week_opens_after_previous_completes() {
+        let current = resolve(2024, &schedule(), date(2024, 9, 10));
+        assert_eq!((current.week, current.in_season), (2, true));
+    }
+
+    #[test]
+    fn test_unfinished_week_stays_current() {
+        let mut windows = schedule();
+        windows[0].complete = false;
+        let current = resolve(2024, &windows, date(2024, 9, 11));
+        assert_eq!(current.week, 1);
+    }
+
+    #[test]
+    fn test_before_season_and_after_final_game() {
+        let before = resolve(2024, &schedule(), date(2024, 7, 1));
+        assert_eq!((before.week, before.in_season), (1, false));
+
+        let mut windows = schedule();
+        windows.iter_mut().for_each(|w| w.complete = true);
+        let after = resolve(2024, &windows, date(2025, 2, 20));
+        assert_eq!((after.season_type.as_str(), after.week, after.in_season), ("POST", 19, false));
+    }
+
+    #[test]
+    fn test_no_schedule() {
+        let current = resolve(2025, &[], date(2025, 5, 1));
+        assert_eq!((current.week, current.in_season), (0, false));
+    }
+}
@@ -0,0 +1,110 @@
+use anyhow::Result;
+use chrono::{Datelike, Weekday};
+use log::{info, error};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::sync::DataPipeline;
+
+/// Days with games (Thursday, Sunday, Monday nights) poll more often
+const GAME_DAYS: [Weekday; 3] = [Weekday::Thu, Weekday::Sun, Weekday::Mon];
+/// How often the shutdown flag is checked while sleeping
+const SHUTDOWN_POLL: Duration = Duration::from_secs(1);
+
+/// Update cadence for daemon mode
+#[derive(Debug, Clone, Copy)]
+pub struct Schedule {
+    pub game_day_interval: Duration,
+    pub idle_interval: Duration,
+}
+
+impl Schedule {
+    /// Interval until the next update, based on the current local weekday.
+    /// Outside the season every day uses the idle interval.
+    pub fn interval_for(&self, weekday: Weekday, in_season: bool) -> Duration {
+        if in_season && GAME_DAYS.contains(&weekday) {
+            self.game_day_interval
+        } else {
+            self.idle_interval
+        }
+    }
+}
+
+/// Run `run_update` on the schedule until SIGTERM or SIGINT.
+///
+/// A failed update is logged and retried on the next tick; a signal received
+/// mid-update lets that update finish before exiting.
+pub fn run(pipeline: &mut DataPipeline, schedule: Schedule) -> Result<()> {
+    let shutdown = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register(signal_hook::consts::SIGTERM, Arc::clone(&shutdown))?;
+    signal_hook::flag::register(signal_hook::consts::SIGINT, Arc::clone(&shutdown))?;
+
+    info!(
+        "🕰️  Daemon started: every {}m on game days, every {}m otherwise",
+        schedule.game_day_interval.as_secs() / 60,
+        schedule.idle_interval.as_secs() / 60
+    );
+
+    while !shutdown.load(Ordering::Relaxed) {
+        if let Err(e) = pipeline.run_update() {
+            error!("Scheduled update failed: {}", e);
+        }
+
+        let in_season = match pipeline.current_week() {
+            Ok(current) => current.in_season,
+            Err(e) => {
+                error!("Could not resolve the current week: {}", e);
+                true
+            }
+        };
+        if in_season && pipeline.config.odds_api_key.is_some() {
+            if let Err(e) = pipeline.import_odds() {
+                error!("Odds poll failed: {}", e);
+            }
+        }
+        let interval = schedule.interval_for(chrono::Local::now().weekday(), in_season);
+        info!("💤 Next update in {}m", interval.as_secs() / 60);
+        sleep_until_shutdown(interval, &shutdown);
+    }
+
+    info!("🛑 Shutdown signal received, daemon stopping");
+    Ok(())
+}
+
+/// Sleep for `duration`, returning early once `shutdown` is set
+pub fn sleep_until_shutdown(duration: Duration, shutdown: &AtomicBool) {
+    let mut remaining = duration;
+    while !remaining.is_zero() && !shutdown.load(Ordering::Relaxed) {
+        let step = remaining.min(SHUTDOWN_POLL);
+        thread::sleep(step);
+        remaining -= step;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game_days_use_short_interval() {
+        let schedule = Schedule {
+            game_day_interval: Duration::from_secs(15 * 60),
+            idle_interval: Duration::from_secs(60 * 60),
+        };
+
+        assert_eq!(schedule.interval_for(Weekday::Sun, true), Duration::from_secs(900));
+        assert_eq!(schedule.interval_for(Weekday::Thu, true), Duration::from_secs(900));
+        assert_eq!(schedule.interval_for(Weekday::Wed, true), Duration::from_secs(3600));
+        assert_eq!(schedule.interval_for(Weekday::Sun, false), Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn test_sleep_returns_on_shutdown() {
+        let shutdown = AtomicBool::new(true);
+        let start = std::time::Instant::now();
+        sleep_until_shutdown(Duration::from_secs(60), &shutdown);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,135 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use csv::StringRecord;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+
+use crate::datasets::{self, AnyDataset, Dataset, SourceFormat};
+use crate::downloader::{Downloader, SourceMissing};
+use crate::parser::parquet_rows::Records;
+use crate::parser::{Game, PlayerStat, RosterPlayer};
+
+/// Where the pipeline reads season files from. The nflverse HTTP downloader
+/// (which also serves `--source-dir`) is the default; embedders and tests can
+/// hand `DataPipeline::with_source` another implementation.
+///
+/// Sources deal in string records so every stage keeps its own parsing, dead
+/// letters and lineage; the `fetch_*` helpers parse a whole file for callers
+/// that just want the rows.
+pub trait DataSource: Send {
+    /// A season of `dataset` as string records, header normalized by its dialect.
+    /// A file the source doesn't have is a `SourceMissing` error.
+    fn records(&self, dataset: &AnyDataset, year: i32, format: SourceFormat) -> Result<(StringRecord, Records)>;
+
+    /// `records` with the SHA-256 of the file, for skipping unchanged files;
+    /// `None` when the source can't tell, so the file is always imported
+    fn fingerprinted_records(
+        &self,
+        dataset: &AnyDataset,
+        year: i32,
+        format: SourceFormat,
+    ) -> Result<(Option<String>, StringRecord, Records)> {
+        let (header, records) = self.records(dataset, year, format)?;
+        Ok((None, header, records))
+    }
+
+    /// Where the file behind `url` was read from and when, for lineage
+    fn origin(&self, url: &str) -> (String, DateTime<Utc>) {
+        (url.to_string(), Utc::now())
+    }
+
+    fn fetch_rosters(&self, year: i32) -> Result<Vec<RosterPlayer>> {
+        fetch(self, &datasets::ROSTERS, year)
+    }
+
+    fn fetch_stats(&self, year: i32) -> Result<Vec<PlayerStat>> {
+        fetch(self, &datasets::PLAYER_STATS, year)
+    }
+
+    fn fetch_schedule(&self, year: i32) -> Result<Vec<Game>> {
+        fetch(self, &datasets::SCHEDULES, year)
+    }
+}
+
+/// Every row of a season file, failing on the first that doesn't parse
+pub fn fetch<T: DeserializeOwned>(source: &(impl DataSource + ?Sized), dataset: &Dataset<T>, year: i32) -> Result<Vec<T>> {
+    let (header, records) = source.records(&dataset.untyped(), year, SourceFormat::Csv)?;
+    records
+        .enumerate()
+        .map(|(i, record)| {
+            let record = record?;
+            dataset.dialect.deserialize(&record, &header).with_context(|| format!("{} {} row {}", dataset.name, year, i + 1))
+        })
+        .collect()
+}
+
+impl DataSource for Downloader {
+    fn records(&self, dataset: &AnyDataset, year: i32, format: SourceFormat) -> Result<(StringRecord, Records)> {
+        Downloader::records(self, dataset, year, format)
+    }
+
+    fn fingerprinted_records(
+        &self,
+        dataset: &AnyDataset,
+        year: i32,
+        format: SourceFormat,
+    ) -> Result<(Option<String>, StringRecord, Records)> {
+        let (fingerprint, header, records) = Downloader::fingerprinted_records(self, dataset, year, format)?;
+        Ok((Some(fingerprint), header, records))
+    }
+
+    fn origin(&self, url: &str) -> (String, DateTime<Utc>) {
+        Downloader::origin(self, url)
+    }
+}
+
+/// Season files held in memory as CSV text, keyed by dataset name and season
+#[derive(Debug, Default)]
+pub struct MemorySource {
+    files: HashMap<(&'static str, i32), String>,
+}
+
+impl MemorySource {
+    pub fn with_file<T>(mut self, dataset: &Dataset<T>, year: i32, csv: &str) -> Self {
+        self.files.insert((dataset.name, year), csv.to_string());
+        self
+    }
+}
+
+impl DataSource for MemorySource {
+    fn records(&self, dataset: &AnyDataset, year: i32, format: SourceFormat) -> Result<(StringRecord, Records)> {
+        if format != SourceFormat::Csv {
+            return Err(anyhow!("MemorySource only holds CSV files"));
+        }
+        let csv = self
+            .files
+            .get(&(dataset.name, year))
+            .ok_or_else(|| SourceMissing(format!("{} {} not in memory source", dataset.name, year)))?;
+        let mut reader = dataset.dialect.reader(std::io::Cursor::new(csv.clone().into_bytes()));
+        let header = dataset.dialect.headers(reader.headers()?);
+        Ok((header, Box::new(reader.into_records().map(|r| r.map_err(anyhow::Error::from)))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_source_fetch() {
+        let source = MemorySource::default().with_file(
+            &datasets::SCHEDULES,
+            2024,
+            "game_id,season,game_type,week,gameday,away_team,home_team,away_score,home_score\n\
+             2024_01_BAL_KC,2024,REG,1,2024-09-05,BAL,KC,20,27\n",
+        );
+        let games = source.fetch_schedule(2024).unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].game_id, "2024_01_BAL_KC");
+        assert_eq!(games[0].home_score, Some(27));
+
+        let e = source.fetch_rosters(2024).unwrap_err();
+        assert!(e.downcast_ref::<SourceMissing>().is_some());
+        assert_eq!(source.fingerprinted_records(&datasets::SCHEDULES.untyped(), 2024, SourceFormat::Csv).unwrap().0, None);
+    }
+}
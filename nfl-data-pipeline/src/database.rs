@@ -1,47 +1,301 @@
 use anyhow::{Result, Context};
-use postgres::Client;
+use postgres::types::ToSql;
+use postgres::{Client, Row};
 use postgres_native_tls::MakeTlsConnector;
 use native_tls::TlsConnector;
-use log::info;
+use r2d2::PooledConnection;
+use r2d2_postgres::PostgresConnectionManager;
+use log::{info, warn};
+use std::collections::HashMap;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::corrections::{StatLine, StatCorrection, STAT_LINE_COLUMNS, SWEPT_SEASON_TYPES};
+use crate::current_week::WeekWindow;
+use crate::drift::SchemaDrift;
+use crate::metrics;
+use crate::migrations;
+use crate::views;
+
+/// TLS connection pool for the target database
+pub type Pool = r2d2::Pool<PostgresConnectionManager<MakeTlsConnector>>;
+/// A connection checked out of the pool; returned to it on drop
+pub type PooledClient = PooledConnection<PostgresConnectionManager<MakeTlsConnector>>;
 
 pub struct Database {
-    client: Client,
+    pool: Pool,
+    /// Connection held for the run's serial work; parallel writers check out others
+    client: PooledClient,
+    cache: CacheLayer,
+    /// Simulation schema, re-applied after a reconnect
+    search_path: Option<String>,
+    /// Between `begin_batch` and its commit or rollback
+    in_batch: bool,
 }
 
-impl Database {
-    pub fn connect(database_url: &str) -> Result<Self> {
-        info!("Connecting to database...");
+/// In-memory team abbreviation and NFL ID → UUID maps.
+///
+/// Once loaded the cache is authoritative: misses are not re-queried, so it must be
+/// refreshed after anything that inserts teams or players (e.g. roster import).
+#[derive(Debug, Default)]
+pub struct CacheLayer {
+    teams: HashMap<String, Uuid>,
+    players: HashMap<String, Uuid>,
+    loaded: bool,
+}
+
+impl CacheLayer {
+    /// Load all team and player IDs
+    pub fn load(client: &mut Client) -> Result<Self> {
+        let teams = client
+            .query("SELECT abbreviation, id FROM teams", &[])
+            .context("Failed to load team cache")?
+            .iter()
+            .map(|r| (r.get(0), r.get(1)))
+            .collect();
+
+        let players = client
+            .query("SELECT nfl_id, id FROM players WHERE nfl_id IS NOT NULL", &[])
+            .context("Failed to load player cache")?
+            .iter()
+            .map(|r| (r.get(0), r.get(1)))
+            .collect();
+
+        Ok(CacheLayer {
+            teams,
+            players,
+            loaded: true,
+        })
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.loaded
+    }
 
-        // Create TLS connector for Heroku PostgreSQL
-        let connector = TlsConnector::builder()
-            .danger_accept_invalid_certs(true) // Heroku uses self-signed certs
-            .build()
-            .context("Failed to create TLS connector")?;
-        let connector = MakeTlsConnector::new(connector);
+    pub fn team_id(&self, abbr: &str) -> Option<Uuid> {
+        self.teams.get(abbr).copied()
+    }
 
-        let client = Client::connect(database_url, connector)
-            .context("Failed to connect to database")?;
+    pub fn player_id(&self, nfl_id: &str) -> Option<Uuid> {
+        self.players.get(nfl_id).copied()
+    }
+
+    pub fn team_count(&self) -> usize {
+        self.teams.len()
+    }
 
-        info!("✅ Database connected");
-        Ok(Database { client })
+    pub fn player_count(&self) -> usize {
+        self.players.len()
     }
+}
+
+/// Weekly import state for an in-season dataset, derived from the games table
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeekStatus {
+    pub week: i32,
+    /// Every game in the week has a final score
+    pub complete: bool,
+}
 
+/// Reconnect attempts before a dropped connection is reported as an error
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+
+impl Database {
+    /// Open a pool of up to `pool_size` connections and check one out for serial work
+    pub fn connect(database_url: &str, pool_size: u32) -> Result<Self> {
+        info!("Connecting to database...");
+        let pool = build_pool(database_url, pool_size)?;
+        let client = pool.get().context("Failed to connect to database")?;
+
+        info!("✅ Database connected (pool of {})", pool_size);
+        Ok(Database {
+            pool,
+            client,
+            cache: CacheLayer::default(),
+            search_path: None,
+            in_batch: false,
+        })
+    }
+
+    /// The underlying client, reconnected first if the server closed it while
+    /// idle. A failed reconnect is logged and the closed client returned, so the
+    /// caller's next statement reports the error.
     pub fn get_client(&mut self) -> &mut Client {
+        if self.client.is_closed() && !self.in_batch {
+            if let Err(e) = self.reconnect() {
+                warn!("⚠️  Database reconnect failed: {:#}", e);
+            }
+        }
         &mut self.client
     }
 
-    /// Check if database connection is healthy
-    pub fn health_check(&mut self) -> Result<()> {
-        self.client
-            .query("SELECT 1", &[])
-            .context("Health check failed")?;
+    /// The pool behind this connection, for parallel writers
+    pub fn pool(&self) -> &Pool {
+        &self.pool
+    }
+
+    /// Simulation schema every connection must select, if any
+    pub fn search_path(&self) -> Option<&str> {
+        self.search_path.as_deref()
+    }
+
+    /// Replace the connection, retrying with exponential backoff (2s, 4s, 8s, ...).
+    /// The pool discards the broken connection when it is returned. A
+    /// simulation's search path is restored on the new session.
+    pub fn reconnect(&mut self) -> Result<()> {
+        let mut attempt = 1;
+        loop {
+            warn!("🔌 Reconnecting to database (attempt {}/{})...", attempt, MAX_RECONNECT_ATTEMPTS);
+            metrics::retry("database");
+            match self.pool.get().context("Failed to connect to database") {
+                Ok(mut client) => {
+                    select_search_path(&mut client, self.search_path.as_deref())?;
+                    self.client = client;
+                    self.in_batch = false;
+                    info!("✅ Database reconnected");
+                    return Ok(());
+                }
+                Err(e) if attempt == MAX_RECONNECT_ATTEMPTS => return Err(e),
+                Err(e) => warn!("Reconnect failed: {:#}", e),
+            }
+            std::thread::sleep(Duration::from_secs(2u64.pow(attempt)));
+            attempt += 1;
+        }
+    }
+
+    /// Run a statement, reconnecting and retrying it if the connection dropped.
+    ///
+    /// Inside a batch transaction nothing is retried: the open transaction died
+    /// with the connection, so the error goes back to the batch to roll it up.
+    fn with_retry<T>(&mut self, mut run: impl FnMut(&mut Client) -> std::result::Result<T, postgres::Error>) -> Result<T> {
+        let mut attempt = 1;
+        loop {
+            match run(&mut self.client) {
+                Ok(value) => return Ok(value),
+                Err(e) if !self.in_batch && attempt < MAX_RECONNECT_ATTEMPTS && is_connection_lost(&e) => {
+                    warn!("⚠️  Database connection lost: {}", e);
+                    self.reconnect()?;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    pub fn execute(&mut self, sql: &str, params: &[&(dyn ToSql + Sync)]) -> Result<u64> {
+        self.with_retry(|client| client.execute(sql, params))
+    }
+
+    pub fn query(&mut self, sql: &str, params: &[&(dyn ToSql + Sync)]) -> Result<Vec<Row>> {
+        self.with_retry(|client| client.query(sql, params))
+    }
+
+    pub fn query_one(&mut self, sql: &str, params: &[&(dyn ToSql + Sync)]) -> Result<Row> {
+        self.with_retry(|client| client.query_one(sql, params))
+    }
+
+    pub fn query_opt(&mut self, sql: &str, params: &[&(dyn ToSql + Sync)]) -> Result<Option<Row>> {
+        self.with_retry(|client| client.query_opt(sql, params))
+    }
+
+    /// Build a throwaway schema from the embedded migrations and view pack and
+    /// point this connection at it, so a run writes nothing to production tables.
+    /// Its tables have their own sequences, triggers and views.
+    ///
+    /// Teams are copied with their rows since every stage resolves team IDs.
+    /// Returns the schema name.
+    pub fn enter_simulation(&mut self) -> Result<String> {
+        let schema = format!("simulate_{}", std::process::id());
+
+        let mut tx = self.get_client().transaction()?;
+        tx.batch_execute(&format!("CREATE SCHEMA {}", schema))?;
+        let version = migrations::apply_to_schema(&mut tx, &schema)?;
+        views::create(&mut tx)?;
+
+        // Production may have columns the migrations don't (or lack newer ones)
+        let columns: Vec<String> = tx
+            .query(
+                "SELECT format('%I', s.column_name) FROM information_schema.columns s
+                 JOIN information_schema.columns p
+                   ON p.table_name = s.table_name AND p.column_name = s.column_name AND p.table_schema = 'public'
+                 WHERE s.table_schema = $1 AND s.table_name = 'teams'
+                 ORDER BY s.ordinal_position",
+                &[&schema],
+            )?
+            .iter()
+            .map(|r| r.get(0))
+            .collect();
+        if !columns.is_empty() {
+            let columns = columns.join(", ");
+            tx.batch_execute(&format!("INSERT INTO {schema}.teams ({columns}) SELECT {columns} FROM public.teams"))?;
+        }
+        tx.commit()?;
+
+        self.client.batch_execute(&format!("SET search_path TO {}, public", schema))?;
+        self.search_path = Some(schema.clone());
+        info!("🧪 Simulation schema {} created at migration {} ({} views)", schema, version, views::VIEWS.len());
+        Ok(schema)
+    }
+
+    /// Non-empty tables in a simulation schema with their row counts
+    pub fn simulation_row_counts(&mut self, schema: &str) -> Result<Vec<(String, i64)>> {
+        let tables: Vec<String> = self.query(
+                "SELECT table_name FROM information_schema.tables
+                 WHERE table_schema = $1 AND table_type = 'BASE TABLE'
+                   AND table_name NOT IN ('schema_migrations', 'schema_views')
+                 ORDER BY table_name",
+                &[&schema],
+            )?
+            .iter()
+            .map(|r| r.get(0))
+            .collect();
+
+        let mut counts = Vec::new();
+        for table in tables {
+            let row = self.query_one(&format!("SELECT COUNT(*) FROM {}.\"{}\"", schema, table), &[])?;
+            let count: i64 = row.get(0);
+            if count > 0 {
+                counts.push((table, count));
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// Drop a simulation schema and restore the default search path
+    pub fn drop_simulation(&mut self, schema: &str) -> Result<()> {
+        self.client.batch_execute(&format!(
+            "SET search_path TO public; DROP SCHEMA IF EXISTS {} CASCADE",
+            schema
+        ))?;
+        self.search_path = None;
+        info!("🧹 Simulation schema {} dropped", schema);
         Ok(())
     }
 
+    /// Reload the team and player ID caches
+    pub fn refresh_cache(&mut self) -> Result<()> {
+        self.cache = CacheLayer::load(self.get_client())?;
+        info!(
+            "🗂️  ID cache loaded: {} teams, {} players",
+            self.cache.team_count(),
+            self.cache.player_count()
+        );
+        Ok(())
+    }
+
+    /// Teams in the ID cache
+    pub fn team_count(&self) -> usize {
+        self.cache.team_count()
+    }
+
     /// Get team ID by abbreviation
     pub fn get_team_id_by_abbr(&mut self, abbr: &str) -> Result<Option<uuid::Uuid>> {
-        let row = self.client
-            .query_opt(
+        if self.cache.is_loaded() {
+            return Ok(self.cache.team_id(abbr));
+        }
+
+        let row = self.query_opt(
                 "SELECT id FROM teams WHERE abbreviation = $1",
                 &[&abbr],
             )?;
@@ -51,8 +305,11 @@ impl Database {
 
     /// Get player ID by NFL ID (gsis_id)
     pub fn get_player_id_by_nfl_id(&mut self, nfl_id: &str) -> Result<Option<uuid::Uuid>> {
-        let row = self.client
-            .query_opt(
+        if self.cache.is_loaded() {
+            return Ok(self.cache.player_id(nfl_id));
+        }
+
+        let row = self.query_opt(
                 "SELECT id FROM players WHERE nfl_id = $1",
                 &[&nfl_id],
             )?;
@@ -60,15 +317,22 @@ impl Database {
         Ok(row.map(|r| r.get(0)))
     }
 
-    /// Get import progress status for a season and data type
-    pub fn get_import_progress(&mut self, season: i32, data_type: &str) -> Result<Option<String>> {
-        let row = self.client
-            .query_opt(
-                "SELECT status FROM import_progress WHERE season = $1 AND data_type = $2",
-                &[&season, &data_type],
-            )?;
+    /// Map of (lowercased name, lowercased college) → player ID, used to link college production.
+    /// Ambiguous pairs (two players sharing name and school) are left out.
+    pub fn get_player_ids_by_name_college(&mut self) -> Result<HashMap<(String, String), Uuid>> {
+        let rows = self.query(
+            "SELECT LOWER(name), LOWER(college), MIN(id::text)::uuid
+             FROM players
+             WHERE college IS NOT NULL
+             GROUP BY LOWER(name), LOWER(college)
+             HAVING COUNT(*) = 1",
+            &[],
+        )?;
 
-        Ok(row.map(|r| r.get(0)))
+        Ok(rows
+            .iter()
+            .map(|r| ((r.get(0), r.get(1)), r.get(2)))
+            .collect())
     }
 
     /// Mark import progress
@@ -79,24 +343,178 @@ impl Database {
         status: &str,
         records_imported: i32,
     ) -> Result<()> {
-        self.client.execute(
-            "INSERT INTO import_progress (season, data_type, status, records_imported, started_at, completed_at)
-             VALUES ($1, $2, $3, $4, NOW(), CASE WHEN $3 = 'completed' THEN NOW() ELSE NULL END)
-             ON CONFLICT (season, data_type)
+        self.mark_week_progress(season, data_type, 0, status, records_imported)
+    }
+
+    /// Mark import progress for a single week (week 0 = entire season)
+    pub fn mark_week_progress(
+        &mut self,
+        season: i32,
+        data_type: &str,
+        week: i32,
+        status: &str,
+        records_imported: i32,
+    ) -> Result<()> {
+        self.execute(
+            "INSERT INTO import_progress (season, data_type, week, status, records_imported, started_at, completed_at)
+             VALUES ($1, $2, $3, $4, $5, NOW(), CASE WHEN $4::varchar = 'completed' THEN NOW() ELSE NULL END)
+             ON CONFLICT (season, data_type, week)
              DO UPDATE SET
-                 status = EXCLUDED.status,
+                 status = CASE
+                     WHEN EXCLUDED.status = 'completed' AND import_progress.failed_batches > 0 THEN 'partial'
+                     ELSE EXCLUDED.status
+                 END,
                  records_imported = EXCLUDED.records_imported,
+                 started_at = EXCLUDED.started_at,
                  completed_at = EXCLUDED.completed_at",
-            &[&season, &data_type, &status, &records_imported],
+            &[&season, &data_type, &week, &status, &records_imported],
+        )?;
+
+        Ok(())
+    }
+
+    /// Clear failed-batch counts before a stage runs again
+    pub fn reset_failed_batches(&mut self, season: i32, data_type: &str) -> Result<()> {
+        self.execute(
+            "UPDATE import_progress SET failed_batches = 0, failed_records = 0, error_message = NULL
+             WHERE season = $1 AND data_type = $2 AND week = 0",
+            &[&season, &data_type],
+        )?;
+        Ok(())
+    }
+
+    /// Fingerprint of the source file behind the season's last completed import
+    pub fn completed_fingerprint(&mut self, season: i32, data_type: &str) -> Result<Option<String>> {
+        let row = self.query_opt(
+            "SELECT source_fingerprint FROM import_progress
+             WHERE season = $1 AND data_type = $2 AND week = 0 AND status = 'completed'",
+            &[&season, &data_type],
+        )?;
+        Ok(row.and_then(|row| row.get(0)))
+    }
+
+    /// Store the fingerprint of the file a season import was read from
+    pub fn record_fingerprint(&mut self, season: i32, data_type: &str, fingerprint: &str) -> Result<()> {
+        self.execute(
+            "UPDATE import_progress SET source_fingerprint = $3 WHERE season = $1 AND data_type = $2 AND week = 0",
+            &[&season, &data_type, &fingerprint],
+        )?;
+        Ok(())
+    }
+
+    /// Mark a season import failed, with the reason in `error_message`
+    pub fn mark_failed(&mut self, season: i32, data_type: &str, error: &str) -> Result<()> {
+        self.execute(
+            "INSERT INTO import_progress (season, data_type, week, status, started_at, error_message)
+             VALUES ($1, $2, 0, 'failed', NOW(), $3)
+             ON CONFLICT (season, data_type, week)
+             DO UPDATE SET status = 'failed', completed_at = NULL, error_message = EXCLUDED.error_message",
+            &[&season, &data_type, &error],
+        )?;
+        Ok(())
+    }
+
+    /// Record a rolled-back batch against the season's progress row. A later
+    /// `completed` mark for the same run is stored as `partial`.
+    pub fn record_failed_batch(&mut self, season: i32, data_type: &str, records: i32, error: &str) -> Result<()> {
+        self.execute(
+            "INSERT INTO import_progress (season, data_type, week, status, started_at, failed_batches, failed_records, error_message)
+             VALUES ($1, $2, 0, 'in_progress', NOW(), 1, $3, $4)
+             ON CONFLICT (season, data_type, week)
+             DO UPDATE SET
+                 failed_batches = import_progress.failed_batches + 1,
+                 failed_records = import_progress.failed_records + EXCLUDED.failed_records,
+                 error_message = EXCLUDED.error_message",
+            &[&season, &data_type, &records, &error],
         )?;
+        Ok(())
+    }
+
+    /// Open a transaction on the connection for one batch of row-by-row writes.
+    ///
+    /// Writers that open their own `transaction()` must not run inside it.
+    pub fn begin_batch(&mut self) -> Result<()> {
+        self.with_retry(|client| client.batch_execute("BEGIN"))?;
+        self.in_batch = true;
+        Ok(())
+    }
 
+    pub fn commit_batch(&mut self) -> Result<()> {
+        self.in_batch = false;
+        self.client.batch_execute("COMMIT")?;
         Ok(())
     }
 
+    /// Roll back the open batch. A dropped connection has already discarded it.
+    pub fn rollback_batch(&mut self) -> Result<()> {
+        self.in_batch = false;
+        if self.client.is_closed() {
+            return Ok(());
+        }
+        self.client.batch_execute("ROLLBACK")?;
+        Ok(())
+    }
+
+    /// Weeks with games that finished since the dataset's last completed weekly import.
+    ///
+    /// Weeks where no game has finished yet are left out; partially finished weeks are
+    /// returned with `complete = false` so they are re-pulled on the next run.
+    pub fn get_weeks_needing_update(&mut self, season: i32, data_type: &str) -> Result<Vec<WeekStatus>> {
+        let rows = self.query(
+            "SELECT g.week,
+                    bool_and(g.home_score IS NOT NULL AND g.away_score IS NOT NULL) AS complete
+             FROM games g
+             LEFT JOIN import_progress ip
+                    ON ip.season = g.season
+                   AND ip.week = g.week
+                   AND ip.data_type = $2
+                   AND ip.status = 'completed'
+             WHERE g.season = $1 AND g.season_type <> 'PRE'
+             GROUP BY g.week, ip.completed_at
+             HAVING bool_or(g.home_score IS NOT NULL AND g.away_score IS NOT NULL)
+                AND (ip.completed_at IS NULL
+                     OR MAX(g.updated_at) FILTER (WHERE g.home_score IS NOT NULL) > ip.completed_at)
+             ORDER BY g.week",
+            &[&season, &data_type],
+        )?;
+
+        Ok(rows
+            .iter()
+            .map(|r| WeekStatus {
+                week: r.get(0),
+                complete: r.get(1),
+            })
+            .collect())
+    }
+
+    /// First/last game date and completion for each non-preseason week of a season
+    pub fn get_week_windows(&mut self, season: i32) -> Result<Vec<WeekWindow>> {
+        let rows = self.query(
+            "SELECT season_type, week,
+                    MIN(game_date AT TIME ZONE 'America/New_York')::date, MAX(game_date AT TIME ZONE 'America/New_York')::date,
+                    bool_and(home_score IS NOT NULL AND away_score IS NOT NULL)
+             FROM games
+             WHERE season = $1 AND season_type <> 'PRE' AND game_date IS NOT NULL
+             GROUP BY season_type, week
+             ORDER BY MIN(game_date)",
+            &[&season],
+        )?;
+
+        Ok(rows
+            .iter()
+            .map(|r| WeekWindow {
+                season_type: r.get(0),
+                week: r.get(1),
+                first_game: r.get(2),
+                last_game: r.get(3),
+                complete: r.get(4),
+            })
+            .collect())
+    }
+
     /// Get count of games for a season
     pub fn count_games(&mut self, season: i32) -> Result<i64> {
-        let row = self.client
-            .query_one(
+        let row = self.query_one(
                 "SELECT COUNT(*) FROM games WHERE season = $1",
                 &[&season],
             )?;
@@ -106,20 +524,178 @@ impl Database {
 
     /// Get count of players for a season
     pub fn count_players(&mut self) -> Result<i64> {
-        let row = self.client
-            .query_one("SELECT COUNT(*) FROM players", &[])?;
+        let row = self.query_one("SELECT COUNT(*) FROM players", &[])?;
 
         Ok(row.get(0))
     }
 
     /// Get count of game stats for a season
     pub fn count_game_stats(&mut self, season: i32) -> Result<i64> {
-        let row = self.client
-            .query_one(
+        let row = self.query_one(
                 "SELECT COUNT(*) FROM game_stats WHERE season = $1",
                 &[&season],
             )?;
 
         Ok(row.get(0))
     }
+
+    /// Latest regular or postseason week with stored stats for a season
+    pub fn get_latest_stat_week(&mut self, season: i32) -> Result<Option<i32>> {
+        let row = self.query_one(
+                "SELECT MAX(week) FROM game_stats WHERE season = $1 AND season_type = ANY($2)",
+                &[&season, &&SWEPT_SEASON_TYPES[..]],
+            )?;
+
+        Ok(row.get(0))
+    }
+
+    /// Get the stored stat line for a player and week
+    pub fn get_stat_line(&mut self, player_id: uuid::Uuid, season: i32, week: i32, season_type: &str) -> Result<Option<StatLine>> {
+        let query = format!(
+            "SELECT {} FROM game_stats WHERE player_id = $1 AND season = $2 AND week = $3 AND season_type = $4",
+            STAT_LINE_COLUMNS
+        );
+        let row = self.query_opt(query.as_str(), &[&player_id, &season, &week, &season_type])?;
+
+        Ok(row.map(|r| StatLine::from_row(&r)))
+    }
+
+    /// Record a stat correction detected by the correction sweep
+    pub fn record_correction(
+        &mut self,
+        player_id: uuid::Uuid,
+        season: i32,
+        week: i32,
+        correction: &StatCorrection,
+    ) -> Result<()> {
+        self.execute(
+            "INSERT INTO stat_corrections (player_id, season, week, field, old_value, new_value)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+            &[
+                &player_id,
+                &season,
+                &week,
+                &correction.field,
+                &correction.old_value,
+                &correction.new_value,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Link combine participants to players through their draft pick's PFR ID
+    pub fn link_combine_results(&mut self) -> Result<u64> {
+        let linked = self.execute(
+            "UPDATE combine_results c
+             SET player_id = d.player_id, updated_at = NOW()
+             FROM draft_picks d
+             WHERE c.pfr_id = d.pfr_player_id
+               AND d.player_id IS NOT NULL
+               AND c.player_id IS DISTINCT FROM d.player_id",
+            &[],
+        )?;
+
+        Ok(linked)
+    }
+
+    /// Columns recorded for the most recent earlier season of a dataset
+    pub fn get_previous_dataset_columns(&mut self, dataset: &str, season: i32) -> Result<Option<Vec<String>>> {
+        let row = self.query_opt(
+            "SELECT columns FROM dataset_schemas
+             WHERE dataset = $1 AND season < $2
+             ORDER BY season DESC
+             LIMIT 1",
+            &[&dataset, &season],
+        )?;
+
+        Ok(row.map(|r| r.get(0)))
+    }
+
+    /// Record a dataset file's header and its drift
+    pub fn record_dataset_schema(
+        &mut self,
+        dataset: &str,
+        season: i32,
+        columns: &[String],
+        drift: &SchemaDrift,
+    ) -> Result<()> {
+        self.execute(
+            "INSERT INTO dataset_schemas (dataset, season, columns, missing_fields, added_columns, removed_columns)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (dataset, season) DO UPDATE SET
+                 columns = EXCLUDED.columns,
+                 missing_fields = EXCLUDED.missing_fields,
+                 added_columns = EXCLUDED.added_columns,
+                 removed_columns = EXCLUDED.removed_columns,
+                 recorded_at = NOW()",
+            &[
+                &dataset,
+                &season,
+                &columns,
+                &drift.missing_fields,
+                &drift.added_columns,
+                &drift.removed_columns,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+}
+
+/// Point a session at a simulation schema, or back at the default search path.
+/// Pooled connections keep session settings, so every checkout sets it.
+pub fn select_search_path(client: &mut Client, schema: Option<&str>) -> Result<()> {
+    match schema {
+        Some(schema) => client.batch_execute(&format!("SET search_path TO {}, public", schema))?,
+        None => client.batch_execute("RESET search_path")?,
+    }
+    Ok(())
+}
+
+/// Pool of TLS connections (Heroku Postgres uses self-signed certificates).
+/// Broken connections are dropped when returned and replaced on demand.
+fn tls_connector() -> Result<MakeTlsConnector> {
+    let connector = TlsConnector::builder()
+        .danger_accept_invalid_certs(true)
+        .build()
+        .context("Failed to create TLS connector")?;
+    Ok(MakeTlsConnector::new(connector))
+}
+
+fn build_pool(database_url: &str, size: u32) -> Result<Pool> {
+    let config: postgres::Config = database_url.parse().context("Invalid DATABASE_URL")?;
+
+    r2d2::Pool::builder()
+        .max_size(size)
+        .min_idle(Some(1))
+        .connection_timeout(Duration::from_secs(30))
+        .build(PostgresConnectionManager::new(config, tls_connector()?))
+        .context("Failed to connect to database")
+}
+
+/// A connection outside the pool, for a session held open for its lifetime
+/// (e.g. one waiting on `LISTEN`)
+pub fn connect_unpooled(database_url: &str) -> Result<Client> {
+    let config: postgres::Config = database_url.parse().context("Invalid DATABASE_URL")?;
+    config.connect(tls_connector()?).context("Failed to connect to database")
+}
+
+/// Errors that mean the session is gone (closed socket, server shutdown or
+/// restart) rather than a problem with the statement
+pub fn is_connection_lost(e: &postgres::Error) -> bool {
+    if e.is_closed() {
+        return true;
+    }
+    if let Some(code) = e.code() {
+        return code.code().starts_with("08") || code.code().starts_with("57P");
+    }
+    std::error::Error::source(e).is_some_and(|source| source.is::<std::io::Error>())
+}
+
+/// `is_connection_lost` for an error that passed through anyhow context
+pub fn is_connection_error(e: &anyhow::Error) -> bool {
+    e.chain()
+        .any(|cause| cause.downcast_ref::<postgres::Error>().is_some_and(is_connection_lost))
 }
@@ -0,0 +1,388 @@
+use csv::{Reader, ReaderBuilder, StringRecord};
+use serde::de::DeserializeOwned;
+use std::io::Read;
+use std::marker::PhantomData;
+
+use crate::parser::{RosterPlayer, PlayerStat, Game, NGSPassing, NGSRushing, NGSReceiving, Injury, DepthChartEntry, SnapCount, PbpPlay, DraftPick, CombineResult, Contract, CollegeStat, TeamRecord};
+
+/// Data-driven description of a downloadable dataset.
+///
+/// A descriptor ties together where a dataset lives, which parser struct its rows
+/// deserialize into (`T`), and which table they land in, so a new source only needs
+/// a descriptor and an upsert instead of a copy of the stage code.
+#[derive(Debug)]
+pub struct Dataset<T> {
+    /// Key used for `import_progress.data_type` and logging
+    pub name: &'static str,
+    /// League the dataset belongs to (e.g. "nfl", "ncaaf")
+    pub league: &'static str,
+    /// Download URL with a `{year}` placeholder (omitted for non-seasonal files)
+    pub url_template: &'static str,
+    /// Table the parsed rows are written to
+    pub target_table: &'static str,
+    /// First season the source publishes, if limited
+    pub min_year: Option<i32>,
+    /// How the source writes its CSV files
+    pub dialect: CsvDialect,
+    /// Columns identifying a row in `data_lineage` (alternatives as `a|b`)
+    pub key_columns: &'static [&'static str],
+    row: PhantomData<fn() -> T>,
+}
+
+/// A descriptor without its row type, as `DataSource`s take it: they deliver
+/// records, which stages parse
+pub type AnyDataset = Dataset<()>;
+
+/// File format requested from a source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceFormat {
+    Csv,
+    /// nflverse publishes Parquet next to most CSV assets (same name, `.parquet`)
+    Parquet,
+}
+
+/// How header names are normalized before matching parser fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderCase {
+    /// Use headers exactly as written
+    AsIs,
+    /// Lowercase, with spaces, dashes and dots turned into underscores ("Player Name" → "player_name")
+    Snake,
+}
+
+/// CSV settings for a dataset's source files
+#[derive(Debug, Clone, Copy)]
+pub struct CsvDialect {
+    pub delimiter: u8,
+    /// Whether fields may be quoted; disable for sources that use quotes literally
+    pub quoting: bool,
+    pub quote: u8,
+    /// Cell values treated as empty before deserialization
+    pub na_tokens: &'static [&'static str],
+    pub header_case: HeaderCase,
+}
+
+impl CsvDialect {
+    /// nflverse release files
+    pub const NFLVERSE: CsvDialect = CsvDialect {
+        delimiter: b',',
+        quoting: true,
+        quote: b'"',
+        na_tokens: &["NA"],
+        header_case: HeaderCase::AsIs,
+    };
+
+    /// sportsdataverse release files, written from both R and Python over the
+    /// years, so header case varies and missing values may be `NA` or `NaN`
+    pub const SPORTSDATAVERSE: CsvDialect = CsvDialect {
+        na_tokens: &["NA", "NaN"],
+        header_case: HeaderCase::Snake,
+        ..CsvDialect::NFLVERSE
+    };
+
+    /// Build a reader for a downloaded file
+    pub fn reader<R: Read>(&self, data: R) -> Reader<R> {
+        ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .quoting(self.quoting)
+            .quote(self.quote)
+            .from_reader(data)
+    }
+
+    /// Normalize header names according to `header_case`
+    pub fn headers(&self, raw: &StringRecord) -> StringRecord {
+        match self.header_case {
+            HeaderCase::AsIs => raw.clone(),
+            HeaderCase::Snake => raw
+                .iter()
+                .map(|h| {
+                    h.trim()
+                        .to_lowercase()
+                        .chars()
+                        .map(|c| if matches!(c, ' ' | '-' | '.') { '_' } else { c })
+                        .collect::<String>()
+                })
+                .collect(),
+        }
+    }
+
+    /// Deserialize a record, blanking NA tokens first
+    pub fn deserialize<T: DeserializeOwned>(&self, record: &StringRecord, headers: &StringRecord) -> csv::Result<T> {
+        if self.na_tokens.is_empty() || !record.iter().any(|v| self.na_tokens.contains(&v)) {
+            return record.deserialize(Some(headers));
+        }
+
+        let cleaned: StringRecord = record
+            .iter()
+            .map(|v| if self.na_tokens.contains(&v) { "" } else { v })
+            .collect();
+        cleaned.deserialize(Some(headers))
+    }
+}
+
+impl<T> Dataset<T> {
+    pub const fn new(
+        name: &'static str,
+        league: &'static str,
+        url_template: &'static str,
+        target_table: &'static str,
+        min_year: Option<i32>,
+    ) -> Self {
+        Dataset {
+            name,
+            league,
+            url_template,
+            target_table,
+            min_year,
+            dialect: CsvDialect::NFLVERSE,
+            key_columns: &[],
+            row: PhantomData,
+        }
+    }
+
+    /// Override the CSV dialect for sources that don't write nflverse-style files
+    pub const fn with_dialect(self, dialect: CsvDialect) -> Self {
+        Dataset { dialect, ..self }
+    }
+
+    /// Columns identifying a row, for lineage keys readers can look up
+    pub const fn keyed(self, key_columns: &'static [&'static str]) -> Self {
+        Dataset { key_columns, ..self }
+    }
+
+    /// This descriptor as an `AnyDataset`
+    pub const fn untyped(&self) -> AnyDataset {
+        Dataset {
+            name: self.name,
+            league: self.league,
+            url_template: self.url_template,
+            target_table: self.target_table,
+            min_year: self.min_year,
+            dialect: self.dialect,
+            key_columns: self.key_columns,
+            row: PhantomData,
+        }
+    }
+
+    /// Resolve the download URL for a season
+    pub fn url(&self, year: i32) -> String {
+        self.url_template.replace("{year}", &year.to_string())
+    }
+
+    /// Download URL for a season in the given format
+    pub fn url_as(&self, year: i32, format: SourceFormat) -> String {
+        let url = self.url(year);
+        let stem = url.strip_suffix(".csv.gz").or_else(|| url.strip_suffix(".csv"));
+        match (format, stem) {
+            (SourceFormat::Parquet, Some(stem)) => format!("{}.parquet", stem),
+            _ => url,
+        }
+    }
+
+    /// Whether the source publishes this dataset for a season
+    pub fn is_available(&self, year: i32) -> bool {
+        self.min_year.is_none_or(|min| year >= min)
+    }
+
+    /// File name of the season's asset (last URL path segment)
+    pub fn file_name(&self, year: i32) -> String {
+        let url = self.url(year);
+        url.rsplit('/').next().unwrap_or(&url).to_string()
+    }
+}
+
+const NFLVERSE: &str = "nfl";
+const CFBFASTR: &str = "ncaaf";
+
+pub const ROSTERS: Dataset<RosterPlayer> = Dataset::new(
+    "rosters",
+    NFLVERSE,
+    "https://github.com/nflverse/nflverse-data/releases/download/rosters/roster_{year}.csv",
+    "players",
+    None,
+)
+.keyed(&["gsis_id"]);
+
+pub const WEEKLY_ROSTERS: Dataset<RosterPlayer> = Dataset::new(
+    "weekly_rosters",
+    NFLVERSE,
+    "https://github.com/nflverse/nflverse-data/releases/download/weekly_rosters/roster_weekly_{year}.csv",
+    "roster_moves",
+    Some(2002),
+)
+.keyed(&["gsis_id", "week"]);
+
+pub const PLAYER_STATS: Dataset<PlayerStat> = Dataset::new(
+    "player_stats",
+    NFLVERSE,
+    "https://github.com/nflverse/nflverse-data/releases/download/player_stats/player_stats_{year}.csv",
+    "game_stats",
+    None,
+)
+.keyed(&["player_id", "season", "week", "season_type"]);
+
+pub const SCHEDULES: Dataset<Game> = Dataset::new(
+    "schedule",
+    NFLVERSE,
+    "https://github.com/nflverse/nflverse-data/releases/download/schedules/sched_{year}.csv",
+    "games",
+    None,
+)
+.keyed(&["game_id"]);
+
+pub const NGS_PASSING: Dataset<NGSPassing> = Dataset::new(
+    "ngs_passing",
+    NFLVERSE,
+    "https://github.com/nflverse/nflverse-data/releases/download/nextgen_stats/ngs_{year}_passing.csv",
+    "advanced_stats",
+    Some(2016),
+)
+.keyed(&["player_gsis_id|gsis_id", "season", "season_type", "week"]);
+
+pub const NGS_RUSHING: Dataset<NGSRushing> = Dataset::new(
+    "ngs_rushing",
+    NFLVERSE,
+    "https://github.com/nflverse/nflverse-data/releases/download/nextgen_stats/ngs_{year}_rushing.csv",
+    "advanced_stats",
+    Some(2016),
+)
+.keyed(&["player_gsis_id|gsis_id", "season", "season_type", "week"]);
+
+pub const NGS_RECEIVING: Dataset<NGSReceiving> = Dataset::new(
+    "ngs_receiving",
+    NFLVERSE,
+    "https://github.com/nflverse/nflverse-data/releases/download/nextgen_stats/ngs_{year}_receiving.csv",
+    "advanced_stats",
+    Some(2016),
+)
+.keyed(&["player_gsis_id|gsis_id", "season", "season_type", "week"]);
+
+pub const INJURIES: Dataset<Injury> = Dataset::new(
+    "injuries",
+    NFLVERSE,
+    "https://github.com/nflverse/nflverse-data/releases/download/injuries/injuries_{year}.csv",
+    "injuries",
+    Some(2009),
+)
+.keyed(&["gsis_id", "season", "game_type", "week"]);
+
+pub const DEPTH_CHARTS: Dataset<DepthChartEntry> = Dataset::new(
+    "depth_charts",
+    NFLVERSE,
+    "https://github.com/nflverse/nflverse-data/releases/download/depth_charts/depth_charts_{year}.csv",
+    "depth_charts",
+    Some(2001),
+);
+
+pub const SNAP_COUNTS: Dataset<SnapCount> = Dataset::new(
+    "snap_counts",
+    NFLVERSE,
+    "https://github.com/nflverse/nflverse-data/releases/download/snap_counts/snap_counts_{year}.csv",
+    "snap_counts",
+    Some(2012),
+)
+.keyed(&["game_id", "pfr_player_id"]);
+
+pub const PLAY_BY_PLAY: Dataset<PbpPlay> = Dataset::new(
+    "play_by_play",
+    NFLVERSE,
+    "https://github.com/nflverse/nflverse-data/releases/download/pbp/play_by_play_{year}.csv",
+    "efficiency_metrics",
+    Some(1999),
+)
+.keyed(&["game_id", "play_id"]);
+
+pub const DRAFT_PICKS: Dataset<DraftPick> = Dataset::new(
+    "draft_picks",
+    NFLVERSE,
+    "https://github.com/nflverse/nflverse-data/releases/download/draft_picks/draft_picks.csv",
+    "draft_picks",
+    None,
+)
+.keyed(&["season", "pick"]);
+
+pub const COMBINE: Dataset<CombineResult> = Dataset::new(
+    "combine",
+    NFLVERSE,
+    "https://github.com/nflverse/nflverse-data/releases/download/combine/combine.csv",
+    "combine_results",
+    None,
+);
+
+pub const COLLEGE_STATS: Dataset<CollegeStat> = Dataset::new(
+    "college_stats",
+    CFBFASTR,
+    "https://github.com/sportsdataverse/cfbfastR-data/releases/download/player_stats/player_stats_{year}.csv",
+    "college_stats",
+    Some(2004),
+)
+.with_dialect(CsvDialect::SPORTSDATAVERSE)
+.keyed(&["athlete_id", "season"]);
+
+/// Franchise names, divisions, colors and logos (every team in one file)
+pub const TEAMS: Dataset<TeamRecord> = Dataset::new(
+    "teams",
+    NFLVERSE,
+    "https://github.com/nflverse/nflverse-data/releases/download/teams/teams_colors_logos.csv",
+    "teams",
+    None,
+)
+.keyed(&["team_abbr"]);
+
+/// OverTheCap contract history (every contract in one gzipped file)
+pub const CONTRACTS: Dataset<Contract> = Dataset::new(
+    "contracts",
+    NFLVERSE,
+    "https://github.com/nflverse/nflverse-data/releases/download/contracts/historical_contracts.csv.gz",
+    "player_contracts",
+    None,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_and_file_name() {
+        assert_eq!(
+            ROSTERS.url(2024),
+            "https://github.com/nflverse/nflverse-data/releases/download/rosters/roster_2024.csv"
+        );
+        assert_eq!(PLAYER_STATS.file_name(2023), "player_stats_2023.csv");
+        assert_eq!(
+            INJURIES.url_as(2024, SourceFormat::Parquet),
+            "https://github.com/nflverse/nflverse-data/releases/download/injuries/injuries_2024.parquet"
+        );
+        assert_eq!(
+            CONTRACTS.url_as(0, SourceFormat::Parquet),
+            "https://github.com/nflverse/nflverse-data/releases/download/contracts/historical_contracts.parquet"
+        );
+    }
+
+    #[test]
+    fn test_dialect_normalizes_headers_and_na() {
+        let dialect = CsvDialect {
+            delimiter: b'|',
+            na_tokens: &["--", "NA"],
+            header_case: HeaderCase::Snake,
+            ..CsvDialect::NFLVERSE
+        };
+        let data = "Season|Game Type|Team|Week|GSIS-ID|Report Status\n2024|REG|KC|1|00-0033873|--\n";
+
+        let mut reader = dialect.reader(data.as_bytes());
+        let headers = dialect.headers(reader.headers().unwrap());
+        assert_eq!(&headers[4], "gsis_id");
+
+        let record = reader.records().next().unwrap().unwrap();
+        let injury: Injury = dialect.deserialize(&record, &headers).unwrap();
+        assert_eq!(injury.team, "KC");
+        assert_eq!(injury.report_status, None);
+    }
+
+    #[test]
+    fn test_availability() {
+        assert!(!NGS_PASSING.is_available(2015));
+        assert!(NGS_PASSING.is_available(2016));
+        assert!(ROSTERS.is_available(1999));
+    }
+}
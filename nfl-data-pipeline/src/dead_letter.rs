@@ -0,0 +1,167 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use csv::StringRecord;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+
+/// A source row as read from the file, keyed by column name
+pub type Row = BTreeMap<String, String>;
+
+/// Where a dead-lettered row failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Failure {
+    /// The row didn't deserialize into the dataset's parser struct
+    Parse,
+    /// The row parsed but its batch was rolled back
+    Write,
+}
+
+/// One line of a dead-letter file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub dataset: String,
+    pub season: i32,
+    pub failure: Failure,
+    pub error: String,
+    pub row: Row,
+    pub failed_at: DateTime<Utc>,
+}
+
+/// Key a record by the file's header
+pub fn row(header: &StringRecord, record: &StringRecord) -> Row {
+    header.iter().zip(record.iter()).map(|(column, value)| (column.to_string(), value.to_string())).collect()
+}
+
+/// Rows that failed to parse or write, appended to `--dead-letter` as JSON lines.
+/// The file is opened on the first failure, so a clean run leaves nothing behind.
+pub struct DeadLetters {
+    path: String,
+    file: Option<File>,
+    written: usize,
+}
+
+impl DeadLetters {
+    pub fn new(path: &str) -> Self {
+        DeadLetters { path: path.to_string(), file: None, written: 0 }
+    }
+
+    /// Append `rows` of a dataset's season, all failed with `error`
+    pub fn write(&mut self, dataset: &str, season: i32, failure: Failure, error: &str, rows: Vec<Row>) -> Result<()> {
+        let failed_at = Utc::now();
+        let letters: Vec<DeadLetter> = rows
+            .into_iter()
+            .map(|row| DeadLetter {
+                dataset: dataset.to_string(),
+                season,
+                failure,
+                error: error.to_string(),
+                row,
+                failed_at,
+            })
+            .collect();
+        self.append(&letters)
+    }
+
+    /// Append entries as they are, e.g. ones a replay can't retry
+    pub fn append(&mut self, letters: &[DeadLetter]) -> Result<()> {
+        let mut lines = String::new();
+        for letter in letters {
+            lines.push_str(&serde_json::to_string(letter)?);
+            lines.push('\n');
+        }
+        self.file()?
+            .write_all(lines.as_bytes())
+            .with_context(|| format!("Failed to write dead letters to {}", self.path))?;
+        self.written += letters.len();
+        Ok(())
+    }
+
+    /// Rows written this run
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Move the file over `target`; an empty file replaces it when nothing was written
+    pub fn replace(mut self, target: &str) -> Result<()> {
+        self.file()?;
+        fs::rename(&self.path, target).with_context(|| format!("Failed to move {} to {}", self.path, target))
+    }
+
+    fn file(&mut self) -> Result<&mut File> {
+        if self.file.is_none() {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .with_context(|| format!("Failed to open dead-letter file {}", self.path))?;
+            self.file = Some(file);
+        }
+        Ok(self.file.as_mut().expect("dead-letter file opened"))
+    }
+}
+
+/// Every entry of a dead-letter file, in file order
+pub fn read(path: &str) -> Result<Vec<DeadLetter>> {
+    let file = File::open(path).with_context(|| format!("Failed to open dead-letter file {}", path))?;
+    let mut letters = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.with_context(|| format!("Failed to read {}", path))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        letters.push(serde_json::from_str(&line).with_context(|| format!("{} line {} is not a dead letter", path, index + 1))?);
+    }
+    Ok(letters)
+}
+
+/// Dead-lettered rows of one dataset and season, read back in place of the
+/// source file. Columns are the union across the rows; a row without one reads
+/// it as empty.
+pub fn records(letters: &[DeadLetter]) -> (StringRecord, Vec<StringRecord>) {
+    let mut columns: Vec<&str> = letters.iter().flat_map(|l| l.row.keys().map(String::as_str)).collect();
+    columns.sort_unstable();
+    columns.dedup();
+
+    let records = letters
+        .iter()
+        .map(|letter| columns.iter().map(|c| letter.row.get(*c).map_or("", String::as_str)).collect())
+        .collect();
+    (columns.into_iter().collect(), records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn letter(row: &[(&str, &str)]) -> DeadLetter {
+        DeadLetter {
+            dataset: "player_stats".to_string(),
+            season: 2024,
+            failure: Failure::Parse,
+            error: "CSV deserialize error: field 3: invalid digit found in string".to_string(),
+            row: row.iter().map(|(c, v)| (c.to_string(), v.to_string())).collect(),
+            failed_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_letters_round_trip_and_read_back_as_records() {
+        let first = letter(&[("player_id", "00-0033873"), ("week", "1"), ("passing_yards", "2x1")]);
+        let second = letter(&[("player_id", "00-0036355"), ("week", "1"), ("targets", "9")]);
+        let line = serde_json::to_string(&first).unwrap();
+        assert!(line.contains("\"failure\":\"parse\""));
+        assert_eq!(serde_json::from_str::<DeadLetter>(&line).unwrap(), first);
+
+        let (header, records) = records(&[first, second]);
+        assert_eq!(header, StringRecord::from(vec!["passing_yards", "player_id", "targets", "week"]));
+        assert_eq!(records[0], StringRecord::from(vec!["2x1", "00-0033873", "", "1"]));
+        assert_eq!(records[1], StringRecord::from(vec!["", "00-0036355", "9", "1"]));
+    }
+}
@@ -0,0 +1,152 @@
+use anyhow::{Context, Result};
+use postgres::Client;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::ops::AddAssign;
+
+use crate::parser::{Game, PlayerStat, RosterPlayer};
+use crate::row_hashes;
+
+/// What writing a set of source rows would do to their table
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Counts {
+    /// Rows whose key isn't stored yet
+    pub inserted: u64,
+    /// Rows stored under their key from different data
+    pub updated: u64,
+    /// Rows stored from identical data (same row hash)
+    pub unchanged: u64,
+    /// Stat lines whose player isn't in `players`, which the write skips
+    pub unresolved: u64,
+}
+
+impl AddAssign for Counts {
+    fn add_assign(&mut self, other: Counts) {
+        self.inserted += other.inserted;
+        self.updated += other.updated;
+        self.unchanged += other.unchanged;
+        self.unresolved += other.unresolved;
+    }
+}
+
+/// What importing a season of one dataset would do
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DatasetDiff {
+    pub dataset: &'static str,
+    pub table: &'static str,
+    /// Source rows read, including those filtered out by `--season-types`
+    pub rows: u64,
+    /// Source rows that failed to parse
+    pub failed: u64,
+    #[serde(flatten)]
+    pub counts: Counts,
+}
+
+/// A source row's key in its table and its row hash
+struct Keyed {
+    key: String,
+    hash: String,
+}
+
+fn tally(rows: &[Keyed], stored_hashes: &HashSet<String>, stored_keys: &HashSet<String>) -> Counts {
+    let mut counts = Counts::default();
+    for row in rows {
+        if stored_hashes.contains(&row.hash) {
+            counts.unchanged += 1;
+        } else if stored_keys.contains(&row.key) {
+            counts.updated += 1;
+        } else {
+            counts.inserted += 1;
+        }
+    }
+    counts
+}
+
+/// Look up the rows' hashes in `table` and their keys with `key_sql`, which
+/// selects the stored keys among `$1`
+fn compare(client: &mut Client, table: &str, key_sql: &str, rows: &[Keyed]) -> Result<Counts> {
+    if rows.is_empty() {
+        return Ok(Counts::default());
+    }
+    let hashes: Vec<&str> = rows.iter().map(|row| row.hash.as_str()).collect();
+    let stored_hashes: HashSet<String> = client
+        .query(&format!("SELECT row_hash FROM {} WHERE row_hash = ANY($1)", table), &[&hashes])
+        .with_context(|| format!("Failed to look up {} row hashes", table))?
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+    let keys: Vec<&str> = rows.iter().map(|row| row.key.as_str()).collect();
+    let stored_keys: HashSet<String> = client
+        .query(key_sql, &[&keys])
+        .with_context(|| format!("Failed to look up {} keys", table))?
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+    Ok(tally(rows, &stored_hashes, &stored_keys))
+}
+
+pub fn players(client: &mut Client, players: &[RosterPlayer], settings: &str) -> Result<Counts> {
+    let rows: Vec<Keyed> = players
+        .iter()
+        .map(|player| Keyed { key: player.gsis_id.clone(), hash: row_hashes::content_hash(player, settings) })
+        .collect();
+    compare(client, "players", "SELECT nfl_id FROM players WHERE nfl_id = ANY($1)", &rows)
+}
+
+pub fn games(client: &mut Client, games: &[Game], settings: &str) -> Result<Counts> {
+    let rows: Vec<Keyed> =
+        games.iter().map(|game| Keyed { key: game.game_id.clone(), hash: row_hashes::content_hash(game, settings) }).collect();
+    compare(client, "games", "SELECT nfl_game_id FROM games WHERE nfl_game_id = ANY($1)", &rows)
+}
+
+/// Stat lines are keyed on player, season, week and season type, the upsert's
+/// conflict target
+pub fn stats(client: &mut Client, stats: &[PlayerStat]) -> Result<Counts> {
+    let player_ids: Vec<&str> = stats.iter().map(|stat| stat.player_id.as_str()).collect();
+    let known: HashSet<String> = client
+        .query("SELECT nfl_id FROM players WHERE nfl_id = ANY($1)", &[&player_ids])
+        .context("Failed to look up stat line players")?
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+    let rows: Vec<Keyed> = stats
+        .iter()
+        .filter(|stat| known.contains(&stat.player_id))
+        .map(|stat| Keyed { key: stat_key(stat), hash: row_hashes::content_hash(stat, "") })
+        .collect();
+    let mut counts = compare(
+        client,
+        "game_stats",
+        "SELECT k.key FROM unnest($1::text[]) AS k(key)
+         JOIN players p ON p.nfl_id = split_part(k.key, '/', 1)
+         JOIN game_stats s ON s.player_id = p.id
+          AND s.season = split_part(k.key, '/', 2)::int
+          AND s.week = split_part(k.key, '/', 3)::int
+          AND s.season_type = split_part(k.key, '/', 4)",
+        &rows,
+    )?;
+    counts.unresolved = (stats.len() - rows.len()) as u64;
+    Ok(counts)
+}
+
+fn stat_key(stat: &PlayerStat) -> String {
+    format!("{}/{}/{}/{}", stat.player_id, stat.season, stat.week, stat.season_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tally_by_hash_then_key() {
+        let keyed = |key: &str, hash: &str| Keyed { key: key.to_string(), hash: hash.to_string() };
+        let rows = vec![keyed("2024_01_BAL_KC", "a1"), keyed("2024_01_GB_PHI", "b2"), keyed("2024_01_PIT_ATL", "c3")];
+        let stored_hashes = HashSet::from(["a1".to_string()]);
+        let stored_keys = HashSet::from(["2024_01_BAL_KC".to_string(), "2024_01_GB_PHI".to_string()]);
+
+        assert_eq!(
+            tally(&rows, &stored_hashes, &stored_keys),
+            Counts { inserted: 1, updated: 1, unchanged: 1, unresolved: 0 }
+        );
+    }
+}
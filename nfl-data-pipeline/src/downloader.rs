@@ -1,15 +1,55 @@
-use anyhow::{Result, anyhow};
-use log::warn;
-use std::time::Duration;
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
+use csv::StringRecord;
+use flate2::read::MultiGzDecoder;
+use log::{debug, info, warn};
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use reqwest::blocking::Client;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+
+use crate::archive::{self, Archive};
+use crate::cache::{CacheEntry, DownloadCache};
+use crate::config::Config;
+use crate::datasets::{Dataset, SourceFormat};
+use crate::metrics;
+use crate::parser::parquet_rows::{self, Records};
+use crate::spill::{self, Payload};
+
+/// Read buffer for streamed responses
+const STREAM_BUFFER_BYTES: usize = 256 * 1024;
+
+/// A season file that doesn't exist upstream (404) or in `--source-dir`
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct SourceMissing(pub String);
 
 pub struct Downloader {
     client: Client,
     max_retries: u32,
+    /// When set, files are fully downloaded before parsing (held in memory up to
+    /// the budget, spilled to disk beyond it) instead of streamed
+    memory_budget: Option<usize>,
+    /// When set, files are kept on disk and revalidated with conditional requests
+    cache: Option<DownloadCache>,
+    /// When set, files are read from this directory by name and never downloaded
+    source_dir: Option<PathBuf>,
+    /// When set, every file downloaded is copied here before parsing
+    archive: Option<Archive>,
 }
 
 impl Downloader {
-    pub fn new(max_retries: u32) -> Self {
+    pub fn new(
+        max_retries: u32,
+        memory_budget: Option<usize>,
+        cache: Option<DownloadCache>,
+        source_dir: Option<PathBuf>,
+        archive: Option<Archive>,
+    ) -> Self {
+        // The blocking client applies this per read, so long streamed files don't time out
         let client = Client::builder()
             .timeout(Duration::from_secs(60))
             .build()
@@ -18,85 +58,227 @@ impl Downloader {
         Downloader {
             client,
             max_retries,
+            memory_budget,
+            cache,
+            source_dir,
+            archive,
         }
     }
 
-    /// Download player stats CSV for a given year
-    pub fn download_player_stats(&self, year: i32) -> Result<String> {
-        let url = format!(
-            "https://github.com/nflverse/nflverse-data/releases/download/player_stats/player_stats_{}.csv",
-            year
-        );
-        self.download_with_retry(&url)
+    /// Downloader for the configured retries, memory budget, cache, source
+    /// directory and archive
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let cache = config.cache_dir.as_ref().map(DownloadCache::new).transpose()?;
+        let source_dir = config.source_dir.as_ref().map(PathBuf::from);
+        if let Some(dir) = source_dir.as_ref().filter(|dir| !dir.is_dir()) {
+            return Err(anyhow!("--source-dir {} is not a directory", dir.display()));
+        }
+        let archive = config.archive.as_deref().map(|destination| Archive::new(destination, Utc::now())).transpose()?;
+        Ok(Downloader::new(config.max_retries, config.memory_budget_bytes(), cache, source_dir, archive))
     }
 
-    /// Download roster CSV for a given year
-    pub fn download_roster(&self, year: i32) -> Result<String> {
-        let url = format!(
-            "https://github.com/nflverse/nflverse-data/releases/download/rosters/roster_{}.csv",
-            year
-        );
-        self.download_with_retry(&url)
+    /// Download a dataset's file for a given year
+    pub fn download<T>(&self, dataset: &Dataset<T>, year: i32) -> Result<Payload> {
+        self.download_as(dataset, year, SourceFormat::Csv).map(|(payload, _)| payload)
     }
 
-    /// Download schedule CSV for a given year
-    pub fn download_schedule(&self, year: i32) -> Result<String> {
-        let url = format!(
-            "https://github.com/nflverse/nflverse-data/releases/download/schedules/sched_{}.csv",
-            year
-        );
-        self.download_with_retry(&url)
+    /// Download a dataset's file in the requested format.
+    ///
+    /// Parquet is always fully downloaded (it can't be read as a stream) and falls
+    /// back to CSV when the season has no Parquet asset. Gzipped CSVs are decompressed
+    /// as they are read. Returns the format received.
+    pub fn download_as<T>(&self, dataset: &Dataset<T>, year: i32, format: SourceFormat) -> Result<(Payload, SourceFormat)> {
+        self.download_payload(dataset, year, format, false)
+            .map(|(payload, format, _)| (payload, format))
     }
 
-    /// Download Next Gen Stats (passing) for a given year
-    pub fn download_ngs_passing(&self, year: i32) -> Result<String> {
-        if year < 2016 {
-            return Err(anyhow!("NGS data only available from 2016 onwards"));
+    /// `download_as`, optionally fingerprinting the file as downloaded. Fingerprinting
+    /// reads the whole file before parsing starts, so it is never streamed (it is
+    /// held in memory up to the budget and spilled beyond it).
+    fn download_payload<T>(
+        &self,
+        dataset: &Dataset<T>,
+        year: i32,
+        format: SourceFormat,
+        fingerprint: bool,
+    ) -> Result<(Payload, SourceFormat, Option<String>)> {
+        if !dataset.is_available(year) {
+            return Err(anyhow!(
+                "{} data only available from {} onwards",
+                dataset.name,
+                dataset.min_year.unwrap_or(year)
+            ));
+        }
+
+        let hash = |payload: Payload| -> Result<(Payload, Option<String>)> {
+            match fingerprint {
+                true => payload.fingerprint().map(|(payload, hash)| (payload, Some(hash))),
+                false => Ok((payload, None)),
+            }
+        };
+
+        // Until the body starts streaming, for files parsed as they download
+        let fetch = |url: &str, buffered: bool| {
+            let started = Instant::now();
+            let fetched = self.fetch(url, buffered);
+            metrics::download(dataset.name, started.elapsed());
+            fetched
+        };
+
+        if format == SourceFormat::Parquet {
+            match fetch(&dataset.url_as(year, format), true)? {
+                Some(payload) => {
+                    let (payload, hash) = hash(payload)?;
+                    return Ok((payload, format, hash));
+                }
+                None => info!("No Parquet asset for {} {}, falling back to CSV", dataset.name, year),
+            }
+        }
+
+        let url = dataset.url(year);
+        match (fetch(&url, fingerprint)?, &self.source_dir) {
+            (Some(payload), _) if url.ends_with(".gz") => {
+                let (payload, hash) = hash(payload)?;
+                let decoder = MultiGzDecoder::new(payload.open()?);
+                let stream = Payload::Stream(Box::new(BufReader::with_capacity(STREAM_BUFFER_BYTES, decoder)));
+                Ok((stream, SourceFormat::Csv, hash))
+            }
+            (Some(payload), _) => {
+                let (payload, hash) = hash(payload)?;
+                Ok((payload, SourceFormat::Csv, hash))
+            }
+            (None, Some(dir)) => Err(SourceMissing(format!("{} not found in {}", dataset.file_name(year), dir.display())).into()),
+            (None, None) => Err(SourceMissing(format!("Data not found (404): {}", url)).into()),
         }
-        let url = format!(
-            "https://github.com/nflverse/nflverse-data/releases/download/nextgen_stats/ngs_{}_passing.csv",
-            year
-        );
-        self.download_with_retry(&url)
     }
 
-    /// Download Next Gen Stats (rushing) for a given year
-    pub fn download_ngs_rushing(&self, year: i32) -> Result<String> {
-        if year < 2016 {
-            return Err(anyhow!("NGS data only available from 2016 onwards"));
+    /// Open the file behind a URL: from `--source-dir` when set (matched on the
+    /// URL's file name, or its gzipped copy from an `--archive` run), otherwise
+    /// downloaded. `None` when it doesn't exist.
+    fn fetch(&self, url: &str, buffered: bool) -> Result<Option<Payload>> {
+        let file_name = url.rsplit('/').next().unwrap_or(url);
+        let Some(dir) = &self.source_dir else {
+            return self.download_with_retry(url, file_name, buffered);
+        };
+
+        let Some(path) = Self::local_path(dir, file_name) else {
+            return Ok(None);
+        };
+        let file = File::open(&path).with_context(|| format!("Failed to open {}", path.display()))?;
+        if path != dir.join(file_name) {
+            let decoder = MultiGzDecoder::new(file);
+            return Ok(Some(Payload::Stream(Box::new(BufReader::with_capacity(STREAM_BUFFER_BYTES, decoder)))));
         }
-        let url = format!(
-            "https://github.com/nflverse/nflverse-data/releases/download/nextgen_stats/ngs_{}_rushing.csv",
-            year
-        );
-        self.download_with_retry(&url)
+        Ok(Some(Payload::Local(file)))
     }
 
-    /// Download Next Gen Stats (receiving) for a given year
-    pub fn download_ngs_receiving(&self, year: i32) -> Result<String> {
-        if year < 2016 {
-            return Err(anyhow!("NGS data only available from 2016 onwards"));
+    /// A file in `--source-dir`: as named, or gzipped the way `--archive` stores it
+    fn local_path(dir: &Path, file_name: &str) -> Option<PathBuf> {
+        [dir.join(file_name), dir.join(archive::archived_name(file_name))].into_iter().find(|path| path.is_file())
+    }
+
+    /// Where the file behind a URL was read from and when it was downloaded: the
+    /// path under `--source-dir` and its modification time, otherwise the URL and
+    /// the cached copy's modification time, or now for a fresh download
+    pub fn origin(&self, url: &str) -> (String, DateTime<Utc>) {
+        let modified = |path: &Path| fs::metadata(path).and_then(|meta| meta.modified()).ok().map(DateTime::<Utc>::from);
+        if let Some(dir) = &self.source_dir {
+            let file_name = url.rsplit('/').next().unwrap_or(url);
+            let path = Self::local_path(dir, file_name).unwrap_or_else(|| dir.join(file_name));
+            let downloaded_at = modified(&path).unwrap_or_else(Utc::now);
+            return (path.display().to_string(), downloaded_at);
         }
-        let url = format!(
-            "https://github.com/nflverse/nflverse-data/releases/download/nextgen_stats/ngs_{}_receiving.csv",
-            year
-        );
-        self.download_with_retry(&url)
+        let cached = self.cache.as_ref().and_then(|cache| cache.path(url)).and_then(|path| modified(&path));
+        (url.to_string(), cached.unwrap_or_else(Utc::now))
+    }
+
+    /// Download a dataset and open its rows as string records, with the header
+    /// normalized by the dataset's dialect. Both formats yield the same records.
+    pub fn records<T>(&self, dataset: &Dataset<T>, year: i32, format: SourceFormat) -> Result<(StringRecord, Records)> {
+        let (payload, format) = self.download_as(dataset, year, format)?;
+        Self::open_records(dataset, payload, format)
     }
 
-    /// Download with automatic retries
-    fn download_with_retry(&self, url: &str) -> Result<String> {
+    /// `records` with the SHA-256 of the file as downloaded
+    pub fn fingerprinted_records<T>(
+        &self,
+        dataset: &Dataset<T>,
+        year: i32,
+        format: SourceFormat,
+    ) -> Result<(String, StringRecord, Records)> {
+        let (payload, format, fingerprint) = self.download_payload(dataset, year, format, true)?;
+        let (header, records) = Self::open_records(dataset, payload, format)?;
+        Ok((fingerprint.unwrap_or_default(), header, records))
+    }
+
+    fn open_records<T>(dataset: &Dataset<T>, payload: Payload, format: SourceFormat) -> Result<(StringRecord, Records)> {
+        let dialect = dataset.dialect;
+        let (raw_header, records): (StringRecord, Records) = match format {
+            SourceFormat::Csv => {
+                let mut reader = dialect.reader(payload.open()?);
+                let raw_header = reader.headers()?.clone();
+                (raw_header, Box::new(reader.into_records().map(|r| r.map_err(anyhow::Error::from))))
+            }
+            SourceFormat::Parquet => parquet_rows::read(payload.into_seekable()?)?,
+        };
+        Ok((dialect.headers(&raw_header), records))
+    }
+
+    /// Download with automatic retries.
+    ///
+    /// Retries cover connecting and the response status; a streamed body that fails
+    /// partway surfaces as a parse error in the stage reading it. A 404 returns
+    /// `None`. `buffered` forces a full download even without a memory budget.
+    ///
+    /// With a cache, the request carries the cached copy's validators; a 304 reuses
+    /// it and a new body is written to the cache before parsing. With an archive,
+    /// a new body is always read in full and archived before parsing.
+    fn download_with_retry(&self, url: &str, file_name: &str, buffered: bool) -> Result<Option<Payload>> {
+        let cached = self.cache.as_ref().and_then(|cache| cache.lookup(url));
+        let archived = |payload: Payload| match &self.archive {
+            Some(archive) => archive.store(url, payload).map(Some),
+            None => Ok(Some(payload)),
+        };
         let mut last_error = None;
 
         for attempt in 1..=self.max_retries {
-            match self.client.get(url).send() {
+            let mut request = self.client.get(url);
+            if let Some(entry) = &cached {
+                if let Some(etag) = &entry.etag {
+                    request = request.header(IF_NONE_MATCH, etag);
+                }
+                if let Some(last_modified) = &entry.last_modified {
+                    request = request.header(IF_MODIFIED_SINCE, last_modified);
+                }
+            }
+
+            match request.send() {
                 Ok(response) => {
+                    if let Some(cache) = &self.cache {
+                        if response.status() == StatusCode::NOT_MODIFIED && cached.is_some() {
+                            debug!("{} not modified, using cached copy", file_name);
+                            return cache.open(url).map(|file| Some(Payload::Local(file)));
+                        }
+                        if response.status().is_success() {
+                            let header = |name| response.headers().get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+                            let entry = CacheEntry {
+                                url: url.to_string(),
+                                etag: header(ETAG),
+                                last_modified: header(LAST_MODIFIED),
+                            };
+                            return archived(Payload::Local(cache.store(&entry, response)?));
+                        }
+                    }
+
                     if response.status().is_success() {
-                        return response
-                            .text()
-                            .map_err(|e| anyhow!("Failed to read response: {}", e));
+                        if self.memory_budget.is_none() && !buffered && self.archive.is_none() {
+                            return Ok(Some(Payload::Stream(Box::new(BufReader::with_capacity(STREAM_BUFFER_BYTES, response)))));
+                        }
+                        let payload = spill::buffer(response, self.memory_budget, file_name)
+                            .map_err(|e| anyhow!("Failed to read response: {}", e))?;
+                        return archived(payload);
                     } else if response.status() == 404 {
-                        return Err(anyhow!("Data not found (404): {}", url));
+                        return Ok(None);
                     } else {
                         warn!(
                             "HTTP {} for {}, attempt {}/{}",
@@ -119,6 +301,7 @@ impl Downloader {
 
             // Exponential backoff
             if attempt < self.max_retries {
+                metrics::retry("download");
                 std::thread::sleep(Duration::from_secs(2u64.pow(attempt)));
             }
         }
@@ -0,0 +1,123 @@
+use csv::StringRecord;
+use serde::de::{self, DeserializeOwned, Deserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+/// Column changes in a dataset file relative to the parser struct and the
+/// previously recorded season.
+///
+/// A renamed column shows up in both lists of the prior-season comparison
+/// (old name removed, new name added); if the parser struct has no alias for
+/// it, the field is also reported missing.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SchemaDrift {
+    /// Parser struct fields with no column of the same name
+    pub missing_fields: Vec<String>,
+    /// Columns present now but not in the previous recorded season
+    pub added_columns: Vec<String>,
+    /// Columns in the previous recorded season that are gone now
+    pub removed_columns: Vec<String>,
+}
+
+impl SchemaDrift {
+    pub fn is_empty(&self) -> bool {
+        self.missing_fields.is_empty() && self.added_columns.is_empty() && self.removed_columns.is_empty()
+    }
+}
+
+/// Compare a file's header against the parser fields and the previous season's columns
+pub fn detect(expected_fields: &[&str], header: &StringRecord, previous: Option<&[String]>) -> SchemaDrift {
+    let columns: Vec<&str> = header.iter().collect();
+
+    let missing_fields = expected_fields
+        .iter()
+        .filter(|f| !columns.contains(f))
+        .map(|f| f.to_string())
+        .collect();
+
+    let (added_columns, removed_columns) = match previous {
+        Some(previous) => (
+            columns
+                .iter()
+                .filter(|c| !previous.iter().any(|p| p == *c))
+                .map(|c| c.to_string())
+                .collect(),
+            previous
+                .iter()
+                .filter(|p| !columns.contains(&p.as_str()))
+                .cloned()
+                .collect(),
+        ),
+        None => (Vec::new(), Vec::new()),
+    };
+
+    SchemaDrift {
+        missing_fields,
+        added_columns,
+        removed_columns,
+    }
+}
+
+/// Field names a parser struct deserializes (primary names, not aliases)
+pub fn struct_fields<T: DeserializeOwned>() -> &'static [&'static str] {
+    let mut fields = None;
+    let _ = T::deserialize(FieldCollector(&mut fields));
+    fields.unwrap_or(&[])
+}
+
+/// Deserializer that records the field list passed to `deserialize_struct` and stops
+struct FieldCollector<'a>(&'a mut Option<&'static [&'static str]>);
+
+impl<'de> Deserializer<'de> for FieldCollector<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("not a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = Some(fields);
+        Err(de::Error::custom("fields collected"))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::NGSPassing;
+
+    #[test]
+    fn test_struct_fields() {
+        let fields = struct_fields::<NGSPassing>();
+        assert!(fields.contains(&"player_display_name"));
+        assert!(fields.contains(&"avg_time_to_throw"));
+    }
+
+    #[test]
+    fn test_detect_against_previous_season() {
+        let header = StringRecord::from(vec!["season", "player_name", "attempts"]);
+        let previous = vec!["season".to_string(), "player_display_name".to_string(), "attempts".to_string()];
+        let drift = detect(&["season", "player_display_name", "attempts"], &header, Some(&previous));
+
+        assert_eq!(drift.missing_fields, vec!["player_display_name"]);
+        assert_eq!(drift.added_columns, vec!["player_name"]);
+        assert_eq!(drift.removed_columns, vec!["player_display_name"]);
+    }
+
+    #[test]
+    fn test_first_season_has_no_column_diff() {
+        let header = StringRecord::from(vec!["season", "attempts"]);
+        let drift = detect(&["season", "attempts"], &header, None);
+        assert!(drift.is_empty());
+    }
+}
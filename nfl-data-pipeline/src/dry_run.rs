@@ -0,0 +1,110 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::diff::Counts;
+
+/// What a dry run would have written to one table
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct TableSummary {
+    /// Rows the import would have upserted
+    pub rows: u64,
+    /// Would-be inserts, updates and unchanged rows, for tables that store row hashes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<Counts>,
+    /// Rows failing validation (they would still be written)
+    pub invalid: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_invalid: Option<String>,
+    /// The upsert of the table's first row, values inlined
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_sql: Option<String>,
+}
+
+/// Per-table summary of a `--dry-run`, filled in as stages run
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct DryRun {
+    tables: BTreeMap<&'static str, TableSummary>,
+}
+
+impl DryRun {
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    pub fn rows(&mut self, table: &'static str, rows: usize) {
+        self.tables.entry(table).or_default().rows += rows as u64;
+    }
+
+    pub fn diff(&mut self, table: &'static str, counts: Counts) {
+        *self.tables.entry(table).or_default().diff.get_or_insert_with(Counts::default) += counts;
+    }
+
+    pub fn invalid(&mut self, table: &'static str, error: String) {
+        let summary = self.tables.entry(table).or_default();
+        summary.invalid += 1;
+        summary.first_invalid.get_or_insert(error);
+    }
+
+    pub fn wants_sample(&self, table: &str) -> bool {
+        self.tables.get(table).is_none_or(|summary| summary.sample_sql.is_none())
+    }
+
+    pub fn sample(&mut self, table: &'static str, sql: String) {
+        self.tables.entry(table).or_default().sample_sql.get_or_insert(sql);
+    }
+
+    /// One line per table, then each table's sample statement
+    pub fn render(&self) -> String {
+        let width = self.tables.keys().map(|table| table.len()).max().unwrap_or(0);
+        let mut out = String::from("Dry run, nothing written. Would write:\n");
+        for (table, summary) in &self.tables {
+            out.push_str(&format!("  {:<width$}  {} rows", table, summary.rows, width = width));
+            if let Some(diff) = summary.diff {
+                out.push_str(&format!(": {} insert, {} update, {} unchanged", diff.inserted, diff.updated, diff.unchanged));
+                if diff.unresolved > 0 {
+                    out.push_str(&format!(", {} skipped (player not in players)", diff.unresolved));
+                }
+            }
+            if let Some(first) = &summary.first_invalid {
+                out.push_str(&format!("; {} invalid, first: {}", summary.invalid, first));
+            }
+            out.push('\n');
+        }
+        for (table, summary) in &self.tables {
+            if let Some(sql) = &summary.sample_sql {
+                out.push_str(&format!("\n-- Sample {} upsert\n{};\n", table, sql));
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_summary() {
+        let mut dry_run = DryRun::default();
+        dry_run.rows("injuries", 5120);
+        dry_run.rows("game_stats", 500);
+        dry_run.diff("game_stats", Counts { inserted: 7, updated: 3, unchanged: 488, unresolved: 2 });
+        dry_run.invalid("game_stats", "Invalid week: 23".to_string());
+        dry_run.invalid("game_stats", "Invalid week: 24".to_string());
+        assert!(dry_run.wants_sample("game_stats"));
+        dry_run.sample("game_stats", "INSERT INTO game_stats (season) VALUES (2024::int4)".to_string());
+        dry_run.sample("game_stats", "INSERT INTO game_stats (season) VALUES (2025::int4)".to_string());
+        assert!(!dry_run.wants_sample("game_stats"));
+
+        assert_eq!(
+            dry_run.render(),
+            "Dry run, nothing written. Would write:\n  \
+               game_stats  500 rows: 7 insert, 3 update, 488 unchanged, 2 skipped (player not in players); 2 invalid, first: Invalid week: 23\n  \
+               injuries    5120 rows\n\
+             \n\
+             -- Sample game_stats upsert\n\
+             INSERT INTO game_stats (season) VALUES (2024::int4);\n"
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use anyhow::{Result, Context};
+use postgres::Client;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::parser::PbpPlay;
+use crate::transformer;
+
+/// Whose plays an efficiency row aggregates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    TeamOffense,
+    Passer,
+    Rusher,
+    Receiver,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::TeamOffense => "team_offense",
+            Role::Passer => "passer",
+            Role::Rusher => "rusher",
+            Role::Receiver => "receiver",
+        }
+    }
+}
+
+/// Garbage time: the possession team's pre-play win probability is below `wp`
+/// or above `1 - wp`. Plays without a win probability are never garbage time.
+#[derive(Debug, Clone, Copy)]
+pub struct GarbageTimeFilter {
+    pub wp: f64,
+}
+
+impl GarbageTimeFilter {
+    pub fn is_garbage_time(&self, play: &PbpPlay) -> bool {
+        play.wp.is_some_and(|wp| wp < self.wp || wp > 1.0 - self.wp)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Totals {
+    plays: i32,
+    epa: f64,
+    successes: i32,
+}
+
+impl Totals {
+    fn add(&mut self, epa: f64, success: bool) {
+        self.plays += 1;
+        self.epa += epa;
+        self.successes += success as i32;
+    }
+}
+
+/// One aggregate, with or without garbage-time plays
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EfficiencyRow {
+    pub role: Role,
+    /// Team abbreviation for team rows, GSIS ID for player rows
+    pub entity: String,
+    pub season_type: String,
+    pub garbage_time_filtered: bool,
+    pub plays: i32,
+    pub total_epa: f64,
+    pub epa_per_play: f64,
+    pub success_rate: f64,
+}
+
+/// Accumulates EPA and success rate per team and player over a season's plays,
+/// both over all plays and with garbage time removed
+pub struct EfficiencyAggregator {
+    filter: GarbageTimeFilter,
+    /// (role, entity, season type) → [all plays, garbage time removed]
+    totals: BTreeMap<(Role, String, String), [Totals; 2]>,
+}
+
+impl EfficiencyAggregator {
+    pub fn new(filter: GarbageTimeFilter) -> Self {
+        EfficiencyAggregator {
+            filter,
+            totals: BTreeMap::new(),
+        }
+    }
+
+    /// Count a play; only pass and run plays with an EPA value are used
+    pub fn add(&mut self, play: &PbpPlay) {
+        let is_pass = play.pass.unwrap_or(0.0) > 0.0;
+        let is_rush = play.rush.unwrap_or(0.0) > 0.0;
+        let Some(epa) = play.epa else { return };
+        if !is_pass && !is_rush {
+            return;
+        }
+
+        let success = play.success.unwrap_or(0.0) > 0.0;
+        let counted = !self.filter.is_garbage_time(play);
+
+        let mut entities = Vec::with_capacity(3);
+        if let Some(team) = &play.posteam {
+            entities.push((Role::TeamOffense, transformer::normalize_team_abbr(team)));
+        }
+        if is_pass {
+            entities.extend(play.passer_player_id.clone().map(|id| (Role::Passer, id)));
+            entities.extend(play.receiver_player_id.clone().map(|id| (Role::Receiver, id)));
+        }
+        if is_rush {
+            entities.extend(play.rusher_player_id.clone().map(|id| (Role::Rusher, id)));
+        }
+
+        for (role, entity) in entities {
+            let totals = self.totals.entry((role, entity, play.season_type.clone())).or_default();
+            totals[0].add(epa, success);
+            if counted {
+                totals[1].add(epa, success);
+            }
+        }
+    }
+
+    pub fn rows(&self) -> Vec<EfficiencyRow> {
+        let mut rows = Vec::with_capacity(self.totals.len() * 2);
+        for ((role, entity, season_type), totals) in &self.totals {
+            for (totals, garbage_time_filtered) in totals.iter().zip([false, true]) {
+                if totals.plays == 0 {
+                    continue;
+                }
+                rows.push(EfficiencyRow {
+                    role: *role,
+                    entity: entity.clone(),
+                    season_type: season_type.clone(),
+                    garbage_time_filtered,
+                    plays: totals.plays,
+                    total_epa: totals.epa,
+                    epa_per_play: totals.epa / totals.plays as f64,
+                    success_rate: totals.successes as f64 / totals.plays as f64,
+                });
+            }
+        }
+        rows
+    }
+}
+
+/// Replace a season's efficiency metrics. Player rows for players not in the
+/// players table are skipped. Returns the number of rows written.
+pub fn write_efficiency_metrics(
+    client: &mut Client,
+    season: i32,
+    filter: GarbageTimeFilter,
+    rows: &[EfficiencyRow],
+) -> Result<u64> {
+    let mut tx = client.transaction()?;
+    tx.execute("DELETE FROM efficiency_metrics WHERE season = $1", &[&season])?;
+
+    let mut written = 0;
+    for row in rows {
+        let wp_threshold = row.garbage_time_filtered.then_some(filter.wp);
+        let (team_abbr, gsis_id) = match row.role {
+            Role::TeamOffense => (Some(row.entity.as_str()), None),
+            _ => (None, Some(row.entity.as_str())),
+        };
+        written += tx
+            .execute(
+                "INSERT INTO efficiency_metrics (season, season_type, role, team_id, player_id, garbage_time_filtered,
+                     wp_threshold, plays, total_epa, epa_per_play, success_rate)
+                 SELECT $1, $2, $3, t.id, p.id, $6, $7::float8, $8, $9::float8, $10::float8, $11::float8
+                 FROM (SELECT $4::text AS team_abbr, $5::text AS gsis_id) k
+                 LEFT JOIN teams t ON t.abbreviation = k.team_abbr
+                 LEFT JOIN players p ON p.nfl_id = k.gsis_id
+                 WHERE t.id IS NOT NULL OR p.id IS NOT NULL",
+                &[
+                    &season,
+                    &row.season_type,
+                    &row.role.as_str(),
+                    &team_abbr,
+                    &gsis_id,
+                    &row.garbage_time_filtered,
+                    &wp_threshold,
+                    &row.plays,
+                    &row.total_epa,
+                    &row.epa_per_play,
+                    &row.success_rate,
+                ],
+            )
+            .with_context(|| format!("Failed to write {} efficiency for {}", row.role.as_str(), row.entity))?;
+    }
+
+    tx.commit()?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(wp: f64, epa: f64, success: bool, pass: bool) -> PbpPlay {
+        PbpPlay {
+            game_id: "2024_01_BAL_KC".to_string(),
+            season: 2024,
+            season_type: "REG".to_string(),
+            week: 1,
+            posteam: Some("KC".to_string()),
+            pass: Some(pass as i32 as f64),
+            rush: Some(!pass as i32 as f64),
+            epa: Some(epa),
+            success: Some(success as i32 as f64),
+            wp: Some(wp),
+            passer_player_id: pass.then(|| "00-0033873".to_string()),
+            rusher_player_id: (!pass).then(|| "00-0036389".to_string()),
+            receiver_player_id: None,
+        }
+    }
+
+    fn find(rows: &[EfficiencyRow], role: Role, filtered: bool) -> &EfficiencyRow {
+        rows.iter()
+            .find(|r| r.role == role && r.garbage_time_filtered == filtered)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_filtered_and_unfiltered_aggregates() {
+        let mut aggregator = EfficiencyAggregator::new(GarbageTimeFilter { wp: 0.10 });
+        aggregator.add(&play(0.50, 0.5, true, true));
+        aggregator.add(&play(0.45, -0.5, false, true));
+        aggregator.add(&play(0.97, 2.0, true, true));
+        aggregator.add(&play(0.60, 0.2, true, false));
+
+        let rows = aggregator.rows();
+        let team_all = find(&rows, Role::TeamOffense, false);
+        assert_eq!(team_all.plays, 4);
+        assert!((team_all.total_epa - 2.2).abs() < 1e-9);
+
+        let passer_all = find(&rows, Role::Passer, false);
+        let passer_filtered = find(&rows, Role::Passer, true);
+        assert_eq!((passer_all.plays, passer_filtered.plays), (3, 2));
+        assert!((passer_filtered.epa_per_play - 0.0).abs() < 1e-9);
+        assert!((passer_filtered.success_rate - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_entity_with_only_garbage_time_has_no_filtered_row() {
+        let mut aggregator = EfficiencyAggregator::new(GarbageTimeFilter { wp: 0.10 });
+        aggregator.add(&play(0.03, 1.0, true, false));
+
+        let rows = aggregator.rows();
+        assert!(rows.iter().all(|r| !r.garbage_time_filtered));
+    }
+
+    #[test]
+    fn test_non_plays_are_ignored() {
+        let mut aggregator = EfficiencyAggregator::new(GarbageTimeFilter { wp: 0.10 });
+        let mut punt = play(0.5, 0.3, false, true);
+        punt.pass = Some(0.0);
+        punt.rush = Some(0.0);
+        aggregator.add(&punt);
+        assert!(aggregator.rows().is_empty());
+    }
+}
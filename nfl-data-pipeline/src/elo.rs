@@ -0,0 +1,148 @@
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use crate::ratings::GameResult;
+
+/// Rating of an average team
+pub const MEAN_RATING: f64 = 1505.0;
+/// Share of last season's rating kept at the start of the next; the rest
+/// regresses to `MEAN_RATING`
+pub const CARRYOVER: f64 = 2.0 / 3.0;
+pub const K_FACTOR: f64 = 20.0;
+/// Elo points added to the home team outside neutral sites
+pub const HOME_FIELD: f64 = 48.0;
+/// Elo points for a team coming off a bye
+pub const BYE_BONUS: f64 = 25.0;
+/// Days of rest from which a team counts as coming off a bye (7 is a normal week)
+pub const BYE_REST_DAYS: i32 = 13;
+/// Rating gaps matter more in the playoffs
+pub const PLAYOFF_MULTIPLIER: f64 = 1.2;
+
+/// A completed game with the schedule context Elo adjusts for
+#[derive(Debug, Clone, Copy)]
+pub struct EloGame {
+    pub result: GameResult,
+    pub playoff: bool,
+    pub neutral_site: bool,
+    pub home_rest: Option<i32>,
+    pub away_rest: Option<i32>,
+}
+
+impl EloGame {
+    /// Home team's edge in Elo points from home field and rest
+    fn home_edge(&self) -> f64 {
+        let off_bye = |rest: Option<i32>| if rest.is_some_and(|days| days >= BYE_REST_DAYS) { BYE_BONUS } else { 0.0 };
+        let home_field = if self.neutral_site { 0.0 } else { HOME_FIELD };
+        home_field + off_bye(self.home_rest) - off_bye(self.away_rest)
+    }
+}
+
+/// FiveThirtyEight-style NFL Elo ratings, updated game by game
+#[derive(Debug, Clone, Default)]
+pub struct Elo {
+    ratings: BTreeMap<Uuid, f64>,
+}
+
+impl Elo {
+    /// Start a season from the previous season's final ratings, each regressed
+    /// a third of the way to the mean. Teams without one start at the mean.
+    pub fn new(previous: &BTreeMap<Uuid, f64>) -> Self {
+        Elo {
+            ratings: previous
+                .iter()
+                .map(|(team, rating)| (*team, CARRYOVER * rating + (1.0 - CARRYOVER) * MEAN_RATING))
+                .collect(),
+        }
+    }
+
+    pub fn rating(&self, team: Uuid) -> f64 {
+        self.ratings.get(&team).copied().unwrap_or(MEAN_RATING)
+    }
+
+    /// Home rating minus away rating, with home field, rest and the playoff multiplier
+    fn rating_gap(&self, game: &EloGame) -> f64 {
+        let gap = self.rating(game.result.home_team) - self.rating(game.result.away_team) + game.home_edge();
+        if game.playoff { gap * PLAYOFF_MULTIPLIER } else { gap }
+    }
+
+    /// Pre-game probability that the home team wins
+    pub fn home_win_probability(&self, game: &EloGame) -> f64 {
+        1.0 / (10f64.powf(-self.rating_gap(game) / 400.0) + 1.0)
+    }
+
+    /// Apply a result. The shift grows with the margin of victory, damped when
+    /// the favorite wins so ratings don't run away. Returns the home team's change.
+    pub fn update(&mut self, game: &EloGame) -> f64 {
+        let result = &game.result;
+        let gap = self.rating_gap(game);
+        let expected = self.home_win_probability(game);
+        let (actual, winner_gap) = match result.home_score.cmp(&result.away_score) {
+            std::cmp::Ordering::Greater => (1.0, gap),
+            std::cmp::Ordering::Less => (0.0, -gap),
+            std::cmp::Ordering::Equal => (0.5, 0.0),
+        };
+
+        let margin = (result.home_score - result.away_score).abs().max(1) as f64;
+        let multiplier = (margin + 1.0).ln() * 2.2 / (winner_gap * 0.001 + 2.2);
+        let shift = K_FACTOR * multiplier * (actual - expected);
+
+        *self.ratings.entry(result.home_team).or_insert(MEAN_RATING) += shift;
+        *self.ratings.entry(result.away_team).or_insert(MEAN_RATING) -= shift;
+        shift
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(home: Uuid, away: Uuid, home_score: i32, away_score: i32) -> EloGame {
+        EloGame {
+            result: GameResult { home_team: home, away_team: away, home_score, away_score },
+            playoff: false,
+            neutral_site: false,
+            home_rest: Some(7),
+            away_rest: Some(7),
+        }
+    }
+
+    #[test]
+    fn test_home_field_and_rest_adjustments() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let elo = Elo::default();
+
+        let home = game(a, b, 0, 0);
+        assert!((elo.home_win_probability(&home) - 0.5685).abs() < 1e-3);
+
+        let neutral = EloGame { neutral_site: true, ..home };
+        assert_eq!(elo.home_win_probability(&neutral), 0.5);
+
+        let away_off_bye = EloGame { away_rest: Some(14), ..home };
+        assert!((away_off_bye.home_edge() - (HOME_FIELD - BYE_BONUS)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_update_is_zero_sum_and_scales_with_margin() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let mut close = Elo::default();
+        let close_shift = close.update(&game(a, b, 21, 20));
+        let mut blowout = Elo::default();
+        let blowout_shift = blowout.update(&game(a, b, 45, 10));
+
+        assert!(close_shift > 0.0 && blowout_shift > close_shift);
+        assert!((close.rating(a) + close.rating(b) - 2.0 * MEAN_RATING).abs() < 1e-9);
+
+        // An upset moves ratings more than the favorite winning by the same margin
+        let mut upset = Elo::default();
+        assert!(-upset.update(&game(a, b, 10, 45)) > blowout_shift);
+    }
+
+    #[test]
+    fn test_new_season_regresses_to_mean() {
+        let team = Uuid::new_v4();
+        let elo = Elo::new(&BTreeMap::from([(team, 1700.0)]));
+        assert!((elo.rating(team) - 1635.0).abs() < 1e-9);
+        assert_eq!(elo.rating(Uuid::new_v4()), MEAN_RATING);
+    }
+}
@@ -0,0 +1,770 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use postgres::Client;
+use serde::Deserialize;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::config::SourcePriority;
+use crate::precedence::{self, Field, Merge, Precedence, Source};
+use crate::game_status::{self, GameStatus};
+use crate::transformer;
+
+/// ESPN's public site API for the NFL
+pub const API_URL: &str = "https://site.api.espn.com/apis/site/v2/sports/football/nfl";
+
+/// Dataset names recorded in `source_runs` and progress logs
+pub const SCHEDULE_DATASET: &str = "espn_schedule";
+pub const BOX_SCORES_DATASET: &str = "espn_box_scores";
+pub const ROSTERS_DATASET: &str = "espn_rosters";
+
+/// Every game of a season, preseason through the Super Bowl, in one request
+pub fn schedule_url(base: &str, season: i32) -> String {
+    format!("{}/scoreboard?dates={}0801-{}0301&limit=1000", base, season, season + 1)
+}
+
+pub fn summary_url(base: &str, espn_game_id: &str) -> String {
+    format!("{}/summary?event={}", base, espn_game_id)
+}
+
+pub fn roster_url(base: &str, team: &str) -> String {
+    format!("{}/teams/{}/roster", base, espn_abbr(team).to_lowercase())
+}
+
+/// ESPN abbreviations that differ from the teams table
+pub fn team_abbr(espn: &str) -> String {
+    match espn {
+        "WSH" => "WAS".to_string(),
+        "LAR" => "LA".to_string(),
+        other => transformer::normalize_team_abbr(other),
+    }
+}
+
+fn espn_abbr(abbr: &str) -> &str {
+    match abbr {
+        "WAS" => "WSH",
+        "LA" => "LAR",
+        other => other,
+    }
+}
+
+/// Event times, with or without seconds ("2024-09-08T17:00Z")
+pub fn parse_kickoff(date: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_str(date, "%Y-%m-%dT%H:%M%#z")
+        .or_else(|_| DateTime::parse_from_rfc3339(date))
+        .ok()
+        .map(|kickoff| kickoff.with_timezone(&Utc))
+}
+
+pub fn fetch(http: &reqwest::blocking::Client, url: &str) -> Result<String> {
+    http.get(url)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.text())
+        .with_context(|| format!("Failed to fetch {}", url))
+}
+
+// Schedule
+
+#[derive(Debug, Deserialize)]
+struct Scoreboard {
+    #[serde(default)]
+    events: Vec<Event>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Event {
+    id: String,
+    date: String,
+    season: EventSeason,
+    #[serde(default)]
+    week: Option<EventWeek>,
+    #[serde(default)]
+    competitions: Vec<Competition>,
+    status: Status,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventSeason {
+    year: i32,
+    /// 1 preseason, 2 regular season, 3 postseason
+    #[serde(rename = "type")]
+    kind: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventWeek {
+    number: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct Competition {
+    #[serde(default)]
+    competitors: Vec<Competitor>,
+    #[serde(default)]
+    venue: Option<Venue>,
+    #[serde(default)]
+    attendance: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Competitor {
+    home_away: String,
+    team: Team,
+    #[serde(default)]
+    score: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Team {
+    abbreviation: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Venue {
+    #[serde(default)]
+    full_name: Option<String>,
+    #[serde(default)]
+    address: Option<Address>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Address {
+    #[serde(default)]
+    city: Option<String>,
+    #[serde(default)]
+    state: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Status {
+    #[serde(rename = "type")]
+    kind: StatusType,
+}
+
+#[derive(Debug, Deserialize)]
+struct StatusType {
+    state: String,
+    #[serde(default)]
+    completed: bool,
+}
+
+/// A game on ESPN's season schedule
+#[derive(Debug, Clone, PartialEq)]
+pub struct EspnGame {
+    pub espn_id: String,
+    pub season: i32,
+    /// REG or POST
+    pub season_type: &'static str,
+    /// Week in nflverse numbering: postseason weeks follow the regular season
+    pub week: i32,
+    pub kickoff: DateTime<Utc>,
+    pub home_team: String,
+    pub away_team: String,
+    pub home_score: Option<i32>,
+    pub away_score: Option<i32>,
+    pub status: GameStatus,
+    pub venue_name: Option<String>,
+    pub venue_city: Option<String>,
+    pub venue_state: Option<String>,
+    pub attendance: Option<i32>,
+}
+
+/// Season type and nflverse week for an ESPN event. Preseason games and the Pro
+/// Bowl (postseason week 4) aren't in nflverse schedules and are left out.
+fn nflverse_week(season: i32, kind: i32, week: i32) -> Option<(&'static str, i32)> {
+    match (kind, week) {
+        (2, week) => Some(("REG", week)),
+        (3, 1..=3) => Some(("POST", transformer::regular_season_weeks(season) + week)),
+        (3, 5) => Some(("POST", transformer::regular_season_weeks(season) + 4)),
+        _ => None,
+    }
+}
+
+pub fn parse_schedule(body: &str) -> Result<Vec<EspnGame>> {
+    let scoreboard: Scoreboard = serde_json::from_str(body).context("Invalid ESPN scoreboard response")?;
+
+    Ok(scoreboard
+        .events
+        .into_iter()
+        .filter_map(|event| {
+            let (season_type, week) = nflverse_week(event.season.year, event.season.kind, event.week?.number)?;
+            let competition = event.competitions.into_iter().next()?;
+            let side = |home_away: &str| competition.competitors.iter().find(|c| c.home_away == home_away);
+            let (home, away) = (side("home")?, side("away")?);
+            let status = if event.status.kind.completed {
+                GameStatus::Final
+            } else if event.status.kind.state == "in" {
+                GameStatus::InProgress
+            } else {
+                GameStatus::Scheduled
+            };
+            let score = |competitor: &Competitor| match status {
+                GameStatus::Scheduled => None,
+                _ => competitor.score.as_deref().and_then(|s| s.parse().ok()),
+            };
+            let venue = competition.venue.as_ref();
+            let address = venue.and_then(|v| v.address.as_ref());
+
+            Some(EspnGame {
+                espn_id: event.id,
+                season: event.season.year,
+                season_type,
+                week,
+                kickoff: parse_kickoff(&event.date)?,
+                home_team: team_abbr(&home.team.abbreviation),
+                away_team: team_abbr(&away.team.abbreviation),
+                home_score: score(home),
+                away_score: score(away),
+                status,
+                venue_name: venue.and_then(|v| v.full_name.clone()),
+                venue_city: address.and_then(|a| a.city.clone()),
+                venue_state: address.and_then(|a| a.state.clone()),
+                attendance: competition.attendance.filter(|a| *a > 0 && status == GameStatus::Final),
+            })
+        })
+        .collect())
+}
+
+pub fn fetch_schedule(http: &reqwest::blocking::Client, base: &str, season: i32) -> Result<Vec<EspnGame>> {
+    parse_schedule(&fetch(http, &schedule_url(base, season))?)
+}
+
+/// Rows one ESPN write touched
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct EspnWrite {
+    /// Existing rows ESPN data was merged into
+    pub merged: usize,
+    /// New rows
+    pub inserted: usize,
+}
+
+/// Merge ESPN's schedule into `games`. A game matches on its ESPN ID (also the
+/// `nfl_game_id` of games the Go importer wrote) or on season, teams and a kickoff
+/// within a day and a half; matched rows take ESPN's values field by field under
+/// `precedence`. Unmatched games are inserted with the ESPN ID as `nfl_game_id`.
+/// Status only moves forward, as for every other writer.
+pub fn write_schedule(client: &mut Client, games: &[EspnGame], precedence: &Precedence) -> Result<EspnWrite> {
+    const VALUES: [(Field, &str, &str); 9] = [
+        (Field::Schedule, "season_type", "$3::text"),
+        (Field::Schedule, "week", "$14::int"),
+        (Field::Kickoff, "game_date", "$4::timestamptz"),
+        (Field::Scores, "home_score", "$7::int"),
+        (Field::Scores, "away_score", "$8::int"),
+        (Field::Venue, "venue_name", "$10::text"),
+        (Field::Venue, "venue_city", "$11::text"),
+        (Field::Venue, "venue_state", "$12::text"),
+        (Field::Venue, "attendance", "$13::int"),
+    ];
+    let status = game_status::merge_sql("g.status", "$9::text");
+    let merge = VALUES
+        .iter()
+        .fold(Merge::new(precedence, Source::Espn, "g").status(status.as_str()), |merge, (field, column, value)| {
+            merge.fill(*field, column, *value)
+        });
+    let update = format!(
+        "UPDATE games g SET
+             espn_game_id = $1,
+             {},
+             status = {},
+             updated_at = NOW()
+         WHERE g.id = (
+             SELECT g2.id FROM games g2
+             JOIN teams ht ON ht.id = g2.home_team_id
+             JOIN teams at ON at.id = g2.away_team_id
+             WHERE g2.espn_game_id = $1 OR g2.nfl_game_id = $1
+                OR (g2.season = $2 AND ht.abbreviation = $5 AND at.abbreviation = $6
+                    AND g2.game_date BETWEEN $4::timestamptz - INTERVAL '36 hours' AND $4::timestamptz + INTERVAL '36 hours')
+             ORDER BY (g2.espn_game_id = $1 OR g2.nfl_game_id = $1) DESC
+             LIMIT 1
+         )
+         RETURNING g.id",
+        merge.set_sql(),
+        status,
+    );
+    let provenance: Vec<(&str, &str)> = VALUES.iter().map(|(_, column, value)| (*column, *value)).collect();
+    let insert = format!(
+        "INSERT INTO games (id, nfl_game_id, espn_game_id, season, season_type, week, game_date, home_team_id, away_team_id,
+             home_score, away_score, status, venue_name, venue_city, venue_state, attendance, provenance, created_at, updated_at)
+         SELECT uuid_generate_v4(), $1, $1, $2, $3, $14, $4::timestamptz, ht.id, at.id,
+                $7::int, $8::int, $9::text, $10::text, $11::text, $12::text, $13::int, {}, NOW(), NOW()
+         FROM teams ht, teams at
+         WHERE ht.abbreviation = $5 AND at.abbreviation = $6",
+        precedence::insert_sql(Source::Espn, &provenance)
+    );
+
+    let mut write = EspnWrite::default();
+    let mut tx = client.transaction()?;
+    for game in games {
+        let status = game.status.as_str();
+        let params: [&(dyn postgres::types::ToSql + Sync); 14] = [
+            &game.espn_id,
+            &game.season,
+            &game.season_type,
+            &game.kickoff,
+            &game.home_team,
+            &game.away_team,
+            &game.home_score,
+            &game.away_score,
+            &status,
+            &game.venue_name,
+            &game.venue_city,
+            &game.venue_state,
+            &game.attendance,
+            &game.week,
+        ];
+        if !tx.query(update.as_str(), &params)?.is_empty() {
+            write.merged += 1;
+        } else {
+            write.inserted += tx
+                .execute(insert.as_str(), &params)
+                .with_context(|| format!("Failed to insert ESPN game {}", game.espn_id))? as usize;
+        }
+    }
+    tx.commit()?;
+    Ok(write)
+}
+
+// Box scores
+
+#[derive(Debug, Deserialize)]
+struct Summary {
+    #[serde(default)]
+    boxscore: Option<BoxScore>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BoxScore {
+    #[serde(default)]
+    players: Vec<TeamPlayers>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TeamPlayers {
+    team: Team,
+    #[serde(default)]
+    statistics: Vec<StatCategory>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StatCategory {
+    name: String,
+    #[serde(default)]
+    keys: Vec<String>,
+    #[serde(default)]
+    athletes: Vec<AthleteStats>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AthleteStats {
+    athlete: Athlete,
+    #[serde(default)]
+    stats: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Athlete {
+    id: String,
+    #[serde(default)]
+    display_name: Option<String>,
+}
+
+/// One player's line in an ESPN box score
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BoxScoreLine {
+    pub espn_player_id: String,
+    pub name: Option<String>,
+    pub team: String,
+    pub completions: i32,
+    pub attempts: i32,
+    pub passing_yards: i32,
+    pub passing_tds: i32,
+    pub interceptions: i32,
+    pub carries: i32,
+    pub rushing_yards: i32,
+    pub rushing_tds: i32,
+    pub receptions: i32,
+    pub targets: i32,
+    pub receiving_yards: i32,
+    pub receiving_tds: i32,
+    pub fumbles: i32,
+    pub fumbles_lost: i32,
+    pub tackles: i32,
+    pub solo_tackles: i32,
+    pub tackles_for_loss: i32,
+    pub qb_hits: i32,
+    pub passes_defended: i32,
+    pub def_sacks: f64,
+    pub def_interceptions: i32,
+    pub def_tds: i32,
+    pub field_goals_made: i32,
+    pub field_goals_attempted: i32,
+    pub fg_long: Option<i32>,
+    pub extra_points_made: i32,
+    pub extra_points_attempted: i32,
+}
+
+impl BoxScoreLine {
+    /// Set the stat behind one box score key. Keys ESPN reports that have no
+    /// `game_stats` column (averages, longs, ratings) are ignored.
+    fn set(&mut self, category: &str, key: &str, value: f64) {
+        let count = value as i32;
+        match (category, key) {
+            ("passing", "completions") => self.completions = count,
+            ("passing", "passingAttempts") => self.attempts = count,
+            ("passing", "passingYards") => self.passing_yards = count,
+            ("passing", "passingTouchdowns") => self.passing_tds = count,
+            ("passing", "interceptions") => self.interceptions = count,
+            ("rushing", "rushingAttempts") => self.carries = count,
+            ("rushing", "rushingYards") => self.rushing_yards = count,
+            ("rushing", "rushingTouchdowns") => self.rushing_tds = count,
+            ("receiving", "receptions") => self.receptions = count,
+            ("receiving", "receivingTargets") => self.targets = count,
+            ("receiving", "receivingYards") => self.receiving_yards = count,
+            ("receiving", "receivingTouchdowns") => self.receiving_tds = count,
+            ("fumbles", "fumbles") => self.fumbles = count,
+            ("fumbles", "fumblesLost") => self.fumbles_lost = count,
+            ("defensive", "totalTackles") => self.tackles = count,
+            ("defensive", "soloTackles") => self.solo_tackles = count,
+            ("defensive", "tacklesForLoss") => self.tackles_for_loss = count,
+            ("defensive", "QBHits") => self.qb_hits = count,
+            ("defensive", "passesDefended") => self.passes_defended = count,
+            ("defensive", "sacks") => self.def_sacks = value,
+            ("defensive", "defensiveTouchdowns") => self.def_tds = count,
+            ("interceptions", "interceptions") => self.def_interceptions = count,
+            ("kicking", "fieldGoalsMade") => self.field_goals_made = count,
+            ("kicking", "fieldGoalAttempts") => self.field_goals_attempted = count,
+            ("kicking", "longFieldGoalMade") => self.fg_long = Some(count),
+            ("kicking", "extraPointsMade") => self.extra_points_made = count,
+            ("kicking", "extraPointAttempts") => self.extra_points_attempted = count,
+            _ => {}
+        }
+    }
+}
+
+/// Player lines from a game summary, one per athlete across stat categories.
+/// Compound keys ("completions/passingAttempts") carry compound values ("22/31").
+pub fn parse_box_score(body: &str) -> Result<Vec<BoxScoreLine>> {
+    let summary: Summary = serde_json::from_str(body).context("Invalid ESPN summary response")?;
+    let mut lines: Vec<BoxScoreLine> = Vec::new();
+
+    for team in summary.boxscore.map(|b| b.players).unwrap_or_default() {
+        let abbr = team_abbr(&team.team.abbreviation);
+        for category in &team.statistics {
+            for athlete in &category.athletes {
+                let position = match lines.iter().position(|l| l.espn_player_id == athlete.athlete.id) {
+                    Some(position) => position,
+                    None => {
+                        lines.push(BoxScoreLine {
+                            espn_player_id: athlete.athlete.id.clone(),
+                            name: athlete.athlete.display_name.clone(),
+                            team: abbr.clone(),
+                            ..Default::default()
+                        });
+                        lines.len() - 1
+                    }
+                };
+                for (key, value) in category.keys.iter().zip(&athlete.stats) {
+                    for (key, value) in key.split('/').zip(value.split('/')) {
+                        if let Ok(value) = value.trim().parse::<f64>() {
+                            lines[position].set(&category.name, key, value);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(lines)
+}
+
+pub fn fetch_box_score(http: &reqwest::blocking::Client, base: &str, espn_game_id: &str) -> Result<Vec<BoxScoreLine>> {
+    parse_box_score(&fetch(http, &summary_url(base, espn_game_id))?)
+}
+
+/// ESPN athlete ID → player ID, from `player_external_ids` and `players.espn_id`
+pub fn player_ids(client: &mut Client) -> Result<HashMap<String, Uuid>> {
+    let rows = client
+        .query(
+            "SELECT espn_id, id FROM players WHERE espn_id IS NOT NULL AND espn_id <> ''
+             UNION ALL
+             SELECT external_id, player_id FROM player_external_ids WHERE source = 'espn'",
+            &[],
+        )
+        .context("Failed to load ESPN player IDs")?;
+    Ok(rows.iter().map(|row| (row.get(0), row.get(1))).collect())
+}
+
+/// Write a game's box score into `game_stats`. With nflverse priority, existing
+/// rows only gain the game and team link; with ESPN priority ESPN's numbers
+/// replace them. Returns the lines written; lines whose athlete isn't linked to
+/// a player are left for the caller to report.
+pub fn write_box_score<'a>(
+    client: &mut Client,
+    game: &EspnGame,
+    lines: &'a [BoxScoreLine],
+    player_ids: &HashMap<String, Uuid>,
+    priority: SourcePriority,
+) -> Result<(usize, Vec<&'a BoxScoreLine>)> {
+    const COLUMNS: [&str; 26] = [
+        "completions", "attempts", "passing_yards", "passing_tds", "interceptions", "carries", "rushing_yards",
+        "rushing_tds", "receptions", "targets", "receiving_yards", "receiving_tds", "fumbles", "fumbles_lost",
+        "tackles", "solo_tackles", "tackles_for_loss", "qb_hits", "passes_defended", "def_interceptions", "def_tds",
+        "field_goals_made", "field_goals_attempted", "extra_points_made", "extra_points_attempted", "fg_long",
+    ];
+    let on_conflict = match priority {
+        SourcePriority::Espn => COLUMNS
+            .iter()
+            .chain(&["def_sacks"])
+            .map(|column| format!("{column} = EXCLUDED.{column}"))
+            .chain(["game_id = EXCLUDED.game_id".to_string(), "team_id = COALESCE(EXCLUDED.team_id, game_stats.team_id)".to_string()])
+            .collect::<Vec<_>>(),
+        SourcePriority::Nflverse => vec![
+            "game_id = COALESCE(game_stats.game_id, EXCLUDED.game_id)".to_string(),
+            "team_id = COALESCE(game_stats.team_id, EXCLUDED.team_id)".to_string(),
+        ],
+    };
+    let placeholders: Vec<String> = (0..COLUMNS.len()).map(|i| format!("${}", i + 6)).collect();
+    let sql = format!(
+        "INSERT INTO game_stats (id, player_id, game_id, season, week, season_type, team_id, {}, def_sacks, created_at, updated_at)
+         VALUES (uuid_generate_v4(), $1, (SELECT id FROM games WHERE espn_game_id = $2 OR nfl_game_id = $2 LIMIT 1), $3, $4, $5,
+                 (SELECT id FROM teams WHERE abbreviation = ${}), {}, ${}::float8, NOW(), NOW())
+         ON CONFLICT (player_id, season, week, season_type) DO UPDATE SET {}, updated_at = NOW()",
+        COLUMNS.join(", "),
+        COLUMNS.len() + 6,
+        placeholders.join(", "),
+        COLUMNS.len() + 7,
+        on_conflict.join(", "),
+    );
+
+    let mut written = 0;
+    let mut unlinked = Vec::new();
+    let mut tx = client.transaction()?;
+    for line in lines {
+        let Some(player_id) = player_ids.get(&line.espn_player_id) else {
+            unlinked.push(line);
+            continue;
+        };
+        let values = [
+            line.completions, line.attempts, line.passing_yards, line.passing_tds, line.interceptions, line.carries,
+            line.rushing_yards, line.rushing_tds, line.receptions, line.targets, line.receiving_yards,
+            line.receiving_tds, line.fumbles, line.fumbles_lost, line.tackles, line.solo_tackles,
+            line.tackles_for_loss, line.qb_hits, line.passes_defended, line.def_interceptions, line.def_tds,
+            line.field_goals_made, line.field_goals_attempted, line.extra_points_made, line.extra_points_attempted,
+        ];
+        let mut params: Vec<&(dyn postgres::types::ToSql + Sync)> =
+            vec![player_id, &game.espn_id, &game.season, &game.week, &game.season_type];
+        params.extend(values.iter().map(|value| value as &(dyn postgres::types::ToSql + Sync)));
+        params.push(&line.fg_long);
+        params.push(&line.team);
+        params.push(&line.def_sacks);
+        tx.execute(sql.as_str(), &params)
+            .with_context(|| format!("Failed to write ESPN box score line for athlete {}", line.espn_player_id))?;
+        written += 1;
+    }
+    tx.commit()?;
+    Ok((written, unlinked))
+}
+
+// Rosters
+
+#[derive(Debug, Deserialize)]
+struct Roster {
+    #[serde(default)]
+    athletes: Vec<RosterGroup>,
+}
+
+/// ESPN groups roster athletes by offense, defense and special teams
+#[derive(Debug, Deserialize)]
+struct RosterGroup {
+    #[serde(default)]
+    items: Vec<RosterAthlete>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RosterAthlete {
+    id: String,
+    #[serde(default)]
+    full_name: Option<String>,
+    #[serde(default)]
+    jersey: Option<String>,
+    #[serde(default)]
+    position: Option<Position>,
+    /// Inches
+    #[serde(default)]
+    height: Option<f64>,
+    /// Pounds
+    #[serde(default)]
+    weight: Option<f64>,
+    #[serde(default)]
+    college: Option<College>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Position {
+    abbreviation: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct College {
+    name: String,
+}
+
+/// A player on an ESPN team roster
+#[derive(Debug, Clone, PartialEq)]
+pub struct RosterEntry {
+    pub espn_player_id: String,
+    pub name: Option<String>,
+    pub team: String,
+    pub position: Option<String>,
+    pub jersey_number: Option<i32>,
+    pub height_inches: Option<i32>,
+    pub weight_pounds: Option<i32>,
+    pub college: Option<String>,
+}
+
+pub fn parse_roster(body: &str, team: &str) -> Result<Vec<RosterEntry>> {
+    let roster: Roster = serde_json::from_str(body).context("Invalid ESPN roster response")?;
+    Ok(roster
+        .athletes
+        .into_iter()
+        .flat_map(|group| group.items)
+        .map(|athlete| RosterEntry {
+            espn_player_id: athlete.id,
+            name: athlete.full_name,
+            team: team.to_string(),
+            position: athlete.position.map(|p| p.abbreviation),
+            jersey_number: athlete.jersey.and_then(|j| j.parse().ok()),
+            height_inches: athlete.height.map(|h| h.round() as i32).filter(|h| *h > 0),
+            weight_pounds: athlete.weight.map(|w| w.round() as i32).filter(|w| *w > 0),
+            college: athlete.college.map(|c| c.name),
+        })
+        .collect())
+}
+
+pub fn fetch_roster(http: &reqwest::blocking::Client, base: &str, team: &str) -> Result<Vec<RosterEntry>> {
+    parse_roster(&fetch(http, &roster_url(base, team))?, team)
+}
+
+/// Merge ESPN roster entries into the players they're linked to, field by field
+/// under `precedence`. ESPN never creates players: athletes without a linked
+/// player are returned for the caller to report.
+pub fn write_roster<'a>(
+    client: &mut Client,
+    entries: &'a [RosterEntry],
+    player_ids: &HashMap<String, Uuid>,
+    precedence: &Precedence,
+) -> Result<(usize, Vec<&'a RosterEntry>)> {
+    let merge = Merge::new(precedence, Source::Espn, "p")
+        .fill(Field::Roster, "team_id", "(SELECT id FROM teams WHERE abbreviation = $2)")
+        .fill(Field::Roster, "position", "$3::text")
+        .fill(Field::Roster, "jersey_number", "$4::int")
+        .fill(Field::Roster, "height_inches", "$5::int")
+        .fill(Field::Roster, "weight_pounds", "$6::int")
+        .fill(Field::Roster, "college", "$7::text");
+    let sql = format!("UPDATE players p SET {}, updated_at = NOW() WHERE p.id = $1", merge.set_sql());
+
+    let mut merged = 0;
+    let mut unlinked = Vec::new();
+    let mut tx = client.transaction()?;
+    for entry in entries {
+        let Some(player_id) = player_ids.get(&entry.espn_player_id) else {
+            unlinked.push(entry);
+            continue;
+        };
+        merged += tx.execute(
+            sql.as_str(),
+            &[
+                player_id,
+                &entry.team,
+                &entry.position,
+                &entry.jersey_number,
+                &entry.height_inches,
+                &entry.weight_pounds,
+                &entry.college,
+            ],
+        )? as usize;
+    }
+    tx.commit()?;
+    Ok((merged, unlinked))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEDULE: &str = r#"{"events": [
+        {"id": "401671789", "date": "2024-09-06T00:20Z", "season": {"year": 2024, "type": 2}, "week": {"number": 1},
+         "competitions": [{"attendance": 73426, "venue": {"fullName": "GEHA Field at Arrowhead Stadium",
+                           "address": {"city": "Kansas City", "state": "MO"}},
+                           "competitors": [{"homeAway": "home", "team": {"abbreviation": "KC"}, "score": "27"},
+                                           {"homeAway": "away", "team": {"abbreviation": "BAL"}, "score": "20"}]}],
+         "status": {"type": {"state": "post", "completed": true}}},
+        {"id": "401671800", "date": "2025-02-10T23:30Z", "season": {"year": 2024, "type": 3}, "week": {"number": 5},
+         "competitions": [{"competitors": [{"homeAway": "home", "team": {"abbreviation": "WSH"}, "score": "0"},
+                                           {"homeAway": "away", "team": {"abbreviation": "LAR"}, "score": "0"}]}],
+         "status": {"type": {"state": "pre", "completed": false}}},
+        {"id": "401671801", "date": "2025-02-02T20:00Z", "season": {"year": 2024, "type": 3}, "week": {"number": 4},
+         "competitions": [], "status": {"type": {"state": "post", "completed": true}}},
+        {"id": "401671700", "date": "2024-08-10T17:00Z", "season": {"year": 2024, "type": 1}, "week": {"number": 1},
+         "competitions": [], "status": {"type": {"state": "post", "completed": true}}}
+    ]}"#;
+
+    #[test]
+    fn test_parse_schedule() {
+        let games = parse_schedule(SCHEDULE).unwrap();
+        assert_eq!(games.len(), 2);
+
+        let opener = &games[0];
+        assert_eq!((opener.season_type, opener.week), ("REG", 1));
+        assert_eq!((opener.home_score, opener.away_score), (Some(27), Some(20)));
+        assert_eq!(opener.status, GameStatus::Final);
+        assert_eq!(opener.venue_city.as_deref(), Some("Kansas City"));
+        assert_eq!(opener.attendance, Some(73426));
+
+        let super_bowl = &games[1];
+        assert_eq!((super_bowl.season_type, super_bowl.week), ("POST", 22));
+        assert_eq!((super_bowl.home_team.as_str(), super_bowl.away_team.as_str()), ("WAS", "LA"));
+        assert_eq!(super_bowl.home_score, None);
+    }
+
+    #[test]
+    fn test_parse_box_score() {
+        let body = r#"{"boxscore": {"players": [{"team": {"abbreviation": "KC"}, "statistics": [
+            {"name": "passing", "keys": ["completions/passingAttempts", "passingYards", "yardsPerPassAttempt",
+                                        "passingTouchdowns", "interceptions"],
+             "athletes": [{"athlete": {"id": "3139477", "displayName": "Patrick Mahomes"},
+                           "stats": ["20/28", "291", "10.4", "1", "1"]}]},
+            {"name": "rushing", "keys": ["rushingAttempts", "rushingYards", "rushingTouchdowns"],
+             "athletes": [{"athlete": {"id": "3139477"}, "stats": ["2", "3", "0"]}]},
+            {"name": "defensive", "keys": ["totalTackles", "soloTackles", "sacks"],
+             "athletes": [{"athlete": {"id": "3051392", "displayName": "Chris Jones"}, "stats": ["4", "3", "1.5"]}]},
+            {"name": "kicking", "keys": ["fieldGoalsMade/fieldGoalAttempts", "longFieldGoalMade"],
+             "athletes": [{"athlete": {"id": "3123076"}, "stats": ["2/2", "--"]}]}
+        ]}]}}"#;
+        let lines = parse_box_score(body).unwrap();
+        assert_eq!(lines.len(), 3);
+
+        let mahomes = &lines[0];
+        assert_eq!((mahomes.completions, mahomes.attempts, mahomes.passing_yards), (20, 28, 291));
+        assert_eq!((mahomes.passing_tds, mahomes.interceptions, mahomes.carries), (1, 1, 2));
+        assert_eq!(mahomes.name.as_deref(), Some("Patrick Mahomes"));
+        assert_eq!(lines[1].def_sacks, 1.5);
+        assert_eq!((lines[2].field_goals_made, lines[2].field_goals_attempted, lines[2].fg_long), (2, 2, None));
+    }
+
+    #[test]
+    fn test_merge_follows_priority() {
+        let takes = |priority| {
+            let precedence = Precedence::new(priority);
+            let merge = Merge::new(&precedence, Source::Espn, "g").fill(Field::Venue, "attendance", "$13");
+            merge.value_sql("attendance")
+        };
+        assert!(takes(SourcePriority::Espn).contains("WHEN 'espn' THEN 0"));
+        assert!(takes(SourcePriority::Nflverse).contains("WHEN 'espn' THEN 1"));
+        assert_eq!(roster_url(API_URL, "WAS"), format!("{}/teams/wsh/roster", API_URL));
+    }
+}
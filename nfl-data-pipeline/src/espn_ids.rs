@@ -0,0 +1,348 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use postgres::Client;
+use serde::Deserialize;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::espn;
+use crate::transformer;
+
+/// Every NFL athlete ESPN has, active or not, in one request
+pub const ATHLETES_URL: &str = "https://sports.core.api.espn.com/v3/sports/football/nfl/athletes?limit=20000";
+pub const DATASET: &str = "espn_athletes";
+/// Scores below this aren't linked (exact unique name plus position and debut year)
+pub const MIN_CONFIDENCE: f64 = 0.75;
+/// The best candidate must beat the runner-up by this much
+const MIN_MARGIN: f64 = 0.15;
+
+#[derive(Debug, Deserialize)]
+struct AthleteList {
+    #[serde(default)]
+    items: Vec<ApiAthlete>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiAthlete {
+    id: String,
+    #[serde(default)]
+    full_name: Option<String>,
+    #[serde(default)]
+    display_name: Option<String>,
+    /// e.g. 1995-09-17T07:00Z
+    #[serde(default)]
+    date_of_birth: Option<String>,
+    #[serde(default)]
+    debut_year: Option<i32>,
+    #[serde(default)]
+    position: Option<Abbreviation>,
+    /// Only present for athletes on a roster; otherwise a `$ref` link
+    #[serde(default)]
+    team: Option<Abbreviation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Abbreviation {
+    #[serde(default)]
+    abbreviation: Option<String>,
+}
+
+/// An ESPN athlete as a link candidate
+#[derive(Debug, Clone, PartialEq)]
+pub struct Athlete {
+    pub espn_id: String,
+    pub name: String,
+    pub birth_date: Option<NaiveDate>,
+    pub debut_year: Option<i32>,
+    pub position: Option<String>,
+    pub team: Option<String>,
+}
+
+pub fn parse_athletes(body: &str) -> Result<Vec<Athlete>> {
+    let list: AthleteList = serde_json::from_str(body).context("Invalid ESPN athletes response")?;
+    Ok(list
+        .items
+        .into_iter()
+        .filter_map(|athlete| {
+            Some(Athlete {
+                name: athlete.full_name.or(athlete.display_name)?,
+                birth_date: athlete
+                    .date_of_birth
+                    .and_then(|date| NaiveDate::parse_from_str(date.get(..10)?, "%Y-%m-%d").ok()),
+                debut_year: athlete.debut_year,
+                position: athlete.position.and_then(|p| p.abbreviation).map(|p| transformer::normalize_position(&p)),
+                team: athlete.team.and_then(|t| t.abbreviation).map(|t| espn::team_abbr(&t)),
+                espn_id: athlete.id,
+            })
+        })
+        .collect())
+}
+
+pub fn fetch_athletes(http: &reqwest::blocking::Client, url: &str) -> Result<Vec<Athlete>> {
+    parse_athletes(&espn::fetch(http, url)?)
+}
+
+/// A player without an ESPN ID
+#[derive(Debug, Clone, PartialEq)]
+pub struct Player {
+    pub id: Uuid,
+    pub name: String,
+    pub position: String,
+    pub team: Option<String>,
+    pub birth_date: Option<NaiveDate>,
+    pub rookie_year: Option<i32>,
+}
+
+/// Players with no ESPN ID in `players.espn_id` or the crosswalk
+pub fn unlinked_players(client: &mut Client) -> Result<Vec<Player>> {
+    load_players(
+        client,
+        "WHERE COALESCE(p.espn_id, '') = ''
+           AND NOT EXISTS (SELECT 1 FROM player_external_ids x WHERE x.player_id = p.id AND x.source = 'espn')",
+    )
+    .context("Failed to load players without ESPN IDs")
+}
+
+/// Every player, as candidates for names found in news items
+pub fn all_players(client: &mut Client) -> Result<Vec<Player>> {
+    load_players(client, "").context("Failed to load players")
+}
+
+fn load_players(client: &mut Client, filter: &str) -> Result<Vec<Player>> {
+    let rows = client.query(
+        &format!(
+            "SELECT p.id, p.name, p.position, t.abbreviation, p.birth_date, COALESCE(p.rookie_year, p.entry_year)
+             FROM players p
+             LEFT JOIN teams t ON t.id = p.team_id
+             {}",
+            filter
+        ),
+        &[],
+    )?;
+    Ok(rows
+        .iter()
+        .map(|row| Player {
+            id: row.get(0),
+            name: row.get(1),
+            position: transformer::normalize_position(row.get(2)),
+            team: row.get(3),
+            birth_date: row.get(4),
+            rookie_year: row.get(5),
+        })
+        .collect())
+}
+
+/// Lowercase name without punctuation or generational suffixes, so
+/// "A.J. Brown" and "AJ Brown", or "Odell Beckham Jr." and "Odell Beckham", match
+pub fn name_key(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace() || *c == '-')
+        .map(|c| if c == '-' { ' ' } else { c.to_ascii_lowercase() })
+        .collect();
+    cleaned
+        .split_whitespace()
+        .filter(|word| !matches!(*word, "jr" | "sr" | "ii" | "iii" | "iv" | "v"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn last_name(key: &str) -> &str {
+    key.rsplit(' ').next().unwrap_or(key)
+}
+
+/// How likely an athlete is the player, from 0 to 1, or `None` when the names
+/// or birth dates rule it out.
+///
+/// - exact name 0.5, or same last name with one first name a prefix of the other
+///   ("Gabe"/"Gabriel", "Pat"/"Patrick", but not "Mike"/"Michael") 0.35
+/// - the only ESPN athlete with that name 0.15
+/// - same birth date 0.3
+/// - same team 0.1, position 0.05, debut year 0.05
+pub fn score(player: &Player, athlete: &Athlete, unique_name: bool) -> Option<f64> {
+    let (ours, theirs) = (name_key(&player.name), name_key(&athlete.name));
+    let mut score: f64 = if ours == theirs {
+        0.5
+    } else {
+        let (first, other) = (ours.split(' ').next()?, theirs.split(' ').next()?);
+        let prefix = first.len().min(other.len()) >= 3 && (first.starts_with(other) || other.starts_with(first));
+        if last_name(&ours) != last_name(&theirs) || !prefix {
+            return None;
+        }
+        0.35
+    };
+    if unique_name {
+        score += 0.15;
+    }
+    match (player.birth_date, athlete.birth_date) {
+        (Some(a), Some(b)) if a == b => score += 0.3,
+        (Some(_), Some(_)) => return None,
+        _ => {}
+    }
+    if player.team.is_some() && player.team == athlete.team {
+        score += 0.1;
+    }
+    if athlete.position.as_deref() == Some(player.position.as_str()) {
+        score += 0.05;
+    }
+    if player.rookie_year.is_some() && player.rookie_year == athlete.debut_year {
+        score += 0.05;
+    }
+    Some((score.min(1.0) * 1000.0).round() / 1000.0)
+}
+
+/// A player linked to an ESPN athlete
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    pub player_id: Uuid,
+    pub player_name: String,
+    pub espn_id: String,
+    pub confidence: f64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Matches {
+    pub links: Vec<Link>,
+    /// Players with more than one close candidate, or sharing their best
+    /// candidate with another player
+    pub ambiguous: usize,
+    /// Players with no candidate at `min_confidence`
+    pub unmatched: usize,
+}
+
+/// Link each player to its best-scoring athlete when that score reaches
+/// `min_confidence` and clearly beats the runner-up. An athlete two players
+/// claim goes to the higher score, or to neither on a tie.
+pub fn match_athletes(players: &[Player], athletes: &[Athlete], min_confidence: f64) -> Matches {
+    let mut by_last_name: HashMap<String, Vec<&Athlete>> = HashMap::new();
+    let mut name_counts: HashMap<String, usize> = HashMap::new();
+    for athlete in athletes {
+        let key = name_key(&athlete.name);
+        by_last_name.entry(last_name(&key).to_string()).or_default().push(athlete);
+        *name_counts.entry(key).or_default() += 1;
+    }
+
+    let mut matches = Matches::default();
+    let mut claims: HashMap<&str, Vec<Link>> = HashMap::new();
+    for player in players {
+        let key = name_key(&player.name);
+        let unique_name = name_counts.get(&key) == Some(&1);
+        let mut scored: Vec<(f64, &Athlete)> = by_last_name
+            .get(last_name(&key))
+            .into_iter()
+            .flatten()
+            .filter_map(|athlete| Some((score(player, athlete, unique_name && name_key(&athlete.name) == key)?, *athlete)))
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        match scored.as_slice() {
+            [(best, _), ..] if *best < min_confidence => matches.unmatched += 1,
+            [] => matches.unmatched += 1,
+            [(best, _), (second, _), ..] if best - second < MIN_MARGIN => matches.ambiguous += 1,
+            [(best, athlete), ..] => claims.entry(athlete.espn_id.as_str()).or_default().push(Link {
+                player_id: player.id,
+                player_name: player.name.clone(),
+                espn_id: athlete.espn_id.clone(),
+                confidence: *best,
+            }),
+        }
+    }
+
+    for (_, mut links) in claims {
+        links.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        if links.len() > 1 && links[0].confidence == links[1].confidence {
+            matches.ambiguous += links.len();
+            continue;
+        }
+        matches.ambiguous += links.len() - 1;
+        matches.links.push(links.swap_remove(0));
+    }
+    matches.links.sort_by(|a, b| a.player_name.cmp(&b.player_name));
+    matches
+}
+
+/// Store links in `players.espn_id` (where empty) and the crosswalk, with
+/// their confidence. Returns the players linked.
+pub fn write_links(client: &mut Client, links: &[Link]) -> Result<usize> {
+    let mut tx = client.transaction()?;
+    let mut written = 0;
+    for link in links {
+        tx.execute(
+            "UPDATE players SET espn_id = $2, updated_at = NOW() WHERE id = $1 AND COALESCE(espn_id, '') = ''",
+            &[&link.player_id, &link.espn_id],
+        )?;
+        written += tx.execute(
+            "INSERT INTO player_external_ids (player_id, source, external_id, match_confidence, updated_at)
+             VALUES ($1, 'espn', $2, ROUND($3::float8::numeric, 3), NOW())
+             ON CONFLICT (player_id, source) DO NOTHING",
+            &[&link.player_id, &link.espn_id, &link.confidence],
+        )? as usize;
+    }
+    tx.commit()?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player(name: &str, position: &str, team: Option<&str>, birth_date: Option<&str>) -> Player {
+        Player {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            position: position.to_string(),
+            team: team.map(String::from),
+            birth_date: birth_date.map(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap()),
+            rookie_year: None,
+        }
+    }
+
+    const ATHLETES: &str = r#"{"count": 4, "items": [
+        {"id": "4047646", "fullName": "A.J. Brown", "dateOfBirth": "1997-06-30T07:00Z", "debutYear": 2019,
+         "position": {"abbreviation": "WR"}, "team": {"abbreviation": "PHI"}},
+        {"id": "2976212", "fullName": "Odell Beckham Jr.", "dateOfBirth": "1992-11-05T08:00Z", "position": {"abbreviation": "WR"},
+         "team": {"$ref": "http://sports.core.api.espn.com/v2/sports/football/leagues/nfl/teams/15"}},
+        {"id": "3116593", "fullName": "Josh Allen", "dateOfBirth": "1996-05-21T07:00Z", "position": {"abbreviation": "QB"}},
+        {"id": "3046779", "fullName": "Josh Allen", "dateOfBirth": "1997-07-13T07:00Z", "position": {"abbreviation": "LB"}},
+        {"id": "999"}
+    ]}"#;
+
+    #[test]
+    fn test_parse_athletes() {
+        let athletes = parse_athletes(ATHLETES).unwrap();
+        assert_eq!(athletes.len(), 4);
+        assert_eq!(athletes[0].birth_date, NaiveDate::from_ymd_opt(1997, 6, 30));
+        assert_eq!((athletes[0].team.as_deref(), athletes[0].debut_year), (Some("PHI"), Some(2019)));
+        assert_eq!(athletes[1].team, None);
+        assert_eq!(athletes[3].position.as_deref(), Some("LB"));
+    }
+
+    #[test]
+    fn test_match_scores_and_ambiguity() {
+        let athletes = parse_athletes(ATHLETES).unwrap();
+        let players = vec![
+            player("AJ Brown", "WR", Some("PHI"), None),
+            player("Odell Beckham", "WR", None, Some("1992-11-05")),
+            // Two ESPN Josh Allens: a shared name alone isn't enough
+            player("Josh Allen", "QB", None, None),
+            player("Josh Allen", "LB", None, Some("1997-07-13")),
+            player("Tom Brady", "QB", Some("TB"), None),
+        ];
+        let matches = match_athletes(&players, &athletes, MIN_CONFIDENCE);
+
+        let linked: Vec<(&str, &str)> =
+            matches.links.iter().map(|link| (link.player_name.as_str(), link.espn_id.as_str())).collect();
+        assert_eq!(linked, [("AJ Brown", "4047646"), ("Josh Allen", "3046779"), ("Odell Beckham", "2976212")]);
+        assert_eq!(matches.links[0].confidence, 0.8);
+        assert_eq!((matches.ambiguous, matches.unmatched), (0, 2));
+
+        // Duplicate player rows claiming the same athlete link neither
+        let duplicates = [players[0].clone(), player("A.J. Brown", "WR", Some("PHI"), None)];
+        let matches = match_athletes(&duplicates, &athletes, MIN_CONFIDENCE);
+        assert_eq!((matches.links.len(), matches.ambiguous), (0, 2));
+
+        // A different birth date rules a candidate out
+        assert_eq!(score(&player("A.J. Brown", "WR", None, Some("1990-01-01")), &athletes[0], true), None);
+    }
+}
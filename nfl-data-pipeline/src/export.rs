@@ -0,0 +1,250 @@
+use anyhow::{anyhow, Context, Result};
+use postgres::fallible_iterator::FallibleIterator;
+use postgres::types::ToSql;
+use postgres::Client;
+use std::io::Write;
+
+/// Columns kept out of exports: merge bookkeeping, not data
+pub(crate) const INTERNAL_COLUMNS: &[&str] = &["row_hash", "provenance"];
+
+/// A table `--mode export` can dump, and which filters apply to it
+#[derive(Debug)]
+struct Entity {
+    name: &'static str,
+    /// References `players` through `player_id` (exported as `player_nfl_id` and `player_name`)
+    player: bool,
+    /// References `teams` through `team_id` (exported as `team`)
+    team: bool,
+    season: bool,
+    week: bool,
+    order: &'static str,
+}
+
+const ENTITIES: &[Entity] = &[
+    Entity { name: "players", player: false, team: true, season: false, week: false, order: "x.name, x.nfl_id" },
+    Entity { name: "teams", player: false, team: false, season: false, week: false, order: "x.abbreviation" },
+    Entity { name: "games", player: false, team: false, season: true, week: true, order: "x.season, x.week, x.game_date, x.nfl_game_id" },
+    Entity { name: "game_stats", player: true, team: true, season: true, week: true, order: "x.season, x.week, p.name, x.id" },
+    Entity { name: "player_season_stats", player: true, team: true, season: true, week: false, order: "x.season, p.name, x.id" },
+    Entity { name: "advanced_stats", player: true, team: false, season: true, week: true, order: "x.season, x.week, x.stat_type, p.name, x.id" },
+    Entity { name: "injuries", player: true, team: true, season: true, week: true, order: "x.season, x.week, t.abbreviation, x.id" },
+    Entity { name: "depth_charts", player: true, team: true, season: true, week: true, order: "x.season, x.week, t.abbreviation, x.position, x.depth_rank, x.id" },
+    Entity { name: "snap_counts", player: true, team: true, season: true, week: true, order: "x.season, x.week, t.abbreviation, x.id" },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    /// One JSON object per line, values keeping their types
+    Jsonl,
+}
+
+impl Format {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "csv" => Ok(Format::Csv),
+            "jsonl" | "ndjson" => Ok(Format::Jsonl),
+            _ => Err(anyhow!("Invalid --format for export: {} (expected csv or jsonl)", value)),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::Jsonl => "jsonl",
+        }
+    }
+}
+
+/// `--season`, `--week` and `--team`
+#[derive(Debug, Default, Clone)]
+pub struct Filters {
+    pub season: Option<i32>,
+    pub week: Option<i32>,
+    pub team: Option<String>,
+}
+
+fn entity(name: &str) -> Result<&'static Entity> {
+    ENTITIES.iter().find(|entity| entity.name == name).ok_or_else(|| {
+        let names: Vec<&str> = ENTITIES.iter().map(|entity| entity.name).collect();
+        anyhow!("Cannot export {}: expected one of {}", name, names.join(", "))
+    })
+}
+
+/// Default output file name, e.g. `game_stats_2023_week1_KC.jsonl`
+pub fn file_name(entity: &str, filters: &Filters, format: Format) -> String {
+    let mut name = entity.to_string();
+    if let Some(season) = filters.season {
+        name.push_str(&format!("_{}", season));
+    }
+    if let Some(week) = filters.week {
+        name.push_str(&format!("_week{}", week));
+    }
+    if let Some(team) = &filters.team {
+        name.push_str(&format!("_{}", team));
+    }
+    format!("{}.{}", name, format.extension())
+}
+
+#[derive(Debug)]
+struct Query {
+    sql: String,
+    /// Output column names
+    names: Vec<String>,
+    params: Vec<Box<dyn ToSql + Sync>>,
+}
+
+/// The export query over `columns` (the table's own, in order)
+fn query(entity: &Entity, columns: &[String], filters: &Filters) -> Result<Query> {
+    let mut select: Vec<(String, String)> = Vec::new();
+    let mut joins = String::new();
+    let mut extra = |expr: &str, alias: &str| {
+        // A table's own column wins over a resolved one of the same name
+        if !columns.iter().any(|column| column == alias) {
+            select.push((expr.to_string(), alias.to_string()));
+        }
+    };
+    match entity.name {
+        "games" => {
+            extra("h.abbreviation", "home_team");
+            extra("a.abbreviation", "away_team");
+            joins.push_str(" JOIN teams h ON h.id = x.home_team_id JOIN teams a ON a.id = x.away_team_id");
+        }
+        _ => {
+            if entity.player {
+                extra("p.nfl_id", "player_nfl_id");
+                extra("p.name", "player_name");
+                joins.push_str(" LEFT JOIN players p ON p.id = x.player_id");
+            }
+            if entity.team {
+                extra("t.abbreviation", "team");
+                joins.push_str(" LEFT JOIN teams t ON t.id = x.team_id");
+            }
+        }
+    }
+    select.extend(columns.iter().map(|column| (format!("x.{}", column), column.clone())));
+
+    let mut conditions = Vec::new();
+    let mut params: Vec<Box<dyn ToSql + Sync>> = Vec::new();
+    let unsupported = |filter: &str| anyhow!("{} can't be filtered by {}", entity.name, filter);
+    if let Some(season) = filters.season {
+        if !entity.season {
+            return Err(unsupported("--season"));
+        }
+        params.push(Box::new(season));
+        conditions.push(format!("x.season = ${}", params.len()));
+    }
+    if let Some(week) = filters.week {
+        if !entity.week {
+            return Err(unsupported("--week"));
+        }
+        params.push(Box::new(week));
+        conditions.push(format!("x.week = ${}", params.len()));
+    }
+    if let Some(team) = &filters.team {
+        params.push(Box::new(team.clone()));
+        let n = params.len();
+        conditions.push(match entity.name {
+            "teams" => format!("x.abbreviation = ${}", n),
+            "games" => format!("(h.abbreviation = ${n} OR a.abbreviation = ${n})", n = n),
+            _ if entity.team => format!("t.abbreviation = ${}", n),
+            _ => return Err(unsupported("--team")),
+        });
+    }
+
+    let sql = format!(
+        "SELECT {} FROM {} x{}{} ORDER BY {}",
+        select.iter().map(|(expr, alias)| format!("{} AS {}", expr, alias)).collect::<Vec<_>>().join(", "),
+        entity.name,
+        joins,
+        match conditions.is_empty() {
+            true => String::new(),
+            false => format!(" WHERE {}", conditions.join(" AND ")),
+        },
+        entity.order,
+    );
+    Ok(Query { sql, names: select.into_iter().map(|(_, alias)| alias).collect(), params })
+}
+
+/// The table's columns in order, minus internal ones
+fn table_columns(client: &mut Client, table: &str) -> Result<Vec<String>> {
+    let rows = client.query(
+        "SELECT column_name::text FROM information_schema.columns
+         WHERE table_schema = current_schema() AND table_name = $1
+         ORDER BY ordinal_position",
+        &[&table],
+    )?;
+    let columns: Vec<String> =
+        rows.iter().map(|row| row.get::<_, String>(0)).filter(|column| !INTERNAL_COLUMNS.contains(&column.as_str())).collect();
+    if columns.is_empty() {
+        return Err(anyhow!("Table {} not found (run --mode migrate)", table));
+    }
+    Ok(columns)
+}
+
+/// Stream an entity's rows, filtered, to `out`; returns the rows written.
+/// Players and teams are referenced by nflverse ID and abbreviation next to
+/// the stored UUIDs, so the files can be used without the database.
+pub fn export(client: &mut Client, name: &str, filters: &Filters, format: Format, out: &mut dyn Write) -> Result<u64> {
+    let entity = entity(name)?;
+    let columns = table_columns(client, entity.name)?;
+    let Query { sql, names, params } = query(entity, &columns, filters)?;
+    let sql = match format {
+        Format::Csv => format!(
+            "SELECT {} FROM ({}) e",
+            names.iter().map(|name| format!("e.{}::text", name)).collect::<Vec<_>>().join(", "),
+            sql
+        ),
+        Format::Jsonl => format!("SELECT row_to_json(e)::text FROM ({}) e", sql),
+    };
+    let mut rows = client
+        .query_raw(&sql, params.iter().map(|param| param.as_ref()))
+        .with_context(|| format!("Failed to export {}", entity.name))?;
+
+    let mut written = 0;
+    match format {
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            writer.write_record(&names)?;
+            while let Some(row) = rows.next()? {
+                writer.write_record((0..row.len()).map(|i| row.get::<_, Option<String>>(i).unwrap_or_default()))?;
+                written += 1;
+            }
+            writer.flush()?;
+        }
+        Format::Jsonl => {
+            while let Some(row) = rows.next()? {
+                writeln!(out, "{}", row.get::<_, String>(0))?;
+                written += 1;
+            }
+            out.flush()?;
+        }
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_query() {
+        let columns: Vec<String> = ["id", "player_id", "season", "week", "team_id", "player_name"].map(String::from).to_vec();
+        let filters = Filters { season: Some(2023), week: None, team: Some("KC".to_string()) };
+        let Query { sql, names, params } = query(entity("depth_charts").unwrap(), &columns, &filters).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT p.nfl_id AS player_nfl_id, t.abbreviation AS team, x.id AS id, x.player_id AS player_id, x.season AS season, \
+             x.week AS week, x.team_id AS team_id, x.player_name AS player_name \
+             FROM depth_charts x LEFT JOIN players p ON p.id = x.player_id LEFT JOIN teams t ON t.id = x.team_id \
+             WHERE x.season = $1 AND t.abbreviation = $2 \
+             ORDER BY x.season, x.week, t.abbreviation, x.position, x.depth_rank, x.id"
+        );
+        assert_eq!(names[..3], ["player_nfl_id", "team", "id"]);
+        assert_eq!(params.len(), 2);
+
+        assert!(query(entity("players").unwrap(), &columns, &filters).unwrap_err().to_string().contains("--season"));
+        assert!(entity("plays").is_err());
+        assert_eq!(file_name("game_stats", &filters, Format::Jsonl), "game_stats_2023_KC.jsonl");
+    }
+}
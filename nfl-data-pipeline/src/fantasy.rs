@@ -0,0 +1,199 @@
+use anyhow::{anyhow, Context, Result};
+use postgres::Client;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::transformer::{DEFENSE_KICKING_COLUMNS, FANTASY_PLAYOFF_WEEKS, OFFENSE_COLUMNS};
+
+/// A named scoring system: points per unit of each stat, plus bonuses
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScoringFormat {
+    pub name: String,
+    /// Points per unit, keyed by game_stats column (e.g. `"passing_tds": 6`)
+    pub points: BTreeMap<String, f64>,
+    #[serde(default)]
+    pub bonuses: Vec<Bonus>,
+}
+
+/// Points awarded once a stat reaches a threshold in a game. Tiers are separate
+/// bonuses on the same stat, and every tier reached counts.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Bonus {
+    pub stat: String,
+    pub threshold: f64,
+    pub points: f64,
+}
+
+/// `--scoring-config` file
+#[derive(Debug, Deserialize)]
+struct ScoringConfig {
+    formats: Vec<ScoringFormat>,
+}
+
+impl ScoringFormat {
+    /// Standard scoring with `per_reception` points per catch
+    fn offense(name: &str, per_reception: f64) -> Self {
+        let points = [
+            ("passing_yards", 0.04),
+            ("passing_tds", 4.0),
+            ("interceptions", -2.0),
+            ("rushing_yards", 0.1),
+            ("rushing_tds", 6.0),
+            ("receptions", per_reception),
+            ("receiving_yards", 0.1),
+            ("receiving_tds", 6.0),
+            ("fumbles_lost", -2.0),
+            ("two_point_conversions", 2.0),
+            ("special_teams_tds", 6.0),
+        ];
+        ScoringFormat {
+            name: name.to_string(),
+            points: points
+                .into_iter()
+                .filter(|(_, value)| *value != 0.0)
+                .map(|(stat, value)| (stat.to_string(), value))
+                .collect(),
+            bonuses: Vec::new(),
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(anyhow!("Scoring format with an empty name"));
+        }
+        let stats = self.points.iter().map(|(stat, value)| (stat, *value));
+        let bonuses = self.bonuses.iter().flat_map(|b| [(&b.stat, b.threshold), (&b.stat, b.points)]);
+        for (stat, value) in stats.chain(bonuses) {
+            if !is_scorable(stat) {
+                return Err(anyhow!("Unknown stat in scoring format {}: {}", self.name, stat));
+            }
+            if !value.is_finite() {
+                return Err(anyhow!("Invalid value for {} in scoring format {}", stat, self.name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Points for one game_stats row as a SQL expression. Stat names are
+    /// checked against the column allowlist by `validate` before use.
+    pub fn points_sql(&self) -> String {
+        let mut terms: Vec<String> = self
+            .points
+            .iter()
+            .map(|(stat, value)| format!("COALESCE({}, 0) * {:?}", stat, value))
+            .collect();
+        terms.extend(self.bonuses.iter().map(|bonus| {
+            format!(
+                "CASE WHEN COALESCE({}, 0) >= {:?} THEN {:?} ELSE 0 END",
+                bonus.stat, bonus.threshold, bonus.points
+            )
+        }));
+        if terms.is_empty() {
+            return "0".to_string();
+        }
+        terms.join(" + ")
+    }
+}
+
+fn is_scorable(stat: &str) -> bool {
+    OFFENSE_COLUMNS.contains(&stat) || DEFENSE_KICKING_COLUMNS.contains(&stat) || stat == "def_sacks"
+}
+
+/// Standard, half-PPR and PPR, plus the formats in `config_path`. A custom
+/// format with a built-in name replaces it.
+pub fn scoring_formats(config_path: Option<&str>) -> Result<Vec<ScoringFormat>> {
+    let mut formats = vec![
+        ScoringFormat::offense("standard", 0.0),
+        ScoringFormat::offense("half_ppr", 0.5),
+        ScoringFormat::offense("ppr", 1.0),
+    ];
+
+    if let Some(path) = config_path {
+        let config: ScoringConfig = serde_json::from_str(
+            &std::fs::read_to_string(path).with_context(|| format!("Failed to read scoring config {}", path))?,
+        )
+        .with_context(|| format!("Invalid scoring config {}", path))?;
+
+        for format in config.formats {
+            format.validate()?;
+            formats.retain(|existing| existing.name != format.name);
+            formats.push(format);
+        }
+    }
+    Ok(formats)
+}
+
+/// Rebuild a season's `fantasy_points` rows for every format (all season types),
+/// tagging regular-season `FANTASY_PLAYOFF_WEEKS`. Returns the number of rows written.
+pub fn refresh_fantasy_points(client: &mut Client, season: i32, formats: &[ScoringFormat]) -> Result<u64> {
+    let mut tx = client.transaction()?;
+    tx.execute("DELETE FROM fantasy_points WHERE season = $1", &[&season])?;
+
+    let mut written = 0;
+    for format in formats {
+        written += tx
+            .execute(
+                &format!(
+                    "INSERT INTO fantasy_points (
+                         player_id, season, week, season_type, scoring, points, fantasy_playoff_week, updated_at
+                     )
+                     SELECT player_id, season, week, season_type, $2, ROUND(({})::numeric, 2),
+                            season_type = 'REG' AND week BETWEEN $3 AND $4, NOW()
+                     FROM game_stats
+                     WHERE season = $1",
+                    format.points_sql()
+                ),
+                &[&season, &format.name, FANTASY_PLAYOFF_WEEKS.start(), FANTASY_PLAYOFF_WEEKS.end()],
+            )
+            .with_context(|| format!("Failed to score {} fantasy points", format.name))?;
+    }
+
+    tx.commit()?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_built_in_formats_differ_only_in_receptions() {
+        let formats = scoring_formats(None).unwrap();
+        let names: Vec<&str> = formats.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["standard", "half_ppr", "ppr"]);
+
+        assert!(!formats[0].points.contains_key("receptions"));
+        assert_eq!(formats[1].points["receptions"], 0.5);
+        assert_eq!(formats[2].points["receptions"], 1.0);
+        assert_eq!(formats[2].points.len(), formats[0].points.len() + 1);
+    }
+
+    #[test]
+    fn test_custom_format_with_tiered_bonuses() {
+        let config: ScoringConfig = serde_json::from_str(
+            r#"{"formats": [{"name": "six_pt_pass", "points": {"passing_tds": 6, "def_sacks": 1.5},
+                "bonuses": [{"stat": "rushing_yards", "threshold": 100, "points": 3},
+                            {"stat": "rushing_yards", "threshold": 200, "points": 3}]}]}"#,
+        )
+        .unwrap();
+        let format = &config.formats[0];
+        format.validate().unwrap();
+
+        assert_eq!(
+            format.points_sql(),
+            "COALESCE(def_sacks, 0) * 1.5 + COALESCE(passing_tds, 0) * 6.0 \
+             + CASE WHEN COALESCE(rushing_yards, 0) >= 100.0 THEN 3.0 ELSE 0 END \
+             + CASE WHEN COALESCE(rushing_yards, 0) >= 200.0 THEN 3.0 ELSE 0 END"
+        );
+    }
+
+    #[test]
+    fn test_unknown_stats_are_rejected() {
+        let format = ScoringFormat {
+            name: "bad".to_string(),
+            points: BTreeMap::from([("passing_yards; DROP TABLE players".to_string(), 1.0)]),
+            bonuses: Vec::new(),
+        };
+        assert!(format.validate().is_err());
+    }
+}
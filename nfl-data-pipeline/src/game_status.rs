@@ -0,0 +1,115 @@
+use chrono::{Duration, Local, NaiveDate};
+
+use crate::parser::Game;
+use crate::transformer;
+
+/// Where a game is in its lifecycle, as stored in `games.status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameStatus {
+    Scheduled,
+    /// Set by `--mode live` while the scoreboard shows the game on
+    InProgress,
+    Final,
+}
+
+const ALL: [GameStatus; 3] = [GameStatus::Scheduled, GameStatus::InProgress, GameStatus::Final];
+
+impl GameStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GameStatus::Scheduled => "scheduled",
+            GameStatus::InProgress => "in_progress",
+            GameStatus::Final => "final",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        ALL.into_iter().find(|status| status.as_str() == value)
+    }
+
+    /// Status a schedule row implies. A game without scores is scheduled until
+    /// the day after its gameday has passed (night games end after midnight, and
+    /// gamedays are Eastern); anything else is final.
+    pub fn from_schedule(home_score: Option<i32>, away_score: Option<i32>, gameday: &str, today: NaiveDate) -> Self {
+        let unplayed = home_score.is_none() && away_score.is_none();
+        match transformer::parse_date(gameday) {
+            Some(date) if unplayed && date >= today - Duration::days(1) => GameStatus::Scheduled,
+            _ => GameStatus::Final,
+        }
+    }
+
+    /// Status a schedule row implies as of today
+    pub fn of(game: &Game) -> Self {
+        GameStatus::from_schedule(game.home_score, game.away_score, &game.gameday, Local::now().date_naive())
+    }
+
+    /// Whether a stored game may move to `next`. Games only move forward:
+    /// scheduled → in progress → final, with scheduled → final when a schedule
+    /// import sees the result before live mode did. A final game stays final, so
+    /// a stale scoreboard or an early schedule file can't reopen it.
+    pub fn can_transition_to(self, next: GameStatus) -> bool {
+        use GameStatus::*;
+        matches!((self, next), (Scheduled, _) | (InProgress, InProgress | Final) | (Final, Final))
+    }
+}
+
+/// SQL condition: whether the stored status `current` may move to `incoming`.
+/// A missing or unrecognized stored status accepts any update.
+pub fn transition_allowed_sql(current: &str, incoming: &str) -> String {
+    let known: Vec<String> = ALL.iter().map(|status| format!("'{}'", status.as_str())).collect();
+    let allowed: Vec<String> = ALL
+        .iter()
+        .flat_map(|from| ALL.iter().filter(|to| from.can_transition_to(**to)).map(move |to| (from, to)))
+        .map(|(from, to)| format!("('{}', '{}')", from.as_str(), to.as_str()))
+        .collect();
+    format!(
+        "({current} IS NULL OR {current} NOT IN ({}) OR ({current}, {incoming}) IN ({}))",
+        known.join(", "),
+        allowed.join(", ")
+    )
+}
+
+/// SQL expression for an upsert's status column: `incoming` when the move is
+/// allowed, otherwise the stored status
+pub fn merge_sql(current: &str, incoming: &str) -> String {
+    format!(
+        "CASE WHEN {} THEN {incoming} ELSE {current} END",
+        transition_allowed_sql(current, incoming)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_status_from_schedule() {
+        let today = date(2024, 9, 9);
+        assert_eq!(GameStatus::from_schedule(None, None, "2024-09-15", today), GameStatus::Scheduled);
+        // Sunday night game, imported after midnight
+        assert_eq!(GameStatus::from_schedule(None, None, "2024-09-08", today), GameStatus::Scheduled);
+        assert_eq!(GameStatus::from_schedule(None, None, "2024-09-05", today), GameStatus::Final);
+        assert_eq!(GameStatus::from_schedule(Some(27), Some(20), "2024-09-08", today), GameStatus::Final);
+    }
+
+    #[test]
+    fn test_transitions_only_move_forward() {
+        use GameStatus::*;
+        assert!(Scheduled.can_transition_to(InProgress));
+        assert!(Scheduled.can_transition_to(Final));
+        assert!(InProgress.can_transition_to(Final));
+        assert!(!InProgress.can_transition_to(Scheduled));
+        assert!(!Final.can_transition_to(InProgress));
+        assert!(!Final.can_transition_to(Scheduled));
+        assert_eq!(GameStatus::parse("in_progress"), Some(InProgress));
+        assert_eq!(GameStatus::parse("postponed"), None);
+
+        let sql = merge_sql("games.status", "EXCLUDED.status");
+        assert!(sql.contains("('in_progress', 'final')"));
+        assert!(!sql.contains("('final', 'scheduled')"));
+    }
+}
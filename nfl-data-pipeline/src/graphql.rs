@@ -0,0 +1,586 @@
+//! `/graphql` on the serve mode API: players, teams, games and weekly stat lines
+//! with their relationships, so a client can fetch e.g. a player with their
+//! last five game lines in one request.
+//!
+//! The schema is built at startup from the served tables' columns, so every
+//! column the REST endpoints return is a field. Root fields read through the
+//! same routes and filters as the REST endpoints. Relationship fields go
+//! through one batching [`DataLoader`]: a page of players asking for their games
+//! costs one query, not one per player. The schema is read-only, and query
+//! depth and complexity are capped.
+
+use anyhow::{Context, Result};
+use async_graphql::dataloader::{DataLoader, Loader};
+use async_graphql::dynamic::{Field, FieldFuture, FieldValue, InputValue, Object, ObjectAccessor, Scalar, Schema, TypeRef};
+use async_graphql::{Error, Value};
+use axum::http::StatusCode;
+use log::warn;
+use serde_json::Value as Json;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::Arc;
+
+use crate::api::{self, Page, Params, Route, Select};
+use crate::database::Pool;
+use crate::export::INTERNAL_COLUMNS;
+
+/// Deepest selection nesting a query may use
+const MAX_DEPTH: usize = 6;
+/// Fields one query may select, counting each nested field once
+const MAX_COMPLEXITY: usize = 1000;
+/// `Player.games(last:)` when not given
+const DEFAULT_LAST: i64 = 5;
+/// Rows a nested list returns unless its `limit` says otherwise
+const DEFAULT_NESTED_LIMIT: i64 = 100;
+/// `json` and `jsonb` columns, passed through as they are stored
+const JSON: &str = "JSON";
+/// Both teams of each game, for matching games on either side
+const SIDES: &str = "CROSS JOIN LATERAL unnest(ARRAY[x.home_team_id, x.away_team_id]) side(team_id)";
+
+/// The object types, named as in the schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Kind {
+    Player,
+    Team,
+    Game,
+    /// One player's stats for one week (a `game_stats` row)
+    GameLine,
+}
+
+impl Kind {
+    const ALL: [Kind; 4] = [Kind::Player, Kind::Team, Kind::Game, Kind::GameLine];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Kind::Player => "Player",
+            Kind::Team => "Team",
+            Kind::Game => "Game",
+            Kind::GameLine => "GameLine",
+        }
+    }
+
+    fn table(&self) -> &'static str {
+        match self {
+            Kind::Player => "players",
+            Kind::Team => "teams",
+            Kind::Game => "games",
+            Kind::GameLine => "game_stats",
+        }
+    }
+
+    fn select(&self) -> Select {
+        match self {
+            Kind::Player => api::players(),
+            Kind::Team => api::teams(),
+            Kind::Game => api::games(),
+            Kind::GameLine => api::stats(),
+        }
+    }
+
+    /// Text fields the REST rows carry beyond the table's columns, other than
+    /// team abbreviations (those are `Team` relations here)
+    fn resolved(&self) -> &'static [&'static str] {
+        match self {
+            Kind::GameLine => &["player_nfl_id", "player_name", "position"],
+            _ => &[],
+        }
+    }
+}
+
+/// A `Query` field: the REST route it reads, with `{arg}` path segments, and the arguments it takes
+struct RootField {
+    name: &'static str,
+    kind: Kind,
+    route: &'static str,
+    arguments: &'static [&'static str],
+}
+
+const ROOT: &[RootField] = &[
+    RootField { name: "player", kind: Kind::Player, route: "/players/{id}", arguments: &["id"] },
+    RootField { name: "players", kind: Kind::Player, route: "/players", arguments: &["team", "position", "status", "limit", "offset"] },
+    RootField { name: "team", kind: Kind::Team, route: "/teams/{abbr}", arguments: &["abbr"] },
+    RootField { name: "teams", kind: Kind::Team, route: "/teams", arguments: &["conference", "division", "limit", "offset"] },
+    RootField { name: "game", kind: Kind::Game, route: "/games/{id}", arguments: &["id"] },
+    RootField { name: "games", kind: Kind::Game, route: "/games", arguments: &["season", "week", "team", "status", "limit", "offset"] },
+    RootField {
+        name: "stats",
+        kind: Kind::GameLine,
+        route: "/stats",
+        arguments: &["season", "week", "team", "player", "position", "limit", "offset"],
+    },
+];
+
+/// What a relationship field asks the loader for. Keys that differ only in
+/// their parent ID are answered by one query.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    /// A row by its internal ID, as in a `*_id` column
+    Row(Kind, String),
+    /// The game a team played in a week, for stat lines without a `game_id`
+    GameOfWeek { season: i32, week: i32, team: String },
+    PlayerGames { player: String, season: Option<i32>, last: i64 },
+    TeamPlayers { team: String, position: Option<String>, status: Option<String>, limit: i64 },
+    TeamGames { team: String, season: Option<i32>, week: Option<i32>, limit: i64 },
+    GameStats { game: String, position: Option<String>, limit: i64 },
+}
+
+impl Key {
+    fn parent_mut(&mut self) -> &mut String {
+        match self {
+            Key::Row(_, id) => id,
+            Key::GameOfWeek { team, .. } => team,
+            Key::PlayerGames { player, .. } => player,
+            Key::TeamPlayers { team, .. } => team,
+            Key::TeamGames { team, .. } => team,
+            Key::GameStats { game, .. } => game,
+        }
+    }
+
+    /// The key with its parent ID blanked, which keys sharing a query have in
+    /// common, and the parent ID
+    fn split(&self) -> (Key, String) {
+        let mut group = self.clone();
+        let parent = std::mem::take(group.parent_mut());
+        (group, parent)
+    }
+
+    fn with_parent(&self, parent: String) -> Key {
+        let mut key = self.clone();
+        *key.parent_mut() = parent;
+        key
+    }
+
+    /// A group's query: the rows to select, the expression parents are matched
+    /// on, and the rows each parent gets
+    fn query(&self) -> (Select, &'static str, i64) {
+        match self {
+            Key::Row(kind, _) => (kind.select(), "x.id::text", 1),
+            // nflverse weekly stats aren't linked to a game; a team plays once a week
+            Key::GameOfWeek { season, week, .. } => {
+                let mut select = api::games();
+                select.join(SIDES);
+                select.filter("x.season = $", *season);
+                select.filter("x.week = $", *week);
+                (select, "side.team_id::text", 1)
+            }
+            Key::PlayerGames { season, last, .. } => {
+                let mut select = api::stats();
+                if let Some(season) = season {
+                    select.filter("x.season = $", *season);
+                }
+                select.order = "x.season DESC, x.week DESC, x.id".to_string();
+                (select, "x.player_id::text", *last)
+            }
+            Key::TeamPlayers { position, status, limit, .. } => {
+                let mut select = api::players();
+                if let Some(position) = position {
+                    select.filter("x.position = $", position.clone());
+                }
+                if let Some(status) = status {
+                    select.filter("x.status = $", status.clone());
+                }
+                (select, "x.team_id::text", *limit)
+            }
+            Key::TeamGames { season, week, limit, .. } => {
+                let mut select = api::games();
+                select.join(SIDES);
+                if let Some(season) = season {
+                    select.filter("x.season = $", *season);
+                }
+                if let Some(week) = week {
+                    select.filter("x.week = $", *week);
+                }
+                (select, "side.team_id::text", *limit)
+            }
+            Key::GameStats { position, limit, .. } => {
+                let mut select = api::stats();
+                select.join("JOIN games g ON g.season = x.season AND g.week = x.week AND x.team_id IN (g.home_team_id, g.away_team_id)");
+                if let Some(position) = position {
+                    select.filter("p.position = $", position.clone());
+                }
+                (select, "g.id::text", *limit)
+            }
+        }
+    }
+}
+
+/// Loads relationship rows, one query per group of keys. Nothing is cached
+/// between batches.
+pub(crate) struct RowLoader {
+    pool: Pool,
+}
+
+impl Loader<Key> for RowLoader {
+    type Value = Vec<Json>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[Key]) -> Result<HashMap<Key, Vec<Json>>, Self::Error> {
+        let mut groups: HashMap<Key, Vec<String>> = HashMap::new();
+        for key in keys {
+            let (group, parent) = key.split();
+            groups.entry(group).or_default().push(parent);
+        }
+        api::with_client(&self.pool, move |client| {
+            let mut found = HashMap::new();
+            for (group, parents) in groups {
+                let (select, parent, limit) = group.query();
+                for (id, rows) in api::grouped(client, select, parent, parents, limit)? {
+                    found.insert(group.with_parent(id), rows);
+                }
+            }
+            Ok(found)
+        })
+        .await
+        .map_err(Arc::new)
+    }
+}
+
+/// Each served table's columns with their Postgres types, minus internal ones
+pub(crate) type Columns = HashMap<String, Vec<(String, String)>>;
+
+fn columns(client: &mut postgres::Client) -> Result<Columns> {
+    let tables: Vec<&str> = Kind::ALL.iter().map(Kind::table).collect();
+    let rows = client.query(
+        "SELECT table_name::text, column_name::text, data_type::text FROM information_schema.columns
+         WHERE table_schema = current_schema() AND table_name = ANY($1)
+         ORDER BY table_name, ordinal_position",
+        &[&tables],
+    )?;
+    let mut columns = Columns::new();
+    for row in &rows {
+        let column: String = row.get(1);
+        if !INTERNAL_COLUMNS.contains(&column.as_str()) {
+            columns.entry(row.get(0)).or_default().push((column, row.get(2)));
+        }
+    }
+    Ok(columns)
+}
+
+/// The GraphQL type a column of Postgres `data_type` is served as
+fn scalar(data_type: &str) -> &'static str {
+    match data_type {
+        "smallint" | "integer" | "bigint" => TypeRef::INT,
+        "real" | "double precision" | "numeric" => TypeRef::FLOAT,
+        "boolean" => TypeRef::BOOLEAN,
+        "json" | "jsonb" | "ARRAY" => JSON,
+        _ => TypeRef::STRING,
+    }
+}
+
+/// A database failure, logged here and reported to the client without details
+fn failed(e: impl Display) -> Error {
+    warn!("⚠️  GraphQL query failed: {:#}", e);
+    Error::new("Query failed")
+}
+
+/// Rows as a list field's value, or the first row as an object field's
+fn objects<'a>(rows: Vec<Json>, list: bool) -> Option<FieldValue<'a>> {
+    match list {
+        true => Some(FieldValue::list(rows.into_iter().map(FieldValue::owned_any))),
+        false => rows.into_iter().next().map(FieldValue::owned_any),
+    }
+}
+
+/// A column of the parent row
+fn column(name: &str, ty: &'static str) -> Field {
+    let key = name.to_string();
+    Field::new(name, TypeRef::named(ty), move |ctx| {
+        let value = ctx.parent_value.downcast_ref::<Json>().and_then(|row| row.get(&key)).filter(|value| !value.is_null()).cloned();
+        FieldFuture::new(async move { Ok(value.map(Value::from_json).transpose()?.map(FieldValue::value)) })
+    })
+}
+
+type KeyFn = dyn Fn(&Json, &ObjectAccessor) -> Result<Option<Key>, Error> + Send + Sync;
+
+/// A field read through the loader, with the key built from the parent row
+/// and the field's arguments; `None` when the row has nothing to link
+fn relation(name: &'static str, kind: Kind, list: bool, key: Box<KeyFn>) -> (&'static str, Field) {
+    let ty = match list {
+        true => TypeRef::named_nn_list_nn(kind.name()),
+        false => TypeRef::named(kind.name()),
+    };
+    let field = Field::new(name, ty, move |ctx| {
+        let key = ctx.parent_value.try_downcast_ref::<Json>().and_then(|row| key(row, &ctx.args));
+        FieldFuture::new(async move {
+            let rows = match key? {
+                Some(key) => ctx.data::<DataLoader<RowLoader>>()?.load_one(key).await.map_err(failed)?.unwrap_or_default(),
+                None => Vec::new(),
+            };
+            Ok(objects(rows, list))
+        })
+    });
+    (name, field)
+}
+
+fn id(row: &Json, column: &str) -> Option<String> {
+    row.get(column).and_then(Json::as_str).map(str::to_string)
+}
+
+fn int(args: &ObjectAccessor, name: &str) -> Result<Option<i32>, Error> {
+    args.get(name).filter(|value| !value.is_null()).map(|value| Ok(value.i64()? as i32)).transpose()
+}
+
+fn text(args: &ObjectAccessor, name: &str) -> Result<Option<String>, Error> {
+    args.get(name).filter(|value| !value.is_null()).map(|value| Ok(value.string()?.to_string())).transpose()
+}
+
+/// The `name` argument's row count, or `default`, bounded like the REST endpoints
+fn limit(args: &ObjectAccessor, name: &str, default: i64) -> Result<i64, Error> {
+    let limit = args.get(name).filter(|value| !value.is_null()).map(|value| value.i64()).transpose()?.unwrap_or(default);
+    let params: Params = [("limit".to_string(), limit.to_string())].into_iter().collect();
+    Ok(Page::from_params(&params).map_err(|e| Error::new(format!("{}: {}", name, e.message)))?.limit)
+}
+
+/// A team by the ID in the row's `column`
+fn team(name: &'static str, column: &'static str) -> (&'static str, Field) {
+    relation(name, Kind::Team, false, Box::new(move |row, _| Ok(id(row, column).map(|team| Key::Row(Kind::Team, team)))))
+}
+
+/// Fields linking one type to another, by name
+fn relations(kind: Kind) -> Vec<(&'static str, Field)> {
+    let nested_limit = || InputValue::new("limit", TypeRef::named(TypeRef::INT)).default_value(DEFAULT_NESTED_LIMIT);
+    let optional = |name: &str, ty: &str| InputValue::new(name, TypeRef::named(ty));
+    match kind {
+        Kind::Player => {
+            let (name, games) = relation(
+                "games",
+                Kind::GameLine,
+                true,
+                Box::new(|row, args| {
+                    let Some(player) = id(row, "id") else {
+                        return Ok(None);
+                    };
+                    Ok(Some(Key::PlayerGames { player, season: int(args, "season")?, last: limit(args, "last", DEFAULT_LAST)? }))
+                }),
+            );
+            let games = games
+                .argument(InputValue::new("last", TypeRef::named(TypeRef::INT)).default_value(DEFAULT_LAST))
+                .argument(optional("season", TypeRef::INT));
+            vec![team("team", "team_id"), (name, games)]
+        }
+        Kind::Team => {
+            let (players_name, players) = relation(
+                "players",
+                Kind::Player,
+                true,
+                Box::new(|row, args| {
+                    let Some(team) = id(row, "id") else {
+                        return Ok(None);
+                    };
+                    Ok(Some(Key::TeamPlayers {
+                        team,
+                        position: text(args, "position")?.map(|position| position.to_uppercase()),
+                        status: text(args, "status")?,
+                        limit: limit(args, "limit", DEFAULT_NESTED_LIMIT)?,
+                    }))
+                }),
+            );
+            let (games_name, games) = relation(
+                "games",
+                Kind::Game,
+                true,
+                Box::new(|row, args| {
+                    let Some(team) = id(row, "id") else {
+                        return Ok(None);
+                    };
+                    Ok(Some(Key::TeamGames {
+                        team,
+                        season: int(args, "season")?,
+                        week: int(args, "week")?,
+                        limit: limit(args, "limit", DEFAULT_NESTED_LIMIT)?,
+                    }))
+                }),
+            );
+            vec![
+                (players_name, players.argument(optional("position", TypeRef::STRING)).argument(optional("status", TypeRef::STRING)).argument(nested_limit())),
+                (games_name, games.argument(optional("season", TypeRef::INT)).argument(optional("week", TypeRef::INT)).argument(nested_limit())),
+            ]
+        }
+        Kind::Game => {
+            let (name, stats) = relation(
+                "stats",
+                Kind::GameLine,
+                true,
+                Box::new(|row, args| {
+                    let Some(game) = id(row, "id") else {
+                        return Ok(None);
+                    };
+                    Ok(Some(Key::GameStats {
+                        game,
+                        position: text(args, "position")?.map(|position| position.to_uppercase()),
+                        limit: limit(args, "limit", DEFAULT_NESTED_LIMIT)?,
+                    }))
+                }),
+            );
+            vec![
+                team("home_team", "home_team_id"),
+                team("away_team", "away_team_id"),
+                (name, stats.argument(optional("position", TypeRef::STRING)).argument(nested_limit())),
+            ]
+        }
+        Kind::GameLine => vec![
+            team("team", "team_id"),
+            relation("player", Kind::Player, false, Box::new(|row, _| Ok(id(row, "player_id").map(|player| Key::Row(Kind::Player, player))))),
+            relation(
+                "game",
+                Kind::Game,
+                false,
+                Box::new(|row, _| {
+                    if let Some(game) = id(row, "game_id") {
+                        return Ok(Some(Key::Row(Kind::Game, game)));
+                    }
+                    let week = |column: &str| row.get(column).and_then(Json::as_i64).map(|value| value as i32);
+                    Ok(match (week("season"), week("week"), id(row, "team_id")) {
+                        (Some(season), Some(week), Some(team)) => Some(Key::GameOfWeek { season, week, team }),
+                        _ => None,
+                    })
+                }),
+            ),
+        ],
+    }
+}
+
+/// A root field, read through its REST route
+fn root(root: &'static RootField) -> Field {
+    let one = root.route.contains('{');
+    let ty = match one {
+        true => TypeRef::named(root.kind.name()),
+        false => TypeRef::named_nn_list_nn(root.kind.name()),
+    };
+    let mut field = Field::new(root.name, ty, move |ctx| {
+        FieldFuture::new(async move {
+            let mut path = String::new();
+            for segment in root.route.split('/').filter(|segment| !segment.is_empty()) {
+                path.push('/');
+                match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                    Some(argument) => path.push_str(ctx.args.try_get(argument)?.string()?),
+                    None => path.push_str(segment),
+                }
+            }
+            let params: Params = ctx
+                .args
+                .iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(name, value)| {
+                    let value = match value.as_value() {
+                        Value::String(value) => value.clone(),
+                        value => value.to_string(),
+                    };
+                    (name.to_string(), value)
+                })
+                .collect();
+            let route = match api::route(&path, &params) {
+                Ok(route) => route,
+                // An ID that doesn't fit in a path can't match anything
+                Err(e) if e.status == StatusCode::NOT_FOUND => return Ok(None),
+                Err(e) => return Err(Error::new(format!("{}: {}", root.name, e.message))),
+            };
+            let pool = ctx.data::<Pool>()?;
+            let rows = api::with_client(pool, move |client| match route {
+                Route::List(select, page) => api::rows(client, select, page),
+                Route::One(select) => Ok(api::one(client, select)?.into_iter().collect()),
+                Route::Search(_) => unreachable!("search isn't a root field"),
+            })
+            .await
+            .map_err(failed)?;
+            Ok(objects(rows, !one))
+        })
+    });
+    for argument in root.arguments {
+        let ty = match *argument {
+            "id" | "abbr" => TypeRef::named_nn(TypeRef::STRING),
+            "season" | "week" | "limit" | "offset" => TypeRef::named(TypeRef::INT),
+            _ => TypeRef::named(TypeRef::STRING),
+        };
+        field = field.argument(InputValue::new(*argument, ty));
+    }
+    field
+}
+
+/// The schema over `columns`, querying `pool`
+pub(crate) fn build(columns: &Columns, pool: Pool) -> Result<Schema> {
+    let query = ROOT.iter().fold(Object::new("Query"), |query, field| query.field(root(field)));
+    let mut schema = Schema::build("Query", None, None).register(query).register(Scalar::new(JSON));
+    for kind in Kind::ALL {
+        let relations = relations(kind);
+        let mut object = Object::new(kind.name());
+        for (name, data_type) in columns.get(kind.table()).into_iter().flatten() {
+            if relations.iter().all(|(relation, _)| relation != name) {
+                object = object.field(column(name, scalar(data_type)));
+            }
+        }
+        for name in kind.resolved() {
+            object = object.field(column(name, TypeRef::STRING));
+        }
+        object = relations.into_iter().fold(object, |object, (_, field)| object.field(field));
+        schema = schema.register(object);
+    }
+    Ok(schema
+        .data(DataLoader::new(RowLoader { pool: pool.clone() }, tokio::spawn))
+        .data(pool)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()?)
+}
+
+/// The schema for the database behind `pool`
+pub(crate) fn schema(pool: &Pool) -> Result<Schema> {
+    let mut client = pool.get().context("No database connection available")?;
+    build(&columns(&mut client)?, pool.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use postgres_native_tls::MakeTlsConnector;
+    use r2d2_postgres::PostgresConnectionManager;
+
+    fn schema() -> Schema {
+        let manager = PostgresConnectionManager::new(
+            "postgres://localhost:1/none".parse().unwrap(),
+            MakeTlsConnector::new(native_tls::TlsConnector::new().unwrap()),
+        );
+        let columns = Columns::from([
+            ("players".to_string(), vec![("id".to_string(), "uuid".to_string()), ("name".to_string(), "text".to_string())]),
+            ("games".to_string(), vec![("week".to_string(), "integer".to_string()), ("stats".to_string(), "jsonb".to_string())]),
+        ]);
+        build(&columns, r2d2::Pool::builder().build_unchecked(manager)).unwrap()
+    }
+
+    #[test]
+    fn test_keys_group_by_arguments() {
+        let key = |player: &str, season| Key::PlayerGames { player: player.to_string(), season, last: 5 };
+        let (group, parent) = key("a", Some(2024)).split();
+        assert_eq!(parent, "a");
+        assert_eq!(key("b", Some(2024)).split().0, group);
+        assert_ne!(key("b", None).split().0, group);
+        assert_eq!(group.with_parent("b".to_string()), key("b", Some(2024)));
+    }
+
+    #[test]
+    fn test_schema_from_columns() {
+        let sdl = schema().sdl();
+        assert!(sdl.contains("games(last: Int = 5, season: Int): [GameLine!]!"), "{}", sdl);
+        assert!(sdl.contains("name: String"));
+        assert!(sdl.contains("week: Int"));
+        // a column named like a relation is served as the relation
+        assert!(sdl.contains("stats(position: String, limit: Int = 100): [GameLine!]!"));
+        assert!(!sdl.contains("stats: JSON"));
+        assert!(sdl.contains("player(id: String!): Player"));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_before_querying() {
+        let schema = schema();
+        assert_eq!(schema.execute("{ __typename }").await.data.into_json().unwrap(), serde_json::json!({ "__typename": "Query" }));
+        for (query, error) in [
+            ("mutation { purge }", "Schema is not configured for mutations"),
+            ("{ player(id: \"x\") { team { players { team { players { team { name } } } } } } }", "nested too deep"),
+            ("{ players { nickname } }", "Unknown field \"nickname\""),
+            ("{ teams(limit: \"ten\") { name } }", "Invalid value"),
+        ] {
+            let errors = schema.execute(query).await.errors;
+            assert!(errors.iter().any(|e| e.message.contains(error)), "{}: {:?}", query, errors);
+        }
+    }
+}
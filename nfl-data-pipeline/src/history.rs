@@ -0,0 +1,105 @@
+use anyhow::{Result, Context};
+use postgres::Client;
+
+/// Tables whose prior versions can be kept for point-in-time queries
+pub const TRACKED_TABLES: [&str; 2] = ["players", "games"];
+
+/// Shared trigger function: store the old row whenever it changes or is deleted.
+/// Upserts that only bump `updated_at` are not recorded.
+const RECORD_HISTORY_FN: &str = "
+CREATE OR REPLACE FUNCTION record_history() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' AND (to_jsonb(OLD) - 'updated_at') = (to_jsonb(NEW) - 'updated_at') THEN
+        RETURN NEW;
+    END IF;
+    EXECUTE format('INSERT INTO %I (row_id, data, valid_to, operation) VALUES ($1, $2, NOW(), $3)', TG_TABLE_NAME || '_history')
+        USING OLD.id, to_jsonb(OLD), TG_OP;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql";
+
+/// History table, trigger and `<table>_as_of(timestamp)` function for one table.
+///
+/// A history row holds a version that stopped being current at `valid_to`, so the
+/// version current at `ts` is the first history row after `ts`, or else the live row.
+fn history_ddl(table: &str) -> String {
+    format!(
+        "CREATE TABLE IF NOT EXISTS {table}_history (
+             history_id BIGSERIAL PRIMARY KEY,
+             row_id UUID NOT NULL,
+             data JSONB NOT NULL,
+             valid_to TIMESTAMP NOT NULL,
+             operation VARCHAR(6) NOT NULL
+         );
+         CREATE INDEX IF NOT EXISTS idx_{table}_history_row ON {table}_history(row_id, valid_to);
+         CREATE INDEX IF NOT EXISTS idx_{table}_history_valid_to ON {table}_history(valid_to);
+
+         DROP TRIGGER IF EXISTS {table}_history_trigger ON {table};
+         CREATE TRIGGER {table}_history_trigger
+             AFTER UPDATE OR DELETE ON {table}
+             FOR EACH ROW EXECUTE FUNCTION record_history();
+
+         CREATE OR REPLACE FUNCTION {table}_as_of(ts TIMESTAMP) RETURNS SETOF {table} AS $$
+             SELECT (jsonb_populate_record(NULL::{table}, v.data)).*
+             FROM (
+                 SELECT DISTINCT ON (h.row_id) h.data
+                 FROM {table}_history h
+                 WHERE h.valid_to > ts AND (h.data->>'created_at')::timestamp <= ts
+                 ORDER BY h.row_id, h.valid_to
+             ) v
+             UNION ALL
+             SELECT t.*
+             FROM {table} t
+             WHERE t.created_at <= ts
+               AND NOT EXISTS (SELECT 1 FROM {table}_history h WHERE h.row_id = t.id AND h.valid_to > ts)
+         $$ LANGUAGE sql STABLE;
+
+         COMMENT ON TABLE {table}_history IS 'Prior versions of {table} rows, managed by the pipeline (--mode history)';"
+    )
+}
+
+/// Create history tables and triggers for every tracked table (idempotent)
+pub fn enable(client: &mut Client) -> Result<()> {
+    let mut tx = client.transaction()?;
+    tx.batch_execute(RECORD_HISTORY_FN)?;
+    for table in TRACKED_TABLES {
+        tx.batch_execute(&history_ddl(table))
+            .with_context(|| format!("Failed to enable history for {}", table))?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Drop triggers, history tables and as-of functions
+pub fn disable(client: &mut Client) -> Result<()> {
+    let mut tx = client.transaction()?;
+    for table in TRACKED_TABLES {
+        tx.batch_execute(&format!(
+            "DROP TRIGGER IF EXISTS {table}_history_trigger ON {table};
+             DROP FUNCTION IF EXISTS {table}_as_of(TIMESTAMP);
+             DROP TABLE IF EXISTS {table}_history;"
+        ))?;
+    }
+    tx.batch_execute("DROP FUNCTION IF EXISTS record_history()")?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Whether history tracking has been enabled
+pub fn is_enabled(client: &mut Client) -> Result<bool> {
+    let row = client.query_one("SELECT to_regclass('players_history') IS NOT NULL", &[])?;
+    Ok(row.get(0))
+}
+
+/// Delete versions that stopped being current more than `retain_days` ago.
+/// Returns the number of history rows removed.
+pub fn prune(client: &mut Client, retain_days: i32) -> Result<u64> {
+    let mut pruned = 0;
+    for table in TRACKED_TABLES {
+        pruned += client.execute(
+            &format!("DELETE FROM {table}_history WHERE valid_to < NOW() - make_interval(days => $1)"),
+            &[&retain_days],
+        )?;
+    }
+    Ok(pruned)
+}
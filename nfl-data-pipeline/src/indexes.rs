@@ -0,0 +1,273 @@
+use anyhow::{Context, Result};
+use log::{info, warn};
+use postgres::Client;
+
+/// Tables smaller than this are cheap to scan and never flagged
+const MIN_FLAGGED_ROWS: i64 = 10_000;
+/// Slowest pipeline statements listed from pg_stat_statements
+const SLOW_STATEMENT_LIMIT: usize = 10;
+
+/// A foreign key with no index leading on its columns. Joins through it and
+/// cascading deletes on the referenced table scan the whole table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissingIndex {
+    pub table: String,
+    pub columns: Vec<String>,
+    pub constraint: String,
+}
+
+impl MissingIndex {
+    pub fn create_sql(&self) -> String {
+        format!(
+            "CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_{}_{} ON {} ({});",
+            self.table,
+            self.columns.join("_"),
+            self.table,
+            self.columns.join(", ")
+        )
+    }
+}
+
+/// A non-unique index on a sizeable table, never used since statistics were
+/// last reset. Every upsert still pays to maintain it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnusedIndex {
+    pub table: String,
+    pub index: String,
+    pub size_bytes: i64,
+}
+
+/// Scan counters for one table from pg_stat_user_tables
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableScans {
+    pub table: String,
+    pub seq_scan: i64,
+    pub seq_tup_read: i64,
+    pub idx_scan: i64,
+    pub live_rows: i64,
+}
+
+impl TableScans {
+    /// A sizeable table read mostly by sequential scans that each read at least
+    /// half of it, i.e. queries filtering on columns without an index
+    pub fn is_seq_scan_heavy(&self) -> bool {
+        self.live_rows >= MIN_FLAGGED_ROWS
+            && self.seq_scan > self.idx_scan
+            && self.seq_tup_read / self.seq_scan.max(1) >= self.live_rows / 2
+    }
+}
+
+/// One normalized statement from pg_stat_statements
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlowStatement {
+    pub query: String,
+    pub calls: i64,
+    pub mean_ms: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct IndexReport {
+    /// When usage counters were last reset; unused means unused since then
+    pub stats_since: Option<String>,
+    pub missing: Vec<MissingIndex>,
+    pub unused: Vec<UnusedIndex>,
+    pub seq_scan_heavy: Vec<TableScans>,
+    /// `None` when the pg_stat_statements extension is not installed
+    pub slow_statements: Option<Vec<SlowStatement>>,
+}
+
+/// Whether a statement reads or writes one of the tables (whole-word match)
+pub fn mentions_table(query: &str, table: &str) -> bool {
+    let query = query.to_lowercase();
+    query.match_indices(table).any(|(start, _)| {
+        let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+        !is_word(query[..start].chars().next_back()) && !is_word(query[start + table.len()..].chars().next())
+    })
+}
+
+/// Inspect the public schema's indexes and statistics
+pub fn analyze(client: &mut Client) -> Result<IndexReport> {
+    let missing = client
+        .query(
+            "SELECT c.conrelid::regclass::text, c.conname::text,
+                    array_agg(a.attname::text ORDER BY k.ord)
+             FROM pg_constraint c
+             CROSS JOIN LATERAL unnest(c.conkey) WITH ORDINALITY AS k(attnum, ord)
+             JOIN pg_attribute a ON a.attrelid = c.conrelid AND a.attnum = k.attnum
+             WHERE c.contype = 'f' AND c.connamespace = 'public'::regnamespace
+               AND NOT EXISTS (
+                   SELECT 1 FROM pg_index i
+                   WHERE i.indrelid = c.conrelid
+                     AND (string_to_array(i.indkey::text, ' ')::int2[])[1:cardinality(c.conkey)] @> c.conkey
+                     AND (string_to_array(i.indkey::text, ' ')::int2[])[1:cardinality(c.conkey)] <@ c.conkey
+               )
+             GROUP BY c.oid, c.conrelid, c.conname
+             ORDER BY 1, 2",
+            &[],
+        )
+        .context("Failed to read foreign keys")?
+        .iter()
+        .map(|row| MissingIndex { table: row.get(0), constraint: row.get(1), columns: row.get(2) })
+        .collect();
+
+    let unused = client
+        .query(
+            "SELECT s.relname::text, s.indexrelname::text, pg_relation_size(s.indexrelid)
+             FROM pg_stat_user_indexes s
+             JOIN pg_index i ON i.indexrelid = s.indexrelid
+             JOIN pg_stat_user_tables t ON t.relid = s.relid
+             WHERE s.schemaname = 'public' AND s.idx_scan = 0 AND t.n_live_tup >= $1
+               AND NOT i.indisunique AND NOT i.indisprimary
+               AND NOT EXISTS (SELECT 1 FROM pg_constraint c WHERE c.conindid = s.indexrelid)
+             ORDER BY pg_relation_size(s.indexrelid) DESC",
+            &[&MIN_FLAGGED_ROWS],
+        )
+        .context("Failed to read index usage")?
+        .iter()
+        .map(|row| UnusedIndex { table: row.get(0), index: row.get(1), size_bytes: row.get(2) })
+        .collect();
+
+    let tables: Vec<TableScans> = client
+        .query(
+            "SELECT relname::text, seq_scan, seq_tup_read, COALESCE(idx_scan, 0), n_live_tup
+             FROM pg_stat_user_tables
+             WHERE schemaname = 'public'
+             ORDER BY seq_tup_read DESC",
+            &[],
+        )
+        .context("Failed to read table scan statistics")?
+        .iter()
+        .map(|row| TableScans {
+            table: row.get(0),
+            seq_scan: row.get(1),
+            seq_tup_read: row.get(2),
+            idx_scan: row.get(3),
+            live_rows: row.get(4),
+        })
+        .collect();
+
+    let slow_statements = slow_statements(client, &tables)?;
+    let stats_since = client
+        .query_one(
+            "SELECT to_char(stats_reset, 'YYYY-MM-DD HH24:MI') FROM pg_stat_database WHERE datname = current_database()",
+            &[],
+        )?
+        .get(0);
+
+    Ok(IndexReport {
+        stats_since,
+        missing,
+        unused,
+        seq_scan_heavy: tables.into_iter().filter(TableScans::is_seq_scan_heavy).collect(),
+        slow_statements,
+    })
+}
+
+/// Slowest statements (by mean time) touching the pipeline's tables
+fn slow_statements(client: &mut Client, tables: &[TableScans]) -> Result<Option<Vec<SlowStatement>>> {
+    let installed = client
+        .query_opt("SELECT 1 FROM pg_extension WHERE extname = 'pg_stat_statements'", &[])?
+        .is_some();
+    if !installed {
+        return Ok(None);
+    }
+
+    let statements = client
+        .query(
+            "SELECT query, calls, mean_exec_time
+             FROM pg_stat_statements
+             WHERE dbid = (SELECT oid FROM pg_database WHERE datname = current_database())
+             ORDER BY mean_exec_time DESC
+             LIMIT 500",
+            &[],
+        )
+        .context("Failed to read pg_stat_statements")?
+        .iter()
+        .map(|row| SlowStatement { query: row.get(0), calls: row.get(1), mean_ms: row.get(2) })
+        .filter(|s| tables.iter().any(|t| mentions_table(&s.query, &t.table)))
+        .take(SLOW_STATEMENT_LIMIT)
+        .collect();
+
+    Ok(Some(statements))
+}
+
+/// Log the report with the statements that would apply each recommendation
+pub fn log_report(report: &IndexReport) {
+    info!("🔍 Foreign keys without an index: {}", report.missing.len());
+    for missing in &report.missing {
+        info!("  {} ({}) via {}", missing.table, missing.columns.join(", "), missing.constraint);
+        info!("    {}", missing.create_sql());
+    }
+
+    info!(
+        "🔍 Unused indexes on tables over {} rows (no scans since {}): {}",
+        MIN_FLAGGED_ROWS,
+        report.stats_since.as_deref().unwrap_or("statistics were reset"),
+        report.unused.len()
+    );
+    for unused in &report.unused {
+        info!("  {}.{} ({} KB)", unused.table, unused.index, unused.size_bytes / 1024);
+        info!("    DROP INDEX CONCURRENTLY IF EXISTS {};", unused.index);
+    }
+
+    info!("🔍 Tables read mostly by full scans: {}", report.seq_scan_heavy.len());
+    for scans in &report.seq_scan_heavy {
+        info!(
+            "  {}: {} seq scans ({} rows read) vs {} index scans, {} live rows",
+            scans.table, scans.seq_scan, scans.seq_tup_read, scans.idx_scan, scans.live_rows
+        );
+    }
+
+    match &report.slow_statements {
+        None => warn!("⚠️  pg_stat_statements is not installed; skipping slow statements (CREATE EXTENSION pg_stat_statements)"),
+        Some(statements) => {
+            info!("🔍 Slowest pipeline statements:");
+            for statement in statements {
+                let query: String = statement.query.split_whitespace().collect::<Vec<_>>().join(" ");
+                info!("  {:>9.1} ms avg × {:<7} {:.160}", statement.mean_ms, statement.calls, query);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_sql_names_index_after_columns() {
+        let missing = MissingIndex {
+            table: "game_stats".to_string(),
+            columns: vec!["player_id".to_string()],
+            constraint: "game_stats_player_id_fkey".to_string(),
+        };
+        assert_eq!(
+            missing.create_sql(),
+            "CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_game_stats_player_id ON game_stats (player_id);"
+        );
+    }
+
+    #[test]
+    fn test_seq_scan_heavy() {
+        let scans = TableScans {
+            table: "game_stats".to_string(),
+            seq_scan: 40,
+            seq_tup_read: 4_000_000,
+            idx_scan: 3,
+            live_rows: 100_000,
+        };
+        assert!(scans.is_seq_scan_heavy());
+        assert!(!TableScans { live_rows: 500, ..scans.clone() }.is_seq_scan_heavy());
+        assert!(!TableScans { idx_scan: 900, ..scans.clone() }.is_seq_scan_heavy());
+        // Scans that stop early (LIMIT) don't count
+        assert!(!TableScans { seq_tup_read: 400, ..scans }.is_seq_scan_heavy());
+    }
+
+    #[test]
+    fn test_mentions_table_whole_word() {
+        assert!(mentions_table("SELECT * FROM games WHERE season = $1", "games"));
+        assert!(mentions_table("UPDATE GAMES SET status = $1", "games"));
+        assert!(!mentions_table("SELECT * FROM game_stats", "games"));
+        assert!(!mentions_table("SELECT * FROM team_games", "games"));
+    }
+}
@@ -0,0 +1,188 @@
+use anyhow::{Context, Result};
+use log::{info, warn};
+use postgres::Client;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::transformer;
+
+/// Offending IDs listed per check
+const MAX_IDS: i64 = 100;
+
+/// A referential integrity check: the rows it selects are violations, each as
+/// an ID to report plus the total count
+struct Check {
+    name: &'static str,
+    description: &'static str,
+    sql: &'static str,
+}
+
+/// Foreign keys on these columns make most violations impossible, but schemas
+/// restored without constraints, or loaded with triggers disabled, can have them
+const CHECKS: &[Check] = &[
+    Check {
+        name: "orphaned_game_stats",
+        description: "game_stats rows whose player is not in players",
+        sql: "SELECT s.id::text, COUNT(*) OVER ()
+              FROM game_stats s
+              WHERE NOT EXISTS (SELECT 1 FROM players p WHERE p.id = s.player_id)
+              ORDER BY s.season DESC, s.week DESC, s.id
+              LIMIT $1",
+    },
+    Check {
+        name: "players_without_team",
+        description: "players with no team_id",
+        sql: "SELECT p.id::text || ' (' || p.name || ')', COUNT(*) OVER ()
+              FROM players p
+              WHERE p.team_id IS NULL
+              ORDER BY p.name
+              LIMIT $1",
+    },
+    Check {
+        name: "games_missing_teams",
+        description: "games whose home or away team is not in teams",
+        sql: "SELECT g.nfl_game_id, COUNT(*) OVER ()
+              FROM games g
+              WHERE NOT EXISTS (SELECT 1 FROM teams t WHERE t.id = g.home_team_id)
+                 OR NOT EXISTS (SELECT 1 FROM teams t WHERE t.id = g.away_team_id)
+              ORDER BY g.nfl_game_id
+              LIMIT $1",
+    },
+];
+
+/// What one check found: the total and up to 100 offending IDs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Violations {
+    pub check: &'static str,
+    pub description: &'static str,
+    pub count: i64,
+    pub ids: Vec<String>,
+}
+
+/// What `repair` changed
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct Repairs {
+    /// Orphaned stat lines deleted
+    pub deleted_game_stats: u64,
+    /// Players given their most recent team
+    pub resolved_players: u64,
+    /// Games whose teams were looked up again from the game ID
+    pub resolved_games: u64,
+    /// Games left as they were: their game ID names no known team
+    pub unresolved_games: Vec<String>,
+}
+
+pub fn check(client: &mut Client) -> Result<Vec<Violations>> {
+    CHECKS
+        .iter()
+        .map(|check| {
+            let rows = client.query(check.sql, &[&MAX_IDS]).with_context(|| format!("Failed to run {}", check.name))?;
+            Ok(Violations {
+                check: check.name,
+                description: check.description,
+                count: rows.first().map_or(0, |row| row.get(1)),
+                ids: rows.iter().map(|row| row.get(0)).collect(),
+            })
+        })
+        .collect()
+}
+
+pub fn log_report(violations: &[Violations]) {
+    for found in violations {
+        match found.count {
+            0 => info!("  ✅ {}: none", found.check),
+            count => {
+                warn!("  ⚠️  {}: {} {}", found.check, count, found.description);
+                let more = count - found.ids.len() as i64;
+                warn!("    {}{}", found.ids.join(", "), if more > 0 { format!(" ... and {} more", more) } else { String::new() });
+            }
+        }
+    }
+}
+
+/// Fix what can be fixed, in one transaction:
+/// - orphaned stat lines are deleted (there is no player to attach them to)
+/// - players without a team get their current team from `player_team_history`,
+///   or else the team of their latest stat line; the rest (free agents, or never
+///   on a known team) stay without one
+/// - games missing a team look both teams up again from the abbreviations in
+///   their game ID (`2024_01_BAL_KC`). Games that still don't resolve are left
+///   for a schedule re-import, since deleting one would orphan its stats.
+pub fn repair(client: &mut Client) -> Result<Repairs> {
+    let mut tx = client.transaction()?;
+    let mut repairs = Repairs {
+        deleted_game_stats: tx.execute(
+            "DELETE FROM game_stats s WHERE NOT EXISTS (SELECT 1 FROM players p WHERE p.id = s.player_id)",
+            &[],
+        )?,
+        ..Repairs::default()
+    };
+
+    repairs.resolved_players = tx.execute(
+        "UPDATE players p SET team_id = latest.team_id
+         FROM (
+             SELECT DISTINCT ON (player_id) player_id, team_id
+             FROM (
+                 SELECT player_id, team_id, is_current, season_start AS season, 0 AS week FROM player_team_history
+                 UNION ALL
+                 SELECT player_id, team_id, false, season, week FROM game_stats WHERE team_id IS NOT NULL
+             ) known
+             WHERE EXISTS (SELECT 1 FROM teams t WHERE t.id = known.team_id)
+             ORDER BY player_id, is_current DESC, season DESC, week DESC
+         ) latest
+         WHERE p.team_id IS NULL AND p.id = latest.player_id",
+        &[],
+    )?;
+
+    let games = tx.query(
+        "SELECT g.id, g.nfl_game_id FROM games g
+         WHERE NOT EXISTS (SELECT 1 FROM teams t WHERE t.id = g.home_team_id)
+            OR NOT EXISTS (SELECT 1 FROM teams t WHERE t.id = g.away_team_id)",
+        &[],
+    )?;
+    for game in games {
+        let (id, nfl_game_id): (Uuid, String) = (game.get(0), game.get(1));
+        let resolved = match game_teams(&nfl_game_id) {
+            Some((away, home)) => tx.execute(
+                "UPDATE games SET
+                     away_team_id = (SELECT id FROM teams WHERE abbreviation = $2),
+                     home_team_id = (SELECT id FROM teams WHERE abbreviation = $3)
+                 WHERE id = $1
+                   AND EXISTS (SELECT 1 FROM teams WHERE abbreviation = $2)
+                   AND EXISTS (SELECT 1 FROM teams WHERE abbreviation = $3)",
+                &[&id, &away, &home],
+            )?,
+            None => 0,
+        };
+        match resolved {
+            0 => repairs.unresolved_games.push(nfl_game_id),
+            _ => repairs.resolved_games += 1,
+        }
+    }
+
+    tx.commit().context("Failed to commit integrity repairs")?;
+    Ok(repairs)
+}
+
+/// Away and home team of an nflverse game ID (`{season}_{week}_{away}_{home}`),
+/// with relocated teams mapped to their current abbreviation
+fn game_teams(nfl_game_id: &str) -> Option<(String, String)> {
+    match nfl_game_id.split('_').collect::<Vec<_>>()[..] {
+        [_, _, away, home] if !away.is_empty() && !home.is_empty() => {
+            Some((transformer::normalize_team_abbr(away), transformer::normalize_team_abbr(home)))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game_teams_from_game_id() {
+        assert_eq!(game_teams("2024_01_BAL_KC"), Some(("BAL".to_string(), "KC".to_string())));
+        assert_eq!(game_teams("2015_10_SD_OAK"), Some(("LAC".to_string(), "LV".to_string())));
+        assert_eq!(game_teams("401671789"), None);
+    }
+}
@@ -0,0 +1,186 @@
+use anyhow::{anyhow, Result};
+
+use crate::config::Config;
+
+/// Update every six hours, catching Sunday's games by early Monday
+const DEFAULT_UPDATE_SCHEDULE: &str = "0 */6 * * *";
+/// nflverse and ESPN kickoffs are Eastern, so schedules are too
+const SCHEDULE_TIME_ZONE: &str = "America/New_York";
+/// Headroom over `--memory-budget-mb` for the binary, connections and parsing
+const MEMORY_OVERHEAD_MB: usize = 256;
+const DEFAULT_MEMORY_MB: usize = 1024;
+
+/// One mode to deploy: a CronJob when it has a schedule, otherwise a Job run once
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobSpec {
+    pub mode: String,
+    pub schedule: Option<String>,
+}
+
+/// A one-off migration Job and an update CronJob
+pub fn default_jobs() -> Vec<JobSpec> {
+    vec![
+        JobSpec { mode: "migrate".to_string(), schedule: None },
+        JobSpec { mode: "update".to_string(), schedule: Some(DEFAULT_UPDATE_SCHEDULE.to_string()) },
+    ]
+}
+
+/// Parse `--jobs`: entries separated by semicolons, each a mode with an
+/// optional cron schedule, e.g. `migrate;update=0 */6 * * *`
+pub fn parse_jobs(value: &str) -> Result<Vec<JobSpec>> {
+    let mut jobs = Vec::new();
+    for entry in value.split(';').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let (mode, schedule) = match entry.split_once('=') {
+            Some((mode, schedule)) => (mode.trim(), Some(schedule.trim().to_string())),
+            None => (entry, None),
+        };
+        if mode.is_empty() || !mode.chars().all(|c| c.is_ascii_lowercase() || c == '-') {
+            return Err(anyhow!("Invalid --jobs entry: {} (expected mode or mode=cron schedule)", entry));
+        }
+        if matches!(mode, "daemon" | "live" | "generate-k8s") {
+            return Err(anyhow!("--jobs: {} mode can't run as a Job (daemon and live run continuously; use a Deployment)", mode));
+        }
+        if let Some(schedule) = &schedule {
+            if schedule.split_whitespace().count() != 5 {
+                return Err(anyhow!("Invalid --jobs schedule for {}: {} (expected five cron fields)", mode, schedule));
+            }
+        }
+        jobs.push(JobSpec { mode: mode.to_string(), schedule });
+    }
+    if jobs.is_empty() {
+        return Err(anyhow!("--jobs lists no modes"));
+    }
+    Ok(jobs)
+}
+
+/// YAML scalar; JSON strings are valid double-quoted YAML
+fn quote(value: &str) -> String {
+    serde_json::to_string(value).expect("strings serialize")
+}
+
+/// Container spec shared by every Job, at `indent` spaces
+fn container(config: &Config, mode: &str, indent: usize) -> String {
+    let pad = " ".repeat(indent);
+    let mut yaml = format!("{pad}- name: pipeline\n{pad}  image: {}\n", quote(&config.k8s_image));
+
+    let args: Vec<String> = ["--mode".to_string(), mode.to_string()].into_iter().chain(config.job_args()).collect();
+    yaml += &format!("{pad}  args: [{}]\n", args.iter().map(|arg| quote(arg)).collect::<Vec<_>>().join(", "));
+
+    yaml += &format!("{pad}  env:\n");
+    let secret = |name: &str| {
+        format!(
+            "{pad}    - name: {name}\n{pad}      valueFrom:\n{pad}        secretKeyRef: {{name: {}, key: {name}}}\n",
+            quote(&config.k8s_secret)
+        )
+    };
+    yaml += &secret("DATABASE_URL");
+    if !config.fanout_database_urls.is_empty() {
+        yaml += &secret("FANOUT_DATABASE_URLS");
+    }
+    if config.archive.as_deref().is_some_and(|destination| destination.starts_with("s3://")) {
+        yaml += &secret("AWS_ACCESS_KEY_ID");
+        yaml += &secret("AWS_SECRET_ACCESS_KEY");
+    }
+    if config.odds_api_key.is_some() {
+        yaml += &secret("ODDS_API_KEY");
+    }
+    yaml += &format!("{pad}    - {{name: RUST_LOG, value: \"info\"}}\n");
+    for (name, value) in config.job_env() {
+        yaml += &format!("{pad}    - {{name: {name}, value: {}}}\n", quote(&value));
+    }
+
+    let memory_mb = config.memory_budget_mb.map_or(DEFAULT_MEMORY_MB, |mb| mb + MEMORY_OVERHEAD_MB);
+    yaml += &format!(
+        "{pad}  resources:\n{pad}    requests: {{cpu: \"{}m\", memory: \"{memory_mb}Mi\"}}\n{pad}    limits: {{memory: \"{memory_mb}Mi\"}}\n",
+        config.writers * 500
+    );
+    yaml
+}
+
+fn metadata(config: &Config, name: &str, mode: &str) -> String {
+    let mut yaml = format!("metadata:\n  name: {}\n", quote(name));
+    if let Some(namespace) = &config.k8s_namespace {
+        yaml += &format!("  namespace: {}\n", quote(namespace));
+    }
+    yaml += &format!(
+        "  labels: {{app.kubernetes.io/name: nfl-data-pipeline, app.kubernetes.io/component: {}}}\n",
+        quote(mode)
+    );
+    yaml
+}
+
+/// Job spec at `indent` spaces. The pipeline retries batches itself, so a
+/// failed pod is retried once.
+fn job_spec(config: &Config, mode: &str, indent: usize) -> String {
+    let pad = " ".repeat(indent);
+    format!(
+        "{pad}backoffLimit: 1\n{pad}template:\n{pad}  spec:\n{pad}    restartPolicy: Never\n{pad}    containers:\n{}",
+        container(config, mode, indent + 6)
+    )
+}
+
+/// Manifests for every configured job, as one multi-document YAML stream
+pub fn render(config: &Config) -> String {
+    let documents: Vec<String> = config
+        .k8s_jobs
+        .iter()
+        .map(|job| {
+            let name = format!("nfl-pipeline-{}", job.mode);
+            match &job.schedule {
+                Some(schedule) => format!(
+                    "apiVersion: batch/v1\nkind: CronJob\n{}spec:\n  schedule: {}\n  timeZone: {}\n  concurrencyPolicy: Forbid\n  successfulJobsHistoryLimit: 3\n  failedJobsHistoryLimit: 3\n  jobTemplate:\n    spec:\n{}",
+                    metadata(config, &name, &job.mode),
+                    quote(schedule),
+                    quote(SCHEDULE_TIME_ZONE),
+                    job_spec(config, &job.mode, 6)
+                ),
+                None => format!(
+                    "apiVersion: batch/v1\nkind: Job\n{}spec:\n{}",
+                    metadata(config, &name, &job.mode),
+                    job_spec(config, &job.mode, 2)
+                ),
+            }
+        })
+        .collect();
+    format!(
+        "# Generated by nfl-data-pipeline {} --mode generate-k8s\n---\n{}",
+        env!("CARGO_PKG_VERSION"),
+        documents.join("---\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_jobs() {
+        let jobs = parse_jobs("migrate; update=0 */6 * * * ;injuries=30 13 * * 3").unwrap();
+        assert_eq!(jobs.len(), 3);
+        assert_eq!(jobs[0], JobSpec { mode: "migrate".to_string(), schedule: None });
+        assert_eq!(jobs[1].schedule.as_deref(), Some("0 */6 * * *"));
+        assert!(parse_jobs("daemon=0 * * * *").is_err());
+        assert!(parse_jobs("update=hourly").is_err());
+        assert!(parse_jobs(" ; ").is_err());
+    }
+
+    #[test]
+    fn test_render_manifests() {
+        let args: Vec<String> = ["nfl-data-pipeline", "--mode", "generate-k8s", "--namespace", "nfl", "--load-method", "copy",
+                                 "--jobs", "migrate;update=0 */6 * * *"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let config = Config::from_args(&args, |key| (key == "PIPELINE_WRITERS").then(|| "2".to_string())).unwrap();
+        let yaml = render(&config);
+
+        assert_eq!(yaml.matches("\n---\n").count(), 2);
+        assert!(yaml.contains("kind: Job\nmetadata:\n  name: \"nfl-pipeline-migrate\"\n  namespace: \"nfl\""));
+        assert!(yaml.contains("kind: CronJob"));
+        assert!(yaml.contains("  schedule: \"0 */6 * * *\"\n  timeZone: \"America/New_York\""));
+        assert!(yaml.contains("args: [\"--mode\", \"update\", \"--load-method\", \"copy\"]"));
+        assert!(yaml.contains("- {name: PIPELINE_WRITERS, value: \"2\"}"));
+        assert!(yaml.contains("secretKeyRef: {name: \"nfl-data-pipeline\", key: DATABASE_URL}"));
+        assert!(yaml.contains("requests: {cpu: \"1000m\", memory: \"1024Mi\"}"));
+    }
+}
@@ -0,0 +1,347 @@
+//! NFL data pipeline: downloads nflverse (and other) sources, validates them and
+//! upserts them into PostgreSQL. The `nfl-data-pipeline` binary is a thin CLI over
+//! this crate; services can embed it to trigger imports in-process:
+//!
+//! ```no_run
+//! use nfl_data_pipeline::{Config, DataPipeline};
+//!
+//! let args: Vec<String> = ["--mode", "year", "--year", "2024"].iter().map(|s| s.to_string()).collect();
+//! let config = Config::from_args(&args, |name| std::env::var(name).ok())?;
+//! let mut pipeline = DataPipeline::new(config)?;
+//! pipeline.import_year(2024)?;
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+use anyhow::{anyhow, Result};
+use log::info;
+use std::time::Duration;
+
+mod aggregates;
+mod api;
+mod api_keys;
+mod archive;
+pub mod arrow_export;
+mod backpressure;
+mod betting;
+mod bulk;
+mod cache;
+mod calendar;
+mod change_log;
+mod completeness;
+pub mod config;
+mod corrections;
+mod current_week;
+mod daemon;
+pub mod data_source;
+pub mod datasets;
+mod dead_letter;
+mod diff;
+pub mod downloader;
+mod drift;
+mod dry_run;
+mod efficiency;
+mod elo;
+mod espn;
+mod espn_ids;
+pub mod export;
+mod fanout;
+mod fantasy;
+mod game_status;
+mod graphql;
+mod history;
+mod indexes;
+mod integrity;
+mod k8s;
+mod lineage;
+mod live;
+pub mod logging;
+mod materialize;
+mod metrics;
+mod migrations;
+mod news;
+mod notify;
+mod odds;
+pub mod parquet_sink;
+pub mod parser;
+mod precedence;
+mod purge;
+mod transformer;
+mod tuning;
+pub mod database;
+mod ratings;
+mod raw_query;
+mod resolution;
+mod reports;
+mod roles;
+pub mod roster_moves;
+mod row_hashes;
+mod sheets;
+pub mod sink;
+mod sleeper;
+mod sources;
+mod spill;
+mod splits;
+mod sql_text;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+mod standings;
+mod status;
+pub mod sync;
+mod telemetry;
+mod usage;
+pub mod validator;
+mod value;
+mod views;
+mod weather;
+mod websocket;
+mod writers;
+
+pub use arrow_export::ArrowExport;
+pub use config::Config;
+pub use data_source::DataSource;
+pub use database::Database;
+pub use downloader::Downloader;
+pub use roster_moves::{diff_rosters, RosterDiff, RosterSnapshot};
+pub use sync::DataPipeline;
+pub use validator::DataValidator;
+
+/// Run `config.mode`, against every target database for fanned-out modes. The
+/// pipeline is blocking (sync Postgres and HTTP clients); call it from
+/// `tokio::task::spawn_blocking` inside an async service.
+pub fn run(config: Config) -> Result<()> {
+    if let Some(addr) = &config.metrics_addr {
+        metrics::serve(addr)?;
+        info!("📈 Metrics on http://{}/metrics", addr);
+    }
+
+    // Runs without a database connection
+    if config.mode == "query-raw" {
+        info!("🔎 Raw query over {} source files", config.year);
+        return raw_query::run(&config);
+    }
+    if config.sink != config::Sink::Postgres {
+        info!("🗃️  Importing into --sink {}", config.sink.as_str());
+        return sink::run(&config);
+    }
+    if config.mode == "generate-k8s" {
+        info!("☸️  Kubernetes manifests for {} jobs", config.k8s_jobs.len());
+        print!("{}", k8s::render(&config));
+        return Ok(());
+    }
+
+    if config.mode == "daemon" && !config.fanout_database_urls.is_empty() {
+        return Err(anyhow!("Daemon mode writes to one database; run a daemon per target instead of FANOUT_DATABASE_URLS"));
+    }
+
+    // Runs before the pipeline loads its ID caches, which need the schema
+    if config.mode == "migrate" {
+        return fanout::for_each_target(&config, |config| {
+            let mut database = Database::connect(&config.database_url, 1)?;
+            let count = migrations::run(database.get_client(), config.baseline, config.dry_run)?;
+            match config.dry_run {
+                true => info!("🧪 Dry run: {} migrations pending", count),
+                false => info!("✅ {} migrations applied", count),
+            }
+            Ok(())
+        });
+    }
+
+    let result = fanout::for_each_target(&config, run_pipeline);
+    if let Some(url) = &config.pushgateway_url {
+        metrics::push(url, &config.mode);
+    }
+    result?;
+
+    info!("✅ Pipeline completed successfully!");
+    Ok(())
+}
+
+/// Run the configured mode against one database
+pub fn run_pipeline(config: Config) -> Result<()> {
+    let notifier = notify::Notifier::from_config(&config)?;
+    let mut pipeline = match DataPipeline::new(config) {
+        Ok(pipeline) => pipeline,
+        Err(e) => {
+            if let Some(notifier) = notifier {
+                notifier.run_failed(&e);
+            }
+            return Err(e);
+        }
+    };
+
+    // Execute based on mode
+    let result = run_mode(&mut pipeline);
+    if let Some(url) = &pipeline.config.telemetry_url {
+        telemetry::send(url, &pipeline.telemetry_report(&result));
+    }
+    pipeline.notify_finished(&result);
+    if pipeline.config.dry_run {
+        pipeline.report_dry_run()?;
+    }
+    if pipeline.is_simulation() {
+        pipeline.finish_simulation()?;
+    }
+    result
+}
+
+/// Dispatch `pipeline.config.mode` to the pipeline
+pub fn run_mode(pipeline: &mut DataPipeline) -> Result<()> {
+    match pipeline.config.mode.as_str() {
+        "full" => {
+            info!("📥 Full import: {} seasons", pipeline.config.end_year - pipeline.config.start_year + 1);
+            pipeline.run_full_import()?;
+        },
+        "year" => {
+            info!("📅 Single year import: {}", pipeline.config.year);
+            pipeline.import_year(pipeline.config.year)?;
+        },
+        "update" => {
+            info!("🔄 Incremental update");
+            pipeline.run_update()?;
+        },
+        "daemon" => {
+            let schedule = daemon::Schedule {
+                game_day_interval: Duration::from_secs(pipeline.config.game_day_interval_mins * 60),
+                idle_interval: Duration::from_secs(pipeline.config.idle_interval_mins * 60),
+            };
+            daemon::run(pipeline, schedule)?;
+        },
+        "live" => {
+            let interval = Duration::from_secs(pipeline.config.live_interval_secs);
+            let idle = Duration::from_secs(pipeline.config.idle_interval_mins * 60);
+            let url = pipeline.config.scoreboard_url.clone();
+            live::run(pipeline, &url, interval, idle)?;
+        },
+        "espn" => {
+            info!("🏈 ESPN schedule, box scores and rosters: {}", pipeline.config.year);
+            pipeline.import_espn(pipeline.config.year)?;
+        },
+        "game-news" => {
+            info!("📰 ESPN game recaps and headlines: {}", pipeline.config.year);
+            pipeline.import_espn_game_news(pipeline.config.year)?;
+        },
+        "player-news" => {
+            info!("📰 Player news: {}", pipeline.config.news_feed_url);
+            pipeline.import_player_news()?;
+        },
+        "odds" => {
+            info!("📈 Betting odds: {}", pipeline.config.odds_api_url);
+            pipeline.import_odds()?;
+        },
+        "sleeper" => {
+            info!("🏆 Sleeper league: {}", pipeline.config.sleeper_league.as_deref().unwrap_or("(none)"));
+            pipeline.import_sleeper_league()?;
+        },
+        "espn-ids" => {
+            info!("🔗 ESPN athlete ID backfill");
+            pipeline.link_espn_ids()?;
+        },
+        "contracts" => {
+            info!("💰 Contract history and value metrics");
+            pipeline.import_contracts()?;
+        },
+        "seed-teams" => {
+            info!("🏟️  Seeding teams");
+            pipeline.seed_teams()?;
+        },
+        "fantasy" => {
+            info!("🏆 Fantasy points: {}", pipeline.config.year);
+            pipeline.run_fantasy(pipeline.config.year)?;
+        },
+        "aggregate" => {
+            info!("📈 Season and career aggregates");
+            pipeline.run_aggregate()?;
+        },
+        "draft" => {
+            info!("🎓 Draft picks and combine import");
+            pipeline.import_draft()?;
+        },
+        "sheets" => {
+            let season = pipeline.current_week()?.season;
+            info!("📊 Google Sheets export: {}", season);
+            pipeline.push_sheets(season)?;
+        },
+        "history" => {
+            info!("🕓 Row history: {}", pipeline.config.action.as_deref().unwrap_or("enable"));
+            pipeline.run_history()?;
+        },
+        "injuries" => {
+            info!("🏥 Injury report sync");
+            pipeline.run_injuries()?;
+        },
+        "corrections" => {
+            info!("🩹 Stat correction sweep");
+            pipeline.run_correction_sweep()?;
+        },
+        "current-week" => {
+            pipeline.print_current_week()?;
+        },
+        "status" => {
+            pipeline.print_status()?;
+        },
+        "calendar" => {
+            info!("🗓️  Team calendars: {}", pipeline.config.year);
+            pipeline.export_calendars(pipeline.config.year)?;
+        },
+        "indexes" => {
+            info!("🔍 Index recommendations");
+            pipeline.report_indexes()?;
+        },
+        "integrity" => {
+            info!("🔗 Referential integrity: {}", pipeline.config.action.as_deref().unwrap_or("check"));
+            pipeline.run_integrity()?;
+        },
+        "diff" => {
+            info!("🔀 Diffing {} source files against the database", pipeline.config.year);
+            pipeline.run_diff()?;
+        },
+        "serve" => {
+            pipeline.run_serve()?;
+        },
+        "api-keys" => {
+            info!("🔑 API keys: {}", pipeline.config.action.as_deref().unwrap_or("list"));
+            pipeline.run_api_keys()?;
+        },
+        "export" => {
+            info!("📤 Exporting {}", pipeline.config.entity.as_deref().unwrap_or("?"));
+            pipeline.run_export()?;
+        },
+        "purge" => {
+            info!("🗑️  Purging {} of {}", pipeline.config.dataset.as_deref().unwrap_or("?"), pipeline.config.year);
+            pipeline.run_purge()?;
+        },
+        "views" => {
+            info!("🪟 View pack: {}", pipeline.config.action.as_deref().unwrap_or("install"));
+            pipeline.run_views()?;
+        },
+        "sources" => {
+            info!("📜 Data sources and attribution");
+            pipeline.report_sources()?;
+        },
+        "lineage" => {
+            info!("🧬 Data lineage");
+            pipeline.trace_lineage()?;
+        },
+        "replay" => {
+            info!("📮 Dead-letter replay");
+            pipeline.replay_dead_letters()?;
+        },
+        "changes" => {
+            info!("📝 Change log");
+            pipeline.report_changes()?;
+        },
+        "unresolved" => {
+            info!("🧩 Unresolved references");
+            pipeline.report_unresolved()?;
+        },
+        "validate" => {
+            info!("✅ Validating existing data");
+            pipeline.validate_data()?;
+        },
+        _ => {
+            // Returned rather than exiting so a simulation schema is still dropped
+            return Err(anyhow!("Invalid mode: {}", pipeline.config.mode));
+        }
+    }
+    Ok(())
+}
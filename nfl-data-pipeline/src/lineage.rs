@@ -0,0 +1,188 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use csv::StringRecord;
+use log::info;
+use postgres::Client;
+use sha2::{Digest, Sha256};
+
+/// Traced rows listed by `--mode lineage`
+const TRACE_LIMIT: i64 = 50;
+
+/// One source file a stage read: where it came from and when it was downloaded
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceFile {
+    pub dataset: &'static str,
+    pub season: i32,
+    /// Download URL, or the path under `--source-dir`
+    pub url: String,
+    /// GitHub release the file was published under (nflverse's `player_stats`, `pbp`, ...)
+    pub release_tag: Option<String>,
+    /// When the file was downloaded: the cached copy's or local file's modification
+    /// time when it wasn't downloaded this run
+    pub downloaded_at: DateTime<Utc>,
+    /// SHA-256 of the file, when the stage fingerprinted it
+    pub sha256: Option<String>,
+}
+
+/// Release tag in a GitHub release asset URL
+pub fn release_tag(url: &str) -> Option<String> {
+    let (_, rest) = url.split_once("/releases/download/")?;
+    rest.split('/').next().filter(|tag| !tag.is_empty()).map(String::from)
+}
+
+/// A row's key in `data_lineage`: its key columns joined with `/` (a column may
+/// name alternatives as `a|b`), or its 1-based row number in the file when the
+/// dataset has no key
+pub fn row_key(record: &StringRecord, header: &StringRecord, key_columns: &[&str], row_number: usize) -> String {
+    if key_columns.is_empty() {
+        return row_number.to_string();
+    }
+    key_columns
+        .iter()
+        .map(|column| {
+            column
+                .split('|')
+                .find_map(|name| header.iter().position(|h| h == name))
+                .and_then(|i| record.get(i))
+                .unwrap_or("")
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// SHA-256 of a row's fields as read, before parsing
+pub fn row_hash(record: &StringRecord) -> String {
+    let mut hasher = Sha256::new();
+    for (i, field) in record.iter().enumerate() {
+        if i > 0 {
+            hasher.update([0x1f]);
+        }
+        hasher.update(field.as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Record a source file read by a run; returns its `source_files` ID
+pub fn record_file(client: &mut Client, run_started_at: NaiveDateTime, file: &SourceFile) -> Result<i64> {
+    let row = client
+        .query_one(
+            "INSERT INTO source_files (run_started_at, dataset, season, url, release_tag, downloaded_at, sha256)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             RETURNING id",
+            &[&run_started_at, &file.dataset, &file.season, &file.url, &file.release_tag, &file.downloaded_at, &file.sha256],
+        )
+        .with_context(|| format!("Failed to record {} source file", file.dataset))?;
+    Ok(row.get(0))
+}
+
+/// Point each (row key, row hash) at the file that produced it. A key read
+/// again keeps its first import time and moves to the newer file.
+pub fn record_rows(client: &mut Client, file_id: i64, dataset: &str, rows: &[(String, String)]) -> Result<u64> {
+    let (keys, hashes): (Vec<&str>, Vec<&str>) = rows.iter().map(|(key, hash)| (key.as_str(), hash.as_str())).unzip();
+    client
+        .execute(
+            "INSERT INTO data_lineage (dataset, row_key, source_file_id, row_hash, first_seen_at, updated_at)
+             SELECT DISTINCT ON (s.row_key) $2, s.row_key, $1, s.row_hash, NOW(), NOW()
+             FROM UNNEST($3::text[], $4::text[]) WITH ORDINALITY AS s (row_key, row_hash, ord)
+             ORDER BY s.row_key, s.ord DESC
+             ON CONFLICT (dataset, row_key) DO UPDATE SET
+                 source_file_id = EXCLUDED.source_file_id,
+                 previous_row_hash = CASE WHEN data_lineage.row_hash IS DISTINCT FROM EXCLUDED.row_hash
+                                          THEN data_lineage.row_hash ELSE data_lineage.previous_row_hash END,
+                 row_hash = EXCLUDED.row_hash,
+                 updated_at = NOW()",
+            &[&file_id, &dataset, &keys, &hashes],
+        )
+        .with_context(|| format!("Failed to record {} lineage", dataset))
+}
+
+/// A row traced back to the file it was last read from
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace {
+    pub dataset: String,
+    pub row_key: String,
+    pub row_hash: String,
+    /// Whether the row changed the last time it was read
+    pub changed: bool,
+    pub url: String,
+    pub release_tag: Option<String>,
+    pub downloaded_at: String,
+    pub sha256: Option<String>,
+    pub run_started_at: String,
+}
+
+/// `--mode lineage`: rows whose key starts with `key_prefix`, optionally in one dataset
+pub fn trace(client: &mut Client, dataset: Option<&str>, key_prefix: &str) -> Result<Vec<Trace>> {
+    let rows = client
+        .query(
+            "SELECT l.dataset, l.row_key, l.row_hash, l.previous_row_hash IS NOT NULL, f.url, f.release_tag,
+                    to_char(f.downloaded_at, 'YYYY-MM-DD HH24:MI:SS TZ'), f.sha256,
+                    to_char(f.run_started_at, 'YYYY-MM-DD HH24:MI')
+             FROM data_lineage l
+             JOIN source_files f ON f.id = l.source_file_id
+             WHERE ($1::text IS NULL OR l.dataset = $1) AND starts_with(l.row_key, $2)
+             ORDER BY l.dataset, l.row_key
+             LIMIT $3",
+            &[&dataset, &key_prefix, &TRACE_LIMIT],
+        )
+        .context("Failed to load lineage")?;
+    Ok(rows
+        .iter()
+        .map(|row| Trace {
+            dataset: row.get(0),
+            row_key: row.get(1),
+            row_hash: row.get(2),
+            changed: row.get(3),
+            url: row.get(4),
+            release_tag: row.get(5),
+            downloaded_at: row.get(6),
+            sha256: row.get(7),
+            run_started_at: row.get(8),
+        })
+        .collect())
+}
+
+pub fn log_trace(traces: &[Trace], key_prefix: &str) {
+    info!("🧬 {} rows traced for {}", traces.len(), key_prefix);
+    for trace in traces {
+        info!("  {} {} (row {}{})", trace.dataset, trace.row_key, &trace.row_hash[..12], if trace.changed { ", changed" } else { "" });
+        info!(
+            "    {}{} downloaded {}, read {}",
+            trace.url,
+            trace.release_tag.as_ref().map(|tag| format!(" [release {}]", tag)).unwrap_or_default(),
+            trace.downloaded_at,
+            trace.run_started_at
+        );
+        if let Some(sha256) = &trace.sha256 {
+            info!("    file sha256 {}", sha256);
+        }
+    }
+    if traces.len() as i64 == TRACE_LIMIT {
+        info!("  (first {} rows; narrow --key for more)", TRACE_LIMIT);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_row_keys_and_hashes() {
+        assert_eq!(
+            release_tag("https://github.com/nflverse/nflverse-data/releases/download/player_stats/player_stats_2024.csv").as_deref(),
+            Some("player_stats")
+        );
+        assert_eq!(release_tag("/data/player_stats_2024.csv"), None);
+
+        let header = StringRecord::from(vec!["player_id", "season", "week", "gsis_id"]);
+        let record = StringRecord::from(vec!["00-0033873", "2024", "3", "00-0033873"]);
+        assert_eq!(row_key(&record, &header, &["player_id", "season", "week"], 7), "00-0033873/2024/3");
+        assert_eq!(row_key(&record, &header, &["player_gsis_id|gsis_id", "week", "missing"], 7), "00-0033873/3/");
+        assert_eq!(row_key(&record, &header, &[], 7), "7");
+
+        let hash = row_hash(&record);
+        assert_eq!(hash.len(), 64);
+        assert_ne!(hash, row_hash(&StringRecord::from(vec!["00-0033873", "2024", "33", "00-0033873"])));
+        assert_ne!(row_hash(&StringRecord::from(vec!["a", "bc"])), row_hash(&StringRecord::from(vec!["ab", "c"])));
+    }
+}
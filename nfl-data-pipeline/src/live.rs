@@ -0,0 +1,339 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use log::{info, error};
+use postgres::Client;
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::daemon::sleep_until_shutdown;
+use crate::espn;
+use crate::game_status::{self, GameStatus};
+use crate::precedence::{Field, Merge, Precedence, Source};
+use crate::sync::DataPipeline;
+
+/// ESPN's public scoreboard for the current NFL week
+pub const SCOREBOARD_URL: &str = "https://site.api.espn.com/apis/site/v2/sports/football/nfl/scoreboard";
+/// Postgres channel each changed game is announced on, for serve mode's `/live` WebSocket
+pub const SCORES_CHANNEL: &str = "live_scores";
+/// Start polling this long before the next kickoff
+const PREGAME_LEAD: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Deserialize)]
+struct Scoreboard {
+    #[serde(default)]
+    events: Vec<Event>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Event {
+    id: String,
+    date: String,
+    #[serde(default)]
+    competitions: Vec<Competition>,
+    status: Status,
+}
+
+#[derive(Debug, Deserialize)]
+struct Competition {
+    #[serde(default)]
+    competitors: Vec<Competitor>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Competitor {
+    home_away: String,
+    team: CompetitorTeam,
+    #[serde(default)]
+    score: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompetitorTeam {
+    abbreviation: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Status {
+    #[serde(default)]
+    display_clock: Option<String>,
+    #[serde(default)]
+    period: Option<i32>,
+    #[serde(rename = "type")]
+    kind: StatusType,
+}
+
+#[derive(Debug, Deserialize)]
+struct StatusType {
+    /// pre, in or post
+    state: String,
+    #[serde(default)]
+    completed: bool,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+/// A game as the scoreboard reports it right now
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiveGame {
+    pub espn_id: String,
+    pub home_team: String,
+    pub away_team: String,
+    pub kickoff: DateTime<Utc>,
+    pub status: GameStatus,
+    /// ESPN's description, e.g. "Halftime" or "End of 3rd Quarter"
+    pub status_detail: Option<String>,
+    pub period: Option<i32>,
+    pub clock: Option<String>,
+    pub home_score: Option<i32>,
+    pub away_score: Option<i32>,
+}
+
+impl LiveGame {
+    pub fn in_progress(&self) -> bool {
+        self.status == GameStatus::InProgress
+    }
+}
+
+/// Parse a scoreboard response. Events without both teams or a readable
+/// kickoff time are skipped.
+pub fn parse_scoreboard(body: &str) -> Result<Vec<LiveGame>> {
+    let scoreboard: Scoreboard = serde_json::from_str(body).context("Invalid scoreboard response")?;
+
+    Ok(scoreboard
+        .events
+        .into_iter()
+        .filter_map(|event| {
+            let competitors = &event.competitions.first()?.competitors;
+            let side = |home_away: &str| competitors.iter().find(|c| c.home_away == home_away);
+            let (home, away) = (side("home")?, side("away")?);
+            let kickoff = espn::parse_kickoff(&event.date)?;
+
+            let status = if event.status.kind.completed {
+                GameStatus::Final
+            } else if event.status.kind.state == "in" {
+                GameStatus::InProgress
+            } else {
+                GameStatus::Scheduled
+            };
+            let score = |competitor: &Competitor| match status {
+                GameStatus::Scheduled => None,
+                _ => competitor.score.as_deref().and_then(|s| s.parse().ok()),
+            };
+
+            Some(LiveGame {
+                espn_id: event.id,
+                home_team: espn::team_abbr(&home.team.abbreviation),
+                away_team: espn::team_abbr(&away.team.abbreviation),
+                kickoff,
+                status,
+                status_detail: event.status.kind.description,
+                period: event.status.period.filter(|p| *p > 0 && status == GameStatus::InProgress),
+                clock: event.status.display_clock.filter(|_| status == GameStatus::InProgress),
+                home_score: score(home),
+                away_score: score(away),
+            })
+        })
+        .collect())
+}
+
+pub fn fetch_scoreboard(http: &reqwest::blocking::Client, url: &str) -> Result<Vec<LiveGame>> {
+    let body = http
+        .get(url)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.text())
+        .with_context(|| format!("Failed to fetch {}", url))?;
+    parse_scoreboard(&body)
+}
+
+/// Rows changed by one poll
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct LiveUpdate {
+    pub matched: usize,
+    pub changed: u64,
+}
+
+/// Write the scoreboard into `games`. A game matches on its ESPN ID (games
+/// imported from ESPN use it as `nfl_game_id`, others merged with ESPN's schedule
+/// keep it in `espn_game_id`) or on its teams with a kickoff
+/// within a day and a half, since schedule rows without a gametime hold midnight Eastern.
+/// Only rows whose status, period, clock or score changed are touched, and a
+/// game the scoreboard would move backwards (a final game shown as live) is left alone.
+/// Scores merge under `precedence`, which by default lets the scoreboard win while
+/// a game is on. Each changed game is announced on `SCORES_CHANNEL` as JSON
+/// when the update commits.
+pub fn update_games(client: &mut Client, games: &[LiveGame], precedence: &Precedence) -> Result<LiveUpdate> {
+    let scores = Merge::new(precedence, Source::Live, "g")
+        .status("$5")
+        .fill(Field::Scores, "home_score", "$9::int")
+        .fill(Field::Scores, "away_score", "$10::int");
+    let mut update = LiveUpdate::default();
+    for game in games {
+        let row = client
+            .query_one(
+                &format!(
+                    "WITH matched AS (
+                         SELECT g.id FROM games g
+                         JOIN teams ht ON ht.id = g.home_team_id
+                         JOIN teams at ON at.id = g.away_team_id
+                         WHERE g.nfl_game_id = $1 OR g.espn_game_id = $1
+                            OR (ht.abbreviation = $2 AND at.abbreviation = $3
+                                AND g.game_date BETWEEN $4::timestamptz - INTERVAL '36 hours' AND $4::timestamptz + INTERVAL '36 hours')
+                     ), updated AS (
+                         UPDATE games g SET
+                             status = $5, status_detail = $6, current_period = $7, game_clock = $8,
+                             {scores},
+                             updated_at = NOW()
+                         FROM matched m
+                         WHERE g.id = m.id
+                           AND {allowed}
+                           AND (g.status, g.status_detail, g.current_period, g.game_clock, g.home_score, g.away_score)
+                               IS DISTINCT FROM ($5, $6, $7, $8, {home_score}, {away_score})
+                         RETURNING g.*
+                     ), notified AS (
+                         SELECT pg_notify('{channel}', json_build_object(
+                             'id', u.id, 'nfl_game_id', u.nfl_game_id, 'season', u.season, 'week', u.week,
+                             'home_team', ht.abbreviation, 'away_team', at.abbreviation,
+                             'home_score', u.home_score, 'away_score', u.away_score,
+                             'status', u.status, 'status_detail', u.status_detail,
+                             'current_period', u.current_period, 'game_clock', u.game_clock, 'updated_at', u.updated_at
+                         )::text)
+                         FROM updated u
+                         JOIN teams ht ON ht.id = u.home_team_id
+                         JOIN teams at ON at.id = u.away_team_id
+                     )
+                     SELECT (SELECT COUNT(*) FROM matched), (SELECT COUNT(*) FROM notified)",
+                    channel = SCORES_CHANNEL,
+                    allowed = game_status::transition_allowed_sql("g.status", "$5"),
+                    scores = scores.set_sql(),
+                    home_score = scores.value_sql("home_score"),
+                    away_score = scores.value_sql("away_score")
+                ),
+                &[
+                    &game.espn_id,
+                    &game.home_team,
+                    &game.away_team,
+                    &game.kickoff,
+                    &game.status.as_str(),
+                    &game.status_detail,
+                    &game.period,
+                    &game.clock,
+                    &game.home_score,
+                    &game.away_score,
+                ],
+            )
+            .with_context(|| format!("Failed to update {} @ {}", game.away_team, game.home_team))?;
+        let (matched, changed): (i64, i64) = (row.get(0), row.get(1));
+        update.matched += usize::from(matched > 0);
+        update.changed += changed as u64;
+    }
+    Ok(update)
+}
+
+/// Time until the next poll: `interval` while a game is on or about to kick
+/// off, otherwise until shortly before the next kickoff, at most `idle`
+pub fn next_poll(games: &[LiveGame], now: DateTime<Utc>, interval: Duration, idle: Duration) -> Duration {
+    if games.iter().any(LiveGame::in_progress) {
+        return interval;
+    }
+    let until_kickoff = games
+        .iter()
+        .filter(|game| game.status == GameStatus::Scheduled)
+        .filter_map(|game| (game.kickoff - now).to_std().ok())
+        .min();
+    match until_kickoff {
+        Some(wait) => wait.saturating_sub(PREGAME_LEAD).clamp(interval, idle),
+        None => idle,
+    }
+}
+
+/// `--mode live`: poll the scoreboard until SIGTERM or SIGINT, every
+/// `interval` while games are on and at most every `idle` otherwise
+pub fn run(pipeline: &mut DataPipeline, url: &str, interval: Duration, idle: Duration) -> Result<()> {
+    let shutdown = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register(signal_hook::consts::SIGTERM, Arc::clone(&shutdown))?;
+    signal_hook::flag::register(signal_hook::consts::SIGINT, Arc::clone(&shutdown))?;
+
+    let http = reqwest::blocking::Client::builder().timeout(Duration::from_secs(20)).build()?;
+    info!("📡 Live scores: polling {} every {}s during games", url, interval.as_secs());
+
+    while !shutdown.load(Ordering::Relaxed) {
+        let games = match fetch_scoreboard(&http, url) {
+            Ok(games) => games,
+            Err(e) => {
+                error!("Scoreboard poll failed: {}", e);
+                Vec::new()
+            }
+        };
+        if let Err(e) = pipeline.apply_live_scores(&games) {
+            error!("Live score update failed: {}", e);
+        }
+
+        let wait = next_poll(&games, Utc::now(), interval, idle);
+        if wait > interval {
+            info!("💤 No games on; next poll in {}m", wait.as_secs() / 60);
+        }
+        sleep_until_shutdown(wait, &shutdown);
+    }
+
+    info!("🛑 Shutdown signal received, live polling stopping");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCOREBOARD: &str = r#"{"events": [
+        {"id": "401671789", "date": "2024-09-08T17:00Z",
+         "competitions": [{"competitors": [
+             {"homeAway": "home", "team": {"abbreviation": "WSH"}, "score": "17"},
+             {"homeAway": "away", "team": {"abbreviation": "TB"}, "score": "24"}]}],
+         "status": {"displayClock": "7:42", "period": 3,
+                    "type": {"state": "in", "completed": false, "description": "In Progress"}}},
+        {"id": "401671790", "date": "2024-09-09T00:20Z",
+         "competitions": [{"competitors": [
+             {"homeAway": "home", "team": {"abbreviation": "LAR"}, "score": "0"},
+             {"homeAway": "away", "team": {"abbreviation": "DET"}, "score": "0"}]}],
+         "status": {"displayClock": "0:00", "period": 0,
+                    "type": {"state": "pre", "completed": false, "description": "Scheduled"}}},
+        {"id": "401671791", "date": "2024-09-08T17:00Z", "competitions": [], "status": {"type": {"state": "pre"}}}
+    ]}"#;
+
+    #[test]
+    fn test_parse_scoreboard() {
+        let games = parse_scoreboard(SCOREBOARD).unwrap();
+        assert_eq!(games.len(), 2);
+
+        let live = &games[0];
+        assert_eq!((live.home_team.as_str(), live.away_team.as_str()), ("WAS", "TB"));
+        assert_eq!(live.status, GameStatus::InProgress);
+        assert_eq!((live.period, live.clock.as_deref()), (Some(3), Some("7:42")));
+        assert_eq!((live.home_score, live.away_score), (Some(17), Some(24)));
+        assert_eq!(live.kickoff.to_rfc3339(), "2024-09-08T17:00:00+00:00");
+
+        // Pregame zeros aren't scores
+        let pregame = &games[1];
+        assert_eq!((pregame.home_team.as_str(), pregame.status), ("LA", GameStatus::Scheduled));
+        assert_eq!((pregame.home_score, pregame.period, pregame.clock.as_deref()), (None, None, None));
+    }
+
+    #[test]
+    fn test_next_poll() {
+        let games = parse_scoreboard(SCOREBOARD).unwrap();
+        let (interval, idle) = (Duration::from_secs(30), Duration::from_secs(3600));
+        let now = games[0].kickoff;
+        assert_eq!(next_poll(&games, now, interval, idle), interval);
+
+        // Between windows: wake ten minutes before the night game
+        let upcoming = &games[1..];
+        let evening = games[1].kickoff - chrono::Duration::minutes(40);
+        assert_eq!(next_poll(upcoming, evening, interval, idle), Duration::from_secs(30 * 60));
+        assert_eq!(next_poll(upcoming, games[1].kickoff - chrono::Duration::hours(6), interval, idle), idle);
+        assert_eq!(next_poll(&[], now, interval, idle), idle);
+    }
+}
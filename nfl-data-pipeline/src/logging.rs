@@ -0,0 +1,118 @@
+use chrono::{SecondsFormat, Utc};
+use log::kv::{self, Key, Value, VisitSource};
+use log::Record;
+use serde_json::{Map, Value as Json};
+use std::io::Write;
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+static RUN_ID: OnceLock<String> = OnceLock::new();
+
+/// How log lines are written (`--log-format`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// env_logger's human-readable lines
+    Text,
+    /// One JSON object per line, for Datadog, Loki and the like
+    Json,
+}
+
+impl LogFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "text" => Some(LogFormat::Text),
+            "json" => Some(LogFormat::Json),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogFormat::Text => "text",
+            LogFormat::Json => "json",
+        }
+    }
+}
+
+/// Identifies this run in every JSON log line: `PIPELINE_RUN_ID` when the
+/// scheduler sets one, a random UUID otherwise
+pub fn run_id() -> &'static str {
+    RUN_ID.get_or_init(|| std::env::var("PIPELINE_RUN_ID").ok().filter(|id| !id.is_empty()).unwrap_or_else(|| Uuid::new_v4().to_string()))
+}
+
+/// Start logging. `RUST_LOG` picks levels in either format.
+pub fn init(format: LogFormat) {
+    let mut builder = env_logger::Builder::from_default_env();
+    if format == LogFormat::Json {
+        let run_id = run_id();
+        builder.format(move |buf, record| writeln!(buf, "{}", json_line(record, run_id)));
+    }
+    builder.init();
+}
+
+/// A record as a JSON object: timestamp, level, target, run ID and message,
+/// plus the event's structured fields (`info!(year = 2024, ...; "...")`)
+fn json_line(record: &Record, run_id: &str) -> String {
+    let mut line = Map::new();
+    line.insert("ts".to_string(), Json::from(Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)));
+    line.insert("level".to_string(), Json::from(record.level().as_str()));
+    line.insert("target".to_string(), Json::from(record.target()));
+    line.insert("run_id".to_string(), Json::from(run_id));
+    line.insert("message".to_string(), Json::from(record.args().to_string().trim()));
+    let _ = record.key_values().visit(&mut Fields(&mut line));
+    Json::Object(line).to_string()
+}
+
+struct Fields<'a>(&'a mut Map<String, Json>);
+
+impl<'kvs> VisitSource<'kvs> for Fields<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        let value = if let Some(n) = value.to_u64() {
+            Json::from(n)
+        } else if let Some(n) = value.to_i64() {
+            Json::from(n)
+        } else if let Some(n) = value.to_f64() {
+            Json::from(n)
+        } else if let Some(b) = value.to_bool() {
+            Json::from(b)
+        } else {
+            Json::from(value.to_string())
+        };
+        self.0.insert(key.as_str().to_string(), value);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_line_carries_run_id_and_fields() {
+        let fields: [(&str, Value); 4] = [
+            ("year", Value::from(2024)),
+            ("data_type", Value::from("rosters")),
+            ("rows_ok", Value::from(3006usize)),
+            ("duration_secs", Value::from(1.5)),
+        ];
+        let line = json_line(
+            &Record::builder()
+                .args(format_args!("  📦 rosters (2024): 3006 rows written"))
+                .level(log::Level::Info)
+                .target("nfl_data_pipeline::sync")
+                .key_values(&fields)
+                .build(),
+            "run-1",
+        );
+
+        let json: Json = serde_json::from_str(&line).unwrap();
+        assert_eq!(json["level"], "INFO");
+        assert_eq!(json["run_id"], "run-1");
+        assert_eq!(json["message"], "📦 rosters (2024): 3006 rows written");
+        assert_eq!(json["year"], 2024);
+        assert_eq!(json["data_type"], "rosters");
+        assert_eq!(json["rows_ok"], 3006);
+        assert_eq!(json["duration_secs"], 1.5);
+        assert!(!line.contains('\n'));
+    }
+}
@@ -0,0 +1,136 @@
+use anyhow::{Context, Result};
+use postgres::Client;
+use sha2::{Digest, Sha256};
+
+/// A table a derived table is computed from, and which of its rows count
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    /// Rows of the season being refreshed
+    Season(&'static str),
+    /// Rows of the season before it, e.g. Elo carried over from last year
+    PriorSeason(&'static str),
+    /// The whole table (tables without a season column)
+    All(&'static str),
+}
+
+impl Input {
+    pub fn table(&self) -> &'static str {
+        match self {
+            Input::Season(table) | Input::PriorSeason(table) | Input::All(table) => table,
+        }
+    }
+}
+
+/// A derived table refreshed per season, and the tables it reads
+#[derive(Debug)]
+pub struct Derived {
+    pub name: &'static str,
+    pub inputs: &'static [Input],
+}
+
+/// Every derived table, in refresh order: a derived table read by another for
+/// the same season comes before it
+pub const DERIVED: &[Derived] = &[
+    Derived { name: "player_splits", inputs: &[Input::Season("game_stats")] },
+    Derived { name: "fantasy_points", inputs: &[Input::Season("game_stats")] },
+    Derived { name: "season_stats", inputs: &[Input::Season("game_stats")] },
+    Derived { name: "role_changes", inputs: &[Input::Season("depth_charts"), Input::Season("snap_counts")] },
+    Derived {
+        name: "usage_alerts",
+        inputs: &[Input::Season("game_stats"), Input::Season("snap_counts"), Input::All("players")],
+    },
+    Derived {
+        name: "value_metrics",
+        inputs: &[
+            Input::Season("game_stats"),
+            Input::All("player_contracts"),
+            Input::All("draft_picks"),
+            Input::All("players"),
+        ],
+    },
+    Derived { name: "coach_records", inputs: &[Input::Season("games"), Input::Season("game_betting_results")] },
+    Derived {
+        name: "referee_tendencies",
+        inputs: &[Input::Season("games"), Input::Season("game_betting_results"), Input::All("game_team_stats")],
+    },
+    Derived { name: "team_ratings", inputs: &[Input::Season("games"), Input::PriorSeason("team_ratings")] },
+    Derived { name: "team_standings", inputs: &[Input::Season("games"), Input::All("teams")] },
+    Derived { name: "season_luck", inputs: &[Input::Season("games")] },
+];
+
+pub fn derived(name: &str) -> Option<&'static Derived> {
+    DERIVED.iter().find(|derived| derived.name == name)
+}
+
+/// SHA-256 over the state of a derived table's inputs for a season, plus
+/// `settings` that change its output (e.g. scoring formats).
+///
+/// An input's state is its row count and newest `xmin`: any insert, update or
+/// delete changes one of them, without needing an `updated_at` column. Upserts
+/// that skip unchanged rows (`row_hash`) leave it alone.
+pub fn freshness_key(client: &mut Client, derived: &Derived, season: i32, settings: &str) -> Result<String> {
+    let mut hasher = Sha256::new();
+    for input in derived.inputs {
+        let state = |filter: &str| {
+            format!("SELECT COUNT(*), COALESCE(MAX(xmin::text::bigint), 0) FROM {} {}", input.table(), filter)
+        };
+        let row = match input {
+            Input::Season(_) => client.query_one(&state("WHERE season = $1"), &[&season]),
+            Input::PriorSeason(_) => client.query_one(&state("WHERE season = $1"), &[&(season - 1)]),
+            Input::All(_) => client.query_one(&state(""), &[]),
+        }
+        .with_context(|| format!("Failed to read the state of {}", input.table()))?;
+        let (rows, newest): (i64, i64) = (row.get(0), row.get(1));
+        hasher.update(format!("{:?}={}:{};", input, rows, newest));
+    }
+    hasher.update([0x1f]);
+    hasher.update(settings.as_bytes());
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Key stored by the last successful refresh of a derived table's season
+pub fn stored_key(client: &mut Client, name: &str, season: i32) -> Result<Option<String>> {
+    let row = client
+        .query_opt("SELECT freshness_key FROM materializations WHERE name = $1 AND season = $2", &[&name, &season])
+        .context("Failed to load materialization state")?;
+    Ok(row.map(|row| row.get(0)))
+}
+
+pub fn store_key(client: &mut Client, name: &str, season: i32, key: &str) -> Result<()> {
+    client
+        .execute(
+            "INSERT INTO materializations (name, season, freshness_key, refreshed_at)
+             VALUES ($1, $2, $3, NOW())
+             ON CONFLICT (name, season) DO UPDATE SET
+                 freshness_key = EXCLUDED.freshness_key,
+                 refreshed_at = EXCLUDED.refreshed_at",
+            &[&name, &season, &key],
+        )
+        .context("Failed to store materialization state")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derived_inputs_refresh_first() {
+        for (index, derived) in DERIVED.iter().enumerate() {
+            for input in derived.inputs {
+                let Input::Season(table) = input else {
+                    continue;
+                };
+                let producer = DERIVED.iter().position(|d| d.name == *table);
+                assert!(
+                    producer.is_none_or(|p| p < index),
+                    "{} reads {} for the same season before it is refreshed",
+                    derived.name,
+                    table
+                );
+            }
+        }
+        assert!(derived("team_standings").is_some());
+        assert!(derived("leaders").is_none());
+    }
+}
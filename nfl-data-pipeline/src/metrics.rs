@@ -0,0 +1,209 @@
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+
+/// Batch write buckets, in seconds
+const WRITE_BUCKETS: &[f64] = &[0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+/// Download buckets, in seconds
+const DOWNLOAD_BUCKETS: &[f64] = &[0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0];
+
+struct Histogram {
+    buckets: &'static [f64],
+    /// Observations at or below each bucket
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new(buckets: &'static [f64]) -> Self {
+        Histogram { buckets, counts: vec![0; buckets.len()], sum: 0.0, count: 0 }
+    }
+
+    fn observe(&mut self, value: f64) {
+        for (bucket, count) in self.buckets.iter().zip(self.counts.iter_mut()) {
+            if value <= *bucket {
+                *count += 1;
+            }
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+/// Counters and histograms collected over the run
+#[derive(Default)]
+struct Registry {
+    /// (dataset, outcome) to rows
+    rows: BTreeMap<(String, &'static str), u64>,
+    /// What was retried (download, database, batch) to retries
+    retries: BTreeMap<&'static str, u64>,
+    downloads: BTreeMap<String, Histogram>,
+    writes: BTreeMap<String, Histogram>,
+}
+
+impl Registry {
+    fn rows(&mut self, dataset: &str, ok: usize, failed: usize) {
+        *self.rows.entry((dataset.to_string(), "ok")).or_default() += ok as u64;
+        *self.rows.entry((dataset.to_string(), "failed")).or_default() += failed as u64;
+    }
+
+    fn retry(&mut self, kind: &'static str) {
+        *self.retries.entry(kind).or_default() += 1;
+    }
+
+    fn download(&mut self, dataset: &str, elapsed: Duration) {
+        self.downloads
+            .entry(dataset.to_string())
+            .or_insert_with(|| Histogram::new(DOWNLOAD_BUCKETS))
+            .observe(elapsed.as_secs_f64());
+    }
+
+    fn write(&mut self, dataset: &str, elapsed: Duration) {
+        self.writes
+            .entry(dataset.to_string())
+            .or_insert_with(|| Histogram::new(WRITE_BUCKETS))
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Prometheus text exposition format
+    fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# HELP nfl_pipeline_rows_total Rows imported (ok) or failed per dataset");
+        let _ = writeln!(out, "# TYPE nfl_pipeline_rows_total counter");
+        for ((dataset, outcome), rows) in &self.rows {
+            let _ = writeln!(out, "nfl_pipeline_rows_total{{dataset=\"{}\",outcome=\"{}\"}} {}", dataset, outcome, rows);
+        }
+        let _ = writeln!(out, "# HELP nfl_pipeline_retries_total Retried downloads, database reconnects and batch writes");
+        let _ = writeln!(out, "# TYPE nfl_pipeline_retries_total counter");
+        for (kind, retries) in &self.retries {
+            let _ = writeln!(out, "nfl_pipeline_retries_total{{kind=\"{}\"}} {}", kind, retries);
+        }
+        render_histogram(&mut out, "nfl_pipeline_download_seconds", "Time to fetch a dataset's file", &self.downloads);
+        render_histogram(&mut out, "nfl_pipeline_batch_write_seconds", "Time to write one batch to the database", &self.writes);
+        out
+    }
+}
+
+fn render_histogram(out: &mut String, name: &str, help: &str, histograms: &BTreeMap<String, Histogram>) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} histogram", name);
+    for (dataset, histogram) in histograms {
+        for (bucket, count) in histogram.buckets.iter().zip(&histogram.counts) {
+            let _ = writeln!(out, "{}_bucket{{dataset=\"{}\",le=\"{}\"}} {}", name, dataset, bucket, count);
+        }
+        let _ = writeln!(out, "{}_bucket{{dataset=\"{}\",le=\"+Inf\"}} {}", name, dataset, histogram.count);
+        let _ = writeln!(out, "{}_sum{{dataset=\"{}\"}} {}", name, dataset, histogram.sum);
+        let _ = writeln!(out, "{}_count{{dataset=\"{}\"}} {}", name, dataset, histogram.count);
+    }
+}
+
+fn registry() -> std::sync::MutexGuard<'static, Registry> {
+    REGISTRY.get_or_init(Default::default).lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Count a stage's rows written and failed
+pub fn rows(dataset: &str, ok: usize, failed: usize) {
+    registry().rows(dataset, ok, failed);
+}
+
+/// Count one retry of a download (`download`), reconnect (`database`) or batch write (`batch`)
+pub fn retry(kind: &'static str) {
+    registry().retry(kind);
+}
+
+/// Record how long a dataset's file took to fetch
+pub fn download(dataset: &str, elapsed: Duration) {
+    registry().download(dataset, elapsed);
+}
+
+/// Record how long one of a dataset's batches took to write
+pub fn batch_write(dataset: &str, elapsed: Duration) {
+    registry().write(dataset, elapsed);
+}
+
+pub fn render() -> String {
+    registry().render()
+}
+
+/// Serve `/metrics` on `addr` from a background thread for the rest of the run
+pub fn serve(addr: &str) -> Result<()> {
+    let listener = TcpListener::bind(addr).with_context(|| format!("Failed to bind --metrics-addr {}", addr))?;
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            let mut request_line = String::new();
+            if BufReader::new(&stream).read_line(&mut request_line).is_err() {
+                continue;
+            }
+            let path = request_line.split_whitespace().nth(1).unwrap_or("");
+            let response = match path.split('?').next() {
+                Some("/metrics") => {
+                    let body = render();
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                }
+                _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+            };
+            if let Err(e) = stream.write_all(response.as_bytes()) {
+                debug!("Metrics response not sent: {}", e);
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Push everything collected to a Pushgateway, grouped by mode, for batch runs
+/// that exit before a scrape would reach them
+pub fn push(gateway: &str, mode: &str) {
+    let url = format!("{}/metrics/job/nfl_data_pipeline/mode/{}", gateway.trim_end_matches('/'), mode);
+    let pushed = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .and_then(|http| http.put(&url).header("Content-Type", "text/plain; version=0.0.4").body(render()).send())
+        .and_then(|response| response.error_for_status());
+    match pushed {
+        Ok(_) => debug!("Metrics pushed to {}", url),
+        Err(e) => warn!("⚠️  Metrics not pushed to {}: {}", url, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_counters_and_histograms() {
+        let mut registry = Registry::default();
+        registry.rows("rosters", 3006, 2);
+        registry.rows("rosters", 10, 0);
+        registry.retry("download");
+        registry.retry("download");
+        registry.write("rosters", Duration::from_millis(40));
+        registry.write("rosters", Duration::from_millis(700));
+
+        let text = registry.render();
+        assert!(text.contains("nfl_pipeline_rows_total{dataset=\"rosters\",outcome=\"ok\"} 3016\n"));
+        assert!(text.contains("nfl_pipeline_rows_total{dataset=\"rosters\",outcome=\"failed\"} 2\n"));
+        assert!(text.contains("nfl_pipeline_retries_total{kind=\"download\"} 2\n"));
+        assert!(text.contains("nfl_pipeline_batch_write_seconds_bucket{dataset=\"rosters\",le=\"0.025\"} 0\n"));
+        assert!(text.contains("nfl_pipeline_batch_write_seconds_bucket{dataset=\"rosters\",le=\"0.05\"} 1\n"));
+        assert!(text.contains("nfl_pipeline_batch_write_seconds_bucket{dataset=\"rosters\",le=\"1\"} 2\n"));
+        assert!(text.contains("nfl_pipeline_batch_write_seconds_bucket{dataset=\"rosters\",le=\"+Inf\"} 2\n"));
+        assert!(text.contains("nfl_pipeline_batch_write_seconds_count{dataset=\"rosters\"} 2\n"));
+        assert!(text.contains("# TYPE nfl_pipeline_download_seconds histogram\n"));
+    }
+}
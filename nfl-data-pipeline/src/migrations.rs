@@ -0,0 +1,366 @@
+use anyhow::{anyhow, Context, Result};
+use log::{debug, info, warn};
+use postgres::{Client, GenericClient, Transaction};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Held while migrating so two deploys can't apply the same migration at once
+const MIGRATION_LOCK_ID: i64 = 0x6e66_6c6d_6967;
+
+/// Extension objects the migrations refer to unqualified, by extension
+const EXTENSION_OBJECTS: &[(&str, &str)] = &[("uuid-ossp", "uuid_generate_v4("), ("pg_trgm", "gin_trgm_ops")];
+
+/// A versioned SQL migration from the repository's `migrations/` directory,
+/// embedded in the binary at build time
+pub struct Migration {
+    pub version: i32,
+    pub file: &'static str,
+    pub sql: &'static str,
+    /// Written for `psql -f`, which carries on past a failing statement
+    pub continue_on_error: bool,
+}
+
+macro_rules! migration {
+    ($version:expr, $file:literal) => {
+        migration!($version, $file, false)
+    };
+    ($version:expr, $file:literal, $continue_on_error:expr) => {
+        Migration {
+            version: $version,
+            file: $file,
+            sql: include_str!(concat!("../../migrations/", $file)),
+            continue_on_error: $continue_on_error,
+        }
+    };
+}
+
+/// Every migration in version order. New files in `migrations/` are added here.
+pub const MIGRATIONS: &[Migration] = &[
+    migration!(1, "001_initial_schema.sql"),
+    migration!(2, "002_add_historical_data.sql"),
+    migration!(3, "003_enhance_comprehensive_schema.sql"),
+    migration!(5, "005_add_defensive_stats.sql"),
+    migration!(6, "006_remove_ai_tables.sql"),
+    // Indexes tables and columns the Go importer created; a fresh database lacks them
+    migration!(7, "007_add_performance_indexes.sql", true),
+    migration!(8, "008_add_play_by_play.sql"),
+    migration!(9, "009_add_historical_import_support.sql"),
+    migration!(10, "010_change_nfl_id_to_varchar.sql"),
+    migration!(11, "011_add_yahoo_fantasy_data.sql"),
+    migration!(12, "012_add_player_name_search_index.sql"),
+    migration!(13, "013_add_weekly_import_progress.sql"),
+    migration!(14, "014_add_stat_corrections.sql"),
+    migration!(15, "015_add_player_splits.sql"),
+    migration!(16, "016_add_college_stats.sql"),
+    migration!(17, "017_add_game_betting_results.sql"),
+    migration!(18, "018_add_coach_referee_reports.sql"),
+    migration!(19, "019_add_season_type.sql"),
+    migration!(20, "020_add_dataset_schemas.sql"),
+    migration!(21, "021_add_game_lines_environment.sql"),
+    migration!(22, "022_add_injuries.sql"),
+    migration!(23, "023_add_depth_charts.sql"),
+    migration!(24, "024_add_draft_combine.sql"),
+    migration!(25, "025_add_player_pedigree.sql"),
+    migration!(26, "026_add_player_external_ids.sql"),
+    migration!(27, "027_add_snap_counts_role_changes.sql"),
+    migration!(28, "028_add_efficiency_metrics.sql"),
+    migration!(29, "029_add_team_ratings.sql"),
+    migration!(30, "030_add_standings_luck.sql"),
+    migration!(31, "031_add_contracts_value_metrics.sql"),
+    migration!(32, "032_add_failed_batches.sql"),
+    migration!(33, "033_add_source_fingerprints.sql"),
+    migration!(34, "034_fix_fresh_schema.sql"),
+    migration!(35, "035_add_team_branding.sql"),
+    migration!(36, "036_add_defense_kicking_stats.sql"),
+    migration!(37, "037_add_fantasy_points.sql"),
+    migration!(38, "038_add_roster_moves.sql"),
+    migration!(39, "039_add_season_career_stats.sql"),
+    migration!(40, "040_add_usage_alerts.sql"),
+    migration!(41, "041_add_standings_ties.sql"),
+    migration!(42, "042_add_elo_ratings.sql"),
+    migration!(43, "043_add_weather_splits.sql"),
+    migration!(44, "044_add_unresolved_references.sql"),
+    migration!(45, "045_add_sources.sql"),
+    migration!(46, "046_add_game_status_lifecycle.sql"),
+    migration!(47, "047_games_kickoff_timestamptz.sql"),
+    migration!(48, "048_add_espn_game_ids.sql"),
+    migration!(49, "049_add_external_id_match_confidence.sql"),
+    migration!(50, "050_add_field_provenance.sql"),
+    migration!(51, "051_add_data_lineage.sql"),
+    migration!(52, "052_add_game_news.sql"),
+    migration!(53, "053_add_player_news.sql"),
+    migration!(54, "054_add_odds_history.sql"),
+    migration!(55, "055_add_row_hashes.sql"),
+    migration!(56, "056_add_change_log.sql"),
+    migration!(57, "057_add_fantasy_leagues.sql"),
+    migration!(58, "058_add_materializations.sql"),
+    migration!(59, "059_add_api_keys.sql"),
+];
+
+impl Migration {
+    /// File name without the version prefix and extension, e.g. `add_injuries`
+    pub fn description(&self) -> &'static str {
+        let stem = self.file.strip_suffix(".sql").unwrap_or(self.file);
+        stem.split_once('_').map_or(stem, |(_, rest)| rest)
+    }
+
+    pub fn checksum(&self) -> String {
+        format!("{:x}", Sha256::digest(self.sql.as_bytes()))
+    }
+}
+
+/// Migrations not yet recorded in `schema_migrations`; `None` when the database
+/// has no migration history at all
+pub fn pending(client: &mut Client) -> Result<Option<Vec<&'static Migration>>> {
+    let applied = applied(client)?;
+    if applied.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(MIGRATIONS.iter().filter(|m| !applied.contains_key(&m.version)).collect()))
+}
+
+/// `--mode migrate`: apply pending migrations in version order, each in its own
+/// transaction together with its `schema_migrations` row.
+///
+/// A database created by hand (tables present, no migration history) is refused
+/// unless `baseline` is given; migrations up to that version are then recorded
+/// as applied without running them. With `dry_run`, pending migrations are only listed.
+pub fn run(client: &mut Client, baseline: Option<i32>, dry_run: bool) -> Result<usize> {
+    client.execute("SELECT pg_advisory_lock($1)", &[&MIGRATION_LOCK_ID])?;
+    let result = migrate(client, baseline, dry_run);
+    client.execute("SELECT pg_advisory_unlock($1)", &[&MIGRATION_LOCK_ID])?;
+    result
+}
+
+fn migrate(client: &mut Client, baseline: Option<i32>, dry_run: bool) -> Result<usize> {
+    let mut applied = applied(client)?;
+    let has_schema: bool = client.query_one("SELECT to_regclass('public.players') IS NOT NULL", &[])?.get(0);
+    if applied.is_empty() && has_schema && baseline.is_none() {
+        return Err(anyhow!(
+            "Database has tables but no migration history; record the migrations already applied with \
+             --mode migrate --baseline <version>"
+        ));
+    }
+
+    if !dry_run {
+        // IF NOT EXISTS and DROP ... IF EXISTS notices would flood the log
+        client.batch_execute(
+            "SET client_min_messages = warning;
+             CREATE TABLE IF NOT EXISTS schema_migrations (
+                 version INT PRIMARY KEY,
+                 description TEXT NOT NULL,
+                 checksum TEXT,
+                 applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+             )",
+        )?;
+    }
+
+    if applied.is_empty() {
+        match baseline {
+            Some(version) => {
+                info!("📌 Recording migrations up to {} as applied (baseline)", version);
+                for migration in MIGRATIONS.iter().filter(|m| m.version <= version) {
+                    if !dry_run {
+                        record(client, migration)?;
+                    }
+                    applied.insert(migration.version, Some(migration.checksum()));
+                }
+            }
+            None => info!("🆕 Empty database, applying every migration"),
+        }
+    }
+
+    for (version, checksum) in &applied {
+        match MIGRATIONS.iter().find(|m| m.version == *version) {
+            Some(migration) if checksum.as_ref().is_some_and(|c| *c != migration.checksum()) => {
+                warn!("⚠️  {} changed after it was applied", migration.file)
+            }
+            Some(_) => {}
+            None => warn!("⚠️  Migration {} is recorded but unknown to this build (binary older than database?)", version),
+        }
+    }
+
+    let pending: Vec<&Migration> = MIGRATIONS.iter().filter(|m| !applied.contains_key(&m.version)).collect();
+    if pending.is_empty() {
+        info!("✅ Schema up to date (version {})", applied.keys().max().copied().unwrap_or(0));
+        return Ok(0);
+    }
+
+    for migration in &pending {
+        if dry_run {
+            info!("  ⏳ {} (pending)", migration.file);
+            continue;
+        }
+        let started = Instant::now();
+        let mut tx = client.transaction()?;
+        execute(&mut tx, migration, migration.sql).with_context(|| format!("Migration {} failed", migration.file))?;
+        record(&mut tx, migration)?;
+        tx.commit()?;
+        info!("  ⬆️  {} ({} ms)", migration.file, started.elapsed().as_millis());
+    }
+
+    Ok(pending.len())
+}
+
+/// Highest applied migration version, 0 without migration history
+pub fn current_version(client: &mut Client) -> Result<i32> {
+    Ok(applied(client)?.keys().max().copied().unwrap_or(0))
+}
+
+/// Applied versions and their checksums (NULL for rows written by a migration itself)
+fn applied(client: &mut Client) -> Result<HashMap<i32, Option<String>>> {
+    let exists: bool = client.query_one("SELECT to_regclass('public.schema_migrations') IS NOT NULL", &[])?.get(0);
+    if !exists {
+        return Ok(HashMap::new());
+    }
+    Ok(client
+        .query("SELECT version, checksum FROM schema_migrations", &[])?
+        .iter()
+        .map(|row| (row.get(0), row.get(1)))
+        .collect())
+}
+
+/// Migration 003 inserts its own row without a checksum, so this upserts
+fn record(client: &mut impl GenericClient, migration: &Migration) -> Result<()> {
+    client.execute(
+        "INSERT INTO schema_migrations (version, description, checksum, applied_at)
+         VALUES ($1, $2, $3, NOW())
+         ON CONFLICT (version) DO UPDATE SET description = EXCLUDED.description, checksum = EXCLUDED.checksum",
+        &[&migration.version, &migration.description(), &migration.checksum()],
+    )?;
+    Ok(())
+}
+
+/// Apply every migration to the empty `schema` in `tx` (a `--simulate` run's schema),
+/// returning the version it ends at.
+///
+/// Only `schema` is on the search path while they run, so a `DROP ... IF EXISTS`
+/// can't reach an object in another schema. The extension objects they use are
+/// qualified with the schema their extension is installed in instead. Each one is
+/// recorded in the schema's own `schema_migrations`, as `--mode migrate` would.
+pub fn apply_to_schema(tx: &mut Transaction, schema: &str) -> Result<i32> {
+    let installed: HashMap<String, String> = tx
+        .query(
+            "SELECT e.extname::text, n.nspname::text FROM pg_extension e JOIN pg_namespace n ON n.oid = e.extnamespace",
+            &[],
+        )?
+        .iter()
+        .map(|row| (row.get(0), row.get(1)))
+        .collect();
+
+    tx.batch_execute(&format!(
+        "SET LOCAL search_path TO {schema};
+         SET LOCAL client_min_messages = warning;
+         CREATE TABLE schema_migrations (
+             version INT PRIMARY KEY,
+             description TEXT NOT NULL,
+             checksum TEXT,
+             applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+         )"
+    ))?;
+    for migration in MIGRATIONS {
+        let sql = qualify_extensions(migration.sql, &installed);
+        execute(tx, migration, &sql).with_context(|| format!("Migration {} failed in schema {}", migration.file, schema))?;
+        record(tx, migration)?;
+    }
+    Ok(MIGRATIONS.last().map_or(0, |m| m.version))
+}
+
+/// Run a migration's `sql`. One marked `continue_on_error` runs a statement at a
+/// time, each behind a savepoint, skipping the ones that fail the way `psql -f` does;
+/// its statements must end with `;` at the end of a line.
+fn execute(tx: &mut Transaction, migration: &Migration, sql: &str) -> Result<()> {
+    if !migration.continue_on_error {
+        tx.batch_execute(sql)?;
+        return Ok(());
+    }
+
+    for statement in statements(sql) {
+        let mut savepoint = tx.transaction()?;
+        match savepoint.batch_execute(statement) {
+            Ok(()) => savepoint.commit()?,
+            Err(e) => {
+                let message = e.as_db_error().map_or_else(|| e.to_string(), |db| db.message().to_string());
+                debug!("Skipped a statement of {}: {}", migration.file, message)
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Statements of a plain SQL script, split where a line ends with `;`
+fn statements(sql: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let (mut start, mut end) = (0, 0);
+    for line in sql.split_inclusive('\n') {
+        end += line.len();
+        if line.trim_end().ends_with(';') {
+            statements.push(&sql[start..end]);
+            start = end;
+        }
+    }
+    statements
+}
+
+/// `sql` with the extension objects it uses prefixed by their installed schema.
+/// Extensions not installed yet are left alone: the migration creates them.
+fn qualify_extensions(sql: &str, installed: &HashMap<String, String>) -> String {
+    EXTENSION_OBJECTS.iter().fold(sql.to_string(), |sql, (extension, object)| match installed.get(*extension) {
+        Some(schema) => sql.replace(object, &format!("\"{}\".{}", schema, object)),
+        None => sql,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_migration_file_is_embedded_in_order() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../migrations");
+        let mut files: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.ends_with(".sql"))
+            .collect();
+        files.sort();
+
+        let embedded: Vec<&str> = MIGRATIONS.iter().map(|m| m.file).collect();
+        assert_eq!(embedded, files);
+        for migration in MIGRATIONS {
+            assert_eq!(migration.file[..3].parse::<i32>().unwrap(), migration.version);
+        }
+        assert!(MIGRATIONS.windows(2).all(|w| w[0].version < w[1].version));
+    }
+
+    #[test]
+    fn test_description_drops_version_prefix() {
+        assert_eq!(MIGRATIONS[0].description(), "initial_schema");
+        let fix = MIGRATIONS.iter().find(|m| m.version == 34).unwrap();
+        assert_eq!(fix.description(), "fix_fresh_schema");
+    }
+
+    #[test]
+    fn test_statements_split_at_line_ends() {
+        let sql = "-- Indexes\nCREATE INDEX a ON t(x);\nCREATE INDEX b ON t(y)\n    WHERE y > 0;\n\nANALYZE t;\n";
+
+        assert_eq!(
+            statements(sql),
+            ["-- Indexes\nCREATE INDEX a ON t(x);\n", "CREATE INDEX b ON t(y)\n    WHERE y > 0;\n", "\nANALYZE t;\n"]
+        );
+    }
+
+    #[test]
+    fn test_extension_objects_are_qualified_when_installed() {
+        let installed = HashMap::from([("uuid-ossp".to_string(), "public".to_string())]);
+        let sql = "CREATE TABLE t (id UUID DEFAULT uuid_generate_v4());
+                   CREATE INDEX i ON t USING gin(name gin_trgm_ops);";
+
+        assert_eq!(
+            qualify_extensions(sql, &installed),
+            "CREATE TABLE t (id UUID DEFAULT \"public\".uuid_generate_v4());
+                   CREATE INDEX i ON t USING gin(name gin_trgm_ops);"
+        );
+    }
+}
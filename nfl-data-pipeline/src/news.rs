@@ -0,0 +1,640 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use postgres::Client;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::espn;
+use crate::espn_ids::{self, Athlete, Player};
+
+/// Dataset name recorded in `source_runs` and telemetry
+pub const GAME_NEWS_DATASET: &str = "espn_game_news";
+/// ESPN's NFL news feed, the default `--news-feed`
+pub const NEWS_URL: &str = "https://site.api.espn.com/apis/site/v2/sports/football/nfl/news?limit=100";
+/// Lowest fuzzy match score a news item links at: an exact name no other
+/// player shares
+pub const MIN_NEWS_CONFIDENCE: f64 = 0.65;
+
+/// Words that make an item an injury update; a trailing `*` matches any ending
+/// ("injur*" for injury, injured, injuries)
+const INJURY_TERMS: [&str; 16] = [
+    "injur*", "questionable", "doubtful", "concussion*", "hamstring*", "ankle*", "knee*", "groin*", "shoulder*",
+    "sidelined", "surgery", "sprain*", "torn", "ir", "mri", "inactive*",
+];
+/// Words that make an item a transaction, when it isn't an injury update
+const TRANSACTION_TERMS: [&str; 15] = [
+    "sign", "signs", "signed", "signing", "releas*", "waive*", "trade*", "claimed", "acquir*", "extension*", "cut",
+    "cuts", "elevat*", "contract*", "reinstat*",
+];
+
+/// What an article is to its game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NewsKind {
+    /// ESPN's write-up of the game itself
+    Recap,
+    /// Related coverage listed with the game
+    Headline,
+}
+
+impl NewsKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NewsKind::Recap => "recap",
+            NewsKind::Headline => "headline",
+        }
+    }
+}
+
+/// One article about a game
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameNews {
+    pub article_id: String,
+    pub kind: NewsKind,
+    pub headline: String,
+    pub summary: Option<String>,
+    /// Recap text without markup
+    pub body: Option<String>,
+    pub url: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Summary {
+    #[serde(default)]
+    article: Option<Article>,
+    #[serde(default)]
+    news: Option<NewsFeed>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NewsFeed {
+    #[serde(default)]
+    articles: Vec<Article>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Article {
+    /// A number in some payloads, a string in others
+    #[serde(default)]
+    id: Option<serde_json::Value>,
+    #[serde(default)]
+    headline: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    story: Option<String>,
+    #[serde(default)]
+    published: Option<String>,
+    #[serde(default)]
+    links: Option<Links>,
+    /// Tags: league, teams and athletes the article is about
+    #[serde(default)]
+    categories: Vec<Category>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Category {
+    #[serde(default, rename = "type")]
+    kind: Option<String>,
+    #[serde(default)]
+    athlete_id: Option<serde_json::Value>,
+    /// The athlete's name on athlete tags
+    #[serde(default)]
+    description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Links {
+    #[serde(default)]
+    web: Option<Link>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Link {
+    #[serde(default)]
+    href: Option<String>,
+}
+
+fn id_string(id: serde_json::Value) -> Option<String> {
+    match id {
+        serde_json::Value::String(id) => Some(id),
+        serde_json::Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+impl Article {
+    fn into_news(self, kind: NewsKind) -> Option<GameNews> {
+        let article_id = id_string(self.id?)?;
+        let headline = self.headline.filter(|headline| !headline.trim().is_empty())?;
+        Some(GameNews {
+            article_id,
+            kind,
+            headline: headline.trim().to_string(),
+            summary: self.description.map(|d| d.trim().to_string()).filter(|d| !d.is_empty()),
+            body: self.story.as_deref().map(plain_text).filter(|body| !body.is_empty()),
+            url: self.links.and_then(|links| links.web).and_then(|web| web.href),
+            published_at: self.published.as_deref().and_then(espn::parse_kickoff),
+        })
+    }
+
+    fn into_item(mut self) -> Option<NewsItem> {
+        let athletes = std::mem::take(&mut self.categories)
+            .into_iter()
+            .filter(|category| category.kind.as_deref() == Some("athlete"))
+            .filter_map(|category| Some((id_string(category.athlete_id?)?, category.description)))
+            .collect();
+        let news = self.into_news(NewsKind::Headline)?;
+        Some(NewsItem {
+            item_id: news.article_id,
+            category: classify(&format!("{} {}", news.headline, news.summary.as_deref().unwrap_or(""))),
+            headline: news.headline,
+            summary: news.summary,
+            url: news.url,
+            published_at: news.published_at,
+            athletes,
+        })
+    }
+}
+
+/// Story HTML as plain text: tags dropped, paragraphs and line breaks kept as
+/// blank lines, common entities decoded
+pub fn plain_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim_start_matches('/').to_ascii_lowercase();
+        let name = tag.split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or("");
+        if matches!(name, "p" | "br" | "div" | "li") {
+            text.push('\n');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(rest);
+    decode_entities(&text)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", " ")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&#8217;", "\u{2019}")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// The recap and related headlines in a game summary, recap first; an article
+/// listed twice is kept once
+pub fn parse_game_news(body: &str) -> Result<Vec<GameNews>> {
+    let summary: Summary = serde_json::from_str(body).context("Failed to parse ESPN summary")?;
+    let recap = summary.article.and_then(|article| article.into_news(NewsKind::Recap));
+    let headlines = summary
+        .news
+        .map(|feed| feed.articles)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|article| article.into_news(NewsKind::Headline));
+
+    let mut news: Vec<GameNews> = Vec::new();
+    for item in recap.into_iter().chain(headlines) {
+        if !news.iter().any(|n| n.article_id == item.article_id) {
+            news.push(item);
+        }
+    }
+    Ok(news)
+}
+
+pub fn fetch_game_news(http: &reqwest::blocking::Client, base: &str, espn_game_id: &str) -> Result<Vec<GameNews>> {
+    parse_game_news(&espn::fetch(http, &espn::summary_url(base, espn_game_id))?)
+}
+
+/// Upsert a game's articles into `game_news`; returns the rows written
+pub fn write_game_news(client: &mut Client, espn_game_id: &str, news: &[GameNews]) -> Result<usize> {
+    let mut written = 0;
+    let mut tx = client.transaction()?;
+    for item in news {
+        written += tx
+            .execute(
+                "INSERT INTO game_news (game_id, source, article_id, kind, headline, summary, body, url, published_at)
+                 SELECT g.id, 'espn', $2, $3, $4, $5, $6, $7, $8
+                 FROM games g WHERE g.espn_game_id = $1
+                 ON CONFLICT (game_id, source, article_id) DO UPDATE SET
+                     kind = EXCLUDED.kind,
+                     headline = EXCLUDED.headline,
+                     summary = EXCLUDED.summary,
+                     body = COALESCE(EXCLUDED.body, game_news.body),
+                     url = COALESCE(EXCLUDED.url, game_news.url),
+                     published_at = COALESCE(EXCLUDED.published_at, game_news.published_at),
+                     updated_at = NOW()
+                 WHERE (game_news.kind, game_news.headline, game_news.summary, game_news.body, game_news.url, game_news.published_at)
+                       IS DISTINCT FROM (EXCLUDED.kind, EXCLUDED.headline, EXCLUDED.summary, COALESCE(EXCLUDED.body, game_news.body),
+                                         COALESCE(EXCLUDED.url, game_news.url), COALESCE(EXCLUDED.published_at, game_news.published_at))",
+                &[
+                    &espn_game_id,
+                    &item.article_id,
+                    &item.kind.as_str(),
+                    &item.headline,
+                    &item.summary,
+                    &item.body,
+                    &item.url,
+                    &item.published_at,
+                ],
+            )
+            .with_context(|| format!("Failed to write ESPN article {}", item.article_id))? as usize;
+    }
+    tx.commit()?;
+    Ok(written)
+}
+
+/// What a news item is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NewsCategory {
+    Injury,
+    Transaction,
+    General,
+}
+
+impl NewsCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NewsCategory::Injury => "injury",
+            NewsCategory::Transaction => "transaction",
+            NewsCategory::General => "news",
+        }
+    }
+}
+
+/// An item's category from its headline and summary; an item that is both
+/// ("placed on IR") counts as an injury update
+pub fn classify(text: &str) -> NewsCategory {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    let mentions = |terms: &[&str]| {
+        words.iter().any(|word| {
+            terms.iter().any(|term| match term.strip_suffix('*') {
+                Some(prefix) => word.starts_with(prefix),
+                None => word == term,
+            })
+        })
+    };
+    if mentions(&INJURY_TERMS) {
+        NewsCategory::Injury
+    } else if mentions(&TRANSACTION_TERMS) {
+        NewsCategory::Transaction
+    } else {
+        NewsCategory::General
+    }
+}
+
+/// Which kind of feed `--news-feed` points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedFormat {
+    /// ESPN's news API, with athlete tags
+    Espn,
+    Rss,
+}
+
+impl FeedFormat {
+    /// `player_news.source`
+    pub fn source(&self) -> &'static str {
+        match self {
+            FeedFormat::Espn => "espn",
+            FeedFormat::Rss => "rss",
+        }
+    }
+
+    /// Dataset name recorded in `source_runs` and telemetry
+    pub fn dataset(&self) -> &'static str {
+        match self {
+            FeedFormat::Espn => "espn_player_news",
+            FeedFormat::Rss => "rss_player_news",
+        }
+    }
+}
+
+/// One item of a news feed
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewsItem {
+    /// ESPN article ID, or the RSS item's guid or link
+    pub item_id: String,
+    pub category: NewsCategory,
+    pub headline: String,
+    pub summary: Option<String>,
+    pub url: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+    /// Athletes ESPN tagged the item with: ESPN ID and name
+    pub athletes: Vec<(String, Option<String>)>,
+}
+
+/// Text of the first `name` element in an RSS item, CDATA unwrapped and
+/// entities decoded
+fn element_text(xml: &str, name: &str) -> Option<String> {
+    let start = xml.find(&format!("<{}", name))?;
+    let open_end = start + xml[start..].find('>')? + 1;
+    if xml[..open_end].ends_with("/>") {
+        return None;
+    }
+    let close = open_end + xml[open_end..].find(&format!("</{}>", name))?;
+    let text = xml[open_end..close].trim();
+    let text = text.strip_prefix("<![CDATA[").and_then(|t| t.strip_suffix("]]>")).unwrap_or(text);
+    Some(decode_entities(text).trim().to_string()).filter(|text| !text.is_empty())
+}
+
+fn parse_rss(body: &str) -> Vec<NewsItem> {
+    body.split("<item>")
+        .skip(1)
+        .filter_map(|item| {
+            let item = item.split("</item>").next()?;
+            let headline = element_text(item, "title")?;
+            let url = element_text(item, "link");
+            let summary = element_text(item, "description").map(|d| plain_text(&d)).filter(|d| !d.is_empty());
+            Some(NewsItem {
+                item_id: element_text(item, "guid").or_else(|| url.clone())?,
+                category: classify(&format!("{} {}", headline, summary.as_deref().unwrap_or(""))),
+                published_at: element_text(item, "pubDate")
+                    .and_then(|date| DateTime::parse_from_rfc2822(&date).ok())
+                    .map(|date| date.with_timezone(&Utc)),
+                headline,
+                summary,
+                url,
+                athletes: Vec::new(),
+            })
+        })
+        .collect()
+}
+
+/// Items of a news feed: ESPN's news API (JSON) or RSS
+pub fn parse_feed(body: &str) -> Result<(FeedFormat, Vec<NewsItem>)> {
+    if body.trim_start().starts_with('<') {
+        return Ok((FeedFormat::Rss, parse_rss(body)));
+    }
+    let feed: NewsFeed = serde_json::from_str(body).context("Failed to parse news feed (expected ESPN news JSON or RSS)")?;
+    Ok((FeedFormat::Espn, feed.articles.into_iter().filter_map(Article::into_item).collect()))
+}
+
+pub fn fetch_feed(http: &reqwest::blocking::Client, url: &str) -> Result<(FeedFormat, Vec<NewsItem>)> {
+    parse_feed(&espn::fetch(http, url)?)
+}
+
+/// Player names in a headline or summary, as name keys of `names`
+fn mentions(text: &str, names: &HashSet<String>) -> Vec<String> {
+    let text = format!("{} ", text).replace("'s ", " ").replace("\u{2019}s ", " ");
+    let key = espn_ids::name_key(&text);
+    let words: Vec<&str> = key.split(' ').collect();
+    let mut found: Vec<String> = Vec::new();
+    for len in (2..=4).rev() {
+        for window in words.windows(len) {
+            let candidate = window.join(" ");
+            if names.contains(&candidate) && !found.contains(&candidate) {
+                found.push(candidate);
+            }
+        }
+    }
+    found
+}
+
+/// A news item linked to a player
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerLink {
+    /// Index into the feed's items
+    pub item: usize,
+    pub player_id: Uuid,
+    /// `espn_id` for a tagged athlete found in the crosswalk, `name` for a fuzzy match
+    pub method: &'static str,
+    pub confidence: f64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Linked {
+    pub links: Vec<PlayerLink>,
+    /// Tagged athletes no player matched: ESPN ID and name
+    pub unlinked_athletes: Vec<(String, Option<String>)>,
+}
+
+/// Link items to players. Athletes ESPN tagged link through the ESPN ID
+/// crosswalk; tagged athletes it doesn't have, and player names in the text of
+/// untagged items, go through the ESPN ID backfill's matcher at
+/// `MIN_NEWS_CONFIDENCE`, so a name two players share links to neither.
+pub fn link_players(items: &[NewsItem], player_ids: &HashMap<String, Uuid>, players: &[Player]) -> Linked {
+    let mut linked = Linked::default();
+    let mut tagged: HashMap<String, Vec<usize>> = HashMap::new();
+    let mut tagged_names: HashMap<&str, Option<&str>> = HashMap::new();
+    let mut mentioned: HashMap<String, Vec<usize>> = HashMap::new();
+    let names: HashSet<String> = players.iter().map(|player| espn_ids::name_key(&player.name)).collect();
+
+    for (i, item) in items.iter().enumerate() {
+        for (espn_id, name) in &item.athletes {
+            match player_ids.get(espn_id) {
+                Some(player_id) => linked.links.push(PlayerLink { item: i, player_id: *player_id, method: "espn_id", confidence: 1.0 }),
+                None => {
+                    tagged.entry(espn_id.clone()).or_default().push(i);
+                    tagged_names.insert(espn_id, name.as_deref());
+                }
+            }
+        }
+        if item.athletes.is_empty() {
+            let text = format!("{} {}", item.headline, item.summary.as_deref().unwrap_or(""));
+            for name in mentions(&text, &names) {
+                mentioned.entry(name).or_default().push(i);
+            }
+        }
+    }
+
+    let tagged_candidates: Vec<Athlete> = tagged_names
+        .iter()
+        .filter_map(|(espn_id, name)| Some(candidate(espn_id, (*name)?)))
+        .collect();
+    let mentioned_candidates: Vec<Athlete> = mentioned.keys().map(|name| candidate(name, name)).collect();
+    let mut matched = HashSet::new();
+    for (candidates, items_of) in [(&tagged_candidates, &tagged), (&mentioned_candidates, &mentioned)] {
+        for link in espn_ids::match_athletes(players, candidates, MIN_NEWS_CONFIDENCE).links {
+            for item in &items_of[&link.espn_id] {
+                linked.links.push(PlayerLink { item: *item, player_id: link.player_id, method: "name", confidence: link.confidence });
+            }
+            matched.insert(link.espn_id);
+        }
+    }
+
+    linked.unlinked_athletes = tagged_names
+        .iter()
+        .filter(|(espn_id, _)| !matched.contains(**espn_id))
+        .map(|(espn_id, name)| (espn_id.to_string(), name.map(str::to_string)))
+        .collect();
+    linked.unlinked_athletes.sort();
+    linked.links.sort_by_key(|link| (link.item, link.player_id));
+    linked.links.dedup_by_key(|link| (link.item, link.player_id));
+    linked
+}
+
+/// A name from a news item as a match candidate; nothing but the name is known
+fn candidate(id: &str, name: &str) -> Athlete {
+    Athlete { espn_id: id.to_string(), name: name.to_string(), birth_date: None, debut_year: None, position: None, team: None }
+}
+
+/// Upsert linked items into `player_news`, one row per item and player;
+/// returns the rows written
+pub fn write_player_news(
+    client: &mut Client,
+    format: FeedFormat,
+    feed_url: &str,
+    items: &[NewsItem],
+    links: &[PlayerLink],
+) -> Result<usize> {
+    let mut written = 0;
+    let mut tx = client.transaction()?;
+    for link in links {
+        let item = &items[link.item];
+        written += tx
+            .execute(
+                "INSERT INTO player_news (player_id, source, feed_url, item_id, category, headline, summary, url, published_at,
+                                          match_method, match_confidence)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, ROUND($11::float8::numeric, 3))
+                 ON CONFLICT (player_id, source, item_id) DO UPDATE SET
+                     category = EXCLUDED.category,
+                     headline = EXCLUDED.headline,
+                     summary = EXCLUDED.summary,
+                     url = COALESCE(EXCLUDED.url, player_news.url),
+                     published_at = COALESCE(EXCLUDED.published_at, player_news.published_at),
+                     match_method = EXCLUDED.match_method,
+                     match_confidence = EXCLUDED.match_confidence,
+                     updated_at = NOW()
+                 WHERE (player_news.category, player_news.headline, player_news.summary, player_news.match_method,
+                        player_news.match_confidence)
+                       IS DISTINCT FROM (EXCLUDED.category, EXCLUDED.headline, EXCLUDED.summary, EXCLUDED.match_method,
+                                         EXCLUDED.match_confidence)",
+                &[
+                    &link.player_id,
+                    &format.source(),
+                    &feed_url,
+                    &item.item_id,
+                    &item.category.as_str(),
+                    &item.headline,
+                    &item.summary,
+                    &item.url,
+                    &item.published_at,
+                    &link.method,
+                    &link.confidence,
+                ],
+            )
+            .with_context(|| format!("Failed to write news item {}", item.item_id))? as usize;
+    }
+    tx.commit()?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_game_news() {
+        let body = r#"{"boxscore": {"players": []},
+            "article": {"id": 41147002, "type": "Recap", "headline": "Chiefs edge Ravens in opener ",
+                        "description": "Patrick Mahomes threw for 291 yards.",
+                        "story": "<p>KANSAS CITY -- Mahomes &amp; Rice <a href=\"x\">connected</a>.</p><p>Next: Cincinnati.</p>",
+                        "published": "2024-09-06T03:14:00Z",
+                        "links": {"web": {"href": "https://www.espn.com/nfl/recap?gameId=401671789"}}},
+            "news": {"articles": [
+                {"id": "41147002", "headline": "Chiefs edge Ravens in opener"},
+                {"id": 41140000, "headline": "Ravens-Chiefs preview", "published": "2024-09-05T12:00Z"},
+                {"id": 41140001, "headline": " "}
+            ]}}"#;
+        let news = parse_game_news(body).unwrap();
+        assert_eq!(news.len(), 2);
+
+        let recap = &news[0];
+        assert_eq!((recap.article_id.as_str(), recap.kind), ("41147002", NewsKind::Recap));
+        assert_eq!(recap.headline, "Chiefs edge Ravens in opener");
+        assert_eq!(recap.body.as_deref(), Some("KANSAS CITY -- Mahomes & Rice connected.\n\nNext: Cincinnati."));
+        assert_eq!(recap.published_at.unwrap().to_rfc3339(), "2024-09-06T03:14:00+00:00");
+
+        assert_eq!((news[1].kind, news[1].url.as_deref()), (NewsKind::Headline, None));
+        assert!(parse_game_news(r#"{"boxscore": {}}"#).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_feeds_and_classify() {
+        let espn = r#"{"header": "NFL News", "articles": [
+            {"id": 41500001, "headline": "Chiefs' Rice (knee) placed on IR", "published": "2024-10-01T15:04:05Z",
+             "categories": [{"type": "league", "leagueId": 28},
+                            {"type": "athlete", "athleteId": 4241372, "description": "Rashee Rice"}]},
+            {"id": 41500002, "headline": "Bills sign veteran receiver", "description": "The team added depth."}
+        ]}"#;
+        let (format, items) = parse_feed(espn).unwrap();
+        assert_eq!((format, items.len()), (FeedFormat::Espn, 2));
+        assert_eq!(items[0].athletes, vec![("4241372".to_string(), Some("Rashee Rice".to_string()))]);
+        assert_eq!((items[0].category, items[1].category), (NewsCategory::Injury, NewsCategory::Transaction));
+
+        let rss = r#"<?xml version="1.0"?><rss><channel><title>NFL News</title>
+            <item><title><![CDATA[Patrick Mahomes throws for 300 yards]]></title>
+                  <link>https://example.com/news/1</link>
+                  <description>&lt;p&gt;Mahomes &amp;amp; Kelce connected twice.&lt;/p&gt;</description>
+                  <pubDate>Mon, 07 Oct 2024 22:15:00 -0400</pubDate></item>
+            <item><title>No link or guid</title></item>
+        </channel></rss>"#;
+        let (format, items) = parse_feed(rss).unwrap();
+        assert_eq!((format, items.len()), (FeedFormat::Rss, 1));
+        assert_eq!(items[0].item_id, "https://example.com/news/1");
+        assert_eq!(items[0].summary.as_deref(), Some("Mahomes & Kelce connected twice."));
+        assert_eq!(items[0].published_at.unwrap().to_rfc3339(), "2024-10-08T02:15:00+00:00");
+        assert_eq!(items[0].category, NewsCategory::General);
+
+        assert_eq!(classify("Significant signal from the coach"), NewsCategory::General);
+        assert!(parse_feed("not a feed").is_err());
+    }
+
+    #[test]
+    fn test_link_players() {
+        let player = |name: &str| Player {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            position: "QB".to_string(),
+            team: None,
+            birth_date: None,
+            rookie_year: None,
+        };
+        let players = [player("Patrick Mahomes"), player("Josh Allen"), player("Josh Allen"), player("Rashee Rice")];
+        let item = |headline: &str, athletes: &[(&str, &str)]| NewsItem {
+            item_id: headline.to_string(),
+            category: classify(headline),
+            headline: headline.to_string(),
+            summary: None,
+            url: None,
+            published_at: None,
+            athletes: athletes.iter().map(|(id, name)| (id.to_string(), Some(name.to_string()))).collect(),
+        };
+        let items = [
+            item("Mahomes limited in practice", &[("3139477", "Patrick Mahomes")]),
+            item("Rice out for season", &[("4241372", "Rashee Rice"), ("999", "Nobody Known")]),
+            item("Patrick Mahomes's big day; Josh Allen answers", &[]),
+        ];
+        let player_ids = HashMap::from([("3139477".to_string(), players[0].id)]);
+
+        let linked = link_players(&items, &player_ids, &players);
+        let links: Vec<_> = linked.links.iter().map(|link| (link.item, link.player_id, link.method)).collect();
+        assert_eq!(links.len(), 3);
+        assert!(links.contains(&(0, players[0].id, "espn_id")));
+        assert!(links.contains(&(1, players[3].id, "name")));
+        // "Josh Allen" is two players, so only Mahomes links from the text
+        assert!(links.contains(&(2, players[0].id, "name")));
+        assert_eq!(linked.unlinked_athletes, vec![("999".to_string(), Some("Nobody Known".to_string()))]);
+        assert!(linked.links.iter().all(|link| link.confidence >= MIN_NEWS_CONFIDENCE));
+    }
+}
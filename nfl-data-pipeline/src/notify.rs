@@ -0,0 +1,296 @@
+use anyhow::{anyhow, Context, Result};
+use handlebars::{handlebars_helper, Handlebars};
+use log::{debug, warn};
+use serde_json::{json, Value};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::logging;
+use crate::telemetry::RunReport;
+use crate::usage::UsageAlert;
+
+/// The `text` of each notification, as Handlebars templates rendered over the
+/// payload's structured fields. `--notify-templates` can replace any of them
+/// with a `<name>.hbs` file.
+const TEMPLATES: &[(&str, &str)] = &[
+    ("stage_failed", "⚠️ NFL pipeline `{{mode}}` {{seasons}}: {{stage}} failed: {{error}}"),
+    ("run_failed", "❌ NFL pipeline `{{mode}}` {{seasons}} failed to start: {{error}}"),
+    (
+        "run_finished",
+        "{{#if error}}❌ NFL pipeline `{{mode}}` {{seasons}} failed after {{decimals duration_secs 1}}s: {{error}}\
+         {{else}}✅ NFL pipeline `{{mode}}` {{seasons}} finished in {{decimals duration_secs 1}}s: {{rows}} rows across {{len datasets}} datasets{{/if}}\
+         {{#each datasets}}\n• {{@key}}: {{this.rows}} rows{{#if this.failed_batches}}, {{this.failed_batches}} batches rolled back{{/if}}{{/each}}\
+         {{#each errors}}\n⚠️ {{this.stage}}: {{this.error}}{{/each}}",
+    ),
+    (
+        "usage_alerts",
+        "📣 NFL pipeline {{season}} week {{week}}: {{len alerts}} usage alerts\
+         {{#each alerts}}\n• {{this.player_name}} ({{#if this.position}}{{this.position}}{{else}}?{{/if}}): \
+         {{this.metric}} {{this.direction}} {{decimals this.baseline 2}} → {{decimals this.value 2}}{{/each}}",
+    ),
+];
+
+// `{{decimals value 2}}`: a number with fixed decimal places
+handlebars_helper!(decimals: |value: f64, places: u64| format!("{:.*}", places as usize, value));
+
+/// The built-in templates, each also kept as `builtin/<name>` to fall back on,
+/// with any found in `dir` taking their place
+fn templates(dir: Option<&Path>) -> Result<Handlebars<'static>> {
+    let mut templates = Handlebars::new();
+    templates.register_escape_fn(handlebars::no_escape);
+    templates.register_helper("decimals", Box::new(decimals));
+    if let Some(dir) = dir.filter(|dir| !dir.is_dir()) {
+        return Err(anyhow!("--notify-templates {} is not a directory", dir.display()));
+    }
+    for (name, template) in TEMPLATES {
+        templates.register_template_string(&format!("builtin/{}", name), template)?;
+        match dir.map(|dir| dir.join(format!("{}.hbs", name))).filter(|path| path.is_file()) {
+            Some(path) => templates
+                .register_template_file(name, &path)
+                .with_context(|| format!("Invalid notification template {}", path.display()))?,
+            None => templates.register_template_string(name, template)?,
+        }
+    }
+    Ok(templates)
+}
+
+/// Posts run summaries and stage alerts to `--notify-url`.
+///
+/// The payload is JSON with a Slack-ready `text` line, so a Slack (or
+/// Mattermost, Discord `/slack`) incoming webhook works as is; other receivers
+/// can read the structured fields alongside it.
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    url: String,
+    mode: String,
+    seasons: String,
+    /// (stage, error) for each stage that failed this run
+    failures: Vec<(String, String)>,
+    templates: Handlebars<'static>,
+}
+
+impl Notifier {
+    /// `None` unless `--notify-url` is set; fails on a template that doesn't parse
+    pub fn from_config(config: &Config) -> Result<Option<Self>> {
+        let Some(url) = &config.notify_url else {
+            return Ok(None);
+        };
+        let seasons = match config.mode.as_str() {
+            "full" => format!("{}-{}", config.start_year, config.end_year),
+            _ => config.year.to_string(),
+        };
+        Ok(Some(Notifier {
+            url: url.clone(),
+            mode: config.mode.clone(),
+            seasons,
+            failures: Vec::new(),
+            templates: templates(config.notify_templates.as_deref().map(Path::new))?,
+        }))
+    }
+
+    /// Alert that a stage failed; the run goes on
+    pub fn stage_failed(&mut self, stage: &str, e: &anyhow::Error) {
+        let error = format!("{:#}", e);
+        self.post(&self.text("stage_failed", json!({
+            "event": "stage_failed",
+            "run_id": logging::run_id(),
+            "mode": self.mode,
+            "seasons": self.seasons,
+            "stage": stage,
+            "error": error,
+        })));
+        self.failures.push((stage.to_string(), error));
+    }
+
+    /// Summary of a finished run: rows per dataset and every error
+    pub fn run_finished(&self, report: &RunReport, result: &Result<()>) {
+        self.post(&self.summary(report, result));
+    }
+
+    /// Alert that a run failed before the pipeline started (e.g. no database)
+    pub fn run_failed(&self, e: &anyhow::Error) {
+        let error = format!("{:#}", e);
+        self.post(&self.text("run_failed", json!({
+            "event": "run_failed",
+            "run_id": logging::run_id(),
+            "mode": self.mode,
+            "seasons": self.seasons,
+            "error": error,
+        })));
+    }
+
+    /// The usage spikes and drops of a season's newest week
+    pub fn usage_alerts(&self, season: i32, alerts: &[UsageAlert]) {
+        if !alerts.is_empty() {
+            self.post(&self.usage_payload(season, alerts));
+        }
+    }
+
+    fn usage_payload(&self, season: i32, alerts: &[UsageAlert]) -> Value {
+        let week = alerts.iter().map(|alert| alert.week).max().unwrap_or_default();
+        self.text("usage_alerts", json!({
+            "event": "usage_alerts",
+            "run_id": logging::run_id(),
+            "mode": self.mode,
+            "season": season,
+            "week": week,
+            "alerts": alerts.iter().map(|alert| json!({
+                "player_id": alert.player_id,
+                "player_name": alert.player_name,
+                "position": alert.position,
+                "season_type": alert.season_type,
+                "metric": alert.metric,
+                "direction": alert.direction(),
+                "baseline": alert.baseline,
+                "value": alert.value,
+            })).collect::<Vec<_>>(),
+        }))
+    }
+
+    fn summary(&self, report: &RunReport, result: &Result<()>) -> Value {
+        self.text("run_finished", json!({
+            "event": if result.is_ok() { "run_completed" } else { "run_failed" },
+            "run_id": logging::run_id(),
+            "mode": self.mode,
+            "seasons": self.seasons,
+            "duration_secs": report.duration_secs,
+            "rows": report.datasets.values().map(|dataset| dataset.rows).sum::<u64>(),
+            "datasets": report.datasets,
+            "errors": self.failures.iter().map(|(stage, error)| json!({ "stage": stage, "error": error })).collect::<Vec<_>>(),
+            "error": result.as_ref().err().map(|e| format!("{:#}", e)),
+        }))
+    }
+
+    /// Render `template` over the payload's fields into its `text`. A custom
+    /// template that fails to render is logged and the built-in one used instead.
+    fn text(&self, template: &str, mut payload: Value) -> Value {
+        let text = self.templates.render(template, &payload).or_else(|e| {
+            warn!("⚠️  Notification template {} failed, using the built-in one: {}", template, e);
+            self.templates.render(&format!("builtin/{}", template), &payload)
+        });
+        payload["text"] = Value::String(text.unwrap_or_default());
+        payload
+    }
+
+    /// Best effort: a failed post is logged and never fails the run
+    fn post(&self, payload: &Value) {
+        let sent = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .and_then(|http| http.post(&self.url).json(payload).send())
+            .and_then(|response| response.error_for_status());
+        match sent {
+            Ok(_) => debug!("Notification sent ({})", payload["event"]),
+            Err(e) => warn!("⚠️  Notification not sent: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::DatasetMetrics;
+    use crate::usage::UsageMetric;
+    use anyhow::anyhow;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_summary_lists_datasets_and_failed_stages() {
+        let notifier = Notifier {
+            url: "http://localhost:9/hook".to_string(),
+            mode: "year".to_string(),
+            seasons: "2024".to_string(),
+            failures: vec![("Snap Counts".to_string(), "HTTP 404".to_string())],
+            templates: templates(None).unwrap(),
+        };
+        let report = RunReport {
+            version: "0.1.0",
+            os: "linux",
+            arch: "x86_64",
+            mode: "year".to_string(),
+            load_method: "unnest",
+            writers: 1,
+            dry_run: false,
+            duration_secs: 312.4,
+            error_class: None,
+            datasets: BTreeMap::from([
+                ("player_stats", DatasetMetrics { rows: 51000, failed_batches: 1 }),
+                ("rosters", DatasetMetrics { rows: 3000, failed_batches: 0 }),
+            ]),
+            stage_errors: BTreeMap::new(),
+        };
+
+        let ok = notifier.summary(&report, &Ok(()));
+        assert_eq!(ok["event"], "run_completed");
+        assert_eq!(
+            ok["text"],
+            "✅ NFL pipeline `year` 2024 finished in 312.4s: 54000 rows across 2 datasets\n\
+             • player_stats: 51000 rows, 1 batches rolled back\n\
+             • rosters: 3000 rows\n\
+             ⚠️ Snap Counts: HTTP 404"
+        );
+        assert_eq!(ok["errors"][0]["stage"], "Snap Counts");
+        assert_eq!(ok["datasets"]["rosters"]["rows"], 3000);
+
+        let failed = notifier.summary(&report, &Err(anyhow!("Failed to connect to database")));
+        assert_eq!(failed["event"], "run_failed");
+        assert_eq!(failed["error"], "Failed to connect to database");
+        assert!(failed["text"].as_str().unwrap().starts_with("❌ NFL pipeline `year` 2024 failed after 312.4s: Failed to connect to database\n• "));
+    }
+
+    #[test]
+    fn test_usage_payload_lists_each_alert() {
+        let notifier = Notifier {
+            url: "http://localhost:9/hook".to_string(),
+            mode: "update".to_string(),
+            seasons: "2024".to_string(),
+            failures: Vec::new(),
+            templates: templates(None).unwrap(),
+        };
+        let alert = UsageAlert {
+            player_id: uuid::Uuid::nil(),
+            player_name: "Test Receiver".to_string(),
+            position: Some("WR".to_string()),
+            season_type: "REG".to_string(),
+            week: 9,
+            metric: UsageMetric::TargetShare,
+            baseline: 0.12,
+            value: 0.31,
+        };
+
+        let payload = notifier.usage_payload(2024, &[alert]);
+        assert_eq!(payload["event"], "usage_alerts");
+        assert_eq!(
+            payload["text"],
+            "📣 NFL pipeline 2024 week 9: 1 usage alerts\n• Test Receiver (WR): target_share spike 0.12 → 0.31"
+        );
+        assert_eq!(payload["alerts"][0]["metric"], "target_share");
+        assert_eq!(payload["alerts"][0]["direction"], "spike");
+    }
+
+    #[test]
+    fn test_custom_templates_replace_the_built_in_text() {
+        let dir = std::env::temp_dir().join(format!("notify_templates_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("stage_failed.hbs"), "{{stage}} broke in {{mode}}: {{error}}").unwrap();
+        std::fs::write(dir.join("run_failed.hbs"), "{{decimals error 1}}").unwrap();
+        let notifier = Notifier {
+            url: "http://localhost:9/hook".to_string(),
+            mode: "update".to_string(),
+            seasons: "2024".to_string(),
+            failures: Vec::new(),
+            templates: templates(Some(&dir)).unwrap(),
+        };
+
+        let payload = json!({ "mode": "update", "seasons": "2024", "stage": "Rosters", "error": "HTTP 500" });
+        assert_eq!(notifier.text("stage_failed", payload.clone())["text"], "Rosters broke in update: HTTP 500");
+        // a template that can't render falls back to the built-in one
+        assert_eq!(notifier.text("run_failed", payload)["text"], "❌ NFL pipeline `update` 2024 failed to start: HTTP 500");
+
+        std::fs::write(dir.join("usage_alerts.hbs"), "{{#each alerts}}").unwrap();
+        assert!(templates(Some(&dir)).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(templates(Some(&dir)).is_err());
+    }
+}
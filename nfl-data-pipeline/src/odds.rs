@@ -0,0 +1,255 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use log::debug;
+use postgres::Client;
+use serde::Deserialize;
+
+/// The Odds API's upcoming and in-progress NFL games
+pub const ODDS_API_URL: &str = "https://api.the-odds-api.com/v4/sports/americanfootball_nfl/odds";
+/// Dataset name recorded in `source_runs` and telemetry
+pub const DATASET: &str = "odds_api";
+
+#[derive(Debug, Deserialize)]
+struct ApiEvent {
+    id: String,
+    commence_time: String,
+    home_team: String,
+    away_team: String,
+    #[serde(default)]
+    bookmakers: Vec<ApiBookmaker>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiBookmaker {
+    key: String,
+    #[serde(default)]
+    last_update: Option<String>,
+    #[serde(default)]
+    markets: Vec<ApiMarket>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiMarket {
+    key: String,
+    #[serde(default)]
+    outcomes: Vec<ApiOutcome>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiOutcome {
+    name: String,
+    #[serde(default)]
+    price: Option<f64>,
+    #[serde(default)]
+    point: Option<f64>,
+}
+
+/// One bookmaker's lines on a game at the time of a poll. Prices are American odds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameOdds {
+    pub event_id: String,
+    pub kickoff: DateTime<Utc>,
+    /// Full team names, e.g. "Kansas City Chiefs"
+    pub home_team: String,
+    pub away_team: String,
+    pub bookmaker: String,
+    /// When the bookmaker last changed any of its lines on the game
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Expected home margin, as in `games.spread_line` (positive = home favored)
+    pub spread_line: Option<f64>,
+    pub home_spread_price: Option<i32>,
+    pub away_spread_price: Option<i32>,
+    pub total_line: Option<f64>,
+    pub over_price: Option<i32>,
+    pub under_price: Option<i32>,
+    pub home_moneyline: Option<i32>,
+    pub away_moneyline: Option<i32>,
+}
+
+/// Request URL for spreads, totals and moneylines: from the given bookmakers, or
+/// every US bookmaker when none are listed
+pub fn odds_url(base: &str, api_key: &str, bookmakers: &[String]) -> String {
+    let books = match bookmakers.is_empty() {
+        true => "regions=us".to_string(),
+        false => format!("bookmakers={}", bookmakers.join(",")),
+    };
+    format!("{}?apiKey={}&{}&markets=h2h,spreads,totals&oddsFormat=american&dateFormat=iso", base, api_key, books)
+}
+
+fn parse_time(time: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(time).ok().map(|time| time.with_timezone(&Utc))
+}
+
+pub fn parse_odds(body: &str) -> Result<Vec<GameOdds>> {
+    let events: Vec<ApiEvent> = serde_json::from_str(body).context("Invalid odds response")?;
+    let mut odds = Vec::new();
+    for event in events {
+        let Some(kickoff) = parse_time(&event.commence_time) else {
+            continue;
+        };
+        for bookmaker in event.bookmakers {
+            let mut lines = GameOdds {
+                event_id: event.id.clone(),
+                kickoff,
+                home_team: event.home_team.clone(),
+                away_team: event.away_team.clone(),
+                bookmaker: bookmaker.key,
+                updated_at: bookmaker.last_update.as_deref().and_then(parse_time),
+                spread_line: None,
+                home_spread_price: None,
+                away_spread_price: None,
+                total_line: None,
+                over_price: None,
+                under_price: None,
+                home_moneyline: None,
+                away_moneyline: None,
+            };
+            let price = |outcome: &ApiOutcome| outcome.price.map(|price| price.round() as i32);
+            for market in &bookmaker.markets {
+                for outcome in &market.outcomes {
+                    let home = outcome.name == event.home_team;
+                    match (market.key.as_str(), outcome.name.as_str()) {
+                        ("h2h", _) if home => lines.home_moneyline = price(outcome),
+                        ("h2h", name) if name == event.away_team => lines.away_moneyline = price(outcome),
+                        ("spreads", _) if home => {
+                            // The home side's point is the handicap it gets: -3.5 when favored by 3.5
+                            lines.spread_line = outcome.point.map(|point| -point);
+                            lines.home_spread_price = price(outcome);
+                        }
+                        ("spreads", name) if name == event.away_team => lines.away_spread_price = price(outcome),
+                        ("totals", "Over") => {
+                            lines.total_line = outcome.point;
+                            lines.over_price = price(outcome);
+                        }
+                        ("totals", "Under") => lines.under_price = price(outcome),
+                        _ => {}
+                    }
+                }
+            }
+            odds.push(lines);
+        }
+    }
+    Ok(odds)
+}
+
+pub fn fetch_odds(http: &reqwest::blocking::Client, url: &str) -> Result<Vec<GameOdds>> {
+    let response = http
+        .get(url)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .context("Failed to fetch odds")?;
+    if let Some(remaining) = response.headers().get("x-requests-remaining").and_then(|v| v.to_str().ok()) {
+        debug!("Odds API requests remaining: {}", remaining);
+    }
+    parse_odds(&response.text().context("Failed to read odds response")?)
+}
+
+/// Result of writing one poll
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct OddsWrite {
+    /// Bookmaker lines whose game was found
+    pub matched: usize,
+    /// Bookmaker lines that moved since the last capture and were recorded
+    pub recorded: usize,
+}
+
+/// Append each bookmaker's lines to `odds_history` when they differ from its
+/// last capture for the game, so the table holds line movement rather than one
+/// row per poll. Games match on full team names and a kickoff within 36 hours.
+pub fn write_odds(client: &mut Client, odds: &[GameOdds]) -> Result<OddsWrite> {
+    let mut write = OddsWrite::default();
+    let mut tx = client.transaction()?;
+    for lines in odds {
+        let row = tx
+            .query_one(
+                "WITH game AS (
+                     SELECT g.id FROM games g
+                     JOIN teams ht ON ht.id = g.home_team_id
+                     JOIN teams at ON at.id = g.away_team_id
+                     WHERE $1 IN (ht.name, ht.city || ' ' || ht.name, ht.city || ' ' || ht.nickname)
+                       AND $2 IN (at.name, at.city || ' ' || at.name, at.city || ' ' || at.nickname)
+                       AND g.game_date BETWEEN $3::timestamptz - INTERVAL '36 hours' AND $3::timestamptz + INTERVAL '36 hours'
+                     ORDER BY ABS(EXTRACT(EPOCH FROM g.game_date - $3::timestamptz))
+                     LIMIT 1
+                 ), latest AS (
+                     SELECT o.spread_line, o.home_spread_price, o.away_spread_price, o.total_line, o.over_price,
+                            o.under_price, o.home_moneyline, o.away_moneyline
+                     FROM odds_history o JOIN game ON o.game_id = game.id
+                     WHERE o.bookmaker = $4
+                     ORDER BY o.captured_at DESC
+                     LIMIT 1
+                 ), inserted AS (
+                     INSERT INTO odds_history (game_id, event_id, bookmaker, bookmaker_updated_at, spread_line,
+                                               home_spread_price, away_spread_price, total_line, over_price, under_price,
+                                               home_moneyline, away_moneyline)
+                     SELECT game.id, $5, $4, $6, $7::float8::numeric, $8, $9, $10::float8::numeric, $11, $12, $13, $14
+                     FROM game
+                     WHERE NOT EXISTS (
+                         SELECT 1 FROM latest l
+                         WHERE (l.spread_line, l.home_spread_price, l.away_spread_price, l.total_line, l.over_price,
+                                l.under_price, l.home_moneyline, l.away_moneyline)
+                               IS NOT DISTINCT FROM ($7::float8::numeric, $8, $9, $10::float8::numeric, $11, $12, $13, $14)
+                     )
+                     RETURNING 1
+                 )
+                 SELECT (SELECT COUNT(*) FROM game), (SELECT COUNT(*) FROM inserted)",
+                &[
+                    &lines.home_team,
+                    &lines.away_team,
+                    &lines.kickoff,
+                    &lines.bookmaker,
+                    &lines.event_id,
+                    &lines.updated_at,
+                    &lines.spread_line,
+                    &lines.home_spread_price,
+                    &lines.away_spread_price,
+                    &lines.total_line,
+                    &lines.over_price,
+                    &lines.under_price,
+                    &lines.home_moneyline,
+                    &lines.away_moneyline,
+                ],
+            )
+            .with_context(|| format!("Failed to record {} odds for {} @ {}", lines.bookmaker, lines.away_team, lines.home_team))?;
+        let (matched, recorded): (i64, i64) = (row.get(0), row.get(1));
+        write.matched += usize::from(matched > 0);
+        write.recorded += recorded as usize;
+    }
+    tx.commit()?;
+    Ok(write)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_odds() {
+        let body = r#"[{"id": "e912304de2b2ce35b473ce2ecd3d1502", "sport_key": "americanfootball_nfl",
+            "commence_time": "2024-09-06T00:20:00Z", "home_team": "Kansas City Chiefs", "away_team": "Baltimore Ravens",
+            "bookmakers": [
+                {"key": "draftkings", "title": "DraftKings", "last_update": "2024-09-05T18:01:12Z", "markets": [
+                    {"key": "h2h", "outcomes": [{"name": "Baltimore Ravens", "price": 124}, {"name": "Kansas City Chiefs", "price": -148}]},
+                    {"key": "spreads", "outcomes": [{"name": "Baltimore Ravens", "price": -110, "point": 3.0},
+                                                    {"name": "Kansas City Chiefs", "price": -110, "point": -3.0}]},
+                    {"key": "totals", "outcomes": [{"name": "Over", "price": -112, "point": 46.5}, {"name": "Under", "price": -108, "point": 46.5}]}
+                ]},
+                {"key": "fanduel", "markets": [{"key": "h2h", "outcomes": [{"name": "Kansas City Chiefs", "price": -150}]}]}
+            ]},
+            {"id": "bad", "commence_time": "soon", "home_team": "A", "away_team": "B", "bookmakers": []}]"#;
+        let odds = parse_odds(body).unwrap();
+        assert_eq!(odds.len(), 2);
+
+        let draftkings = &odds[0];
+        assert_eq!((draftkings.bookmaker.as_str(), draftkings.kickoff.to_rfc3339().as_str()), ("draftkings", "2024-09-06T00:20:00+00:00"));
+        assert_eq!((draftkings.spread_line, draftkings.home_spread_price, draftkings.away_spread_price), (Some(3.0), Some(-110), Some(-110)));
+        assert_eq!((draftkings.total_line, draftkings.over_price, draftkings.under_price), (Some(46.5), Some(-112), Some(-108)));
+        assert_eq!((draftkings.home_moneyline, draftkings.away_moneyline), (Some(-148), Some(124)));
+
+        let fanduel = &odds[1];
+        assert_eq!((fanduel.home_moneyline, fanduel.spread_line, fanduel.updated_at), (Some(-150), None, None));
+
+        assert!(odds_url(ODDS_API_URL, "k", &[]).contains("?apiKey=k&regions=us&markets=h2h,spreads,totals"));
+        assert!(odds_url(ODDS_API_URL, "k", &["draftkings".to_string(), "fanduel".to_string()]).contains("&bookmakers=draftkings,fanduel&"));
+    }
+}
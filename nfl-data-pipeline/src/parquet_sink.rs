@@ -0,0 +1,192 @@
+use anyhow::{anyhow, Context, Result};
+use arrow_array::builder::{Float64Builder, Int64Builder, StringBuilder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema};
+use log::info;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::parser::{Game, PlayerStat, RosterPlayer};
+use crate::sink::{self, ColumnType, StatsSink, Table, Value};
+
+/// The column files are partitioned on; it lives in the directory name, not the file
+const PARTITION: &str = "season";
+
+/// `--sink parquet`: one Parquet file per table and season under `--out`,
+/// Hive-style (`game_stats/season=2024/part-0.parquet`), so DuckDB, Polars,
+/// Spark and pyarrow read the directory as one partitioned table. Rows are held
+/// until their season's file is finished; re-importing a season replaces its files.
+pub struct ParquetSink {
+    out: PathBuf,
+    tables: BTreeMap<&'static str, Table>,
+    /// Rows waiting to be written, by table and season
+    pending: BTreeMap<(&'static str, i64), Vec<Vec<Value>>>,
+}
+
+impl ParquetSink {
+    pub fn open(out: &Path) -> Result<Self> {
+        fs::create_dir_all(out).with_context(|| format!("Failed to create {}", out.display()))?;
+        let tables = [sink::players_table(), sink::games_table(), sink::game_stats_table()]
+            .into_iter()
+            .map(|table| (table.name, table))
+            .collect();
+        Ok(ParquetSink { out: out.to_path_buf(), tables, pending: BTreeMap::new() })
+    }
+
+    fn buffer<T>(&mut self, table: &'static str, rows: &[T], row: impl Fn(&T) -> Vec<Value>) -> Result<()> {
+        let season = partition_index(&self.tables[table])?;
+        for item in rows {
+            let values = row(item);
+            let Value::Integer(year) = values[season] else {
+                return Err(anyhow!("{} row without a season", table));
+            };
+            self.pending.entry((table, year)).or_default().push(values);
+        }
+        Ok(())
+    }
+
+    fn partition_path(&self, table: &str, season: i64) -> PathBuf {
+        self.out.join(table).join(format!("{}={}", PARTITION, season)).join("part-0.parquet")
+    }
+}
+
+fn partition_index(table: &Table) -> Result<usize> {
+    table
+        .columns
+        .iter()
+        .position(|(name, _)| *name == PARTITION)
+        .ok_or_else(|| anyhow!("{} has no {} column", table.name, PARTITION))
+}
+
+/// The rows as a record batch of `table`'s columns, minus the partition column
+fn record_batch(table: &Table, rows: &[Vec<Value>]) -> Result<RecordBatch> {
+    let skip = partition_index(table)?;
+    let mut fields = Vec::new();
+    let mut arrays: Vec<ArrayRef> = Vec::new();
+    for (i, (name, ty)) in table.columns.iter().enumerate().filter(|(i, _)| *i != skip) {
+        let (data_type, array): (DataType, ArrayRef) = match ty {
+            ColumnType::Integer => {
+                let mut builder = Int64Builder::with_capacity(rows.len());
+                for row in rows {
+                    builder.append_option(match row[i] {
+                        Value::Integer(value) => Some(value),
+                        _ => None,
+                    });
+                }
+                (DataType::Int64, Arc::new(builder.finish()))
+            }
+            ColumnType::Real => {
+                let mut builder = Float64Builder::with_capacity(rows.len());
+                for row in rows {
+                    builder.append_option(match row[i] {
+                        Value::Real(value) => Some(value),
+                        Value::Integer(value) => Some(value as f64),
+                        _ => None,
+                    });
+                }
+                (DataType::Float64, Arc::new(builder.finish()))
+            }
+            ColumnType::Text => {
+                let mut builder = StringBuilder::new();
+                for row in rows {
+                    builder.append_option(match &row[i] {
+                        Value::Text(value) => Some(value.as_str()),
+                        _ => None,
+                    });
+                }
+                (DataType::Utf8, Arc::new(builder.finish()))
+            }
+        };
+        let nullable = !table.key.contains(name);
+        fields.push(Field::new(*name, data_type, nullable));
+        arrays.push(array);
+    }
+    Ok(RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?)
+}
+
+fn write_parquet(path: &Path, batch: &RecordBatch) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // Written beside the target and renamed, so readers never see half a file
+    let partial = path.with_extension("parquet.partial");
+    let file = File::create(&partial).with_context(|| format!("Failed to create {}", partial.display()))?;
+    let properties = WriterProperties::builder().set_compression(Compression::ZSTD(ZstdLevel::default())).build();
+    let mut writer = ArrowWriter::try_new(file, batch.schema(), Some(properties))?;
+    writer.write(batch)?;
+    writer.close()?;
+    fs::rename(&partial, path).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
+impl StatsSink for ParquetSink {
+    fn write_players(&mut self, players: &[RosterPlayer]) -> Result<()> {
+        self.buffer("players", players, sink::player_row)
+    }
+
+    fn write_games(&mut self, games: &[Game]) -> Result<()> {
+        self.buffer("games", games, sink::game_row)
+    }
+
+    fn write_stats(&mut self, stats: &[PlayerStat]) -> Result<()> {
+        self.buffer("game_stats", stats, sink::stat_row)
+    }
+
+    fn finish_table(&mut self, table: &'static str) -> Result<()> {
+        let seasons: Vec<i64> = self.pending.keys().filter(|(name, _)| *name == table).map(|(_, season)| *season).collect();
+        for season in seasons {
+            let rows = self.pending.remove(&(table, season)).unwrap_or_default();
+            let path = self.partition_path(table, season);
+            write_parquet(&path, &record_batch(&self.tables[table], &rows)?)?;
+            info!("  📦 {} rows → {}", rows.len(), path.display());
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let tables: Vec<&'static str> = self.tables.keys().copied().collect();
+        for table in tables {
+            self.finish_table(table)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source::MemorySource;
+    use crate::datasets;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    #[test]
+    fn test_partitioned_files() {
+        let out = std::env::temp_dir().join(format!("parquet-sink-{}", std::process::id()));
+        let source = MemorySource::default().with_file(
+            &datasets::SCHEDULES,
+            2024,
+            "game_id,season,game_type,week,gameday,away_team,home_team,away_score,home_score\n\
+             2024_01_BAL_KC,2024,REG,1,2024-09-05,BAL,KC,20,27\n\
+             2024_01_GB_PHI,2024,REG,1,2024-09-06,GB,PHI,29,34\n",
+        );
+        let config = crate::config::Config::from_args(&[], |name| (name == "DATABASE_URL").then(|| "postgres://db".to_string())).unwrap();
+        let mut sink = ParquetSink::open(&out).unwrap();
+        sink::import_season(&config, &source, &mut sink, 2024).unwrap();
+        sink.finish().unwrap();
+
+        let path = out.join("games/season=2024/part-0.parquet");
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let metadata = reader.metadata();
+        assert_eq!(metadata.file_metadata().num_rows(), 2);
+        let schema = metadata.file_metadata().schema_descr();
+        assert_eq!(schema.num_columns(), sink::games_table().columns.len() - 1);
+        assert!(!(0..schema.num_columns()).any(|i| schema.column(i).name() == PARTITION));
+        assert!(!out.join("game_stats").exists());
+        fs::remove_dir_all(&out).unwrap();
+    }
+}
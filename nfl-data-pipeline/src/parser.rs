@@ -3,90 +3,214 @@ use serde::{Deserialize, Serialize};
 /// Player from roster CSV
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct RosterPlayer {
+    #[serde(deserialize_with = "flex::i32")]
     pub season: i32,
     pub team: String,
     pub position: String,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub depth_chart_position: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub jersey_number: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub status: Option<String>,
     pub full_name: String,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub first_name: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub last_name: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub birth_date: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub height: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub weight: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub college: Option<String>,
     pub gsis_id: String,  // NFL player ID
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub espn_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub sportradar_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub yahoo_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub rotowire_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub pff_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub pfr_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub fantasy_data_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub sleeper_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub years_exp: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub headshot_url: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub entry_year: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub rookie_year: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub draft_club: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub draft_number: Option<i32>,
+    /// Only present in the weekly roster files
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub week: Option<i32>,
 }
 
 /// Player stat from player_stats CSV
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct PlayerStat {
     pub player_id: String,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub player_name: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub player_display_name: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub position: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub position_group: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub headshot_url: Option<String>,
+    #[serde(default, alias = "team", deserialize_with = "flex::opt_string")]
     pub recent_team: Option<String>,
+    #[serde(deserialize_with = "flex::i32")]
     pub season: i32,
+    #[serde(deserialize_with = "flex::i32")]
     pub week: i32,
     pub season_type: String,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub completions: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub attempts: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub passing_yards: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub passing_tds: Option<i32>,
+    #[serde(default, alias = "passing_interceptions", deserialize_with = "flex::opt_i32")]
     pub interceptions: Option<i32>,
+    #[serde(default, alias = "sacks_suffered", deserialize_with = "flex::opt_f64")]
     pub sacks: Option<f64>,
+    #[serde(default, alias = "sack_yards_lost", deserialize_with = "flex::opt_f64")]
     pub sack_yards: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub sack_fumbles: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub sack_fumbles_lost: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub passing_air_yards: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub passing_yards_after_catch: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub passing_first_downs: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub passing_epa: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub passing_2pt_conversions: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub pacr: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub dakota: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub carries: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub rushing_yards: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub rushing_tds: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub rushing_fumbles: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub rushing_fumbles_lost: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub rushing_first_downs: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub rushing_epa: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub rushing_2pt_conversions: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub receptions: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub targets: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub receiving_yards: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub receiving_tds: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub receiving_fumbles: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub receiving_fumbles_lost: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub receiving_air_yards: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub receiving_yards_after_catch: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub receiving_first_downs: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub receiving_epa: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub receiving_2pt_conversions: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub racr: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub target_share: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub air_yards_share: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub wopr: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub special_teams_tds: Option<i32>,
+    // Defense (combined stats_player files and the legacy defensive files)
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub def_tackles_solo: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub def_tackle_assists: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub def_tackles_for_loss: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub def_fumbles_forced: Option<i32>,
+    /// Half sacks are credited when two defenders share one
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub def_sacks: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub def_qb_hits: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub def_interceptions: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub def_pass_defended: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub def_tds: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub def_safeties: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub fumble_recovery_opp: Option<i32>,
+    // Kicking
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub fg_made: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub fg_att: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub fg_long: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub fg_made_0_19: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub fg_made_20_29: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub fg_made_30_39: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub fg_made_40_49: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub fg_made_50_59: Option<i32>,
+    #[serde(default, rename = "fg_made_60_", deserialize_with = "flex::opt_i32")]
+    pub fg_made_60: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub pat_made: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub pat_att: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub fantasy_points: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub fantasy_points_ppr: Option<f64>,
 }
 
@@ -94,78 +218,821 @@ pub struct PlayerStat {
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Game {
     pub game_id: String,
+    #[serde(deserialize_with = "flex::i32")]
     pub season: i32,
     pub game_type: String,
+    #[serde(deserialize_with = "flex::i32")]
     pub week: i32,
     pub gameday: String,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub weekday: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub gametime: Option<String>,
     pub away_team: String,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub away_score: Option<i32>,
     pub home_team: String,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub home_score: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub location: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub result: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub total: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub overtime: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub old_game_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub gsis: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub nfl_detail_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub pfr: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub pff: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub espn: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub ftn: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub away_rest: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub home_rest: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub away_moneyline: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub home_moneyline: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub spread_line: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub away_spread_odds: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub home_spread_odds: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub total_line: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub under_odds: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub over_odds: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub div_game: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub roof: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub surface: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub temp: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub wind: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub away_qb_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub home_qb_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub away_qb_name: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub home_qb_name: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub away_coach: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub home_coach: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub referee: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub stadium_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub stadium: Option<String>,
 }
 
 /// Next Gen Stats (Passing)
+///
+/// NGS files have renamed a few columns over the years; aliases accept the
+/// older names so a 2016+ backfill parses every season.
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct NGSPassing {
+    #[serde(deserialize_with = "flex::i32")]
     pub season: i32,
+    #[serde(default = "default_season_type")]
     pub season_type: String,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub week: Option<i32>,
+    #[serde(alias = "player_name")]
     pub player_display_name: String,
+    #[serde(default, deserialize_with = "flex::opt_string")]
     pub player_position: Option<String>,
+    #[serde(alias = "team")]
     pub team_abbr: String,
+    #[serde(default, alias = "gsis_id", deserialize_with = "flex::opt_string")]
     pub player_gsis_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub attempts: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub pass_yards: Option<i32>,
+    #[serde(default, alias = "pass_tds", deserialize_with = "flex::opt_i32")]
     pub pass_touchdowns: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub interceptions: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub passer_rating: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub completions: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub completion_percentage: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub expected_completion_percentage: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub completion_percentage_above_expectation: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub avg_time_to_throw: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub avg_completed_air_yards: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub avg_intended_air_yards: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub avg_air_yards_differential: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub aggressiveness: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
     pub max_completed_air_distance: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub avg_air_yards_to_sticks: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
     pub passer_rating_under_pressure: Option<f64>,
 }
+
+/// Next Gen Stats (Rushing)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct NGSRushing {
+    #[serde(deserialize_with = "flex::i32")]
+    pub season: i32,
+    #[serde(default = "default_season_type")]
+    pub season_type: String,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub week: Option<i32>,
+    #[serde(alias = "player_name")]
+    pub player_display_name: String,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub player_position: Option<String>,
+    #[serde(alias = "team")]
+    pub team_abbr: String,
+    #[serde(default, alias = "gsis_id", deserialize_with = "flex::opt_string")]
+    pub player_gsis_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub efficiency: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub percent_attempts_gte_eight_defenders: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub avg_time_to_los: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub rush_attempts: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub rush_yards: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub expected_rush_yards: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub rush_yards_over_expected: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub avg_rush_yards: Option<f64>,
+    #[serde(default, alias = "rush_tds", deserialize_with = "flex::opt_i32")]
+    pub rush_touchdowns: Option<i32>,
+}
+
+/// Next Gen Stats (Receiving)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct NGSReceiving {
+    #[serde(deserialize_with = "flex::i32")]
+    pub season: i32,
+    #[serde(default = "default_season_type")]
+    pub season_type: String,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub week: Option<i32>,
+    #[serde(alias = "player_name")]
+    pub player_display_name: String,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub player_position: Option<String>,
+    #[serde(alias = "team")]
+    pub team_abbr: String,
+    #[serde(default, alias = "gsis_id", deserialize_with = "flex::opt_string")]
+    pub player_gsis_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub avg_cushion: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub avg_separation: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub avg_intended_air_yards: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub percent_share_of_intended_air_yards: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub receptions: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub targets: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub catch_percentage: Option<f64>,
+    #[serde(default, alias = "rec_yards", deserialize_with = "flex::opt_i32")]
+    pub yards: Option<i32>,
+    #[serde(default, alias = "rec_tds", deserialize_with = "flex::opt_i32")]
+    pub rec_touchdowns: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub avg_yac: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub avg_expected_yac: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub avg_yac_above_expectation: Option<f64>,
+}
+
+/// Early NGS files predate the season_type column and only cover the regular season
+fn default_season_type() -> String {
+    "REG".to_string()
+}
+
+/// Weekly injury report row from injuries CSV
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Injury {
+    #[serde(deserialize_with = "flex::i32")]
+    pub season: i32,
+    pub game_type: String,
+    pub team: String,
+    #[serde(deserialize_with = "flex::i32")]
+    pub week: i32,
+    pub gsis_id: String,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub position: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub full_name: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub report_primary_injury: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub report_secondary_injury: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub report_status: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub practice_primary_injury: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub practice_secondary_injury: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub practice_status: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub date_modified: Option<String>,
+}
+
+/// Weekly depth chart entry from depth_charts CSV
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DepthChartEntry {
+    #[serde(deserialize_with = "flex::i32")]
+    pub season: i32,
+    #[serde(alias = "team")]
+    pub club_code: String,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub week: Option<i32>,
+    pub game_type: String,
+    /// Rank within the slot (1 = starter)
+    #[serde(deserialize_with = "flex::i32")]
+    pub depth_team: i32,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub formation: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub gsis_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub jersey_number: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub position: Option<String>,
+    pub depth_position: String,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub full_name: Option<String>,
+}
+
+/// Play from play_by_play CSV, limited to the columns used for efficiency metrics
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PbpPlay {
+    pub game_id: String,
+    #[serde(deserialize_with = "flex::i32")]
+    pub season: i32,
+    #[serde(default = "default_season_type")]
+    pub season_type: String,
+    #[serde(deserialize_with = "flex::i32")]
+    pub week: i32,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub posteam: Option<String>,
+    /// 1 on dropbacks (including sacks and scrambles)
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub pass: Option<f64>,
+    /// 1 on designed runs
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub rush: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub epa: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub success: Option<f64>,
+    /// Possession team's win probability before the play
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub wp: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub passer_player_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub rusher_player_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub receiver_player_id: Option<String>,
+}
+
+/// Per-game snap counts from snap_counts CSV (PFR player IDs)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SnapCount {
+    pub game_id: String,
+    #[serde(deserialize_with = "flex::i32")]
+    pub season: i32,
+    pub game_type: String,
+    #[serde(deserialize_with = "flex::i32")]
+    pub week: i32,
+    pub player: String,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub pfr_player_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub position: Option<String>,
+    pub team: String,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub offense_snaps: Option<i32>,
+    /// Share of the team's offensive snaps, 0-1
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub offense_pct: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub defense_snaps: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub defense_pct: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub st_snaps: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub st_pct: Option<f64>,
+}
+
+/// Draft pick from draft_picks CSV (all seasons in one file)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DraftPick {
+    #[serde(deserialize_with = "flex::i32")]
+    pub season: i32,
+    #[serde(deserialize_with = "flex::i32")]
+    pub round: i32,
+    #[serde(deserialize_with = "flex::i32")]
+    pub pick: i32,
+    pub team: String,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub gsis_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub pfr_player_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub cfb_player_id: Option<String>,
+    pub pfr_player_name: String,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub position: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub category: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub side: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub college: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub age: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_bool")]
+    pub hof: Option<bool>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub allpro: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub probowls: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub seasons_started: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub w_av: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub car_av: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub games: Option<i32>,
+}
+
+/// Combine participant from combine CSV (all seasons in one file)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CombineResult {
+    #[serde(deserialize_with = "flex::i32")]
+    pub season: i32,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub draft_team: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub draft_round: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub draft_ovr: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub pfr_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub cfb_id: Option<String>,
+    pub player_name: String,
+    pub pos: String,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub school: Option<String>,
+    /// Height as feet-inches, e.g. "6-2"
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub ht: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub wt: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub forty: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub bench: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub vertical: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub broad_jump: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub cone: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub shuttle: Option<f64>,
+}
+
+/// Franchise from nflverse's teams_colors_logos CSV (one row per abbreviation,
+/// including relocated franchises' old ones)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TeamRecord {
+    pub team_abbr: String,
+    /// Full name, e.g. "Kansas City Chiefs"
+    pub team_name: String,
+    /// Nickname, e.g. "Chiefs"
+    pub team_nick: String,
+    pub team_conf: String,
+    /// Conference and division, e.g. "AFC West"
+    pub team_division: String,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub team_color: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub team_color2: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub team_logo_espn: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub team_wordmark: Option<String>,
+}
+
+/// Player contract from OverTheCap's contract history (all seasons in one file).
+/// Money columns are in millions of dollars.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Contract {
+    pub player: String,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub position: Option<String>,
+    pub team: String,
+    #[serde(default, deserialize_with = "flex::opt_bool")]
+    pub is_active: Option<bool>,
+    #[serde(deserialize_with = "flex::i32")]
+    pub year_signed: i32,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub years: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub value: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub apy: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub guaranteed: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_f64")]
+    pub apy_cap_pct: Option<f64>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub otc_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub gsis_id: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub draft_year: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub draft_round: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub draft_overall: Option<i32>,
+}
+
+/// College season stats from cfbfastR player stats CSV
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CollegeStat {
+    #[serde(deserialize_with = "flex::i32")]
+    pub season: i32,
+    pub athlete_id: String,
+    pub player: String,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub team: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub conference: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_string")]
+    pub position: Option<String>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub games: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub passing_completions: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub passing_attempts: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub passing_yards: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub passing_tds: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub passing_int: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub rushing_car: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub rushing_yds: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub rushing_td: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub receiving_rec: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub receiving_yds: Option<i32>,
+    #[serde(default, deserialize_with = "flex::opt_i32")]
+    pub receiving_td: Option<i32>,
+}
+
+/// Parquet files are read row by row into the same string records the CSV path
+/// produces, so the parser structs (and their lenient `flex` fields) apply unchanged.
+pub mod parquet_rows {
+    use anyhow::Result;
+    use chrono::{DateTime, NaiveDate};
+    use csv::StringRecord;
+    use parquet::file::reader::{ChunkReader, FileReader, SerializedFileReader};
+    use parquet::record::reader::RowIter;
+    use parquet::record::Field;
+
+    use crate::spill::Seekable;
+
+    pub type Records = Box<dyn Iterator<Item = Result<StringRecord>>>;
+
+    /// Column names and a row iterator for a Parquet file
+    pub fn read(source: Seekable) -> Result<(StringRecord, Records)> {
+        match source {
+            Seekable::Memory(bytes) => read_from(bytes),
+            Seekable::Disk(file) => read_from(file),
+        }
+    }
+
+    fn read_from<R: ChunkReader + 'static>(source: R) -> Result<(StringRecord, Records)> {
+        let reader = SerializedFileReader::new(source)?;
+        let header: StringRecord = reader
+            .metadata()
+            .file_metadata()
+            .schema()
+            .get_fields()
+            .iter()
+            .map(|field| field.name())
+            .collect();
+
+        let rows = RowIter::from_file_into(Box::new(reader)).map(|row| {
+            let row = row?;
+            Ok(row.get_column_iter().map(|(_, field)| cell(field)).collect())
+        });
+        Ok((header, Box::new(rows)))
+    }
+
+    /// Render a cell the way nflverse writes it in CSV: nulls empty, whole numbers
+    /// without a trailing `.0`, dates as YYYY-MM-DD
+    pub fn cell(field: &Field) -> String {
+        match field {
+            Field::Null => String::new(),
+            Field::Str(value) => value.clone(),
+            Field::Float(value) => number(*value as f64),
+            Field::Double(value) => number(*value),
+            Field::Date(days) => NaiveDate::default()
+                .checked_add_signed(chrono::Duration::days(*days as i64))
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+            Field::TimestampMillis(ms) => timestamp(DateTime::from_timestamp_millis(*ms)),
+            Field::TimestampMicros(us) => timestamp(DateTime::from_timestamp_micros(*us)),
+            other => other.to_string(),
+        }
+    }
+
+    fn number(value: f64) -> String {
+        if value.is_finite() && value.fract() == 0.0 && value.abs() < 1e15 {
+            (value as i64).to_string()
+        } else {
+            value.to_string()
+        }
+    }
+
+    fn timestamp(value: Option<DateTime<chrono::Utc>>) -> String {
+        value.map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string()).unwrap_or_default()
+    }
+}
+
+/// Lenient cell parsing for nflverse files.
+///
+/// Historical files mix empty cells, `NA`, and floats like `12.0` in integer
+/// columns; strict deserialization drops those rows entirely.
+mod flex {
+    use serde::de::{self, Deserializer, Visitor};
+    use serde::Deserialize;
+    use std::fmt;
+
+    /// Cell values that mean "no data"
+    fn is_missing(value: &str) -> bool {
+        matches!(value, "" | "NA" | "N/A" | "NaN" | "NULL" | "null")
+    }
+
+    struct NumberVisitor;
+
+    impl<'de> Visitor<'de> for NumberVisitor {
+        type Value = Option<f64>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a number, an empty cell or NA")
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+            Ok(Some(v as f64))
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+            Ok(Some(v as f64))
+        }
+
+        fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+            Ok(Some(v))
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            let v = v.trim();
+            if is_missing(v) {
+                return Ok(None);
+            }
+            v.parse().map(Some).map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))
+        }
+
+        fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+            // nflverse writes some 0/1 flags as TRUE/FALSE
+            Ok(Some(if v { 1.0 } else { 0.0 }))
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+            d.deserialize_any(self)
+        }
+    }
+
+    pub fn opt_f64<'de, D: Deserializer<'de>>(d: D) -> Result<Option<f64>, D::Error> {
+        d.deserialize_option(NumberVisitor)
+    }
+
+    /// Integers, accepting integral floats such as `12.0`
+    pub fn opt_i32<'de, D: Deserializer<'de>>(d: D) -> Result<Option<i32>, D::Error> {
+        match opt_f64(d)? {
+            Some(v) if v.fract() == 0.0 && v >= i32::MIN as f64 && v <= i32::MAX as f64 => Ok(Some(v as i32)),
+            Some(v) => Err(de::Error::invalid_value(de::Unexpected::Float(v), &"an integer")),
+            None => Ok(None),
+        }
+    }
+
+    pub fn i32<'de, D: Deserializer<'de>>(d: D) -> Result<i32, D::Error> {
+        opt_i32(d)?.ok_or_else(|| de::Error::custom("missing required integer"))
+    }
+
+    /// Booleans written as TRUE/FALSE, true/false or 1/0
+    pub fn opt_bool<'de, D: Deserializer<'de>>(d: D) -> Result<Option<bool>, D::Error> {
+        match Option::<String>::deserialize(d)?.as_deref().map(str::trim) {
+            None => Ok(None),
+            Some(v) if is_missing(v) => Ok(None),
+            Some(v) => match v.to_lowercase().as_str() {
+                "true" | "1" => Ok(Some(true)),
+                "false" | "0" => Ok(Some(false)),
+                _ => Err(de::Error::invalid_value(de::Unexpected::Str(v), &"a boolean")),
+            },
+        }
+    }
+
+    pub fn opt_string<'de, D: Deserializer<'de>>(d: D) -> Result<Option<String>, D::Error> {
+        Ok(Option::<String>::deserialize(d)?.filter(|v| !is_missing(v.trim())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse<T: serde::de::DeserializeOwned>(csv: &str) -> Result<T, csv::Error> {
+        csv::Reader::from_reader(csv.as_bytes()).deserialize().next().unwrap()
+    }
+
+    #[test]
+    fn test_parquet_cells_match_csv_text() {
+        use parquet::record::Field;
+        assert_eq!(parquet_rows::cell(&Field::Null), "");
+        assert_eq!(parquet_rows::cell(&Field::Double(2024.0)), "2024");
+        assert_eq!(parquet_rows::cell(&Field::Double(0.0)), "0");
+        assert_eq!(parquet_rows::cell(&Field::Double(-0.25)), "-0.25");
+        assert_eq!(parquet_rows::cell(&Field::Str("KC".to_string())), "KC");
+        assert_eq!(parquet_rows::cell(&Field::Date(19971)), "2024-09-05");
+        assert_eq!(parquet_rows::cell(&Field::Bool(true)), "true");
+    }
+
+    #[test]
+    fn test_parquet_rows_read_as_records() {
+        use parquet::data_type::{ByteArray, ByteArrayType, DoubleType};
+        use parquet::file::writer::SerializedFileWriter;
+        use parquet::schema::parser::parse_message_type;
+        use std::sync::Arc;
+
+        let schema = parse_message_type(
+            "message roster { REQUIRED BYTE_ARRAY team (UTF8); OPTIONAL DOUBLE jersey_number; }",
+        )
+        .unwrap();
+        let mut buffer = Vec::new();
+        let mut writer = SerializedFileWriter::new(&mut buffer, Arc::new(schema), Default::default()).unwrap();
+        let mut group = writer.next_row_group().unwrap();
+        let mut column = group.next_column().unwrap().unwrap();
+        column
+            .typed::<ByteArrayType>()
+            .write_batch(&[ByteArray::from("KC"), ByteArray::from("BUF")], None, None)
+            .unwrap();
+        column.close().unwrap();
+        let mut column = group.next_column().unwrap().unwrap();
+        column.typed::<DoubleType>().write_batch(&[15.0], Some(&[1, 0]), None).unwrap();
+        column.close().unwrap();
+        group.close().unwrap();
+        writer.close().unwrap();
+
+        let (header, rows) = parquet_rows::read(crate::spill::Seekable::Memory(buffer.into())).unwrap();
+        assert_eq!(header, vec!["team", "jersey_number"]);
+        let rows: Vec<_> = rows.map(|r| r.unwrap()).collect();
+        assert_eq!(rows[0], vec!["KC", "15"]);
+        assert_eq!(rows[1], vec!["BUF", ""]);
+    }
+
+    #[test]
+    fn test_na_and_empty_cells_are_none() {
+        let stat: PlayerStat = parse(
+            "player_id,player_name,season,week,season_type,passing_tds,passing_epa,recent_team\n\
+             00-0033873,NA,2024,1,REG,NA,,KC\n",
+        )
+        .unwrap();
+
+        assert_eq!(stat.player_name, None);
+        assert_eq!(stat.passing_tds, None);
+        assert_eq!(stat.passing_epa, None);
+        assert_eq!(stat.recent_team.as_deref(), Some("KC"));
+    }
+
+    #[test]
+    fn test_integral_floats_in_integer_columns() {
+        let stat: PlayerStat = parse(
+            "player_id,season,week,season_type,passing_tds,interceptions\n\
+             00-0033873,2024.0,3.0,REG,2.0,1\n",
+        )
+        .unwrap();
+
+        assert_eq!(stat.season, 2024);
+        assert_eq!(stat.week, 3);
+        assert_eq!(stat.passing_tds, Some(2));
+        assert_eq!(stat.interceptions, Some(1));
+    }
+
+    #[test]
+    fn test_fractional_value_in_integer_column_is_rejected() {
+        let result: Result<PlayerStat, _> = parse(
+            "player_id,season,week,season_type,passing_tds\n\
+             00-0033873,2024,1,REG,1.5\n",
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_renamed_stat_columns() {
+        let stat: PlayerStat = parse(
+            "player_id,season,week,season_type,team,passing_interceptions,sacks_suffered\n\
+             00-0033873,2024,1,REG,KC,2,3\n",
+        )
+        .unwrap();
+
+        assert_eq!(stat.recent_team.as_deref(), Some("KC"));
+        assert_eq!(stat.interceptions, Some(2));
+        assert_eq!(stat.sacks, Some(3.0));
+    }
+
+    #[test]
+    fn test_uppercase_booleans() {
+        let pick: DraftPick = parse(
+            "season,round,pick,team,pfr_player_name,hof,w_av\n\
+             2005,1,24,GNB,Aaron Rodgers,FALSE,NA\n",
+        )
+        .unwrap();
+
+        assert_eq!(pick.hof, Some(false));
+        assert_eq!(pick.w_av, None);
+    }
+
+    #[test]
+    fn test_ids_keep_leading_zeros() {
+        let player: RosterPlayer = parse(
+            "season,team,position,full_name,gsis_id,espn_id,jersey_number\n\
+             2024,KC,QB,Patrick Mahomes,00-0033873,03139477,15\n",
+        )
+        .unwrap();
+
+        assert_eq!(player.espn_id.as_deref(), Some("03139477"));
+        assert_eq!(player.jersey_number, Some(15));
+    }
+}
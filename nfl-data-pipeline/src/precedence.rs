@@ -0,0 +1,377 @@
+use anyhow::{anyhow, Result};
+
+use crate::config::SourcePriority;
+use crate::game_status::{self, GameStatus};
+
+/// Where a value came from, as recorded in a row's `provenance`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// nflverse release files
+    Nflverse,
+    /// nflverse files read from `--source-dir`; ranks with nflverse
+    Local,
+    /// ESPN's site API: schedules, box scores and rosters
+    Espn,
+    /// ESPN's scoreboard, polled by `--mode live`
+    Live,
+}
+
+/// Sources a rule can order (local files rank with nflverse)
+const RANKED: [Source; 3] = [Source::Nflverse, Source::Espn, Source::Live];
+
+impl Source {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Source::Nflverse => "nflverse",
+            Source::Local => "local",
+            Source::Espn => "espn",
+            Source::Live => "live",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        RANKED.into_iter().find(|source| source.as_str() == value)
+    }
+
+    fn ranked_as(self) -> Source {
+        match self {
+            Source::Local => Source::Nflverse,
+            source => source,
+        }
+    }
+}
+
+/// Columns that share a precedence rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// games.home_score, away_score
+    Scores,
+    /// games.game_date
+    Kickoff,
+    /// games.season_type, week
+    Schedule,
+    /// games.venue_name, venue_city, venue_state, attendance
+    Venue,
+    /// players.team_id, position, jersey_number, height_inches, weight_pounds, college
+    Roster,
+}
+
+const FIELDS: [Field; 5] = [Field::Scores, Field::Kickoff, Field::Schedule, Field::Venue, Field::Roster];
+
+impl Field {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Field::Scores => "scores",
+            Field::Kickoff => "kickoff",
+            Field::Schedule => "schedule",
+            Field::Venue => "venue",
+            Field::Roster => "roster",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        FIELDS.into_iter().find(|field| field.as_str() == value)
+    }
+
+    /// Whether the rows it belongs to have a game status
+    fn per_game(&self) -> bool {
+        *self != Field::Roster
+    }
+}
+
+/// Source order for a field, optionally only while a game has a status
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub field: Field,
+    pub status: Option<GameStatus>,
+    pub order: Vec<Source>,
+}
+
+/// Which source wins each field. Fields without a rule follow
+/// `--source-priority`, except scores of a game in progress, where the live
+/// scoreboard is ahead of everything else.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Precedence {
+    priority: SourcePriority,
+    rules: Vec<Rule>,
+}
+
+impl Precedence {
+    pub fn new(priority: SourcePriority) -> Self {
+        Precedence { priority, rules: Vec::new() }
+    }
+
+    /// Parse `--precedence`: rules separated by semicolons, each
+    /// `field[@status]=source,source,...`, e.g.
+    /// `scores@in_progress=live,espn,nflverse;venue=espn,nflverse`
+    pub fn parse(priority: SourcePriority, value: &str) -> Result<Self> {
+        let mut rules = Vec::new();
+        for entry in value.split(';').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let invalid = || anyhow!("Invalid --precedence rule: {} (expected field[@status]=source,source)", entry);
+            let (target, sources) = entry.split_once('=').ok_or_else(invalid)?;
+            let (field, status) = match target.trim().split_once('@') {
+                Some((field, status)) => (field, Some(status)),
+                None => (target.trim(), None),
+            };
+            let field = Field::parse(field).ok_or_else(|| {
+                anyhow!("Unknown --precedence field: {} (expected scores, kickoff, schedule, venue or roster)", field)
+            })?;
+            let status = match status {
+                Some(status) if field.per_game() => Some(
+                    GameStatus::parse(status)
+                        .ok_or_else(|| anyhow!("Unknown game status in --precedence: {} (expected scheduled, in_progress or final)", status))?,
+                ),
+                Some(_) => return Err(anyhow!("--precedence: {} has no game status", field.as_str())),
+                None => None,
+            };
+            let mut order = Vec::new();
+            for source in sources.split(',').map(str::trim) {
+                let source = Source::parse(source)
+                    .ok_or_else(|| anyhow!("Unknown --precedence source: {} (expected nflverse, espn or live)", source))?;
+                if order.contains(&source) {
+                    return Err(invalid());
+                }
+                order.push(source);
+            }
+            rules.push(Rule { field, status, order });
+        }
+        Ok(Precedence { priority, rules })
+    }
+
+    /// The explicit rules, in `--precedence` syntax
+    pub fn rules_string(&self) -> String {
+        self.rules
+            .iter()
+            .map(|rule| {
+                let status = rule.status.map(|status| format!("@{}", status.as_str())).unwrap_or_default();
+                let order: Vec<&str> = rule.order.iter().map(Source::as_str).collect();
+                format!("{}{}={}", rule.field.as_str(), status, order.join(","))
+            })
+            .collect::<Vec<_>>()
+            .join(";")
+    }
+
+    pub fn has_rules(&self) -> bool {
+        !self.rules.is_empty()
+    }
+
+    /// Sources of a field in priority order, for a game with `status`
+    pub fn order(&self, field: Field, status: Option<GameStatus>) -> Vec<Source> {
+        let default = match self.priority {
+            SourcePriority::Nflverse => [Source::Nflverse, Source::Espn, Source::Live],
+            SourcePriority::Espn => [Source::Espn, Source::Live, Source::Nflverse],
+        };
+        let explicit = self
+            .rules
+            .iter()
+            .filter(|rule| rule.field == field)
+            .find(|rule| rule.status.is_some() && rule.status == status)
+            .or_else(|| self.rules.iter().find(|rule| rule.field == field && rule.status.is_none()));
+        let mut order = match explicit {
+            Some(rule) => rule.order.clone(),
+            None if field == Field::Scores && status == Some(GameStatus::InProgress) => {
+                vec![Source::Live, Source::Espn, Source::Nflverse]
+            }
+            None => default.to_vec(),
+        };
+        order.extend(default.into_iter().filter(|source| !order.contains(source)).collect::<Vec<_>>());
+        order
+    }
+
+    fn rank(&self, field: Field, status: Option<GameStatus>, source: Source) -> usize {
+        let source = source.ranked_as();
+        self.order(field, status).iter().position(|s| *s == source).unwrap_or(RANKED.len())
+    }
+
+    /// SQL rank of the source named by `source_sql` (lower wins). Unknown
+    /// provenance ranks below every source. With `status_sql`, the rank
+    /// follows the game's status.
+    fn rank_sql(&self, field: Field, status_sql: Option<&str>, source_sql: &str) -> String {
+        let for_status = |status: Option<GameStatus>| {
+            let arms: String = [Source::Nflverse, Source::Local, Source::Espn, Source::Live]
+                .iter()
+                .map(|source| format!(" WHEN '{}' THEN {}", source.as_str(), self.rank(field, status, *source)))
+                .collect();
+            format!("CASE {}{} ELSE {} END", source_sql, arms, RANKED.len())
+        };
+        let Some(status_sql) = status_sql.filter(|_| field.per_game()) else {
+            return for_status(None);
+        };
+        let statuses = [GameStatus::Scheduled, GameStatus::InProgress, GameStatus::Final];
+        if statuses.iter().all(|status| self.order(field, Some(*status)) == self.order(field, None)) {
+            return for_status(None);
+        }
+        let arms: String = statuses
+            .iter()
+            .map(|status| format!(" WHEN '{}' THEN {}", status.as_str(), for_status(Some(*status))))
+            .collect();
+        format!("CASE {}{} ELSE {} END", status_sql, arms, for_status(None))
+    }
+}
+
+/// One source's values merged into a stored row, column by column. A value
+/// replaces the stored one when its source ranks at least as high as the
+/// source that wrote it; a missing value clears a column its own source wrote,
+/// unless the column is only filled. `provenance` records the source of every
+/// column the merge sets.
+pub struct Merge<'a> {
+    precedence: &'a Precedence,
+    source: Source,
+    /// Name or alias of the stored row
+    row: &'a str,
+    /// SQL for the game's status after the write
+    status: Option<String>,
+    columns: Vec<Column<'a>>,
+}
+
+struct Column<'a> {
+    field: Field,
+    name: &'a str,
+    value: String,
+    /// Whether a missing value clears the stored one
+    clears: bool,
+}
+
+impl<'a> Merge<'a> {
+    pub fn new(precedence: &'a Precedence, source: Source, row: &'a str) -> Self {
+        Merge { precedence, source, row, status: None, columns: Vec::new() }
+    }
+
+    pub fn status(mut self, status_sql: impl Into<String>) -> Self {
+        self.status = Some(status_sql.into());
+        self
+    }
+
+    /// A column the source always provides, so a missing value is news
+    pub fn column(mut self, field: Field, column: &'a str, value_sql: impl Into<String>) -> Self {
+        self.columns.push(Column { field, name: column, value: value_sql.into(), clears: true });
+        self
+    }
+
+    /// A column the source only sometimes provides: a missing value keeps the stored one
+    pub fn fill(mut self, field: Field, column: &'a str, value_sql: impl Into<String>) -> Self {
+        self.columns.push(Column { field, name: column, value: value_sql.into(), clears: false });
+        self
+    }
+
+    /// Whether the incoming value of a column wins
+    fn takes(&self, column: &Column) -> String {
+        let status = self.status.as_deref();
+        let stored = self.precedence.rank_sql(column.field, status, &format!("{}.provenance->>'{}'", self.row, column.name));
+        let incoming = self.precedence.rank_sql(column.field, status, &format!("'{}'", self.source.as_str()));
+        let value = &column.value;
+        let takes = format!("({value}) IS NOT NULL AND ({}.{} IS NULL OR {stored} >= {incoming})", self.row, column.name);
+        match column.clears {
+            true => format!("({takes} OR ({value}) IS NULL AND {stored} = {incoming})"),
+            false => format!("({takes})"),
+        }
+    }
+
+    /// A column's value after the merge
+    pub fn value_sql(&self, column: &str) -> String {
+        let column = self.columns.iter().find(|c| c.name == column).expect("merged column");
+        format!("CASE WHEN {} THEN {} ELSE {}.{} END", self.takes(column), column.value, self.row, column.name)
+    }
+
+    /// SET assignments for every column, then `provenance`
+    pub fn set_sql(&self) -> String {
+        let mut assignments: Vec<String> =
+            self.columns.iter().map(|c| format!("{} = {}", c.name, self.value_sql(c.name))).collect();
+        let cleared: Vec<String> = self
+            .columns
+            .iter()
+            .filter(|c| c.clears)
+            .map(|c| format!("CASE WHEN ({}) IS NULL AND {} THEN '{}' END", c.value, self.takes(c), c.name))
+            .collect();
+        let set: Vec<String> = self
+            .columns
+            .iter()
+            .map(|c| {
+                format!("'{}', CASE WHEN ({}) IS NOT NULL AND {} THEN '{}' END", c.name, c.value, self.takes(c), self.source.as_str())
+            })
+            .collect();
+        let kept = match cleared.is_empty() {
+            true => format!("{}.provenance", self.row),
+            false => format!("({}.provenance - array_remove(ARRAY[{}], NULL))", self.row, cleared.join(", ")),
+        };
+        assignments.push(format!("provenance = {} || jsonb_strip_nulls(jsonb_build_object({}))", kept, set.join(", ")));
+        assignments.join(",\n")
+    }
+}
+
+/// SET assignments merging a schedule upsert's `EXCLUDED` row into `games`
+pub fn schedule_upsert_sql(precedence: &Precedence, source: Source) -> String {
+    Merge::new(precedence, source, "games")
+        .status(game_status::merge_sql("games.status", "EXCLUDED.status"))
+        .column(Field::Schedule, "season_type", "EXCLUDED.season_type")
+        .column(Field::Kickoff, "game_date", "EXCLUDED.game_date")
+        .column(Field::Scores, "home_score", "EXCLUDED.home_score")
+        .column(Field::Scores, "away_score", "EXCLUDED.away_score")
+        .fill(Field::Venue, "venue_name", "EXCLUDED.venue_name")
+        .set_sql()
+}
+
+/// SET assignments merging a roster upsert's `EXCLUDED` row into `players`
+pub fn roster_upsert_sql(precedence: &Precedence, source: Source) -> String {
+    ["team_id", "position", "jersey_number", "height_inches", "weight_pounds", "college"]
+        .into_iter()
+        .fold(Merge::new(precedence, source, "players"), |merge, column| {
+            merge.column(Field::Roster, column, format!("EXCLUDED.{}", column))
+        })
+        .set_sql()
+}
+
+/// `provenance` of a new row: `source` for each (column, value SQL) with a value
+pub fn insert_sql(source: Source, columns: &[(&str, &str)]) -> String {
+    let pairs: Vec<String> = columns
+        .iter()
+        .map(|(column, value)| format!("'{column}', CASE WHEN ({value}) IS NOT NULL THEN '{}' END", source.as_str()))
+        .collect();
+    format!("jsonb_strip_nulls(jsonb_build_object({}))", pairs.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_follows_rules_then_priority() {
+        let precedence = Precedence::new(SourcePriority::Nflverse);
+        use Source::*;
+        assert_eq!(precedence.order(Field::Scores, Some(GameStatus::InProgress)), [Live, Espn, Nflverse]);
+        assert_eq!(precedence.order(Field::Scores, Some(GameStatus::Final)), [Nflverse, Espn, Live]);
+        assert_eq!(precedence.rank(Field::Roster, None, Local), 0);
+
+        let precedence = Precedence::parse(SourcePriority::Nflverse, "venue=espn; scores@final=espn").unwrap();
+        assert_eq!(precedence.order(Field::Venue, None), [Espn, Nflverse, Live]);
+        assert_eq!(precedence.order(Field::Scores, Some(GameStatus::Final)), [Espn, Nflverse, Live]);
+        assert_eq!(precedence.rules_string(), "venue=espn;scores@final=espn");
+
+        assert!(Precedence::parse(SourcePriority::Nflverse, "roster@final=espn").is_err());
+        assert!(Precedence::parse(SourcePriority::Nflverse, "scores=espn,espn").is_err());
+        assert!(Precedence::parse(SourcePriority::Nflverse, "odds=espn").is_err());
+    }
+
+    #[test]
+    fn test_merge_sql() {
+        let precedence = Precedence::new(SourcePriority::Nflverse);
+        let merge = Merge::new(&precedence, Source::Live, "g")
+            .status("$5")
+            .column(Field::Scores, "home_score", "$9::int");
+        let value = merge.value_sql("home_score");
+        // Ranks depend on the status only for scores
+        assert!(value.starts_with("CASE WHEN (($9::int) IS NOT NULL AND (g.home_score IS NULL OR CASE $5 WHEN 'scheduled'"));
+        assert!(value.contains("WHEN 'in_progress' THEN CASE g.provenance->>'home_score' WHEN 'nflverse' THEN 2 WHEN 'local' THEN 2 WHEN 'espn' THEN 1 WHEN 'live' THEN 0 ELSE 3 END"));
+        assert!(merge.set_sql().contains("provenance = (g.provenance - array_remove(ARRAY[CASE WHEN ($9::int) IS NULL AND"));
+
+        let roster = Merge::new(&precedence, Source::Espn, "p").status("$5").column(Field::Roster, "college", "$7::text");
+        assert!(roster.value_sql("college").contains("(p.college IS NULL OR CASE p.provenance->>'college' WHEN 'nflverse' THEN 0"));
+        let venue = Merge::new(&precedence, Source::Espn, "g").fill(Field::Venue, "venue_name", "$10::text");
+        assert!(!venue.value_sql("venue_name").contains("IS NULL AND"));
+        assert!(venue.set_sql().contains("provenance = g.provenance || jsonb_strip_nulls("));
+        assert_eq!(
+            insert_sql(Source::Espn, &[("home_score", "$7")]),
+            "jsonb_strip_nulls(jsonb_build_object('home_score', CASE WHEN ($7) IS NOT NULL THEN 'espn' END))"
+        );
+    }
+}
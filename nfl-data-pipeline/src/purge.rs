@@ -0,0 +1,120 @@
+use anyhow::{anyhow, Context, Result};
+use postgres::Client;
+use serde::Serialize;
+
+use crate::datasets;
+
+/// A dataset whose rows for a season can be deleted: its table and the extra
+/// filter telling its rows apart from other datasets sharing the table
+#[derive(Debug)]
+struct Purgeable {
+    dataset: &'static str,
+    table: &'static str,
+    filter: Option<&'static str>,
+}
+
+/// Season datasets that nothing else references. Schedules (`games`) are
+/// referenced by stats, plays and odds, and rosters write `players`, which spans
+/// seasons; re-importing those overwrites them in place.
+const PURGEABLE: &[Purgeable] = &[
+    Purgeable { dataset: datasets::WEEKLY_ROSTERS.name, table: datasets::WEEKLY_ROSTERS.target_table, filter: None },
+    Purgeable { dataset: datasets::PLAYER_STATS.name, table: datasets::PLAYER_STATS.target_table, filter: None },
+    Purgeable { dataset: datasets::INJURIES.name, table: datasets::INJURIES.target_table, filter: None },
+    Purgeable { dataset: datasets::DEPTH_CHARTS.name, table: datasets::DEPTH_CHARTS.target_table, filter: None },
+    Purgeable { dataset: datasets::SNAP_COUNTS.name, table: datasets::SNAP_COUNTS.target_table, filter: None },
+    Purgeable { dataset: datasets::PLAY_BY_PLAY.name, table: datasets::PLAY_BY_PLAY.target_table, filter: None },
+    Purgeable {
+        dataset: datasets::NGS_PASSING.name,
+        table: datasets::NGS_PASSING.target_table,
+        filter: Some("stat_type = 'passing'"),
+    },
+    Purgeable {
+        dataset: datasets::NGS_RUSHING.name,
+        table: datasets::NGS_RUSHING.target_table,
+        filter: Some("stat_type = 'rushing'"),
+    },
+    Purgeable {
+        dataset: datasets::NGS_RECEIVING.name,
+        table: datasets::NGS_RECEIVING.target_table,
+        filter: Some("stat_type = 'receiving'"),
+    },
+    Purgeable { dataset: datasets::COLLEGE_STATS.name, table: datasets::COLLEGE_STATS.target_table, filter: None },
+];
+
+/// What purging one dataset's season deleted (or would delete)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Purged {
+    pub dataset: &'static str,
+    pub table: &'static str,
+    pub rows: u64,
+    /// `import_progress` rows cleared, season and weeks
+    pub progress: u64,
+    pub lineage: u64,
+}
+
+/// The datasets `--dataset` names: one of `PURGEABLE`, or `all` for every one
+fn select(dataset: &str) -> Result<Vec<&'static Purgeable>> {
+    if dataset == "all" {
+        return Ok(PURGEABLE.iter().collect());
+    }
+    PURGEABLE.iter().find(|purgeable| purgeable.dataset == dataset).map(|purgeable| vec![purgeable]).ok_or_else(|| {
+        let names: Vec<&str> = PURGEABLE.iter().map(|purgeable| purgeable.dataset).collect();
+        anyhow!("Cannot purge {}: expected all or one of {}", dataset, names.join(", "))
+    })
+}
+
+fn delete_sql(purgeable: &Purgeable) -> String {
+    match purgeable.filter {
+        Some(filter) => format!("DELETE FROM {} WHERE season = $1 AND {}", purgeable.table, filter),
+        None => format!("DELETE FROM {} WHERE season = $1", purgeable.table),
+    }
+}
+
+/// Delete a season of `dataset` (or `all`) with its import progress, fingerprint
+/// included, and lineage, so the next run imports it from scratch. Everything
+/// happens in one transaction, which a dry run rolls back after counting.
+/// Derived tables refresh on the next `--mode materialize`, since their inputs changed.
+pub fn purge(client: &mut Client, season: i32, dataset: &str, dry_run: bool) -> Result<Vec<Purged>> {
+    let selected = select(dataset)?;
+    let mut tx = client.transaction()?;
+    let mut purged = Vec::new();
+    for purgeable in selected {
+        let rows = tx
+            .execute(delete_sql(purgeable).as_str(), &[&season])
+            .with_context(|| format!("Failed to purge {} {}", purgeable.dataset, season))?;
+        let progress = tx.execute(
+            "DELETE FROM import_progress WHERE season = $1 AND data_type = $2",
+            &[&season, &purgeable.dataset],
+        )?;
+        let lineage = tx.execute(
+            "DELETE FROM data_lineage l USING source_files f
+             WHERE l.source_file_id = f.id AND l.dataset = $2 AND f.dataset = $2 AND f.season = $1",
+            &[&season, &purgeable.dataset],
+        )?;
+        purged.push(Purged { dataset: purgeable.dataset, table: purgeable.table, rows, progress, lineage });
+    }
+
+    if dry_run {
+        tx.rollback()?;
+    } else {
+        tx.commit().with_context(|| format!("Failed to commit purge of {}", season))?;
+    }
+    Ok(purged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_purgeable_datasets() {
+        assert_eq!(delete_sql(select("player_stats").unwrap()[0]), "DELETE FROM game_stats WHERE season = $1");
+        assert_eq!(
+            delete_sql(select("ngs_rushing").unwrap()[0]),
+            "DELETE FROM advanced_stats WHERE season = $1 AND stat_type = 'rushing'"
+        );
+        assert_eq!(select("all").unwrap().len(), PURGEABLE.len());
+        let e = select("schedule").unwrap_err().to_string();
+        assert!(e.starts_with("Cannot purge schedule: expected all or one of weekly_rosters, player_stats"));
+    }
+}
@@ -0,0 +1,269 @@
+use anyhow::{Result, Context};
+use postgres::Client;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use crate::elo::{Elo, EloGame};
+
+/// Ridge term added to the rating system so it stays solvable while parts of the
+/// league haven't played each other yet (each group is then centered on zero)
+const RIDGE: f64 = 1e-6;
+
+/// A completed game
+#[derive(Debug, Clone, Copy)]
+pub struct GameResult {
+    pub home_team: Uuid,
+    pub away_team: Uuid,
+    pub home_score: i32,
+    pub away_score: i32,
+}
+
+/// Simple Rating System values for one team
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TeamRating {
+    pub team_id: Uuid,
+    pub games: i32,
+    /// Average margin of victory
+    pub mov: f64,
+    /// Strength of schedule: average rating of opponents
+    pub sos: f64,
+    /// `mov + sos`
+    pub srs: f64,
+}
+
+/// Simple Rating System: each team's rating is its average margin plus the average
+/// rating of its opponents, centered so the league averages zero.
+///
+/// Solved directly as the equivalent least-squares system
+/// `games_i * r_i - sum(opponent ratings) = total margin_i`.
+pub fn simple_rating_system(games: &[GameResult]) -> Vec<TeamRating> {
+    let mut index: BTreeMap<Uuid, usize> = BTreeMap::new();
+    for game in games {
+        for team in [game.home_team, game.away_team] {
+            let next = index.len();
+            index.entry(team).or_insert(next);
+        }
+    }
+
+    let n = index.len();
+    let mut matrix = vec![vec![0.0; n]; n];
+    let mut margins = vec![0.0; n];
+    let mut played = vec![0; n];
+
+    for game in games {
+        let (h, a) = (index[&game.home_team], index[&game.away_team]);
+        let margin = (game.home_score - game.away_score) as f64;
+        matrix[h][h] += 1.0;
+        matrix[a][a] += 1.0;
+        matrix[h][a] -= 1.0;
+        matrix[a][h] -= 1.0;
+        margins[h] += margin;
+        margins[a] -= margin;
+        played[h] += 1;
+        played[a] += 1;
+    }
+    for (i, row) in matrix.iter_mut().enumerate() {
+        row[i] += RIDGE;
+    }
+
+    let ratings = solve(matrix, margins.clone());
+
+    let mut opponents: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for game in games {
+        let (h, a) = (index[&game.home_team], index[&game.away_team]);
+        opponents[h].push(a);
+        opponents[a].push(h);
+    }
+
+    index
+        .into_iter()
+        .map(|(team_id, i)| {
+            let games = played[i];
+            let mov = margins[i] / games as f64;
+            let sos = opponents[i].iter().map(|&j| ratings[j]).sum::<f64>() / games as f64;
+            TeamRating {
+                team_id,
+                games,
+                mov,
+                sos,
+                srs: mov + sos,
+            }
+        })
+        .collect()
+}
+
+/// Gaussian elimination with partial pivoting
+fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Vec<f64> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))
+            .unwrap_or(col);
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        for row in col + 1..n {
+            let factor = a[row][col] / a[col][col];
+            if factor == 0.0 {
+                continue;
+            }
+            let (upper, lower) = a.split_at_mut(row);
+            for (value, pivot_value) in lower[0][col..].iter_mut().zip(&upper[col][col..]) {
+                *value -= factor * pivot_value;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    x
+}
+
+/// Final Elo rating of each team in a season, from its last rated week
+fn final_elo(client: &mut Client, season: i32) -> Result<BTreeMap<Uuid, f64>> {
+    let rows = client
+        .query(
+            "SELECT DISTINCT ON (team_id) team_id, elo::float8
+             FROM team_ratings
+             WHERE season = $1 AND elo IS NOT NULL
+             ORDER BY team_id, season_type = 'POST' DESC, week DESC",
+            &[&season],
+        )
+        .context("Failed to load previous Elo ratings")?;
+    Ok(rows.iter().map(|row| (row.get(0), row.get(1))).collect())
+}
+
+/// Rebuild a season's week-by-week ratings from completed games.
+///
+/// Each week's row rates teams on every game through that week: SRS over all of
+/// them, and Elo carried game by game from the previous season's final ratings
+/// (import seasons in order so those exist). Returns the number of rating rows
+/// written.
+pub fn refresh_team_ratings(client: &mut Client, season: i32) -> Result<u64> {
+    let mut elo = Elo::new(&final_elo(client, season - 1)?);
+    let rows = client
+        .query(
+            "SELECT season_type, week, home_team_id, away_team_id, home_score, away_score,
+                    location = 'Neutral', home_rest, away_rest
+             FROM games
+             WHERE season = $1 AND season_type <> 'PRE'
+               AND home_score IS NOT NULL AND away_score IS NOT NULL
+               AND home_team_id IS NOT NULL AND away_team_id IS NOT NULL
+             ORDER BY season_type = 'POST', week, game_date",
+            &[&season],
+        )
+        .context("Failed to load completed games")?;
+
+    let mut tx = client.transaction()?;
+    tx.execute("DELETE FROM team_ratings WHERE season = $1", &[&season])?;
+
+    let mut games = Vec::with_capacity(rows.len());
+    let mut written = 0;
+    for (i, row) in rows.iter().enumerate() {
+        let result = GameResult {
+            home_team: row.get(2),
+            away_team: row.get(3),
+            home_score: row.get(4),
+            away_score: row.get(5),
+        };
+        games.push(result);
+
+        let season_type: String = row.get(0);
+        elo.update(&EloGame {
+            result,
+            playoff: season_type == "POST",
+            neutral_site: row.get::<_, Option<bool>>(6).unwrap_or(false),
+            home_rest: row.get(7),
+            away_rest: row.get(8),
+        });
+
+        let week: i32 = row.get(1);
+        let week_done = rows.get(i + 1).is_none_or(|next| {
+            next.get::<_, String>(0) != season_type || next.get::<_, i32>(1) != week
+        });
+        if !week_done {
+            continue;
+        }
+
+        for rating in simple_rating_system(&games) {
+            written += tx.execute(
+                "INSERT INTO team_ratings (season, season_type, week, team_id, games, mov, sos, srs, elo, updated_at)
+                 VALUES ($1, $2, $3, $4, $5, $6::float8, $7::float8, $8::float8, $9::float8, NOW())",
+                &[
+                    &season,
+                    &season_type,
+                    &week,
+                    &rating.team_id,
+                    &rating.games,
+                    &rating.mov,
+                    &rating.sos,
+                    &rating.srs,
+                    &elo.rating(rating.team_id),
+                ],
+            )?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(home: Uuid, away: Uuid, home_score: i32, away_score: i32) -> GameResult {
+        GameResult { home_team: home, away_team: away, home_score, away_score }
+    }
+
+    fn rating(ratings: &[TeamRating], team: Uuid) -> &TeamRating {
+        ratings.iter().find(|r| r.team_id == team).unwrap()
+    }
+
+    #[test]
+    fn test_round_robin_matches_definition() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let ratings = simple_rating_system(&[game(a, b, 24, 14), game(b, c, 21, 17), game(c, a, 20, 27)]);
+
+        let total: f64 = ratings.iter().map(|r| r.srs).sum();
+        assert!(total.abs() < 1e-6);
+
+        // Every rating equals its margin plus the average opponent rating
+        for r in &ratings {
+            assert!((r.srs - (r.mov + r.sos)).abs() < 1e-9);
+        }
+        let ra = rating(&ratings, a);
+        assert!((ra.mov - 8.5).abs() < 1e-9);
+        assert!((ra.srs - (ra.mov + (rating(&ratings, b).srs + rating(&ratings, c).srs) / 2.0)).abs() < 1e-4);
+        assert!(ra.srs > rating(&ratings, b).srs && rating(&ratings, b).srs > rating(&ratings, c).srs);
+    }
+
+    #[test]
+    fn test_disconnected_opening_week() {
+        let (a, b, c, d) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let ratings = simple_rating_system(&[game(a, b, 30, 20), game(c, d, 17, 14)]);
+
+        assert!((rating(&ratings, a).srs - 5.0).abs() < 1e-3);
+        assert!((rating(&ratings, b).srs + 5.0).abs() < 1e-3);
+        assert!((rating(&ratings, c).srs - 1.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_schedule_strength_adjusts_margin() {
+        // A and C both beat B by 7, but C also lost to the strong team D
+        let (a, b, c, d) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let ratings = simple_rating_system(&[
+            game(a, b, 21, 14),
+            game(c, b, 28, 21),
+            game(d, c, 35, 17),
+            game(d, a, 24, 10),
+        ]);
+        assert!(rating(&ratings, c).sos > rating(&ratings, b).sos);
+        assert!(rating(&ratings, d).srs > rating(&ratings, a).srs);
+    }
+}
@@ -0,0 +1,175 @@
+use anyhow::{anyhow, Result};
+use datafusion::arrow::csv::WriterBuilder;
+use datafusion::arrow::util::pretty::pretty_format_batches;
+use datafusion::prelude::{CsvReadOptions, ParquetReadOptions, SessionContext};
+use log::{debug, info};
+use std::path::PathBuf;
+
+use crate::cache::DownloadCache;
+use crate::config::Config;
+use crate::datasets::{self, CsvDialect, Dataset, SourceFormat};
+
+/// A dataset-season file found on disk, registered as a table named after the dataset
+#[derive(Debug, Clone)]
+pub struct RawTable {
+    pub name: &'static str,
+    pub path: PathBuf,
+    pub format: SourceFormat,
+    pub dialect: CsvDialect,
+}
+
+/// Where query-raw looks for files: `--source-dir` by file name, then the download cache
+struct Locator {
+    source_dir: Option<PathBuf>,
+    cache: Option<DownloadCache>,
+}
+
+impl Locator {
+    /// The season's file, preferring Parquet when both formats are on disk.
+    /// Gzipped files are skipped (the query engine is built without compression).
+    fn find<T>(&self, dataset: &Dataset<T>, year: i32) -> Option<RawTable> {
+        [SourceFormat::Parquet, SourceFormat::Csv].into_iter().find_map(|format| {
+            let url = dataset.url_as(year, format);
+            if url.ends_with(".gz") {
+                debug!("Skipping gzipped {}", dataset.name);
+                return None;
+            }
+            let file_name = url.rsplit('/').next().unwrap_or(&url);
+            let path = self
+                .source_dir
+                .as_ref()
+                .map(|dir| dir.join(file_name))
+                .filter(|p| p.is_file())
+                .or_else(|| self.cache.as_ref().and_then(|cache| cache.path(&url)))?;
+            Some(RawTable { name: dataset.name, path, format, dialect: dataset.dialect })
+        })
+    }
+}
+
+/// Every dataset with a file on disk for the season
+fn find_tables(locator: &Locator, year: i32) -> Vec<RawTable> {
+    [
+        locator.find(&datasets::ROSTERS, year),
+        locator.find(&datasets::WEEKLY_ROSTERS, year),
+        locator.find(&datasets::PLAYER_STATS, year),
+        locator.find(&datasets::SCHEDULES, year),
+        locator.find(&datasets::NGS_PASSING, year),
+        locator.find(&datasets::NGS_RUSHING, year),
+        locator.find(&datasets::NGS_RECEIVING, year),
+        locator.find(&datasets::INJURIES, year),
+        locator.find(&datasets::DEPTH_CHARTS, year),
+        locator.find(&datasets::SNAP_COUNTS, year),
+        locator.find(&datasets::PLAY_BY_PLAY, year),
+        locator.find(&datasets::DRAFT_PICKS, year),
+        locator.find(&datasets::COMBINE, year),
+        locator.find(&datasets::COLLEGE_STATS, year),
+        locator.find(&datasets::CONTRACTS, year),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+async fn register(ctx: &SessionContext, table: &RawTable) -> Result<()> {
+    let path = table.path.to_string_lossy();
+    match table.format {
+        SourceFormat::Parquet => {
+            let extension = table.path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+            let options = ParquetReadOptions { file_extension: extension, ..Default::default() };
+            ctx.register_parquet(table.name, path.as_ref(), options).await?;
+        }
+        SourceFormat::Csv => {
+            let extension = table.path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+            let options = CsvReadOptions::new()
+                .delimiter(table.dialect.delimiter)
+                .quote(table.dialect.quote)
+                .file_extension(extension);
+            ctx.register_csv(table.name, path.as_ref(), options).await?;
+        }
+    }
+    Ok(())
+}
+
+/// `--mode query-raw`: run `--sql` against the season's source files as they were
+/// downloaded, before any parsing or transformation. Tables are named after the
+/// datasets (rosters, player_stats, schedule, ...). Prints a table, or CSV with
+/// `--format csv`.
+///
+/// CSV cells are not cleaned: NA tokens stay literal, so a column containing them
+/// is text and needs `TRY_CAST` for arithmetic.
+pub fn run(config: &Config) -> Result<()> {
+    let sql = config
+        .sql
+        .as_deref()
+        .ok_or_else(|| anyhow!("--mode query-raw requires --sql \"SELECT ...\""))?;
+    let locator = Locator {
+        source_dir: config.source_dir.as_ref().map(PathBuf::from),
+        cache: config.cache_dir.as_ref().map(DownloadCache::new).transpose()?,
+    };
+    if locator.source_dir.is_none() && locator.cache.is_none() {
+        return Err(anyhow!("--mode query-raw reads local files; set --cache-dir or --source-dir"));
+    }
+
+    let tables = find_tables(&locator, config.year);
+    if tables.is_empty() {
+        return Err(anyhow!("No {} source files on disk; run an import with --cache-dir first", config.year));
+    }
+    for table in &tables {
+        info!("  📄 {} → {}", table.name, table.path.display());
+    }
+
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+    let batches = runtime.block_on(async {
+        let ctx = SessionContext::new();
+        for table in &tables {
+            register(&ctx, table).await?;
+        }
+        anyhow::Ok(ctx.sql(sql).await?.collect().await?)
+    })?;
+
+    if config.format.as_deref() == Some("csv") {
+        let mut writer = WriterBuilder::new().with_header(true).build(std::io::stdout());
+        for batch in &batches {
+            writer.write(batch)?;
+        }
+    } else {
+        println!("{}", pretty_format_batches(&batches)?);
+    }
+    info!("{} rows", batches.iter().map(|b| b.num_rows()).sum::<usize>());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_over_source_dir() {
+        let dir = std::env::temp_dir().join(format!("nfl-pipeline-raw-query-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("injuries_2024.csv"),
+            "season,gsis_id,week,report_status\n2024,00-0033873,1,Out\n2024,00-0036355,1,NA\n",
+        )
+        .unwrap();
+        let locator = Locator { source_dir: Some(dir.clone()), cache: None };
+
+        let tables = find_tables(&locator, 2024);
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].name, "injuries");
+
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let batches = runtime.block_on(async {
+            let ctx = SessionContext::new();
+            register(&ctx, &tables[0]).await.unwrap();
+            ctx.sql("SELECT COUNT(*) AS n FROM injuries WHERE report_status = 'NA' AND week = 1")
+                .await
+                .unwrap()
+                .collect()
+                .await
+                .unwrap()
+        });
+        std::fs::remove_dir_all(dir).unwrap();
+        assert_eq!(pretty_format_batches(&batches).unwrap().to_string().lines().nth(3).unwrap(), "| 1 |");
+    }
+}
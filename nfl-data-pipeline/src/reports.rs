@@ -0,0 +1,110 @@
+use anyhow::{Result, Context};
+use postgres::Client;
+
+/// Bye weeks show up as 13+ days of rest in the schedule
+pub const POST_BYE_MIN_REST: i32 = 13;
+
+/// Rebuild coach records for a season from graded games.
+/// Returns the number of coach rows written.
+pub fn refresh_coach_records(client: &mut Client, season: i32) -> Result<u64> {
+    let mut tx = client.transaction()?;
+    tx.execute("DELETE FROM coach_records WHERE season = $1", &[&season])?;
+
+    let written = tx
+        .execute(
+            "WITH sides AS (
+                 SELECT season, home_team AS team, home_coach AS coach,
+                        home_score - away_score AS margin,
+                        home_rest AS rest,
+                        spread_line > 0 AS favorite,
+                        spread_line < 0 AS underdog,
+                        CASE spread_result WHEN 'home' THEN 'cover' WHEN 'away' THEN 'loss' ELSE spread_result END AS ats
+                 FROM game_betting_results
+                 WHERE season = $1 AND home_coach IS NOT NULL
+                 UNION ALL
+                 SELECT season, away_team, away_coach,
+                        away_score - home_score,
+                        away_rest,
+                        spread_line < 0,
+                        spread_line > 0,
+                        CASE spread_result WHEN 'away' THEN 'cover' WHEN 'home' THEN 'loss' ELSE spread_result END
+                 FROM game_betting_results
+                 WHERE season = $1 AND away_coach IS NOT NULL
+             )
+             INSERT INTO coach_records (
+                 season, coach, team, games, wins, losses, ties,
+                 ats_wins, ats_losses, ats_pushes, post_bye_wins, post_bye_losses,
+                 favorite_wins, favorite_losses, favorite_ats_wins, favorite_ats_losses,
+                 underdog_wins, underdog_losses, underdog_ats_wins, underdog_ats_losses, updated_at
+             )
+             SELECT season, coach, team, COUNT(*),
+                    COUNT(*) FILTER (WHERE margin > 0),
+                    COUNT(*) FILTER (WHERE margin < 0),
+                    COUNT(*) FILTER (WHERE margin = 0),
+                    COUNT(*) FILTER (WHERE ats = 'cover'),
+                    COUNT(*) FILTER (WHERE ats = 'loss'),
+                    COUNT(*) FILTER (WHERE ats = 'push'),
+                    COUNT(*) FILTER (WHERE rest >= $2 AND margin > 0),
+                    COUNT(*) FILTER (WHERE rest >= $2 AND margin < 0),
+                    COUNT(*) FILTER (WHERE favorite AND margin > 0),
+                    COUNT(*) FILTER (WHERE favorite AND margin < 0),
+                    COUNT(*) FILTER (WHERE favorite AND ats = 'cover'),
+                    COUNT(*) FILTER (WHERE favorite AND ats = 'loss'),
+                    COUNT(*) FILTER (WHERE underdog AND margin > 0),
+                    COUNT(*) FILTER (WHERE underdog AND margin < 0),
+                    COUNT(*) FILTER (WHERE underdog AND ats = 'cover'),
+                    COUNT(*) FILTER (WHERE underdog AND ats = 'loss'),
+                    NOW()
+             FROM sides
+             GROUP BY season, coach, team",
+            &[&season, &POST_BYE_MIN_REST],
+        )
+        .context("Failed to refresh coach records")?;
+
+    tx.commit()?;
+    Ok(written)
+}
+
+/// Rebuild referee crew tendencies for a season.
+///
+/// Penalty rates come from team box scores in game_team_stats and stay NULL for
+/// games the box score importer hasn't loaded.
+pub fn refresh_referee_tendencies(client: &mut Client, season: i32) -> Result<u64> {
+    let mut tx = client.transaction()?;
+    tx.execute("DELETE FROM referee_tendencies WHERE season = $1", &[&season])?;
+
+    let written = tx
+        .execute(
+            "WITH penalties AS (
+                 SELECT g.nfl_game_id,
+                        SUM(gts.penalties) AS penalties,
+                        SUM(gts.penalty_yards) AS penalty_yards
+                 FROM games g
+                 JOIN game_team_stats gts ON gts.game_id = g.id
+                 WHERE g.season = $1
+                 GROUP BY g.nfl_game_id
+             )
+             INSERT INTO referee_tendencies (
+                 season, referee, games, home_wins, home_win_rate, avg_total_points,
+                 over_rate, penalties_per_game, penalty_yards_per_game, updated_at
+             )
+             SELECT r.season, r.referee, COUNT(*),
+                    COUNT(*) FILTER (WHERE r.home_score > r.away_score),
+                    ROUND(AVG(CASE WHEN r.home_score > r.away_score THEN 1.0 ELSE 0.0 END), 3),
+                    ROUND(AVG(r.home_score + r.away_score), 2),
+                    ROUND(AVG(CASE WHEN r.total_result = 'over' THEN 1.0 ELSE 0.0 END)
+                          FILTER (WHERE r.total_result IS NOT NULL), 3),
+                    ROUND(AVG(p.penalties), 2),
+                    ROUND(AVG(p.penalty_yards), 2),
+                    NOW()
+             FROM game_betting_results r
+             LEFT JOIN penalties p ON p.nfl_game_id = r.nfl_game_id
+             WHERE r.season = $1 AND r.referee IS NOT NULL
+             GROUP BY r.season, r.referee",
+            &[&season],
+        )
+        .context("Failed to refresh referee tendencies")?;
+
+    tx.commit()?;
+    Ok(written)
+}
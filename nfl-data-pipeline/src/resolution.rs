@@ -0,0 +1,282 @@
+use anyhow::{Context, Result};
+use log::{info, warn};
+use postgres::Client;
+use std::collections::BTreeMap;
+
+/// What a reference failed to resolve to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ReferenceKind {
+    /// Abbreviation missing from `teams`
+    Team,
+    /// GSIS ID missing from `players`
+    Player,
+    /// ESPN athlete ID no player carries
+    EspnAthlete,
+}
+
+impl ReferenceKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReferenceKind::Team => "team",
+            ReferenceKind::Player => "player",
+            ReferenceKind::EspnAthlete => "espn_athlete",
+        }
+    }
+
+    fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "team" => Some(ReferenceKind::Team),
+            "player" => Some(ReferenceKind::Player),
+            "espn_athlete" => Some(ReferenceKind::EspnAthlete),
+            _ => None,
+        }
+    }
+}
+
+/// One row an import dropped or left unlinked because a lookup failed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unresolved {
+    pub kind: ReferenceKind,
+    pub reference: String,
+    /// Dataset the row came from (`player_stats`, `schedules`, ...)
+    pub dataset: &'static str,
+    pub season: i32,
+    /// Human-readable hint, e.g. the player's name
+    pub context: Option<String>,
+}
+
+impl Unresolved {
+    pub fn team(abbr: &str, dataset: &'static str, season: i32) -> Self {
+        Unresolved { kind: ReferenceKind::Team, reference: abbr.to_string(), dataset, season, context: None }
+    }
+
+    pub fn player(nfl_id: &str, name: Option<&str>, dataset: &'static str, season: i32) -> Self {
+        Unresolved {
+            kind: ReferenceKind::Player,
+            reference: nfl_id.to_string(),
+            dataset,
+            season,
+            context: name.map(str::to_string),
+        }
+    }
+
+    pub fn espn_athlete(espn_id: &str, name: Option<&str>, dataset: &'static str, season: i32) -> Self {
+        Unresolved { kind: ReferenceKind::EspnAthlete, ..Unresolved::player(espn_id, name, dataset, season) }
+    }
+}
+
+/// Add occurrences to `unresolved_references`, one row per kind, reference and
+/// dataset. Recording is best effort: a failure is logged, never returned, so
+/// it can't fail the import that hit the lookup.
+pub fn record(client: &mut Client, unresolved: &[Unresolved]) {
+    if unresolved.is_empty() {
+        return;
+    }
+    if let Err(e) = try_record(client, unresolved) {
+        warn!("⚠️  Failed to record unresolved references: {}", e);
+    }
+}
+
+/// Occurrences of one reference within a batch
+struct Seen<'a> {
+    occurrences: i64,
+    last_season: i32,
+    context: Option<&'a str>,
+}
+
+fn try_record(client: &mut Client, unresolved: &[Unresolved]) -> Result<()> {
+    let mut grouped: BTreeMap<(ReferenceKind, &str, &str), Seen> = BTreeMap::new();
+    for item in unresolved {
+        let seen = grouped
+            .entry((item.kind, item.reference.as_str(), item.dataset))
+            .or_insert(Seen { occurrences: 0, last_season: item.season, context: None });
+        seen.occurrences += 1;
+        seen.last_season = seen.last_season.max(item.season);
+        seen.context = seen.context.or(item.context.as_deref());
+    }
+
+    let mut tx = client.transaction()?;
+    for ((kind, reference, dataset), seen) in grouped {
+        tx.execute(
+            "INSERT INTO unresolved_references (kind, reference, dataset, context, last_season, occurrences,
+                 first_seen, last_seen)
+             VALUES ($1, $2, $3, $4, $5, $6, NOW(), NOW())
+             ON CONFLICT (kind, reference, dataset) DO UPDATE SET
+                 context = COALESCE(EXCLUDED.context, unresolved_references.context),
+                 last_season = GREATEST(EXCLUDED.last_season, unresolved_references.last_season),
+                 occurrences = unresolved_references.occurrences + EXCLUDED.occurrences,
+                 last_seen = NOW(),
+                 resolved_at = NULL",
+            &[&kind.as_str(), &reference, &dataset, &seen.context, &seen.last_season, &seen.occurrences],
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// The abbreviations among `abbrs` with no row in `teams`
+pub fn unknown_teams<'a>(client: &mut Client, abbrs: impl IntoIterator<Item = &'a str>) -> Result<Vec<String>> {
+    let mut abbrs: Vec<&str> = abbrs.into_iter().collect();
+    abbrs.sort_unstable();
+    abbrs.dedup();
+    let rows = client
+        .query(
+            "SELECT a FROM UNNEST($1::text[]) a WHERE NOT EXISTS (SELECT 1 FROM teams t WHERE t.abbreviation = a)",
+            &[&abbrs],
+        )
+        .context("Failed to look up teams")?;
+    Ok(rows.iter().map(|row| row.get(0)).collect())
+}
+
+/// The GSIS IDs among `nfl_ids` with no row in `players`
+pub fn missing_players<'a>(client: &mut Client, nfl_ids: impl IntoIterator<Item = &'a str>) -> Result<Vec<String>> {
+    let mut nfl_ids: Vec<&str> = nfl_ids.into_iter().collect();
+    nfl_ids.sort_unstable();
+    nfl_ids.dedup();
+    let rows = client
+        .query(
+            "SELECT i FROM UNNEST($1::text[]) i WHERE NOT EXISTS (SELECT 1 FROM players p WHERE p.nfl_id = i)",
+            &[&nfl_ids],
+        )
+        .context("Failed to look up players")?;
+    Ok(rows.iter().map(|row| row.get(0)).collect())
+}
+
+/// An open entry in the report
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnresolvedReference {
+    pub kind: ReferenceKind,
+    pub reference: String,
+    pub dataset: String,
+    pub context: Option<String>,
+    pub last_season: i32,
+    pub occurrences: i64,
+    pub first_seen: String,
+    pub last_seen: String,
+}
+
+impl UnresolvedReference {
+    pub fn suggested_fix(&self) -> String {
+        match self.kind {
+            ReferenceKind::Team => format!(
+                "Run --mode seed-teams; if {} is a relocated or alternate code, map it in transformer::normalize_team_abbr",
+                self.reference
+            ),
+            ReferenceKind::Player => format!(
+                "Import the {} roster (--mode year --year {}) so {} exists in players, then re-import {}",
+                self.last_season,
+                self.last_season,
+                match &self.context {
+                    Some(name) => format!("{} ({})", name, self.reference),
+                    None => self.reference.clone(),
+                },
+                self.dataset
+            ),
+            ReferenceKind::EspnAthlete => format!(
+                "Import the {} roster so a player carries ESPN ID {}{}, then rerun --mode espn --year {}",
+                self.last_season,
+                self.reference,
+                self.context.as_ref().map(|name| format!(" ({})", name)).unwrap_or_default(),
+                self.last_season
+            ),
+        }
+    }
+}
+
+/// `--mode unresolved`: references seen in the last `since_days` days that still
+/// don't resolve, most frequent first. Entries that now resolve (the team or
+/// player has since been added) are marked resolved and left out.
+pub fn report(client: &mut Client, since_days: i32) -> Result<Vec<UnresolvedReference>> {
+    client
+        .execute(
+            "UPDATE unresolved_references u SET resolved_at = NOW()
+             WHERE resolved_at IS NULL
+               AND ((kind = 'team' AND EXISTS (SELECT 1 FROM teams t WHERE t.abbreviation = u.reference))
+                 OR (kind = 'player' AND EXISTS (SELECT 1 FROM players p WHERE p.nfl_id = u.reference))
+                 OR (kind = 'espn_athlete' AND (EXISTS (SELECT 1 FROM players p WHERE p.espn_id = u.reference)
+                     OR EXISTS (SELECT 1 FROM player_external_ids e WHERE e.source = 'espn' AND e.external_id = u.reference))))",
+            &[],
+        )
+        .context("Failed to mark resolved references")?;
+
+    let rows = client
+        .query(
+            "SELECT kind, reference, dataset, context, last_season, occurrences,
+                    to_char(first_seen, 'YYYY-MM-DD HH24:MI'), to_char(last_seen, 'YYYY-MM-DD HH24:MI')
+             FROM unresolved_references
+             WHERE resolved_at IS NULL AND last_seen >= NOW() - make_interval(days => $1)
+             ORDER BY kind, occurrences DESC, reference",
+            &[&since_days],
+        )
+        .context("Failed to load unresolved references")?;
+
+    Ok(rows
+        .iter()
+        .filter_map(|row| {
+            Some(UnresolvedReference {
+                kind: ReferenceKind::parse(row.get(0))?,
+                reference: row.get(1),
+                dataset: row.get(2),
+                context: row.get(3),
+                last_season: row.get(4),
+                occurrences: row.get(5),
+                first_seen: row.get(6),
+                last_seen: row.get(7),
+            })
+        })
+        .collect())
+}
+
+pub fn log_report(references: &[UnresolvedReference], since_days: i32) {
+    info!("🧩 Unresolved references seen in the last {} days: {}", since_days, references.len());
+    for reference in references {
+        info!(
+            "  {} {}{} in {} ×{} (season {}, first {}, last {})",
+            reference.kind.as_str(),
+            reference.reference,
+            reference.context.as_ref().map(|c| format!(" ({})", c)).unwrap_or_default(),
+            reference.dataset,
+            reference.occurrences,
+            reference.last_season,
+            reference.first_seen,
+            reference.last_seen
+        );
+        info!("    → {}", reference.suggested_fix());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference(kind: ReferenceKind, reference: &str, context: Option<&str>) -> UnresolvedReference {
+        UnresolvedReference {
+            kind,
+            reference: reference.to_string(),
+            dataset: "player_stats".to_string(),
+            context: context.map(str::to_string),
+            last_season: 2024,
+            occurrences: 3,
+            first_seen: "2024-09-08 12:00".to_string(),
+            last_seen: "2024-09-15 12:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_suggested_fixes() {
+        let player = reference(ReferenceKind::Player, "00-0039999", Some("Rookie Back"));
+        assert_eq!(
+            player.suggested_fix(),
+            "Import the 2024 roster (--mode year --year 2024) so Rookie Back (00-0039999) exists in players, then re-import player_stats"
+        );
+        assert!(reference(ReferenceKind::Team, "WSH", None).suggested_fix().contains("normalize_team_abbr"));
+    }
+
+    #[test]
+    fn test_kind_round_trips() {
+        for kind in [ReferenceKind::Team, ReferenceKind::Player, ReferenceKind::EspnAthlete] {
+            assert_eq!(ReferenceKind::parse(kind.as_str()), Some(kind));
+        }
+        assert_eq!(ReferenceKind::parse("coach"), None);
+    }
+}
@@ -0,0 +1,316 @@
+use anyhow::{Result, Context};
+use postgres::Client;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Positions with a single primary role, where a new snap leader means a new starter
+pub const SNAP_ROLE_POSITIONS: [&str; 3] = ["QB", "RB", "TE"];
+/// Smallest swing in the snap-share gap (0-1) recorded as a role change
+pub const MIN_SNAP_SWING: f64 = 0.10;
+
+/// One player's standing in a team slot for a week: depth rank, or snap share
+#[derive(Debug, Clone)]
+pub struct SlotEntry {
+    pub team_id: Uuid,
+    pub position: String,
+    pub season_type: String,
+    pub week: i32,
+    pub player_id: Option<Uuid>,
+    pub player_name: String,
+    /// Depth rank (1 = starter) or offensive snap share
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoleChangeKind {
+    /// A different player is listed first on the depth chart
+    DepthChartStarter,
+    /// A different player led the position in offensive snaps
+    SnapShareLeader,
+}
+
+impl RoleChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RoleChangeKind::DepthChartStarter => "depth_chart_starter",
+            RoleChangeKind::SnapShareLeader => "snap_share_leader",
+        }
+    }
+}
+
+/// A player taking over a slot from the previous week's holder
+#[derive(Debug, Clone, Serialize)]
+pub struct RoleChange {
+    pub kind: RoleChangeKind,
+    pub team_id: Uuid,
+    pub position: String,
+    pub season_type: String,
+    pub week: i32,
+    pub player_id: Option<Uuid>,
+    pub player_name: String,
+    pub previous_player_id: Option<Uuid>,
+    pub previous_player_name: String,
+    /// Depth charts: ranks the new starter climbed. Snaps: swing in the share gap
+    /// between the two players since the previous week.
+    pub magnitude: f64,
+}
+
+type Slot = (Uuid, String);
+type Week = (i32, String);
+
+/// Group entries by (team, position), then by week in order
+fn by_slot(entries: &[SlotEntry]) -> BTreeMap<Slot, BTreeMap<Week, Vec<&SlotEntry>>> {
+    let mut slots: BTreeMap<Slot, BTreeMap<Week, Vec<&SlotEntry>>> = BTreeMap::new();
+    for entry in entries {
+        slots
+            .entry((entry.team_id, entry.position.clone()))
+            .or_default()
+            .entry((entry.week, entry.season_type.clone()))
+            .or_default()
+            .push(entry);
+    }
+    slots
+}
+
+fn same_player(a: &SlotEntry, b: &SlotEntry) -> bool {
+    match (a.player_id, b.player_id) {
+        (Some(a), Some(b)) => a == b,
+        _ => a.player_name == b.player_name,
+    }
+}
+
+fn find<'a>(week: &[&'a SlotEntry], player: &SlotEntry) -> Option<&'a SlotEntry> {
+    week.iter().copied().find(|e| same_player(e, player))
+}
+
+fn starter<'a>(week: &[&'a SlotEntry]) -> Option<&'a SlotEntry> {
+    week.iter().copied().find(|e| e.value == 1.0)
+}
+
+fn leader<'a>(week: &[&'a SlotEntry]) -> Option<&'a SlotEntry> {
+    week.iter().copied().max_by(|a, b| a.value.total_cmp(&b.value))
+}
+
+fn role_change(kind: RoleChangeKind, new: &SlotEntry, previous: &SlotEntry, magnitude: f64) -> RoleChange {
+    RoleChange {
+        kind,
+        team_id: new.team_id,
+        position: new.position.clone(),
+        season_type: new.season_type.clone(),
+        week: new.week,
+        player_id: new.player_id,
+        player_name: new.player_name.clone(),
+        previous_player_id: previous.player_id,
+        previous_player_name: previous.player_name.clone(),
+        magnitude,
+    }
+}
+
+/// Depth chart starter changes between consecutive charted weeks.
+///
+/// A new starter who wasn't on the previous chart counts as climbing from just
+/// below its last listed rank.
+pub fn detect_depth_chart_changes(entries: &[SlotEntry]) -> Vec<RoleChange> {
+    let mut changes = Vec::new();
+    for weeks in by_slot(entries).values() {
+        let weeks: Vec<&Vec<&SlotEntry>> = weeks.values().collect();
+        for pair in weeks.windows(2) {
+            let (previous, current) = (pair[0], pair[1]);
+            let (Some(old), Some(new)) = (starter(previous), starter(current)) else { continue };
+            if same_player(old, new) {
+                continue;
+            }
+
+            let deepest = previous.iter().map(|e| e.value).fold(1.0, f64::max);
+            let prior_rank = find(previous, new).map(|e| e.value).unwrap_or(deepest + 1.0);
+            changes.push(role_change(RoleChangeKind::DepthChartStarter, new, old, prior_rank - 1.0));
+        }
+    }
+    changes
+}
+
+/// Snap-share leader flips between consecutive played weeks at `SNAP_ROLE_POSITIONS`.
+///
+/// Only flips where the gap between the new and old leader moved by at least
+/// `MIN_SNAP_SWING` are kept, which filters out near-even timeshares.
+pub fn detect_snap_share_changes(entries: &[SlotEntry]) -> Vec<RoleChange> {
+    let mut changes = Vec::new();
+    for ((_, position), weeks) in by_slot(entries) {
+        if !SNAP_ROLE_POSITIONS.contains(&position.as_str()) {
+            continue;
+        }
+
+        let weeks: Vec<&Vec<&SlotEntry>> = weeks.values().collect();
+        for pair in weeks.windows(2) {
+            let (previous, current) = (pair[0], pair[1]);
+            let (Some(old), Some(new)) = (leader(previous), leader(current)) else { continue };
+            if same_player(old, new) {
+                continue;
+            }
+
+            let share = |week: &[&SlotEntry], player: &SlotEntry| find(week, player).map(|e| e.value).unwrap_or(0.0);
+            let gap_now = new.value - share(current, old);
+            let gap_before = share(previous, new) - old.value;
+            let swing = gap_now - gap_before;
+            if swing >= MIN_SNAP_SWING {
+                changes.push(role_change(RoleChangeKind::SnapShareLeader, new, old, swing));
+            }
+        }
+    }
+    changes
+}
+
+/// Rebuild a season's role changes from depth_charts and snap_counts.
+/// Returns the number of role changes written.
+pub fn refresh_role_changes(client: &mut Client, season: i32) -> Result<u64> {
+    let depth: Vec<SlotEntry> = client
+        .query(
+            "SELECT team_id, position, season_type, week, player_id, COALESCE(player_name, ''), depth_rank::float8
+             FROM depth_charts
+             WHERE season = $1 AND season_type <> 'PRE' AND formation IS DISTINCT FROM 'Special Teams'",
+            &[&season],
+        )
+        .context("Failed to load depth charts")?
+        .iter()
+        .map(slot_entry)
+        .collect();
+
+    let snaps: Vec<SlotEntry> = client
+        .query(
+            "SELECT team_id, position, season_type, week, player_id, player_name, offense_pct::float8
+             FROM snap_counts
+             WHERE season = $1 AND season_type <> 'PRE' AND team_id IS NOT NULL AND position IS NOT NULL AND offense_pct IS NOT NULL",
+            &[&season],
+        )
+        .context("Failed to load snap counts")?
+        .iter()
+        .map(slot_entry)
+        .collect();
+
+    let mut changes = detect_depth_chart_changes(&depth);
+    changes.extend(detect_snap_share_changes(&snaps));
+
+    let mut tx = client.transaction()?;
+    tx.execute("DELETE FROM role_changes WHERE season = $1", &[&season])?;
+    for change in &changes {
+        tx.execute(
+            "INSERT INTO role_changes (season, season_type, week, team_id, position, kind, player_id, player_name,
+                 previous_player_id, previous_player_name, magnitude)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11::float8)
+             ON CONFLICT (season, season_type, week, team_id, position, kind) DO NOTHING",
+            &[
+                &season,
+                &change.season_type,
+                &change.week,
+                &change.team_id,
+                &change.position,
+                &change.kind.as_str(),
+                &change.player_id,
+                &change.player_name,
+                &change.previous_player_id,
+                &change.previous_player_name,
+                &change.magnitude,
+            ],
+        )?;
+    }
+    tx.commit()?;
+
+    Ok(changes.len() as u64)
+}
+
+fn slot_entry(row: &postgres::Row) -> SlotEntry {
+    SlotEntry {
+        team_id: row.get(0),
+        position: row.get(1),
+        season_type: row.get(2),
+        week: row.get(3),
+        player_id: row.get(4),
+        player_name: row.get(5),
+        value: row.get(6),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(team: Uuid, position: &str, week: i32, name: &str, value: f64) -> SlotEntry {
+        SlotEntry {
+            team_id: team,
+            position: position.to_string(),
+            season_type: "REG".to_string(),
+            week,
+            player_id: None,
+            player_name: name.to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn test_depth_chart_starter_change() {
+        let team = Uuid::new_v4();
+        let entries = vec![
+            entry(team, "RB", 5, "Pacheco", 1.0),
+            entry(team, "RB", 5, "Hunt", 2.0),
+            entry(team, "RB", 5, "Steele", 3.0),
+            entry(team, "RB", 6, "Hunt", 1.0),
+            entry(team, "RB", 6, "Pacheco", 2.0),
+            entry(team, "RB", 7, "Hunt", 1.0),
+            entry(team, "QB", 6, "Mahomes", 1.0),
+            entry(team, "QB", 7, "Mahomes", 1.0),
+        ];
+
+        let changes = detect_depth_chart_changes(&entries);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].week, 6);
+        assert_eq!(changes[0].player_name, "Hunt");
+        assert_eq!(changes[0].previous_player_name, "Pacheco");
+        assert_eq!(changes[0].magnitude, 1.0);
+    }
+
+    #[test]
+    fn test_unlisted_player_climbs_from_below_chart() {
+        let team = Uuid::new_v4();
+        let entries = vec![
+            entry(team, "QB", 1, "Starter", 1.0),
+            entry(team, "QB", 1, "Backup", 2.0),
+            entry(team, "QB", 2, "Signing", 1.0),
+        ];
+        assert_eq!(detect_depth_chart_changes(&entries)[0].magnitude, 2.0);
+    }
+
+    #[test]
+    fn test_snap_share_flip() {
+        let team = Uuid::new_v4();
+        let entries = vec![
+            entry(team, "RB", 3, "Lead", 0.70),
+            entry(team, "RB", 3, "Change", 0.30),
+            entry(team, "RB", 4, "Lead", 0.35),
+            entry(team, "RB", 4, "Change", 0.62),
+        ];
+
+        let changes = detect_snap_share_changes(&entries);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, RoleChangeKind::SnapShareLeader);
+        assert_eq!(changes[0].player_name, "Change");
+        assert!((changes[0].magnitude - 0.67).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_timeshare_and_wide_receivers_ignored() {
+        let team = Uuid::new_v4();
+        let entries = vec![
+            entry(team, "RB", 3, "A", 0.52),
+            entry(team, "RB", 3, "B", 0.48),
+            entry(team, "RB", 4, "A", 0.49),
+            entry(team, "RB", 4, "B", 0.51),
+            entry(team, "WR", 3, "X", 0.95),
+            entry(team, "WR", 3, "Y", 0.40),
+            entry(team, "WR", 4, "X", 0.30),
+            entry(team, "WR", 4, "Y", 0.90),
+        ];
+        assert!(detect_snap_share_changes(&entries).is_empty());
+    }
+}
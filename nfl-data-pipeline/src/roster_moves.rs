@@ -0,0 +1,300 @@
+use anyhow::{Context, Result};
+use log::info;
+use postgres::Client;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::parser::RosterPlayer;
+use crate::transformer;
+
+/// One player's listing in a roster snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RosterEntry {
+    pub gsis_id: String,
+    pub name: String,
+    /// Current abbreviation (relocated teams normalized)
+    pub team: String,
+    pub position: String,
+    /// nflverse roster status: ACT, RES (reserve lists), INA, DEV (practice squad), CUT, ...
+    pub status: Option<String>,
+    pub jersey_number: Option<i32>,
+}
+
+impl From<&RosterPlayer> for RosterEntry {
+    fn from(player: &RosterPlayer) -> Self {
+        RosterEntry {
+            gsis_id: player.gsis_id.clone(),
+            name: player.full_name.clone(),
+            team: transformer::normalize_team_abbr(&player.team),
+            position: player.position.clone(),
+            status: player.status.clone(),
+            jersey_number: player.jersey_number,
+        }
+    }
+}
+
+/// Every player on a roster in one week, keyed by GSIS ID
+#[derive(Debug, Clone, Default)]
+pub struct RosterSnapshot {
+    pub week: i32,
+    pub players: BTreeMap<String, RosterEntry>,
+}
+
+impl RosterSnapshot {
+    /// Snapshot from parsed roster rows; a player listed twice keeps the last row
+    pub fn from_players<'a>(week: i32, players: impl IntoIterator<Item = &'a RosterPlayer>) -> Self {
+        RosterSnapshot {
+            week,
+            players: players
+                .into_iter()
+                .map(|player| (player.gsis_id.clone(), RosterEntry::from(player)))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RosterField {
+    Team,
+    Position,
+    Status,
+    JerseyNumber,
+}
+
+impl RosterField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RosterField::Team => "team",
+            RosterField::Position => "position",
+            RosterField::Status => "status",
+            RosterField::JerseyNumber => "jersey_number",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldChange {
+    pub field: RosterField,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+/// A player on both rosters whose listing changed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChangedPlayer {
+    pub before: RosterEntry,
+    pub after: RosterEntry,
+    pub changes: Vec<FieldChange>,
+}
+
+/// Difference between two roster snapshots, each list ordered by GSIS ID
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RosterDiff {
+    /// On the later roster only (signings, call-ups, rookies)
+    pub added: Vec<RosterEntry>,
+    /// On the earlier roster only (releases, retirements)
+    pub removed: Vec<RosterEntry>,
+    /// On both with a different team, position, status or jersey number
+    pub changed: Vec<ChangedPlayer>,
+}
+
+impl RosterDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Flatten into one move per added or removed player and per changed field
+    pub fn moves(&self) -> Vec<RosterMove<'_>> {
+        let mut moves: Vec<RosterMove> = Vec::new();
+        moves.extend(self.added.iter().map(|entry| RosterMove {
+            player: entry,
+            kind: "added",
+            field: None,
+            old_value: None,
+            new_value: Some(entry.team.as_str()),
+        }));
+        moves.extend(self.removed.iter().map(|entry| RosterMove {
+            player: entry,
+            kind: "removed",
+            field: None,
+            old_value: Some(entry.team.as_str()),
+            new_value: None,
+        }));
+        for player in &self.changed {
+            moves.extend(player.changes.iter().map(|change| RosterMove {
+                player: &player.after,
+                kind: "changed",
+                field: Some(change.field),
+                old_value: change.old_value.as_deref(),
+                new_value: change.new_value.as_deref(),
+            }));
+        }
+        moves
+    }
+
+    /// Team changes only: trades and waiver claims
+    pub fn team_changes(&self) -> impl Iterator<Item = &ChangedPlayer> {
+        self.changed
+            .iter()
+            .filter(|player| player.changes.iter().any(|change| change.field == RosterField::Team))
+    }
+}
+
+/// One row of a transaction feed, as stored in `roster_moves`. Adds and
+/// removals carry the team joined or left.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RosterMove<'a> {
+    pub player: &'a RosterEntry,
+    /// `added`, `removed` or `changed`
+    pub kind: &'static str,
+    pub field: Option<RosterField>,
+    pub old_value: Option<&'a str>,
+    pub new_value: Option<&'a str>,
+}
+
+/// Compare two roster snapshots, e.g. consecutive weeks of one season
+pub fn diff_rosters(before: &RosterSnapshot, after: &RosterSnapshot) -> RosterDiff {
+    let mut diff = RosterDiff::default();
+
+    for (gsis_id, entry) in &after.players {
+        match before.players.get(gsis_id) {
+            None => diff.added.push(entry.clone()),
+            Some(previous) => {
+                let changes = field_changes(previous, entry);
+                if !changes.is_empty() {
+                    diff.changed.push(ChangedPlayer { before: previous.clone(), after: entry.clone(), changes });
+                }
+            }
+        }
+    }
+    diff.removed = before
+        .players
+        .iter()
+        .filter(|(gsis_id, _)| !after.players.contains_key(*gsis_id))
+        .map(|(_, entry)| entry.clone())
+        .collect();
+
+    diff
+}
+
+fn field_changes(before: &RosterEntry, after: &RosterEntry) -> Vec<FieldChange> {
+    let fields = [
+        (RosterField::Team, Some(before.team.clone()), Some(after.team.clone())),
+        (RosterField::Position, Some(before.position.clone()), Some(after.position.clone())),
+        (RosterField::Status, before.status.clone(), after.status.clone()),
+        (
+            RosterField::JerseyNumber,
+            before.jersey_number.map(|n| n.to_string()),
+            after.jersey_number.map(|n| n.to_string()),
+        ),
+    ];
+    fields
+        .into_iter()
+        .filter(|(_, old_value, new_value)| old_value != new_value)
+        .map(|(field, old_value, new_value)| FieldChange { field, old_value, new_value })
+        .collect()
+}
+
+/// Rebuild a season's `roster_moves` from its weekly snapshots (in week order),
+/// diffing each week against the one before. Returns the number of moves written.
+pub fn write_roster_moves(client: &mut Client, season: i32, snapshots: &[RosterSnapshot]) -> Result<u64> {
+    let mut tx = client.transaction()?;
+    tx.execute("DELETE FROM roster_moves WHERE season = $1", &[&season])?;
+
+    let mut written = 0;
+    let mut trades = 0;
+    for pair in snapshots.windows(2) {
+        let (previous, current) = (&pair[0], &pair[1]);
+        let diff = diff_rosters(previous, current);
+        if diff.is_empty() {
+            continue;
+        }
+        trades += diff.team_changes().count();
+
+        for roster_move in diff.moves() {
+            let entry = roster_move.player;
+            written += tx
+                .execute(
+                    "INSERT INTO roster_moves (season, week, previous_week, gsis_id, player_id, player_name, move,
+                         field, old_value, new_value)
+                     SELECT $1, $2, $3, $4, p.id, $5, $6, $7, $8, $9
+                     FROM (SELECT $4::text AS gsis_id) k
+                     LEFT JOIN players p ON p.nfl_id = k.gsis_id",
+                    &[
+                        &season,
+                        &current.week,
+                        &previous.week,
+                        &entry.gsis_id,
+                        &entry.name,
+                        &roster_move.kind,
+                        &roster_move.field.map(|field| field.as_str()),
+                        &roster_move.old_value,
+                        &roster_move.new_value,
+                    ],
+                )
+                .with_context(|| format!("Failed to write roster move for {}", entry.name))?;
+        }
+    }
+
+    tx.commit()?;
+    info!("  🔁 {} team changes across {} weekly rosters", trades, snapshots.len());
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(gsis_id: &str, team: &str, status: &str) -> RosterEntry {
+        RosterEntry {
+            gsis_id: gsis_id.to_string(),
+            name: format!("Player {}", gsis_id),
+            team: team.to_string(),
+            position: "WR".to_string(),
+            status: Some(status.to_string()),
+            jersey_number: Some(11),
+        }
+    }
+
+    fn snapshot(week: i32, entries: &[RosterEntry]) -> RosterSnapshot {
+        RosterSnapshot {
+            week,
+            players: entries.iter().map(|e| (e.gsis_id.clone(), e.clone())).collect(),
+        }
+    }
+
+    #[test]
+    fn test_diff_classifies_added_removed_and_changed() {
+        let before = snapshot(5, &[entry("A", "KC", "ACT"), entry("B", "KC", "ACT"), entry("C", "NYJ", "ACT")]);
+        let after = snapshot(6, &[entry("A", "KC", "ACT"), entry("C", "NE", "RES"), entry("D", "KC", "DEV")]);
+
+        let diff = diff_rosters(&before, &after);
+        assert_eq!(diff.added, vec![entry("D", "KC", "DEV")]);
+        assert_eq!(diff.removed, vec![entry("B", "KC", "ACT")]);
+        assert_eq!(diff.changed.len(), 1);
+
+        let fields: Vec<RosterField> = diff.changed[0].changes.iter().map(|c| c.field).collect();
+        assert_eq!(fields, [RosterField::Team, RosterField::Status]);
+        assert_eq!(diff.changed[0].changes[0].old_value.as_deref(), Some("NYJ"));
+        assert_eq!(diff.team_changes().count(), 1);
+        assert_eq!(diff.moves().len(), 4);
+    }
+
+    #[test]
+    fn test_identical_snapshots_have_no_diff() {
+        let roster = snapshot(1, &[entry("A", "KC", "ACT")]);
+        assert!(diff_rosters(&roster, &roster).is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_normalizes_relocated_teams() {
+        let csv = "season,team,position,full_name,gsis_id,week\n2019,OAK,QB,Derek Carr,00-0031280,1\n";
+        let players: Vec<RosterPlayer> = csv::Reader::from_reader(csv.as_bytes())
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let roster = RosterSnapshot::from_players(1, &players);
+        assert_eq!(roster.players["00-0031280"].team, "LV");
+    }
+}
@@ -0,0 +1,84 @@
+use anyhow::{Context, Result};
+use log::debug;
+use postgres::Client;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+use crate::precedence::{Precedence, Source};
+
+/// SHA-256 of a parsed source row and the settings it's merged with, stored in
+/// `row_hash` on `players`, `games` and `game_stats`
+pub fn content_hash<T: Serialize>(row: &T, settings: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(serde_json::to_vec(row).unwrap_or_default());
+    hasher.update([0x1f]);
+    hasher.update(settings.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Merge settings of a roster or schedule write: changing the source or
+/// precedence rules changes every row's hash, so the next import rewrites them
+pub fn merge_settings(precedence: &Precedence, source: Source) -> String {
+    format!("{} {:?}", source.as_str(), precedence)
+}
+
+/// The rows whose hash isn't stored in `table` yet. A row's hash covers its key
+/// columns, so a stored hash means the row was last written from identical data
+/// and can be left alone, keeping its `updated_at`. Everything is kept when
+/// `skip_unchanged` is off (`--force`).
+pub fn changed<'a, T: Serialize + Clone>(
+    client: &mut Client,
+    table: &str,
+    rows: &'a [T],
+    settings: &str,
+    skip_unchanged: bool,
+) -> Result<Cow<'a, [T]>> {
+    if !skip_unchanged || rows.is_empty() {
+        return Ok(Cow::Borrowed(rows));
+    }
+    let hashes: Vec<String> = rows.iter().map(|row| content_hash(row, settings)).collect();
+    let stored: HashSet<String> = client
+        .query(&format!("SELECT row_hash FROM {} WHERE row_hash = ANY($1)", table), &[&hashes])
+        .with_context(|| format!("Failed to look up {} row hashes", table))?
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+    if stored.is_empty() {
+        return Ok(Cow::Borrowed(rows));
+    }
+    let changed: Vec<T> = rows
+        .iter()
+        .zip(&hashes)
+        .filter(|(_, hash)| !stored.contains(*hash))
+        .map(|(row, _)| row.clone())
+        .collect();
+    debug!("{} unchanged {} rows skipped", rows.len() - changed.len(), table);
+    Ok(Cow::Owned(changed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SourcePriority;
+
+    #[derive(Serialize)]
+    struct Row {
+        id: &'static str,
+        yards: Option<i32>,
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let hash = content_hash(&Row { id: "00-0033873", yards: Some(291) }, "");
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, content_hash(&Row { id: "00-0033873", yards: Some(291) }, ""));
+        assert_ne!(hash, content_hash(&Row { id: "00-0033873", yards: None }, ""));
+        assert_ne!(hash, content_hash(&Row { id: "00-0033874", yards: Some(291) }, ""));
+
+        let nflverse = merge_settings(&Precedence::new(SourcePriority::Nflverse), Source::Nflverse);
+        let espn = merge_settings(&Precedence::new(SourcePriority::Espn), Source::Nflverse);
+        assert_ne!(content_hash(&Row { id: "a", yards: None }, &nflverse), content_hash(&Row { id: "a", yards: None }, &espn));
+    }
+}
@@ -0,0 +1,262 @@
+use anyhow::{anyhow, Context, Result};
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use postgres::Client;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SHEETS_API: &str = "https://sheets.googleapis.com/v4/spreadsheets";
+const SHEETS_SCOPE: &str = "https://www.googleapis.com/auth/spreadsheets";
+
+/// Derived tables that can be pushed to a sheet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Export {
+    /// Regular-season yardage and touchdown leaders
+    Leaders,
+    /// Season records with Pythagorean expectation
+    Standings,
+    /// Scores from the latest week with a final
+    WeeklyRecap,
+}
+
+impl Export {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "leaders" => Some(Export::Leaders),
+            "standings" => Some(Export::Standings),
+            "weekly_recap" => Some(Export::WeeklyRecap),
+            _ => None,
+        }
+    }
+
+    /// Query for a season; every column is text and its name becomes the header
+    fn query(&self) -> &'static str {
+        match self {
+            Export::Leaders => {
+                "SELECT p.name AS player, p.position, COALESCE(t.abbreviation, '') AS team, s.games::text AS games,
+                        s.passing_yards::text AS pass_yds, s.rushing_yards::text AS rush_yds,
+                        s.receiving_yards::text AS rec_yds, s.receptions::text AS rec,
+                        (s.passing_tds + s.rushing_tds + s.receiving_tds)::text AS tds
+                 FROM player_splits s
+                 JOIN players p ON p.id = s.player_id
+                 LEFT JOIN teams t ON t.id = p.team_id
+                 WHERE s.season = $1 AND s.split = 'regular_season'
+                 ORDER BY s.passing_yards + s.rushing_yards + s.receiving_yards DESC
+                 LIMIT 100"
+            }
+            Export::Standings => {
+                "SELECT t.abbreviation AS team, t.conference, t.division, s.wins::text AS w, s.losses::text AS l,
+                        s.ties::text AS t, s.points_for::text AS pf, s.points_against::text AS pa,
+                        s.point_differential::text AS diff, COALESCE(s.expected_wins::text, '') AS expected_wins,
+                        COALESCE(s.win_luck::text, '') AS win_luck,
+                        COALESCE(s.one_score_wins || '-' || s.one_score_losses, '') AS one_score
+                 FROM team_standings s
+                 JOIN teams t ON t.id = s.team_id
+                 WHERE s.season = $1 AND s.week IS NULL
+                 ORDER BY t.conference, t.division, s.win_pct DESC, s.point_differential DESC"
+            }
+            Export::WeeklyRecap => {
+                "SELECT g.season_type, g.week::text AS week, to_char(g.game_date AT TIME ZONE 'America/New_York', 'YYYY-MM-DD') AS date,
+                        a.abbreviation AS away, COALESCE(g.away_score::text, '') AS away_score,
+                        h.abbreviation AS home, COALESCE(g.home_score::text, '') AS home_score,
+                        COALESCE(g.status, '') AS status
+                 FROM games g
+                 JOIN teams a ON a.id = g.away_team_id
+                 JOIN teams h ON h.id = g.home_team_id
+                 WHERE (g.season, g.season_type, g.week) = (
+                     SELECT season, season_type, week FROM games
+                     WHERE season = $1 AND home_score IS NOT NULL
+                     ORDER BY game_date DESC LIMIT 1
+                 )
+                 ORDER BY g.game_date, h.abbreviation"
+            }
+        }
+    }
+}
+
+/// `--sheets-config` file: where each export goes
+#[derive(Debug, Deserialize)]
+pub struct SheetsConfig {
+    /// Path to a Google service account key; the sheets must be shared with its email
+    pub credentials: String,
+    pub exports: Vec<SheetTarget>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SheetTarget {
+    pub spreadsheet_id: String,
+    /// Tab name, overwritten on every push
+    pub sheet: String,
+    /// leaders, standings or weekly_recap
+    pub table: String,
+}
+
+impl SheetsConfig {
+    pub fn load(path: &str) -> Result<Self> {
+        let config: SheetsConfig = serde_json::from_str(
+            &std::fs::read_to_string(path).with_context(|| format!("Failed to read sheets config {}", path))?,
+        )
+        .with_context(|| format!("Invalid sheets config {}", path))?;
+
+        if let Some(target) = config.exports.iter().find(|t| Export::parse(&t.table).is_none()) {
+            return Err(anyhow!(
+                "Invalid table in sheets config: {} (expected leaders, standings or weekly_recap)",
+                target.table
+            ));
+        }
+        Ok(config)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ServiceAccount {
+    client_email: String,
+    private_key: String,
+    token_uri: String,
+}
+
+#[derive(Serialize)]
+struct Claims<'a> {
+    iss: &'a str,
+    scope: &'a str,
+    aud: &'a str,
+    iat: u64,
+    exp: u64,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+/// Sheets API client authorized as a service account
+pub struct SheetsClient {
+    http: reqwest::blocking::Client,
+    token: String,
+}
+
+impl SheetsClient {
+    /// Exchange a signed service account assertion for an access token
+    pub fn connect(credentials: &str) -> Result<Self> {
+        let account: ServiceAccount = serde_json::from_str(
+            &std::fs::read_to_string(credentials)
+                .with_context(|| format!("Failed to read service account key {}", credentials))?,
+        )
+        .context("Invalid service account key")?;
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let claims = Claims {
+            iss: &account.client_email,
+            scope: SHEETS_SCOPE,
+            aud: &account.token_uri,
+            iat: now,
+            exp: now + 3600,
+        };
+        let assertion = encode(
+            &Header::new(Algorithm::RS256),
+            &claims,
+            &EncodingKey::from_rsa_pem(account.private_key.as_bytes())?,
+        )?;
+
+        let http = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()?;
+        let response = http
+            .post(&account.token_uri)
+            .form(&[("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"), ("assertion", &assertion)])
+            .send()?
+            .error_for_status()
+            .context("Service account token request failed")?;
+
+        Ok(SheetsClient {
+            http,
+            token: response.json::<TokenResponse>()?.access_token,
+        })
+    }
+
+    /// Replace a tab's contents with `rows`
+    pub fn replace(&self, spreadsheet_id: &str, sheet: &str, rows: &[Vec<String>]) -> Result<()> {
+        let range = a1_range(sheet);
+        self.http
+            .post(values_url(spreadsheet_id, &format!("{}:clear", range))?)
+            .bearer_auth(&self.token)
+            .json(&json!({}))
+            .send()?
+            .error_for_status()
+            .with_context(|| format!("Failed to clear sheet {}", sheet))?;
+
+        let mut url = values_url(spreadsheet_id, &range)?;
+        url.query_pairs_mut().append_pair("valueInputOption", "USER_ENTERED");
+        self.http
+            .put(url)
+            .bearer_auth(&self.token)
+            .json(&json!({ "range": range, "majorDimension": "ROWS", "values": rows }))
+            .send()?
+            .error_for_status()
+            .with_context(|| format!("Failed to write sheet {}", sheet))?;
+        Ok(())
+    }
+}
+
+/// Range covering a whole tab, quoted so names with spaces or quotes work
+fn a1_range(sheet: &str) -> String {
+    format!("'{}'", sheet.replace('\'', "''"))
+}
+
+fn values_url(spreadsheet_id: &str, range: &str) -> Result<Url> {
+    let mut url = Url::parse(SHEETS_API)?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow!("Invalid Sheets API URL"))?
+        .extend([spreadsheet_id, "values", range]);
+    Ok(url)
+}
+
+/// Header row followed by the export's rows for a season
+pub fn table_values(client: &mut Client, export: Export, season: i32) -> Result<Vec<Vec<String>>> {
+    let statement = client.prepare(export.query())?;
+    let header = statement.columns().iter().map(|c| c.name().to_string()).collect();
+
+    let mut rows = vec![header];
+    for row in client.query(&statement, &[&season])? {
+        rows.push((0..row.len()).map(|i| row.get::<_, Option<String>>(i).unwrap_or_default()).collect());
+    }
+    Ok(rows)
+}
+
+/// Push every configured export for a season, each followed by a blank row and
+/// the `footer` lines when there are any. Returns the number of tabs written.
+pub fn push(client: &mut Client, config: &SheetsConfig, season: i32, footer: &[String]) -> Result<usize> {
+    let sheets = SheetsClient::connect(&config.credentials)?;
+    for target in &config.exports {
+        let export = Export::parse(&target.table).ok_or_else(|| anyhow!("Unknown export {}", target.table))?;
+        let mut rows = table_values(client, export, season)?;
+        if !footer.is_empty() {
+            rows.push(Vec::new());
+            rows.extend(footer.iter().map(|line| vec![line.clone()]));
+        }
+        sheets.replace(&target.spreadsheet_id, &target.sheet, &rows)?;
+    }
+    Ok(config.exports.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tab_names_are_quoted_and_encoded() {
+        assert_eq!(a1_range("Weekly Recap"), "'Weekly Recap'");
+        assert_eq!(a1_range("Bob's Board"), "'Bob''s Board'");
+        assert_eq!(
+            values_url("abc123", "'Weekly Recap':clear").unwrap().as_str(),
+            "https://sheets.googleapis.com/v4/spreadsheets/abc123/values/'Weekly%20Recap':clear"
+        );
+    }
+
+    #[test]
+    fn test_export_names() {
+        assert_eq!(Export::parse("weekly_recap"), Some(Export::WeeklyRecap));
+        assert_eq!(Export::parse("box_scores"), None);
+    }
+}
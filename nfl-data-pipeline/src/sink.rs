@@ -0,0 +1,465 @@
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, Sink};
+use crate::data_source::DataSource;
+use crate::datasets::{self, Dataset};
+use crate::downloader::{Downloader, SourceMissing};
+use crate::parquet_sink::ParquetSink;
+use crate::parser::{Game, PlayerStat, RosterPlayer};
+use crate::transformer;
+
+/// A file sink for the core datasets (rosters, schedules, player stats).
+/// `--sink sqlite|parquet` write flat tables keyed on nflverse IDs and need no
+/// database server; PostgreSQL imports go through `DataPipeline`'s stages, with
+/// ID resolution, merge precedence and progress tracking.
+pub trait StatsSink {
+    fn write_players(&mut self, players: &[RosterPlayer]) -> Result<()>;
+    fn write_games(&mut self, games: &[Game]) -> Result<()>;
+    fn write_stats(&mut self, stats: &[PlayerStat]) -> Result<()>;
+
+    /// Called after the last batch of a season's file for `table`
+    fn finish_table(&mut self, _table: &'static str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called once every season is written
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Column type in the flat tables file sinks write
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Integer,
+    Real,
+    Text,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::Integer(value as i64)
+    }
+}
+
+impl From<Option<i32>> for Value {
+    fn from(value: Option<i32>) -> Self {
+        value.map_or(Value::Null, Value::from)
+    }
+}
+
+impl From<Option<f64>> for Value {
+    fn from(value: Option<f64>) -> Self {
+        value.map_or(Value::Null, Value::Real)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::Text(value.to_string())
+    }
+}
+
+impl From<Option<&str>> for Value {
+    fn from(value: Option<&str>) -> Self {
+        value.map_or(Value::Null, Value::from)
+    }
+}
+
+/// A flat table: its columns in row order and its primary key
+#[derive(Debug)]
+pub struct Table {
+    pub name: &'static str,
+    pub columns: Vec<(&'static str, ColumnType)>,
+    pub key: &'static [&'static str],
+}
+
+pub fn players_table() -> Table {
+    use ColumnType::*;
+    Table {
+        name: "players",
+        columns: vec![
+            ("nfl_id", Text),
+            ("name", Text),
+            ("first_name", Text),
+            ("last_name", Text),
+            ("position", Text),
+            ("team", Text),
+            ("jersey_number", Integer),
+            ("status", Text),
+            ("height_inches", Integer),
+            ("weight_pounds", Integer),
+            ("birth_date", Text),
+            ("college", Text),
+            ("years_pro", Integer),
+            ("rookie_year", Integer),
+            ("draft_club", Text),
+            ("draft_pick", Integer),
+            ("headshot_url", Text),
+            ("espn_id", Text),
+            ("season", Integer),
+        ],
+        key: &["nfl_id"],
+    }
+}
+
+pub fn player_row(player: &RosterPlayer) -> Vec<Value> {
+    vec![
+        player.gsis_id.as_str().into(),
+        player.full_name.as_str().into(),
+        player.first_name.as_deref().into(),
+        player.last_name.as_deref().into(),
+        player.position.as_str().into(),
+        transformer::normalize_team_abbr(&player.team).as_str().into(),
+        player.jersey_number.into(),
+        player.status.as_deref().unwrap_or("active").into(),
+        player.height.as_deref().and_then(transformer::height_to_inches).into(),
+        player.weight.into(),
+        player.birth_date.as_deref().and_then(transformer::parse_date).map(|date| date.to_string()).as_deref().into(),
+        player.college.as_deref().into(),
+        player.years_exp.into(),
+        player.rookie_year.into(),
+        player.draft_club.as_deref().map(transformer::normalize_team_abbr).as_deref().into(),
+        player.draft_number.into(),
+        player.headshot_url.as_deref().into(),
+        player.espn_id.as_deref().into(),
+        player.season.into(),
+    ]
+}
+
+pub fn games_table() -> Table {
+    use ColumnType::*;
+    Table {
+        name: "games",
+        columns: vec![
+            ("nfl_game_id", Text),
+            ("season", Integer),
+            ("season_type", Text),
+            ("game_type", Text),
+            ("week", Integer),
+            ("game_date", Text),
+            ("kickoff", Text),
+            ("home_team", Text),
+            ("away_team", Text),
+            ("home_score", Integer),
+            ("away_score", Integer),
+            ("overtime", Integer),
+            ("location", Text),
+            ("roof", Text),
+            ("surface", Text),
+            ("stadium", Text),
+            ("spread_line", Real),
+            ("total_line", Real),
+            ("home_moneyline", Real),
+            ("away_moneyline", Real),
+            ("home_rest", Integer),
+            ("away_rest", Integer),
+            ("referee", Text),
+        ],
+        key: &["nfl_game_id"],
+    }
+}
+
+pub fn game_row(game: &Game) -> Vec<Value> {
+    let kickoff = transformer::kickoff(&game.gameday, game.gametime.as_deref()).map(|kickoff| kickoff.to_rfc3339());
+    vec![
+        game.game_id.as_str().into(),
+        game.season.into(),
+        transformer::season_type_for_game_type(&game.game_type).into(),
+        game.game_type.as_str().into(),
+        game.week.into(),
+        game.gameday.as_str().into(),
+        kickoff.as_deref().into(),
+        transformer::normalize_team_abbr(&game.home_team).as_str().into(),
+        transformer::normalize_team_abbr(&game.away_team).as_str().into(),
+        game.home_score.into(),
+        game.away_score.into(),
+        game.overtime.into(),
+        game.location.as_deref().into(),
+        game.roof.as_deref().into(),
+        game.surface.as_deref().into(),
+        game.stadium.as_deref().into(),
+        game.spread_line.into(),
+        game.total_line.into(),
+        game.home_moneyline.into(),
+        game.away_moneyline.into(),
+        game.home_rest.into(),
+        game.away_rest.into(),
+        game.referee.as_deref().into(),
+    ]
+}
+
+pub fn game_stats_table() -> Table {
+    use ColumnType::*;
+    let mut columns = vec![
+        ("player_id", Text),
+        ("season", Integer),
+        ("week", Integer),
+        ("season_type", Text),
+        ("team", Text),
+        ("position", Text),
+        ("completions", Integer),
+        ("attempts", Integer),
+        ("passing_yards", Integer),
+        ("passing_tds", Integer),
+        ("interceptions", Integer),
+        ("rushing_yards", Integer),
+        ("rushing_tds", Integer),
+        ("receptions", Integer),
+        ("targets", Integer),
+        ("receiving_yards", Integer),
+        ("receiving_tds", Integer),
+        ("target_share", Real),
+        ("def_sacks", Real),
+        ("fantasy_points", Real),
+        ("fantasy_points_ppr", Real),
+    ];
+    columns.extend(transformer::DEFENSE_KICKING_COLUMNS.iter().map(|column| (*column, Integer)));
+    Table { name: "game_stats", columns, key: &["player_id", "season", "week", "season_type"] }
+}
+
+/// Counting stats are whole numbers in the database, as `DataPipeline` writes them
+pub fn stat_row(stat: &PlayerStat) -> Vec<Value> {
+    let whole = |value: Option<f64>| Value::from(value.map(|v| v as i32));
+    let mut row = vec![
+        stat.player_id.as_str().into(),
+        stat.season.into(),
+        stat.week.into(),
+        stat.season_type.as_str().into(),
+        stat.recent_team.as_deref().map(transformer::normalize_team_abbr).as_deref().into(),
+        stat.position.as_deref().into(),
+        whole(stat.completions),
+        whole(stat.attempts),
+        whole(stat.passing_yards),
+        stat.passing_tds.into(),
+        stat.interceptions.into(),
+        whole(stat.rushing_yards),
+        stat.rushing_tds.into(),
+        whole(stat.receptions),
+        whole(stat.targets),
+        whole(stat.receiving_yards),
+        stat.receiving_tds.into(),
+        stat.target_share.into(),
+        stat.def_sacks.into(),
+        stat.fantasy_points.into(),
+        stat.fantasy_points_ppr.into(),
+    ];
+    row.extend(transformer::defense_kicking_values(stat).into_iter().map(Value::from));
+    row
+}
+
+/// What importing a season wrote
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Imported {
+    pub players: usize,
+    pub games: usize,
+    pub stats: usize,
+    /// Rows that failed to parse
+    pub failed: usize,
+}
+
+/// Import a season's rosters, schedule and player stats from `source` into
+/// `sink`, honouring `--season-types`. A file the source doesn't have is
+/// skipped with a warning.
+pub fn import_season(config: &Config, source: &dyn DataSource, sink: &mut dyn StatsSink, year: i32) -> Result<Imported> {
+    let mut imported = Imported::default();
+    let batch_size = config.batch_size;
+
+    let (players, failed) = stream(source, &datasets::ROSTERS, year, batch_size, |_| true, |batch| sink.write_players(batch))?;
+    sink.finish_table(datasets::ROSTERS.target_table)?;
+    imported.players = players;
+    imported.failed += failed;
+
+    let accept = |game: &Game| config.includes_season_type(transformer::season_type_for_game_type(&game.game_type));
+    let (games, failed) = stream(source, &datasets::SCHEDULES, year, batch_size, accept, |batch| sink.write_games(batch))?;
+    sink.finish_table(datasets::SCHEDULES.target_table)?;
+    imported.games = games;
+    imported.failed += failed;
+
+    let accept = |stat: &PlayerStat| config.includes_season_type(&stat.season_type);
+    let (stats, failed) = stream(source, &datasets::PLAYER_STATS, year, batch_size, accept, |batch| sink.write_stats(batch))?;
+    sink.finish_table(datasets::PLAYER_STATS.target_table)?;
+    imported.stats = stats;
+    imported.failed += failed;
+
+    Ok(imported)
+}
+
+/// Parse a season file in batches, returning rows written and rows failed
+fn stream<T: DeserializeOwned>(
+    source: &dyn DataSource,
+    dataset: &Dataset<T>,
+    year: i32,
+    batch_size: usize,
+    accept: impl Fn(&T) -> bool,
+    mut write: impl FnMut(&[T]) -> Result<()>,
+) -> Result<(usize, usize)> {
+    let (header, records) = match source.records(&dataset.untyped(), year, datasets::SourceFormat::Csv) {
+        Ok(opened) => opened,
+        Err(e) if e.downcast_ref::<SourceMissing>().is_some() => {
+            warn!("  ⚠️  {} {}: {}", dataset.name, year, e);
+            return Ok((0, 0));
+        }
+        Err(e) => return Err(e),
+    };
+    let (mut written, mut failed) = (0, 0);
+    let mut batch = Vec::with_capacity(batch_size);
+    for record in records {
+        match record.and_then(|record| Ok(dataset.dialect.deserialize::<T>(&record, &header)?)) {
+            Ok(row) if accept(&row) => batch.push(row),
+            Ok(_) => {}
+            Err(e) => {
+                failed += 1;
+                if failed <= 10 {
+                    warn!("Failed to parse {} row: {}", dataset.name, e);
+                }
+            }
+        }
+        if batch.len() >= batch_size {
+            write(&batch)?;
+            written += batch.len();
+            batch.clear();
+        }
+    }
+    if !batch.is_empty() {
+        write(&batch)?;
+        written += batch.len();
+    }
+    Ok((written, failed))
+}
+
+/// `--out` for a file sink: a file path as given, or a directory to put
+/// `file_name` in
+pub fn output_path(out: &str, file_name: &str) -> PathBuf {
+    let out = Path::new(out);
+    match out.extension() {
+        Some(_) => out.to_path_buf(),
+        None => out.join(file_name),
+    }
+}
+
+/// `--sink` other than postgres: import `--year` (or `--start-year` to
+/// `--end-year` in full mode) into a file, without a database server
+pub fn run(config: &Config) -> Result<()> {
+    let years: Vec<i32> = match config.mode.as_str() {
+        "year" => vec![config.year],
+        "full" => (config.start_year..=config.end_year).collect(),
+        mode => return Err(anyhow!("--sink {} supports --mode year and full, not {}", config.sink.as_str(), mode)),
+    };
+    let source = Downloader::from_config(config)?;
+    let mut sink = open(config)?;
+    for year in years {
+        info!("📅 {}", year);
+        let imported = import_season(config, &source, sink.as_mut(), year)?;
+        info!(
+            "  ✅ {} players, {} games, {} stat lines ({} rows failed to parse)",
+            imported.players, imported.games, imported.stats, imported.failed
+        );
+    }
+    sink.finish()
+}
+
+fn open(config: &Config) -> Result<Box<dyn StatsSink>> {
+    match config.sink {
+        Sink::Postgres => Err(anyhow!("--sink postgres runs the pipeline, not a file sink")),
+        #[cfg(feature = "sqlite")]
+        Sink::Sqlite => {
+            let path = output_path(&config.out_dir, "nfl.sqlite");
+            info!("🪶 Writing to SQLite database {}", path.display());
+            Ok(Box::new(crate::sqlite::SqliteSink::open(&path)?))
+        }
+        #[cfg(not(feature = "sqlite"))]
+        Sink::Sqlite => Err(anyhow!("--sink sqlite needs a build with the sqlite feature (cargo build --release --features sqlite)")),
+        Sink::Parquet => {
+            info!("📦 Writing Parquet files under {}", config.out_dir);
+            Ok(Box::new(ParquetSink::open(Path::new(&config.out_dir))?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source::MemorySource;
+
+    /// Rows written per call, by table
+    #[derive(Default)]
+    struct Recorder {
+        writes: Vec<(&'static str, usize)>,
+    }
+
+    impl StatsSink for Recorder {
+        fn write_players(&mut self, players: &[RosterPlayer]) -> Result<()> {
+            self.writes.push(("players", players.len()));
+            Ok(())
+        }
+
+        fn write_games(&mut self, games: &[Game]) -> Result<()> {
+            self.writes.push(("games", games.len()));
+            Ok(())
+        }
+
+        fn write_stats(&mut self, stats: &[PlayerStat]) -> Result<()> {
+            self.writes.push(("game_stats", stats.len()));
+            Ok(())
+        }
+
+        fn finish_table(&mut self, table: &'static str) -> Result<()> {
+            self.writes.push((table, 0));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_import_season_batches_and_filters() {
+        let source = MemorySource::default().with_file(
+            &datasets::SCHEDULES,
+            2024,
+            "game_id,season,game_type,week,gameday,away_team,home_team\n\
+             2024_01_BAL_KC,2024,REG,1,2024-09-05,BAL,KC\n\
+             2024_01_GB_PHI,2024,REG,1,2024-09-06,GB,PHI\n\
+             2024_01_PIT_ATL,2024,REG,1,2024-09-08,PIT,ATL\n\
+             2024_19_GB_PHI,2024,WC,19,2025-01-12,GB,PHI\n\
+             not_a_game,2024,REG,x,2024-09-08,PIT,ATL\n",
+        );
+        let config = Config::from_args(
+            &["--mode".to_string(), "year".to_string(), "--batch-size".to_string(), "2".to_string()],
+            |name| (name == "DATABASE_URL").then(|| "postgres://db".to_string()),
+        )
+        .unwrap();
+        let mut recorder = Recorder::default();
+
+        let imported = import_season(&config, &source, &mut recorder, 2024).unwrap();
+        assert_eq!(imported, Imported { players: 0, games: 3, stats: 0, failed: 1 });
+        assert_eq!(
+            recorder.writes,
+            [("players", 0), ("games", 2), ("games", 1), ("games", 0), ("game_stats", 0)]
+        );
+    }
+
+    #[test]
+    fn test_rows_match_columns() {
+        let game: Game = datasets::CsvDialect::NFLVERSE
+            .deserialize(
+                &csv::StringRecord::from(vec!["2024_01_BAL_KC", "2024", "REG", "1", "2024-09-05", "20:20", "BAL", "KC"]),
+                &csv::StringRecord::from(vec!["game_id", "season", "game_type", "week", "gameday", "gametime", "away_team", "home_team"]),
+            )
+            .unwrap();
+        let row = game_row(&game);
+        assert_eq!(row.len(), games_table().columns.len());
+        assert_eq!(row[6], Value::Text("2024-09-06T00:20:00+00:00".to_string()));
+        assert_eq!(row[9], Value::Null);
+        assert_eq!(game_stats_table().columns.len(), 21 + transformer::DEFENSE_KICKING_COLUMNS.len());
+    }
+}
@@ -0,0 +1,341 @@
+use anyhow::{Context, Result};
+use postgres::Client;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::transformer;
+
+/// Sleeper's public read-only API
+pub const API_URL: &str = "https://api.sleeper.app/v1";
+/// `platform` of Sleeper rows in the fantasy league tables
+pub const PLATFORM: &str = "sleeper";
+/// Dataset name recorded in `source_runs` and telemetry
+pub const DATASET: &str = "sleeper_league";
+/// Last week a Sleeper league can have matchups in
+const LAST_WEEK: i32 = 18;
+
+#[derive(Debug, Deserialize)]
+pub struct League {
+    pub league_id: String,
+    pub name: String,
+    pub season: String,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub total_rosters: Option<i32>,
+    #[serde(default)]
+    pub scoring_settings: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct User {
+    pub user_id: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub metadata: Option<UserMetadata>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UserMetadata {
+    #[serde(default)]
+    pub team_name: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RosterSettings {
+    #[serde(default)]
+    pub wins: Option<i32>,
+    #[serde(default)]
+    pub losses: Option<i32>,
+    #[serde(default)]
+    pub ties: Option<i32>,
+    /// Points for, split into whole points and hundredths
+    #[serde(default)]
+    pub fpts: Option<i32>,
+    #[serde(default)]
+    pub fpts_decimal: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Roster {
+    pub roster_id: i32,
+    #[serde(default)]
+    pub owner_id: Option<String>,
+    #[serde(default)]
+    pub players: Option<Vec<String>>,
+    #[serde(default)]
+    pub starters: Option<Vec<String>>,
+    #[serde(default)]
+    pub reserve: Option<Vec<String>>,
+    #[serde(default)]
+    pub taxi: Option<Vec<String>>,
+    #[serde(default)]
+    pub settings: RosterSettings,
+}
+
+impl Roster {
+    /// Every rostered player with its slot: `starter`, `reserve` (IR), `taxi` or `bench`.
+    /// Empty starter slots (`"0"`) are left out.
+    pub fn slots(&self) -> Vec<(&str, &'static str)> {
+        let lists = [(&self.starters, "starter"), (&self.reserve, "reserve"), (&self.taxi, "taxi"), (&self.players, "bench")];
+        let mut slots: Vec<(&str, &'static str)> = Vec::new();
+        for (list, slot) in lists {
+            for id in list.iter().flatten().map(String::as_str) {
+                if id != "0" && !slots.iter().any(|(seen, _)| *seen == id) {
+                    slots.push((id, slot));
+                }
+            }
+        }
+        slots
+    }
+
+    pub fn points_for(&self) -> Option<f64> {
+        let whole = self.settings.fpts?;
+        Some(whole as f64 + self.settings.fpts_decimal.unwrap_or(0) as f64 / 100.0)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Matchup {
+    pub roster_id: i32,
+    /// Rosters sharing a matchup ID play each other that week; `None` on a bye
+    #[serde(default)]
+    pub matchup_id: Option<i32>,
+    #[serde(default)]
+    pub points: Option<f64>,
+    #[serde(default)]
+    pub starters: Option<Vec<String>>,
+    #[serde(default)]
+    pub players_points: Option<HashMap<String, f64>>,
+}
+
+/// Sleeper's view of the NFL calendar
+#[derive(Debug, Deserialize)]
+pub struct NflState {
+    pub week: i32,
+    pub season: String,
+}
+
+/// Everything imported for one league
+#[derive(Debug)]
+pub struct LeagueData {
+    pub league: League,
+    pub users: Vec<User>,
+    pub rosters: Vec<Roster>,
+    /// Weeks with matchups, in order
+    pub matchups: Vec<(i32, Vec<Matchup>)>,
+}
+
+fn get<T: DeserializeOwned>(http: &reqwest::blocking::Client, url: &str) -> Result<T> {
+    http.get(url)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.json())
+        .with_context(|| format!("Failed to fetch {}", url))
+}
+
+pub fn fetch_state(http: &reqwest::blocking::Client, base: &str) -> Result<NflState> {
+    get(http, &format!("{}/state/nfl", base))
+}
+
+/// A league, its users and rosters, and its matchups through the current week
+/// (every week for past seasons)
+pub fn fetch_league(http: &reqwest::blocking::Client, base: &str, league_id: &str, state: &NflState) -> Result<LeagueData> {
+    let league: League = get(http, &format!("{}/league/{}", base, league_id))?;
+    let users = get(http, &format!("{}/league/{}/users", base, league_id))?;
+    let rosters = get(http, &format!("{}/league/{}/rosters", base, league_id))?;
+
+    let last_week = if league.season == state.season { state.week.min(LAST_WEEK) } else { LAST_WEEK };
+    let mut matchups = Vec::new();
+    for week in 1..=last_week {
+        let week_matchups: Vec<Matchup> = get(http, &format!("{}/league/{}/matchups/{}", base, league_id, week))?;
+        if !week_matchups.is_empty() {
+            matchups.push((week, week_matchups));
+        }
+    }
+    Ok(LeagueData { league, users, rosters, matchups })
+}
+
+/// Our players by Sleeper ID, from the roster files' crosswalk, and teams by
+/// abbreviation for team defenses, which Sleeper keys by team
+pub struct Links {
+    players: HashMap<String, Uuid>,
+    teams: HashMap<String, Uuid>,
+}
+
+impl Links {
+    pub fn load(client: &mut Client) -> Result<Self> {
+        let players = client
+            .query("SELECT external_id, player_id FROM player_external_ids WHERE source = $1", &[&PLATFORM])
+            .context("Failed to load Sleeper IDs")?
+            .iter()
+            .map(|row| (row.get(0), row.get(1)))
+            .collect();
+        let teams = client
+            .query("SELECT abbreviation, id FROM teams", &[])?
+            .iter()
+            .map(|row| (row.get(0), row.get(1)))
+            .collect();
+        Ok(Links { players, teams })
+    }
+
+    /// (player, team) behind a Sleeper player ID
+    fn resolve(&self, sleeper_id: &str) -> (Option<Uuid>, Option<Uuid>) {
+        match self.players.get(sleeper_id) {
+            Some(player_id) => (Some(*player_id), None),
+            None if sleeper_id.chars().all(|c| c.is_ascii_alphabetic()) => {
+                (None, self.teams.get(&transformer::normalize_team_abbr(sleeper_id)).copied())
+            }
+            None => (None, None),
+        }
+    }
+}
+
+/// Counts from writing one league
+#[derive(Debug, Default)]
+pub struct LeagueWrite {
+    pub rosters: usize,
+    pub roster_players: usize,
+    /// Rostered Sleeper IDs with no player or team defense in the database
+    pub unlinked: usize,
+    pub matchups: usize,
+}
+
+/// Replace a league's rosters and matchups with the imported ones
+pub fn write_league(client: &mut Client, data: &LeagueData, links: &Links) -> Result<LeagueWrite> {
+    let league_id = data.league.league_id.as_str();
+    let season: i32 = data.league.season.parse().with_context(|| format!("Invalid league season: {}", data.league.season))?;
+    let mut write = LeagueWrite::default();
+    let mut tx = client.transaction()?;
+
+    tx.execute(
+        "INSERT INTO fantasy_leagues (platform, league_id, name, season, status, total_rosters, scoring_settings, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7::text::jsonb, NOW())
+         ON CONFLICT (platform, league_id) DO UPDATE SET
+             name = EXCLUDED.name,
+             season = EXCLUDED.season,
+             status = EXCLUDED.status,
+             total_rosters = EXCLUDED.total_rosters,
+             scoring_settings = EXCLUDED.scoring_settings,
+             updated_at = NOW()",
+        &[&PLATFORM, &league_id, &data.league.name, &season, &data.league.status, &data.league.total_rosters, &data.league.scoring_settings.to_string()],
+    )
+    .context("Failed to write league")?;
+
+    let users: HashMap<&str, &User> = data.users.iter().map(|user| (user.user_id.as_str(), user)).collect();
+    tx.execute("DELETE FROM fantasy_roster_players WHERE platform = $1 AND league_id = $2", &[&PLATFORM, &league_id])?;
+    for roster in &data.rosters {
+        let owner = roster.owner_id.as_deref().and_then(|id| users.get(id));
+        let team_name = owner.and_then(|user| user.metadata.as_ref()).and_then(|metadata| metadata.team_name.as_deref());
+        tx.execute(
+            "INSERT INTO fantasy_rosters (platform, league_id, roster_id, owner_id, owner_name, team_name, wins, losses, ties,
+                 points_for, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10::float8::numeric, NOW())
+             ON CONFLICT (platform, league_id, roster_id) DO UPDATE SET
+                 owner_id = EXCLUDED.owner_id,
+                 owner_name = EXCLUDED.owner_name,
+                 team_name = EXCLUDED.team_name,
+                 wins = EXCLUDED.wins,
+                 losses = EXCLUDED.losses,
+                 ties = EXCLUDED.ties,
+                 points_for = EXCLUDED.points_for,
+                 updated_at = NOW()",
+            &[
+                &PLATFORM,
+                &league_id,
+                &roster.roster_id,
+                &roster.owner_id,
+                &owner.and_then(|user| user.display_name.as_deref()),
+                &team_name,
+                &roster.settings.wins,
+                &roster.settings.losses,
+                &roster.settings.ties,
+                &roster.points_for(),
+            ],
+        )
+        .with_context(|| format!("Failed to write roster {}", roster.roster_id))?;
+        write.rosters += 1;
+
+        for (sleeper_id, slot) in roster.slots() {
+            let (player_id, team_id) = links.resolve(sleeper_id);
+            write.unlinked += usize::from(player_id.is_none() && team_id.is_none());
+            tx.execute(
+                "INSERT INTO fantasy_roster_players (platform, league_id, roster_id, platform_player_id, player_id, team_id, slot)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)",
+                &[&PLATFORM, &league_id, &roster.roster_id, &sleeper_id, &player_id, &team_id, &slot],
+            )?;
+            write.roster_players += 1;
+        }
+    }
+
+    for (week, matchups) in &data.matchups {
+        tx.execute(
+            "DELETE FROM fantasy_matchup_players WHERE platform = $1 AND league_id = $2 AND week = $3",
+            &[&PLATFORM, &league_id, week],
+        )?;
+        for matchup in matchups {
+            tx.execute(
+                "INSERT INTO fantasy_matchups (platform, league_id, season, week, roster_id, matchup_id, points, updated_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7::float8::numeric, NOW())
+                 ON CONFLICT (platform, league_id, week, roster_id) DO UPDATE SET
+                     season = EXCLUDED.season,
+                     matchup_id = EXCLUDED.matchup_id,
+                     points = EXCLUDED.points,
+                     updated_at = NOW()",
+                &[&PLATFORM, &league_id, &season, week, &matchup.roster_id, &matchup.matchup_id, &matchup.points],
+            )?;
+            write.matchups += 1;
+
+            let starters = matchup.starters.as_deref().unwrap_or_default();
+            for (sleeper_id, points) in matchup.players_points.iter().flatten() {
+                let (player_id, team_id) = links.resolve(sleeper_id);
+                tx.execute(
+                    "INSERT INTO fantasy_matchup_players (platform, league_id, week, roster_id, platform_player_id, player_id,
+                         team_id, starter, points)
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9::float8::numeric)",
+                    &[
+                        &PLATFORM,
+                        &league_id,
+                        week,
+                        &matchup.roster_id,
+                        sleeper_id,
+                        &player_id,
+                        &team_id,
+                        &starters.contains(sleeper_id),
+                        points,
+                    ],
+                )?;
+            }
+        }
+    }
+
+    tx.commit()?;
+    Ok(write)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roster_slots_and_points() {
+        let roster: Roster = serde_json::from_str(
+            r#"{"roster_id": 3, "owner_id": "7311", "players": ["4046", "6794", "KC", "9509", "8150"],
+                "starters": ["4046", "0", "KC"], "reserve": ["6794"], "taxi": null,
+                "settings": {"wins": 9, "losses": 5, "ties": 0, "fpts": 1654, "fpts_decimal": 38}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            roster.slots(),
+            vec![("4046", "starter"), ("KC", "starter"), ("6794", "reserve"), ("9509", "bench"), ("8150", "bench")]
+        );
+        assert_eq!(roster.points_for(), Some(1654.38));
+
+        let empty: Roster = serde_json::from_str(r#"{"roster_id": 4, "owner_id": null, "players": null, "settings": {}}"#).unwrap();
+        assert!(empty.slots().is_empty());
+        assert_eq!(empty.points_for(), None);
+    }
+}
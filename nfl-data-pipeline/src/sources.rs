@@ -0,0 +1,241 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+use log::info;
+use postgres::Client;
+
+use crate::datasets;
+
+/// An upstream publisher whose data lands in the database
+#[derive(Debug, PartialEq, Eq)]
+pub struct Source {
+    /// Key in `sources` and `source_runs`
+    pub key: &'static str,
+    pub name: &'static str,
+    pub homepage: &'static str,
+    pub license: &'static str,
+    /// Credit line to reproduce wherever the data is published
+    pub attribution: &'static str,
+}
+
+pub const NFLVERSE: Source = Source {
+    key: "nflverse",
+    name: "nflverse",
+    homepage: "https://github.com/nflverse/nflverse-data",
+    license: "CC-BY-4.0",
+    attribution: "NFL data from nflverse (github.com/nflverse), licensed under CC BY 4.0",
+};
+
+/// Contracts are Over the Cap's data, republished by nflverse
+pub const OVER_THE_CAP: Source = Source {
+    key: "overthecap",
+    name: "Over the Cap",
+    homepage: "https://overthecap.com",
+    license: "Over the Cap terms of use (attribution required)",
+    attribution: "Contract data courtesy of Over the Cap (overthecap.com) via nflverse",
+};
+
+pub const CFBFASTR: Source = Source {
+    key: "cfbfastr",
+    name: "cfbfastR-data (sportsdataverse)",
+    homepage: "https://github.com/sportsdataverse/cfbfastR-data",
+    license: "sportsdataverse and CollegeFootballData.com terms of use",
+    attribution: "College stats from cfbfastR (sportsdataverse) and CollegeFootballData.com",
+};
+
+pub const ESPN: Source = Source {
+    key: "espn",
+    name: "ESPN",
+    homepage: "https://www.espn.com/nfl/scoreboard",
+    license: "ESPN terms of use (not openly licensed)",
+    attribution: "Live scores courtesy of ESPN",
+};
+
+pub const THE_ODDS_API: Source = Source {
+    key: "the_odds_api",
+    name: "The Odds API",
+    homepage: "https://the-odds-api.com",
+    license: "The Odds API terms of use (not openly licensed)",
+    attribution: "Betting odds courtesy of The Odds API",
+};
+
+pub const SLEEPER: Source = Source {
+    key: "sleeper",
+    name: "Sleeper",
+    homepage: "https://docs.sleeper.com",
+    license: "Sleeper API terms of use (not openly licensed)",
+    attribution: "Fantasy league data courtesy of Sleeper",
+};
+
+/// RSS feeds configured with `--news-feed`; each item keeps its publisher's link
+pub const NEWS_FEEDS: Source = Source {
+    key: "news_feeds",
+    name: "RSS news feeds",
+    homepage: "https://www.rssboard.org/rss-specification",
+    license: "Each publisher's terms of use",
+    attribution: "Player news from publishers' RSS feeds, linked to the original articles",
+};
+
+/// Every known source, in the order footers list them
+pub const SOURCES: &[&Source] = &[&NFLVERSE, &OVER_THE_CAP, &CFBFASTR, &ESPN, &THE_ODDS_API, &SLEEPER, &NEWS_FEEDS];
+
+/// Dataset name recorded for `--mode live` polls
+pub const SCOREBOARD_DATASET: &str = "scoreboard";
+
+/// The source behind a dataset, from its name and download URL
+pub fn for_dataset(name: &str, url: &str) -> &'static Source {
+    if name == datasets::CONTRACTS.name {
+        &OVER_THE_CAP
+    } else if url.contains("sportsdataverse/") {
+        &CFBFASTR
+    } else if name == SCOREBOARD_DATASET || name.starts_with("espn_") || url.contains("espn.com/") {
+        &ESPN
+    } else if name.starts_with("odds_") || url.contains("the-odds-api.com/") {
+        &THE_ODDS_API
+    } else if name.starts_with("sleeper_") || url.contains("api.sleeper.app/") {
+        &SLEEPER
+    } else if name.starts_with("rss_") {
+        &NEWS_FEEDS
+    } else {
+        &NFLVERSE
+    }
+}
+
+fn by_key(key: &str) -> Option<&'static Source> {
+    SOURCES.iter().copied().find(|source| source.key == key)
+}
+
+/// Record that a run read a dataset, refreshing the source's row in `sources`
+/// with this build's license and attribution. One row per run, dataset and season.
+pub fn record_run(
+    client: &mut Client,
+    run_started_at: NaiveDateTime,
+    mode: &str,
+    dataset: &str,
+    season: i32,
+    url: &str,
+) -> Result<()> {
+    let source = for_dataset(dataset, url);
+    let mut tx = client.transaction()?;
+    tx.execute(
+        "INSERT INTO sources (key, name, homepage, license, attribution, updated_at)
+         VALUES ($1, $2, $3, $4, $5, NOW())
+         ON CONFLICT (key) DO UPDATE SET
+             name = EXCLUDED.name, homepage = EXCLUDED.homepage, license = EXCLUDED.license,
+             attribution = EXCLUDED.attribution, updated_at = NOW()
+         WHERE (sources.name, sources.homepage, sources.license, sources.attribution)
+               IS DISTINCT FROM (EXCLUDED.name, EXCLUDED.homepage, EXCLUDED.license, EXCLUDED.attribution)",
+        &[&source.key, &source.name, &source.homepage, &source.license, &source.attribution],
+    )?;
+    tx.execute(
+        "INSERT INTO source_runs (run_started_at, mode, source_key, dataset, season, url, license, attribution)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+         ON CONFLICT (run_started_at, dataset, season) DO NOTHING",
+        &[&run_started_at, &mode, &source.key, &dataset, &season, &url, &source.license, &source.attribution],
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Sources any run has read from, in footer order
+pub fn in_database(client: &mut Client) -> Result<Vec<&'static Source>> {
+    let keys: Vec<String> = client
+        .query("SELECT DISTINCT source_key FROM source_runs", &[])
+        .context("Failed to load recorded sources")?
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+    Ok(SOURCES.iter().copied().filter(|source| keys.iter().any(|key| key == source.key)).collect())
+}
+
+/// Attribution lines for an export footer, one per source
+pub fn footer(sources: &[&Source]) -> Vec<String> {
+    SOURCES
+        .iter()
+        .filter(|source| sources.contains(*source))
+        .map(|source| format!("{} ({})", source.attribution, source.homepage))
+        .collect()
+}
+
+/// A source's use across every recorded run
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceUsage {
+    pub source: &'static Source,
+    pub datasets: Vec<String>,
+    pub first_season: Option<i32>,
+    pub last_season: Option<i32>,
+    pub runs: i64,
+    pub last_run: String,
+}
+
+/// `--mode sources`: what each source contributed and when it was last read
+pub fn report(client: &mut Client) -> Result<Vec<SourceUsage>> {
+    let rows = client
+        .query(
+            "SELECT source_key, array_agg(DISTINCT dataset ORDER BY dataset),
+                    MIN(NULLIF(season, 0)), MAX(NULLIF(season, 0)),
+                    COUNT(DISTINCT run_started_at), to_char(MAX(run_started_at), 'YYYY-MM-DD HH24:MI')
+             FROM source_runs
+             GROUP BY source_key",
+            &[],
+        )
+        .context("Failed to load source usage")?;
+
+    let mut usage: Vec<SourceUsage> = rows
+        .iter()
+        .filter_map(|row| {
+            Some(SourceUsage {
+                source: by_key(row.get(0))?,
+                datasets: row.get(1),
+                first_season: row.get(2),
+                last_season: row.get(3),
+                runs: row.get(4),
+                last_run: row.get(5),
+            })
+        })
+        .collect();
+    usage.sort_by_key(|u| SOURCES.iter().position(|source| *source == u.source));
+    Ok(usage)
+}
+
+pub fn log_report(usage: &[SourceUsage]) {
+    info!("📜 Data sources recorded: {}", usage.len());
+    for entry in usage {
+        let seasons = match (entry.first_season, entry.last_season) {
+            (Some(first), Some(last)) if first != last => format!(", seasons {}-{}", first, last),
+            (Some(season), _) => format!(", season {}", season),
+            _ => String::new(),
+        };
+        info!(
+            "  {} [{}]: {} runs, last {}{}",
+            entry.source.name, entry.source.license, entry.runs, entry.last_run, seasons
+        );
+        info!("    datasets: {}", entry.datasets.join(", "));
+        info!("    attribution: {}", entry.source.attribution);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dataset_sources() {
+        assert_eq!(for_dataset(datasets::ROSTERS.name, &datasets::ROSTERS.url(2024)).key, "nflverse");
+        assert_eq!(for_dataset(datasets::CONTRACTS.name, &datasets::CONTRACTS.url(0)).key, "overthecap");
+        assert_eq!(for_dataset(datasets::COLLEGE_STATS.name, &datasets::COLLEGE_STATS.url(2023)).key, "cfbfastr");
+        assert_eq!(for_dataset(SCOREBOARD_DATASET, "http://localhost:8080/scoreboard").key, "espn");
+        assert_eq!(for_dataset("espn_athletes", "http://localhost:8080/athletes").key, "espn");
+        assert_eq!(for_dataset("odds_api", "https://api.the-odds-api.com/v4/sports/americanfootball_nfl/odds").key, "the_odds_api");
+        assert_eq!(for_dataset("sleeper_league", "https://api.sleeper.app/v1/league/1048326254392201216").key, "sleeper");
+        assert_eq!(for_dataset("rss_player_news", "https://www.rotowire.com/rss/news.php?sport=NFL").key, "news_feeds");
+    }
+
+    #[test]
+    fn test_footer_follows_source_order() {
+        let footer = footer(&[&ESPN, &NFLVERSE, &ESPN]);
+        assert_eq!(footer.len(), 2);
+        assert!(footer[0].starts_with("NFL data from nflverse"));
+        assert_eq!(footer[1], "Live scores courtesy of ESPN (https://www.espn.com/nfl/scoreboard)");
+        assert!(super::footer(&[]).is_empty());
+    }
+}
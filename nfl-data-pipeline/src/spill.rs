@@ -0,0 +1,354 @@
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use log::info;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use sha2::{Digest, Sha256};
+use std::io::{self, BufRead, BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::dead_letter::Row;
+
+/// Parsed rows take several times their CSV size in memory; in-flight batches
+/// get this fraction of the budget
+const BATCH_BUDGET_DIVISOR: usize = 4;
+
+/// Distinguishes spill files created by the same process
+static SPILL_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A downloaded file: streamed straight from the response, spooled in memory or
+/// to a temp file once it outgrew the memory budget, or a local file (download
+/// cache or `--source-dir`)
+pub enum Payload {
+    Stream(Box<dyn Read + Send>),
+    Memory(Vec<u8>),
+    Disk(SpillFile),
+    Local(File),
+}
+
+impl Payload {
+    /// Read the payload's bytes. A spilled file is removed once the reader is dropped.
+    pub fn open(self) -> Result<Box<dyn Read + Send>> {
+        match self {
+            Payload::Stream(reader) => Ok(reader),
+            Payload::Memory(bytes) => Ok(Box::new(Cursor::new(bytes))),
+            Payload::Disk(spill) => {
+                let file = File::open(&spill.path)
+                    .with_context(|| format!("Failed to open spill file {}", spill.path.display()))?;
+                Ok(Box::new(SpillReader {
+                    reader: BufReader::new(file),
+                    _spill: spill,
+                }))
+            }
+            Payload::Local(file) => Ok(Box::new(BufReader::new(file))),
+        }
+    }
+
+    /// Random-access copy of the payload, for formats that can't be read front to back.
+    /// A streamed payload is read into memory first.
+    pub fn into_seekable(self) -> Result<Seekable> {
+        match self {
+            Payload::Stream(mut reader) => {
+                let mut bytes = Vec::new();
+                reader.read_to_end(&mut bytes)?;
+                Ok(Seekable::Memory(Bytes::from(bytes)))
+            }
+            Payload::Memory(bytes) => Ok(Seekable::Memory(Bytes::from(bytes))),
+            Payload::Disk(spill) => {
+                let file = File::open(&spill.path)
+                    .with_context(|| format!("Failed to open spill file {}", spill.path.display()))?;
+                // The open handle keeps the data readable after the spill file is unlinked
+                drop(spill);
+                Ok(Seekable::Disk(file))
+            }
+            Payload::Local(file) => Ok(Seekable::Disk(file)),
+        }
+    }
+
+    /// SHA-256 of the bytes as downloaded (a gzipped file is hashed compressed),
+    /// returned with the payload rewound for parsing. A stream is read into memory first.
+    pub fn fingerprint(self) -> Result<(Payload, String)> {
+        let mut hasher = Sha256::new();
+        let mut hashed = Ok(0);
+        let payload = self.inspect(|reader| hashed = io::copy(reader, &mut hasher))?;
+        hashed?;
+        Ok((payload, format!("{:x}", hasher.finalize())))
+    }
+
+    /// Hand the bytes as downloaded to `read` once, returning the payload rewound
+    /// for parsing. A stream is read into memory first.
+    pub fn inspect(self, read: impl FnOnce(&mut dyn Read)) -> Result<Payload> {
+        match self {
+            Payload::Stream(mut reader) => {
+                let mut bytes = Vec::new();
+                reader.read_to_end(&mut bytes)?;
+                read(&mut bytes.as_slice());
+                Ok(Payload::Memory(bytes))
+            }
+            Payload::Memory(bytes) => {
+                read(&mut bytes.as_slice());
+                Ok(Payload::Memory(bytes))
+            }
+            Payload::Disk(spill) => {
+                let mut file = File::open(&spill.path)
+                    .with_context(|| format!("Failed to open spill file {}", spill.path.display()))?;
+                read(&mut file);
+                Ok(Payload::Disk(spill))
+            }
+            Payload::Local(mut file) => {
+                read(&mut file);
+                file.seek(SeekFrom::Start(0))?;
+                Ok(Payload::Local(file))
+            }
+        }
+    }
+
+}
+
+/// A fully downloaded payload that supports random access
+pub enum Seekable {
+    Memory(Bytes),
+    Disk(File),
+}
+
+/// Reader that keeps its spill file alive until parsing finishes
+struct SpillReader {
+    reader: BufReader<File>,
+    _spill: SpillFile,
+}
+
+impl Read for SpillReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl BufRead for SpillReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.reader.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.reader.consume(amount)
+    }
+}
+
+/// Temp file removed when dropped
+#[derive(Debug)]
+pub struct SpillFile {
+    path: PathBuf,
+}
+
+impl SpillFile {
+    fn create(name: &str) -> Result<(Self, File)> {
+        let id = SPILL_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!("nfl-pipeline-{}-{}-{}.spill", std::process::id(), id, name));
+        let file = File::create(&path)
+            .with_context(|| format!("Failed to create spill file {}", path.display()))?;
+        Ok((SpillFile { path }, file))
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Raw CSV bytes a pending batch may hold before it is flushed
+pub fn batch_budget(budget: Option<usize>) -> Option<usize> {
+    budget.map(|b| (b / BATCH_BUDGET_DIVISOR).max(1))
+}
+
+/// Read `source` fully, keeping it in memory while it fits in `budget` bytes and
+/// spilling to a temp file once it doesn't. No budget means always in memory.
+pub fn buffer<R: Read>(mut source: R, budget: Option<usize>, name: &str) -> Result<Payload> {
+    let Some(budget) = budget else {
+        let mut bytes = Vec::new();
+        source.read_to_end(&mut bytes)?;
+        return Ok(Payload::Memory(bytes));
+    };
+
+    // Read one byte past the budget to learn whether the source fits
+    let mut bytes = Vec::new();
+    (&mut source).take(budget as u64 + 1).read_to_end(&mut bytes)?;
+    if bytes.len() <= budget {
+        return Ok(Payload::Memory(bytes));
+    }
+
+    let (spill, mut file) = SpillFile::create(name)?;
+    file.write_all(&bytes)?;
+    drop(bytes);
+    let copied = io::copy(&mut source, &mut file)?;
+    file.flush()?;
+
+    info!(
+        "  💾 {} exceeded the {} MB memory budget, spilled {} MB to disk",
+        name,
+        budget / (1024 * 1024),
+        (budget as u64 + 1 + copied) / (1024 * 1024)
+    );
+    Ok(Payload::Disk(spill))
+}
+
+/// A parsed batch, its source rows and the source bytes it was parsed from
+pub type Batch<T> = (Vec<T>, Vec<Row>, usize);
+
+/// Parsed batches set aside on disk while the writers' queue is over the batch
+/// budget, so parsing keeps going without holding them in memory. Batches are
+/// written as JSON lines and read back in order once parsing ends.
+pub struct SpilledBatches {
+    name: &'static str,
+    file: Option<(SpillFile, BufWriter<File>)>,
+    batches: usize,
+    bytes: usize,
+}
+
+/// One spilled batch: the parsed rows, their source rows and source byte count
+#[derive(Serialize, Deserialize)]
+struct SpilledBatch<T> {
+    rows: Vec<T>,
+    source_rows: Vec<Row>,
+    bytes: usize,
+}
+
+impl SpilledBatches {
+    pub fn new(name: &'static str) -> Self {
+        SpilledBatches { name, file: None, batches: 0, bytes: 0 }
+    }
+
+    /// Append a batch, creating the spill file on first use
+    pub fn push<T: Serialize>(&mut self, rows: Vec<T>, source_rows: Vec<Row>, bytes: usize) -> Result<()> {
+        let (_, writer) = match &mut self.file {
+            Some(file) => file,
+            None => {
+                let (spill, file) = SpillFile::create(&format!("{}-batches", self.name))?;
+                self.file.insert((spill, BufWriter::new(file)))
+            }
+        };
+        serde_json::to_writer(&mut *writer, &SpilledBatch { rows, source_rows, bytes })?;
+        writer.write_all(b"\n")?;
+        self.batches += 1;
+        self.bytes += bytes;
+        Ok(())
+    }
+
+    /// Read the batches back in the order they were spilled. The spill file is
+    /// removed once the iterator is dropped.
+    pub fn drain<T: DeserializeOwned>(self) -> Result<impl Iterator<Item = Result<Batch<T>>>> {
+        let name = self.name;
+        let lines = match self.file {
+            Some((spill, mut writer)) => {
+                writer.flush()?;
+                info!(
+                    "  💾 {} writers fell behind, spilled {} batches ({} MB of source rows) to disk",
+                    name,
+                    self.batches,
+                    self.bytes / (1024 * 1024)
+                );
+                let file = File::open(&spill.path)
+                    .with_context(|| format!("Failed to open spill file {}", spill.path.display()))?;
+                Some(SpillReader { reader: BufReader::new(file), _spill: spill }.lines())
+            }
+            None => None,
+        };
+
+        Ok(lines.into_iter().flatten().map(move |line| {
+            let batch: SpilledBatch<T> =
+                serde_json::from_str(&line?).with_context(|| format!("Corrupt {} spill file", name))?;
+            Ok((batch.rows, batch.source_rows, batch.bytes))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all(payload: Payload) -> Vec<u8> {
+        let mut out = Vec::new();
+        payload.open().unwrap().read_to_end(&mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_small_payload_stays_in_memory() {
+        let payload = buffer(&b"season,week\n2024,1\n"[..], Some(1024), "test").unwrap();
+        assert!(matches!(payload, Payload::Memory(_)));
+        assert_eq!(read_all(payload), b"season,week\n2024,1\n");
+    }
+
+    #[test]
+    fn test_fingerprint_matches_across_payload_kinds() {
+        let data = b"season,week\n2024,1\n";
+        let (memory, memory_hash) = Payload::Memory(data.to_vec()).fingerprint().unwrap();
+        let (spilled, spilled_hash) = buffer(&data[..], Some(4), "test").unwrap().fingerprint().unwrap();
+        let (_, stream_hash) = Payload::Stream(Box::new(&data[..])).fingerprint().unwrap();
+
+        assert_eq!(memory_hash, spilled_hash);
+        assert_eq!(memory_hash, stream_hash);
+        assert_eq!(memory_hash.len(), 64);
+        // Hashing leaves the payload readable from the start
+        assert_eq!(read_all(memory), data);
+        assert_eq!(read_all(spilled), data);
+
+        let (_, other) = Payload::Memory(b"season,week\n2024,2\n".to_vec()).fingerprint().unwrap();
+        assert_ne!(memory_hash, other);
+    }
+
+    #[test]
+    fn test_exact_budget_stays_in_memory() {
+        let payload = buffer(&b"abcd"[..], Some(4), "test").unwrap();
+        assert!(matches!(payload, Payload::Memory(_)));
+    }
+
+    #[test]
+    fn test_large_payload_spills_and_cleans_up() {
+        let data: Vec<u8> = (0..10_000u32).flat_map(|i| i.to_le_bytes()).collect();
+        let payload = buffer(data.as_slice(), Some(100), "test").unwrap();
+        assert!(matches!(payload, Payload::Disk(_)));
+
+        let Payload::Disk(ref spill) = payload else { unreachable!() };
+        let path = spill.path.clone();
+        assert!(path.exists());
+        assert_eq!(read_all(payload), data);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_spilled_batches_read_back_in_order() {
+        use crate::parser::PlayerStat;
+
+        let stats: Vec<PlayerStat> = csv::Reader::from_reader(
+            &b"player_id,player_display_name,recent_team,season,week,season_type,passing_yards,interceptions\n\
+               00-0033873,Patrick Mahomes,KC,2024,1,REG,291.0,1\n\
+               00-0034796,Lamar Jackson,BAL,2024,1,REG,NA,\n"[..],
+        )
+        .deserialize()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+        let mut spilled = SpilledBatches::new("test");
+        let source_row = Row::from([("player_id".to_string(), "00-0033873".to_string())]);
+        spilled.push(vec![stats[0].clone()], vec![source_row.clone()], 60).unwrap();
+        spilled.push(vec![stats[1].clone()], Vec::new(), 55).unwrap();
+        let path = spilled.file.as_ref().unwrap().0.path.clone();
+
+        let batches: Vec<Batch<PlayerStat>> = spilled.drain().unwrap().collect::<Result<_>>().unwrap();
+        assert!(!path.exists());
+        assert_eq!(batches.len(), 2);
+
+        let (first, first_rows, first_bytes) = &batches[0];
+        assert_eq!(first[0].player_id, "00-0033873");
+        assert_eq!(first[0].passing_yards, Some(291.0));
+        assert_eq!(first[0].interceptions, Some(1));
+        assert_eq!((first_rows.as_slice(), *first_bytes), (&[source_row][..], 60));
+
+        let (second, second_rows, second_bytes) = &batches[1];
+        assert_eq!(second[0].recent_team.as_deref(), Some("BAL"));
+        assert_eq!((second[0].passing_yards, second[0].interceptions), (None, None));
+        assert_eq!((second_rows.len(), *second_bytes), (0, 55));
+    }
+}
@@ -0,0 +1,64 @@
+use anyhow::{Result, Context};
+use postgres::Client;
+
+use crate::transformer::FANTASY_PLAYOFF_WEEKS;
+use crate::weather;
+
+/// A named range of regular-season weeks aggregated into `player_splits`
+pub struct Split {
+    pub name: &'static str,
+    pub first_week: i32,
+    pub last_week: i32,
+}
+
+/// Splits materialized for every season
+pub const SPLITS: [Split; 2] = [
+    Split {
+        name: "regular_season",
+        first_week: 1,
+        last_week: 18,
+    },
+    Split {
+        name: "fantasy_playoffs",
+        first_week: *FANTASY_PLAYOFF_WEEKS.start(),
+        last_week: *FANTASY_PLAYOFF_WEEKS.end(),
+    },
+];
+
+/// Rebuild all splits for a season from game_stats, then the wind and cold
+/// splits (see `weather`). Returns the number of split rows written.
+pub fn refresh_player_splits(client: &mut Client, season: i32) -> Result<u64> {
+    let mut tx = client.transaction()?;
+    tx.execute("DELETE FROM player_splits WHERE season = $1", &[&season])?;
+
+    let mut written = 0;
+    for split in SPLITS.iter() {
+        written += tx
+            .execute(
+                "INSERT INTO player_splits (
+                     player_id, season, split, first_week, last_week, games,
+                     passing_yards, rushing_yards, receiving_yards,
+                     passing_tds, rushing_tds, receiving_tds,
+                     receptions, targets, interceptions, completions, attempts,
+                     field_goals_made, field_goals_attempted, updated_at
+                 )
+                 SELECT player_id, season, $2, $3, $4, COUNT(*),
+                        COALESCE(SUM(passing_yards), 0), COALESCE(SUM(rushing_yards), 0),
+                        COALESCE(SUM(receiving_yards), 0), COALESCE(SUM(passing_tds), 0),
+                        COALESCE(SUM(rushing_tds), 0), COALESCE(SUM(receiving_tds), 0),
+                        COALESCE(SUM(receptions), 0), COALESCE(SUM(targets), 0),
+                        COALESCE(SUM(interceptions), 0), COALESCE(SUM(completions), 0),
+                        COALESCE(SUM(attempts), 0), COALESCE(SUM(field_goals_made), 0),
+                        COALESCE(SUM(field_goals_attempted), 0), NOW()
+                 FROM game_stats
+                 WHERE season = $1 AND season_type = 'REG' AND week BETWEEN $3 AND $4
+                 GROUP BY player_id, season",
+                &[&season, &split.name, &split.first_week, &split.last_week],
+            )
+            .with_context(|| format!("Failed to refresh {} split", split.name))?;
+    }
+    written += weather::write_weather_splits(&mut tx, season)?;
+
+    tx.commit()?;
+    Ok(written)
+}
@@ -0,0 +1,175 @@
+use anyhow::{anyhow, Context, Result};
+use bytes::BytesMut;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use postgres::types::{FromSql, IsNull, ToSql, Type};
+use postgres::Client;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use uuid::Uuid;
+
+/// `sql` with each `$n` replaced by the value bound to it as a typed SQL literal,
+/// e.g. `'00-0033873'::text` or `NULL::int4`. The statement is prepared (not
+/// run) to learn the parameter types the server infers.
+pub fn render(client: &mut Client, sql: &str, params: &[&(dyn ToSql + Sync)]) -> Result<String> {
+    render_as(&param_types(client, sql)?, sql, params)
+}
+
+fn param_types(client: &mut Client, sql: &str) -> Result<Vec<Type>> {
+    Ok(client.prepare(sql).context("Failed to prepare statement for rendering")?.params().to_vec())
+}
+
+fn render_as(types: &[Type], sql: &str, params: &[&(dyn ToSql + Sync)]) -> Result<String> {
+    let literals = types.iter().zip(params).map(|(ty, param)| literal(ty, *param)).collect::<Result<Vec<_>>>()?;
+    Ok(substitute(sql, &literals))
+}
+
+/// `--emit-sql`: rendered statements written to a script that applies them in
+/// one transaction (`psql -v ON_ERROR_STOP=1 -f`)
+pub struct Emitter {
+    path: String,
+    out: BufWriter<File>,
+    statements: u64,
+    /// Parameter types per statement text, so each is prepared once
+    types: HashMap<String, Vec<Type>>,
+}
+
+impl Emitter {
+    pub fn create(path: &str, mode: &str) -> Result<Self> {
+        let file = File::create(path).with_context(|| format!("Failed to create {}", path))?;
+        let mut out = BufWriter::new(file);
+        writeln!(out, "-- nfl-data-pipeline --mode {}, generated {}", mode, Utc::now().format("%Y-%m-%d %H:%M:%S UTC"))?;
+        writeln!(out, "-- Apply with: psql \"$DATABASE_URL\" -v ON_ERROR_STOP=1 -f {}\n", path)?;
+        writeln!(out, "BEGIN;\n")?;
+        Ok(Emitter { path: path.to_string(), out, statements: 0, types: HashMap::new() })
+    }
+
+    /// Render a statement into the script, returning it as written
+    pub fn emit(&mut self, client: &mut Client, sql: &str, params: &[&(dyn ToSql + Sync)]) -> Result<String> {
+        if !self.types.contains_key(sql) {
+            self.types.insert(sql.to_string(), param_types(client, sql)?);
+        }
+        let rendered = render_as(&self.types[sql], sql, params)?;
+        self.write(&rendered)?;
+        Ok(rendered)
+    }
+
+    fn write(&mut self, statement: &str) -> Result<()> {
+        writeln!(self.out, "{};\n", statement).with_context(|| format!("Failed to write {}", self.path))?;
+        self.statements += 1;
+        Ok(())
+    }
+
+    /// Close the transaction and flush; returns the path and statement count
+    pub fn finish(mut self) -> Result<(String, u64)> {
+        writeln!(self.out, "COMMIT;")?;
+        self.out.flush().with_context(|| format!("Failed to write {}", self.path))?;
+        Ok((self.path, self.statements))
+    }
+}
+
+/// A value as a literal of `ty`: encoded the way the driver would send it, then
+/// decoded back and quoted
+fn literal(ty: &Type, param: &(dyn ToSql + Sync)) -> Result<String> {
+    let mut buf = BytesMut::new();
+    if let IsNull::Yes = param.to_sql_checked(ty, &mut buf).map_err(|e| anyhow!("{}", e))? {
+        return Ok(format!("NULL::{}", ty.name()));
+    }
+    let raw = &buf[..];
+    let decode_error = |e: Box<dyn std::error::Error + Sync + Send>| anyhow!("Failed to render {} value: {}", ty, e);
+    let text = match *ty {
+        Type::BOOL => bool::from_sql(ty, raw).map_err(decode_error)?.to_string(),
+        Type::INT2 => i16::from_sql(ty, raw).map_err(decode_error)?.to_string(),
+        Type::INT4 => i32::from_sql(ty, raw).map_err(decode_error)?.to_string(),
+        Type::INT8 => i64::from_sql(ty, raw).map_err(decode_error)?.to_string(),
+        Type::FLOAT4 => float(f32::from_sql(ty, raw).map_err(decode_error)? as f64),
+        Type::FLOAT8 => float(f64::from_sql(ty, raw).map_err(decode_error)?),
+        Type::TEXT | Type::VARCHAR | Type::BPCHAR | Type::NAME => quote(&String::from_sql(ty, raw).map_err(decode_error)?),
+        Type::UUID => quote(&Uuid::from_sql(ty, raw).map_err(decode_error)?.to_string()),
+        Type::DATE => quote(&NaiveDate::from_sql(ty, raw).map_err(decode_error)?.to_string()),
+        Type::TIME => quote(&NaiveTime::from_sql(ty, raw).map_err(decode_error)?.to_string()),
+        Type::TIMESTAMP => quote(&NaiveDateTime::from_sql(ty, raw).map_err(decode_error)?.to_string()),
+        Type::TIMESTAMPTZ => quote(&DateTime::<Utc>::from_sql(ty, raw).map_err(decode_error)?.to_rfc3339()),
+        _ => return Err(anyhow!("Cannot render a {} parameter as SQL", ty)),
+    };
+    Ok(format!("{}::{}", text, ty.name()))
+}
+
+fn float(value: f64) -> String {
+    match value {
+        v if v.is_nan() => "'NaN'".to_string(),
+        v if v.is_infinite() => format!("'{}Infinity'", if v < 0.0 { "-" } else { "" }),
+        v => v.to_string(),
+    }
+}
+
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// Replace `$1`, `$2`, ... with `literals[0]`, `literals[1]`, ...; `$10` is
+/// read as one placeholder, not `$1` followed by `0`
+fn substitute(sql: &str, literals: &[String]) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut chars = sql.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c != '$' {
+            out.push(c);
+            continue;
+        }
+        let mut end = start + 1;
+        while let Some((i, d)) = chars.peek().copied().filter(|(_, d)| d.is_ascii_digit()) {
+            end = i + d.len_utf8();
+            chars.next();
+        }
+        match sql[start + 1..end].parse::<usize>().ok().and_then(|n| literals.get(n.wrapping_sub(1))) {
+            Some(literal) => out.push_str(literal),
+            None => out.push_str(&sql[start..end]),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_literals() {
+        assert_eq!(literal(&Type::TEXT, &"Ja'Marr Chase").unwrap(), "'Ja''Marr Chase'::text");
+        assert_eq!(literal(&Type::INT4, &Some(291)).unwrap(), "291::int4");
+        assert_eq!(literal(&Type::INT4, &None::<i32>).unwrap(), "NULL::int4");
+        assert_eq!(literal(&Type::FLOAT8, &Some(0.25)).unwrap(), "0.25::float8");
+        assert_eq!(literal(&Type::FLOAT8, &f64::NAN).unwrap(), "'NaN'::float8");
+        let kickoff = NaiveDate::from_ymd_opt(2024, 9, 5).unwrap().and_hms_opt(20, 20, 0).unwrap().and_utc();
+        assert_eq!(literal(&Type::TIMESTAMPTZ, &kickoff).unwrap(), "'2024-09-05T20:20:00+00:00'::timestamptz");
+        assert!(literal(&Type::INT4, &"12").is_err());
+    }
+
+    #[test]
+    fn test_emitted_script_is_one_transaction() {
+        let path = std::env::temp_dir().join(format!("emit-sql-{}.sql", std::process::id()));
+        let mut emitter = Emitter::create(path.to_str().unwrap(), "year").unwrap();
+        let types = [Type::TEXT, Type::INT4];
+        let rendered = render_as(&types, "UPDATE players SET weight_pounds = $2 WHERE nfl_id = $1", &[&"00-0033873", &227]).unwrap();
+        emitter.write(&rendered).unwrap();
+        let (_, statements) = emitter.finish().unwrap();
+
+        let script = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(statements, 1);
+        assert!(script.starts_with("-- nfl-data-pipeline --mode year, generated "));
+        assert!(script.ends_with(
+            "BEGIN;\n\nUPDATE players SET weight_pounds = 227::int4 WHERE nfl_id = '00-0033873'::text;\n\nCOMMIT;\n"
+        ));
+    }
+
+    #[test]
+    fn test_substitute_placeholders() {
+        let literals: Vec<String> = (1..=10).map(|n| format!("v{}", n)).collect();
+        assert_eq!(
+            substitute("VALUES ($1, $10, $2::int, 'a$b', $11)", &literals),
+            "VALUES (v1, v10, v2::int, 'a$b', $11)"
+        );
+    }
+}
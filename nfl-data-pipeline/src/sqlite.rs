@@ -0,0 +1,133 @@
+//! `--sink sqlite`: the core tables in a local SQLite file, through rusqlite
+//! with a bundled SQLite (built with `--features sqlite`).
+
+use anyhow::Result;
+use rusqlite::types::{ToSqlOutput, ValueRef};
+use rusqlite::{params_from_iter, Connection, ToSql};
+use std::path::Path;
+
+use crate::parser::{Game, PlayerStat, RosterPlayer};
+use crate::sink::{self, ColumnType, StatsSink, Table, Value};
+
+impl ToSql for Value {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Borrowed(match self {
+            Value::Null => ValueRef::Null,
+            Value::Integer(value) => ValueRef::Integer(*value),
+            Value::Real(value) => ValueRef::Real(*value),
+            Value::Text(value) => ValueRef::Text(value.as_bytes()),
+        }))
+    }
+}
+
+fn create_sql(table: &Table) -> String {
+    let columns: Vec<String> = table
+        .columns
+        .iter()
+        .map(|(name, ty)| {
+            let ty = match ty {
+                ColumnType::Integer => "INTEGER",
+                ColumnType::Real => "REAL",
+                ColumnType::Text => "TEXT",
+            };
+            format!("{} {}", name, ty)
+        })
+        .collect();
+    format!("CREATE TABLE IF NOT EXISTS {} ({}, PRIMARY KEY ({}))", table.name, columns.join(", "), table.key.join(", "))
+}
+
+/// Rows replace the stored row with the same key, so re-importing a season
+/// updates it in place
+fn insert_sql(table: &Table) -> String {
+    let names: Vec<&str> = table.columns.iter().map(|(name, _)| *name).collect();
+    let params: Vec<String> = (1..=names.len()).map(|n| format!("?{}", n)).collect();
+    format!("INSERT OR REPLACE INTO {} ({}) VALUES ({})", table.name, names.join(", "), params.join(", "))
+}
+
+/// Writes the core tables into a SQLite file, one transaction per season file
+pub struct SqliteSink {
+    connection: Connection,
+    players: String,
+    games: String,
+    game_stats: String,
+}
+
+impl SqliteSink {
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let connection = Connection::open(path)?;
+        let tables = [sink::players_table(), sink::games_table(), sink::game_stats_table()];
+        for table in &tables {
+            connection.execute_batch(&create_sql(table))?;
+        }
+        connection.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_games_season_week ON games (season, week);
+             CREATE INDEX IF NOT EXISTS idx_game_stats_season_week ON game_stats (season, week);
+             BEGIN",
+        )?;
+        let [players, games, game_stats] = tables.map(|table| insert_sql(&table));
+        Ok(SqliteSink { connection, players, games, game_stats })
+    }
+
+    fn insert<T>(&self, sql: &str, rows: &[T], row: impl Fn(&T) -> Vec<Value>) -> Result<()> {
+        let mut statement = self.connection.prepare_cached(sql)?;
+        for item in rows {
+            statement.execute(params_from_iter(row(item)))?;
+        }
+        Ok(())
+    }
+}
+
+impl StatsSink for SqliteSink {
+    fn write_players(&mut self, players: &[RosterPlayer]) -> Result<()> {
+        self.insert(&self.players, players, sink::player_row)
+    }
+
+    fn write_games(&mut self, games: &[Game]) -> Result<()> {
+        self.insert(&self.games, games, sink::game_row)
+    }
+
+    fn write_stats(&mut self, stats: &[PlayerStat]) -> Result<()> {
+        self.insert(&self.game_stats, stats, sink::stat_row)
+    }
+
+    fn finish_table(&mut self, _table: &'static str) -> Result<()> {
+        Ok(self.connection.execute_batch("COMMIT; BEGIN")?)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        Ok(self.connection.execute_batch("COMMIT")?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source::MemorySource;
+    use crate::datasets;
+
+    #[test]
+    fn test_sqlite_sink_upserts() {
+        let path = std::env::temp_dir().join(format!("sink-{}.sqlite", std::process::id()));
+        let source = MemorySource::default().with_file(
+            &datasets::SCHEDULES,
+            2024,
+            "game_id,season,game_type,week,gameday,away_team,home_team,away_score,home_score\n\
+             2024_01_BAL_KC,2024,REG,1,2024-09-05,BAL,KC,,\n\
+             2024_01_BAL_KC,2024,REG,1,2024-09-05,BAL,KC,20,27\n\
+             2024_01_GB_PHI,2024,REG,1,2024-09-06,GB,PHI,29,34\n",
+        );
+        let config = crate::config::Config::from_args(&[], |name| (name == "DATABASE_URL").then(|| "postgres://db".to_string())).unwrap();
+        let mut sink = SqliteSink::open(&path).unwrap();
+        sink::import_season(&config, &source, &mut sink, 2024).unwrap();
+        sink.finish().unwrap();
+
+        let count = |sql: &str| sink.connection.query_row(sql, [], |row| row.get::<_, i64>(0)).unwrap();
+        assert_eq!(count("SELECT COUNT(*) FROM games"), 2);
+        assert_eq!(count("SELECT home_score FROM games WHERE nfl_game_id = '2024_01_BAL_KC'"), 27);
+        drop(sink);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,518 @@
+use anyhow::{Result, Context};
+use postgres::Client;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use crate::ratings::GameResult;
+
+/// Exponent fitted to NFL scoring (Football Outsiders); baseball's 2 underrates blowouts
+pub const PYTHAGOREAN_EXPONENT: f64 = 2.37;
+/// Largest final margin that counts as a one-score game
+pub const ONE_SCORE_MARGIN: i32 = 8;
+
+/// Playoff seeds per conference: 5 through 1989, 6 from 1990 and 7 since the
+/// 2020 expansion
+pub fn playoff_seeds(season: i32) -> usize {
+    match season {
+        ..=1989 => 5,
+        1990..=2019 => 6,
+        _ => 7,
+    }
+}
+
+/// A team's regular-season record, points and one-score results
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SeasonRecord {
+    pub wins: i32,
+    pub losses: i32,
+    pub ties: i32,
+    pub points_for: i32,
+    pub points_against: i32,
+    pub one_score_wins: i32,
+    pub one_score_losses: i32,
+    pub one_score_ties: i32,
+}
+
+impl SeasonRecord {
+    fn add(&mut self, scored: i32, allowed: i32) {
+        self.points_for += scored;
+        self.points_against += allowed;
+        let one_score = (scored - allowed).abs() <= ONE_SCORE_MARGIN;
+        match scored.cmp(&allowed) {
+            std::cmp::Ordering::Greater => {
+                self.wins += 1;
+                self.one_score_wins += one_score as i32;
+            }
+            std::cmp::Ordering::Less => {
+                self.losses += 1;
+                self.one_score_losses += one_score as i32;
+            }
+            std::cmp::Ordering::Equal => {
+                self.ties += 1;
+                self.one_score_ties += 1;
+            }
+        }
+    }
+
+    pub fn games(&self) -> i32 {
+        self.wins + self.losses + self.ties
+    }
+
+    /// Ties count as half a win
+    pub fn win_pct(&self) -> f64 {
+        match self.games() {
+            0 => 0.0,
+            games => (self.wins as f64 + 0.5 * self.ties as f64) / games as f64,
+        }
+    }
+
+    /// `PF^x / (PF^x + PA^x)`; 0.5 before any points are scored
+    pub fn pythagorean_win_pct(&self) -> f64 {
+        let scored = (self.points_for as f64).powf(PYTHAGOREAN_EXPONENT);
+        let allowed = (self.points_against as f64).powf(PYTHAGOREAN_EXPONENT);
+        if scored + allowed == 0.0 {
+            0.5
+        } else {
+            scored / (scored + allowed)
+        }
+    }
+
+    pub fn expected_wins(&self) -> f64 {
+        self.pythagorean_win_pct() * self.games() as f64
+    }
+
+    /// Wins above (positive) or below the point differential's expectation
+    pub fn win_luck(&self) -> f64 {
+        (self.win_pct() - self.pythagorean_win_pct()) * self.games() as f64
+    }
+}
+
+/// Season records for every team that played
+pub fn season_records(games: &[GameResult]) -> BTreeMap<Uuid, SeasonRecord> {
+    let mut records: BTreeMap<Uuid, SeasonRecord> = BTreeMap::new();
+    for game in games {
+        records.entry(game.home_team).or_default().add(game.home_score, game.away_score);
+        records.entry(game.away_team).or_default().add(game.away_score, game.home_score);
+    }
+    records
+}
+
+/// Write season-total standings rows (week NULL) with Pythagorean expectation and
+/// one-score records from completed regular-season games. Returns the number of
+/// teams written.
+pub fn refresh_season_luck(client: &mut Client, season: i32) -> Result<u64> {
+    let games: Vec<GameResult> = client
+        .query(
+            "SELECT home_team_id, away_team_id, home_score, away_score
+             FROM games
+             WHERE season = $1 AND season_type = 'REG'
+               AND home_score IS NOT NULL AND away_score IS NOT NULL
+               AND home_team_id IS NOT NULL AND away_team_id IS NOT NULL",
+            &[&season],
+        )
+        .context("Failed to load completed games")?
+        .iter()
+        .map(|row| GameResult {
+            home_team: row.get(0),
+            away_team: row.get(1),
+            home_score: row.get(2),
+            away_score: row.get(3),
+        })
+        .collect();
+
+    let mut tx = client.transaction()?;
+    let mut written = 0;
+    for (team_id, record) in season_records(&games) {
+        written += tx.execute(
+            "INSERT INTO team_standings (team_id, season, week, wins, losses, ties, win_pct, points_for, points_against,
+                 point_differential, pythagorean_win_pct, expected_wins, win_luck, one_score_wins, one_score_losses,
+                 one_score_ties, updated_at)
+             VALUES ($1, $2, NULL, $3, $4, $5, $6::float8, $7::int, $8::int, $7::int - $8::int, $9::float8, $10::float8, $11::float8, $12, $13, $14, NOW())
+             ON CONFLICT (team_id, season) WHERE week IS NULL DO UPDATE SET
+                 wins = EXCLUDED.wins,
+                 losses = EXCLUDED.losses,
+                 ties = EXCLUDED.ties,
+                 win_pct = EXCLUDED.win_pct,
+                 points_for = EXCLUDED.points_for,
+                 points_against = EXCLUDED.points_against,
+                 point_differential = EXCLUDED.point_differential,
+                 pythagorean_win_pct = EXCLUDED.pythagorean_win_pct,
+                 expected_wins = EXCLUDED.expected_wins,
+                 win_luck = EXCLUDED.win_luck,
+                 one_score_wins = EXCLUDED.one_score_wins,
+                 one_score_losses = EXCLUDED.one_score_losses,
+                 one_score_ties = EXCLUDED.one_score_ties,
+                 updated_at = NOW()",
+            &[
+                &team_id,
+                &season,
+                &record.wins,
+                &record.losses,
+                &record.ties,
+                &record.win_pct(),
+                &record.points_for,
+                &record.points_against,
+                &record.pythagorean_win_pct(),
+                &record.expected_wins(),
+                &record.win_luck(),
+                &record.one_score_wins,
+                &record.one_score_losses,
+                &record.one_score_ties,
+            ],
+        )?;
+    }
+    tx.commit()?;
+
+    Ok(written)
+}
+
+/// Wins, losses and ties in one slice of the schedule
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct SplitRecord {
+    pub wins: i32,
+    pub losses: i32,
+    pub ties: i32,
+}
+
+impl SplitRecord {
+    fn add(&mut self, result: char) {
+        match result {
+            'W' => self.wins += 1,
+            'L' => self.losses += 1,
+            _ => self.ties += 1,
+        }
+    }
+}
+
+/// A team's conference and division, for split records and ranks
+#[derive(Debug, Clone)]
+pub struct TeamGroup {
+    pub conference: String,
+    pub division: String,
+}
+
+/// A completed regular-season game and its week
+#[derive(Debug, Clone)]
+pub struct WeekResult {
+    pub week: i32,
+    pub game: GameResult,
+}
+
+/// A team's standing after a week
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TeamStanding {
+    pub record: SeasonRecord,
+    pub home: SplitRecord,
+    pub away: SplitRecord,
+    pub division: SplitRecord,
+    pub conference: SplitRecord,
+    /// `W`, `L` or `T` per game, oldest first
+    #[serde(skip)]
+    results: Vec<char>,
+    pub division_rank: Option<i32>,
+    pub conference_rank: Option<i32>,
+    /// Top `playoff_seeds(season)` in the conference, division winners first
+    pub playoff_seed: Option<i32>,
+}
+
+impl TeamStanding {
+    fn add(&mut self, scored: i32, allowed: i32, home: bool, division_game: bool, conference_game: bool) {
+        self.record.add(scored, allowed);
+        let result = match scored.cmp(&allowed) {
+            std::cmp::Ordering::Greater => 'W',
+            std::cmp::Ordering::Less => 'L',
+            std::cmp::Ordering::Equal => 'T',
+        };
+        if home {
+            self.home.add(result);
+        } else {
+            self.away.add(result);
+        }
+        if division_game {
+            self.division.add(result);
+        }
+        if conference_game {
+            self.conference.add(result);
+        }
+        self.results.push(result);
+    }
+
+    pub fn point_differential(&self) -> i32 {
+        self.record.points_for - self.record.points_against
+    }
+
+    /// Current streak such as `W3` or `L1`; empty before the first game
+    pub fn streak(&self) -> String {
+        let Some(&last) = self.results.last() else { return String::new() };
+        let length = self.results.iter().rev().take_while(|&&result| result == last).count();
+        format!("{}{}", last, length)
+    }
+}
+
+/// Cumulative standings after each week with a completed game, keyed by week.
+///
+/// Division ranks order by win percentage, division wins, then point
+/// differential. Conference ranks put division winners first, then order by win
+/// percentage, conference wins and point differential; the top
+/// `playoff_seeds(season)` are seeded. Teams without a conference and
+/// division in `groups` get records but no ranks.
+pub fn weekly_standings(
+    season: i32,
+    games: &[WeekResult],
+    groups: &BTreeMap<Uuid, TeamGroup>,
+) -> BTreeMap<i32, BTreeMap<Uuid, TeamStanding>> {
+    let mut by_week: BTreeMap<i32, Vec<&GameResult>> = BTreeMap::new();
+    for result in games {
+        by_week.entry(result.week).or_default().push(&result.game);
+    }
+
+    let mut running: BTreeMap<Uuid, TeamStanding> = BTreeMap::new();
+    let mut weeks = BTreeMap::new();
+    for (week, games) in by_week {
+        for game in games {
+            let (home, away) = (groups.get(&game.home_team), groups.get(&game.away_team));
+            let conference_game = matches!((home, away), (Some(h), Some(a)) if h.conference == a.conference);
+            let division_game = conference_game && matches!((home, away), (Some(h), Some(a)) if h.division == a.division);
+
+            running
+                .entry(game.home_team)
+                .or_default()
+                .add(game.home_score, game.away_score, true, division_game, conference_game);
+            running
+                .entry(game.away_team)
+                .or_default()
+                .add(game.away_score, game.home_score, false, division_game, conference_game);
+        }
+
+        let mut standings = running.clone();
+        rank(&mut standings, groups, playoff_seeds(season));
+        weeks.insert(week, standings);
+    }
+    weeks
+}
+
+fn rank(standings: &mut BTreeMap<Uuid, TeamStanding>, groups: &BTreeMap<Uuid, TeamGroup>, seeds: usize) {
+    let by_record = |a: &TeamStanding, b: &TeamStanding| b.record.win_pct().total_cmp(&a.record.win_pct());
+
+    let mut divisions: BTreeMap<(&str, &str), Vec<Uuid>> = BTreeMap::new();
+    let mut conferences: BTreeMap<&str, Vec<Uuid>> = BTreeMap::new();
+    for team_id in standings.keys() {
+        if let Some(group) = groups.get(team_id) {
+            divisions.entry((&group.conference, &group.division)).or_default().push(*team_id);
+            conferences.entry(&group.conference).or_default().push(*team_id);
+        }
+    }
+
+    for mut teams in divisions.into_values() {
+        teams.sort_by(|a, b| {
+            let (a, b) = (&standings[a], &standings[b]);
+            by_record(a, b)
+                .then(b.division.wins.cmp(&a.division.wins))
+                .then(b.point_differential().cmp(&a.point_differential()))
+        });
+        for (rank, team_id) in teams.iter().enumerate() {
+            standings.get_mut(team_id).unwrap().division_rank = Some(rank as i32 + 1);
+        }
+    }
+
+    for mut teams in conferences.into_values() {
+        teams.sort_by(|a, b| {
+            let (a, b) = (&standings[a], &standings[b]);
+            (b.division_rank == Some(1))
+                .cmp(&(a.division_rank == Some(1)))
+                .then(by_record(a, b))
+                .then(b.conference.wins.cmp(&a.conference.wins))
+                .then(b.point_differential().cmp(&a.point_differential()))
+        });
+        for (rank, team_id) in teams.iter().enumerate() {
+            let standing = standings.get_mut(team_id).unwrap();
+            standing.conference_rank = Some(rank as i32 + 1);
+            standing.playoff_seed = (rank < seeds).then_some(rank as i32 + 1);
+        }
+    }
+}
+
+/// Rebuild a season's weekly `team_standings` rows (week set) from completed
+/// regular-season games. The season-total row (week NULL) is left to
+/// `refresh_season_luck`. Returns the number of rows written.
+pub fn refresh_standings(client: &mut Client, season: i32) -> Result<u64> {
+    let groups: BTreeMap<Uuid, TeamGroup> = client
+        .query("SELECT id, conference, division FROM teams", &[])
+        .context("Failed to load teams")?
+        .iter()
+        .map(|row| (row.get(0), TeamGroup { conference: row.get(1), division: row.get(2) }))
+        .collect();
+
+    let games: Vec<WeekResult> = client
+        .query(
+            "SELECT week, home_team_id, away_team_id, home_score, away_score
+             FROM games
+             WHERE season = $1 AND season_type = 'REG'
+               AND home_score IS NOT NULL AND away_score IS NOT NULL
+               AND home_team_id IS NOT NULL AND away_team_id IS NOT NULL",
+            &[&season],
+        )
+        .context("Failed to load completed games")?
+        .iter()
+        .map(|row| WeekResult {
+            week: row.get(0),
+            game: GameResult {
+                home_team: row.get(1),
+                away_team: row.get(2),
+                home_score: row.get(3),
+                away_score: row.get(4),
+            },
+        })
+        .collect();
+
+    let mut tx = client.transaction()?;
+    tx.execute("DELETE FROM team_standings WHERE season = $1 AND week IS NOT NULL", &[&season])?;
+    let mut written = 0;
+    for (week, standings) in weekly_standings(season, &games, &groups) {
+        for (team_id, standing) in standings {
+            let record = &standing.record;
+            written += tx.execute(
+                "INSERT INTO team_standings (team_id, season, week, wins, losses, ties, win_pct, points_for,
+                     points_against, point_differential, home_wins, home_losses, away_wins, away_losses,
+                     division_wins, division_losses, division_ties, conference_wins, conference_losses,
+                     conference_ties, current_streak, division_rank, conference_rank, playoff_seed, updated_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7::float8, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18,
+                     $19, $20, $21, $22, $23, $24, NOW())",
+                &[
+                    &team_id,
+                    &season,
+                    &week,
+                    &record.wins,
+                    &record.losses,
+                    &record.ties,
+                    &record.win_pct(),
+                    &record.points_for,
+                    &record.points_against,
+                    &standing.point_differential(),
+                    &standing.home.wins,
+                    &standing.home.losses,
+                    &standing.away.wins,
+                    &standing.away.losses,
+                    &standing.division.wins,
+                    &standing.division.losses,
+                    &standing.division.ties,
+                    &standing.conference.wins,
+                    &standing.conference.losses,
+                    &standing.conference.ties,
+                    &standing.streak(),
+                    &standing.division_rank,
+                    &standing.conference_rank,
+                    &standing.playoff_seed,
+                ],
+            )?;
+        }
+    }
+    tx.commit()?;
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(home: Uuid, away: Uuid, home_score: i32, away_score: i32) -> GameResult {
+        GameResult { home_team: home, away_team: away, home_score, away_score }
+    }
+
+    #[test]
+    fn test_one_score_records() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let records = season_records(&[game(a, b, 24, 17), game(c, a, 31, 10), game(b, c, 20, 20)]);
+
+        let ra = &records[&a];
+        assert_eq!((ra.wins, ra.losses, ra.ties), (1, 1, 0));
+        assert_eq!((ra.one_score_wins, ra.one_score_losses), (1, 0));
+        assert_eq!((ra.points_for, ra.points_against), (34, 48));
+        assert_eq!(records[&b].one_score_ties, 1);
+        assert_eq!(records[&c].one_score_wins, 0);
+    }
+
+    #[test]
+    fn test_pythagorean_expectation_and_luck() {
+        let even = SeasonRecord { wins: 8, losses: 9, points_for: 380, points_against: 380, ..Default::default() };
+        assert!((even.pythagorean_win_pct() - 0.5).abs() < 1e-9);
+        assert!((even.expected_wins() - 8.5).abs() < 1e-9);
+        assert!((even.win_luck() + 0.5).abs() < 1e-9);
+
+        // 2022 Vikings: 13-4 with a -3 point differential
+        let vikings = SeasonRecord { wins: 13, losses: 4, points_for: 424, points_against: 427, ..Default::default() };
+        assert!(vikings.expected_wins() < 8.5);
+        assert!(vikings.win_luck() > 4.5);
+    }
+
+    #[test]
+    fn test_no_points_is_even() {
+        assert_eq!(SeasonRecord::default().pythagorean_win_pct(), 0.5);
+        assert_eq!(SeasonRecord::default().win_luck(), 0.0);
+    }
+
+    #[test]
+    fn test_weekly_splits_streaks_and_ranks() {
+        let (kc, lv, buf) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let group = |division: &str| TeamGroup { conference: "AFC".to_string(), division: division.to_string() };
+        let groups = BTreeMap::from([(kc, group("West")), (lv, group("West")), (buf, group("East"))]);
+        let week = |week, game| WeekResult { week, game };
+        let games = [
+            week(1, game(kc, lv, 27, 20)),
+            week(2, game(buf, kc, 30, 17)),
+            week(3, game(lv, buf, 24, 21)),
+            week(3, game(kc, lv, 10, 13)),
+        ];
+
+        let weeks = weekly_standings(2024, &games, &groups);
+        assert_eq!(weeks.keys().copied().collect::<Vec<_>>(), [1, 2, 3]);
+        assert!(!weeks[&1].contains_key(&buf));
+
+        let kc_week3 = &weeks[&3][&kc];
+        assert_eq!((kc_week3.record.wins, kc_week3.record.losses), (1, 2));
+        assert_eq!(kc_week3.home, SplitRecord { wins: 1, losses: 1, ties: 0 });
+        assert_eq!(kc_week3.division, SplitRecord { wins: 1, losses: 1, ties: 0 });
+        assert_eq!(kc_week3.conference.losses, 2);
+        assert_eq!(kc_week3.streak(), "L2");
+
+        // LV (2-1) wins the West; BUF (1-1) is a division winner too and seeds second
+        let lv_week3 = &weeks[&3][&lv];
+        assert_eq!(lv_week3.streak(), "W2");
+        assert_eq!((lv_week3.division_rank, lv_week3.conference_rank), (Some(1), Some(1)));
+        assert_eq!(weeks[&3][&buf].playoff_seed, Some(2));
+        assert_eq!((kc_week3.division_rank, kc_week3.playoff_seed), (Some(2), Some(3)));
+    }
+
+    #[test]
+    fn test_seeds_per_conference_by_era() {
+        assert_eq!(playoff_seeds(1989), 5);
+        assert_eq!(playoff_seeds(1990), 6);
+        assert_eq!(playoff_seeds(2019), 6);
+        assert_eq!(playoff_seeds(2020), 7);
+    }
+
+    #[test]
+    fn test_six_seeds_before_2020() {
+        // Eight two-team divisions in one conference, each decided in week 1
+        let group = |division: String| TeamGroup { conference: "AFC".to_string(), division };
+        let mut groups = BTreeMap::new();
+        let mut games = Vec::new();
+        for i in 0..8 {
+            let (winner, loser) = (Uuid::new_v4(), Uuid::new_v4());
+            groups.insert(winner, group(format!("D{}", i)));
+            groups.insert(loser, group(format!("D{}", i)));
+            games.push(WeekResult { week: 1, game: game(winner, loser, 20 + i, 10) });
+        }
+
+        let seeded = |season| {
+            weekly_standings(season, &games, &groups)[&1]
+                .values()
+                .filter(|standing| standing.playoff_seed.is_some())
+                .count()
+        };
+        assert_eq!(seeded(2019), 6);
+        assert_eq!(seeded(2020), 7);
+    }
+}
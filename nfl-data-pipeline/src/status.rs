@@ -0,0 +1,216 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+use postgres::Client;
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::datasets;
+
+/// Season imports tracked in `import_progress`, in the order `import_year` runs
+/// them, with the first season each is published for
+const SEASON_DATASETS: &[(&str, Option<i32>)] = &[
+    (datasets::ROSTERS.name, datasets::ROSTERS.min_year),
+    (datasets::WEEKLY_ROSTERS.name, datasets::WEEKLY_ROSTERS.min_year),
+    (datasets::PLAYER_STATS.name, datasets::PLAYER_STATS.min_year),
+    (datasets::INJURIES.name, datasets::INJURIES.min_year),
+    (datasets::DEPTH_CHARTS.name, datasets::DEPTH_CHARTS.min_year),
+    (datasets::SNAP_COUNTS.name, datasets::SNAP_COUNTS.min_year),
+    (datasets::PLAY_BY_PLAY.name, datasets::PLAY_BY_PLAY.min_year),
+    (datasets::NGS_PASSING.name, datasets::NGS_PASSING.min_year),
+    (datasets::NGS_RUSHING.name, datasets::NGS_RUSHING.min_year),
+    (datasets::NGS_RECEIVING.name, datasets::NGS_RECEIVING.min_year),
+];
+
+/// Where one season of one dataset stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum State {
+    Completed,
+    /// Completed with rolled-back batches
+    Partial,
+    Failed,
+    InProgress,
+    /// Published but never imported
+    Missing,
+    /// Not published for the season
+    Unavailable,
+}
+
+impl State {
+    fn parse(status: &str) -> Self {
+        match status {
+            "completed" => State::Completed,
+            "partial" => State::Partial,
+            "failed" => State::Failed,
+            _ => State::InProgress,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            State::Completed => "ok",
+            State::Partial => "partial",
+            State::Failed => "FAILED",
+            State::InProgress => "running",
+            State::Missing => "missing",
+            State::Unavailable => "-",
+        }
+    }
+
+    /// Whether the season still needs an import
+    fn needs_backfill(&self) -> bool {
+        matches!(self, State::Partial | State::Failed | State::Missing)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Cell {
+    pub season: i32,
+    pub data_type: &'static str,
+    pub state: State,
+    pub records: i32,
+    pub updated_at: Option<NaiveDateTime>,
+    pub error: Option<String>,
+}
+
+/// One row of `import_progress` (season level, week 0)
+struct Progress {
+    status: String,
+    records: i32,
+    updated_at: Option<NaiveDateTime>,
+    error: Option<String>,
+}
+
+/// The season × dataset grid for `start..=end`, newest season first. College
+/// stats get a column when `with_college` is set.
+pub fn grid(client: &mut Client, start: i32, end: i32, with_college: bool) -> Result<Vec<Vec<Cell>>> {
+    let mut progress: HashMap<(i32, String), Progress> = client
+        .query(
+            "SELECT season, data_type, status, COALESCE(records_imported, 0),
+                    COALESCE(completed_at, started_at), error_message
+             FROM import_progress
+             WHERE week = 0 AND season BETWEEN $1 AND $2",
+            &[&start, &end],
+        )
+        .context("Failed to load import progress")?
+        .into_iter()
+        .map(|row| {
+            let progress = Progress { status: row.get(2), records: row.get(3), updated_at: row.get(4), error: row.get(5) };
+            ((row.get(0), row.get(1)), progress)
+        })
+        .collect();
+
+    let mut columns = SEASON_DATASETS.to_vec();
+    if with_college {
+        columns.push((datasets::COLLEGE_STATS.name, datasets::COLLEGE_STATS.min_year));
+    }
+    Ok((start..=end)
+        .rev()
+        .map(|season| {
+            columns
+                .iter()
+                .map(|&(data_type, min_year)| match progress.remove(&(season, data_type.to_string())) {
+                    Some(row) => Cell {
+                        season,
+                        data_type,
+                        state: State::parse(&row.status),
+                        records: row.records,
+                        updated_at: row.updated_at,
+                        error: row.error,
+                    },
+                    None => Cell {
+                        season,
+                        data_type,
+                        state: if min_year.is_some_and(|min| season < min) { State::Unavailable } else { State::Missing },
+                        records: 0,
+                        updated_at: None,
+                        error: None,
+                    },
+                })
+                .collect()
+        })
+        .collect())
+}
+
+/// The grid as an aligned text table: status and record count per cell and each
+/// season's last update, then the seasons that need a backfill and why
+pub fn render(grid: &[Vec<Cell>]) -> String {
+    let Some(first) = grid.first() else {
+        return "No seasons in range\n".to_string();
+    };
+    let text = |cell: &Cell| match cell.state {
+        State::Completed | State::Partial | State::InProgress => format!("{} {}", cell.state.label(), cell.records),
+        _ => cell.state.label().to_string(),
+    };
+    let widths: Vec<usize> = first
+        .iter()
+        .enumerate()
+        .map(|(column, cell)| grid.iter().map(|row| text(&row[column]).len()).max().unwrap_or(0).max(cell.data_type.len()))
+        .collect();
+
+    let mut out = String::from("season");
+    for (cell, width) in first.iter().zip(&widths) {
+        out.push_str(&format!("  {:<width$}", cell.data_type, width = width));
+    }
+    out.push_str("  last updated\n");
+    for row in grid {
+        out.push_str(&format!("{:<6}", row[0].season));
+        for (cell, width) in row.iter().zip(&widths) {
+            out.push_str(&format!("  {:<width$}", text(cell), width = width));
+        }
+        let updated = row.iter().filter_map(|cell| cell.updated_at).max();
+        out.push_str(&format!("  {}\n", updated.map_or("never".to_string(), |at| at.format("%Y-%m-%d %H:%M").to_string())));
+    }
+
+    let backfill: Vec<&Vec<Cell>> = grid.iter().filter(|row| row.iter().any(|cell| cell.state.needs_backfill())).collect();
+    match backfill.len() {
+        0 => out.push_str("\nEvery season is complete\n"),
+        count => {
+            out.push_str(&format!("\n{} seasons need a backfill:\n", count));
+            for row in backfill {
+                let datasets: Vec<&str> =
+                    row.iter().filter(|cell| cell.state.needs_backfill()).map(|cell| cell.data_type).collect();
+                out.push_str(&format!("  {}: {}\n", row[0].season, datasets.join(", ")));
+                for cell in row.iter().filter(|cell| cell.state.needs_backfill()) {
+                    if let Some(error) = &cell.error {
+                        out.push_str(&format!("    {}: {}\n", cell.data_type, error));
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(season: i32, data_type: &'static str, state: State, records: i32) -> Cell {
+        Cell { season, data_type, state, records, updated_at: None, error: None }
+    }
+
+    #[test]
+    fn test_render_grid_and_backfill() {
+        let updated = NaiveDateTime::parse_from_str("2026-10-15 16:52:09", "%Y-%m-%d %H:%M:%S").unwrap();
+        let grid = vec![
+            vec![
+                Cell { updated_at: Some(updated), ..cell(2024, "rosters", State::Completed, 3006) },
+                Cell { error: Some("HTTP 404".to_string()), ..cell(2024, "snap_counts", State::Failed, 0) },
+            ],
+            vec![cell(2011, "rosters", State::Missing, 0), cell(2011, "snap_counts", State::Unavailable, 0)],
+        ];
+
+        assert_eq!(
+            render(&grid),
+            "season  rosters  snap_counts  last updated\n\
+             2024    ok 3006  FAILED       2026-10-15 16:52\n\
+             2011    missing  -            never\n\
+             \n\
+             2 seasons need a backfill:\n  \
+               2024: snap_counts\n    \
+                 snap_counts: HTTP 404\n  \
+               2011: rosters\n"
+        );
+    }
+}
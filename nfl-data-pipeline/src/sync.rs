@@ -1,266 +1,3487 @@
-use anyhow::Result;
-use log::{info, warn, error};
+use anyhow::{anyhow, Context, Result};
+use log::{debug, info, warn, error};
 use indicatif::{ProgressBar, ProgressStyle};
-use csv::ReaderBuilder;
-use chrono::Datelike;
-
-use crate::config::Config;
-use crate::database::Database;
+use csv::StringRecord;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use chrono::{Datelike, NaiveDateTime, Utc, Weekday};
+use postgres::types::ToSql;
+use postgres::Client;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Instant;
+use uuid::Uuid;
+
+use crate::aggregates;
+use crate::api;
+use crate::api_keys;
+use crate::backpressure::{self, Action};
+use crate::betting;
+use crate::bulk;
+use crate::calendar;
+use crate::config::{Config, LoadMethod};
+use crate::change_log;
+use crate::completeness;
+use crate::corrections::{self, StatLine};
+use crate::current_week::{self, CurrentWeek};
+use crate::database::{self, Database, WeekStatus};
+use crate::datasets::{self, Dataset, SourceFormat};
+use crate::dead_letter::{self, DeadLetter, DeadLetters, Failure, Row};
+use crate::diff::{self, DatasetDiff};
+use crate::drift;
+use crate::dry_run::DryRun;
+use crate::espn::{self, EspnGame};
+use crate::espn_ids;
+use crate::export;
+use crate::efficiency::{self, EfficiencyAggregator, GarbageTimeFilter};
+use crate::fantasy;
+use crate::game_status::{self, GameStatus};
+use crate::data_source::DataSource;
 use crate::downloader::Downloader;
-use crate::parser::{RosterPlayer, PlayerStat, Game};
+use crate::history;
+use crate::indexes;
+use crate::integrity;
+use crate::live::{self, LiveGame};
+use crate::materialize;
+use crate::metrics;
+use crate::migrations;
+use crate::lineage;
+use crate::news;
+use crate::notify::Notifier;
+use crate::odds;
+use crate::precedence::{self, Precedence, Source};
+use crate::purge;
+use crate::parser::{RosterPlayer, PlayerStat, Game, CollegeStat, CombineResult, Contract, DepthChartEntry, DraftPick, Injury, SnapCount, NGSPassing, NGSRushing, NGSReceiving, TeamRecord};
+use crate::ratings;
+use crate::reports;
+use crate::resolution::{self, Unresolved};
+use crate::roles;
+use crate::roster_moves::{self, RosterSnapshot};
+use crate::row_hashes;
+use crate::sink;
+use crate::sheets::{self, SheetsConfig};
+use crate::sleeper;
+use crate::sources;
+use crate::spill::{self, SpilledBatches};
+use crate::splits;
+use crate::sql_text::{self, Emitter};
+use crate::standings;
+use crate::status;
+use crate::telemetry::{RunReport, Telemetry};
 use crate::transformer;
+use crate::tuning::Tuner;
+use crate::usage;
+use crate::validator::DataValidator;
+use crate::views;
+use crate::value;
+use crate::writers::ParallelWriter;
+
+/// Season key for progress and schema tracking of files that cover every season
+const ALL_SEASONS: i32 = 0;
+/// Gaps logged per season by `validate_data`; `--format json` has them all
+const MAX_LOGGED_GAPS: usize = 10;
+
+/// Datasets `--mode replay` retries, in replay order so teams, players and games
+/// exist before the rows that link to them. Play-by-play and weekly rosters only
+/// feed aggregates, and contracts replace their table, so those need a season
+/// re-import instead.
+const REPLAYABLE: [&str; 13] = [
+    datasets::TEAMS.name,
+    datasets::ROSTERS.name,
+    datasets::SCHEDULES.name,
+    datasets::DRAFT_PICKS.name,
+    datasets::COMBINE.name,
+    datasets::PLAYER_STATS.name,
+    datasets::INJURIES.name,
+    datasets::DEPTH_CHARTS.name,
+    datasets::SNAP_COUNTS.name,
+    datasets::NGS_PASSING.name,
+    datasets::NGS_RUSHING.name,
+    datasets::NGS_RECEIVING.name,
+    datasets::COLLEGE_STATS.name,
+];
+
+/// A season import skipped because its source file matches the last completed import
+#[derive(Debug, thiserror::Error)]
+#[error("source file unchanged since the last completed import")]
+pub struct Unchanged;
+
+/// Records a stage read from its source file, and how many failed to parse
+#[derive(Debug, Default)]
+struct Parsed {
+    rows: usize,
+    failed: usize,
+}
 
 pub struct DataPipeline {
     pub config: Config,
-    downloader: Downloader,
+    /// Season files come from here: nflverse over HTTP (or `--source-dir`) unless embedded with another source
+    source: Box<dyn DataSource>,
     database: Database,
+    /// (name, school) → player ID, loaded for the college stats stage
+    college_links: HashMap<(String, String), Uuid>,
+    /// Schema created for `--simulate`, dropped by `finish_simulation`
+    simulation_schema: Option<String>,
+    /// Batch size and writer tuning per dataset, kept across seasons
+    tuners: HashMap<&'static str, Arc<Tuner>>,
+    /// When `--backpressure` last sampled the server's load
+    load_sampled: Option<Instant>,
+    /// Source file fingerprints read this run, stored once the season import completes
+    fingerprints: HashMap<(i32, &'static str), String>,
+    /// Identifies this run in `source_runs`
+    started_at: NaiveDateTime,
+    /// (dataset, season) already recorded in `source_runs` this run
+    recorded_sources: HashSet<(&'static str, i32)>,
+    /// Run metrics for `--telemetry-url`
+    telemetry: Telemetry,
+    /// Stage alerts and the run summary for `--notify-url`
+    notifier: Option<Notifier>,
+    /// What `--dry-run` would have written, per table
+    dry_run: DryRun,
+    /// Script the captured upserts go to with `--emit-sql`
+    emitter: Option<Emitter>,
+    /// Failed rows collected for `--mode replay`, when `--dead-letter` is set
+    dead_letters: Option<DeadLetters>,
+    /// Dead-lettered rows read in place of each (dataset, season)'s source file during a replay
+    replaying: HashMap<(&'static str, i32), Vec<DeadLetter>>,
 }
 
-impl DataPipeline {
-    pub fn new(config: Config) -> Result<Self> {
-        let downloader = Downloader::new(config.max_retries);
-        let database = Database::connect(&config.database_url)?;
+impl DataPipeline {
+    pub fn new(config: Config) -> Result<Self> {
+        let downloader = Downloader::from_config(&config)?;
+        Self::with_source(config, Box::new(downloader))
+    }
+
+    /// A pipeline reading season files from `source` instead of nflverse
+    pub fn with_source(config: Config, source: Box<dyn DataSource>) -> Result<Self> {
+        let mut database = Database::connect(&config.database_url, config.pool_size())?;
+        match migrations::pending(database.get_client()) {
+            Ok(Some(pending)) if !pending.is_empty() => {
+                warn!("⚠️  {} pending migrations, starting with {} (run --mode migrate)", pending.len(), pending[0].file)
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Migration check failed: {}", e),
+        }
+        let simulation_schema = if config.simulate {
+            Some(database.enter_simulation()?)
+        } else {
+            None
+        };
+        database.refresh_cache()?;
+        if database.team_count() == 0 && config.mode != "seed-teams" {
+            warn!("⚠️  teams table is empty, so team lookups will miss (run --mode seed-teams)");
+        }
+
+        let dead_letters = config.dead_letter.as_deref().map(DeadLetters::new);
+        let emitter = config.emit_sql.as_deref().map(|path| Emitter::create(path, &config.mode)).transpose()?;
+
+        Ok(DataPipeline {
+            telemetry: Telemetry::start(&config),
+            notifier: Notifier::from_config(&config)?,
+            dry_run: DryRun::default(),
+            emitter,
+            config,
+            source,
+            database,
+            college_links: HashMap::new(),
+            simulation_schema,
+            tuners: HashMap::new(),
+            load_sampled: None,
+            fingerprints: HashMap::new(),
+            started_at: Utc::now().naive_utc(),
+            recorded_sources: HashSet::new(),
+            dead_letters,
+            replaying: HashMap::new(),
+        })
+    }
+
+    /// Log a failed stage; an unchanged source file is reported as skipped instead
+    fn stage_failed(&mut self, stage: &str, e: anyhow::Error) {
+        if e.downcast_ref::<Unchanged>().is_some() {
+            info!("  ⏭️  {}: skipped (unchanged)", stage);
+        } else {
+            warn!("  ⚠️  {} failed: {}", stage, e);
+            self.telemetry.stage_failed(stage, &e);
+            if let Some(notifier) = &mut self.notifier {
+                notifier.stage_failed(stage, &e);
+            }
+        }
+    }
+
+    /// Post the run summary to `--notify-url`
+    pub fn notify_finished(&self, result: &Result<()>) {
+        if let Some(notifier) = &self.notifier {
+            notifier.run_finished(&self.telemetry.report(result), result);
+        }
+    }
+
+    /// What `--dry-run` would have written, per table (JSON on stdout with `--format json`)
+    pub fn report_dry_run(&mut self) -> Result<()> {
+        if let Some(emitter) = self.emitter.take() {
+            let (path, statements) = emitter.finish()?;
+            info!("📝 {} statements written to {}", statements, path);
+        }
+        if self.dry_run.is_empty() {
+            return Ok(());
+        }
+        if self.config.format.as_deref() == Some("json") {
+            println!("{}", serde_json::to_string(&self.dry_run)?);
+        }
+        for line in self.dry_run.render().lines() {
+            info!("🧪 {}", line);
+        }
+        Ok(())
+    }
+
+    /// Anonymous summary of this run for `--telemetry-url`
+    pub fn telemetry_report(&self, result: &Result<()>) -> RunReport {
+        self.telemetry.report(result)
+    }
+
+    pub fn is_simulation(&self) -> bool {
+        self.simulation_schema.is_some()
+    }
+
+    /// Report what a simulated run wrote, validate it, then drop the simulation schema
+    pub fn finish_simulation(&mut self) -> Result<()> {
+        let Some(schema) = self.simulation_schema.take() else {
+            return Ok(());
+        };
+
+        let summary = self.database.simulation_row_counts(&schema);
+        let validation = self.validate_data();
+        self.database.drop_simulation(&schema)?;
+
+        info!("🧪 Simulation summary (rows in simulation schema):");
+        for (table, count) in summary? {
+            info!("  {}: {}", table, count);
+        }
+        validation
+    }
+
+    /// Run full import for all years
+    pub fn run_full_import(&mut self) -> Result<()> {
+        let pb = ProgressBar::new((self.config.end_year - self.config.start_year + 1) as u64);
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} {msg}")
+                .unwrap(),
+        );
+
+        for year in self.config.start_year..=self.config.end_year {
+            pb.set_message(format!("Importing {}", year));
+
+            match self.import_year(year) {
+                Ok(_) => {
+                    pb.inc(1);
+                }
+                Err(e) => {
+                    error!("Failed to import year {}: {}", year, e);
+                    pb.inc(1);
+                }
+            }
+        }
+
+        pb.finish_with_message("Import complete!");
+
+        // Draft history spans all seasons, load it once after players exist
+        if let Err(e) = self.import_draft() {
+            warn!("  ⚠️  Draft import failed: {}", e);
+        }
+
+        Ok(())
+    }
+
+    /// Import data for a single year
+    pub fn import_year(&mut self, year: i32) -> Result<()> {
+        info!("📅 Importing data for year {}...", year);
+
+        // 1. Import rosters (players)
+        match self.import_rosters(year) {
+            Ok(count) => info!("  ✅ Rosters: {} players", count),
+            Err(e) => self.stage_failed("Rosters", e),
+        }
+
+        // Week-over-week roster changes (2002+)
+        if datasets::WEEKLY_ROSTERS.is_available(year) {
+            match self.import_roster_moves(year) {
+                Ok(count) => info!("  ✅ Roster Moves: {} moves", count),
+                Err(e) => self.stage_failed("Roster Moves", e),
+            }
+        }
+
+        // 2. Import schedule (games), before stats so weekly progress can see which weeks finished
+        match self.import_schedule(year) {
+            Ok(count) => info!("  ✅ Schedule: {} games", count),
+            Err(e) => self.stage_failed("Schedule", e),
+        }
+
+        // Box scores and rosters from ESPN, merged into the same games
+        if self.config.espn {
+            if let Err(e) = self.import_espn(year) {
+                self.stage_failed("ESPN Schedule", e);
+            }
+        }
+
+        // 3. Import player stats
+        match self.import_player_stats(year) {
+            Ok(count) => info!("  ✅ Player Stats: {} records", count),
+            Err(e) => self.stage_failed("Player Stats", e),
+        }
+
+        // Weekly injury reports (2009+)
+        if datasets::INJURIES.is_available(year) {
+            match self.import_injuries(year) {
+                Ok(count) => info!("  ✅ Injuries: {} reports", count),
+                Err(e) => self.stage_failed("Injuries", e),
+            }
+        }
+
+        // Weekly depth charts (2001+)
+        if datasets::DEPTH_CHARTS.is_available(year) {
+            match self.import_depth_charts(year) {
+                Ok(count) => info!("  ✅ Depth Charts: {} entries", count),
+                Err(e) => self.stage_failed("Depth Charts", e),
+            }
+        }
+
+        // Per-game snap counts (2012+)
+        if datasets::SNAP_COUNTS.is_available(year) {
+            match self.import_snap_counts(year) {
+                Ok(count) => info!("  ✅ Snap Counts: {} records", count),
+                Err(e) => self.stage_failed("Snap Counts", e),
+            }
+        }
+
+        // EPA and success rate from play-by-play, with and without garbage time
+        if datasets::PLAY_BY_PLAY.is_available(year) {
+            match self.import_efficiency(year) {
+                Ok(count) => info!("  ✅ Efficiency: {} aggregates", count),
+                Err(e) => self.stage_failed("Efficiency", e),
+            }
+        }
+
+        // Derived splits (regular season, fantasy playoffs)
+        self.refresh_splits(year);
+
+        // Weekly fantasy points under each scoring format
+        self.refresh_fantasy_points(year);
+
+        // Season and career totals for API readers
+        self.refresh_aggregates(year);
+
+        // Position battles from depth chart and snap share changes
+        self.refresh_role_changes(year);
+
+        // Snap share, touch and target share spikes and drops
+        self.refresh_usage_alerts(year);
+
+        // Production per cap dollar under each player's contract
+        self.refresh_value_metrics(year);
+
+        // Seasonal coach and referee reports
+        self.refresh_reports(year);
+
+        // Opponent-adjusted team strength and standings after each week, then record vs. point differential
+        self.refresh_team_ratings(year);
+        self.refresh_standings(year);
+        self.refresh_season_luck(year);
+
+        // 4. Import Next Gen Stats (2016+)
+        if datasets::NGS_PASSING.is_available(year) {
+            match self.import_ngs_passing(year) {
+                Ok(count) => info!("  ✅ NGS Passing: {} records", count),
+                Err(e) => self.stage_failed("NGS Passing", e),
+            }
+            match self.import_ngs_rushing(year) {
+                Ok(count) => info!("  ✅ NGS Rushing: {} records", count),
+                Err(e) => self.stage_failed("NGS Rushing", e),
+            }
+            match self.import_ngs_receiving(year) {
+                Ok(count) => info!("  ✅ NGS Receiving: {} records", count),
+                Err(e) => self.stage_failed("NGS Receiving", e),
+            }
+        }
+
+        // 5. College production for NFL players (optional)
+        if self.config.include_college {
+            match self.import_college_stats(year) {
+                Ok(count) => info!("  ✅ College Stats: {} records", count),
+                Err(e) => self.stage_failed("College Stats", e),
+            }
+        }
+
+        info!(year = year; "✅ Year {} import complete", year);
+        Ok(())
+    }
+
+    /// Download a dataset, parse rows into its parser struct and write accepted rows in batches.
+    ///
+    /// `accept` sees every parsed row (with access to the pipeline for lookups) and decides
+    /// whether it is written; `write` receives full batches. Returns the number of rows written.
+    fn run_stage<T: DeserializeOwned>(
+        &mut self,
+        dataset: &Dataset<T>,
+        year: i32,
+        accept: impl FnMut(&mut Self, &T) -> Result<bool>,
+        write: fn(&mut Self, &[T]) -> Result<()>,
+    ) -> Result<usize> {
+        self.write_stage(dataset, year, false, accept, write)
+    }
+
+    /// `run_stage` for a season import recorded with `mark_completed`: fails with
+    /// `Unchanged` when the file matches the one behind the last completed import,
+    /// unless `--force` is set.
+    fn import_stage<T: DeserializeOwned>(
+        &mut self,
+        dataset: &Dataset<T>,
+        year: i32,
+        accept: impl FnMut(&mut Self, &T) -> Result<bool>,
+        write: fn(&mut Self, &[T]) -> Result<()>,
+    ) -> Result<usize> {
+        self.write_stage(dataset, year, true, accept, write)
+    }
+
+    fn write_stage<T: DeserializeOwned>(
+        &mut self,
+        dataset: &Dataset<T>,
+        year: i32,
+        skip_unchanged: bool,
+        accept: impl FnMut(&mut Self, &T) -> Result<bool>,
+        write: fn(&mut Self, &[T]) -> Result<()>,
+    ) -> Result<usize> {
+        let tuner = self.tuner(dataset.name);
+        let stage_started = Instant::now();
+        let mut imported = 0;
+        let mut failed_batches = 0;
+        let mut failed_rows = 0;
+        let parsed = self.stream_stage(dataset, year, skip_unchanged, &tuner, accept, |pipeline, batch, source_rows, _| {
+            let started = Instant::now();
+            match pipeline.write_batch(dataset, year, &tuner, write, &batch, source_rows)? {
+                true => {
+                    tuner.record(batch.len(), started.elapsed());
+                    metrics::batch_write(dataset.name, started.elapsed());
+                    imported += batch.len();
+                }
+                false => {
+                    failed_batches += 1;
+                    failed_rows += batch.len();
+                }
+            }
+            Ok(())
+        })?;
+
+        self.finish_stage(dataset.name, year, stage_started, &parsed, imported, failed_batches, failed_rows)?;
+        Ok(imported)
+    }
+
+    /// `import_stage` with batches handed to `--writers` workers on pooled connections
+    /// while parsing continues. Falls back to `import_stage` with `inline` when writes
+    /// can't run in parallel (one writer, dry run or row-by-row inserts).
+    ///
+    /// With a memory budget, batches that would take the workers' queue past the
+    /// batch budget are spilled to disk instead, and streamed into the workers
+    /// once parsing ends.
+    fn import_parallel_stage<T, W>(
+        &mut self,
+        dataset: &Dataset<T>,
+        year: i32,
+        accept: impl FnMut(&mut Self, &T) -> Result<bool>,
+        inline: fn(&mut Self, &[T]) -> Result<()>,
+        write: W,
+    ) -> Result<usize>
+    where
+        T: DeserializeOwned + Serialize + Send + 'static,
+        W: Fn(&mut Client, &[T]) -> Result<()> + Send + Sync + 'static,
+    {
+        if !self.config.parallel_writes() {
+            return self.import_stage(dataset, year, accept, inline);
+        }
+
+        let tuner = self.tuner(dataset.name);
+        let stage_started = Instant::now();
+        self.fit_writers(&tuner);
+        let writer = ParallelWriter::start(
+            self.database.pool(),
+            self.database.search_path(),
+            Arc::clone(&tuner),
+            dataset.name,
+            write,
+        );
+        let queue_budget = spill::batch_budget(self.config.memory_budget_bytes());
+        let mut spilled = SpilledBatches::new(dataset.name);
+        let parsed = self.stream_stage(dataset, year, true, &tuner, accept, |_, batch, source_rows, bytes| {
+            let in_flight = writer.in_flight();
+            match queue_budget {
+                Some(budget) if in_flight > 0 && in_flight + bytes > budget => spilled.push(batch, source_rows, bytes),
+                _ => writer.send(batch, source_rows, bytes),
+            }
+        })?;
+        for batch in spilled.drain()? {
+            let (batch, source_rows, bytes) = batch?;
+            writer.send(batch, source_rows, bytes)?;
+        }
+        let mut outcome = writer.finish()?;
+
+        for failed in &mut outcome.failed {
+            self.database.record_failed_batch(year, dataset.name, failed.rows as i32, &failed.error)?;
+            let source_rows = std::mem::take(&mut failed.source_rows);
+            self.dead_letter(dataset.name, year, Failure::Write, &failed.error, source_rows);
+        }
+        let failed_rows = outcome.failed.iter().map(|failed| failed.rows).sum();
+        self.finish_stage(dataset.name, year, stage_started, &parsed, outcome.written, outcome.failed.len(), failed_rows)?;
+        Ok(outcome.written)
+    }
+
+    /// Report a stage's rows: a warning for rolled-back batches, telemetry, and
+    /// an event with the counts as structured fields for `--log-format json`.
+    /// Fails when the stage is over `--max-error-rate`.
+    #[allow(clippy::too_many_arguments)]
+    fn finish_stage(
+        &mut self,
+        dataset: &'static str,
+        year: i32,
+        started: Instant,
+        parsed: &Parsed,
+        written: usize,
+        failed_batches: usize,
+        failed_rows: usize,
+    ) -> Result<()> {
+        if failed_batches > 0 {
+            warn!("  ⚠️  {} {} batches were rolled back (see import_progress)", failed_batches, dataset);
+        }
+        self.telemetry.dataset(dataset, written, failed_batches);
+        metrics::rows(dataset, written, parsed.failed + failed_rows);
+
+        let failed = parsed.failed + failed_rows;
+        let duration = started.elapsed();
+        info!(
+            year = year, data_type = dataset, rows_read = parsed.rows, rows_ok = written, rows_failed = failed,
+            duration_ms = duration.as_millis() as u64;
+            "  📦 {} ({}): {} rows {}, {} failed in {:.1}s",
+            dataset, year, written, if self.config.dry_run { "would be written" } else { "written" }, failed, duration.as_secs_f64()
+        );
+        self.check_error_rate(dataset, year, parsed.rows, failed)
+    }
+
+    /// `--max-error-rate`: fail a stage when too many of the rows it read failed
+    /// to parse or write. Rows that landed stay, but the season's progress is
+    /// marked `failed` instead of `completed`, so the next run imports it again.
+    /// Replays retry known-bad rows and are exempt.
+    fn check_error_rate(&mut self, dataset: &'static str, year: i32, rows: usize, failed: usize) -> Result<()> {
+        let Some(max) = self.config.max_error_rate else {
+            return Ok(());
+        };
+        if rows == 0 || self.config.mode == "replay" {
+            return Ok(());
+        }
+        let rate = failed as f64 / rows as f64;
+        if rate <= max {
+            return Ok(());
+        }
+
+        let error = format!(
+            "{} of {} {} rows failed ({:.2}%, over --max-error-rate {:.2}%)",
+            failed,
+            rows,
+            dataset,
+            rate * 100.0,
+            max * 100.0
+        );
+        if !self.config.dry_run {
+            self.database.mark_failed(year, dataset, &error)?;
+        }
+        Err(anyhow!(error))
+    }
+
+    /// Mark a season import completed and store the fingerprint of the file it
+    /// read, so the next run can skip the stage while the file is unchanged
+    fn mark_completed(&mut self, year: i32, data_type: &'static str, imported: usize) -> Result<()> {
+        self.database.mark_progress(year, data_type, "completed", imported as i32)?;
+        if let Some(fingerprint) = self.fingerprints.remove(&(year, data_type)) {
+            if let Err(e) = self.database.record_fingerprint(year, data_type, &fingerprint) {
+                warn!("Failed to record {} fingerprint: {}", data_type, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Batch size and writer tuning for a dataset
+    fn tuner(&mut self, name: &'static str) -> Arc<Tuner> {
+        let config = &self.config;
+        Arc::clone(self.tuners.entry(name).or_insert_with(|| Arc::new(Tuner::from_config(name, config))))
+    }
+
+    /// `--backpressure`: before a stage's writers start, allow no more of them
+    /// than the server has spare connections for
+    fn fit_writers(&mut self, tuner: &Tuner) {
+        if !tuner.backs_off() {
+            return;
+        }
+        self.load_sampled = Some(Instant::now());
+        match backpressure::sample(self.database.get_client()) {
+            Ok(load) => tuner.limit(load.writers_allowed()),
+            Err(e) => debug!("Load not sampled: {:#}", e),
+        }
+    }
+
+    /// `--backpressure`: every few seconds, check the server's connections and
+    /// lock waits and shed, throttle or relax writers to match. A failed sample
+    /// is logged and changes nothing.
+    fn sample_load(&mut self, tuner: &Tuner) {
+        if !tuner.backs_off() || self.load_sampled.is_some_and(|at| at.elapsed() < backpressure::SAMPLE_EVERY) {
+            return;
+        }
+        self.load_sampled = Some(Instant::now());
+        match backpressure::sample(self.database.get_client()) {
+            Ok(load) => match load.action() {
+                Action::Shed(count) => tuner.limit(tuner.writers().saturating_sub(count)),
+                Action::Throttle(reason) => tuner.throttle(&reason),
+                Action::Relax => tuner.relax(),
+                Action::Hold => {}
+            },
+            Err(e) => debug!("Load not sampled: {:#}", e),
+        }
+    }
+
+    /// Parse a dataset's rows and hand accepted rows to `flush` in batches of the
+    /// tuner's current size, or smaller when the memory budget runs out first,
+    /// together with the source bytes each batch was parsed from
+    ///
+    /// With `skip_unchanged`, the file is fingerprinted before parsing; a match with
+    /// the last completed import returns `Unchanged`, anything else is kept for
+    /// `mark_completed`.
+    ///
+    /// With `--dead-letter`, rows that fail to parse are written there, and each
+    /// batch goes to `flush` with its source rows so the rows of a rolled-back batch can be too.
+    /// During a replay the dead-lettered rows stand in for the source file.
+    fn stream_stage<T: DeserializeOwned>(
+        &mut self,
+        dataset: &Dataset<T>,
+        year: i32,
+        skip_unchanged: bool,
+        tuner: &Tuner,
+        accept: impl FnMut(&mut Self, &T) -> Result<bool>,
+        flush: impl FnMut(&mut Self, Vec<T>, Vec<Row>, usize) -> Result<()>,
+    ) -> Result<Parsed> {
+        debug!("Stage {} ({} → {}) for {}", dataset.name, dataset.league, dataset.target_table, year);
+        if let Some(letters) = self.replaying.remove(&(dataset.name, year)) {
+            let (header, records) = dead_letter::records(&letters);
+            return self.parse_records(dataset, year, &header, records.into_iter().map(Ok), None, tuner, accept, flush);
+        }
+
+        let format = self.config.source_format;
+        let (header, records) = if skip_unchanged && !self.config.force && !self.config.dry_run {
+            let (fingerprint, header, records) = self.source.fingerprinted_records(&dataset.untyped(), year, format)?;
+            if let Some(fingerprint) = fingerprint {
+                match self.database.completed_fingerprint(year, dataset.name) {
+                    Ok(Some(last)) if last == fingerprint => return Err(Unchanged.into()),
+                    Ok(_) => {
+                        self.fingerprints.insert((year, dataset.name), fingerprint);
+                    }
+                    Err(e) => warn!("Fingerprint lookup failed for {}: {}", dataset.name, e),
+                }
+            }
+            (header, records)
+        } else {
+            self.source.records(&dataset.untyped(), year, format)?
+        };
+        let url = dataset.url_as(year, format);
+        self.record_source(dataset.name, year, &url);
+        let lineage_file = self.lineage_file(dataset.name, year, &url);
+        if let Err(e) = self.track_schema(dataset, year, &header) {
+            warn!("Schema tracking failed for {}: {}", dataset.name, e);
+        }
+
+        if !self.config.dry_run {
+            self.database.reset_failed_batches(year, dataset.name)?;
+        }
+
+        self.parse_records(dataset, year, &header, records, lineage_file, tuner, accept, flush)
+    }
+
+    /// Parse records against `header` and batch the accepted rows for `stream_stage`
+    #[allow(clippy::too_many_arguments)]
+    fn parse_records<T: DeserializeOwned>(
+        &mut self,
+        dataset: &Dataset<T>,
+        year: i32,
+        header: &StringRecord,
+        records: impl Iterator<Item = Result<StringRecord>>,
+        lineage_file: Option<i64>,
+        tuner: &Tuner,
+        mut accept: impl FnMut(&mut Self, &T) -> Result<bool>,
+        mut flush: impl FnMut(&mut Self, Vec<T>, Vec<Row>, usize) -> Result<()>,
+    ) -> Result<Parsed> {
+        let dialect = dataset.dialect;
+        let keep_rows = self.dead_letters.is_some();
+        let mut parsed = Parsed::default();
+        let mut batch = Vec::new();
+        let mut source_rows = Vec::new();
+        let mut batch_bytes = 0;
+        let mut traced = Vec::new();
+        let batch_budget = spill::batch_budget(self.config.memory_budget_bytes());
+
+        for (index, record) in records.enumerate() {
+            parsed.rows += 1;
+            let record = match record {
+                Ok(record) => record,
+                Err(e) => {
+                    // Unreadable records have no row to dead-letter
+                    parsed.failed += 1;
+                    warn!("Failed to parse {} row: {}", dataset.name, e);
+                    continue;
+                }
+            };
+            match dialect.deserialize::<T>(&record, header) {
+                Ok(row) => {
+                    if !accept(self, &row)? {
+                        continue;
+                    }
+                    if lineage_file.is_some() {
+                        traced.push((
+                            lineage::row_key(&record, header, dataset.key_columns, index + 1),
+                            lineage::row_hash(&record),
+                        ));
+                    }
+                    if keep_rows {
+                        source_rows.push(dead_letter::row(header, &record));
+                    }
+                    batch.push(row);
+                    batch_bytes += record.as_slice().len();
+
+                    // Wide rows (pbp, participation) flush early to stay within the memory budget
+                    if batch.len() >= tuner.batch_size() || batch_budget.is_some_and(|b| batch_bytes >= b) {
+                        self.sample_load(tuner);
+                        if let Some(file_id) = lineage_file {
+                            self.record_lineage(dataset.name, file_id, &std::mem::take(&mut traced));
+                        }
+                        flush(self, std::mem::take(&mut batch), std::mem::take(&mut source_rows), batch_bytes)?;
+                        batch_bytes = 0;
+                    }
+                }
+                Err(e) => {
+                    parsed.failed += 1;
+                    warn!("Failed to parse {} row: {}", dataset.name, e);
+                    self.dead_letter(dataset.name, year, Failure::Parse, &e.to_string(), vec![dead_letter::row(header, &record)]);
+                }
+            }
+        }
+
+        if parsed.failed > 0 {
+            warn!("  ⚠️  {} {} rows failed to parse and were skipped", parsed.failed, dataset.name);
+        }
+
+        // Insert remaining
+        if !batch.is_empty() {
+            if let Some(file_id) = lineage_file {
+                self.record_lineage(dataset.name, file_id, &traced);
+            }
+            flush(self, batch, source_rows, batch_bytes)?;
+        }
+
+        Ok(parsed)
+    }
+
+    /// Append failed rows to `--dead-letter`. Best effort: a failed write is
+    /// logged and the stage goes on.
+    fn dead_letter(&mut self, dataset: &str, year: i32, failure: Failure, error: &str, rows: Vec<Row>) {
+        let Some(dead_letters) = &mut self.dead_letters else {
+            return;
+        };
+        if rows.is_empty() {
+            return;
+        }
+        if let Err(e) = dead_letters.write(dataset, year, failure, error, rows) {
+            warn!("Failed to dead-letter {} rows: {:#}", dataset, e);
+        }
+    }
+
+    /// Write one batch. Writers commit or roll back the whole batch, so a failure
+    /// is recorded in import_progress (and its source rows dead-lettered) and the
+    /// stage moves on; returns whether the batch landed.
+    ///
+    /// A batch cut off by a dropped connection is written again once on a new
+    /// connection; writers upsert, so a replay is harmless. With `--backpressure`,
+    /// one that timed out is retried once after throttling.
+    fn write_batch<T>(
+        &mut self,
+        dataset: &Dataset<T>,
+        year: i32,
+        tuner: &Tuner,
+        write: fn(&mut Self, &[T]) -> Result<()>,
+        batch: &[T],
+        source_rows: Vec<Row>,
+    ) -> Result<bool> {
+        let mut result = write(self, batch);
+        if let Err(e) = &result {
+            if database::is_connection_error(e) {
+                warn!("  ⚠️  Connection lost during {} batch, retrying: {:#}", dataset.name, e);
+                self.database.reconnect()?;
+                result = write(self, batch);
+            } else if let Some(reason) = backpressure::overload(e).filter(|_| tuner.backs_off()) {
+                tuner.throttle(reason);
+                metrics::retry("batch");
+                std::thread::sleep(backpressure::BACKOFF);
+                result = write(self, batch);
+            }
+        }
+
+        match result {
+            Ok(()) => {
+                if self.config.dry_run {
+                    self.dry_run.rows(dataset.target_table, batch.len());
+                }
+                Ok(true)
+            }
+            Err(e) => {
+                warn!("  ⚠️  {} batch of {} rows rolled back: {:#}", dataset.name, batch.len(), e);
+                let error = format!("{:#}", e);
+                self.database.record_failed_batch(year, dataset.name, batch.len() as i32, &error)?;
+                self.dead_letter(dataset.name, year, Failure::Write, &error, source_rows);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Provenance of values read from nflverse files
+    fn nflverse_source(&self) -> Source {
+        match self.config.source_dir {
+            Some(_) => Source::Local,
+            None => Source::Nflverse,
+        }
+    }
+
+    /// Record in `source_runs` that this run read a dataset, once per season.
+    /// Best effort: a failure is logged and the run goes on.
+    fn record_source(&mut self, dataset: &'static str, season: i32, url: &str) {
+        if self.config.dry_run || !self.recorded_sources.insert((dataset, season)) {
+            return;
+        }
+        let (started_at, mode) = (self.started_at, self.config.mode.clone());
+        if let Err(e) = sources::record_run(self.database.get_client(), started_at, &mode, dataset, season, url) {
+            warn!("⚠️  Failed to record the {} source: {}", dataset, e);
+        }
+    }
+
+    /// Register the file a stage reads when `--lineage` is set; its ID, or
+    /// `None` when lineage is off or the file couldn't be recorded
+    fn lineage_file(&mut self, dataset: &'static str, season: i32, url: &str) -> Option<i64> {
+        if !self.config.lineage || self.config.dry_run {
+            return None;
+        }
+        let (location, downloaded_at) = self.source.origin(url);
+        let file = lineage::SourceFile {
+            dataset,
+            season,
+            release_tag: lineage::release_tag(&location),
+            url: location,
+            downloaded_at,
+            sha256: self.fingerprints.get(&(season, dataset)).cloned(),
+        };
+        match lineage::record_file(self.database.get_client(), self.started_at, &file) {
+            Ok(id) => Some(id),
+            Err(e) => {
+                warn!("⚠️  Failed to record the {} source file: {}", dataset, e);
+                None
+            }
+        }
+    }
+
+    fn record_lineage(&mut self, dataset: &'static str, file_id: i64, rows: &[(String, String)]) {
+        if let Err(e) = lineage::record_rows(self.database.get_client(), file_id, dataset, rows) {
+            warn!("⚠️  {}", e);
+        }
+    }
+
+    /// `--mode lineage`: the files behind rows whose key starts with `--key`
+    pub fn trace_lineage(&mut self) -> Result<()> {
+        let key = self.config.lineage_key.clone().ok_or_else(|| anyhow!("--mode lineage needs --key (e.g. a player or game ID)"))?;
+        let traces = lineage::trace(self.database.get_client(), self.config.dataset.as_deref(), &key)?;
+        lineage::log_trace(&traces, &key);
+        Ok(())
+    }
+
+    /// Attribution lines for exports when `--attribution` is set: every source
+    /// recorded in the database, or `fallback` when none has been yet
+    fn export_footer(&mut self, fallback: &[&sources::Source]) -> Vec<String> {
+        if !self.config.attribution {
+            return Vec::new();
+        }
+        match sources::in_database(self.database.get_client()) {
+            Ok(recorded) if !recorded.is_empty() => sources::footer(&recorded),
+            Ok(_) => sources::footer(fallback),
+            Err(e) => {
+                warn!("⚠️  Failed to load recorded sources for the export footer: {}", e);
+                sources::footer(fallback)
+            }
+        }
+    }
+
+    /// Dry run of a roster, schedule or stat batch: validate every row, diff the
+    /// batch against `table` and keep the first row's upsert as its sample SQL
+    fn dry_run_batch<T>(
+        &mut self,
+        table: &'static str,
+        rows: &[T],
+        validate: fn(&DataValidator, &T) -> Result<()>,
+        diff: impl FnOnce(&mut Client, &[T]) -> Result<diff::Counts>,
+        sample: impl Fn(&mut Self, &T) -> Result<()>,
+    ) -> Result<()> {
+        let validator = DataValidator::new();
+        for row in rows {
+            if let Err(e) = validate(&validator, row) {
+                self.dry_run.invalid(table, format!("{:#}", e));
+            }
+        }
+        let counts = diff(self.database.get_client(), rows)?;
+        self.dry_run.diff(table, counts);
+        for row in rows {
+            if self.emitter.is_none() && !self.dry_run.wants_sample(table) {
+                break;
+            }
+            sample(self, row)?;
+        }
+        Ok(())
+    }
+
+    /// In a dry run, keep a row's upsert, values inlined, as `table`'s sample
+    /// (and with `--emit-sql` write it to the script) instead of running it.
+    /// True when the statement was captured and must not run.
+    fn capture(&mut self, table: &'static str, sql: &str, params: &[&(dyn ToSql + Sync)]) -> Result<bool> {
+        if !self.config.dry_run {
+            return Ok(false);
+        }
+        let rendered = match &mut self.emitter {
+            Some(emitter) => emitter.emit(self.database.get_client(), sql, params)?,
+            None if self.dry_run.wants_sample(table) => sql_text::render(self.database.get_client(), sql, params)?,
+            None => return Ok(true),
+        };
+        self.dry_run.sample(table, rendered);
+        Ok(true)
+    }
+
+    /// Run row-by-row writes in one transaction, rolled back if any row fails
+    fn in_batch_transaction(&mut self, write: impl FnOnce(&mut Self) -> Result<()>) -> Result<()> {
+        self.database.begin_batch()?;
+        match write(self) {
+            Ok(()) => self.database.commit_batch(),
+            Err(e) => {
+                self.database.rollback_batch()?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Write per-team availability calendars (games and byes) for a season.
+    ///
+    /// Honors `--team`, `--format ics|json` (default ics) and `--out`.
+    pub fn export_calendars(&mut self, year: i32) -> Result<usize> {
+        let format = self.config.format.clone().unwrap_or_else(|| "ics".to_string());
+        if format != "ics" && format != "json" {
+            return Err(anyhow!("Invalid --format for calendar: {} (expected ics or json)", format));
+        }
+
+        let (header, records) = self.source.records(&datasets::SCHEDULES.untyped(), year, SourceFormat::Csv)?;
+        self.record_source(datasets::SCHEDULES.name, year, &datasets::SCHEDULES.url(year));
+        let dialect = datasets::SCHEDULES.dialect;
+        let mut games = Vec::new();
+        for record in records {
+            match record.and_then(|r| Ok(dialect.deserialize::<Game>(&r, &header)?)) {
+                Ok(game) => {
+                    let season_type = transformer::season_type_for_game_type(&game.game_type);
+                    if self.config.includes_season_type(season_type) {
+                        games.push(game);
+                    }
+                }
+                Err(e) => warn!("Failed to parse {} row: {}", datasets::SCHEDULES.name, e),
+            }
+        }
+
+        let attribution = match self.config.attribution {
+            true => sources::footer(&[&sources::NFLVERSE]),
+            false => Vec::new(),
+        };
+        let out_dir = std::path::Path::new(&self.config.out_dir);
+        std::fs::create_dir_all(out_dir)?;
+
+        let mut written = 0;
+        for mut team_calendar in calendar::build_team_calendars(&games) {
+            if self.config.team.as_ref().is_some_and(|t| *t != team_calendar.team) {
+                continue;
+            }
+
+            team_calendar.attribution = attribution.clone();
+            let path = out_dir.join(format!("{}_{}.{}", team_calendar.team, year, format));
+            let contents = match format.as_str() {
+                "json" => serde_json::to_string_pretty(&team_calendar)?,
+                _ => calendar::to_ics(&team_calendar),
+            };
+            std::fs::write(&path, contents)?;
+            written += 1;
+        }
+
+        info!("  ✅ Calendars: {} teams written to {}", written, out_dir.display());
+        Ok(written)
+    }
+
+    /// Compare a dataset file's header with the parser and the previous season,
+    /// logging and recording any drift
+    fn track_schema<T: DeserializeOwned>(&mut self, dataset: &Dataset<T>, year: i32, header: &StringRecord) -> Result<()> {
+        let previous = self.database.get_previous_dataset_columns(dataset.name, year)?;
+        let drift = drift::detect(drift::struct_fields::<T>(), header, previous.as_deref());
+
+        if !drift.is_empty() {
+            warn!(
+                "Schema drift in {} {}: missing {:?}, added {:?}, removed {:?}",
+                dataset.name, year, drift.missing_fields, drift.added_columns, drift.removed_columns
+            );
+        }
+
+        if !self.config.dry_run {
+            let columns: Vec<String> = header.iter().map(String::from).collect();
+            self.database.record_dataset_schema(dataset.name, year, &columns, &drift)?;
+        }
+
+        Ok(())
+    }
+
+    /// Import rosters for a year
+    fn import_rosters(&mut self, year: i32) -> Result<usize> {
+        info!("  [1/4] Importing rosters for {}...", year);
+
+        let (method, skip_unchanged) = (self.config.load_method, !self.config.force);
+        let (precedence, source) = (self.config.precedence.clone(), self.nflverse_source());
+        let imported = self.import_parallel_stage(
+            &datasets::ROSTERS,
+            year,
+            |_, _| Ok(true),
+            Self::upsert_players_batch,
+            move |client, players| write_players(client, method, players, &precedence, source, skip_unchanged),
+        )?;
+
+        if !self.config.dry_run {
+            self.mark_completed(year, datasets::ROSTERS.name, imported)?;
+            // Newly inserted players must be visible to the stat import
+            self.database.refresh_cache()?;
+        }
+
+        Ok(imported)
+    }
+
+    /// Import schedule for a year
+    fn import_schedule(&mut self, year: i32) -> Result<usize> {
+        info!("  [2/4] Importing schedule for {}...", year);
+
+        // Completed games with lines are kept for grading
+        let mut completed = Vec::new();
+        let (method, skip_unchanged) = (self.config.load_method, !self.config.force);
+        let (precedence, source) = (self.config.precedence.clone(), self.nflverse_source());
+        let imported = self.import_parallel_stage(
+            &datasets::SCHEDULES,
+            year,
+            |pipeline, game| {
+                let season_type = transformer::season_type_for_game_type(&game.game_type);
+                let keep = pipeline.config.includes_season_type(season_type);
+                if keep && betting::is_gradable(game) {
+                    completed.push(game.clone());
+                }
+                Ok(keep)
+            },
+            Self::upsert_games_batch,
+            move |client, games| write_games(client, method, games, &precedence, source, skip_unchanged),
+        )?;
+
+        if !self.config.dry_run {
+            self.mark_completed(year, datasets::SCHEDULES.name, imported)?;
+        }
+
+        // Grade completed games against closing lines
+        match self.import_betting_results(year, &completed) {
+            Ok(count) => info!("  ✅ Betting Results: {} games graded", count),
+            Err(e) => self.stage_failed("Betting Results", e),
+        }
+
+        Ok(imported)
+    }
+
+    /// Import player stats for a year
+    fn import_player_stats(&mut self, year: i32) -> Result<usize> {
+        self.import_player_stats_weeks(year, None)
+    }
+
+    /// Import player stats for a year, optionally restricted to specific weeks.
+    ///
+    /// Week-level progress is recorded for every week that produced rows; weeks whose
+    /// games have not all finished are marked `in_progress` so they are re-pulled later.
+    fn import_player_stats_weeks(&mut self, year: i32, weeks: Option<&[WeekStatus]>) -> Result<usize> {
+        info!("  [3/4] Importing player stats for {}...", year);
+
+        let mut per_week: BTreeMap<i32, usize> = BTreeMap::new();
+        let (method, skip_unchanged) = (self.config.load_method, !self.config.force);
+        let imported = self.import_parallel_stage(
+            &datasets::PLAYER_STATS,
+            year,
+            |pipeline, stat| {
+                // Preseason week numbers overlap the regular season, so weekly
+                // tracking only covers REG and POST (weeks 19+)
+                let weekly = stat.season_type != "PRE";
+                let wanted_week = weeks.is_none_or(|w| weekly && w.iter().any(|ws| ws.week == stat.week));
+
+                let keep = pipeline.config.includes_season_type(&stat.season_type) && wanted_week;
+                if keep && weekly {
+                    *per_week.entry(stat.week).or_insert(0) += 1;
+                }
+                Ok(keep)
+            },
+            Self::upsert_stats_batch,
+            move |client, stats| write_stats(client, method, stats, skip_unchanged),
+        )?;
+
+        if !self.config.dry_run {
+            let data_type = datasets::PLAYER_STATS.name;
+            for (week, count) in &per_week {
+                let complete = weeks
+                    .and_then(|w| w.iter().find(|ws| ws.week == *week))
+                    .is_none_or(|ws| ws.complete);
+                let status = if complete { "completed" } else { "in_progress" };
+                self.database.mark_week_progress(year, data_type, *week, status, *count as i32)?;
+            }
+
+            // Season-level progress only reflects full-season imports
+            if weeks.is_none() {
+                self.mark_completed(year, data_type, imported)?;
+            }
+        }
+
+        Ok(imported)
+    }
+
+    /// Import college production for players already in the players table.
+    ///
+    /// Rows are linked on name + school; college players who never reached the NFL
+    /// (or can't be matched unambiguously) are skipped.
+    fn import_college_stats(&mut self, year: i32) -> Result<usize> {
+        info!("  [5/5] Importing college stats for {}...", year);
+
+        self.college_links = self.database.get_player_ids_by_name_college()?;
+        let mut seen = 0;
+
+        let imported = self.import_stage(
+            &datasets::COLLEGE_STATS,
+            year,
+            |pipeline, stat| {
+                seen += 1;
+                Ok(pipeline.college_player_id(stat).is_some())
+            },
+            Self::upsert_college_stats_batch,
+        )?;
+
+        info!("  🔗 College rows linked to NFL players: {}/{}", imported, seen);
+
+        if !self.config.dry_run {
+            self.mark_completed(year, datasets::COLLEGE_STATS.name, imported)?;
+        }
+
+        Ok(imported)
+    }
+
+    fn college_player_id(&self, stat: &CollegeStat) -> Option<Uuid> {
+        let key = (
+            stat.player.to_lowercase(),
+            stat.team.as_deref().unwrap_or_default().to_lowercase(),
+        );
+        self.college_links.get(&key).copied()
+    }
+
+    fn upsert_college_stats_batch(&mut self, stats: &[CollegeStat]) -> Result<()> {
+        if self.config.dry_run {
+            return Ok(());
+        }
+
+        let links: Vec<Option<Uuid>> = stats.iter().map(|s| self.college_player_id(s)).collect();
+        let client = self.database.get_client();
+        let mut tx = client.transaction()?;
+
+        for (stat, player_id) in stats.iter().zip(links) {
+
+            tx.execute(
+                "INSERT INTO college_stats (cfb_player_id, player_id, season, player_name, team, conference, position, games,
+                     passing_completions, passing_attempts, passing_yards, passing_tds, interceptions,
+                     rushing_attempts, rushing_yards, rushing_tds, receptions, receiving_yards, receiving_tds)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19)
+                 ON CONFLICT (cfb_player_id, season) DO UPDATE SET
+                     player_id = EXCLUDED.player_id,
+                     team = EXCLUDED.team,
+                     conference = EXCLUDED.conference,
+                     position = EXCLUDED.position,
+                     games = EXCLUDED.games,
+                     passing_completions = EXCLUDED.passing_completions,
+                     passing_attempts = EXCLUDED.passing_attempts,
+                     passing_yards = EXCLUDED.passing_yards,
+                     passing_tds = EXCLUDED.passing_tds,
+                     interceptions = EXCLUDED.interceptions,
+                     rushing_attempts = EXCLUDED.rushing_attempts,
+                     rushing_yards = EXCLUDED.rushing_yards,
+                     rushing_tds = EXCLUDED.rushing_tds,
+                     receptions = EXCLUDED.receptions,
+                     receiving_yards = EXCLUDED.receiving_yards,
+                     receiving_tds = EXCLUDED.receiving_tds,
+                     updated_at = NOW()",
+                &[
+                    &stat.athlete_id,
+                    &player_id,
+                    &stat.season,
+                    &stat.player,
+                    &stat.team,
+                    &stat.conference,
+                    &stat.position,
+                    &stat.games,
+                    &stat.passing_completions,
+                    &stat.passing_attempts,
+                    &stat.passing_yards,
+                    &stat.passing_tds,
+                    &stat.passing_int,
+                    &stat.rushing_car,
+                    &stat.rushing_yds,
+                    &stat.rushing_td,
+                    &stat.receiving_rec,
+                    &stat.receiving_yds,
+                    &stat.receiving_td,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Grade completed games with closing lines against the spread and total,
+    /// from the rows the schedule stage already parsed
+    fn import_betting_results(&mut self, year: i32, games: &[Game]) -> Result<usize> {
+        self.upsert_betting_results_batch(games)?;
+
+        if !self.config.dry_run {
+            self.database.mark_progress(year, "betting_results", "completed", games.len() as i32)?;
+        }
+
+        Ok(games.len())
+    }
+
+    fn upsert_betting_results_batch(&mut self, games: &[Game]) -> Result<()> {
+        if self.config.dry_run {
+            return Ok(());
+        }
+
+        let client = self.database.get_client();
+        let mut tx = client.transaction()?;
+
+        for game in games {
+            let (home_score, away_score) = match (game.home_score, game.away_score) {
+                (Some(home), Some(away)) => (home, away),
+                _ => continue,
+            };
+            let spread_result = game
+                .spread_line
+                .map(|line| betting::grade_spread(home_score, away_score, line).as_str());
+            let total_result = game
+                .total_line
+                .map(|line| betting::grade_total(home_score, away_score, line).as_str());
+
+            tx.execute(
+                "INSERT INTO game_betting_results (nfl_game_id, season, week, home_team, away_team, home_coach, away_coach,
+                     home_score, away_score, spread_line, total_line, spread_result, total_result,
+                     referee, home_rest, away_rest, graded_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10::float8::numeric, $11::float8::numeric, $12, $13,
+                     $14, $15, $16, NOW())
+                 ON CONFLICT (nfl_game_id) DO UPDATE SET
+                     home_coach = EXCLUDED.home_coach,
+                     away_coach = EXCLUDED.away_coach,
+                     referee = EXCLUDED.referee,
+                     home_rest = EXCLUDED.home_rest,
+                     away_rest = EXCLUDED.away_rest,
+                     home_score = EXCLUDED.home_score,
+                     away_score = EXCLUDED.away_score,
+                     spread_line = EXCLUDED.spread_line,
+                     total_line = EXCLUDED.total_line,
+                     spread_result = EXCLUDED.spread_result,
+                     total_result = EXCLUDED.total_result,
+                     graded_at = NOW()",
+                &[
+                    &game.game_id,
+                    &game.season,
+                    &game.week,
+                    &transformer::normalize_team_abbr(&game.home_team),
+                    &transformer::normalize_team_abbr(&game.away_team),
+                    &game.home_coach,
+                    &game.away_coach,
+                    &home_score,
+                    &away_score,
+                    &game.spread_line,
+                    &game.total_line,
+                    &spread_result,
+                    &total_result,
+                    &game.referee,
+                    &game.home_rest,
+                    &game.away_rest,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Rebuild seasonal coach and referee reports (failures are logged, not fatal)
+    fn refresh_reports(&mut self, year: i32) {
+        self.materialize("coach_records", "Coach Records", year, "", |client| {
+            let count = reports::refresh_coach_records(client, year)?;
+            info!("  ✅ Coach Records: {} rows", count);
+            Ok(())
+        });
+        self.materialize("referee_tendencies", "Referee Tendencies", year, "", |client| {
+            let count = reports::refresh_referee_tendencies(client, year)?;
+            info!("  ✅ Referee Tendencies: {} rows", count);
+            Ok(())
+        });
+    }
+
+    /// Refresh a derived table's season unless its inputs and `settings` are
+    /// unchanged since its last refresh (`--force` refreshes regardless). The
+    /// freshness key is stored once the refresh succeeds; failures are logged,
+    /// not fatal. Without readable state (migration 058 not applied) every
+    /// refresh runs.
+    fn materialize(
+        &mut self,
+        name: &'static str,
+        label: &str,
+        year: i32,
+        settings: &str,
+        refresh: impl FnOnce(&mut Client) -> Result<()>,
+    ) {
+        if self.config.dry_run {
+            return;
+        }
+
+        let derived = materialize::derived(name).expect("derived table is registered");
+        let client = self.database.get_client();
+        let mut key = match materialize::freshness_key(client, derived, year, settings) {
+            Ok(key) => Some(key),
+            Err(e) => {
+                warn!("Freshness check failed for {}: {:#}", name, e);
+                None
+            }
+        };
+        if let Some(current) = &key {
+            match materialize::stored_key(client, name, year) {
+                Ok(Some(stored)) if stored == *current && !self.config.force => {
+                    info!("  ⏭️  {}: skipped (inputs unchanged)", label);
+                    return;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("Materialization state unavailable for {}: {:#}", name, e);
+                    key = None;
+                }
+            }
+        }
+
+        match refresh(client) {
+            Ok(()) => {
+                if let Some(key) = key {
+                    if let Err(e) = materialize::store_key(client, name, year, &key) {
+                        warn!("Failed to store freshness of {}: {:#}", name, e);
+                    }
+                }
+            }
+            Err(e) => warn!("  ⚠️  {} failed: {}", label, e),
+        }
+    }
+
+    /// Import weekly injury reports for a year
+    fn import_injuries(&mut self, year: i32) -> Result<usize> {
+        info!("  Importing injury reports for {}...", year);
+
+        let imported = self.import_stage(
+            &datasets::INJURIES,
+            year,
+            |pipeline, injury| {
+                let season_type = transformer::season_type_for_game_type(&injury.game_type);
+                Ok(pipeline.config.includes_season_type(season_type))
+            },
+            Self::upsert_injuries_batch,
+        )?;
+
+        if !self.config.dry_run {
+            self.mark_completed(year, datasets::INJURIES.name, imported)?;
+        }
+
+        Ok(imported)
+    }
+
+    fn upsert_injuries_batch(&mut self, injuries: &[Injury]) -> Result<()> {
+        if self.config.dry_run {
+            return Ok(());
+        }
+
+        let mut ids = Vec::with_capacity(injuries.len());
+        for injury in injuries {
+            let player_id = self.database.get_player_id_by_nfl_id(&injury.gsis_id)?;
+            let team_id = self.database.get_team_id_by_abbr(&transformer::normalize_team_abbr(&injury.team))?;
+            ids.push((player_id, team_id));
+        }
+
+        let client = self.database.get_client();
+        let mut tx = client.transaction()?;
+
+        for (injury, (player_id, team_id)) in injuries.iter().zip(ids) {
+            let Some(player_id) = player_id else { continue };
+
+            tx.execute(
+                "INSERT INTO injuries (player_id, team_id, season, season_type, week, report_status,
+                     report_primary_injury, report_secondary_injury, practice_status,
+                     practice_primary_injury, practice_secondary_injury, date_modified)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12::text::timestamp)
+                 ON CONFLICT (player_id, season, season_type, week) DO UPDATE SET
+                     team_id = EXCLUDED.team_id,
+                     report_status = EXCLUDED.report_status,
+                     report_primary_injury = EXCLUDED.report_primary_injury,
+                     report_secondary_injury = EXCLUDED.report_secondary_injury,
+                     practice_status = EXCLUDED.practice_status,
+                     practice_primary_injury = EXCLUDED.practice_primary_injury,
+                     practice_secondary_injury = EXCLUDED.practice_secondary_injury,
+                     date_modified = EXCLUDED.date_modified,
+                     updated_at = NOW()",
+                &[
+                    &player_id,
+                    &team_id,
+                    &injury.season,
+                    &transformer::season_type_for_game_type(&injury.game_type),
+                    &injury.week,
+                    &injury.report_status,
+                    &injury.report_primary_injury,
+                    &injury.report_secondary_injury,
+                    &injury.practice_status,
+                    &injury.practice_primary_injury,
+                    &injury.practice_secondary_injury,
+                    &injury.date_modified,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Import weekly depth charts for a year
+    fn import_depth_charts(&mut self, year: i32) -> Result<usize> {
+        info!("  Importing depth charts for {}...", year);
+
+        let imported = self.import_stage(
+            &datasets::DEPTH_CHARTS,
+            year,
+            |pipeline, entry| {
+                let season_type = transformer::season_type_for_game_type(&entry.game_type);
+                Ok(entry.week.is_some() && pipeline.config.includes_season_type(season_type))
+            },
+            Self::upsert_depth_charts_batch,
+        )?;
+
+        if !self.config.dry_run {
+            self.mark_completed(year, datasets::DEPTH_CHARTS.name, imported)?;
+        }
+
+        Ok(imported)
+    }
+
+    fn upsert_depth_charts_batch(&mut self, entries: &[DepthChartEntry]) -> Result<()> {
+        if self.config.dry_run {
+            return Ok(());
+        }
+
+        let mut ids = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let team_id = self.database.get_team_id_by_abbr(&transformer::normalize_team_abbr(&entry.club_code))?;
+            let player_id = match &entry.gsis_id {
+                Some(gsis_id) => self.database.get_player_id_by_nfl_id(gsis_id)?,
+                None => None,
+            };
+            ids.push((team_id, player_id));
+        }
+
+        let client = self.database.get_client();
+        let mut tx = client.transaction()?;
+        let mut unresolved = Vec::new();
+
+        for (entry, (team_id, player_id)) in entries.iter().zip(ids) {
+            let Some(team_id) = team_id else {
+                warn!("Team {} not found", entry.club_code);
+                unresolved.push(Unresolved::team(&entry.club_code, datasets::DEPTH_CHARTS.name, entry.season));
+                continue;
+            };
+            if let (None, Some(gsis_id)) = (player_id, &entry.gsis_id) {
+                unresolved.push(Unresolved::player(gsis_id, entry.full_name.as_deref(), datasets::DEPTH_CHARTS.name, entry.season));
+            }
+
+            tx.execute(
+                "INSERT INTO depth_charts (season, season_type, week, team_id, position, depth_rank, formation,
+                     player_id, gsis_id, player_name, jersey_number)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                 ON CONFLICT (season, season_type, week, team_id, position, depth_rank) DO UPDATE SET
+                     formation = EXCLUDED.formation,
+                     player_id = EXCLUDED.player_id,
+                     gsis_id = EXCLUDED.gsis_id,
+                     player_name = EXCLUDED.player_name,
+                     jersey_number = EXCLUDED.jersey_number,
+                     updated_at = NOW()",
+                &[
+                    &entry.season,
+                    &transformer::season_type_for_game_type(&entry.game_type),
+                    &entry.week,
+                    &team_id,
+                    &entry.depth_position,
+                    &entry.depth_team,
+                    &entry.formation,
+                    &player_id,
+                    &entry.gsis_id,
+                    &entry.full_name,
+                    &entry.jersey_number,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        resolution::record(client, &unresolved);
+        Ok(())
+    }
+
+    fn import_snap_counts(&mut self, year: i32) -> Result<usize> {
+        info!("  Importing snap counts for {}...", year);
+
+        let imported = self.import_stage(
+            &datasets::SNAP_COUNTS,
+            year,
+            |pipeline, snap| {
+                let season_type = transformer::season_type_for_game_type(&snap.game_type);
+                Ok(snap.pfr_player_id.is_some() && pipeline.config.includes_season_type(season_type))
+            },
+            Self::upsert_snap_counts_batch,
+        )?;
+
+        if !self.config.dry_run {
+            self.mark_completed(year, datasets::SNAP_COUNTS.name, imported)?;
+        }
+
+        Ok(imported)
+    }
+
+    /// Snap counts key players by PFR ID, so player_id comes from the external ID crosswalk
+    fn upsert_snap_counts_batch(&mut self, snaps: &[SnapCount]) -> Result<()> {
+        if self.config.dry_run {
+            return Ok(());
+        }
+
+        let mut team_ids = Vec::with_capacity(snaps.len());
+        for snap in snaps {
+            team_ids.push(self.database.get_team_id_by_abbr(&transformer::normalize_team_abbr(&snap.team))?);
+        }
+
+        let client = self.database.get_client();
+        let mut tx = client.transaction()?;
+
+        for (snap, team_id) in snaps.iter().zip(team_ids) {
+            tx.execute(
+                "INSERT INTO snap_counts (nfl_game_id, season, season_type, week, team_id, player_id, pfr_player_id,
+                     player_name, position, offense_snaps, offense_pct, defense_snaps, defense_pct, st_snaps, st_pct)
+                 VALUES ($1, $2, $3, $4, $5,
+                     (SELECT player_id FROM player_external_ids WHERE source = 'pfr' AND external_id = $6 LIMIT 1),
+                     $6, $7, $8, $9, $10::float8, $11, $12::float8, $13, $14::float8)
+                 ON CONFLICT (nfl_game_id, pfr_player_id) DO UPDATE SET
+                     team_id = EXCLUDED.team_id,
+                     player_id = EXCLUDED.player_id,
+                     player_name = EXCLUDED.player_name,
+                     position = EXCLUDED.position,
+                     offense_snaps = EXCLUDED.offense_snaps,
+                     offense_pct = EXCLUDED.offense_pct,
+                     defense_snaps = EXCLUDED.defense_snaps,
+                     defense_pct = EXCLUDED.defense_pct,
+                     st_snaps = EXCLUDED.st_snaps,
+                     st_pct = EXCLUDED.st_pct,
+                     updated_at = NOW()",
+                &[
+                    &snap.game_id,
+                    &snap.season,
+                    &transformer::season_type_for_game_type(&snap.game_type),
+                    &snap.week,
+                    &team_id,
+                    &snap.pfr_player_id,
+                    &snap.player,
+                    &snap.position,
+                    &snap.offense_snaps,
+                    &snap.offense_pct,
+                    &snap.defense_snaps,
+                    &snap.defense_pct,
+                    &snap.st_snaps,
+                    &snap.st_pct,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Aggregate a season's play-by-play into team and player efficiency metrics.
+    ///
+    /// Plays are folded into the aggregates as they stream in; only the aggregates
+    /// are written. Returns the number of aggregate rows.
+    fn import_efficiency(&mut self, year: i32) -> Result<usize> {
+        info!("  Aggregating play-by-play efficiency for {}...", year);
+
+        let filter = GarbageTimeFilter { wp: self.config.garbage_time_wp };
+        let mut aggregator = EfficiencyAggregator::new(filter);
+        self.import_stage(
+            &datasets::PLAY_BY_PLAY,
+            year,
+            |pipeline, play| {
+                if pipeline.config.includes_season_type(&play.season_type) {
+                    aggregator.add(play);
+                }
+                Ok(false)
+            },
+            Self::discard_batch,
+        )?;
+
+        let rows = aggregator.rows();
+        if self.config.dry_run {
+            self.dry_run.rows(datasets::PLAY_BY_PLAY.target_table, rows.len());
+            return Ok(rows.len());
+        }
+
+        let written = efficiency::write_efficiency_metrics(self.database.get_client(), year, filter, &rows)?;
+        self.mark_completed(year, datasets::PLAY_BY_PLAY.name, written as usize)?;
+        Ok(written as usize)
+    }
+
+    /// Diff a season's weekly roster snapshots into `roster_moves`. Returns the
+    /// number of moves written.
+    fn import_roster_moves(&mut self, year: i32) -> Result<usize> {
+        info!("  Diffing weekly rosters for {}...", year);
+
+        let mut weeks: BTreeMap<i32, Vec<RosterPlayer>> = BTreeMap::new();
+        self.import_stage(
+            &datasets::WEEKLY_ROSTERS,
+            year,
+            |_, player| {
+                if let Some(week) = player.week {
+                    weeks.entry(week).or_default().push(player.clone());
+                }
+                Ok(false)
+            },
+            Self::discard_batch,
+        )?;
+
+        let snapshots: Vec<RosterSnapshot> = weeks
+            .iter()
+            .map(|(week, players)| RosterSnapshot::from_players(*week, players))
+            .collect();
+        if self.config.dry_run {
+            let moves = snapshots
+                .windows(2)
+                .map(|pair| roster_moves::diff_rosters(&pair[0], &pair[1]))
+                .map(|diff| diff.moves().len())
+                .sum();
+            self.dry_run.rows(datasets::WEEKLY_ROSTERS.target_table, moves);
+            return Ok(moves);
+        }
+
+        let written = roster_moves::write_roster_moves(self.database.get_client(), year, &snapshots)?;
+        self.mark_completed(year, datasets::WEEKLY_ROSTERS.name, written as usize)?;
+        Ok(written as usize)
+    }
+
+    /// Batch writer for stages that only aggregate in `accept`
+    fn discard_batch<T>(&mut self, _rows: &[T]) -> Result<()> {
+        Ok(())
+    }
+
+    /// `--mode seed-teams`: load the current franchises from nflverse's teams file.
+    /// Existing rows are updated in place (matched on abbreviation) so their IDs
+    /// and every foreign key to them survive.
+    pub fn seed_teams(&mut self) -> Result<()> {
+        let teams = self.run_stage(
+            &datasets::TEAMS,
+            ALL_SEASONS,
+            |_, team| Ok(!transformer::NON_CURRENT_TEAM_ABBRS.contains(&team.team_abbr.as_str())),
+            Self::upsert_teams_batch,
+        )?;
+        info!("  ✅ Teams: {}", teams);
+
+        if !self.config.dry_run {
+            self.database.mark_progress(ALL_SEASONS, datasets::TEAMS.name, "completed", teams as i32)?;
+            self.database.refresh_cache()?;
+        }
+        Ok(())
+    }
+
+    fn upsert_teams_batch(&mut self, teams: &[TeamRecord]) -> Result<()> {
+        if self.config.dry_run {
+            return Ok(());
+        }
+
+        let client = self.database.get_client();
+        let mut tx = client.transaction()?;
+
+        for team in teams {
+            tx.execute(
+                "INSERT INTO teams (abbreviation, name, nickname, city, conference, division, color, alternate_color,
+                     logo_url, wordmark_url)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                 ON CONFLICT (abbreviation) DO UPDATE SET
+                     name = EXCLUDED.name,
+                     nickname = EXCLUDED.nickname,
+                     city = EXCLUDED.city,
+                     conference = EXCLUDED.conference,
+                     division = EXCLUDED.division,
+                     color = COALESCE(EXCLUDED.color, teams.color),
+                     alternate_color = COALESCE(EXCLUDED.alternate_color, teams.alternate_color),
+                     logo_url = COALESCE(EXCLUDED.logo_url, teams.logo_url),
+                     wordmark_url = COALESCE(EXCLUDED.wordmark_url, teams.wordmark_url),
+                     updated_at = NOW()",
+                &[
+                    &team.team_abbr,
+                    &team.team_name,
+                    &team.team_nick,
+                    &transformer::team_city(&team.team_name, &team.team_nick),
+                    &team.team_conf,
+                    &transformer::division_name(&team.team_division, &team.team_conf),
+                    &team.team_color,
+                    &team.team_color2,
+                    &team.team_logo_espn,
+                    &team.team_wordmark,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Import the full draft history and combine results.
+    ///
+    /// Both files cover every season, so this runs once (and can be re-run safely)
+    /// instead of per year. Combine rows link to players through draft picks' PFR IDs.
+    pub fn import_draft(&mut self) -> Result<()> {
+        info!("  Importing draft picks...");
+        let picks = self.run_stage(&datasets::DRAFT_PICKS, ALL_SEASONS, |_, _| Ok(true), Self::upsert_draft_picks_batch)?;
+        info!("  ✅ Draft Picks: {} picks", picks);
+
+        info!("  Importing combine results...");
+        let combine = self.run_stage(&datasets::COMBINE, ALL_SEASONS, |_, _| Ok(true), Self::upsert_combine_batch)?;
+        info!("  ✅ Combine: {} participants", combine);
+
+        if !self.config.dry_run {
+            let linked = self.database.link_combine_results()?;
+            info!("  🔗 Combine results linked to players: {}", linked);
+            self.database.mark_progress(ALL_SEASONS, datasets::DRAFT_PICKS.name, "completed", picks as i32)?;
+            self.database.mark_progress(ALL_SEASONS, datasets::COMBINE.name, "completed", combine as i32)?;
+        }
+
+        Ok(())
+    }
+
+    fn upsert_draft_picks_batch(&mut self, picks: &[DraftPick]) -> Result<()> {
+        if self.config.dry_run {
+            return Ok(());
+        }
+
+        let mut ids = Vec::with_capacity(picks.len());
+        for pick in picks {
+            let team_id = self.database.get_team_id_by_abbr(&transformer::normalize_team_abbr(&pick.team))?;
+            let player_id = match &pick.gsis_id {
+                Some(gsis_id) => self.database.get_player_id_by_nfl_id(gsis_id)?,
+                None => None,
+            };
+            ids.push((team_id, player_id));
+        }
+
+        let client = self.database.get_client();
+        let mut tx = client.transaction()?;
+
+        for (pick, (team_id, player_id)) in picks.iter().zip(ids) {
+            tx.execute(
+                "INSERT INTO draft_picks (season, round, pick, team_id, team_abbr, player_id, gsis_id, pfr_player_id,
+                     cfb_player_id, player_name, position, category, side, college, age, hof, allpro, probowls,
+                     seasons_started, w_av, car_av, games)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22)
+                 ON CONFLICT (season, pick) DO UPDATE SET
+                     round = EXCLUDED.round,
+                     team_id = EXCLUDED.team_id,
+                     team_abbr = EXCLUDED.team_abbr,
+                     player_id = COALESCE(EXCLUDED.player_id, draft_picks.player_id),
+                     gsis_id = EXCLUDED.gsis_id,
+                     pfr_player_id = EXCLUDED.pfr_player_id,
+                     cfb_player_id = EXCLUDED.cfb_player_id,
+                     player_name = EXCLUDED.player_name,
+                     position = EXCLUDED.position,
+                     category = EXCLUDED.category,
+                     side = EXCLUDED.side,
+                     college = EXCLUDED.college,
+                     age = EXCLUDED.age,
+                     hof = EXCLUDED.hof,
+                     allpro = EXCLUDED.allpro,
+                     probowls = EXCLUDED.probowls,
+                     seasons_started = EXCLUDED.seasons_started,
+                     w_av = EXCLUDED.w_av,
+                     car_av = EXCLUDED.car_av,
+                     games = EXCLUDED.games,
+                     updated_at = NOW()",
+                &[
+                    &pick.season,
+                    &pick.round,
+                    &pick.pick,
+                    &team_id,
+                    &pick.team,
+                    &player_id,
+                    &pick.gsis_id,
+                    &pick.pfr_player_id,
+                    &pick.cfb_player_id,
+                    &pick.pfr_player_name,
+                    &pick.position,
+                    &pick.category,
+                    &pick.side,
+                    &pick.college,
+                    &pick.age,
+                    &pick.hof,
+                    &pick.allpro,
+                    &pick.probowls,
+                    &pick.seasons_started,
+                    &pick.w_av,
+                    &pick.car_av,
+                    &pick.games,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn upsert_combine_batch(&mut self, results: &[CombineResult]) -> Result<()> {
+        if self.config.dry_run {
+            return Ok(());
+        }
+
+        let client = self.database.get_client();
+        let mut tx = client.transaction()?;
+
+        for result in results {
+            let height_inches = result.ht.as_deref().and_then(transformer::height_to_inches);
+
+            tx.execute(
+                "INSERT INTO combine_results (season, pfr_id, cfb_id, player_name, position, school, draft_team,
+                     draft_round, draft_pick, height_inches, weight_pounds, forty, bench, vertical, broad_jump, cone, shuttle)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12::float8, $13, $14::float8, $15, $16::float8, $17::float8)
+                 ON CONFLICT (season, player_name, position, (COALESCE(school, ''))) DO UPDATE SET
+                     pfr_id = EXCLUDED.pfr_id,
+                     cfb_id = EXCLUDED.cfb_id,
+                     draft_team = EXCLUDED.draft_team,
+                     draft_round = EXCLUDED.draft_round,
+                     draft_pick = EXCLUDED.draft_pick,
+                     height_inches = EXCLUDED.height_inches,
+                     weight_pounds = EXCLUDED.weight_pounds,
+                     forty = EXCLUDED.forty,
+                     bench = EXCLUDED.bench,
+                     vertical = EXCLUDED.vertical,
+                     broad_jump = EXCLUDED.broad_jump,
+                     cone = EXCLUDED.cone,
+                     shuttle = EXCLUDED.shuttle,
+                     updated_at = NOW()",
+                &[
+                    &result.season,
+                    &result.pfr_id,
+                    &result.cfb_id,
+                    &result.player_name,
+                    &result.pos,
+                    &result.school,
+                    &result.draft_team,
+                    &result.draft_round,
+                    &result.draft_ovr,
+                    &height_inches,
+                    &result.wt,
+                    &result.forty,
+                    &result.bench,
+                    &result.vertical,
+                    &result.broad_jump,
+                    &result.cone,
+                    &result.shuttle,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Import OverTheCap contract history and rebuild value metrics for the
+    /// configured seasons.
+    ///
+    /// The file holds every contract, so it replaces the table: rows from earlier
+    /// runs are removed only after the new file has loaded.
+    pub fn import_contracts(&mut self) -> Result<()> {
+        info!("  Importing contracts...");
+        let started: chrono::NaiveDateTime = self.database.get_client().query_one("SELECT NOW()::timestamp", &[])?.get(0);
+        let contracts = self.run_stage(&datasets::CONTRACTS, ALL_SEASONS, |_, _| Ok(true), Self::insert_contracts_batch)?;
+        info!("  ✅ Contracts: {} contracts", contracts);
+
+        if self.config.dry_run {
+            return Ok(());
+        }
+
+        let replaced = self
+            .database
+            .get_client()
+            .execute("DELETE FROM player_contracts WHERE created_at < $1", &[&started])?;
+        info!("  🧹 Previous contracts replaced: {}", replaced);
+        self.database.mark_progress(ALL_SEASONS, datasets::CONTRACTS.name, "completed", contracts as i32)?;
+
+        for year in self.config.start_year..=self.config.end_year {
+            self.refresh_value_metrics(year);
+        }
+        Ok(())
+    }
+
+    fn insert_contracts_batch(&mut self, contracts: &[Contract]) -> Result<()> {
+        if self.config.dry_run {
+            return Ok(());
+        }
+
+        let mut player_ids = Vec::with_capacity(contracts.len());
+        for contract in contracts {
+            player_ids.push(match &contract.gsis_id {
+                Some(gsis_id) => self.database.get_player_id_by_nfl_id(gsis_id)?,
+                None => None,
+            });
+        }
+
+        let client = self.database.get_client();
+        let mut tx = client.transaction()?;
+
+        for (contract, player_id) in contracts.iter().zip(player_ids) {
+            tx.execute(
+                "INSERT INTO player_contracts (player_id, gsis_id, otc_id, player_name, position, team, is_active,
+                     year_signed, years, value, apy, guaranteed, apy_cap_pct, draft_year, draft_round, draft_overall)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10::float8, $11::float8, $12::float8, $13::float8, $14, $15, $16)",
+                &[
+                    &player_id,
+                    &contract.gsis_id,
+                    &contract.otc_id,
+                    &contract.player,
+                    &contract.position,
+                    &contract.team,
+                    &contract.is_active,
+                    &contract.year_signed,
+                    &contract.years,
+                    &contract.value,
+                    &contract.apy,
+                    &contract.guaranteed,
+                    &contract.apy_cap_pct,
+                    &contract.draft_year,
+                    &contract.draft_round,
+                    &contract.draft_overall,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn refresh_value_metrics(&mut self, year: i32) {
+        self.materialize("value_metrics", "Value Metrics", year, "", |client| {
+            let count = value::refresh_value_metrics(client, year)?;
+            info!("  ✅ Value Metrics: {} players", count);
+            Ok(())
+        });
+    }
+
+    /// Rebuild derived player splits for a season (failures are logged, not fatal)
+    fn refresh_splits(&mut self, year: i32) {
+        self.materialize("player_splits", "Splits", year, "", |client| {
+            let count = splits::refresh_player_splits(client, year)?;
+            info!("  ✅ Splits: {} rows", count);
+            Ok(())
+        });
+    }
+
+    /// `--mode fantasy`: rescore a season, e.g. after changing `--scoring-config`
+    pub fn run_fantasy(&mut self, year: i32) -> Result<()> {
+        let formats = fantasy::scoring_formats(self.config.scoring_config.as_deref())?;
+        if self.config.dry_run {
+            let names: Vec<&str> = formats.iter().map(|f| f.name.as_str()).collect();
+            info!("  🧪 Dry run: would score {} with {}", year, names.join(", "));
+            return Ok(());
+        }
+        let count = fantasy::refresh_fantasy_points(self.database.get_client(), year, &formats)?;
+        info!("  ✅ Fantasy Points: {} rows ({} formats)", count, formats.len());
+        Ok(())
+    }
+
+    fn refresh_fantasy_points(&mut self, year: i32) {
+        let formats = match fantasy::scoring_formats(self.config.scoring_config.as_deref()) {
+            Ok(formats) => formats,
+            Err(e) => {
+                warn!("  ⚠️  Fantasy Points failed: {}", e);
+                return;
+            }
+        };
+        // Editing --scoring-config rescores seasons whose stats didn't change
+        self.materialize("fantasy_points", "Fantasy Points", year, &format!("{:?}", formats), |client| {
+            let count = fantasy::refresh_fantasy_points(client, year, &formats)?;
+            info!("  ✅ Fantasy Points: {} rows", count);
+            Ok(())
+        });
+    }
+
+    /// `--mode aggregate`: rebuild season and career stats for every season
+    pub fn run_aggregate(&mut self) -> Result<()> {
+        if self.config.dry_run {
+            info!("  🧪 Dry run: would rebuild season_stats and career_stats");
+            return Ok(());
+        }
+        let (seasons, careers) = aggregates::rebuild_aggregates(self.database.get_client())?;
+        info!("  ✅ Aggregates: {} season rows, {} career rows", seasons, careers);
+        Ok(())
+    }
+
+    fn refresh_aggregates(&mut self, year: i32) {
+        self.materialize("season_stats", "Aggregates", year, "", |client| {
+            let (seasons, careers) = aggregates::refresh_aggregates(client, year)?;
+            info!("  ✅ Aggregates: {} season rows, {} career rows", seasons, careers);
+            Ok(())
+        });
+    }
+
+    fn refresh_team_ratings(&mut self, year: i32) {
+        self.materialize("team_ratings", "Team Ratings", year, "", |client| {
+            let count = ratings::refresh_team_ratings(client, year)?;
+            info!("  ✅ Team Ratings: {} rows", count);
+            Ok(())
+        });
+    }
+
+    fn refresh_standings(&mut self, year: i32) {
+        self.materialize("team_standings", "Standings", year, "", |client| {
+            let count = standings::refresh_standings(client, year)?;
+            info!("  ✅ Standings: {} team-weeks", count);
+            Ok(())
+        });
+    }
+
+    fn refresh_season_luck(&mut self, year: i32) {
+        self.materialize("season_luck", "Pythagorean Standings", year, "", |client| {
+            let count = standings::refresh_season_luck(client, year)?;
+            info!("  ✅ Pythagorean Standings: {} teams", count);
+            Ok(())
+        });
+    }
+
+    fn refresh_role_changes(&mut self, year: i32) {
+        self.materialize("role_changes", "Role Changes", year, "", |client| {
+            let count = roles::refresh_role_changes(client, year)?;
+            info!("  ✅ Role Changes: {} detected", count);
+            Ok(())
+        });
+    }
+
+    /// Rebuild the season's usage alerts, then log and post those for its latest week
+    fn refresh_usage_alerts(&mut self, year: i32) {
+        let mut latest_alerts = Vec::new();
+        self.materialize("usage_alerts", "Usage Alerts", year, "", |client| {
+            let alerts = usage::refresh_usage_alerts(client, year)?;
+            info!("  ✅ Usage Alerts: {} detected", alerts.len());
+            let latest = alerts.iter().map(|a| a.week).max();
+            latest_alerts = alerts.into_iter().filter(|a| Some(a.week) == latest).collect();
+            for alert in &latest_alerts {
+                info!(
+                    "    📣 Week {} {}: {} {} {:.2} → {:.2}",
+                    alert.week,
+                    alert.player_name,
+                    alert.metric.as_str(),
+                    alert.direction(),
+                    alert.baseline,
+                    alert.value
+                );
+            }
+            Ok(())
+        });
+        if let Some(notifier) = &self.notifier {
+            notifier.usage_alerts(year, &latest_alerts);
+        }
+    }
+
+    /// Import NGS passing stats
+    fn import_ngs_passing(&mut self, year: i32) -> Result<usize> {
+        info!("  [4/4] Importing NGS passing for {}...", year);
+        let imported = self.import_stage(
+            &datasets::NGS_PASSING,
+            year,
+            |pipeline, row| Ok(pipeline.config.includes_season_type(&row.season_type)),
+            Self::upsert_ngs_passing_batch,
+        )?;
+        self.mark_ngs_progress(datasets::NGS_PASSING.name, year, imported)?;
+        Ok(imported)
+    }
+
+    /// Import NGS rushing stats
+    fn import_ngs_rushing(&mut self, year: i32) -> Result<usize> {
+        info!("  Importing NGS rushing for {}...", year);
+        let imported = self.import_stage(
+            &datasets::NGS_RUSHING,
+            year,
+            |pipeline, row| Ok(pipeline.config.includes_season_type(&row.season_type)),
+            Self::upsert_ngs_rushing_batch,
+        )?;
+        self.mark_ngs_progress(datasets::NGS_RUSHING.name, year, imported)?;
+        Ok(imported)
+    }
+
+    /// Import NGS receiving stats
+    fn import_ngs_receiving(&mut self, year: i32) -> Result<usize> {
+        info!("  Importing NGS receiving for {}...", year);
+        let imported = self.import_stage(
+            &datasets::NGS_RECEIVING,
+            year,
+            |pipeline, row| Ok(pipeline.config.includes_season_type(&row.season_type)),
+            Self::upsert_ngs_receiving_batch,
+        )?;
+        self.mark_ngs_progress(datasets::NGS_RECEIVING.name, year, imported)?;
+        Ok(imported)
+    }
+
+    fn mark_ngs_progress(&mut self, data_type: &'static str, year: i32, imported: usize) -> Result<()> {
+        if !self.config.dry_run {
+            self.mark_completed(year, data_type, imported)?;
+        }
+        Ok(())
+    }
+
+    /// Resolve NGS rows to player IDs, dropping players we don't have
+    fn ngs_player_ids(&mut self, gsis_ids: Vec<Option<&String>>) -> Result<Vec<Option<Uuid>>> {
+        gsis_ids
+            .into_iter()
+            .map(|id| match id {
+                Some(id) => self.database.get_player_id_by_nfl_id(id),
+                None => Ok(None),
+            })
+            .collect()
+    }
+
+    fn upsert_ngs_passing_batch(&mut self, rows: &[NGSPassing]) -> Result<()> {
+        if self.config.dry_run {
+            return Ok(());
+        }
+
+        let player_ids = self.ngs_player_ids(rows.iter().map(|r| r.player_gsis_id.as_ref()).collect())?;
+        let client = self.database.get_client();
+        let mut tx = client.transaction()?;
+
+        for (row, player_id) in rows.iter().zip(player_ids) {
+            let Some(player_id) = player_id else { continue };
+            // Week 0 is the season aggregate row
+            let week = row.week.unwrap_or(0);
+
+            tx.execute(
+                "INSERT INTO advanced_stats (player_id, season, week, stat_type,
+                     avg_time_to_throw, avg_completed_air_yards, avg_intended_air_yards, avg_air_yards_differential,
+                     max_completed_air_distance, avg_air_yards_to_sticks, attempts, pass_yards, pass_touchdowns,
+                     interceptions, passer_rating, completions, completion_percentage,
+                     expected_completion_percentage, completion_percentage_above_expectation)
+                 VALUES ($1, $2, $3, 'passing', $4::float8, $5::float8, $6::float8, $7::float8, $8, $9::float8,
+                     $10, $11, $12, $13, $14::float8, $15, $16::float8, $17::float8, $18::float8)
+                 ON CONFLICT (player_id, season, week, stat_type) DO UPDATE SET
+                     avg_time_to_throw = EXCLUDED.avg_time_to_throw,
+                     avg_completed_air_yards = EXCLUDED.avg_completed_air_yards,
+                     avg_intended_air_yards = EXCLUDED.avg_intended_air_yards,
+                     avg_air_yards_differential = EXCLUDED.avg_air_yards_differential,
+                     max_completed_air_distance = EXCLUDED.max_completed_air_distance,
+                     avg_air_yards_to_sticks = EXCLUDED.avg_air_yards_to_sticks,
+                     attempts = EXCLUDED.attempts,
+                     pass_yards = EXCLUDED.pass_yards,
+                     pass_touchdowns = EXCLUDED.pass_touchdowns,
+                     interceptions = EXCLUDED.interceptions,
+                     passer_rating = EXCLUDED.passer_rating,
+                     completions = EXCLUDED.completions,
+                     completion_percentage = EXCLUDED.completion_percentage,
+                     expected_completion_percentage = EXCLUDED.expected_completion_percentage,
+                     completion_percentage_above_expectation = EXCLUDED.completion_percentage_above_expectation,
+                     updated_at = NOW()",
+                &[
+                    &player_id,
+                    &row.season,
+                    &week,
+                    &row.avg_time_to_throw,
+                    &row.avg_completed_air_yards,
+                    &row.avg_intended_air_yards,
+                    &row.avg_air_yards_differential,
+                    &row.max_completed_air_distance,
+                    &row.avg_air_yards_to_sticks,
+                    &row.attempts,
+                    &row.pass_yards,
+                    &row.pass_touchdowns,
+                    &row.interceptions,
+                    &row.passer_rating,
+                    &row.completions,
+                    &row.completion_percentage,
+                    &row.expected_completion_percentage,
+                    &row.completion_percentage_above_expectation,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn upsert_ngs_rushing_batch(&mut self, rows: &[NGSRushing]) -> Result<()> {
+        if self.config.dry_run {
+            return Ok(());
+        }
+
+        let player_ids = self.ngs_player_ids(rows.iter().map(|r| r.player_gsis_id.as_ref()).collect())?;
+        let client = self.database.get_client();
+        let mut tx = client.transaction()?;
+
+        for (row, player_id) in rows.iter().zip(player_ids) {
+            let Some(player_id) = player_id else { continue };
+            let week = row.week.unwrap_or(0);
+
+            tx.execute(
+                "INSERT INTO advanced_stats (player_id, season, week, stat_type,
+                     efficiency, percent_attempts_gte_eight_defenders, avg_time_to_los, rush_attempts, rush_yards,
+                     expected_rush_yards, rush_yards_over_expected, avg_rush_yards, rush_touchdowns)
+                 VALUES ($1, $2, $3, 'rushing', $4::float8, $5::float8, $6::float8, $7, $8,
+                     round($9::float8), round($10::float8), $11::float8, $12)
+                 ON CONFLICT (player_id, season, week, stat_type) DO UPDATE SET
+                     efficiency = EXCLUDED.efficiency,
+                     percent_attempts_gte_eight_defenders = EXCLUDED.percent_attempts_gte_eight_defenders,
+                     avg_time_to_los = EXCLUDED.avg_time_to_los,
+                     rush_attempts = EXCLUDED.rush_attempts,
+                     rush_yards = EXCLUDED.rush_yards,
+                     expected_rush_yards = EXCLUDED.expected_rush_yards,
+                     rush_yards_over_expected = EXCLUDED.rush_yards_over_expected,
+                     avg_rush_yards = EXCLUDED.avg_rush_yards,
+                     rush_touchdowns = EXCLUDED.rush_touchdowns,
+                     updated_at = NOW()",
+                &[
+                    &player_id,
+                    &row.season,
+                    &week,
+                    &row.efficiency,
+                    &row.percent_attempts_gte_eight_defenders,
+                    &row.avg_time_to_los,
+                    &row.rush_attempts,
+                    &row.rush_yards,
+                    &row.expected_rush_yards,
+                    &row.rush_yards_over_expected,
+                    &row.avg_rush_yards,
+                    &row.rush_touchdowns,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn upsert_ngs_receiving_batch(&mut self, rows: &[NGSReceiving]) -> Result<()> {
+        if self.config.dry_run {
+            return Ok(());
+        }
+
+        let player_ids = self.ngs_player_ids(rows.iter().map(|r| r.player_gsis_id.as_ref()).collect())?;
+        let client = self.database.get_client();
+        let mut tx = client.transaction()?;
+
+        for (row, player_id) in rows.iter().zip(player_ids) {
+            let Some(player_id) = player_id else { continue };
+            let week = row.week.unwrap_or(0);
+
+            tx.execute(
+                "INSERT INTO advanced_stats (player_id, season, week, stat_type,
+                     avg_cushion, avg_separation, avg_intended_air_yards_receiving, percent_share_of_intended_air_yards,
+                     receptions, targets, catch_percentage, yards, rec_touchdowns,
+                     avg_yac, avg_expected_yac, avg_yac_above_expectation)
+                 VALUES ($1, $2, $3, 'receiving', $4::float8, $5::float8, $6::float8, $7::float8,
+                     $8, $9, $10::float8, $11, $12, $13::float8, $14::float8, $15::float8)
+                 ON CONFLICT (player_id, season, week, stat_type) DO UPDATE SET
+                     avg_cushion = EXCLUDED.avg_cushion,
+                     avg_separation = EXCLUDED.avg_separation,
+                     avg_intended_air_yards_receiving = EXCLUDED.avg_intended_air_yards_receiving,
+                     percent_share_of_intended_air_yards = EXCLUDED.percent_share_of_intended_air_yards,
+                     receptions = EXCLUDED.receptions,
+                     targets = EXCLUDED.targets,
+                     catch_percentage = EXCLUDED.catch_percentage,
+                     yards = EXCLUDED.yards,
+                     rec_touchdowns = EXCLUDED.rec_touchdowns,
+                     avg_yac = EXCLUDED.avg_yac,
+                     avg_expected_yac = EXCLUDED.avg_expected_yac,
+                     avg_yac_above_expectation = EXCLUDED.avg_yac_above_expectation,
+                     updated_at = NOW()",
+                &[
+                    &player_id,
+                    &row.season,
+                    &week,
+                    &row.avg_cushion,
+                    &row.avg_separation,
+                    &row.avg_intended_air_yards,
+                    &row.percent_share_of_intended_air_yards,
+                    &row.receptions,
+                    &row.targets,
+                    &row.catch_percentage,
+                    &row.yards,
+                    &row.rec_touchdowns,
+                    &row.avg_yac,
+                    &row.avg_expected_yac,
+                    &row.avg_yac_above_expectation,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Run incremental update
+    pub fn run_update(&mut self) -> Result<()> {
+        info!("🔄 Running incremental update...");
+
+        let current_year = self.current_week()?.season;
+
+        // Final scores for the season so far, so finished weeks show up below
+        match self.import_schedule(current_year) {
+            Ok(count) => info!("  ✅ Schedule: {} games", count),
+            Err(e) => self.stage_failed("Schedule", e),
+        }
+
+        // Without a schedule we can't tell which weeks finished, so refresh the whole season
+        if self.database.count_games(current_year)? == 0 {
+            warn!("No games found for {}, running full season import", current_year);
+            return self.import_year(current_year);
+        }
+
+        // Injury reports publish midweek, before the week's games finish
+        match self.import_injuries(current_year) {
+            Ok(count) => info!("  ✅ Injuries: {} reports", count),
+            Err(e) => self.stage_failed("Injuries", e),
+        }
+
+        let weeks = self.database.get_weeks_needing_update(current_year, "player_stats")?;
+        if weeks.is_empty() {
+            info!("  ✅ No newly finished weeks for {}", current_year);
+            return Ok(());
+        }
+
+        let week_list: Vec<String> = weeks.iter().map(|w| w.week.to_string()).collect();
+        info!("  📆 Weeks to refresh: {}", week_list.join(", "));
+
+        // Rosters change during the season, refresh them before stats
+        match self.import_rosters(current_year) {
+            Ok(count) => info!("  ✅ Rosters: {} players", count),
+            Err(e) => self.stage_failed("Rosters", e),
+        }
+        match self.import_roster_moves(current_year) {
+            Ok(count) => info!("  ✅ Roster Moves: {} moves", count),
+            Err(e) => self.stage_failed("Roster Moves", e),
+        }
+
+        match self.import_player_stats_weeks(current_year, Some(&weeks)) {
+            Ok(count) => info!("  ✅ Player Stats: {} records", count),
+            Err(e) if e.downcast_ref::<Unchanged>().is_some() => self.stage_failed("Player Stats", e),
+            Err(e) => return Err(e),
+        }
+
+        // Depth charts and snap counts feed the position battle detector
+        match self.import_depth_charts(current_year) {
+            Ok(count) => info!("  ✅ Depth Charts: {} entries", count),
+            Err(e) => self.stage_failed("Depth Charts", e),
+        }
+        match self.import_snap_counts(current_year) {
+            Ok(count) => info!("  ✅ Snap Counts: {} records", count),
+            Err(e) => self.stage_failed("Snap Counts", e),
+        }
+
+        self.refresh_splits(current_year);
+        self.refresh_fantasy_points(current_year);
+        self.refresh_aggregates(current_year);
+        self.refresh_role_changes(current_year);
+        self.refresh_usage_alerts(current_year);
+        self.refresh_team_ratings(current_year);
+        self.refresh_standings(current_year);
+        self.refresh_season_luck(current_year);
+        self.prune_history();
+
+        if self.config.sleeper_league.is_some() {
+            if let Err(e) = self.import_sleeper_league() {
+                self.stage_failed("Sleeper League", e);
+            }
+        }
+
+        if self.config.sheets_config.is_some() {
+            if let Err(e) = self.push_sheets(current_year) {
+                warn!("  ⚠️  Google Sheets export failed: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Push the derived tables listed in `--sheets-config` to Google Sheets
+    pub fn push_sheets(&mut self, season: i32) -> Result<()> {
+        let path = self
+            .config
+            .sheets_config
+            .clone()
+            .ok_or_else(|| anyhow!("--sheets-config (or PIPELINE_SHEETS_CONFIG) is required"))?;
+        let config = SheetsConfig::load(&path)?;
+
+        if self.config.dry_run {
+            info!("  🔍 Dry run: would push {} tabs to Google Sheets", config.exports.len());
+            return Ok(());
+        }
+
+        let footer = self.export_footer(&[&sources::NFLVERSE]);
+        let pushed = sheets::push(self.database.get_client(), &config, season, &footer)?;
+        info!("  ✅ Google Sheets: {} tabs updated for {}", pushed, season);
+        Ok(())
+    }
+
+    /// Enable, prune or disable point-in-time history for players and games
+    pub fn run_history(&mut self) -> Result<()> {
+        let client = self.database.get_client();
+        match self.config.action.as_deref().unwrap_or("enable") {
+            "enable" => {
+                history::enable(client)?;
+                info!("  ✅ History enabled for: {}", history::TRACKED_TABLES.join(", "));
+            }
+            "prune" => {
+                let pruned = history::prune(client, self.config.history_retain_days)?;
+                info!("  ✅ History pruned: {} versions older than {} days", pruned, self.config.history_retain_days);
+            }
+            "disable" => {
+                history::disable(client)?;
+                info!("  ✅ History disabled and history tables dropped");
+            }
+            action => return Err(anyhow!("Invalid --action for history: {} (expected enable, prune or disable)", action)),
+        }
+        Ok(())
+    }
+
+    /// `--mode integrity`: orphaned stat lines, players without a team and games
+    /// referencing missing teams, with `--action fix` to repair what can be
+    pub fn run_integrity(&mut self) -> Result<()> {
+        let violations = integrity::check(self.database.get_client())?;
+        if self.config.format.as_deref() == Some("json") {
+            println!("{}", serde_json::to_string(&violations)?);
+        } else {
+            integrity::log_report(&violations);
+        }
+
+        match self.config.action.as_deref().unwrap_or("check") {
+            "check" => {}
+            "fix" if self.config.dry_run => info!("  🧪 Dry run: nothing repaired"),
+            "fix" => {
+                let repairs = integrity::repair(self.database.get_client())?;
+                info!(
+                    "  ✅ Repaired: {} orphaned stat lines deleted, {} players and {} games re-resolved",
+                    repairs.deleted_game_stats, repairs.resolved_players, repairs.resolved_games
+                );
+                if !repairs.unresolved_games.is_empty() {
+                    warn!(
+                        "  ⚠️  {} games name no known team (re-import their schedules): {}",
+                        repairs.unresolved_games.len(),
+                        repairs.unresolved_games.join(", ")
+                    );
+                }
+            }
+            action => return Err(anyhow!("Invalid --action for integrity: {} (expected check or fix)", action)),
+        }
+        Ok(())
+    }
+
+    /// `--mode diff`: download `--year`'s rosters, schedule and player stats (or
+    /// just `--dataset`) and report what importing them would insert, update or
+    /// leave alone, without writing anything
+    pub fn run_diff(&mut self) -> Result<()> {
+        const DIFFABLE: [&str; 3] = [datasets::ROSTERS.name, datasets::SCHEDULES.name, datasets::PLAYER_STATS.name];
+        let only = self.config.dataset.clone();
+        if let Some(dataset) = only.as_deref().filter(|dataset| !DIFFABLE.contains(dataset)) {
+            return Err(anyhow!("Cannot diff {}: expected one of {}", dataset, DIFFABLE.join(", ")));
+        }
+        let wanted = |name: &str| only.as_deref().is_none_or(|dataset| dataset == name);
+
+        let year = self.config.year;
+        let settings = row_hashes::merge_settings(&self.config.precedence, self.nflverse_source());
+        let mut diffs = Vec::new();
+        if wanted(datasets::ROSTERS.name) {
+            diffs.push(self.diff_dataset(&datasets::ROSTERS, year, |_, _| true, |client, players| diff::players(client, players, &settings))?);
+        }
+        if wanted(datasets::SCHEDULES.name) {
+            diffs.push(self.diff_dataset(
+                &datasets::SCHEDULES,
+                year,
+                |config, game| config.includes_season_type(transformer::season_type_for_game_type(&game.game_type)),
+                |client, games| diff::games(client, games, &settings),
+            )?);
+        }
+        if wanted(datasets::PLAYER_STATS.name) {
+            diffs.push(self.diff_dataset(
+                &datasets::PLAYER_STATS,
+                year,
+                |config, stat| config.includes_season_type(&stat.season_type),
+                diff::stats,
+            )?);
+        }
+
+        if self.config.format.as_deref() == Some("json") {
+            println!("{}", serde_json::to_string(&diffs)?);
+        }
+        for found in &diffs {
+            let counts = found.counts;
+            info!(
+                "  📋 {} {} → {}: {} to insert, {} to update, {} unchanged",
+                found.dataset, year, found.table, counts.inserted, counts.updated, counts.unchanged
+            );
+            if counts.unresolved > 0 {
+                warn!("    ⚠️  {} stat lines name players not in players yet (import rosters first)", counts.unresolved);
+            }
+            if found.failed > 0 {
+                warn!("    ⚠️  {} of {} source rows failed to parse", found.failed, found.rows);
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse a season of `dataset` in batches and compare the rows `accept` keeps
+    /// with the database
+    fn diff_dataset<T: DeserializeOwned>(
+        &mut self,
+        dataset: &Dataset<T>,
+        year: i32,
+        accept: impl Fn(&Config, &T) -> bool,
+        compare: impl Fn(&mut Client, &[T]) -> Result<diff::Counts>,
+    ) -> Result<DatasetDiff> {
+        let (header, records) = self.source.records(&dataset.untyped(), year, self.config.source_format)?;
+        let mut found = DatasetDiff {
+            dataset: dataset.name,
+            table: dataset.target_table,
+            rows: 0,
+            failed: 0,
+            counts: diff::Counts::default(),
+        };
+        let mut batch = Vec::new();
+        for record in records {
+            found.rows += 1;
+            let row = record.and_then(|record| Ok(dataset.dialect.deserialize::<T>(&record, &header)?));
+            match row {
+                Ok(row) if accept(&self.config, &row) => batch.push(row),
+                Ok(_) => {}
+                Err(e) => {
+                    found.failed += 1;
+                    debug!("Failed to parse {} row: {}", dataset.name, e);
+                }
+            }
+            if batch.len() >= self.config.batch_size {
+                found.counts += compare(self.database.get_client(), &batch)?;
+                batch.clear();
+            }
+        }
+        found.counts += compare(self.database.get_client(), &batch)?;
+        Ok(found)
+    }
+
+    /// `--mode serve`: the read API on `--addr` until SIGTERM or SIGINT, with
+    /// requests sharing this pipeline's connection pool and live score updates
+    /// pushed over the `/live` WebSocket
+    pub fn run_serve(&mut self) -> Result<()> {
+        api::serve(self.database.pool().clone(), &self.config.database_url, &self.config.api_addr)
+    }
+
+    /// `--mode api-keys`: issue a key for serve mode (printed once, only its
+    /// hash is stored), list keys, or revoke one by `--key-id`
+    pub fn run_api_keys(&mut self) -> Result<()> {
+        let client = self.database.get_client();
+        match self.config.action.as_deref().unwrap_or("list") {
+            "create" => {
+                let name = self.config.key_name.as_deref().ok_or_else(|| anyhow!("--action create needs --name"))?;
+                let (id, key) = api_keys::create(client, name, self.config.key_rate_limit)?;
+                info!("  ✅ Key {} for {} ({} requests per minute); it won't be shown again:", id, name, self.config.key_rate_limit);
+                println!("{}", key);
+            }
+            "list" => {
+                for key in api_keys::list(client)? {
+                    let state = match key.revoked_at {
+                        Some(revoked) => format!("revoked {}", revoked.format("%Y-%m-%d")),
+                        None => "active".to_string(),
+                    };
+                    info!(
+                        "  {:>4}  {:<24} {:>5}/min  created {}  {}",
+                        key.id,
+                        key.name,
+                        key.per_minute,
+                        key.created_at.format("%Y-%m-%d"),
+                        state
+                    );
+                }
+            }
+            "revoke" => {
+                let id = self.config.key_id.ok_or_else(|| anyhow!("--action revoke needs --key-id"))?;
+                if !api_keys::revoke(client, id)? {
+                    return Err(anyhow!("No active API key with id {}", id));
+                }
+                info!("  ✅ Key {} revoked; serve mode stops accepting it within a minute", id);
+            }
+            action => return Err(anyhow!("Invalid --action for api-keys: {} (expected create, list or revoke)", action)),
+        }
+        Ok(())
+    }
+
+    /// `--mode export`: write `--entity` as CSV or JSONL (`--format`) to a file
+    /// under `--out` (or stdout with `--out -`), optionally limited to
+    /// `--season`, `--week` and `--team`
+    pub fn run_export(&mut self) -> Result<u64> {
+        let entity = self.config.entity.clone().ok_or_else(|| anyhow!("--mode export requires --entity (e.g. --entity game_stats)"))?;
+        let format = export::Format::parse(self.config.format.as_deref().unwrap_or("csv"))?;
+        let filters = export::Filters { season: self.config.season, week: self.config.week, team: self.config.team.clone() };
+
+        if self.config.out_dir == "-" {
+            let stdout = std::io::stdout();
+            let mut out = stdout.lock();
+            let rows = export::export(self.database.get_client(), &entity, &filters, format, &mut out)?;
+            info!("  ✅ {} {} rows written to stdout", rows, entity);
+            return Ok(rows);
+        }
+        let path = sink::output_path(&self.config.out_dir, &export::file_name(&entity, &filters, format));
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = std::fs::File::create(&path).with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut out = std::io::BufWriter::new(file);
+        let rows = export::export(self.database.get_client(), &entity, &filters, format, &mut out)?;
+        info!("  ✅ {} {} rows written to {}", rows, entity, path.display());
+        Ok(rows)
+    }
+
+    /// `--mode purge`: delete `--year` of `--dataset` (or `all`) and its import
+    /// progress so it can be re-imported cleanly
+    pub fn run_purge(&mut self) -> Result<()> {
+        let dataset = self.config.dataset.clone().ok_or_else(|| anyhow!("--mode purge needs --dataset (e.g. player_stats, or all)"))?;
+        let purged = purge::purge(self.database.get_client(), self.config.year, &dataset, self.config.dry_run)?;
+        if self.config.format.as_deref() == Some("json") {
+            println!("{}", serde_json::to_string(&purged)?);
+        }
+        let verb = if self.config.dry_run { "would delete" } else { "deleted" };
+        for found in &purged {
+            info!(
+                "  🗑️  {} {}: {} {} rows from {}, {} progress and {} lineage rows",
+                found.dataset, self.config.year, verb, found.rows, found.table, found.progress, found.lineage
+            );
+        }
+        if self.config.dry_run {
+            info!("  🧪 Dry run: rolled back");
+        }
+        Ok(())
+    }
+
+    /// `--mode views`: install, drop or report on the convenience view pack
+    pub fn run_views(&mut self) -> Result<()> {
+        let client = self.database.get_client();
+        match self.config.action.as_deref().unwrap_or("install") {
+            "install" if self.config.dry_run => {
+                let names: Vec<&str> = views::VIEWS.iter().map(|view| view.name).collect();
+                info!("  🧪 Dry run: would install {}", names.join(", "));
+            }
+            "install" => {
+                let count = views::install(client)?;
+                info!("  ✅ {} views installed", count);
+            }
+            "drop" => {
+                let count = views::drop(client)?;
+                info!("  ✅ {} views dropped", count);
+            }
+            "status" => {
+                for (view, state) in views::status(client)? {
+                    info!("  {:<18} {:<9} (needs migration {})", view.name, state.as_str(), view.requires);
+                }
+            }
+            action => return Err(anyhow!("Invalid --action for views: {} (expected install, drop or status)", action)),
+        }
+        Ok(())
+    }
+
+    /// Drop expired row history and change log entries after an update (history
+    /// is a no-op unless enabled)
+    fn prune_history(&mut self) {
+        if self.config.dry_run {
+            return;
+        }
+
+        let client = self.database.get_client();
+        match change_log::prune(client, self.config.history_retain_days) {
+            Ok(0) => {}
+            Ok(pruned) => info!("  🧹 Change log: pruned {} expired changes", pruned),
+            Err(e) => warn!("  ⚠️  Change log prune failed: {}", e),
+        }
+        let result = history::is_enabled(client).and_then(|enabled| {
+            if enabled {
+                history::prune(client, self.config.history_retain_days)
+            } else {
+                Ok(0)
+            }
+        });
+        match result {
+            Ok(0) => {}
+            Ok(pruned) => info!("  🧹 History: pruned {} expired versions", pruned),
+            Err(e) => warn!("  ⚠️  History prune failed: {}", e),
+        }
+    }
+
+    /// Current NFL season and week, from the stored schedule and today's date
+    pub fn current_week(&mut self) -> Result<CurrentWeek> {
+        let today = chrono::Local::now().date_naive();
+        let season = current_week::season_for_date(today);
+        let windows = self.database.get_week_windows(season)?;
+        Ok(current_week::resolve(season, &windows, today))
+    }
+
+    /// Print the current season and week for downstream services
+    pub fn print_current_week(&mut self) -> Result<()> {
+        let current = self.current_week()?;
+        match self.config.format.as_deref() {
+            Some("json") => println!("{}", serde_json::to_string(&current)?),
+            _ => println!(
+                "{} {} week {}{}",
+                current.season,
+                current.season_type,
+                current.week,
+                if current.in_season { "" } else { " (out of season)" }
+            ),
+        }
+        Ok(())
+    }
+
+    /// `--mode status`: which seasons of `--start-year`..`--end-year` each
+    /// dataset has imported, failed or is missing, from `import_progress`
+    pub fn print_status(&mut self) -> Result<()> {
+        let (start, end) = (self.config.start_year, self.config.end_year);
+        let grid = status::grid(self.database.get_client(), start, end, self.config.include_college)?;
+        match self.config.format.as_deref() {
+            Some("json") => println!("{}", serde_json::to_string(&grid.concat())?),
+            _ => print!("{}", status::render(&grid)),
+        }
+        Ok(())
+    }
 
-        Ok(DataPipeline {
-            config,
-            downloader,
-            database,
-        })
+    /// Refresh the current season's injury reports
+    pub fn run_injuries(&mut self) -> Result<()> {
+        let current_year = self.current_week()?.season;
+        match self.import_injuries(current_year) {
+            Ok(count) => info!("  ✅ Injuries: {} reports", count),
+            Err(e) if e.downcast_ref::<Unchanged>().is_some() => self.stage_failed("Injuries", e),
+            Err(e) => return Err(e),
+        }
+        Ok(())
     }
 
-    /// Run full import for all years
-    pub fn run_full_import(&mut self) -> Result<()> {
-        let pb = ProgressBar::new((self.config.end_year - self.config.start_year + 1) as u64);
-        pb.set_style(
-            ProgressStyle::default_bar()
-                .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} {msg}")
-                .unwrap(),
-        );
+    /// Re-import the previous two weeks' stats and log official stat corrections.
+    ///
+    /// Corrections land on Thursdays, so the sweep only runs then unless `--force` is set.
+    pub fn run_correction_sweep(&mut self) -> Result<()> {
+        let today = chrono::Local::now();
+        if today.weekday() != Weekday::Thu && !self.config.force {
+            info!("  ⏭️  Correction sweep only runs on Thursdays (use --force to override)");
+            return Ok(());
+        }
 
-        for year in self.config.start_year..=self.config.end_year {
-            pb.set_message(format!("Importing {}", year));
+        let season = self.current_week()?.season;
+        let latest_week = match self.database.get_latest_stat_week(season)? {
+            Some(week) => week,
+            None => {
+                info!("  ⏭️  No stats imported for {} yet, nothing to correct", season);
+                return Ok(());
+            }
+        };
+        let weeks = [latest_week - 1, latest_week];
+        info!("🩹 Sweeping stat corrections for {} weeks {}-{}...", season, weeks[0], weeks[1]);
+
+        let mut corrected_rows = 0;
+        let mut corrected_fields = 0;
+
+        self.run_stage(
+            &datasets::PLAYER_STATS,
+            season,
+            |pipeline, stat| {
+                let swept = corrections::SWEPT_SEASON_TYPES.contains(&stat.season_type.as_str())
+                    && pipeline.config.includes_season_type(&stat.season_type);
+                if !swept || !weeks.contains(&stat.week) {
+                    return Ok(false);
+                }
 
-            match self.import_year(year) {
-                Ok(_) => {
-                    pb.inc(1);
+                let player_id = match pipeline.database.get_player_id_by_nfl_id(&stat.player_id)? {
+                    Some(id) => id,
+                    None => return Ok(true),
+                };
+                let stored = match pipeline.database.get_stat_line(player_id, stat.season, stat.week, &stat.season_type)? {
+                    Some(line) => line,
+                    None => return Ok(true),
+                };
+
+                let diffs = corrections::diff_stat_lines(&stored, &StatLine::from_stat(stat));
+                if !diffs.is_empty() {
+                    corrected_rows += 1;
+                    corrected_fields += diffs.len();
                 }
-                Err(e) => {
-                    error!("Failed to import year {}: {}", year, e);
-                    pb.inc(1);
+                for diff in &diffs {
+                    info!(
+                        "  ✏️  {} week {}: {} {:?} → {:?}",
+                        stat.player_display_name.as_deref().unwrap_or(&stat.player_id),
+                        stat.week,
+                        diff.field,
+                        diff.old_value,
+                        diff.new_value
+                    );
+                    if !pipeline.config.dry_run {
+                        pipeline.database.record_correction(player_id, stat.season, stat.week, diff)?;
+                    }
                 }
-            }
-        }
 
-        pb.finish_with_message("Import complete!");
+                Ok(true)
+            },
+            Self::upsert_stats_batch,
+        )?;
+
+        info!(
+            "  ✅ Corrections: {} fields changed across {} stat lines",
+            corrected_fields, corrected_rows
+        );
         Ok(())
     }
 
-    /// Import data for a single year
-    pub fn import_year(&mut self, year: i32) -> Result<()> {
-        info!("📅 Importing data for year {}...", year);
+    /// Report foreign keys without indexes, unused indexes, full-scan-heavy tables
+    /// and slow statements on the target database
+    pub fn report_indexes(&mut self) -> Result<()> {
+        let report = indexes::analyze(self.database.get_client())?;
+        indexes::log_report(&report);
+        Ok(())
+    }
 
-        // 1. Import rosters (players)
-        match self.import_rosters(year) {
-            Ok(count) => info!("  ✅ Rosters: {} players", count),
-            Err(e) => warn!("  ⚠️  Rosters failed: {}", e),
+    /// Write one scoreboard poll into `games` (`--mode live`)
+    pub fn apply_live_scores(&mut self, games: &[LiveGame]) -> Result<()> {
+        let in_progress = games.iter().filter(|game| game.in_progress()).count();
+        if self.config.dry_run {
+            info!("  🧪 Dry run: {} games on the scoreboard, {} in progress", games.len(), in_progress);
+            return Ok(());
         }
 
-        // 2. Import schedule (games) - SKIPPED: Use ESPN API via Go importer instead
-        // NFLverse schedule format is different, easier to use ESPN for schedules
-        info!("  ⏭️  Schedule: Skipping (use Go importer with ESPN API)");
-
-        // 3. Import player stats
-        match self.import_player_stats(year) {
-            Ok(count) => info!("  ✅ Player Stats: {} records", count),
-            Err(e) => warn!("  ⚠️  Player Stats failed: {}", e),
+        let url = self.config.scoreboard_url.clone();
+        self.record_source(sources::SCOREBOARD_DATASET, ALL_SEASONS, &url);
+        let update = live::update_games(self.database.get_client(), games, &self.config.precedence)?;
+        if update.changed > 0 {
+            info!("  📡 {} games updated ({} in progress, {}/{} on the scoreboard matched)",
+                  update.changed, in_progress, update.matched, games.len());
         }
+        if update.matched < games.len() {
+            debug!("{} scoreboard games not in the games table", games.len() - update.matched);
+        }
+        Ok(())
+    }
 
-        // 4. Import Next Gen Stats (2016+)
-        if year >= 2016 {
-            match self.import_ngs_passing(year) {
-                Ok(count) => info!("  ✅ NGS Passing: {} records", count),
-                Err(e) => warn!("  ⚠️  NGS Passing failed: {}", e),
+    /// Merge ESPN's schedule for a season into `games`, then the box scores of
+    /// its finished games and, for the current season, team rosters
+    /// (`--mode espn`, or season imports with `--espn`)
+    pub fn import_espn(&mut self, season: i32) -> Result<()> {
+        let http = reqwest::blocking::Client::builder().timeout(std::time::Duration::from_secs(20)).build()?;
+        let base = self.config.espn_api_url.clone();
+
+        let games = espn::fetch_schedule(&http, &base, season)?;
+        if self.config.dry_run {
+            info!("  🧪 Dry run: {} ESPN games for {}", games.len(), season);
+            return Ok(());
+        }
+        self.record_source(espn::SCHEDULE_DATASET, season, &espn::schedule_url(&base, season));
+        let write = espn::write_schedule(self.database.get_client(), &games, &self.config.precedence)?;
+        self.telemetry.dataset(espn::SCHEDULE_DATASET, write.merged + write.inserted, 0);
+        info!("  ✅ ESPN Schedule: {} games ({} merged, {} new)", games.len(), write.merged, write.inserted);
+
+        if season == self.current_week()?.season {
+            if let Err(e) = self.link_espn_ids() {
+                self.stage_failed("ESPN IDs", e);
+            }
+        }
+        match self.import_espn_box_scores(&http, &base, season, &games) {
+            Ok(count) => info!("  ✅ ESPN Box Scores: {} player lines", count),
+            Err(e) => self.stage_failed("ESPN Box Scores", e),
+        }
+        if season == self.current_week()?.season {
+            match self.import_espn_rosters(&http, &base, season) {
+                Ok(count) => info!("  ✅ ESPN Rosters: {} players updated", count),
+                Err(e) => self.stage_failed("ESPN Rosters", e),
+            }
+        }
+        if self.config.include_news {
+            match self.import_game_news(&http, &base, season, &games) {
+                Ok(count) => info!("  ✅ ESPN Game News: {} articles", count),
+                Err(e) => self.stage_failed("ESPN Game News", e),
+            }
+            if season == self.current_week()?.season {
+                if let Err(e) = self.import_player_news() {
+                    self.stage_failed("Player News", e);
+                }
             }
         }
-
-        info!("✅ Year {} import complete", year);
         Ok(())
     }
 
-    /// Import rosters for a year
-    fn import_rosters(&mut self, year: i32) -> Result<usize> {
-        info!("  [1/4] Importing rosters for {}...", year);
-
-        let csv_data = self.downloader.download_roster(year)?;
-        let mut reader = ReaderBuilder::new()
-            .from_reader(csv_data.as_bytes());
+    /// Injury updates, transactions and other news from `--news-feed`, linked to
+    /// players (`--mode player-news`, and current-season ESPN imports with
+    /// `--with-news`). Tagged athletes no player matches go to the unresolved report.
+    pub fn import_player_news(&mut self) -> Result<()> {
+        let http = reqwest::blocking::Client::builder().timeout(std::time::Duration::from_secs(20)).build()?;
+        let url = self.config.news_feed_url.clone();
 
-        let mut imported = 0;
-        let mut batch = Vec::new();
+        let (format, items) = news::fetch_feed(&http, &url)?;
+        let player_ids = espn::player_ids(self.database.get_client())?;
+        let players = espn_ids::all_players(self.database.get_client())?;
+        let linked = news::link_players(&items, &player_ids, &players);
 
-        for result in reader.deserialize::<RosterPlayer>() {
-            match result {
-                Ok(player) => {
-                    batch.push(player);
+        if self.config.dry_run {
+            for link in &linked.links {
+                info!("  🧪 {} → {} ({}, {:.2})", items[link.item].headline, link.player_id, link.method, link.confidence);
+            }
+        } else {
+            self.record_source(format.dataset(), self.config.year, &url);
+            let written = news::write_player_news(self.database.get_client(), format, &url, &items, &linked.links)?;
+            let unresolved: Vec<Unresolved> = linked
+                .unlinked_athletes
+                .iter()
+                .map(|(espn_id, name)| Unresolved::espn_athlete(espn_id, name.as_deref(), format.dataset(), self.config.year))
+                .collect();
+            resolution::record(self.database.get_client(), &unresolved);
+            self.telemetry.dataset(format.dataset(), written, 0);
+        }
+        let linked_items: HashSet<usize> = linked.links.iter().map(|link| link.item).collect();
+        info!(
+            "  ✅ Player News: {} {} items, {} linked to {} players ({} tagged athletes unmatched)",
+            items.len(),
+            format.source(),
+            linked_items.len(),
+            linked.links.iter().map(|link| link.player_id).collect::<HashSet<_>>().len(),
+            linked.unlinked_athletes.len()
+        );
+        Ok(())
+    }
 
-                    if batch.len() >= self.config.batch_size {
-                        self.upsert_players_batch(&batch)?;
-                        imported += batch.len();
-                        batch.clear();
-                    }
-                }
-                Err(e) => warn!("Failed to parse roster row: {}", e),
+    /// `--mode odds` and each daemon cycle in season: capture the current lines of
+    /// upcoming games, recording a row per bookmaker whenever a line moved
+    pub fn import_odds(&mut self) -> Result<()> {
+        let api_key = self
+            .config
+            .odds_api_key
+            .clone()
+            .ok_or_else(|| anyhow!("Odds polling needs an API key: set ODDS_API_KEY"))?;
+        let http = reqwest::blocking::Client::builder().timeout(std::time::Duration::from_secs(20)).build()?;
+        let base = self.config.odds_api_url.clone();
+
+        let odds = odds::fetch_odds(&http, &odds::odds_url(&base, &api_key, &self.config.odds_bookmakers))?;
+        let games: HashSet<&str> = odds.iter().map(|lines| lines.event_id.as_str()).collect();
+        if self.config.dry_run {
+            for lines in &odds {
+                info!(
+                    "  🧪 {} @ {} ({}): spread {:?}, total {:?}, moneyline {:?}/{:?}",
+                    lines.away_team, lines.home_team, lines.bookmaker, lines.spread_line, lines.total_line,
+                    lines.away_moneyline, lines.home_moneyline
+                );
             }
+            info!("  ✅ Betting Odds: {} lines on {} games (dry run)", odds.len(), games.len());
+            return Ok(());
         }
 
-        // Insert remaining
-        if !batch.is_empty() {
-            self.upsert_players_batch(&batch)?;
-            imported += batch.len();
+        self.record_source(odds::DATASET, self.config.year, &base);
+        let write = odds::write_odds(self.database.get_client(), &odds)?;
+        self.telemetry.dataset(odds::DATASET, write.recorded, odds.len() - write.matched);
+        if write.matched < odds.len() {
+            warn!("  ⚠️  {} bookmaker lines did not match a scheduled game", odds.len() - write.matched);
         }
+        info!(
+            "  ✅ Betting Odds: {} lines on {} games, {} moved since the last poll",
+            odds.len(),
+            games.len(),
+            write.recorded
+        );
+        Ok(())
+    }
 
-        if !self.config.dry_run {
-            self.database.mark_progress(year, "rosters", "completed", imported as i32)?;
+    /// `--mode sleeper` and incremental updates with `--league`: a Sleeper league's
+    /// rosters and matchups, linked to players by the roster files' Sleeper IDs
+    pub fn import_sleeper_league(&mut self) -> Result<()> {
+        let league_id = self
+            .config
+            .sleeper_league
+            .clone()
+            .ok_or_else(|| anyhow!("--mode sleeper needs --league (or PIPELINE_SLEEPER_LEAGUE)"))?;
+        let http = reqwest::blocking::Client::builder().timeout(std::time::Duration::from_secs(20)).build()?;
+        let base = self.config.sleeper_api_url.clone();
+
+        let state = sleeper::fetch_state(&http, &base)?;
+        let data = sleeper::fetch_league(&http, &base, &league_id, &state)?;
+        if self.config.dry_run {
+            info!(
+                "  🧪 {} ({}): {} rosters, {} weeks of matchups",
+                data.league.name,
+                data.league.season,
+                data.rosters.len(),
+                data.matchups.len()
+            );
+            return Ok(());
         }
 
-        Ok(imported)
+        let url = format!("{}/league/{}", base, league_id);
+        self.record_source(sleeper::DATASET, data.league.season.parse().unwrap_or(self.config.year), &url);
+        let links = sleeper::Links::load(self.database.get_client())?;
+        let write = sleeper::write_league(self.database.get_client(), &data, &links)?;
+        self.telemetry.dataset(sleeper::DATASET, write.roster_players + write.matchups, 0);
+        if write.unlinked > 0 {
+            warn!("  ⚠️  {} rostered Sleeper players have no player with their Sleeper ID (import their roster season first)", write.unlinked);
+        }
+        info!(
+            "  ✅ Sleeper League: {} ({}), {} rosters with {} players, {} matchups",
+            data.league.name,
+            data.league.season,
+            write.rosters,
+            write.roster_players,
+            write.matchups
+        );
+        Ok(())
     }
 
-    /// Import schedule for a year
-    fn import_schedule(&mut self, year: i32) -> Result<usize> {
-        info!("  [2/4] Importing schedule for {}...", year);
+    /// `--mode game-news`: recaps and headlines of a season's finished games
+    pub fn import_espn_game_news(&mut self, season: i32) -> Result<()> {
+        let http = reqwest::blocking::Client::builder().timeout(std::time::Duration::from_secs(20)).build()?;
+        let base = self.config.espn_api_url.clone();
 
-        let csv_data = self.downloader.download_schedule(year)?;
-        let mut reader = ReaderBuilder::new()
-            .from_reader(csv_data.as_bytes());
+        let games = espn::fetch_schedule(&http, &base, season)?;
+        let count = self.import_game_news(&http, &base, season, &games)?;
+        info!("  ✅ ESPN Game News: {} articles", count);
+        Ok(())
+    }
 
-        let mut imported = 0;
-        let mut batch = Vec::new();
+    /// Recaps and related headlines of finished games, linked to the games by
+    /// ESPN ID. Games that already have a recap are skipped unless forced.
+    fn import_game_news(
+        &mut self,
+        http: &reqwest::blocking::Client,
+        base: &str,
+        season: i32,
+        games: &[EspnGame],
+    ) -> Result<usize> {
+        let finished: Vec<&str> = games
+            .iter()
+            .filter(|game| game.status == GameStatus::Final)
+            .map(|game| game.espn_id.as_str())
+            .collect();
+        let pending: Vec<String> = self
+            .database
+            .get_client()
+            .query(
+                "SELECT g.espn_game_id FROM games g
+                 WHERE g.espn_game_id = ANY($1)
+                   AND ($2 OR NOT EXISTS (SELECT 1 FROM game_news n WHERE n.game_id = g.id AND n.kind = 'recap'))
+                 ORDER BY g.game_date",
+                &[&finished, &self.config.force],
+            )?
+            .iter()
+            .map(|row| row.get(0))
+            .collect();
+        if pending.is_empty() {
+            return Ok(0);
+        }
+        if self.config.dry_run {
+            info!("  🧪 Dry run: would fetch news for {} ESPN games", pending.len());
+            return Ok(0);
+        }
 
-        for result in reader.deserialize::<Game>() {
+        self.record_source(news::GAME_NEWS_DATASET, season, &espn::summary_url(base, "{event}"));
+        let (mut written, mut failed) = (0, 0);
+        for espn_game_id in &pending {
+            let result = news::fetch_game_news(http, base, espn_game_id)
+                .and_then(|items| news::write_game_news(self.database.get_client(), espn_game_id, &items));
             match result {
-                Ok(game) => {
-                    // Only import regular season games
-                    if game.game_type == "REG" {
-                        batch.push(game);
-
-                        if batch.len() >= self.config.batch_size {
-                            self.upsert_games_batch(&batch)?;
-                            imported += batch.len();
-                            batch.clear();
-                        }
-                    }
+                Ok(count) => written += count,
+                Err(e) => {
+                    warn!("  ⚠️  ESPN news for game {} failed: {}", espn_game_id, e);
+                    failed += 1;
                 }
-                Err(e) => warn!("Failed to parse schedule row: {}", e),
             }
         }
+        self.telemetry.dataset(news::GAME_NEWS_DATASET, written, failed);
+        Ok(written)
+    }
 
-        // Insert remaining
-        if !batch.is_empty() {
-            self.upsert_games_batch(&batch)?;
-            imported += batch.len();
-        }
+    /// Backfill ESPN IDs of players without one by matching ESPN's athlete list
+    /// on name, birth date, team, position and debut year (`--mode espn-ids`,
+    /// and current-season imports with `--espn`)
+    pub fn link_espn_ids(&mut self) -> Result<()> {
+        let http = reqwest::blocking::Client::builder().timeout(std::time::Duration::from_secs(60)).build()?;
+        let url = self.config.espn_athletes_url.clone();
 
-        if !self.config.dry_run {
-            self.database.mark_progress(year, "schedule", "completed", imported as i32)?;
+        let players = espn_ids::unlinked_players(self.database.get_client())?;
+        if players.is_empty() {
+            info!("  ✅ ESPN IDs: every player is linked");
+            return Ok(());
         }
+        let linked = espn::player_ids(self.database.get_client())?;
+        let athletes: Vec<_> = espn_ids::fetch_athletes(&http, &url)?
+            .into_iter()
+            .filter(|athlete| !linked.contains_key(&athlete.espn_id))
+            .collect();
+        let matches = espn_ids::match_athletes(&players, &athletes, self.config.min_confidence);
 
-        Ok(imported)
+        if self.config.dry_run {
+            for link in &matches.links {
+                info!("  🧪 {} → ESPN {} ({:.2})", link.player_name, link.espn_id, link.confidence);
+            }
+        } else {
+            self.record_source(espn_ids::DATASET, self.config.year, &url);
+            let written = espn_ids::write_links(self.database.get_client(), &matches.links)?;
+            self.telemetry.dataset(espn_ids::DATASET, written, 0);
+        }
+        info!(
+            "  ✅ ESPN IDs: {} of {} players linked ({} ambiguous, {} without a match at {:.2})",
+            matches.links.len(),
+            players.len(),
+            matches.ambiguous,
+            matches.unmatched,
+            self.config.min_confidence
+        );
+        Ok(())
     }
 
-    /// Import player stats for a year
-    fn import_player_stats(&mut self, year: i32) -> Result<usize> {
-        info!("  [3/4] Importing player stats for {}...", year);
-
-        let csv_data = self.downloader.download_player_stats(year)?;
-        let mut reader = ReaderBuilder::new()
-            .from_reader(csv_data.as_bytes());
-
-        let mut imported = 0;
-        let mut batch = Vec::new();
+    /// Box scores of finished games with no stat lines linked yet (all finished
+    /// games with `--force`)
+    fn import_espn_box_scores(
+        &mut self,
+        http: &reqwest::blocking::Client,
+        base: &str,
+        season: i32,
+        games: &[EspnGame],
+    ) -> Result<usize> {
+        let finished: Vec<&str> = games
+            .iter()
+            .filter(|game| game.status == GameStatus::Final)
+            .map(|game| game.espn_id.as_str())
+            .collect();
+        let pending: HashSet<String> = self
+            .database
+            .get_client()
+            .query(
+                "SELECT g.espn_game_id FROM games g
+                 WHERE g.espn_game_id = ANY($1)
+                   AND ($2 OR NOT EXISTS (SELECT 1 FROM game_stats s WHERE s.game_id = g.id))",
+                &[&finished, &self.config.force],
+            )?
+            .iter()
+            .map(|row| row.get(0))
+            .collect();
+        if pending.is_empty() {
+            return Ok(0);
+        }
 
-        for result in reader.deserialize::<PlayerStat>() {
+        self.record_source(espn::BOX_SCORES_DATASET, season, &espn::summary_url(base, "{event}"));
+        let player_ids = espn::player_ids(self.database.get_client())?;
+        let (mut written, mut failed) = (0, 0);
+        let mut unresolved = Vec::new();
+        for game in games.iter().filter(|game| pending.contains(&game.espn_id)) {
+            let result = espn::fetch_box_score(http, base, &game.espn_id).and_then(|lines| {
+                let (count, unlinked) = espn::write_box_score(
+                    self.database.get_client(),
+                    game,
+                    &lines,
+                    &player_ids,
+                    self.config.source_priority,
+                )?;
+                unresolved.extend(unlinked.iter().map(|line| {
+                    Unresolved::espn_athlete(&line.espn_player_id, line.name.as_deref(), espn::BOX_SCORES_DATASET, season)
+                }));
+                Ok(count)
+            });
             match result {
-                Ok(stat) => {
-                    // Only import regular season stats
-                    if stat.season_type == "REG" {
-                        batch.push(stat);
-
-                        if batch.len() >= self.config.batch_size {
-                            self.upsert_stats_batch(&batch)?;
-                            imported += batch.len();
-                            batch.clear();
-                        }
-                    }
+                Ok(count) => written += count,
+                Err(e) => {
+                    warn!("  ⚠️  ESPN box score {} ({} @ {}) failed: {}", game.espn_id, game.away_team, game.home_team, e);
+                    failed += 1;
                 }
-                Err(e) => warn!("Failed to parse stat row: {}", e),
             }
         }
-
-        // Insert remaining
-        if !batch.is_empty() {
-            self.upsert_stats_batch(&batch)?;
-            imported += batch.len();
+        if !unresolved.is_empty() {
+            debug!("{} ESPN box score lines have no linked player", unresolved.len());
+            resolution::record(self.database.get_client(), &unresolved);
         }
+        self.telemetry.dataset(espn::BOX_SCORES_DATASET, written, failed);
+        Ok(written)
+    }
 
-        if !self.config.dry_run {
-            self.database.mark_progress(year, "player_stats", "completed", imported as i32)?;
+    /// Current rosters of every team, merged into the players they're linked to
+    fn import_espn_rosters(&mut self, http: &reqwest::blocking::Client, base: &str, season: i32) -> Result<usize> {
+        let teams: Vec<String> = self
+            .database
+            .get_client()
+            .query("SELECT abbreviation FROM teams ORDER BY abbreviation", &[])?
+            .iter()
+            .map(|row| row.get(0))
+            .collect();
+
+        self.record_source(espn::ROSTERS_DATASET, season, &espn::roster_url(base, "{team}"));
+        let player_ids = espn::player_ids(self.database.get_client())?;
+        let (mut merged, mut failed) = (0, 0);
+        let mut unresolved = Vec::new();
+        for team in &teams {
+            let result = espn::fetch_roster(http, base, team).and_then(|entries| {
+                let (count, unlinked) =
+                    espn::write_roster(self.database.get_client(), &entries, &player_ids, &self.config.precedence)?;
+                unresolved.extend(unlinked.iter().map(|entry| {
+                    Unresolved::espn_athlete(&entry.espn_player_id, entry.name.as_deref(), espn::ROSTERS_DATASET, season)
+                }));
+                Ok(count)
+            });
+            match result {
+                Ok(count) => merged += count,
+                Err(e) => {
+                    warn!("  ⚠️  ESPN roster {} failed: {}", team, e);
+                    failed += 1;
+                }
+            }
         }
-
-        Ok(imported)
+        if !unresolved.is_empty() {
+            resolution::record(self.database.get_client(), &unresolved);
+        }
+        self.telemetry.dataset(espn::ROSTERS_DATASET, merged, failed);
+        Ok(merged)
     }
 
-    /// Import NGS passing stats
-    fn import_ngs_passing(&mut self, year: i32) -> Result<usize> {
-        info!("  [4/4] Importing NGS passing for {}...", year);
+    /// `--mode sources`: licenses and attribution of every source runs have read
+    pub fn report_sources(&mut self) -> Result<()> {
+        let usage = sources::report(self.database.get_client())?;
+        sources::log_report(&usage);
+        Ok(())
+    }
 
-        let csv_data = self.downloader.download_ngs_passing(year)?;
-        // Parsing logic here (similar to above)
+    /// `--mode unresolved`: teams and players imports failed to look up
+    pub fn report_unresolved(&mut self) -> Result<()> {
+        let since_days = self.config.since_days;
+        let references = resolution::report(self.database.get_client(), since_days)?;
+        resolution::log_report(&references, since_days);
+        Ok(())
+    }
 
-        Ok(0) // Placeholder
+    /// `--mode changes`: field-level changes recorded by updates
+    pub fn report_changes(&mut self) -> Result<()> {
+        let table = self.config.change_table.as_deref();
+        if let Some(table) = table.filter(|table| !change_log::TRACKED_TABLES.contains(table)) {
+            return Err(anyhow!("Invalid --table: {} (expected {})", table, change_log::TRACKED_TABLES.join(", ")));
+        }
+        let since_days = self.config.since_days;
+        let changes = change_log::recent(self.database.get_client(), since_days, table)?;
+        change_log::log_report(&changes, since_days);
+        Ok(())
     }
 
-    /// Run incremental update
-    pub fn run_update(&mut self) -> Result<()> {
-        info!("🔄 Running incremental update...");
+    /// `--mode replay`: retry the rows in the `--dead-letter` file through the
+    /// regular parsers and batch writers. Rows that fail again, and rows of
+    /// datasets that can't be replayed, are written to a new file that then
+    /// replaces it, so it ends up holding only what still fails.
+    pub fn replay_dead_letters(&mut self) -> Result<()> {
+        let path = self.config.dead_letter.clone().ok_or_else(|| anyhow!("--mode replay needs --dead-letter <file.jsonl>"))?;
+        let letters = dead_letter::read(&path)?;
+        info!("📮 Replaying {} dead-lettered rows from {}", letters.len(), path);
+
+        let mut retry = DeadLetters::new(&format!("{}.retry", path));
+        let mut groups: BTreeMap<(usize, i32), Vec<DeadLetter>> = BTreeMap::new();
+        let mut kept = Vec::new();
+        for letter in letters {
+            match REPLAYABLE.iter().position(|name| *name == letter.dataset) {
+                Some(order) => groups.entry((order, letter.season)).or_default().push(letter),
+                None => kept.push(letter),
+            }
+        }
+        if !kept.is_empty() {
+            warn!("  ⚠️  {} rows can't be replayed row by row and were kept (re-import their seasons)", kept.len());
+            retry.append(&kept)?;
+        }
+        self.dead_letters = Some(retry);
+
+        for ((order, season), letters) in groups {
+            let dataset = REPLAYABLE[order];
+            let rows = letters.len();
+            self.replaying.insert((dataset, season), letters.clone());
+            match self.replay_stage(dataset, season) {
+                Ok(written) => info!("  ✅ {} ({}): {}/{} rows written", dataset, season, written, rows),
+                Err(e) => {
+                    // Kept whole; writers upsert, so rows that did land are harmless to retry
+                    warn!("  ⚠️  {} ({}) replay failed: {:#}", dataset, season, e);
+                    self.replaying.remove(&(dataset, season));
+                    self.dead_letter_all(&letters)?;
+                }
+            }
+        }
 
-        // Get current year
-        let current_year = chrono::Utc::now().year();
+        let retry = self.dead_letters.take().expect("replay dead letters");
+        if self.config.dry_run {
+            let _ = std::fs::remove_file(retry.path());
+            return Ok(());
+        }
+        let remaining = retry.written();
+        retry.replace(&path)?;
+        if remaining > 0 {
+            warn!("  ⚠️  {} rows still fail, left in {}", remaining, path);
+        } else {
+            info!("  ✅ Every row replayed, {} is now empty", path);
+        }
+        Ok(())
+    }
 
-        // Update current season
-        self.import_year(current_year)?;
+    fn dead_letter_all(&mut self, letters: &[DeadLetter]) -> Result<()> {
+        match &mut self.dead_letters {
+            Some(dead_letters) => dead_letters.append(letters),
+            None => Ok(()),
+        }
+    }
 
-        Ok(())
+    /// Write one dataset's replayed rows for a season with its batch writer
+    fn replay_stage(&mut self, dataset: &str, season: i32) -> Result<usize> {
+        let written = match dataset {
+            d if d == datasets::TEAMS.name => self.run_stage(&datasets::TEAMS, season, |_, _| Ok(true), Self::upsert_teams_batch)?,
+            d if d == datasets::ROSTERS.name => {
+                let written = self.run_stage(&datasets::ROSTERS, season, |_, _| Ok(true), Self::upsert_players_batch)?;
+                // Replayed players must be visible to replayed stats
+                self.database.refresh_cache()?;
+                written
+            }
+            d if d == datasets::SCHEDULES.name => self.run_stage(&datasets::SCHEDULES, season, |_, _| Ok(true), Self::upsert_games_batch)?,
+            d if d == datasets::DRAFT_PICKS.name => {
+                self.run_stage(&datasets::DRAFT_PICKS, season, |_, _| Ok(true), Self::upsert_draft_picks_batch)?
+            }
+            d if d == datasets::COMBINE.name => self.run_stage(&datasets::COMBINE, season, |_, _| Ok(true), Self::upsert_combine_batch)?,
+            d if d == datasets::PLAYER_STATS.name => {
+                self.run_stage(&datasets::PLAYER_STATS, season, |_, _| Ok(true), Self::upsert_stats_batch)?
+            }
+            d if d == datasets::INJURIES.name => self.run_stage(&datasets::INJURIES, season, |_, _| Ok(true), Self::upsert_injuries_batch)?,
+            d if d == datasets::DEPTH_CHARTS.name => {
+                self.run_stage(&datasets::DEPTH_CHARTS, season, |_, _| Ok(true), Self::upsert_depth_charts_batch)?
+            }
+            d if d == datasets::SNAP_COUNTS.name => {
+                self.run_stage(&datasets::SNAP_COUNTS, season, |_, _| Ok(true), Self::upsert_snap_counts_batch)?
+            }
+            d if d == datasets::NGS_PASSING.name => {
+                self.run_stage(&datasets::NGS_PASSING, season, |_, _| Ok(true), Self::upsert_ngs_passing_batch)?
+            }
+            d if d == datasets::NGS_RUSHING.name => {
+                self.run_stage(&datasets::NGS_RUSHING, season, |_, _| Ok(true), Self::upsert_ngs_rushing_batch)?
+            }
+            d if d == datasets::NGS_RECEIVING.name => {
+                self.run_stage(&datasets::NGS_RECEIVING, season, |_, _| Ok(true), Self::upsert_ngs_receiving_batch)?
+            }
+            d if d == datasets::COLLEGE_STATS.name => {
+                self.college_links = self.database.get_player_ids_by_name_college()?;
+                self.run_stage(
+                    &datasets::COLLEGE_STATS,
+                    season,
+                    |pipeline, stat| Ok(pipeline.college_player_id(stat).is_some()),
+                    Self::upsert_college_stats_batch,
+                )?
+            }
+            _ => return Err(anyhow!("{} rows can't be replayed", dataset)),
+        };
+        Ok(written)
     }
 
     /// Validate existing data
     pub fn validate_data(&mut self) -> Result<()> {
         info!("✅ Validating data...");
 
+        let mut audits = Vec::new();
         for year in self.config.start_year..=self.config.end_year {
             let games = self.database.count_games(year)?;
             let stats = self.database.count_game_stats(year)?;
 
             info!("  {} - Games: {}, Stats: {}", year, games, stats);
+            audits.push(completeness::audit(self.database.get_client(), year)?);
         }
 
         let total_players = self.database.count_players()?;
         info!("  Total players: {}", total_players);
 
+        let violations = integrity::check(self.database.get_client())?;
+        let gaps: usize = audits.iter().map(|audit| audit.gaps.len()).sum();
+        if self.config.format.as_deref() == Some("json") {
+            let report = serde_json::json!({ "gaps": gaps, "seasons": audits, "integrity": violations });
+            println!("{}", serde_json::to_string(&report)?);
+            return Ok(());
+        }
+        integrity::log_report(&violations);
+        for audit in audits.iter().filter(|audit| !audit.gaps.is_empty()) {
+            warn!(
+                "  ⚠️  {}: {} gaps ({} of {} regular season games, {} final)",
+                audit.season,
+                audit.gaps.len(),
+                audit.regular_games,
+                audit.expected_regular_games,
+                audit.final_games
+            );
+            for gap in audit.gaps.iter().take(MAX_LOGGED_GAPS) {
+                warn!("    {}", gap.detail);
+            }
+            if audit.gaps.len() > MAX_LOGGED_GAPS {
+                warn!("    ... and {} more (--format json lists them all)", audit.gaps.len() - MAX_LOGGED_GAPS);
+            }
+        }
+        match gaps {
+            0 => info!("  ✅ Every season is complete"),
+            _ => warn!("  ⚠️  {} completeness gaps across {} seasons", gaps, audits.iter().filter(|a| !a.gaps.is_empty()).count()),
+        }
+
         Ok(())
     }
 
-    // Batch upsert methods (placeholder - implement actual SQL)
+    // Batch upsert methods
     fn upsert_players_batch(&mut self, players: &[RosterPlayer]) -> Result<()> {
+        let source = self.nflverse_source();
         if self.config.dry_run {
-            return Ok(());
+            let settings = row_hashes::merge_settings(&self.config.precedence, source);
+            return self.dry_run_batch(
+                "players",
+                players,
+                DataValidator::validate_player,
+                |client, players| diff::players(client, players, &settings),
+                Self::upsert_player,
+            );
         }
 
-        for player in players {
-            self.upsert_player(player)?;
+        match self.config.load_method {
+            LoadMethod::Insert => {
+                let settings = row_hashes::merge_settings(&self.config.precedence, source);
+                let players = row_hashes::changed(self.database.get_client(), "players", players, &settings, !self.config.force)?;
+                self.in_batch_transaction(|pipeline| {
+                    for player in players.iter() {
+                        pipeline.upsert_player(player)?;
+                    }
+                    Ok(())
+                })
+            }
+            method => {
+                let skip_unchanged = !self.config.force;
+                write_players(self.database.get_client(), method, players, &self.config.precedence, source, skip_unchanged)
+            }
         }
-
-        Ok(())
     }
 
     fn upsert_player(&mut self, player: &RosterPlayer) -> Result<()> {
@@ -268,8 +3489,10 @@ impl DataPipeline {
         let team_id = self.database.get_team_id_by_abbr(&team_abbr)?;
 
         let height_inches = player.height.as_ref().and_then(|h| transformer::height_to_inches(h));
-
-        let client = self.database.get_client();
+        let birth_date = player.birth_date.as_deref().and_then(transformer::parse_date);
+        let draft_club = player.draft_club.as_deref().map(transformer::normalize_team_abbr);
+        let source = self.nflverse_source();
+        let row_hash = row_hashes::content_hash(player, &row_hashes::merge_settings(&self.config.precedence, source));
 
         // Convert to explicit types to match PostgreSQL expectations
         let nfl_id: &str = &player.gsis_id;
@@ -278,48 +3501,110 @@ impl DataPipeline {
         let status: &str = player.status.as_deref().unwrap_or("active");
         let college: Option<&str> = player.college.as_deref();
 
-        client.execute(
-            "INSERT INTO players (id, nfl_id, name, position, team_id, jersey_number, height_inches, weight_pounds, college, status, created_at, updated_at)
-             VALUES (uuid_generate_v4(), $1::text, $2::text, $3::text, $4, $5, $6, $7, $8::text, $9::text, NOW(), NOW())
+        // Pedigree fields keep their stored value when a season's roster leaves them blank
+        let sql = format!(
+            "INSERT INTO players (id, nfl_id, name, position, team_id, jersey_number, height_inches, weight_pounds, college, status,
+                 birth_date, years_pro, entry_year, rookie_year, draft_club, draft_year, draft_pick, headshot_url, provenance, row_hash, created_at, updated_at)
+             VALUES (uuid_generate_v4(), $1::text, $2::text, $3::text, $4, $5, $6, $7, $8::text, $9::text,
+                 $10, $11, $12::int, $13, $14::text, CASE WHEN $15::int IS NOT NULL THEN $12::int END, $15, $16::text, {provenance}, $17, NOW(), NOW())
              ON CONFLICT (nfl_id) DO UPDATE SET
                  name = EXCLUDED.name,
-                 position = EXCLUDED.position,
-                 team_id = EXCLUDED.team_id,
-                 jersey_number = EXCLUDED.jersey_number,
-                 height_inches = EXCLUDED.height_inches,
-                 weight_pounds = EXCLUDED.weight_pounds,
-                 college = EXCLUDED.college,
+                 {merge},
                  status = EXCLUDED.status,
-                 updated_at = NOW()",
-            &[
-                &nfl_id,
-                &name,
-                &position,
-                &team_id,
-                &player.jersey_number,
-                &height_inches,
-                &player.weight,
-                &college,
-                &status,
-            ],
-        )?;
+                 birth_date = COALESCE(EXCLUDED.birth_date, players.birth_date),
+                 years_pro = COALESCE(EXCLUDED.years_pro, players.years_pro),
+                 entry_year = COALESCE(EXCLUDED.entry_year, players.entry_year),
+                 rookie_year = COALESCE(EXCLUDED.rookie_year, players.rookie_year),
+                 draft_club = COALESCE(EXCLUDED.draft_club, players.draft_club),
+                 draft_year = COALESCE(EXCLUDED.draft_year, players.draft_year),
+                 draft_pick = COALESCE(EXCLUDED.draft_pick, players.draft_pick),
+                 headshot_url = COALESCE(EXCLUDED.headshot_url, players.headshot_url),
+                 row_hash = EXCLUDED.row_hash,
+                 updated_at = NOW()
+             RETURNING id",
+            provenance = precedence::insert_sql(
+                source,
+                &[("position", "$3::text"), ("team_id", "$4::uuid"), ("jersey_number", "$5::int"), ("height_inches", "$6::int"), ("weight_pounds", "$7::int"), ("college", "$8::text")]
+            ),
+            merge = precedence::roster_upsert_sql(&self.config.precedence, source)
+        );
+        let params: [&(dyn ToSql + Sync); 17] = [
+            &nfl_id,
+            &name,
+            &position,
+            &team_id,
+            &player.jersey_number,
+            &height_inches,
+            &player.weight,
+            &college,
+            &status,
+            &birth_date,
+            &player.years_exp,
+            &player.entry_year,
+            &player.rookie_year,
+            &draft_club,
+            &player.draft_number,
+            &player.headshot_url,
+            &row_hash,
+        ];
+        // Nothing reads the returned ID of a captured upsert
+        if self.capture("players", sql.trim_end().trim_end_matches("RETURNING id").trim_end(), &params)? {
+            // The script can't know the player's ID, so it looks it up by nfl_id
+            if self.emitter.is_some() {
+                let sql = EXTERNAL_ID_UPSERT.replacen("$1", "(SELECT id FROM players WHERE nfl_id = $1)", 1);
+                for (source, external_id) in transformer::external_ids(player) {
+                    self.capture("player_external_ids", &sql, &[&nfl_id, &source, &external_id])?;
+                }
+            }
+            return Ok(());
+        }
+        let client = self.database.get_client();
+        let row = client.query_one(&sql, &params)?;
+        let player_id: Uuid = row.get(0);
+
+        for (source, external_id) in transformer::external_ids(player) {
+            client.execute(EXTERNAL_ID_UPSERT, &[&player_id, &source, &external_id])?;
+        }
 
         Ok(())
     }
 
     fn upsert_games_batch(&mut self, games: &[Game]) -> Result<()> {
+        let source = self.nflverse_source();
         if self.config.dry_run {
-            return Ok(());
+            let settings = row_hashes::merge_settings(&self.config.precedence, source);
+            return self.dry_run_batch(
+                "games",
+                games,
+                DataValidator::validate_game,
+                |client, games| diff::games(client, games, &settings),
+                |pipeline, game| pipeline.upsert_game(game).map(|_| ()),
+            );
         }
 
-        for game in games {
-            self.upsert_game(game)?;
+        match self.config.load_method {
+            LoadMethod::Insert => {
+                let settings = row_hashes::merge_settings(&self.config.precedence, source);
+                let games = row_hashes::changed(self.database.get_client(), "games", games, &settings, !self.config.force)?;
+                let mut unresolved = Vec::new();
+                self.in_batch_transaction(|pipeline| {
+                    for game in games.iter() {
+                        unresolved.extend(pipeline.upsert_game(game)?);
+                    }
+                    Ok(())
+                })?;
+                resolution::record(self.database.get_client(), &unresolved);
+                Ok(())
+            }
+            method => {
+                let skip_unchanged = !self.config.force;
+                write_games(self.database.get_client(), method, games, &self.config.precedence, source, skip_unchanged)
+            }
         }
-
-        Ok(())
     }
 
-    fn upsert_game(&mut self, game: &Game) -> Result<()> {
+    /// Upsert one game; returns its unknown teams when it was skipped
+    fn upsert_game(&mut self, game: &Game) -> Result<Option<Unresolved>> {
         let home_team_abbr = transformer::normalize_team_abbr(&game.home_team);
         let away_team_abbr = transformer::normalize_team_abbr(&game.away_team);
 
@@ -328,66 +3613,180 @@ impl DataPipeline {
 
         if home_team_id.is_none() {
             warn!("Home team {} not found", home_team_abbr);
-            return Ok(());
+            return Ok(Some(Unresolved::team(&home_team_abbr, datasets::SCHEDULES.name, game.season)));
         }
         if away_team_id.is_none() {
             warn!("Away team {} not found", away_team_abbr);
-            return Ok(());
+            return Ok(Some(Unresolved::team(&away_team_abbr, datasets::SCHEDULES.name, game.season)));
         }
 
-        let client = self.database.get_client();
-        client.execute(
-            "INSERT INTO games (id, nfl_game_id, season, week, game_date, home_team_id, away_team_id, home_score, away_score, status, created_at, updated_at)
-             VALUES (uuid_generate_v4(), $1, $2, $3, $4, $5, $6, $7, $8, $9, NOW(), NOW())
+        let source = self.nflverse_source();
+        let row_hash = row_hashes::content_hash(game, &row_hashes::merge_settings(&self.config.precedence, source));
+        let sql = format!(
+            "INSERT INTO games (id, nfl_game_id, season, week, game_date, home_team_id, away_team_id, home_score, away_score, status, season_type,
+                 game_time_et, spread_line, total_line, home_moneyline, away_moneyline, home_spread_odds, away_spread_odds,
+                 over_odds, under_odds, roof, surface, weather_temp, weather_wind_speed, venue_name, referee, home_rest, away_rest,
+                 location, provenance, row_hash, created_at, updated_at)
+             VALUES (uuid_generate_v4(), $1, $2, $3, $4, $5, $6, $7, $8, $9, $10,
+                 $11, $12::float8, $13::float8, $14::float8, $15::float8, $16::float8, $17::float8,
+                 $18::float8, $19::float8, $20, $21, round($22::float8), round($23::float8), $24, $25, $26, $27, $28,
+                 {provenance}, $29, NOW(), NOW())
              ON CONFLICT (nfl_game_id) DO UPDATE SET
-                 home_score = EXCLUDED.home_score,
-                 away_score = EXCLUDED.away_score,
-                 status = EXCLUDED.status,
+                 {merge},
+                 status = {status},
+                 game_time_et = COALESCE(EXCLUDED.game_time_et, games.game_time_et),
+                 spread_line = COALESCE(EXCLUDED.spread_line, games.spread_line),
+                 total_line = COALESCE(EXCLUDED.total_line, games.total_line),
+                 home_moneyline = COALESCE(EXCLUDED.home_moneyline, games.home_moneyline),
+                 away_moneyline = COALESCE(EXCLUDED.away_moneyline, games.away_moneyline),
+                 home_spread_odds = COALESCE(EXCLUDED.home_spread_odds, games.home_spread_odds),
+                 away_spread_odds = COALESCE(EXCLUDED.away_spread_odds, games.away_spread_odds),
+                 over_odds = COALESCE(EXCLUDED.over_odds, games.over_odds),
+                 under_odds = COALESCE(EXCLUDED.under_odds, games.under_odds),
+                 roof = COALESCE(EXCLUDED.roof, games.roof),
+                 surface = COALESCE(EXCLUDED.surface, games.surface),
+                 weather_temp = COALESCE(EXCLUDED.weather_temp, games.weather_temp),
+                 weather_wind_speed = COALESCE(EXCLUDED.weather_wind_speed, games.weather_wind_speed),
+                 referee = COALESCE(EXCLUDED.referee, games.referee),
+                 home_rest = COALESCE(EXCLUDED.home_rest, games.home_rest),
+                 away_rest = COALESCE(EXCLUDED.away_rest, games.away_rest),
+                 location = COALESCE(EXCLUDED.location, games.location),
+                 row_hash = EXCLUDED.row_hash,
                  updated_at = NOW()",
-            &[
-                &game.game_id,
-                &game.season,
-                &game.week,
-                &game.gameday,
-                &home_team_id,
-                &away_team_id,
-                &game.home_score,
-                &game.away_score,
-                &"final",
-            ],
-        )?;
+            provenance = precedence::insert_sql(
+                source,
+                &[("season_type", "$10::varchar"), ("week", "$3::int"), ("game_date", "$4::timestamptz"), ("home_score", "$7::int"), ("away_score", "$8::int"), ("venue_name", "$24::text")]
+            ),
+            merge = precedence::schedule_upsert_sql(&self.config.precedence, source),
+            status = game_status::merge_sql("games.status", "EXCLUDED.status")
+        );
+        let params: [&(dyn ToSql + Sync); 29] = [
+            &game.game_id,
+            &game.season,
+            &game.week,
+            &transformer::kickoff(&game.gameday, game.gametime.as_deref()),
+            &home_team_id,
+            &away_team_id,
+            &game.home_score,
+            &game.away_score,
+            &GameStatus::of(game).as_str(),
+            &transformer::season_type_for_game_type(&game.game_type),
+            &game.gametime,
+            &game.spread_line,
+            &game.total_line,
+            &game.home_moneyline,
+            &game.away_moneyline,
+            &game.home_spread_odds,
+            &game.away_spread_odds,
+            &game.over_odds,
+            &game.under_odds,
+            &game.roof,
+            &game.surface,
+            &game.temp,
+            &game.wind,
+            &game.stadium,
+            &game.referee,
+            &game.home_rest,
+            &game.away_rest,
+            &game.location,
+            &row_hash,
+        ];
+        if self.capture("games", &sql, &params)? {
+            return Ok(None);
+        }
+        self.database.get_client().execute(&sql, &params)?;
 
-        Ok(())
+        Ok(None)
     }
 
     fn upsert_stats_batch(&mut self, stats: &[PlayerStat]) -> Result<()> {
         if self.config.dry_run {
-            return Ok(());
+            return self.dry_run_batch(
+                "game_stats",
+                stats,
+                DataValidator::validate_stat,
+                diff::stats,
+                |pipeline, stat| pipeline.upsert_stat(stat).map(|_| ()),
+            );
         }
 
-        for stat in stats {
-            if let Err(e) = self.upsert_stat(stat) {
-                warn!("Failed to upsert stat for {}: {}", stat.player_display_name.as_ref().unwrap_or(&"unknown".to_string()), e);
+        match self.config.load_method {
+            LoadMethod::Insert => {
+                let stats = row_hashes::changed(self.database.get_client(), "game_stats", stats, "", !self.config.force)?;
+                self.insert_stats(&stats)
             }
+            method => write_stats(self.database.get_client(), method, stats, !self.config.force),
         }
+    }
 
+    fn insert_stats(&mut self, stats: &[PlayerStat]) -> Result<()> {
+        let mut skipped = 0;
+        self.in_batch_transaction(|pipeline| {
+            for stat in stats {
+                let written = pipeline.upsert_stat(stat).with_context(|| {
+                    format!("Failed to upsert stat for {}", stat.player_display_name.as_deref().unwrap_or("unknown"))
+                })?;
+                skipped += usize::from(!written);
+            }
+            Ok(())
+        })?;
+        record_unresolved_stats(self.database.get_client(), stats, skipped);
         Ok(())
     }
 
-    fn upsert_stat(&mut self, stat: &PlayerStat) -> Result<()> {
+    /// Upsert one stat line; false when the player isn't in the players table
+    fn upsert_stat(&mut self, stat: &PlayerStat) -> Result<bool> {
         // Get player ID
         let player_id = self.database.get_player_id_by_nfl_id(&stat.player_id)?;
 
         if player_id.is_none() {
             // Player not found, skip
-            return Ok(());
+            return Ok(false);
         }
 
-        let client = self.database.get_client();
-        client.execute(
-            "INSERT INTO game_stats (id, player_id, season, week, passing_yards, rushing_yards, receiving_yards, passing_tds, rushing_tds, receiving_tds, receptions, targets, attempts, completions, interceptions, created_at, updated_at)
-             VALUES (uuid_generate_v4(), $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, NOW(), NOW())
-             ON CONFLICT (player_id, season, week) DO UPDATE SET
+        let extras = transformer::defense_kicking_values(stat);
+        let extra_params = (16..16 + extras.len()).map(|n| format!("${}", n)).collect::<Vec<_>>().join(", ");
+        let def_sacks_param = 16 + extras.len();
+        let (passing_yards, rushing_yards, receiving_yards) = (
+            stat.passing_yards.map(|v| v as i32),
+            stat.rushing_yards.map(|v| v as i32),
+            stat.receiving_yards.map(|v| v as i32),
+        );
+        let (receptions, targets, attempts, completions) = (
+            stat.receptions.map(|v| v as i32),
+            stat.targets.map(|v| v as i32),
+            stat.attempts.map(|v| v as i32),
+            stat.completions.map(|v| v as i32),
+        );
+        let mut params: Vec<&(dyn ToSql + Sync)> = vec![
+            &player_id,
+            &stat.season,
+            &stat.week,
+            &passing_yards,
+            &rushing_yards,
+            &receiving_yards,
+            &stat.passing_tds,
+            &stat.rushing_tds,
+            &stat.receiving_tds,
+            &receptions,
+            &targets,
+            &attempts,
+            &completions,
+            &stat.interceptions,
+            &stat.season_type,
+        ];
+        params.extend(extras.iter().map(|value| value as &(dyn ToSql + Sync)));
+        params.push(&stat.def_sacks);
+        params.push(&stat.target_share);
+        let team_abbr = stat.recent_team.as_deref().map(transformer::normalize_team_abbr);
+        params.push(&team_abbr);
+        let row_hash = row_hashes::content_hash(stat, "");
+        params.push(&row_hash);
+
+        let sql = format!("INSERT INTO game_stats (id, player_id, season, week, passing_yards, rushing_yards, receiving_yards, passing_tds, rushing_tds, receiving_tds, receptions, targets, attempts, completions, interceptions, season_type, {}, def_sacks, target_share, team_id, row_hash, created_at, updated_at)
+             VALUES (uuid_generate_v4(), $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, {}, ${}::float8, ${}::float8,
+                     (SELECT id FROM teams WHERE abbreviation = ${}), ${}, NOW(), NOW())
+             ON CONFLICT (player_id, season, week, season_type) DO UPDATE SET
                  passing_yards = EXCLUDED.passing_yards,
                  rushing_yards = EXCLUDED.rushing_yards,
                  receiving_yards = EXCLUDED.receiving_yards,
@@ -399,25 +3798,141 @@ impl DataPipeline {
                  attempts = EXCLUDED.attempts,
                  completions = EXCLUDED.completions,
                  interceptions = EXCLUDED.interceptions,
+                 {},
+                 def_sacks = EXCLUDED.def_sacks,
+                 target_share = EXCLUDED.target_share,
+                 team_id = COALESCE(EXCLUDED.team_id, game_stats.team_id),
+                 row_hash = EXCLUDED.row_hash,
                  updated_at = NOW()",
-            &[
-                &player_id,
-                &stat.season,
-                &stat.week,
-                &stat.passing_yards.map(|v| v as i32),
-                &stat.rushing_yards.map(|v| v as i32),
-                &stat.receiving_yards.map(|v| v as i32),
-                &stat.passing_tds,
-                &stat.rushing_tds,
-                &stat.receiving_tds,
-                &stat.receptions.map(|v| v as i32),
-                &stat.targets.map(|v| v as i32),
-                &stat.attempts.map(|v| v as i32),
-                &stat.completions.map(|v| v as i32),
-                &stat.interceptions,
-            ],
-        )?;
+            bulk::defense_kicking_sql("{}"),
+            extra_params,
+            def_sacks_param,
+            def_sacks_param + 1,
+            def_sacks_param + 2,
+            def_sacks_param + 3,
+            bulk::defense_kicking_sql("{} = EXCLUDED.{}"),
+        );
+        if self.capture("game_stats", &sql, &params)? {
+            return Ok(true);
+        }
+        self.database.get_client().execute(&sql, &params)?;
 
-        Ok(())
+        Ok(true)
+    }
+}
+
+/// A player's ID in another system, cleared of any fuzzy match confidence
+const EXTERNAL_ID_UPSERT: &str = "INSERT INTO player_external_ids (player_id, source, external_id, updated_at)
+     VALUES ($1, $2, $3, NOW())
+     ON CONFLICT (player_id, source) DO UPDATE SET
+         external_id = EXCLUDED.external_id,
+         match_confidence = NULL,
+         updated_at = NOW()
+     WHERE player_external_ids.external_id IS DISTINCT FROM EXCLUDED.external_id
+        OR player_external_ids.match_confidence IS NOT NULL";
+
+// Set-based batch writers, run inline or on parallel workers' connections
+
+fn write_players(
+    client: &mut Client,
+    method: LoadMethod,
+    players: &[RosterPlayer],
+    precedence: &Precedence,
+    source: Source,
+    skip_unchanged: bool,
+) -> Result<()> {
+    let settings = row_hashes::merge_settings(precedence, source);
+    let players = row_hashes::changed(client, "players", players, &settings, skip_unchanged)?;
+    if players.is_empty() {
+        return Ok(());
+    }
+    match method {
+        LoadMethod::Copy => bulk::copy_players(client, &players, precedence, source)?,
+        _ => bulk::unnest_players(client, &players, precedence, source)?,
+    };
+    Ok(())
+}
+
+fn write_games(
+    client: &mut Client,
+    method: LoadMethod,
+    games: &[Game],
+    precedence: &Precedence,
+    source: Source,
+    skip_unchanged: bool,
+) -> Result<()> {
+    let settings = row_hashes::merge_settings(precedence, source);
+    let games = row_hashes::changed(client, "games", games, &settings, skip_unchanged)?;
+    if games.is_empty() {
+        return Ok(());
+    }
+    let merged = match method {
+        LoadMethod::Copy => bulk::copy_games(client, &games, precedence, source)?,
+        _ => bulk::unnest_games(client, &games, precedence, source)?,
+    };
+    if (merged as usize) < games.len() {
+        warn!("{} games skipped (team not found)", games.len() - merged as usize);
+        let abbrs: Vec<(String, i32)> = games
+            .iter()
+            .flat_map(|game| [&game.home_team, &game.away_team].map(|team| (transformer::normalize_team_abbr(team), game.season)))
+            .collect();
+        match resolution::unknown_teams(client, abbrs.iter().map(|(abbr, _)| abbr.as_str())) {
+            Ok(unknown) => {
+                let unresolved: Vec<Unresolved> = abbrs
+                    .iter()
+                    .filter(|(abbr, _)| unknown.contains(abbr))
+                    .map(|(abbr, season)| Unresolved::team(abbr, datasets::SCHEDULES.name, *season))
+                    .collect();
+                resolution::record(client, &unresolved);
+            }
+            Err(e) => warn!("⚠️  Failed to look up skipped games' teams: {}", e),
+        }
+    }
+    Ok(())
+}
+
+fn write_stats(client: &mut Client, method: LoadMethod, stats: &[PlayerStat], skip_unchanged: bool) -> Result<()> {
+    let stats = row_hashes::changed(client, "game_stats", stats, "", skip_unchanged)?;
+    if stats.is_empty() {
+        return Ok(());
     }
+    let merged = match method {
+        LoadMethod::Copy => bulk::copy_stats(client, &stats)?,
+        _ => bulk::unnest_stats(client, &stats)?,
+    };
+    if (merged as usize) < stats.len() {
+        warn!("{} stat rows skipped (player not found)", stats.len() - merged as usize);
+    }
+    record_unresolved_stats(client, &stats, stats.len().saturating_sub(merged as usize));
+    Ok(())
+}
+
+/// Record the players behind `skipped` dropped stat lines, and teams in the
+/// batch that left `game_stats.team_id` empty
+fn record_unresolved_stats(client: &mut Client, stats: &[PlayerStat], skipped: usize) {
+    let mut unresolved = Vec::new();
+    if skipped > 0 {
+        match resolution::missing_players(client, stats.iter().map(|stat| stat.player_id.as_str())) {
+            Ok(missing) => unresolved.extend(stats.iter().filter(|stat| missing.contains(&stat.player_id)).map(|stat| {
+                Unresolved::player(&stat.player_id, stat.player_display_name.as_deref(), datasets::PLAYER_STATS.name, stat.season)
+            })),
+            Err(e) => warn!("⚠️  Failed to look up skipped stat players: {}", e),
+        }
+    }
+
+    let teams: Vec<(String, i32)> = stats
+        .iter()
+        .filter_map(|stat| Some((transformer::normalize_team_abbr(stat.recent_team.as_deref()?), stat.season)))
+        .collect();
+    match resolution::unknown_teams(client, teams.iter().map(|(abbr, _)| abbr.as_str())) {
+        Ok(unknown) => unresolved.extend(
+            teams
+                .iter()
+                .filter(|(abbr, _)| unknown.contains(abbr))
+                .map(|(abbr, season)| Unresolved::team(abbr, datasets::PLAYER_STATS.name, *season)),
+        ),
+        Err(e) => warn!("⚠️  Failed to look up stat teams: {}", e),
+    }
+
+    resolution::record(client, &unresolved);
 }
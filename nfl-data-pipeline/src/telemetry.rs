@@ -0,0 +1,180 @@
+use anyhow::Result;
+use log::{debug, warn};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use crate::config::{Config, LoadMethod};
+use crate::database;
+use crate::downloader::SourceMissing;
+use crate::sync::Unchanged;
+
+/// Coarse kind of a stage or run failure. Only the class is reported, never the
+/// message, which can contain hostnames, paths or credentials.
+pub fn error_class(e: &anyhow::Error) -> &'static str {
+    if database::is_connection_error(e) {
+        return "db_connection";
+    }
+    for cause in e.chain() {
+        if cause.downcast_ref::<Unchanged>().is_some() {
+            return "unchanged";
+        }
+        if cause.downcast_ref::<SourceMissing>().is_some() {
+            return "source_missing";
+        }
+        if cause.downcast_ref::<postgres::Error>().is_some() {
+            return "db_query";
+        }
+        if let Some(http) = cause.downcast_ref::<reqwest::Error>() {
+            return match http.status().map(|status| status.as_u16()) {
+                Some(404) => "http_not_found",
+                Some(status) if status >= 500 => "http_server",
+                _ if http.is_timeout() => "http_timeout",
+                _ => "http",
+            };
+        }
+        if cause.downcast_ref::<csv::Error>().is_some()
+            || cause.downcast_ref::<serde_json::Error>().is_some()
+            || cause.downcast_ref::<parquet::errors::ParquetError>().is_some()
+        {
+            return "parse";
+        }
+        if cause.downcast_ref::<std::io::Error>().is_some() {
+            return "io";
+        }
+    }
+    "other"
+}
+
+/// Rows one dataset wrote during the run
+#[derive(Debug, Default, Clone, Serialize, PartialEq)]
+pub struct DatasetMetrics {
+    pub rows: u64,
+    pub failed_batches: u64,
+}
+
+/// What a run reports: no database URLs, hostnames, team or player data
+#[derive(Debug, Clone, Serialize)]
+pub struct RunReport {
+    pub version: &'static str,
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub mode: String,
+    pub load_method: &'static str,
+    pub writers: usize,
+    pub dry_run: bool,
+    pub duration_secs: f64,
+    /// `None` when the run succeeded
+    pub error_class: Option<&'static str>,
+    pub datasets: BTreeMap<&'static str, DatasetMetrics>,
+    /// Failed stages by error class, e.g. {"Snap Counts": {"http_not_found": 1}}
+    pub stage_errors: BTreeMap<String, BTreeMap<&'static str, u32>>,
+}
+
+/// Metrics collected while a pipeline runs, sent once at the end when
+/// `PIPELINE_TELEMETRY_URL` is set
+#[derive(Debug)]
+pub struct Telemetry {
+    started: Instant,
+    mode: String,
+    load_method: LoadMethod,
+    writers: usize,
+    dry_run: bool,
+    datasets: BTreeMap<&'static str, DatasetMetrics>,
+    stage_errors: BTreeMap<String, BTreeMap<&'static str, u32>>,
+}
+
+impl Telemetry {
+    pub fn start(config: &Config) -> Self {
+        Telemetry::for_run(&config.mode, config.load_method, config.writers, config.dry_run)
+    }
+
+    fn for_run(mode: &str, load_method: LoadMethod, writers: usize, dry_run: bool) -> Self {
+        Telemetry {
+            started: Instant::now(),
+            mode: mode.to_string(),
+            load_method,
+            writers,
+            dry_run,
+            datasets: BTreeMap::new(),
+            stage_errors: BTreeMap::new(),
+        }
+    }
+
+    pub fn dataset(&mut self, name: &'static str, rows: usize, failed_batches: usize) {
+        let metrics = self.datasets.entry(name).or_default();
+        metrics.rows += rows as u64;
+        metrics.failed_batches += failed_batches as u64;
+    }
+
+    pub fn stage_failed(&mut self, stage: &str, e: &anyhow::Error) {
+        *self.stage_errors.entry(stage.to_string()).or_default().entry(error_class(e)).or_default() += 1;
+    }
+
+    pub fn report(&self, result: &Result<()>) -> RunReport {
+        RunReport {
+            version: env!("CARGO_PKG_VERSION"),
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            mode: self.mode.clone(),
+            load_method: self.load_method.as_str(),
+            writers: self.writers,
+            dry_run: self.dry_run,
+            duration_secs: (self.started.elapsed().as_secs_f64() * 10.0).round() / 10.0,
+            error_class: result.as_ref().err().map(error_class),
+            datasets: self.datasets.clone(),
+            stage_errors: self.stage_errors.clone(),
+        }
+    }
+}
+
+/// Post a run report. Best effort: failures are logged at debug level so an
+/// unreachable endpoint never affects the run.
+pub fn send(url: &str, report: &RunReport) {
+    let sent = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .and_then(|http| http.post(url).json(report).send())
+        .and_then(|response| response.error_for_status());
+    match sent {
+        Ok(_) => debug!("Telemetry sent to {}", url),
+        Err(e) if e.is_builder() => warn!("⚠️  Invalid PIPELINE_TELEMETRY_URL {}: {}", url, e),
+        Err(e) => debug!("Telemetry not sent: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{anyhow, Context};
+
+    #[test]
+    fn test_error_classes() {
+        let unchanged = anyhow::Error::from(Unchanged).context("Player Stats");
+        assert_eq!(error_class(&unchanged), "unchanged");
+
+        let io = anyhow::Error::from(std::io::Error::new(std::io::ErrorKind::PermissionDenied, "/srv/secret"));
+        assert_eq!(error_class(&io), "io");
+
+        let parse = serde_json::from_str::<u32>("x").context("Invalid scoring config");
+        assert_eq!(error_class(&parse.unwrap_err()), "parse");
+        assert_eq!(error_class(&anyhow!("teams table empty")), "other");
+    }
+
+    #[test]
+    fn test_report_carries_classes_not_messages() {
+        let mut telemetry = Telemetry::for_run("year", LoadMethod::Copy, 4, false);
+        telemetry.dataset("player_stats", 500, 0);
+        telemetry.dataset("player_stats", 120, 1);
+        let failure = anyhow::Error::from(std::io::Error::other("host=db.internal password=hunter2"));
+        telemetry.stage_failed("Snap Counts", &failure);
+
+        let report = telemetry.report(&Err(failure));
+        assert_eq!(report.datasets["player_stats"], DatasetMetrics { rows: 620, failed_batches: 1 });
+
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(json.contains(r#""stage_errors":{"Snap Counts":{"io":1}}"#));
+        assert!(json.contains(r#""error_class":"io""#));
+        assert!(!json.contains("hunter2"));
+    }
+}
@@ -1,6 +1,17 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::America::New_York;
 use std::collections::HashMap;
+use std::ops::RangeInclusive;
 use once_cell::sync::Lazy;
 
+use crate::parser::{
+    CollegeStat, CombineResult, Contract, DepthChartEntry, DraftPick, Game, Injury, NGSPassing, NGSReceiving,
+    NGSRushing, PbpPlay, PlayerStat, RosterPlayer, SnapCount,
+};
+
+/// Regular-season weeks most fantasy leagues use for their playoffs
+pub const FANTASY_PLAYOFF_WEEKS: RangeInclusive<i32> = 15..=17;
+
 /// Map historical team abbreviations to current ones
 static TEAM_MAPPING: Lazy<HashMap<&'static str, &'static str>> = Lazy::new(|| {
     let mut m = HashMap::new();
@@ -27,6 +38,25 @@ pub fn normalize_team_abbr(abbr: &str) -> String {
         .unwrap_or_else(|| abbr.to_uppercase())
 }
 
+/// Rows of nflverse's teams file that duplicate a current franchise: relocated
+/// teams' old abbreviations and the Rams' alternate "LAR"
+pub const NON_CURRENT_TEAM_ABBRS: [&str; 4] = ["LAR", "OAK", "SD", "STL"];
+
+/// City part of a franchise's full name ("Kansas City Chiefs" → "Kansas City")
+pub fn team_city(full_name: &str, nickname: &str) -> String {
+    full_name
+        .strip_suffix(nickname)
+        .map(str::trim)
+        .filter(|city| !city.is_empty())
+        .unwrap_or(full_name)
+        .to_string()
+}
+
+/// Division without its conference ("AFC West" → "West")
+pub fn division_name(division: &str, conference: &str) -> String {
+    division.strip_prefix(conference).unwrap_or(division).trim().to_string()
+}
+
 /// Convert height string (e.g., "6-2") to inches
 pub fn height_to_inches(height_str: &str) -> Option<i32> {
     let parts: Vec<&str> = height_str.split('-').collect();
@@ -39,6 +69,60 @@ pub fn height_to_inches(height_str: &str) -> Option<i32> {
     }
 }
 
+/// Parse an nflverse YYYY-MM-DD date
+pub fn parse_date(date_str: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date_str.trim(), "%Y-%m-%d").ok()
+}
+
+/// Regular-season weeks in a season; postseason weeks are numbered after them
+pub fn regular_season_weeks(season: i32) -> i32 {
+    if season >= 2021 { 18 } else { 17 }
+}
+
+/// Kickoff instant from a schedule's gameday and gametime. nflverse lists every
+/// gametime in Eastern time, international games included (London kickoffs are
+/// 09:30), so times are read as America/New_York with its DST rules whatever the
+/// stadium. Without a gametime the kickoff is midnight Eastern on the gameday.
+pub fn kickoff(gameday: &str, gametime: Option<&str>) -> Option<DateTime<Utc>> {
+    let date = parse_date(gameday)?;
+    let time = gametime
+        .map(str::trim)
+        .and_then(|t| NaiveTime::parse_from_str(t, "%H:%M").or_else(|_| NaiveTime::parse_from_str(t, "%H:%M:%S")).ok())
+        .unwrap_or(NaiveTime::MIN);
+    let local = date.and_time(time);
+    // The hour repeated in November reads as the earlier (EDT) one; an hour
+    // skipped in March reads as if the clocks had already moved
+    New_York
+        .from_local_datetime(&local)
+        .earliest()
+        .or_else(|| New_York.from_local_datetime(&(local + Duration::hours(1))).earliest())
+        .map(|kickoff| kickoff.with_timezone(&Utc))
+}
+
+/// Source names used in `player_external_ids`, in roster column order
+pub const EXTERNAL_ID_SOURCES: [&str; 8] = [
+    "espn", "sportradar", "yahoo", "rotowire", "pff", "pfr", "fantasy_data", "sleeper",
+];
+
+/// A roster player's non-empty IDs in other providers, as (source, id) pairs
+pub fn external_ids(player: &RosterPlayer) -> Vec<(&'static str, &str)> {
+    let ids = [
+        &player.espn_id,
+        &player.sportradar_id,
+        &player.yahoo_id,
+        &player.rotowire_id,
+        &player.pff_id,
+        &player.pfr_id,
+        &player.fantasy_data_id,
+        &player.sleeper_id,
+    ];
+    EXTERNAL_ID_SOURCES
+        .iter()
+        .zip(ids)
+        .filter_map(|(source, id)| id.as_deref().map(str::trim).filter(|id| !id.is_empty()).map(|id| (*source, id)))
+        .collect()
+}
+
 /// Normalize player position
 pub fn normalize_position(pos: &str) -> String {
     match pos.to_uppercase().as_str() {
@@ -51,17 +135,179 @@ pub fn normalize_position(pos: &str) -> String {
     }
 }
 
-/// Clean player name (remove suffixes like Jr., III, etc.)
-pub fn clean_player_name(name: &str) -> String {
-    name.replace(" Jr.", "")
-        .replace(" Sr.", "")
-        .replace(" II", "")
-        .replace(" III", "")
-        .replace(" IV", "")
-        .trim()
-        .to_string()
+/// Map an nflverse schedule game_type (REG, WC, DIV, CON, SB, PRE) to a season type
+pub fn season_type_for_game_type(game_type: &str) -> &'static str {
+    match game_type {
+        "REG" => "REG",
+        "PRE" => "PRE",
+        _ => "POST",
+    }
+}
+
+/// Passing, rushing and receiving game_stats columns
+pub const OFFENSE_COLUMNS: [&str; 11] = [
+    "completions",
+    "attempts",
+    "passing_yards",
+    "passing_tds",
+    "interceptions",
+    "rushing_yards",
+    "rushing_tds",
+    "targets",
+    "receptions",
+    "receiving_yards",
+    "receiving_tds",
+];
+
+/// Integer game_stats columns beyond the passing/rushing/receiving line: combined
+/// fumbles and two-point conversions, defense, kicking and carries. Every stat
+/// writer binds these in this order from `defense_kicking_values`; `def_sacks` and
+/// `target_share` are written separately since they need decimal columns.
+pub const DEFENSE_KICKING_COLUMNS: [&str; 24] = [
+    "fumbles",
+    "fumbles_lost",
+    "two_point_conversions",
+    "special_teams_tds",
+    "tackles",
+    "solo_tackles",
+    "tackle_assists",
+    "tackles_for_loss",
+    "qb_hits",
+    "passes_defended",
+    "def_interceptions",
+    "def_fumbles_forced",
+    "def_fumble_recoveries",
+    "def_tds",
+    "def_safeties",
+    "field_goals_made",
+    "field_goals_attempted",
+    "fg_long",
+    "fg_made_0_39",
+    "fg_made_40_49",
+    "fg_made_50_plus",
+    "extra_points_made",
+    "extra_points_attempted",
+    "carries",
+];
+
+/// Sum of the values present, or `None` when the file has none of the columns
+fn sum_present(values: &[Option<i32>]) -> Option<i32> {
+    values.iter().flatten().copied().reduce(|a, b| a + b)
+}
+
+/// Values for `DEFENSE_KICKING_COLUMNS`
+pub fn defense_kicking_values(stat: &PlayerStat) -> [Option<i32>; 24] {
+    [
+        sum_present(&[stat.sack_fumbles, stat.rushing_fumbles, stat.receiving_fumbles]),
+        sum_present(&[stat.sack_fumbles_lost, stat.rushing_fumbles_lost, stat.receiving_fumbles_lost]),
+        sum_present(&[stat.passing_2pt_conversions, stat.rushing_2pt_conversions, stat.receiving_2pt_conversions]),
+        stat.special_teams_tds,
+        sum_present(&[stat.def_tackles_solo, stat.def_tackle_assists]),
+        stat.def_tackles_solo,
+        stat.def_tackle_assists,
+        stat.def_tackles_for_loss,
+        stat.def_qb_hits,
+        stat.def_pass_defended,
+        stat.def_interceptions,
+        stat.def_fumbles_forced,
+        stat.fumble_recovery_opp,
+        stat.def_tds,
+        stat.def_safeties,
+        stat.fg_made,
+        stat.fg_att,
+        stat.fg_long,
+        sum_present(&[stat.fg_made_0_19, stat.fg_made_20_29, stat.fg_made_30_39]),
+        stat.fg_made_40_49,
+        sum_present(&[stat.fg_made_50_59, stat.fg_made_60]),
+        stat.pat_made,
+        stat.pat_att,
+        stat.carries.map(|v| v as i32),
+    ]
+}
+
+/// In-place cleanup applied to parsed rows before they leave the pipeline
+/// without going through the database writers (which normalize as they bind)
+pub trait Normalize {
+    fn normalize(&mut self) {}
+}
+
+fn normalize_in_place(abbr: &mut String) {
+    *abbr = normalize_team_abbr(abbr);
+}
+
+impl Normalize for RosterPlayer {
+    fn normalize(&mut self) {
+        normalize_in_place(&mut self.team);
+        self.draft_club.iter_mut().for_each(normalize_in_place);
+    }
+}
+
+impl Normalize for PlayerStat {
+    fn normalize(&mut self) {
+        self.recent_team.iter_mut().for_each(normalize_in_place);
+    }
+}
+
+impl Normalize for Game {
+    fn normalize(&mut self) {
+        normalize_in_place(&mut self.home_team);
+        normalize_in_place(&mut self.away_team);
+    }
+}
+
+impl Normalize for NGSPassing {
+    fn normalize(&mut self) {
+        normalize_in_place(&mut self.team_abbr);
+    }
 }
 
+impl Normalize for NGSRushing {
+    fn normalize(&mut self) {
+        normalize_in_place(&mut self.team_abbr);
+    }
+}
+
+impl Normalize for NGSReceiving {
+    fn normalize(&mut self) {
+        normalize_in_place(&mut self.team_abbr);
+    }
+}
+
+impl Normalize for Injury {
+    fn normalize(&mut self) {
+        normalize_in_place(&mut self.team);
+    }
+}
+
+impl Normalize for DepthChartEntry {
+    fn normalize(&mut self) {
+        normalize_in_place(&mut self.club_code);
+    }
+}
+
+impl Normalize for PbpPlay {
+    fn normalize(&mut self) {
+        self.posteam.iter_mut().for_each(normalize_in_place);
+    }
+}
+
+impl Normalize for SnapCount {
+    fn normalize(&mut self) {
+        normalize_in_place(&mut self.team);
+    }
+}
+
+impl Normalize for DraftPick {
+    fn normalize(&mut self) {
+        normalize_in_place(&mut self.team);
+    }
+}
+
+// Team columns here are college teams or full names, not abbreviations
+impl Normalize for CombineResult {}
+impl Normalize for Contract {}
+impl Normalize for CollegeStat {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,6 +320,36 @@ mod tests {
         assert_eq!(normalize_team_abbr("KC"), "KC");
     }
 
+    #[test]
+    fn test_team_city_and_division() {
+        assert_eq!(team_city("Kansas City Chiefs", "Chiefs"), "Kansas City");
+        assert_eq!(team_city("Washington Commanders", "Commanders"), "Washington");
+        assert_eq!(team_city("Commanders", "Commanders"), "Commanders");
+        assert_eq!(division_name("AFC West", "AFC"), "West");
+        assert_eq!(division_name("West", "AFC"), "West");
+    }
+
+    #[test]
+    fn test_defense_kicking_values() {
+        let csv = "player_id,season,week,season_type,rushing_fumbles,receiving_fumbles,def_tackles_solo,\
+                   def_tackle_assists,def_sacks,fg_made,fg_made_20_29,fg_made_30_39,fg_made_50_59,fg_made_60_\n\
+                   00-0036913,2024,1,REG,1,NA,4,3,1.5,3,1,NA,1,1\n";
+        let stat: PlayerStat = csv::Reader::from_reader(csv.as_bytes())
+            .deserialize()
+            .next()
+            .unwrap()
+            .unwrap();
+        let values = defense_kicking_values(&stat);
+        let value = |column: &str| values[DEFENSE_KICKING_COLUMNS.iter().position(|c| *c == column).unwrap()];
+
+        assert_eq!(value("fumbles"), Some(1));
+        assert_eq!(value("fumbles_lost"), None);
+        assert_eq!(value("tackles"), Some(7));
+        assert_eq!(value("fg_made_0_39"), Some(1));
+        assert_eq!(value("fg_made_50_plus"), Some(2));
+        assert_eq!(stat.def_sacks, Some(1.5));
+    }
+
     #[test]
     fn test_height_conversion() {
         assert_eq!(height_to_inches("6-2"), Some(74));
@@ -81,10 +357,49 @@ mod tests {
         assert_eq!(height_to_inches("invalid"), None);
     }
 
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("1995-09-17"), NaiveDate::from_ymd_opt(1995, 9, 17));
+        assert_eq!(parse_date("09/17/1995"), None);
+    }
+
+    #[test]
+    fn test_kickoff_in_eastern_time() {
+        let utc = |gameday, gametime| kickoff(gameday, gametime).map(|k| k.to_rfc3339());
+        // EDT: a Thursday night opener lands on the next UTC day
+        assert_eq!(utc("2024-09-05", Some("20:20")), Some("2024-09-06T00:20:00+00:00".to_string()));
+        // EST after the first Sunday in November
+        assert_eq!(utc("2024-11-03", Some("13:00")), Some("2024-11-03T18:00:00+00:00".to_string()));
+        // London, listed in Eastern time like every other game
+        assert_eq!(utc("2024-10-13", Some("09:30")), Some("2024-10-13T13:30:00+00:00".to_string()));
+        assert_eq!(utc("2024-12-25", None), Some("2024-12-25T05:00:00+00:00".to_string()));
+        assert_eq!(utc("TBD", Some("13:00")), None);
+    }
+
+    #[test]
+    fn test_external_ids() {
+        let csv = "season,team,position,full_name,gsis_id,espn_id,pfr_id,sleeper_id\n\
+                   2024,KC,QB,Patrick Mahomes,00-0033873,3139477,MahoPa00,\n";
+        let player: RosterPlayer = csv::Reader::from_reader(csv.as_bytes())
+            .deserialize()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(external_ids(&player), vec![("espn", "3139477"), ("pfr", "MahoPa00")]);
+    }
+
     #[test]
     fn test_position_normalization() {
         assert_eq!(normalize_position("HB"), "RB");
         assert_eq!(normalize_position("ILB"), "LB");
         assert_eq!(normalize_position("QB"), "QB");
     }
+
+    #[test]
+    fn test_season_type_for_game_type() {
+        assert_eq!(season_type_for_game_type("REG"), "REG");
+        assert_eq!(season_type_for_game_type("WC"), "POST");
+        assert_eq!(season_type_for_game_type("SB"), "POST");
+        assert_eq!(season_type_for_game_type("PRE"), "PRE");
+    }
 }
@@ -0,0 +1,309 @@
+use log::info;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+
+/// Batches slower than this are halved (lock hold time, statement timeouts)
+const SLOW_BATCH: Duration = Duration::from_secs(2);
+/// Batches faster than this grow by half; round trips dominate their cost
+const FAST_BATCH: Duration = Duration::from_millis(250);
+/// Batches per writer in one throughput sample
+const WINDOW_BATCHES_PER_WRITER: usize = 8;
+/// Throughput changes smaller than this are treated as noise
+const MIN_GAIN: f64 = 0.05;
+
+/// Limits `--auto-tune` stays within
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub min_batch: usize,
+    pub max_batch: usize,
+    pub max_writers: usize,
+}
+
+/// Batch size and writer concurrency for one dataset.
+///
+/// Fixed unless `--auto-tune` is set. Adaptive tuning keeps each batch's upsert
+/// latency between 250 ms and 2 s by growing or halving the batch, and
+/// hill-climbs the number of active writers on rows written per second: it keeps
+/// stepping in one direction while throughput holds and turns around when it
+/// drops (connection limits, lock contention, a small dyno).
+///
+/// With `--backpressure`, the server's load caps writers on top of that: they
+/// are shed when connections run short and halved, with the batch size, on lock
+/// waits and statement timeouts, then allowed back one at a time.
+pub struct Tuner {
+    name: &'static str,
+    adaptive: bool,
+    backpressure: bool,
+    bounds: Bounds,
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    batch_size: usize,
+    writers: usize,
+    adding_writers: bool,
+    /// Most writers the server's load allows
+    ceiling: usize,
+    window_start: Option<Instant>,
+    window_rows: usize,
+    window_batches: usize,
+    last_throughput: Option<f64>,
+}
+
+impl Tuner {
+    /// Tuner for a dataset, starting from `--batch-size` and, when auto-tuning,
+    /// a single writer
+    pub fn from_config(name: &'static str, config: &Config) -> Self {
+        let bounds = Bounds {
+            min_batch: config.min_batch_size,
+            max_batch: config.max_batch_size,
+            max_writers: if config.parallel_writes() { config.writers } else { 1 },
+        };
+        let writers = if config.auto_tune { 1 } else { bounds.max_writers };
+        Tuner {
+            name,
+            adaptive: config.auto_tune,
+            backpressure: config.backpressure,
+            bounds,
+            state: Mutex::new(State {
+                batch_size: config.batch_size.clamp(bounds.min_batch, bounds.max_batch),
+                writers,
+                adding_writers: true,
+                ceiling: bounds.max_writers,
+                window_start: None,
+                window_rows: 0,
+                window_batches: 0,
+                last_throughput: None,
+            }),
+        }
+    }
+
+    pub fn batch_size(&self) -> usize {
+        self.state.lock().unwrap().batch_size
+    }
+
+    /// Writers allowed to take batches right now
+    pub fn writers(&self) -> usize {
+        self.state.lock().unwrap().writers
+    }
+
+    /// Worker threads to start, one per writer the tuner may allow
+    pub fn max_writers(&self) -> usize {
+        self.bounds.max_writers
+    }
+
+    /// Whether writers back off under server load (`--backpressure`)
+    pub fn backs_off(&self) -> bool {
+        self.backpressure
+    }
+
+    /// Halve writers and batch size, and keep writers there until the load eases
+    pub fn throttle(&self, reason: &str) {
+        let mut state = self.state.lock().unwrap();
+        let writers = (state.writers / 2).max(1);
+        let batch_size = (state.batch_size / 2).max(self.bounds.min_batch);
+        info!(
+            "  🚦 {} throttled ({}): writers {} → {}, batch size {} → {}",
+            self.name, reason, state.writers, writers, state.batch_size, batch_size
+        );
+        state.writers = writers;
+        state.ceiling = writers;
+        state.batch_size = batch_size;
+        Self::restart_window(&mut state);
+    }
+
+    /// Allow at most `writers` writers (at least one) until the load eases
+    pub fn limit(&self, writers: usize) {
+        let mut state = self.state.lock().unwrap();
+        let ceiling = writers.clamp(1, self.bounds.max_writers);
+        if ceiling < state.writers {
+            info!("  🚦 {} connections running out: writers {} → {}", self.name, state.writers, ceiling);
+            state.writers = ceiling;
+            Self::restart_window(&mut state);
+        }
+        state.ceiling = ceiling;
+    }
+
+    /// Allow one more writer than the load did. Fixed tuners take it up straight
+    /// away; adaptive ones climb to it when throughput improves.
+    pub fn relax(&self) {
+        let mut state = self.state.lock().unwrap();
+        if state.ceiling >= self.bounds.max_writers {
+            return;
+        }
+        state.ceiling += 1;
+        if !self.adaptive {
+            info!("  🚦 {} load eased: writers {} → {}", self.name, state.writers, state.ceiling);
+            state.writers = state.ceiling;
+        }
+    }
+
+    fn restart_window(state: &mut State) {
+        state.window_start = None;
+        state.window_rows = 0;
+        state.window_batches = 0;
+        state.last_throughput = None;
+    }
+
+    /// Record an upsert of `rows` rows that took `elapsed`
+    pub fn record(&self, rows: usize, elapsed: Duration) {
+        self.record_at(rows, elapsed, Instant::now());
+    }
+
+    fn record_at(&self, rows: usize, elapsed: Duration, now: Instant) {
+        if !self.adaptive {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+
+        // The last batch of a stage is usually partial and says little about latency
+        if rows * 2 >= state.batch_size {
+            let resized = if elapsed > SLOW_BATCH {
+                (state.batch_size / 2).max(self.bounds.min_batch)
+            } else if elapsed < FAST_BATCH {
+                (state.batch_size * 3 / 2).min(self.bounds.max_batch)
+            } else {
+                state.batch_size
+            };
+            if resized != state.batch_size {
+                info!(
+                    "  🎛️  {} batch size {} → {} ({} ms per batch)",
+                    self.name,
+                    state.batch_size,
+                    resized,
+                    elapsed.as_millis()
+                );
+                state.batch_size = resized;
+            }
+        }
+
+        if self.bounds.max_writers > 1 {
+            self.sample_throughput(&mut state, rows, elapsed, now);
+        }
+    }
+
+    fn sample_throughput(&self, state: &mut State, rows: usize, elapsed: Duration, now: Instant) {
+        let start = *state.window_start.get_or_insert(now - elapsed);
+        state.window_rows += rows;
+        state.window_batches += 1;
+        if state.window_batches < WINDOW_BATCHES_PER_WRITER * state.writers {
+            return;
+        }
+
+        let throughput = state.window_rows as f64 / (now - start).as_secs_f64().max(1e-3);
+        if state.last_throughput.is_some_and(|last| throughput < last * (1.0 - MIN_GAIN)) {
+            state.adding_writers = !state.adding_writers;
+        }
+        if (state.adding_writers && state.writers >= state.ceiling) || (!state.adding_writers && state.writers == 1) {
+            state.adding_writers = !state.adding_writers;
+        }
+
+        let writers = if state.adding_writers { state.writers + 1 } else { state.writers - 1 };
+        // Held at one writer by backpressure
+        let writers = writers.min(state.ceiling);
+        if writers != state.writers {
+            info!(
+                "  🎛️  {} writers {} → {} ({:.0} rows/s)",
+                self.name, state.writers, writers, throughput
+            );
+        }
+        state.writers = writers;
+        state.last_throughput = Some(throughput);
+        state.window_start = Some(now);
+        state.window_rows = 0;
+        state.window_batches = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tuner(batch_size: usize, max_writers: usize) -> Tuner {
+        Tuner {
+            name: "player_stats",
+            adaptive: true,
+            backpressure: false,
+            bounds: Bounds { min_batch: 100, max_batch: 1000, max_writers },
+            state: Mutex::new(State {
+                batch_size,
+                writers: 1,
+                adding_writers: true,
+                ceiling: max_writers,
+                window_start: None,
+                window_rows: 0,
+                window_batches: 0,
+                last_throughput: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_batch_size_follows_latency_within_bounds() {
+        let tuner = tuner(500, 1);
+        tuner.record(500, Duration::from_millis(100));
+        assert_eq!(tuner.batch_size(), 750);
+        tuner.record(750, Duration::from_millis(100));
+        assert_eq!(tuner.batch_size(), 1000);
+        tuner.record(1000, Duration::from_millis(800));
+        assert_eq!(tuner.batch_size(), 1000);
+
+        for _ in 0..5 {
+            let size = tuner.batch_size();
+            tuner.record(size, Duration::from_secs(5));
+        }
+        assert_eq!(tuner.batch_size(), 100);
+        assert_eq!(tuner.writers(), 1);
+    }
+
+    #[test]
+    fn test_partial_batch_does_not_resize() {
+        let tuner = tuner(500, 1);
+        tuner.record(40, Duration::from_secs(5));
+        assert_eq!(tuner.batch_size(), 500);
+    }
+
+    #[test]
+    fn test_writers_climb_until_throughput_drops() {
+        let tuner = tuner(500, 4);
+        let mut now = Instant::now();
+        // Rows per second for each window as writers go 1, 2, 3, 2
+        for rate in [1000.0, 1800.0, 1200.0] {
+            let batches = WINDOW_BATCHES_PER_WRITER * tuner.writers();
+            for _ in 0..batches {
+                now += Duration::from_secs_f64(500.0 / rate);
+                tuner.record_at(500, Duration::from_millis(500), now);
+            }
+        }
+        assert_eq!(tuner.writers(), 2);
+    }
+
+    #[test]
+    fn test_fixed_tuner_ignores_latency() {
+        let mut tuner = tuner(500, 4);
+        tuner.adaptive = false;
+        tuner.record(500, Duration::from_secs(5));
+        assert_eq!((tuner.batch_size(), tuner.writers()), (500, 1));
+    }
+
+    #[test]
+    fn test_backpressure_caps_writers_until_load_eases() {
+        let mut tuner = tuner(800, 4);
+        tuner.adaptive = false;
+        tuner.state.lock().unwrap().writers = 4;
+
+        tuner.throttle("statement timeout");
+        assert_eq!((tuner.batch_size(), tuner.writers()), (400, 2));
+        tuner.limit(0);
+        assert_eq!(tuner.writers(), 1);
+        tuner.relax();
+        tuner.relax();
+        assert_eq!(tuner.writers(), 3);
+        tuner.relax();
+        tuner.relax();
+        assert_eq!(tuner.writers(), 4);
+    }
+}
@@ -0,0 +1,248 @@
+use anyhow::{Result, Context};
+use postgres::Client;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Previous games averaged into the baseline a week is compared against
+pub const BASELINE_GAMES: usize = 3;
+
+/// One player's usage in a game
+#[derive(Debug, Clone)]
+pub struct UsageLine {
+    pub player_id: Uuid,
+    pub player_name: String,
+    pub position: Option<String>,
+    pub season_type: String,
+    pub week: i32,
+    /// Share of the team's offensive snaps (0-1)
+    pub snap_share: Option<f64>,
+    /// Carries plus receptions
+    pub touches: Option<f64>,
+    /// Share of the team's targets (0-1)
+    pub target_share: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageMetric {
+    SnapShare,
+    Touches,
+    TargetShare,
+}
+
+impl UsageMetric {
+    pub const ALL: [UsageMetric; 3] = [UsageMetric::SnapShare, UsageMetric::Touches, UsageMetric::TargetShare];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UsageMetric::SnapShare => "snap_share",
+            UsageMetric::Touches => "touches",
+            UsageMetric::TargetShare => "target_share",
+        }
+    }
+
+    /// Smallest change from the baseline that raises an alert
+    pub fn threshold(&self) -> f64 {
+        match self {
+            UsageMetric::SnapShare => 0.20,
+            UsageMetric::Touches => 8.0,
+            UsageMetric::TargetShare => 0.10,
+        }
+    }
+
+    fn value(&self, line: &UsageLine) -> Option<f64> {
+        match self {
+            UsageMetric::SnapShare => line.snap_share,
+            UsageMetric::Touches => line.touches,
+            UsageMetric::TargetShare => line.target_share,
+        }
+    }
+}
+
+/// A week where a player's usage moved past a metric's threshold
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageAlert {
+    pub player_id: Uuid,
+    pub player_name: String,
+    pub position: Option<String>,
+    pub season_type: String,
+    pub week: i32,
+    pub metric: UsageMetric,
+    /// Average over the player's previous `BASELINE_GAMES` games with a value
+    pub baseline: f64,
+    pub value: f64,
+}
+
+impl UsageAlert {
+    pub fn delta(&self) -> f64 {
+        self.value - self.baseline
+    }
+
+    /// `spike` or `drop`
+    pub fn direction(&self) -> &'static str {
+        if self.delta() > 0.0 { "spike" } else { "drop" }
+    }
+}
+
+/// Usage spikes and drops in each player's games, in order, per season type.
+///
+/// Each game is compared against the average of the player's previous
+/// `BASELINE_GAMES` games with a value for the metric, so the first game of a
+/// season type never alerts.
+pub fn detect_usage_alerts(lines: &[UsageLine]) -> Vec<UsageAlert> {
+    let mut timelines: BTreeMap<(Uuid, &str), BTreeMap<i32, &UsageLine>> = BTreeMap::new();
+    for line in lines {
+        timelines
+            .entry((line.player_id, line.season_type.as_str()))
+            .or_default()
+            .insert(line.week, line);
+    }
+
+    let mut alerts = Vec::new();
+    for games in timelines.values() {
+        for metric in UsageMetric::ALL {
+            let mut history: Vec<f64> = Vec::new();
+            for line in games.values() {
+                let Some(value) = metric.value(line) else { continue };
+                if !history.is_empty() {
+                    let recent = &history[history.len().saturating_sub(BASELINE_GAMES)..];
+                    let baseline = recent.iter().sum::<f64>() / recent.len() as f64;
+                    if (value - baseline).abs() >= metric.threshold() {
+                        alerts.push(UsageAlert {
+                            player_id: line.player_id,
+                            player_name: line.player_name.clone(),
+                            position: line.position.clone(),
+                            season_type: line.season_type.clone(),
+                            week: line.week,
+                            metric,
+                            baseline,
+                            value,
+                        });
+                    }
+                }
+                history.push(value);
+            }
+        }
+    }
+    alerts
+}
+
+/// Rebuild a season's usage alerts from game_stats and snap_counts. Returns the
+/// alerts, ordered by week.
+pub fn refresh_usage_alerts(client: &mut Client, season: i32) -> Result<Vec<UsageAlert>> {
+    let lines: Vec<UsageLine> = client
+        .query(
+            "SELECT g.player_id, p.name, p.position, g.season_type, g.week, sc.offense_pct::float8,
+                    CASE WHEN g.carries IS NULL AND g.receptions IS NULL THEN NULL
+                         ELSE (COALESCE(g.carries, 0) + COALESCE(g.receptions, 0))::float8 END,
+                    g.target_share::float8
+             FROM game_stats g
+             JOIN players p ON p.id = g.player_id
+             LEFT JOIN snap_counts sc ON sc.player_id = g.player_id AND sc.season = g.season
+                  AND sc.week = g.week AND sc.season_type = g.season_type
+             WHERE g.season = $1 AND g.season_type <> 'PRE'",
+            &[&season],
+        )
+        .context("Failed to load usage")?
+        .iter()
+        .map(|row| UsageLine {
+            player_id: row.get(0),
+            player_name: row.get(1),
+            position: row.get(2),
+            season_type: row.get(3),
+            week: row.get(4),
+            snap_share: row.get(5),
+            touches: row.get(6),
+            target_share: row.get(7),
+        })
+        .collect();
+
+    let mut alerts = detect_usage_alerts(&lines);
+    alerts.sort_by(|a, b| (a.week, &a.player_name).cmp(&(b.week, &b.player_name)));
+
+    let mut tx = client.transaction()?;
+    tx.execute("DELETE FROM usage_alerts WHERE season = $1", &[&season])?;
+    for alert in &alerts {
+        tx.execute(
+            "INSERT INTO usage_alerts (season, season_type, week, player_id, player_name, position, metric,
+                 direction, baseline, value, delta)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9::float8, $10::float8, $11::float8)",
+            &[
+                &season,
+                &alert.season_type,
+                &alert.week,
+                &alert.player_id,
+                &alert.player_name,
+                &alert.position,
+                &alert.metric.as_str(),
+                &alert.direction(),
+                &alert.baseline,
+                &alert.value,
+                &alert.delta(),
+            ],
+        )?;
+    }
+    tx.commit()?;
+
+    Ok(alerts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(player: Uuid, week: i32, snap_share: Option<f64>, touches: f64) -> UsageLine {
+        UsageLine {
+            player_id: player,
+            player_name: "Back".to_string(),
+            position: Some("RB".to_string()),
+            season_type: "REG".to_string(),
+            week,
+            snap_share,
+            touches: Some(touches),
+            target_share: None,
+        }
+    }
+
+    #[test]
+    fn test_spike_against_trailing_average() {
+        let player = Uuid::new_v4();
+        let lines = vec![
+            line(player, 1, Some(0.30), 6.0),
+            line(player, 2, Some(0.35), 8.0),
+            line(player, 3, Some(0.40), 7.0),
+            line(player, 4, Some(0.75), 21.0),
+        ];
+
+        let alerts = detect_usage_alerts(&lines);
+        assert_eq!(alerts.len(), 2);
+        assert!(alerts.iter().all(|a| a.week == 4 && a.direction() == "spike"));
+
+        let touches = alerts.iter().find(|a| a.metric == UsageMetric::Touches).unwrap();
+        assert_eq!(touches.baseline, 7.0);
+        assert_eq!(touches.delta(), 14.0);
+    }
+
+    #[test]
+    fn test_drop_and_missing_values_skipped() {
+        let player = Uuid::new_v4();
+        let lines = vec![
+            line(player, 1, Some(0.80), 18.0),
+            line(player, 2, None, 17.0),
+            line(player, 3, Some(0.45), 15.0),
+        ];
+
+        let alerts = detect_usage_alerts(&lines);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].metric, UsageMetric::SnapShare);
+        assert_eq!(alerts[0].direction(), "drop");
+        assert_eq!(alerts[0].baseline, 0.80);
+    }
+
+    #[test]
+    fn test_first_game_never_alerts() {
+        let lines = vec![line(Uuid::new_v4(), 1, Some(1.0), 30.0)];
+        assert!(detect_usage_alerts(&lines).is_empty());
+    }
+}
@@ -1,8 +1,9 @@
 use anyhow::{Result, anyhow};
-use log::{warn, info};
+use log::warn;
 
 use crate::parser::{RosterPlayer, PlayerStat, Game};
 
+#[derive(Default)]
 pub struct DataValidator;
 
 impl DataValidator {
@@ -53,7 +54,7 @@ impl DataValidator {
 
         // Validate weight (if present)
         if let Some(weight) = player.weight {
-            if weight < 150 || weight > 400 {
+            if !(150..=400).contains(&weight) {
                 warn!("Unusual weight: {} for {}", weight, player.full_name);
             }
         }
@@ -88,19 +89,19 @@ impl DataValidator {
         }
 
         // Validate game type
-        let valid_types = vec!["REG", "PRE", "POST", "WC", "DIV", "CON", "SB"];
+        let valid_types = ["REG", "PRE", "POST", "WC", "DIV", "CON", "SB"];
         if !valid_types.contains(&game.game_type.as_str()) {
             warn!("Unknown game type: {}", game.game_type);
         }
 
         // Validate scores (if present)
         if let Some(home_score) = game.home_score {
-            if home_score < 0 || home_score > 100 {
+            if !(0..=100).contains(&home_score) {
                 warn!("Unusual home score: {} in {}", home_score, game.game_id);
             }
         }
         if let Some(away_score) = game.away_score {
-            if away_score < 0 || away_score > 100 {
+            if !(0..=100).contains(&away_score) {
                 warn!("Unusual away score: {} in {}", away_score, game.game_id);
             }
         }
@@ -126,87 +127,30 @@ impl DataValidator {
         }
 
         // Validate season type
-        let valid_types = vec!["REG", "PRE", "POST"];
+        let valid_types = ["REG", "PRE", "POST"];
         if !valid_types.contains(&stat.season_type.as_str()) {
             warn!("Unknown season type: {}", stat.season_type);
         }
 
         // Validate reasonable stat ranges (warnings only)
         if let Some(yards) = stat.passing_yards {
-            if yards < 0.0 || yards > 600.0 {
+            if !(0.0..=600.0).contains(&yards) {
                 warn!("Unusual passing yards: {} for {}", yards, stat.player_id);
             }
         }
         if let Some(yards) = stat.rushing_yards {
-            if yards < -20.0 || yards > 300.0 {
+            if !(-20.0..=300.0).contains(&yards) {
                 warn!("Unusual rushing yards: {} for {}", yards, stat.player_id);
             }
         }
         if let Some(yards) = stat.receiving_yards {
-            if yards < 0.0 || yards > 300.0 {
+            if !(0.0..=300.0).contains(&yards) {
                 warn!("Unusual receiving yards: {} for {}", yards, stat.player_id);
             }
         }
 
         Ok(())
     }
-
-    /// Validate batch of players
-    pub fn validate_player_batch(&self, players: &[RosterPlayer]) -> (usize, usize) {
-        let mut valid = 0;
-        let mut invalid = 0;
-
-        for player in players {
-            match self.validate_player(player) {
-                Ok(_) => valid += 1,
-                Err(e) => {
-                    warn!("Invalid player {}: {}", player.gsis_id, e);
-                    invalid += 1;
-                }
-            }
-        }
-
-        info!("Player validation: {} valid, {} invalid", valid, invalid);
-        (valid, invalid)
-    }
-
-    /// Validate batch of games
-    pub fn validate_game_batch(&self, games: &[Game]) -> (usize, usize) {
-        let mut valid = 0;
-        let mut invalid = 0;
-
-        for game in games {
-            match self.validate_game(game) {
-                Ok(_) => valid += 1,
-                Err(e) => {
-                    warn!("Invalid game {}: {}", game.game_id, e);
-                    invalid += 1;
-                }
-            }
-        }
-
-        info!("Game validation: {} valid, {} invalid", valid, invalid);
-        (valid, invalid)
-    }
-
-    /// Validate batch of stats
-    pub fn validate_stat_batch(&self, stats: &[PlayerStat]) -> (usize, usize) {
-        let mut valid = 0;
-        let mut invalid = 0;
-
-        for stat in stats {
-            match self.validate_stat(stat) {
-                Ok(_) => valid += 1,
-                Err(e) => {
-                    warn!("Invalid stat for {}: {}", stat.player_id, e);
-                    invalid += 1;
-                }
-            }
-        }
-
-        info!("Stat validation: {} valid, {} invalid", valid, invalid);
-        (valid, invalid)
-    }
 }
 
 #[cfg(test)]
@@ -246,6 +190,7 @@ mod tests {
             rookie_year: Some(2017),
             draft_club: Some("KC".to_string()),
             draft_number: Some(10),
+            week: None,
         };
 
         assert!(validator.validate_player(&valid_player).is_ok());
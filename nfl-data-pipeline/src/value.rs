@@ -0,0 +1,60 @@
+use anyhow::{Result, Context};
+use postgres::Client;
+
+/// Full-PPR fantasy points for one game_stats row
+pub const PPR_POINTS_SQL: &str = "
+    COALESCE(passing_yards, 0) * 0.04 + COALESCE(passing_tds, 0) * 4 - COALESCE(interceptions, 0) * 2
+    + COALESCE(rushing_yards, 0) * 0.1 + COALESCE(rushing_tds, 0) * 6
+    + COALESCE(receptions, 0) + COALESCE(receiving_yards, 0) * 0.1 + COALESCE(receiving_tds, 0) * 6
+    - COALESCE(fumbles_lost, 0) * 2 + COALESCE(two_point_conversions, 0) * 2";
+
+/// Rebuild a season's value metrics: regular-season PPR production under the
+/// contract in effect that season (latest signed whose term covers it).
+///
+/// A contract signed in the player's draft year is treated as the rookie deal;
+/// draft slots come from draft_picks, falling back to OverTheCap's own draft
+/// columns. Returns the number of players written.
+pub fn refresh_value_metrics(client: &mut Client, season: i32) -> Result<u64> {
+    let mut tx = client.transaction()?;
+    tx.execute("DELETE FROM value_metrics WHERE season = $1", &[&season])?;
+
+    let written = tx
+        .execute(
+            &format!(
+                "WITH contract AS (
+                     SELECT DISTINCT ON (c.player_id) c.player_id, c.year_signed, c.apy, c.apy_cap_pct,
+                            c.draft_year, c.draft_round, c.draft_overall
+                     FROM player_contracts c
+                     WHERE c.player_id IS NOT NULL AND c.apy > 0
+                       AND c.year_signed <= $1 AND $1 < c.year_signed + GREATEST(COALESCE(c.years, 1), 1)
+                     ORDER BY c.player_id, c.year_signed DESC, c.apy DESC
+                 ),
+                 production AS (
+                     SELECT player_id, COUNT(*)::int AS games, SUM({PPR_POINTS_SQL}) AS points
+                     FROM game_stats
+                     WHERE season = $1 AND season_type = 'REG'
+                     GROUP BY player_id
+                 )
+                 INSERT INTO value_metrics (season, player_id, position, contract_year_signed, apy, apy_cap_pct,
+                     rookie_contract, draft_round, draft_pick, games, fantasy_points_ppr, points_per_million,
+                     cost_per_point, updated_at)
+                 SELECT $1, k.player_id, p.position, k.year_signed, k.apy, k.apy_cap_pct,
+                        COALESCE(COALESCE(d.season, k.draft_year) = k.year_signed, FALSE),
+                        COALESCE(d.round, k.draft_round), COALESCE(d.pick, k.draft_overall),
+                        pr.games, pr.points, pr.points / k.apy,
+                        CASE WHEN pr.points > 0 THEN k.apy * 1000000 / pr.points END,
+                        NOW()
+                 FROM contract k
+                 JOIN production pr ON pr.player_id = k.player_id
+                 JOIN players p ON p.id = k.player_id
+                 LEFT JOIN LATERAL (
+                     SELECT season, round, pick FROM draft_picks WHERE player_id = k.player_id ORDER BY season DESC LIMIT 1
+                 ) d ON TRUE"
+            ),
+            &[&season],
+        )
+        .context("Failed to refresh value metrics")?;
+
+    tx.commit()?;
+    Ok(written)
+}
@@ -0,0 +1,163 @@
+use anyhow::{anyhow, Context, Result};
+use postgres::{Client, Transaction};
+use sha2::{Digest, Sha256};
+
+use crate::migrations;
+
+/// A convenience view from `migrations/views/`, embedded at build time
+pub struct View {
+    pub name: &'static str,
+    /// Lowest migration version providing every column the view reads
+    pub requires: i32,
+    pub sql: &'static str,
+}
+
+macro_rules! view {
+    ($name:literal, $requires:expr) => {
+        View { name: $name, requires: $requires, sql: include_str!(concat!("../../migrations/views/", $name, ".sql")) }
+    };
+}
+
+/// The view pack installed by `--mode views`. New files in `migrations/views/` are added here.
+pub const VIEWS: &[View] = &[
+    view!("player_game_logs", 43),
+    view!("current_rosters", 2),
+    view!("season_leaders", 39),
+    view!("team_schedules", 21),
+];
+
+impl View {
+    pub fn checksum(&self) -> String {
+        format!("{:x}", Sha256::digest(self.sql.as_bytes()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewState {
+    Missing,
+    Current,
+    /// Installed from an older definition; `--mode views` replaces it
+    Outdated,
+}
+
+impl ViewState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ViewState::Missing => "missing",
+            ViewState::Current => "current",
+            ViewState::Outdated => "outdated",
+        }
+    }
+}
+
+/// Create or replace every view in one transaction, recording each definition's
+/// checksum in `schema_views`. Refused until the schema has the migrations the
+/// views read from.
+pub fn install(client: &mut Client) -> Result<usize> {
+    let version = migrations::current_version(client)?;
+    if let Some(view) = VIEWS.iter().find(|view| view.requires > version) {
+        return Err(anyhow!(
+            "View {} needs migration {} (schema is at {}); run --mode migrate first",
+            view.name,
+            view.requires,
+            version
+        ));
+    }
+
+    let mut tx = client.transaction()?;
+    create(&mut tx)?;
+    tx.commit()?;
+    Ok(VIEWS.len())
+}
+
+/// Create or replace every view in `tx`'s current schema and record the
+/// checksums, without checking the schema's migration version
+pub fn create(tx: &mut Transaction) -> Result<()> {
+    tx.batch_execute(
+        "SET LOCAL client_min_messages = warning;
+         CREATE TABLE IF NOT EXISTS schema_views (
+             name TEXT PRIMARY KEY,
+             checksum TEXT NOT NULL,
+             installed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+         )",
+    )?;
+    for view in VIEWS {
+        // Dropped first: CREATE OR REPLACE can't remove or reorder columns
+        tx.batch_execute(&format!("DROP VIEW IF EXISTS {}", view.name))?;
+        tx.batch_execute(view.sql).with_context(|| format!("Failed to create view {}", view.name))?;
+        tx.execute(
+            "INSERT INTO schema_views (name, checksum, installed_at) VALUES ($1, $2, NOW())
+             ON CONFLICT (name) DO UPDATE SET checksum = EXCLUDED.checksum, installed_at = NOW()",
+            &[&view.name, &view.checksum()],
+        )?;
+    }
+    Ok(())
+}
+
+/// Drop every view in the pack and forget its checksum
+pub fn drop(client: &mut Client) -> Result<usize> {
+    let mut tx = client.transaction()?;
+    tx.batch_execute("SET LOCAL client_min_messages = warning")?;
+    for view in VIEWS {
+        tx.batch_execute(&format!("DROP VIEW IF EXISTS {}", view.name))
+            .with_context(|| format!("Failed to drop view {} (other views depend on it?)", view.name))?;
+    }
+    tx.batch_execute("DROP TABLE IF EXISTS schema_views")?;
+    tx.commit()?;
+    Ok(VIEWS.len())
+}
+
+/// Each view's state against this build's definitions
+pub fn status(client: &mut Client) -> Result<Vec<(&'static View, ViewState)>> {
+    let exists: bool = client.query_one("SELECT to_regclass('public.schema_views') IS NOT NULL", &[])?.get(0);
+    let recorded: Vec<(String, String)> = match exists {
+        false => Vec::new(),
+        true => client
+            .query(
+                "SELECT v.name, v.checksum FROM schema_views v
+                 WHERE to_regclass('public.' || v.name) IS NOT NULL",
+                &[],
+            )?
+            .iter()
+            .map(|row| (row.get(0), row.get(1)))
+            .collect(),
+    };
+
+    Ok(VIEWS
+        .iter()
+        .map(|view| {
+            let state = match recorded.iter().find(|(name, _)| name == view.name) {
+                None => ViewState::Missing,
+                Some((_, checksum)) if *checksum == view.checksum() => ViewState::Current,
+                Some(_) => ViewState::Outdated,
+            };
+            (view, state)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_view_file_is_embedded_with_its_header() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../migrations/views");
+        let mut files: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.ends_with(".sql"))
+            .collect();
+        files.sort();
+
+        let mut embedded: Vec<String> = VIEWS.iter().map(|view| format!("{}.sql", view.name)).collect();
+        embedded.sort();
+        assert_eq!(embedded, files);
+
+        for view in VIEWS {
+            assert!(view.sql.starts_with(&format!("-- View: {}\n-- Requires: migration {:03}", view.name, view.requires)));
+            assert!(view.sql.contains(&format!("CREATE VIEW {} AS", view.name)));
+            assert!(view.requires <= migrations::MIGRATIONS.last().unwrap().version);
+        }
+    }
+}
@@ -0,0 +1,356 @@
+use anyhow::{Context, Result};
+use postgres::Transaction;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Sustained wind (mph) from which an outdoor game counts as windy
+pub const WIND_MPH: i32 = 15;
+/// Kickoff temperature (°F) at or below which an outdoor game counts as cold
+pub const COLD_F: i32 = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Weather {
+    Wind,
+    Cold,
+}
+
+impl Weather {
+    pub const ALL: [Weather; 2] = [Weather::Wind, Weather::Cold];
+
+    /// `player_splits.split` name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Weather::Wind => "wind",
+            Weather::Cold => "cold",
+        }
+    }
+}
+
+/// Conditions at kickoff, from the schedule's roof and weather columns
+#[derive(Debug, Clone, Default)]
+pub struct GameWeather {
+    /// outdoors, dome, closed or open
+    pub roof: Option<String>,
+    pub temp: Option<i32>,
+    pub wind: Option<i32>,
+}
+
+impl GameWeather {
+    /// Domes and closed retractable roofs
+    fn indoors(&self) -> bool {
+        matches!(self.roof.as_deref(), Some("dome") | Some("closed"))
+    }
+
+    pub fn is(&self, weather: Weather) -> bool {
+        !self.indoors()
+            && match weather {
+                Weather::Wind => self.wind.is_some_and(|mph| mph >= WIND_MPH),
+                Weather::Cold => self.temp.is_some_and(|degrees| degrees <= COLD_F),
+            }
+    }
+
+    /// Indoors, or outdoors with recorded weather that is neither windy nor
+    /// cold. Outdoor games missing a reading are in no split.
+    pub fn is_fair(&self) -> bool {
+        self.indoors()
+            || (self.temp.is_some() && self.wind.is_some() && !Weather::ALL.iter().any(|weather| self.is(*weather)))
+    }
+}
+
+/// Stat totals over a set of games
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Totals {
+    pub games: i32,
+    /// Games with a pass attempt, the denominator for passing yards per game
+    pub passing_games: i32,
+    pub completions: i32,
+    pub attempts: i32,
+    pub passing_yards: i32,
+    pub passing_tds: i32,
+    pub interceptions: i32,
+    pub rushing_yards: i32,
+    pub rushing_tds: i32,
+    pub receiving_yards: i32,
+    pub receiving_tds: i32,
+    pub receptions: i32,
+    pub targets: i32,
+    pub field_goals_made: i32,
+    pub field_goals_attempted: i32,
+}
+
+impl Totals {
+    fn add(&mut self, other: &Totals) {
+        self.games += other.games;
+        self.passing_games += other.passing_games;
+        self.completions += other.completions;
+        self.attempts += other.attempts;
+        self.passing_yards += other.passing_yards;
+        self.passing_tds += other.passing_tds;
+        self.interceptions += other.interceptions;
+        self.rushing_yards += other.rushing_yards;
+        self.rushing_tds += other.rushing_tds;
+        self.receiving_yards += other.receiving_yards;
+        self.receiving_tds += other.receiving_tds;
+        self.receptions += other.receptions;
+        self.targets += other.targets;
+        self.field_goals_made += other.field_goals_made;
+        self.field_goals_attempted += other.field_goals_attempted;
+    }
+
+    pub fn passing_yards_per_game(&self) -> Option<f64> {
+        (self.passing_games > 0).then(|| self.passing_yards as f64 / self.passing_games as f64)
+    }
+
+    pub fn yards_per_attempt(&self) -> Option<f64> {
+        (self.attempts > 0).then(|| self.passing_yards as f64 / self.attempts as f64)
+    }
+
+    pub fn completion_pct(&self) -> Option<f64> {
+        (self.attempts > 0).then(|| self.completions as f64 / self.attempts as f64)
+    }
+
+    pub fn fg_pct(&self) -> Option<f64> {
+        (self.field_goals_attempted > 0).then(|| self.field_goals_made as f64 / self.field_goals_attempted as f64)
+    }
+}
+
+/// One player's game with the conditions it was played in
+#[derive(Debug, Clone)]
+pub struct WeatherLine {
+    pub player_id: Uuid,
+    pub week: i32,
+    pub weather: GameWeather,
+    /// The game's stats, with `games` 1
+    pub stats: Totals,
+}
+
+/// A player's games in one condition, compared with their fair-weather games
+#[derive(Debug, Clone)]
+pub struct WeatherSplit {
+    pub player_id: Uuid,
+    pub weather: Weather,
+    pub first_week: i32,
+    pub last_week: i32,
+    pub totals: Totals,
+    pub baseline: Totals,
+}
+
+impl WeatherSplit {
+    /// Condition rate minus fair-weather rate, when both exist
+    fn delta(&self, rate: fn(&Totals) -> Option<f64>) -> Option<f64> {
+        Some(rate(&self.totals)? - rate(&self.baseline)?)
+    }
+
+    pub fn passing_yards_per_game_delta(&self) -> Option<f64> {
+        self.delta(Totals::passing_yards_per_game)
+    }
+
+    pub fn yards_per_attempt_delta(&self) -> Option<f64> {
+        self.delta(Totals::yards_per_attempt)
+    }
+
+    pub fn completion_pct_delta(&self) -> Option<f64> {
+        self.delta(Totals::completion_pct)
+    }
+
+    pub fn fg_pct_delta(&self) -> Option<f64> {
+        self.delta(Totals::fg_pct)
+    }
+
+    /// Passers and kickers with games both in the condition and in fair weather
+    fn has_delta(&self) -> bool {
+        self.passing_yards_per_game_delta().is_some() || self.fg_pct_delta().is_some()
+    }
+}
+
+/// Wind and cold splits per player. A game can be both windy and cold; only
+/// players with a passing or kicking delta get a split.
+pub fn weather_splits(lines: &[WeatherLine]) -> Vec<WeatherSplit> {
+    let mut players: BTreeMap<Uuid, Vec<&WeatherLine>> = BTreeMap::new();
+    for line in lines {
+        players.entry(line.player_id).or_default().push(line);
+    }
+
+    let mut splits = Vec::new();
+    for (player_id, games) in players {
+        let mut baseline = Totals::default();
+        for line in games.iter().filter(|line| line.weather.is_fair()) {
+            baseline.add(&line.stats);
+        }
+
+        for weather in Weather::ALL {
+            let matching: Vec<&&WeatherLine> = games.iter().filter(|line| line.weather.is(weather)).collect();
+            let (Some(first_week), Some(last_week)) =
+                (matching.iter().map(|line| line.week).min(), matching.iter().map(|line| line.week).max())
+            else {
+                continue;
+            };
+
+            let mut totals = Totals::default();
+            for line in &matching {
+                totals.add(&line.stats);
+            }
+            let split = WeatherSplit { player_id, weather, first_week, last_week, totals, baseline };
+            if split.has_delta() {
+                splits.push(split);
+            }
+        }
+    }
+    splits
+}
+
+/// Write a season's wind and cold rows into `player_splits` (regular season and
+/// playoffs). Stat lines are matched to games through `game_stats.team_id`.
+/// Returns the number of rows written.
+pub fn write_weather_splits(tx: &mut Transaction, season: i32) -> Result<u64> {
+    let lines: Vec<WeatherLine> = tx
+        .query(
+            "SELECT g.player_id, g.week, gm.roof, gm.weather_temp, gm.weather_wind_speed,
+                    COALESCE(g.completions, 0), COALESCE(g.attempts, 0), COALESCE(g.passing_yards, 0),
+                    COALESCE(g.passing_tds, 0), COALESCE(g.interceptions, 0), COALESCE(g.rushing_yards, 0),
+                    COALESCE(g.rushing_tds, 0), COALESCE(g.receiving_yards, 0), COALESCE(g.receiving_tds, 0),
+                    COALESCE(g.receptions, 0), COALESCE(g.targets, 0), COALESCE(g.field_goals_made, 0),
+                    COALESCE(g.field_goals_attempted, 0)
+             FROM game_stats g
+             JOIN games gm ON gm.season = g.season AND gm.week = g.week AND gm.season_type = g.season_type
+                  AND g.team_id IN (gm.home_team_id, gm.away_team_id)
+             WHERE g.season = $1 AND g.season_type <> 'PRE'",
+            &[&season],
+        )
+        .context("Failed to load weather lines")?
+        .iter()
+        .map(|row| {
+            let attempts: i32 = row.get(6);
+            WeatherLine {
+                player_id: row.get(0),
+                week: row.get(1),
+                weather: GameWeather { roof: row.get(2), temp: row.get(3), wind: row.get(4) },
+                stats: Totals {
+                    games: 1,
+                    passing_games: (attempts > 0) as i32,
+                    completions: row.get(5),
+                    attempts,
+                    passing_yards: row.get(7),
+                    passing_tds: row.get(8),
+                    interceptions: row.get(9),
+                    rushing_yards: row.get(10),
+                    rushing_tds: row.get(11),
+                    receiving_yards: row.get(12),
+                    receiving_tds: row.get(13),
+                    receptions: row.get(14),
+                    targets: row.get(15),
+                    field_goals_made: row.get(16),
+                    field_goals_attempted: row.get(17),
+                },
+            }
+        })
+        .collect();
+
+    let mut written = 0;
+    for split in weather_splits(&lines) {
+        let t = &split.totals;
+        written += tx
+            .execute(
+                "INSERT INTO player_splits (
+                     player_id, season, split, first_week, last_week, games,
+                     passing_yards, rushing_yards, receiving_yards,
+                     passing_tds, rushing_tds, receiving_tds,
+                     receptions, targets, interceptions, completions, attempts,
+                     field_goals_made, field_goals_attempted, baseline_games,
+                     passing_yards_per_game_delta, yards_per_attempt_delta, completion_pct_delta, fg_pct_delta,
+                     updated_at
+                 )
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20,
+                         $21::float8, $22::float8, $23::float8, $24::float8, NOW())",
+                &[
+                    &split.player_id,
+                    &season,
+                    &split.weather.as_str(),
+                    &split.first_week,
+                    &split.last_week,
+                    &t.games,
+                    &t.passing_yards,
+                    &t.rushing_yards,
+                    &t.receiving_yards,
+                    &t.passing_tds,
+                    &t.rushing_tds,
+                    &t.receiving_tds,
+                    &t.receptions,
+                    &t.targets,
+                    &t.interceptions,
+                    &t.completions,
+                    &t.attempts,
+                    &t.field_goals_made,
+                    &t.field_goals_attempted,
+                    &split.baseline.games,
+                    &split.passing_yards_per_game_delta(),
+                    &split.yards_per_attempt_delta(),
+                    &split.completion_pct_delta(),
+                    &split.fg_pct_delta(),
+                ],
+            )
+            .with_context(|| format!("Failed to write {} split", split.weather.as_str()))?;
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weather(roof: &str, temp: Option<i32>, wind: Option<i32>) -> GameWeather {
+        GameWeather { roof: Some(roof.to_string()), temp, wind }
+    }
+
+    fn kick(player: Uuid, week: i32, weather: GameWeather, made: i32, attempted: i32) -> WeatherLine {
+        WeatherLine {
+            player_id: player,
+            week,
+            weather,
+            stats: Totals { games: 1, field_goals_made: made, field_goals_attempted: attempted, ..Totals::default() },
+        }
+    }
+
+    #[test]
+    fn test_conditions() {
+        let windy_cold = weather("outdoors", Some(20), Some(22));
+        assert!(windy_cold.is(Weather::Wind) && windy_cold.is(Weather::Cold) && !windy_cold.is_fair());
+
+        // Weather under a roof doesn't count
+        let dome = weather("dome", Some(20), Some(22));
+        assert!(!dome.is(Weather::Wind) && dome.is_fair());
+        assert!(weather("open", Some(30), Some(5)).is(Weather::Cold));
+
+        // Outdoors without a reading is neither a condition nor a baseline game
+        let unknown = weather("outdoors", Some(60), None);
+        assert!(!unknown.is(Weather::Wind) && !unknown.is_fair());
+    }
+
+    #[test]
+    fn test_deltas_against_fair_weather() {
+        let kicker = Uuid::new_v4();
+        let lines = vec![
+            kick(kicker, 1, weather("dome", None, None), 3, 3),
+            kick(kicker, 2, weather("outdoors", Some(65), Some(5)), 2, 2),
+            kick(kicker, 3, weather("outdoors", Some(40), Some(20)), 1, 2),
+            kick(kicker, 4, weather("outdoors", Some(40), None), 0, 3),
+        ];
+
+        let splits = weather_splits(&lines);
+        assert_eq!(splits.len(), 1);
+        let wind = &splits[0];
+        assert_eq!(wind.weather, Weather::Wind);
+        assert_eq!((wind.first_week, wind.last_week, wind.totals.games, wind.baseline.games), (3, 3, 1, 2));
+        assert_eq!(wind.fg_pct_delta(), Some(-0.5));
+        assert_eq!(wind.passing_yards_per_game_delta(), None);
+    }
+
+    #[test]
+    fn test_skips_players_without_passing_or_kicking() {
+        let mut line = kick(Uuid::new_v4(), 1, weather("outdoors", Some(10), Some(3)), 0, 0);
+        line.stats.rushing_yards = 80;
+        let mut fair = line.clone();
+        fair.weather = weather("dome", None, None);
+        assert!(weather_splits(&[line, fair]).is_empty());
+    }
+}
@@ -0,0 +1,154 @@
+//! `/live` on the serve mode API: a WebSocket that pushes each game live mode
+//! updates, relayed from Postgres notifications, so scoreboards don't poll.
+//! Server-to-client only: clients' text and binary frames are ignored.
+
+use anyhow::{anyhow, Result};
+use axum::extract::ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade};
+use axum::response::Response;
+use log::{debug, info, warn};
+use postgres::fallible_iterator::FallibleIterator;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, error::TrySendError, Receiver, Sender};
+use tokio::time::{self, Instant};
+
+use crate::database;
+use crate::live;
+
+/// Messages a client may fall behind by before it is dropped
+const CLIENT_QUEUE: usize = 256;
+/// A ping goes out after this long without a message, so idle proxies keep the connection
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+/// Wait before reconnecting the notification listener
+const RELAY_RETRY: Duration = Duration::from_secs(5);
+/// Largest message accepted from a client; they only send control frames
+const MAX_MESSAGE: usize = 64 * 1024;
+
+/// Connected clients, each with a bounded queue of messages to send
+#[derive(Default)]
+pub struct Hub {
+    clients: Mutex<Vec<Sender<Arc<str>>>>,
+}
+
+impl Hub {
+    pub fn subscribe(&self) -> Receiver<Arc<str>> {
+        let (sender, receiver) = mpsc::channel(CLIENT_QUEUE);
+        self.clients.lock().unwrap().push(sender);
+        receiver
+    }
+
+    /// Queue `message` for every client, dropping those gone or too far behind;
+    /// returns how many clients it was queued for
+    pub fn broadcast(&self, message: &str) -> usize {
+        let message: Arc<str> = message.into();
+        let mut clients = self.clients.lock().unwrap();
+        clients.retain(|client| match client.try_send(Arc::clone(&message)) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                warn!("⚠️  Dropping a /live client {} messages behind", CLIENT_QUEUE);
+                false
+            }
+            Err(TrySendError::Closed(_)) => false,
+        });
+        clients.len()
+    }
+}
+
+/// Forward `live::SCORES_CHANNEL` notifications to `hub` for the rest of the
+/// process, reconnecting when the listening session drops
+pub fn relay(database_url: String, hub: Arc<Hub>) {
+    loop {
+        if let Err(e) = listen(&database_url, &hub) {
+            warn!("⚠️  Live score relay stopped: {:#}; reconnecting in {}s", e, RELAY_RETRY.as_secs());
+        }
+        thread::sleep(RELAY_RETRY);
+    }
+}
+
+fn listen(database_url: &str, hub: &Hub) -> Result<()> {
+    let mut client = database::connect_unpooled(database_url)?;
+    client.batch_execute(&format!("LISTEN {}", live::SCORES_CHANNEL))?;
+    info!("📡 Relaying live score updates to /live");
+    let mut notifications = client.notifications();
+    let mut updates = notifications.blocking_iter();
+    while let Some(update) = updates.next()? {
+        let clients = hub.broadcast(update.payload());
+        debug!("Live update sent to {} clients: {}", clients, update.payload());
+    }
+    Err(anyhow!("connection closed"))
+}
+
+/// Accept a `/live` client: once upgraded, push every broadcast until it leaves
+pub fn upgrade(upgrade: WebSocketUpgrade, hub: Arc<Hub>) -> Response {
+    upgrade.max_message_size(MAX_MESSAGE).on_upgrade(move |socket| session(socket, hub))
+}
+
+async fn session(mut socket: WebSocket, hub: Arc<Hub>) {
+    let mut updates = hub.subscribe();
+    debug!("/live client connected");
+    let idle = time::sleep(PING_INTERVAL);
+    tokio::pin!(idle);
+    loop {
+        let sent = tokio::select! {
+            update = updates.recv() => match update {
+                Some(update) => socket.send(Message::Text(update.as_ref().into())).await,
+                // Dropped by the hub for falling behind
+                None => {
+                    let close = CloseFrame { code: close_code::POLICY, reason: "Too far behind".into() };
+                    let _ = socket.send(Message::Close(Some(close))).await;
+                    break;
+                }
+            },
+            () = &mut idle => socket.send(Message::Ping(Default::default())).await,
+            // Pings are answered by the socket itself; other messages are ignored
+            received = socket.recv() => match received {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => continue,
+            },
+        };
+        if sent.is_err() {
+            break;
+        }
+        idle.as_mut().reset(Instant::now() + PING_INTERVAL);
+    }
+    debug!("/live client disconnected");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::extract::ws::WebSocketUpgrade;
+    use axum::routing::get;
+    use axum::Router;
+    use futures_util::StreamExt;
+
+    #[test]
+    fn test_hub_drops_gone_clients() {
+        let hub = Hub::default();
+        let mut updates = hub.subscribe();
+        let gone = hub.subscribe();
+        drop(gone);
+        assert_eq!(hub.broadcast("{\"status\":\"final\"}"), 1);
+        assert_eq!(&*updates.try_recv().unwrap(), "{\"status\":\"final\"}");
+    }
+
+    #[tokio::test]
+    async fn test_live_client_receives_broadcasts() {
+        let hub = Arc::new(Hub::default());
+        let served = Arc::clone(&hub);
+        let app = Router::new().route("/live", get(move |ws: WebSocketUpgrade| async move { upgrade(ws, served) }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let (mut client, _) = tokio_tungstenite::connect_async(format!("ws://{}/live", addr)).await.unwrap();
+        // The session subscribes once the upgrade completes
+        while hub.broadcast("{\"status\":\"in_progress\"}") == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let message = client.next().await.unwrap().unwrap();
+        assert_eq!(message.into_text().unwrap(), "{\"status\":\"in_progress\"}");
+    }
+}
@@ -0,0 +1,217 @@
+use anyhow::{anyhow, Context, Result};
+use log::warn;
+use postgres::error::SqlState;
+use postgres::Client;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::backpressure;
+use crate::database::{self, Pool, PooledClient};
+use crate::dead_letter::Row;
+use crate::metrics;
+use crate::spill::Batch;
+use crate::tuning::Tuner;
+
+/// Parsed batches queued per worker before the parser blocks
+const QUEUED_BATCHES_PER_WORKER: usize = 2;
+/// How often a worker parked by the tuner checks whether it may resume
+const PARKED_POLL: Duration = Duration::from_millis(50);
+
+/// A batch that failed on its worker and was rolled back
+#[derive(Debug)]
+pub struct FailedBatch {
+    pub rows: usize,
+    pub error: String,
+    /// Source rows behind the batch, kept while dead letters are enabled
+    pub source_rows: Vec<Row>,
+}
+
+/// What the workers wrote once the channel drained
+#[derive(Debug, Default)]
+pub struct Outcome {
+    pub written: usize,
+    pub failed: Vec<FailedBatch>,
+}
+
+/// Upsert workers consuming parsed batches from a bounded channel, each on its
+/// own pooled connection.
+///
+/// Only set-based writers belong here: every batch is a single statement (or a
+/// COPY plus merge in one transaction), so batches commit independently and in
+/// any order. Overlapping keys across concurrent batches can deadlock; the loser
+/// is retried once, as is a batch cut off by a dropped connection.
+///
+/// One thread is started per writer the tuner may allow; threads beyond its
+/// current `writers()` stay parked and take no batches.
+pub struct ParallelWriter<T> {
+    sender: Option<SyncSender<Batch<T>>>,
+    workers: Vec<JoinHandle<Outcome>>,
+    closed: Arc<AtomicBool>,
+    /// Source bytes of the batches queued or being written
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl<T: Send + 'static> ParallelWriter<T> {
+    /// Start the workers writing with `write`, reporting each batch's latency to
+    /// `tuner`. A simulation's schema is selected on every connection they check out.
+    pub fn start<W>(pool: &Pool, search_path: Option<&str>, tuner: Arc<Tuner>, name: &'static str, write: W) -> Self
+    where
+        W: Fn(&mut Client, &[T]) -> Result<()> + Send + Sync + 'static,
+    {
+        let (sender, receiver) = mpsc::sync_channel(tuner.max_writers() * QUEUED_BATCHES_PER_WORKER);
+        let receiver = Arc::new(Mutex::new(receiver));
+        let write = Arc::new(write);
+        let closed = Arc::new(AtomicBool::new(false));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+
+        let workers = (0..tuner.max_writers())
+            .map(|index| {
+                let worker = Worker {
+                    index,
+                    pool: pool.clone(),
+                    search_path: search_path.map(String::from),
+                    name,
+                    tuner: Arc::clone(&tuner),
+                    closed: Arc::clone(&closed),
+                    in_flight: Arc::clone(&in_flight),
+                };
+                let receiver = Arc::clone(&receiver);
+                let write = Arc::clone(&write);
+                std::thread::spawn(move || worker.run(&receiver, write.as_ref()))
+            })
+            .collect();
+
+        ParallelWriter { sender: Some(sender), workers, closed, in_flight }
+    }
+
+    /// Queue a batch with its source rows and the source bytes it was parsed
+    /// from, blocking while every worker is busy and the queue is full
+    pub fn send(&self, batch: Vec<T>, source_rows: Vec<Row>, bytes: usize) -> Result<()> {
+        self.in_flight.fetch_add(bytes, Ordering::Relaxed);
+        self.sender
+            .as_ref()
+            .expect("writer already finished")
+            .send((batch, source_rows, bytes))
+            .map_err(|_| anyhow!("All upsert workers have stopped"))
+    }
+
+    /// Source bytes of the batches queued or being written
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// Close the channel and wait for the workers to drain it
+    pub fn finish(mut self) -> Result<Outcome> {
+        drop(self.sender.take());
+        self.closed.store(true, Ordering::Relaxed);
+        let mut outcome = Outcome::default();
+        for worker in self.workers.drain(..) {
+            let done = worker.join().map_err(|_| anyhow!("Upsert worker panicked"))?;
+            outcome.written += done.written;
+            outcome.failed.extend(done.failed);
+        }
+        Ok(outcome)
+    }
+}
+
+/// Stops parked workers when the parser bails out before `finish`
+impl<T> Drop for ParallelWriter<T> {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Relaxed);
+    }
+}
+
+struct Worker {
+    index: usize,
+    pool: Pool,
+    search_path: Option<String>,
+    name: &'static str,
+    tuner: Arc<Tuner>,
+    closed: Arc<AtomicBool>,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl Worker {
+    fn run<T>(self, receiver: &Mutex<Receiver<Batch<T>>>, write: &dyn Fn(&mut Client, &[T]) -> Result<()>) -> Outcome {
+        let mut outcome = Outcome::default();
+        let mut connection = None;
+
+        loop {
+            if self.index >= self.tuner.writers() {
+                if self.closed.load(Ordering::Relaxed) {
+                    break;
+                }
+                // Parked workers give their connection back to the pool
+                connection = None;
+                std::thread::sleep(PARKED_POLL);
+                continue;
+            }
+
+            // The lock is only held while waiting for the next batch
+            let Ok((batch, source_rows, bytes)) = receiver.lock().map_err(|_| ()).and_then(|r| r.recv().map_err(|_| ())) else {
+                break;
+            };
+
+            let started = Instant::now();
+            let mut result = self.write(&mut connection, write, &batch);
+            if let Err(e) = &result {
+                if database::is_connection_error(e) || is_deadlock(e) {
+                    warn!("  ⚠️  {} batch interrupted, retrying: {:#}", self.name, e);
+                    metrics::retry("batch");
+                    result = self.write(&mut connection, write, &batch);
+                } else if let Some(reason) = backpressure::overload(e).filter(|_| self.tuner.backs_off()) {
+                    self.tuner.throttle(reason);
+                    metrics::retry("batch");
+                    std::thread::sleep(backpressure::BACKOFF);
+                    result = self.write(&mut connection, write, &batch);
+                }
+            }
+
+            match result {
+                Ok(()) => {
+                    self.tuner.record(batch.len(), started.elapsed());
+                    metrics::batch_write(self.name, started.elapsed());
+                    outcome.written += batch.len();
+                }
+                Err(e) => {
+                    warn!("  ⚠️  {} batch of {} rows rolled back: {:#}", self.name, batch.len(), e);
+                    outcome.failed.push(FailedBatch { rows: batch.len(), error: format!("{:#}", e), source_rows });
+                }
+            }
+            self.in_flight.fetch_sub(bytes, Ordering::Relaxed);
+        }
+
+        outcome
+    }
+
+    /// Write on the worker's connection, checking out a new one if it has none
+    /// or the last one was closed
+    fn write<T>(
+        &self,
+        connection: &mut Option<PooledClient>,
+        write: &dyn Fn(&mut Client, &[T]) -> Result<()>,
+        batch: &[T],
+    ) -> Result<()> {
+        let client = match connection {
+            Some(client) if !client.is_closed() => client,
+            _ => {
+                let mut fresh = self.pool.get().context("Failed to check out a database connection")?;
+                database::select_search_path(&mut fresh, self.search_path.as_deref())?;
+                connection.insert(fresh)
+            }
+        };
+        write(client, batch)
+    }
+}
+
+fn is_deadlock(e: &anyhow::Error) -> bool {
+    e.chain().any(|cause| {
+        cause
+            .downcast_ref::<postgres::Error>()
+            .and_then(|e| e.code())
+            .is_some_and(|code| *code == SqlState::T_R_DEADLOCK_DETECTED)
+    })
+}
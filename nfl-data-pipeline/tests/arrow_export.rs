@@ -0,0 +1,52 @@
+//! The Arrow export as an embedding crate sees it: parsed, normalized season
+//! files from a `--source-dir` as record batches and IPC files, with no database.
+
+use arrow_array::{Array, RecordBatch, StringArray};
+use nfl_data_pipeline::arrow_export::write_ipc;
+use nfl_data_pipeline::{datasets, ArrowExport, Config};
+use std::fs::File;
+use std::path::PathBuf;
+
+const SEASON: i32 = 2099;
+
+const ROSTER: &str = "season,team,position,full_name,gsis_id\n\
+    2099,KC,QB,Test Passer,00-0099001\n\
+    2099,OAK,WR,Test Receiver,00-0099002\n";
+
+/// A temp directory removed when the test ends
+struct SourceDir(PathBuf);
+
+impl SourceDir {
+    fn create(test: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("nfl-pipeline-{}-{}", std::process::id(), test));
+        std::fs::create_dir_all(&dir).unwrap();
+        SourceDir(dir)
+    }
+}
+
+impl Drop for SourceDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn test_record_batches_from_source_dir() {
+    let dir = SourceDir::create("arrow_export");
+    std::fs::write(dir.0.join(datasets::ROSTERS.file_name(SEASON)), ROSTER).unwrap();
+    let args: Vec<String> = ["--mode", "year", "--source-dir", dir.0.to_str().unwrap()].iter().map(|s| s.to_string()).collect();
+    let config = Config::from_args(&args, |name| (name == "DATABASE_URL").then(|| "postgres://unused".to_string())).unwrap();
+
+    let export = ArrowExport::new(&config).unwrap();
+    let rosters = export.record_batch(&datasets::ROSTERS, SEASON).unwrap();
+    assert_eq!(rosters.num_rows(), 2);
+    let teams = rosters.column_by_name("team").unwrap().as_any().downcast_ref::<StringArray>().unwrap();
+    // Normalized as an import would: Oakland is the Raiders' current abbreviation
+    assert_eq!((teams.value(0), teams.value(1)), ("KC", "LV"));
+
+    let path = dir.0.join("rosters.arrow");
+    write_ipc(&path, &[rosters]).unwrap();
+    let reader = arrow_ipc::reader::FileReader::try_new(File::open(&path).unwrap(), None).unwrap();
+    let batches: Vec<RecordBatch> = reader.map(|batch| batch.unwrap()).collect();
+    assert_eq!(batches[0].num_rows(), 2);
+}
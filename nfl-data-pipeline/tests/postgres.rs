@@ -0,0 +1,411 @@
+//! Imports against a real PostgreSQL server. Each test creates and migrates a
+//! database of its own and drops it afterwards. The tests are skipped unless
+//! `PIPELINE_TEST_DATABASE_URL` points at a server they may create databases on,
+//! e.g. `postgres://postgres@localhost/postgres`.
+
+use nfl_data_pipeline::data_source::MemorySource;
+use nfl_data_pipeline::{datasets, Config, DataPipeline};
+use postgres::{Client, NoTls};
+use std::path::Path;
+
+const SEASON: i32 = 2099;
+
+const TEAMS: &str = "team_abbr,team_name,team_nick,team_conf,team_division\n\
+    KC,Kansas City Chiefs,Chiefs,AFC,AFC West\n\
+    BUF,Buffalo Bills,Bills,AFC,AFC East\n\
+    SF,San Francisco 49ers,49ers,NFC,NFC West\n\
+    GB,Green Bay Packers,Packers,NFC,NFC North\n";
+
+/// Week 1 is final, week 2 not played yet
+const SCHEDULE: &str = "game_id,season,game_type,week,gameday,gametime,away_team,away_score,home_team,home_score,location,\
+away_rest,home_rest,away_moneyline,home_moneyline,spread_line,total_line,roof,surface,temp,wind,referee,stadium\n\
+    2099_01_BUF_KC,2099,REG,1,2099-09-10,20:20,BUF,20,KC,27,Home,7,7,150,-175,3.5,47.5,outdoors,grass,28,18,Bill Vinovich,GEHA Field at Arrowhead Stadium\n\
+    2099_01_GB_SF,2099,REG,1,2099-09-13,16:25,GB,17,SF,24,Home,7,7,-130,110,-2.5,44,dome,fieldturf,,,Shawn Smith,Levi's Stadium\n\
+    2099_02_SF_KC,2099,REG,2,2099-09-20,13:00,SF,,KC,,Home,7,10,,,1.5,45.5,outdoors,grass,65,5,,GEHA Field at Arrowhead Stadium\n";
+
+const ROSTER: &str = "season,team,position,full_name,gsis_id\n\
+    2099,KC,QB,Test Passer,00-0099001\n";
+
+/// A cold, windy week 1 at home and a fair week 2
+const PLAYER_STATS: &str = "player_id,player_display_name,recent_team,season,week,season_type,completions,attempts,passing_yards\n\
+    00-0099001,Test Passer,KC,2099,1,REG,18,30,180\n\
+    00-0099001,Test Passer,KC,2099,2,REG,25,35,300\n";
+
+/// A migrated database of the test's own, dropped when the test ends
+struct TestDatabase {
+    server: Client,
+    name: String,
+    url: String,
+}
+
+impl TestDatabase {
+    fn create(test: &str) -> Option<Self> {
+        let database = Self::empty(test)?;
+        nfl_data_pipeline::run(database.config(&["--mode", "migrate"])).unwrap();
+        Some(database)
+    }
+
+    /// A database without any migrations applied
+    fn empty(test: &str) -> Option<Self> {
+        let Ok(server_url) = std::env::var("PIPELINE_TEST_DATABASE_URL") else {
+            eprintln!("skipping {}: PIPELINE_TEST_DATABASE_URL is not set", test);
+            return None;
+        };
+        let name = format!("nfl_test_{}_{}", std::process::id(), test);
+        let mut server = connect(&server_url);
+        server.batch_execute(&format!("DROP DATABASE IF EXISTS {name} WITH (FORCE)")).unwrap();
+        server.batch_execute(&format!("CREATE DATABASE {name}")).unwrap();
+
+        Some(TestDatabase { server, url: with_dbname(&server_url, &name), name })
+    }
+
+    fn config(&self, args: &[&str]) -> Config {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        Config::from_args(&args, |name| (name == "DATABASE_URL").then(|| self.url.clone())).unwrap()
+    }
+
+    /// A year pipeline with `args` over `source`, with the teams seeded
+    fn pipeline(&self, args: &[&str], source: MemorySource) -> DataPipeline {
+        let source = source.with_file(&datasets::TEAMS, 0, TEAMS);
+        let args = [&["--mode", "year"], args].concat();
+        let mut pipeline = DataPipeline::with_source(self.config(&args), Box::new(source)).unwrap();
+        pipeline.seed_teams().unwrap();
+        pipeline
+    }
+
+    fn client(&self) -> Client {
+        connect(&self.url)
+    }
+}
+
+impl Drop for TestDatabase {
+    fn drop(&mut self) {
+        let _ = self.server.batch_execute(&format!("DROP DATABASE IF EXISTS {} WITH (FORCE)", self.name));
+    }
+}
+
+/// A plain connection to the local test server
+fn connect(url: &str) -> Client {
+    Client::connect(url, NoTls).unwrap()
+}
+
+/// SQL files in `dir` sorted by name, e.g. the migrations in version order
+fn sql_files(dir: &Path) -> Vec<(String, String)> {
+    let mut files: Vec<(String, String)> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "sql"))
+        .map(|path| (path.file_name().unwrap().to_string_lossy().into_owned(), std::fs::read_to_string(&path).unwrap()))
+        .collect();
+    files.sort();
+    files
+}
+
+/// `url` pointing at database `name`, for both URL and key=value connection strings
+fn with_dbname(url: &str, name: &str) -> String {
+    match url.split_once("://") {
+        Some((scheme, rest)) => {
+            let (rest, query) = rest.split_once('?').map_or((rest, None), |(rest, query)| (rest, Some(query)));
+            let host = rest.split_once('/').map_or(rest, |(host, _)| host);
+            format!("{}://{}/{}{}", scheme, host, name, query.map(|q| format!("?{}", q)).unwrap_or_default())
+        }
+        None => format!("{} dbname={}", url, name),
+    }
+}
+
+#[test]
+fn test_import_year_writes_games() {
+    let Some(database) = TestDatabase::create("import_year_games") else { return };
+    let mut pipeline = database.pipeline(&[], MemorySource::default().with_file(&datasets::SCHEDULES, SEASON, SCHEDULE));
+    pipeline.import_year(SEASON).unwrap();
+
+    let games: Vec<String> = database
+        .client()
+        .query(
+            "SELECT format('%s %s week %s: %s at %s, %s-%s', g.nfl_game_id, g.season_type, g.week,
+                           a.abbreviation, h.abbreviation, g.away_score, g.home_score)
+             FROM games g JOIN teams h ON h.id = g.home_team_id JOIN teams a ON a.id = g.away_team_id
+             WHERE g.season = $1 ORDER BY g.nfl_game_id",
+            &[&SEASON],
+        )
+        .unwrap()
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+    assert_eq!(
+        games,
+        [
+            "2099_01_BUF_KC REG week 1: BUF at KC, 20-27",
+            "2099_01_GB_SF REG week 1: GB at SF, 17-24",
+            "2099_02_SF_KC REG week 2: SF at KC, -",
+        ]
+    );
+}
+
+#[test]
+fn test_schedule_lines_and_environment() {
+    for method in ["unnest", "copy", "insert"] {
+        let Some(database) = TestDatabase::create(&format!("schedule_columns_{}", method)) else { return };
+        let source = MemorySource::default().with_file(&datasets::SCHEDULES, SEASON, SCHEDULE);
+        database.pipeline(&["--load-method", method], source).import_year(SEASON).unwrap();
+
+        let row = database
+            .client()
+            .query_one(
+                "SELECT spread_line::float8, total_line::float8, home_moneyline::float8, away_moneyline::float8,
+                        roof, surface, weather_temp, weather_wind_speed, referee, venue_name, game_time_et,
+                        to_char(game_date AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI')
+                 FROM games WHERE nfl_game_id = '2099_01_BUF_KC'",
+                &[],
+            )
+            .unwrap();
+        assert_eq!(row.get::<_, Option<f64>>(0), Some(3.5), "{}", method);
+        assert_eq!(row.get::<_, Option<f64>>(1), Some(47.5), "{}", method);
+        assert_eq!(row.get::<_, Option<f64>>(2), Some(-175.0), "{}", method);
+        assert_eq!(row.get::<_, Option<f64>>(3), Some(150.0), "{}", method);
+        assert_eq!(row.get::<_, Option<String>>(4).as_deref(), Some("outdoors"), "{}", method);
+        assert_eq!(row.get::<_, Option<String>>(5).as_deref(), Some("grass"), "{}", method);
+        assert_eq!(row.get::<_, Option<i32>>(6), Some(28), "{}", method);
+        assert_eq!(row.get::<_, Option<i32>>(7), Some(18), "{}", method);
+        assert_eq!(row.get::<_, Option<String>>(8).as_deref(), Some("Bill Vinovich"), "{}", method);
+        assert_eq!(row.get::<_, Option<String>>(9).as_deref(), Some("GEHA Field at Arrowhead Stadium"), "{}", method);
+        assert_eq!(row.get::<_, Option<String>>(10).as_deref(), Some("20:20"), "{}", method);
+        // 20:20 Eastern, daylight time
+        assert_eq!(row.get::<_, String>(11), "2099-09-11 00:20", "{}", method);
+    }
+}
+
+#[test]
+fn test_elo_rest_adjustment_from_schedule() {
+    let Some(database) = TestDatabase::create("elo_rest") else { return };
+    let kc_elo = |schedule: &str| {
+        let source = MemorySource::default().with_file(&datasets::SCHEDULES, SEASON, schedule);
+        database.pipeline(&[], source).import_year(SEASON).unwrap();
+        database
+            .client()
+            .query_one(
+                "SELECT r.elo::float8 FROM team_ratings r JOIN teams t ON t.id = r.team_id
+                 WHERE r.season = $1 AND r.week = 1 AND t.abbreviation = 'KC'",
+                &[&SEASON],
+            )
+            .unwrap()
+            .get::<_, f64>(0)
+    };
+
+    let normal_rest = kc_elo(SCHEDULE);
+    // KC off a bye: favored by more, so the same win gains less
+    let off_bye = kc_elo(&SCHEDULE.replace("Home,7,7,150", "Home,7,14,150"));
+    assert!(normal_rest > 1505.0);
+    assert!(off_bye < normal_rest - 0.5, "bye week adjustment missing: {} vs {}", off_bye, normal_rest);
+}
+
+#[test]
+fn test_weather_splits_from_schedule() {
+    let Some(database) = TestDatabase::create("weather_splits") else { return };
+    let source = MemorySource::default()
+        .with_file(&datasets::SCHEDULES, SEASON, SCHEDULE)
+        .with_file(&datasets::ROSTERS, SEASON, ROSTER)
+        .with_file(&datasets::PLAYER_STATS, SEASON, PLAYER_STATS);
+    database.pipeline(&[], source).import_year(SEASON).unwrap();
+
+    let splits: Vec<(String, i32, f64)> = database
+        .client()
+        .query(
+            "SELECT split, games, passing_yards_per_game_delta::float8 FROM player_splits
+             WHERE season = $1 AND split IN ('wind', 'cold') ORDER BY split",
+            &[&SEASON],
+        )
+        .unwrap()
+        .iter()
+        .map(|row| (row.get(0), row.get(1), row.get(2)))
+        .collect();
+    assert_eq!(splits, [("cold".to_string(), 1, -120.0), ("wind".to_string(), 1, -120.0)]);
+}
+
+#[test]
+fn test_fantasy_points_tag_fantasy_playoff_weeks() {
+    let Some(database) = TestDatabase::create("fantasy_playoff_weeks") else { return };
+    let stats = format!("{}00-0099001,Test Passer,KC,2099,16,REG,20,31,250\n", PLAYER_STATS);
+    let source = MemorySource::default()
+        .with_file(&datasets::SCHEDULES, SEASON, SCHEDULE)
+        .with_file(&datasets::ROSTERS, SEASON, ROSTER)
+        .with_file(&datasets::PLAYER_STATS, SEASON, &stats);
+    database.pipeline(&[], source).import_year(SEASON).unwrap();
+
+    let weeks: Vec<(i32, bool)> = database
+        .client()
+        .query(
+            "SELECT week, fantasy_playoff_week FROM fantasy_points WHERE season = $1 AND scoring = 'ppr' ORDER BY week",
+            &[&SEASON],
+        )
+        .unwrap()
+        .iter()
+        .map(|row| (row.get(0), row.get(1)))
+        .collect();
+    assert_eq!(weeks, [(1, false), (2, false), (16, true)]);
+}
+
+#[test]
+fn test_schedule_status_lifecycle() {
+    let final_week_2 = SCHEDULE.replace("13:00,SF,,KC,,", "13:00,SF,13,KC,30,");
+    for method in ["unnest", "copy", "insert"] {
+        let Some(database) = TestDatabase::create(&format!("status_lifecycle_{}", method)) else { return };
+        let status = |schedule: &str| {
+            let source = MemorySource::default().with_file(&datasets::SCHEDULES, SEASON, schedule);
+            database.pipeline(&["--load-method", method], source).import_year(SEASON).unwrap();
+            database
+                .client()
+                .query_one("SELECT status FROM games WHERE nfl_game_id = '2099_02_SF_KC'", &[])
+                .unwrap()
+                .get::<_, String>(0)
+        };
+
+        assert_eq!(status(SCHEDULE), "scheduled", "{}", method);
+        assert_eq!(status(&final_week_2), "final", "{}", method);
+        // A stale file without the score doesn't reopen the game
+        assert_eq!(status(SCHEDULE), "final", "{}", method);
+    }
+}
+
+#[test]
+fn test_simulation_schema_built_from_migrations() {
+    let Some(database) = TestDatabase::create("simulation") else { return };
+    database.pipeline(&[], MemorySource::default());
+    let schema = format!("simulate_{}", std::process::id());
+
+    let source = MemorySource::default().with_file(&datasets::SCHEDULES, SEASON, SCHEDULE);
+    let mut pipeline = database.pipeline(&["--simulate"], source);
+    pipeline.import_year(SEASON).unwrap();
+
+    let mut client = database.client();
+    let count = |client: &mut Client, sql: &str| -> i64 { client.query_one(sql, &[&schema]).unwrap().get(0) };
+    assert_eq!(count(&mut client, "SELECT COUNT(*) FROM games WHERE $1 <> ''"), 0);
+    assert_eq!(count(&mut client, &format!("SELECT COUNT(*) FROM {schema}.games WHERE $1 <> ''")), 3);
+    // Migration 056's change_log trigger fires in the simulation, not in production,
+    // on a connection set up the way the pipeline's are
+    client
+        .batch_execute(&format!(
+            "SET search_path TO {schema}, public;
+             UPDATE games SET home_score = 30, away_score = 13 WHERE week = 2;
+             RESET search_path"
+        ))
+        .unwrap();
+    assert!(count(&mut client, &format!("SELECT COUNT(*) FROM {schema}.change_log WHERE $1 <> ''")) > 0);
+    assert_eq!(count(&mut client, "SELECT COUNT(*) FROM change_log WHERE $1 <> ''"), 0);
+    let views: Vec<String> = client
+        .query(
+            "SELECT table_name::text FROM information_schema.views WHERE table_schema = $1 ORDER BY table_name",
+            &[&schema],
+        )
+        .unwrap()
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+    assert_eq!(
+        views,
+        [
+            "current_rosters",
+            "dataset_schema_drift",
+            "player_game_logs",
+            "season_leaders",
+            "team_ats_records",
+            "team_schedules"
+        ]
+    );
+    // Serial columns draw from the simulation's own sequences
+    assert_eq!(
+        count(
+            &mut client,
+            "SELECT COUNT(*) FROM pg_attrdef a
+             JOIN pg_class t ON t.oid = a.adrelid
+             JOIN pg_depend d ON d.classid = 'pg_attrdef'::regclass AND d.objid = a.oid
+             JOIN pg_class seq ON seq.oid = d.refobjid AND seq.relkind = 'S'
+             WHERE t.relnamespace = $1::text::regnamespace AND seq.relnamespace <> t.relnamespace",
+        ),
+        0
+    );
+
+    pipeline.finish_simulation().unwrap();
+    assert_eq!(count(&mut client, "SELECT COUNT(*) FROM pg_namespace WHERE nspname = $1"), 0);
+}
+
+#[test]
+fn test_kickoff_migration_converts_games_and_keeps_views() {
+    let Some(database) = TestDatabase::empty("kickoff_migration") else { return };
+    let migrations_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../migrations");
+    let migrations = sql_files(&migrations_dir);
+    let (before, after): (Vec<_>, Vec<_>) = migrations.iter().partition(|(file, _)| file.as_str() < "047");
+    let mut client = database.client();
+    // Created by --mode migrate before the first migration; some record themselves in it
+    client
+        .batch_execute(
+            "CREATE TABLE schema_migrations (version INT PRIMARY KEY, description TEXT NOT NULL, checksum TEXT,
+                                             applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW())",
+        )
+        .unwrap();
+    for (file, sql) in before {
+        // 007 runs statement by statement, skipping what doesn't apply, as --mode migrate does
+        if file.starts_with("007_") {
+            for statement in sql.split(';') {
+                let _ = client.batch_execute(statement);
+            }
+        } else {
+            client.batch_execute(sql).unwrap();
+        }
+    }
+
+    client
+        .batch_execute(
+            "INSERT INTO teams (abbreviation, name, city, conference, division) VALUES
+                 ('KC', 'Kansas City Chiefs', 'Kansas City', 'AFC', 'AFC West'),
+                 ('BUF', 'Buffalo Bills', 'Buffalo', 'AFC', 'AFC East');
+             INSERT INTO games (nfl_game_id, season, week, season_type, game_date, game_time_et, home_team_id, away_team_id)
+             SELECT g.nfl_game_id, 2099, g.week, 'REG', g.game_date::timestamp, g.game_time_et, home.id, away.id
+             FROM (VALUES ('2099_01_BUF_KC', 1, '2099-09-10', '20:20'),
+                          ('2099_02_BUF_KC', 2, '2099-09-20', NULL),
+                          ('401999001', 3, '2099-09-28 00:00:00', NULL)) AS g (nfl_game_id, week, game_date, game_time_et),
+                  teams home, teams away
+             WHERE home.abbreviation = 'KC' AND away.abbreviation = 'BUF'",
+        )
+        .unwrap();
+    for (_, sql) in sql_files(&migrations_dir.join("views")) {
+        client.batch_execute(&sql).unwrap();
+    }
+
+    client.batch_execute(&after[0].1).unwrap();
+
+    let kickoffs: Vec<(String, String)> = client
+        .query(
+            "SELECT nfl_game_id, to_char(game_date AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI') FROM games ORDER BY week",
+            &[],
+        )
+        .unwrap()
+        .iter()
+        .map(|row| (row.get(0), row.get(1)))
+        .collect();
+    assert_eq!(
+        kickoffs,
+        [
+            // 20:20 Eastern (EDT) on the gameday
+            ("2099_01_BUF_KC".to_string(), "2099-09-11 00:20".to_string()),
+            // No kickoff time: midnight Eastern
+            ("2099_02_BUF_KC".to_string(), "2099-09-20 04:00".to_string()),
+            // An ESPN kickoff at midnight UTC stays there
+            ("401999001".to_string(), "2099-09-28 00:00".to_string()),
+        ]
+    );
+
+    let views: Vec<String> = client
+        .query(
+            "SELECT table_name::text FROM information_schema.columns
+             WHERE column_name = 'game_date' AND data_type = 'timestamp with time zone'
+               AND table_name IN ('player_game_logs', 'team_schedules')
+             ORDER BY table_name",
+            &[],
+        )
+        .unwrap()
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+    assert_eq!(views, ["player_game_logs", "team_schedules"]);
+}
@@ -0,0 +1,43 @@
+//! The weekly roster diff as an embedding crate sees it: parsed roster rows in,
+//! typed adds, drops and field changes out.
+
+use nfl_data_pipeline::parser::RosterPlayer;
+use nfl_data_pipeline::roster_moves::RosterField;
+use nfl_data_pipeline::{diff_rosters, RosterSnapshot};
+
+const WEEK_5: &str = "season,team,position,full_name,gsis_id,status,week\n\
+    2099,KC,QB,Test Passer,00-0099001,ACT,5\n\
+    2099,NYJ,WR,Test Receiver,00-0099002,ACT,5\n\
+    2099,OAK,TE,Test Tight End,00-0099003,ACT,5\n";
+
+const WEEK_6: &str = "season,team,position,full_name,gsis_id,status,week\n\
+    2099,NE,WR,Test Receiver,00-0099002,ACT,6\n\
+    2099,LV,TE,Test Tight End,00-0099003,ACT,6\n\
+    2099,KC,RB,Test Rusher,00-0099004,DEV,6\n";
+
+fn snapshot(week: i32, csv: &str) -> RosterSnapshot {
+    let players: Vec<RosterPlayer> = csv::Reader::from_reader(csv.as_bytes())
+        .deserialize()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    RosterSnapshot::from_players(week, &players)
+}
+
+#[test]
+fn test_diff_rosters_week_over_week() {
+    let diff = diff_rosters(&snapshot(5, WEEK_5), &snapshot(6, WEEK_6));
+    assert!(!diff.is_empty());
+
+    assert_eq!(diff.added.len(), 1);
+    assert_eq!(diff.added[0].name, "Test Rusher");
+    assert_eq!(diff.removed.len(), 1);
+    assert_eq!(diff.removed[0].name, "Test Passer");
+
+    // OAK -> LV is a relocation, not a move
+    let trades: Vec<&str> = diff.team_changes().map(|player| player.after.name.as_str()).collect();
+    assert_eq!(trades, ["Test Receiver"]);
+    assert_eq!(diff.changed[0].changes[0].field, RosterField::Team);
+
+    let kinds: Vec<&str> = diff.moves().iter().map(|roster_move| roster_move.kind).collect();
+    assert_eq!(kinds, ["added", "removed", "changed"]);
+}